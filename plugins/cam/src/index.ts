@@ -578,6 +578,7 @@ export default function (api) {
     parameters: Type.Object({
       reply: Type.String({ description: "回复内容（如 'y', 'n', 或具体回复）" }),
       target: Type.Optional(Type.String({ description: "目标 agent（可选，支持通配符如 'cam-*'）" })),
+      sender: Type.Optional(Type.String({ description: "发送者用户 ID（如 tg:123456），按 reply_auth 授权" })),
     }),
     async execute(_id, params) {
       try {
@@ -590,11 +591,33 @@ export default function (api) {
     },
   });
 
+  api.registerTool({
+    name: "cam_receive_attachment",
+    description: "用户回复通知时附带图片或文件：保存到 agent 工作区并告诉 agent 文件路径 / Forward a photo or file replied to a notification to the agent",
+    parameters: Type.Object({
+      source: Type.String({ description: "附件的下载地址（如 Telegram 文件地址）或已下载的本机路径" }),
+      reply_to: Type.Optional(Type.String({ description: "被回复的通知消息 ID" })),
+      agent_id: Type.Optional(Type.String({ description: "目标 agent（未提供 reply_to 时必填）" })),
+      name: Type.Optional(Type.String({ description: "文件名" })),
+      caption: Type.Optional(Type.String({ description: "随附件发来的文字" })),
+    }),
+    async execute(_id, params) {
+      try {
+        const result = await callCamMcp("agent_receive_attachment", params, 75000);
+        return { content: [{ type: "text", text: JSON.stringify(result) }] };
+      } catch (error) {
+        api.logger.error("cam_receive_attachment failed", { error: error.message });
+        return { content: [{ type: "text", text: JSON.stringify({ error: true, message: error.message }) }] };
+      }
+    },
+  });
+
   api.registerTool({
     name: "cam_handle_user_reply",
     description: "处理用户回复（由通知系统调用）/ Handle a user reply from notification system",
     parameters: Type.Object({
       reply: Type.String({ description: "用户回复内容" }),
+      sender: Type.String({ description: "发送者用户 ID（如 tg:123456），按 reply_auth 授权" }),
       context: Type.Optional(Type.String({ description: "回复上下文（如 agent_id 或 session_id）" })),
     }),
     async execute(_id, params) {
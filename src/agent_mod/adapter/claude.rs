@@ -4,13 +4,47 @@
 use super::*;
 use crate::agent::AgentType;
 use regex::Regex;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
 /// 静态编译的提示符正则表达式，避免每次调用都编译
 static PROMPT_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)^[❯>]\s*$").expect("Invalid prompt regex"));
 
+/// 状态栏的剩余上下文（`Context left until auto-compact: 8%`、`Context low (8% remaining)`）
+static CONTEXT_LEFT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)context (?:left until auto-compact:\s*|low \()(\d{1,3})%")
+        .expect("Invalid context regex")
+});
+
+/// 提供凭据（API key、OAuth token）或改用云厂商的环境变量
+const CREDENTIAL_ENV_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+    "CLAUDE_CODE_USE_BEDROCK",
+    "CLAUDE_CODE_USE_VERTEX",
+];
+
+/// 是否有可用的登录凭据：环境变量、`~/.claude/.credentials.json`（Linux），
+/// 或 `~/.claude.json` 中的登录账号（macOS 凭据在钥匙串中，只在这里留下账号信息）、
+/// settings.json 中的 `apiKeyHelper`
+pub fn has_credentials(home: &Path, has_env: impl Fn(&str) -> bool) -> bool {
+    let file_contains = |path: PathBuf, keys: &[&str]| {
+        std::fs::read_to_string(path).is_ok_and(|content| {
+            keys.iter()
+                .any(|key| content.contains(&format!("\"{}\"", key)))
+        })
+    };
+    CREDENTIAL_ENV_VARS.iter().any(|key| has_env(key))
+        || home.join(".claude/.credentials.json").exists()
+        || file_contains(
+            home.join(".claude.json"),
+            &["oauthAccount", "primaryApiKey"],
+        )
+        || file_contains(home.join(".claude/settings.json"), &["apiKeyHelper"])
+}
+
 pub struct ClaudeAdapter;
 
 impl AgentAdapter for ClaudeAdapter {
@@ -45,6 +79,7 @@ impl AgentAdapter for ClaudeAdapter {
                 "notification".into(),
                 "PreToolUse".into(),
                 "PostToolUse".into(),
+                "SubagentStop".into(),
             ],
             mcp_support: true,
             json_output: false,
@@ -67,9 +102,25 @@ impl AgentAdapter for ClaudeAdapter {
         which::which("claude").is_ok()
     }
 
+    fn image_reference(&self, path: &Path) -> Option<String> {
+        // `@路径` 引用图片文件，Claude Code 读取后作为图片附件
+        Some(format!("@{}", path.display()))
+    }
+
+    fn is_authenticated(&self) -> Option<bool> {
+        let home = dirs::home_dir()?;
+        Some(has_credentials(&home, |key| {
+            std::env::var_os(key).is_some()
+        }))
+    }
+
     fn parse_hook_event(&self, payload: &str) -> Option<HookEvent> {
         let value: serde_json::Value = serde_json::from_str(payload).ok()?;
-        let event_type = value.get("event")?.as_str()?;
+        // Claude Code 原生 hook 输入使用 hook_event_name
+        let event_type = value
+            .get("event")
+            .or_else(|| value.get("hook_event_name"))?
+            .as_str()?;
         let cwd = value
             .get("cwd")
             .and_then(|v| v.as_str())
@@ -100,18 +151,24 @@ impl AgentAdapter for ClaudeAdapter {
                     _ => None,
                 }
             }
-            "PreToolUse" => {
-                let tool = value
-                    .get("tool_name")
+            "PreToolUse" => Some(HookEvent::ToolStarting {
+                tool: tool_name(&value),
+                input: value
+                    .get("tool_input")
+                    .cloned()
+                    .unwrap_or(serde_json::Value::Null),
+                tool_use_id: value
+                    .get("tool_use_id")
                     .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-                Some(HookEvent::PermissionRequest {
-                    tool,
-                    action: "execute".into(),
-                    cwd,
-                })
-            }
+                    .map(String::from),
+                cwd,
+            }),
+            "PostToolUse" => Some(HookEvent::ToolExecuted {
+                tool: tool_name(&value),
+                success: tool_response_succeeded(value.get("tool_response")),
+                duration_ms: None,
+            }),
+            "SubagentStop" => Some(HookEvent::SubagentStop { session_id, cwd }),
             _ => None,
         }
     }
@@ -125,6 +182,49 @@ impl AgentAdapter for ClaudeAdapter {
             || terminal_output.contains("Welcome to")
             || terminal_output.contains("Claude Code")
     }
+
+    fn parse_context_usage(&self, terminal_output: &str) -> Option<u8> {
+        usage_from_remaining(&CONTEXT_LEFT_RE, terminal_output)
+    }
+
+    fn translate_reply(&self, reply: &str, prompt: PromptKind) -> Vec<KeySequence> {
+        // Claude Code 的权限/选项菜单按数字键立即选中，y/n 映射到 Yes/No 选项。
+        // 菜单中不追加「文本 + Enter」兜底：按键被忽略时 Enter 会选中光标所在的选项
+        match (prompt, reply) {
+            (PromptKind::SelectMenu | PromptKind::NumberedChoice, _) => {
+                if let Some(n) = choice_number(reply) {
+                    return vec![
+                        KeySequence::Raw(reply.to_string()),
+                        KeySequence::arrow_select(n),
+                    ];
+                }
+                default_reply_keys(reply, prompt)
+            }
+            (PromptKind::Confirmation, "y") => vec![
+                KeySequence::Raw("1".to_string()),
+                KeySequence::Text("y".to_string()),
+            ],
+            // Esc 会中断整个任务而不只是拒绝这一步
+            (PromptKind::Confirmation, "n") => vec![KeySequence::Raw("n".to_string())],
+            _ => default_reply_keys(reply, prompt),
+        }
+    }
+}
+
+fn tool_name(value: &serde_json::Value) -> String {
+    value
+        .get("tool_name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// PostToolUse 的 tool_response 是否表示成功（`is_error`/`interrupted` 为 true 或 `success` 为 false 视为失败）
+pub fn tool_response_succeeded(response: Option<&serde_json::Value>) -> bool {
+    let flag = |key: &str| response.and_then(|r| r.get(key)).and_then(|v| v.as_bool());
+    !(flag("is_error") == Some(true)
+        || flag("interrupted") == Some(true)
+        || flag("success") == Some(false))
 }
 
 #[cfg(test)]
@@ -137,6 +237,23 @@ mod tests {
         assert_eq!(adapter.get_command(), "claude");
     }
 
+    #[test]
+    fn test_has_credentials() {
+        let home = tempfile::tempdir().unwrap();
+        let no_env = |_: &str| false;
+        assert!(!has_credentials(home.path(), no_env));
+        assert!(has_credentials(home.path(), |key| key == "ANTHROPIC_API_KEY"));
+
+        std::fs::write(home.path().join(".claude.json"), r#"{"numStartups": 3}"#).unwrap();
+        assert!(!has_credentials(home.path(), no_env));
+        std::fs::write(
+            home.path().join(".claude.json"),
+            r#"{"oauthAccount": {"emailAddress": "dev@example.com"}}"#,
+        )
+        .unwrap();
+        assert!(has_credentials(home.path(), no_env));
+    }
+
     #[test]
     fn test_get_resume_command() {
         let adapter = ClaudeAdapter;
@@ -190,6 +307,55 @@ mod tests {
         assert!(adapter.detect_ready("Welcome to the session"));
     }
 
+    #[test]
+    fn test_parse_context_usage() {
+        let adapter = ClaudeAdapter;
+        let screen = "⏺ Done\n\n╭──────╮\n│ >    │\n╰──────╯\n  ? for shortcuts   Context left until auto-compact: 8%";
+        assert_eq!(adapter.parse_context_usage(screen), Some(92));
+        assert_eq!(
+            adapter.parse_context_usage("❯ \n  Context low (3% remaining) · Run /compact"),
+            Some(97)
+        );
+        assert_eq!(adapter.parse_context_usage("Output\n❯ "), None);
+
+        // 滚动历史中的旧状态栏（/compact 之前）不算
+        let compacted = format!(
+            "Context left until auto-compact: 5%\n{}❯ ",
+            "line\n".repeat(20)
+        );
+        assert_eq!(adapter.parse_context_usage(&compacted), None);
+    }
+
+    #[test]
+    fn test_translate_reply_menu_selects_by_digit() {
+        let adapter = ClaudeAdapter;
+        let keys = adapter.translate_reply("2", PromptKind::SelectMenu);
+        assert_eq!(
+            keys,
+            vec![
+                KeySequence::Raw("2".to_string()),
+                KeySequence::arrow_select(2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_translate_reply_confirmation() {
+        let adapter = ClaudeAdapter;
+        assert_eq!(
+            adapter.translate_reply("y", PromptKind::Confirmation)[0],
+            KeySequence::Raw("1".to_string())
+        );
+        assert_eq!(
+            adapter.translate_reply("n", PromptKind::Confirmation),
+            vec![KeySequence::Raw("n".to_string())]
+        );
+        assert_eq!(
+            adapter.translate_reply("fix it", PromptKind::FreeText),
+            vec![KeySequence::Text("fix it".to_string())]
+        );
+    }
+
     #[test]
     fn test_detect_ready_negative() {
         let adapter = ClaudeAdapter;
@@ -252,15 +418,56 @@ mod tests {
         let payload = r#"{"event":"PreToolUse","tool_name":"Bash","cwd":"/workspace"}"#;
         let event = adapter.parse_hook_event(payload).unwrap();
         match event {
-            HookEvent::PermissionRequest { tool, action, cwd } => {
+            HookEvent::ToolStarting {
+                tool,
+                input,
+                tool_use_id,
+                cwd,
+            } => {
                 assert_eq!(tool, "Bash");
-                assert_eq!(action, "execute");
+                assert!(input.is_null());
+                assert!(tool_use_id.is_none());
                 assert_eq!(cwd, "/workspace");
             }
-            _ => panic!("Expected PermissionRequest"),
+            _ => panic!("Expected ToolStarting"),
+        }
+
+        // Claude Code 原生字段名
+        let payload = r#"{"hook_event_name":"PreToolUse","tool_name":"Edit","tool_use_id":"toolu_1","tool_input":{"file_path":"a.rs"},"cwd":"/w"}"#;
+        match adapter.parse_hook_event(payload).unwrap() {
+            HookEvent::ToolStarting {
+                input, tool_use_id, ..
+            } => {
+                assert_eq!(input["file_path"], "a.rs");
+                assert_eq!(tool_use_id.as_deref(), Some("toolu_1"));
+            }
+            _ => panic!("Expected ToolStarting"),
         }
     }
 
+    #[test]
+    fn test_parse_post_tool_use_and_subagent_stop() {
+        let adapter = ClaudeAdapter;
+        let payload = r#"{"hook_event_name":"PostToolUse","tool_name":"Bash","tool_response":{"is_error":true},"cwd":"/w"}"#;
+        match adapter.parse_hook_event(payload).unwrap() {
+            HookEvent::ToolExecuted { tool, success, .. } => {
+                assert_eq!(tool, "Bash");
+                assert!(!success);
+            }
+            _ => panic!("Expected ToolExecuted"),
+        }
+        assert!(tool_response_succeeded(Some(
+            &serde_json::json!({"stdout": "ok"})
+        )));
+        assert!(tool_response_succeeded(None));
+
+        let payload = r#"{"hook_event_name":"SubagentStop","session_id":"s1","cwd":"/w"}"#;
+        assert!(matches!(
+            adapter.parse_hook_event(payload).unwrap(),
+            HookEvent::SubagentStop { session_id: Some(ref id), .. } if id == "s1"
+        ));
+    }
+
     #[test]
     fn test_parse_invalid_json() {
         let adapter = ClaudeAdapter;
@@ -6,7 +6,13 @@
 
 use super::*;
 use crate::agent::AgentType;
-use std::path::PathBuf;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// 状态栏的剩余上下文（`42% context left`）
+static CONTEXT_LEFT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(\d{1,3})% context left").expect("Invalid context regex"));
 
 pub struct CodexAdapter;
 
@@ -59,6 +65,22 @@ impl AgentAdapter for CodexAdapter {
         which::which("codex").is_ok()
     }
 
+    fn image_reference(&self, path: &Path) -> Option<String> {
+        // Codex TUI 把粘贴的图片文件路径转为图片附件
+        Some(path.display().to_string())
+    }
+
+    fn is_authenticated(&self) -> Option<bool> {
+        if ["OPENAI_API_KEY", "CODEX_API_KEY"]
+            .iter()
+            .any(|key| std::env::var_os(key).is_some())
+        {
+            return Some(true);
+        }
+        // `codex login` 写入 ~/.codex/auth.json
+        Some(dirs::home_dir()?.join(".codex/auth.json").exists())
+    }
+
     fn parse_hook_event(&self, payload: &str) -> Option<HookEvent> {
         // Codex notify payload 作为 JSON 传递
         let value: serde_json::Value = serde_json::from_str(payload).ok()?;
@@ -105,6 +127,21 @@ impl AgentAdapter for CodexAdapter {
             || terminal_output.contains("? for shortcuts")
             || terminal_output.contains("context left")
     }
+
+    fn parse_context_usage(&self, terminal_output: &str) -> Option<u8> {
+        usage_from_remaining(&CONTEXT_LEFT_RE, terminal_output)
+    }
+
+    fn translate_reply(&self, reply: &str, prompt: PromptKind) -> Vec<KeySequence> {
+        // Codex 审批弹窗使用单键快捷键（y/n），无需回车
+        if prompt == PromptKind::Confirmation && (reply == "y" || reply == "n") {
+            return vec![
+                KeySequence::Raw(reply.to_string()),
+                KeySequence::Text(reply.to_string()),
+            ];
+        }
+        default_reply_keys(reply, prompt)
+    }
 }
 
 #[cfg(test)]
@@ -132,6 +169,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_translate_reply() {
+        let adapter = CodexAdapter;
+        assert_eq!(
+            adapter.translate_reply("y", PromptKind::Confirmation)[0],
+            KeySequence::Raw("y".to_string())
+        );
+        assert_eq!(
+            adapter.translate_reply("3", PromptKind::SelectMenu)[0],
+            KeySequence::arrow_select(3)
+        );
+    }
+
     #[test]
     fn test_detection_strategy() {
         let adapter = CodexAdapter;
@@ -235,6 +285,11 @@ mod tests {
   ? for shortcuts                                            100% context left"#;
 
         assert!(adapter.detect_ready(normal));
+        assert_eq!(adapter.parse_context_usage(normal), Some(0));
+        assert_eq!(
+            adapter.parse_context_usage("› \n  42% context left"),
+            Some(58)
+        );
     }
 
     #[test]
@@ -22,12 +22,7 @@ pub struct BackupManager {
 impl BackupManager {
     /// 创建新的备份管理器
     pub fn new() -> Self {
-        let backup_dir = dirs::config_dir()
-            .unwrap_or_else(|| {
-                tracing::warn!("Could not determine config directory, using current directory");
-                PathBuf::from(".")
-            })
-            .join("code-agent-monitor/backups");
+        let backup_dir = crate::infra::paths::state_dir().join("backups");
         Self {
             backup_dir,
             max_backups: DEFAULT_MAX_BACKUPS,
@@ -83,7 +83,11 @@ impl AgentAdapter for GenericAdapter {
     }
 
     fn is_installed(&self) -> bool {
-        which::which(&self.command).is_ok()
+        // 命令可能带参数（如 `sleep 3600`），只检查程序名
+        self.command
+            .split_whitespace()
+            .next()
+            .is_some_and(|program| which::which(program).is_ok())
     }
 
     fn parse_hook_event(&self, _payload: &str) -> Option<HookEvent> {
@@ -96,6 +100,15 @@ impl AgentAdapter for GenericAdapter {
         // 实际使用时会配合 AI 检测
         !terminal_output.is_empty()
     }
+
+    fn pause_keys(&self) -> Option<KeySequence> {
+        // 未知 CLI 的中断键不确定，需在 config.json 的 pause_keys 中配置
+        None
+    }
+
+    fn resume_keys(&self) -> Option<KeySequence> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -33,11 +33,83 @@ pub trait AgentAdapter: Send + Sync {
     /// 检测是否已安装
     fn is_installed(&self) -> bool;
 
+    /// 检测是否已登录（None 表示无法判断）
+    fn is_authenticated(&self) -> Option<bool> {
+        None
+    }
+
+    /// 在输入中引用图片文件的文本（None 表示不支持图片输入）
+    fn image_reference(&self, _path: &std::path::Path) -> Option<String> {
+        None
+    }
+
     /// 解析 hook 事件
     fn parse_hook_event(&self, payload: &str) -> Option<HookEvent>;
 
     /// 检测就绪状态
     fn detect_ready(&self, terminal_output: &str) -> bool;
+
+    /// 将标准化回复转换为按键序列，第一个为首选方式，其余为验证失败时的备用方式
+    fn translate_reply(&self, reply: &str, prompt: PromptKind) -> Vec<KeySequence> {
+        default_reply_keys(reply, prompt)
+    }
+
+    /// 安全中断当前任务的按键（`cam pause`），None 表示没有安全的中断方式
+    fn pause_keys(&self) -> Option<KeySequence> {
+        Some(KeySequence::Keys(vec!["Escape".to_string()]))
+    }
+
+    /// 中断后继续任务的输入（`cam resume-all`）
+    fn resume_keys(&self) -> Option<KeySequence> {
+        Some(KeySequence::Text("continue".to_string()))
+    }
+
+    /// 从状态栏解析上下文窗口使用率（百分比），未显示时返回 None
+    fn parse_context_usage(&self, _terminal_output: &str) -> Option<u8> {
+        None
+    }
+}
+
+/// 只在屏幕末尾查找状态栏，避免匹配到滚动历史中的旧状态栏
+const STATUS_LINE_TAIL: usize = 8;
+
+/// 用"剩余百分比"正则解析状态栏，返回已用百分比（取最后一次出现）
+pub fn usage_from_remaining(re: &regex::Regex, terminal_output: &str) -> Option<u8> {
+    let lines: Vec<&str> = terminal_output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    lines[lines.len().saturating_sub(STATUS_LINE_TAIL)..]
+        .iter()
+        .rev()
+        .find_map(|line| re.captures_iter(line).last())
+        .and_then(|caps| caps.get(1)?.as_str().parse::<u8>().ok())
+        .map(|remaining| 100 - remaining.min(100))
+}
+
+/// 通用回复按键转换
+pub fn default_reply_keys(reply: &str, prompt: PromptKind) -> Vec<KeySequence> {
+    if let Some(n) = choice_number(reply) {
+        return match prompt {
+            // 菜单中不用「文本 + Enter」兜底，Enter 会选中光标所在的选项
+            PromptKind::SelectMenu => vec![
+                KeySequence::arrow_select(n),
+                KeySequence::Raw(reply.to_string()),
+            ],
+            _ => vec![
+                KeySequence::Text(reply.to_string()),
+                KeySequence::Raw(reply.to_string()),
+                KeySequence::arrow_select(n),
+            ],
+        };
+    }
+    if (reply == "y" || reply == "n") && prompt != PromptKind::FreeText {
+        return vec![
+            KeySequence::Text(reply.to_string()),
+            KeySequence::Raw(reply.to_string()),
+        ];
+    }
+    vec![KeySequence::Text(reply.to_string())]
 }
 
 /// 获取适配器
@@ -59,8 +131,64 @@ pub mod opencode;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::agent::extractor::MessageType;
     use crate::agent::AgentType;
 
+    #[test]
+    fn test_default_reply_keys_for_numbers() {
+        assert_eq!(
+            default_reply_keys("3", PromptKind::NumberedChoice),
+            vec![
+                KeySequence::Text("3".to_string()),
+                KeySequence::Raw("3".to_string()),
+                KeySequence::Keys(vec![
+                    "Down".to_string(),
+                    "Down".to_string(),
+                    "Enter".to_string()
+                ]),
+            ]
+        );
+        // 选择菜单优先使用方向键
+        assert_eq!(
+            default_reply_keys("1", PromptKind::SelectMenu)[0],
+            KeySequence::Keys(vec!["Enter".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_default_reply_keys_for_text() {
+        assert_eq!(default_reply_keys("y", PromptKind::Confirmation).len(), 2);
+        assert_eq!(
+            default_reply_keys("run the tests", PromptKind::FreeText),
+            vec![KeySequence::Text("run the tests".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_prompt_kind_detect() {
+        let menu = "Do you want to proceed?\n❯ 1. Yes\n  2. No";
+        let list = "Pick one:\n1. Fast\n2. Safe";
+        assert_eq!(
+            PromptKind::detect(Some(&MessageType::Choice), menu, "2"),
+            PromptKind::SelectMenu
+        );
+        assert_eq!(
+            PromptKind::detect(Some(&MessageType::Choice), list, "2"),
+            PromptKind::NumberedChoice
+        );
+        assert_eq!(
+            PromptKind::detect(Some(&MessageType::OpenEnded), menu, "2"),
+            PromptKind::FreeText
+        );
+        // 没有消息类型时按回复推断
+        assert_eq!(
+            PromptKind::detect(None, "", "2"),
+            PromptKind::NumberedChoice
+        );
+        assert_eq!(PromptKind::detect(None, "", "y"), PromptKind::Confirmation);
+        assert_eq!(PromptKind::detect(None, "", "ok"), PromptKind::FreeText);
+    }
+
     #[test]
     fn test_get_adapter_claude() {
         let adapter = get_adapter(&AgentType::Claude);
@@ -4,6 +4,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::agent::extractor::MessageType;
+
 /// 检测策略
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DetectionStrategy {
@@ -64,12 +66,24 @@ pub enum HookEvent {
     },
     /// 权限回复
     PermissionReplied { tool: String, approved: bool },
+    /// 工具即将执行（Claude Code PreToolUse，每次工具调用都会触发）
+    ToolStarting {
+        tool: String,
+        input: serde_json::Value,
+        tool_use_id: Option<String>,
+        cwd: String,
+    },
     /// 工具执行完成
     ToolExecuted {
         tool: String,
         success: bool,
         duration_ms: Option<u64>,
     },
+    /// 子 agent 完成（Claude Code SubagentStop）
+    SubagentStop {
+        session_id: Option<String>,
+        cwd: String,
+    },
     /// Turn 完成（Codex 特有）
     TurnComplete {
         thread_id: String,
@@ -84,3 +98,79 @@ pub enum HookEvent {
         payload: serde_json::Value,
     },
 }
+
+/// 回复的按键发送方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySequence {
+    /// 输入文本并回车
+    Text(String),
+    /// 只输入文本，不回车（数字直接选中的菜单）
+    Raw(String),
+    /// tmux 命名按键（如 Down、Enter、Escape）
+    Keys(Vec<String>),
+}
+
+impl KeySequence {
+    /// 用于展示的描述
+    pub fn describe(&self) -> String {
+        match self {
+            KeySequence::Text(text) => format!("{} + Enter", text),
+            KeySequence::Raw(text) => text.clone(),
+            KeySequence::Keys(keys) => keys.join(" "),
+        }
+    }
+
+    /// 从第一个选项开始用方向键选中第 n 项
+    pub fn arrow_select(n: usize) -> Self {
+        let mut keys = vec!["Down".to_string(); n.saturating_sub(1)];
+        keys.push("Enter".to_string());
+        KeySequence::Keys(keys)
+    }
+}
+
+/// 当前提示的交互方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    /// 编号选项，输入数字作答
+    NumberedChoice,
+    /// 带光标的选择菜单（❯ 1. Yes）
+    SelectMenu,
+    /// 是/否确认
+    Confirmation,
+    /// 自由文本
+    FreeText,
+}
+
+impl PromptKind {
+    /// 根据提取的消息类型和提示内容判断交互方式
+    ///
+    /// 没有消息类型时（旧数据或未经提取）按回复内容推断。
+    pub fn detect(message_type: Option<&MessageType>, context: &str, reply: &str) -> Self {
+        match message_type {
+            Some(MessageType::Choice) if has_menu_cursor(context) => PromptKind::SelectMenu,
+            Some(MessageType::Choice) => PromptKind::NumberedChoice,
+            Some(MessageType::Confirmation) => PromptKind::Confirmation,
+            Some(MessageType::OpenEnded) | Some(MessageType::Idle { .. }) => PromptKind::FreeText,
+            None if choice_number(reply).is_some() => PromptKind::NumberedChoice,
+            None if reply == "y" || reply == "n" => PromptKind::Confirmation,
+            None => PromptKind::FreeText,
+        }
+    }
+}
+
+/// 选项行前是否有光标标记（❯/›/▶/→ 后跟编号）
+fn has_menu_cursor(context: &str) -> bool {
+    context.lines().any(|line| {
+        let line = line.trim_start();
+        ['❯', '›', '▶', '→'].iter().any(|cursor| {
+            line.strip_prefix(*cursor)
+                .map(|rest| rest.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+                .unwrap_or(false)
+        })
+    })
+}
+
+/// 解析 1-9 的选项编号
+pub fn choice_number(reply: &str) -> Option<usize> {
+    reply.parse().ok().filter(|n| (1..=9).contains(n))
+}
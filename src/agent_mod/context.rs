@@ -0,0 +1,21 @@
+//! 上下文窗口监控 - 使用率超过阈值时提醒执行 /compact
+//!
+//! 上下文快满时 agent 会自动压缩或开始遗忘早期内容，提前手动 `/compact` 更可控。
+//! Watcher 每轮通过适配器从状态栏解析使用率（Claude Code 的 `Context left until
+//! auto-compact: 8%`、Codex 的 `42% context left`），写入 agents.json 的 `context_usage`，
+//! 供 `cam list` 和 TUI 显示；超过阈值时发送一条 MEDIUM 通知，降回阈值以下后重新计算。
+//!
+//! 阈值由 config.json 的 `context_alert_percent` 设置（默认 90，0 表示关闭）。
+
+/// 默认提醒阈值（百分比）
+pub const CONTEXT_ALERT_PERCENT: u8 = 90;
+
+/// 读取提醒阈值（百分比），None 表示关闭
+pub fn context_alert_percent() -> Option<u8> {
+    let configured = crate::infra::paths::config_section::<u64>("context_alert_percent");
+    match configured {
+        Some(0) => None,
+        Some(percent) => Some(percent.min(100) as u8),
+        None => Some(CONTEXT_ALERT_PERCENT),
+    }
+}
@@ -15,9 +15,7 @@ pub struct WatcherDaemon {
 impl WatcherDaemon {
     /// 创建新的 daemon 管理器
     pub fn new() -> Self {
-        let data_dir = dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".config/code-agent-monitor");
+        let data_dir = crate::infra::paths::state_dir();
 
         let _ = fs::create_dir_all(&data_dir);
 
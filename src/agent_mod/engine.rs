@@ -0,0 +1,573 @@
+//! Watch 引擎 - 所有监控入口共用的事件过滤与 事件→通知 管道
+//!
+//! `cam watch`（前台）、`cam watch-daemon`（后台）与 `cam watch-trigger` 都通过
+//! `WatchEngine` 轮询并发送通知，事件到通知的映射只在 `to_notification` 中维护。
+//!
+//! 后台 daemon 通过 [`OutboundQueue`] 发送：轮询只入队，单个发送线程按 agent 保序发送。
+//!
+//! 嵌入 CAM 的应用可用 [`WatchEngine::with_notifier`] 注册自定义 [`Notifier`]，
+//! 与 OpenClaw 一起接收每条通知事件（工具调用除外）。
+//!
+//! 前台监控可按 agent 与事件类型过滤：
+//! `cam watch --filter agent=cam-* --events waiting,error`
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use tracing::{debug, error, info, warn};
+
+use crate::agent::recovery::recovery_message;
+use crate::agent::stuck::format_elapsed;
+use crate::agent::{AgentWatcher, WatchEvent};
+use crate::notification::{
+    NotificationEvent, Notifier, OpenclawNotifier, Outbound, OutboundQueue, SendResult,
+};
+use crate::session::reconcile::notify_resolved_enabled;
+use crate::session::PendingResolution;
+
+/// 事件类别（`--events` 的取值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Waiting,
+    Exited,
+    Error,
+    Tool,
+    Stuck,
+    Idle,
+    Context,
+    Rule,
+    Recovery,
+    Resolved,
+    Resumed,
+}
+
+impl EventKind {
+    /// 所有类别名称
+    pub const NAMES: [&'static str; 11] = [
+        "waiting", "exited", "error", "tool", "stuck", "idle", "context", "rule", "recovery",
+        "resolved", "resumed",
+    ];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "waiting" => Ok(Self::Waiting),
+            "exited" | "exit" => Ok(Self::Exited),
+            "error" => Ok(Self::Error),
+            "tool" | "tools" => Ok(Self::Tool),
+            "stuck" => Ok(Self::Stuck),
+            "idle" => Ok(Self::Idle),
+            "context" => Ok(Self::Context),
+            "rule" | "rules" => Ok(Self::Rule),
+            "recovery" => Ok(Self::Recovery),
+            "resolved" => Ok(Self::Resolved),
+            "resumed" => Ok(Self::Resumed),
+            other => Err(anyhow!(
+                "未知事件类型: {}（可选: {}）",
+                other,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// 事件所属类别
+    pub fn of(event: &WatchEvent) -> Self {
+        match event {
+            WatchEvent::WaitingForInput { .. } => Self::Waiting,
+            WatchEvent::AgentExited { .. } => Self::Exited,
+            WatchEvent::Error { .. } => Self::Error,
+            WatchEvent::ToolUse { .. } | WatchEvent::ToolUseBatch { .. } => Self::Tool,
+            WatchEvent::AgentStuck { .. } => Self::Stuck,
+            WatchEvent::AgentIdle { .. } => Self::Idle,
+            WatchEvent::ContextHigh { .. } => Self::Context,
+            WatchEvent::RuleMatched { .. } => Self::Rule,
+            WatchEvent::Recovery { .. } => Self::Recovery,
+            WatchEvent::ConfirmationResolved { .. } => Self::Resolved,
+            WatchEvent::AgentResumed { .. } => Self::Resumed,
+        }
+    }
+}
+
+/// 事件过滤条件（默认不过滤）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchFilter {
+    /// agent ID 的 glob 模式（支持 `*` 和 `?`）
+    pub agent: Option<String>,
+    /// 只保留这些类别（None 表示全部）
+    pub events: Option<Vec<EventKind>>,
+}
+
+impl WatchFilter {
+    /// 解析 `--filter key=value`（可多次）与 `--events a,b`
+    pub fn parse(filters: &[String], events: Option<&str>) -> Result<Self> {
+        let mut filter = Self::default();
+        for item in filters {
+            let (key, value) = item
+                .split_once('=')
+                .ok_or_else(|| anyhow!("无效的过滤条件: {}（格式: agent=<glob>）", item))?;
+            match key.trim() {
+                "agent" => filter.agent = Some(value.trim().to_string()),
+                other => return Err(anyhow!("未知的过滤字段: {}（可选: agent）", other)),
+            }
+        }
+        if let Some(events) = events {
+            let kinds = events
+                .split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(EventKind::parse)
+                .collect::<Result<Vec<_>>>()?;
+            filter.events = Some(kinds);
+        }
+        Ok(filter)
+    }
+
+    /// 事件是否满足过滤条件
+    pub fn matches(&self, event: &WatchEvent) -> bool {
+        if let Some(pattern) = &self.agent {
+            if !glob_match(pattern, event.agent_id()) {
+                return false;
+            }
+        }
+        match &self.events {
+            Some(kinds) => kinds.contains(&EventKind::of(event)),
+            None => true,
+        }
+    }
+}
+
+/// 简单 glob 匹配：`*` 匹配任意长度，`?` 匹配单个字符
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 的位置及其当前匹配到的文本位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 把监控事件转换为通知事件（不需要通知的事件返回 None）
+///
+/// `project_path` 只在需要时调用（等待输入、卡住）。工具调用走 `send_event`
+/// 的紧急度路由，不在此转换。
+pub fn to_notification(
+    event: &WatchEvent,
+    project_path: impl FnOnce(&str) -> String,
+    notify_resolved: bool,
+) -> Option<NotificationEvent> {
+    match event {
+        WatchEvent::AgentExited {
+            agent_id,
+            project_path,
+        } => {
+            Some(NotificationEvent::agent_exited(agent_id).with_project_path(project_path.clone()))
+        }
+        WatchEvent::Error {
+            agent_id, message, ..
+        } => Some(NotificationEvent::error(agent_id, message)),
+        WatchEvent::WaitingForInput {
+            agent_id,
+            pattern_type,
+            context,
+            dedup_key,
+            is_decision_required,
+        } => Some(
+            NotificationEvent::waiting_for_input_with_decision(
+                agent_id,
+                pattern_type,
+                *is_decision_required,
+            )
+            .with_project_path(project_path(agent_id))
+            .with_terminal_snapshot(context.clone())
+            .with_dedup_key(dedup_key.clone()),
+        ),
+        WatchEvent::ConfirmationResolved {
+            agent_id,
+            context,
+            resolution: PendingResolution::ResolvedInTerminal,
+            ..
+        } if notify_resolved => {
+            let preview = crate::infra::truncate_str(context, 97);
+            Some(NotificationEvent::notification(
+                agent_id,
+                "resolved_in_terminal",
+                format!("已在终端中处理: {}", preview),
+            ))
+        }
+        // 批次间隔由 watcher 控制，跳过去重以免被 burst 保护吞掉
+        WatchEvent::ToolUseBatch {
+            agent_id, summary, ..
+        } => Some(
+            NotificationEvent::notification(agent_id, "tool_batch", summary).with_skip_dedup(true),
+        ),
+        WatchEvent::AgentStuck {
+            agent_id,
+            elapsed_secs,
+            last_action,
+        } => {
+            // 不附带终端快照：快照仍显示处理中，会被 is_processing 跳过
+            let elapsed = format_elapsed(*elapsed_secs);
+            let message = match last_action {
+                Some(action) => format!("可能卡住了：{} 无变化，最后操作: {}", elapsed, action),
+                None => format!("可能卡住了：处理中 {} 无变化", elapsed),
+            };
+            Some(
+                NotificationEvent::notification(agent_id, "agent_stuck", message)
+                    .with_project_path(project_path(agent_id))
+                    .with_skip_dedup(true),
+            )
+        }
+        WatchEvent::AgentIdle {
+            agent_id,
+            project_path,
+            idle_secs,
+        } => {
+            let project = std::path::Path::new(project_path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(agent_id);
+            let message = format!(
+                "{} 已空闲 {}，回复 /kill 停止，/keep 不再提醒",
+                project,
+                format_elapsed(*idle_secs)
+            );
+            Some(
+                NotificationEvent::notification(agent_id, "agent_idle", message)
+                    .with_project_path(project_path.clone())
+                    .with_skip_dedup(true),
+            )
+        }
+        WatchEvent::ContextHigh {
+            agent_id,
+            project_path,
+            usage_percent,
+        } => {
+            let project = std::path::Path::new(project_path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(agent_id);
+            let message = format!(
+                "{} 上下文已用 {}%，建议执行 /compact",
+                project, usage_percent
+            );
+            Some(
+                NotificationEvent::notification(agent_id, "context_high", message)
+                    .with_project_path(project_path.clone())
+                    .with_skip_dedup(true),
+            )
+        }
+        WatchEvent::RuleMatched {
+            agent_id,
+            project_path,
+            rule,
+            line,
+            completed_tasks,
+        } => {
+            let mut message = format!("[{}] {}", rule.display_name(), line);
+            if !completed_tasks.is_empty() {
+                message.push_str(&format!("\n已完成任务 #{}", completed_tasks.join(", #")));
+            }
+            Some(
+                NotificationEvent::notification(agent_id, rule.notification_type(), message)
+                    .with_project_path(project_path.clone())
+                    .with_skip_dedup(true),
+            )
+        }
+        WatchEvent::Recovery {
+            agent_id,
+            action,
+            attempt,
+            max_attempts,
+            outcome,
+        } => {
+            let notification_type = if outcome.needs_attention() {
+                "recovery_failed"
+            } else {
+                "recovery"
+            };
+            let message = recovery_message(action, *attempt, *max_attempts, outcome);
+            Some(
+                NotificationEvent::notification(agent_id, notification_type, message)
+                    .with_skip_dedup(true),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// 监控引擎：轮询 agent、过滤事件并通过统一管道发送通知
+pub struct WatchEngine {
+    watcher: AgentWatcher,
+    /// None 表示只产出事件不发送通知
+    notifier: Option<Arc<OpenclawNotifier>>,
+    /// 自定义通知器
+    custom: Vec<Arc<dyn Notifier>>,
+    /// 设置后 `poll` 只入队，由发送线程保序发送
+    outbound: Option<OutboundQueue>,
+    filter: WatchFilter,
+    notify_resolved: bool,
+}
+
+impl WatchEngine {
+    pub fn new(notifier: Option<OpenclawNotifier>, filter: WatchFilter) -> Self {
+        Self {
+            watcher: AgentWatcher::new(),
+            notifier: notifier.map(Arc::new),
+            custom: Vec::new(),
+            outbound: None,
+            filter,
+            notify_resolved: notify_resolved_enabled(),
+        }
+    }
+
+    /// 通过出站队列异步发送（watcher daemon 使用），没有任何通知器时无效
+    pub fn with_outbound_queue(mut self) -> Self {
+        self.start_outbound();
+        self
+    }
+
+    /// 注册自定义通知器（可多次调用）
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.custom.push(notifier);
+        if self.outbound.is_some() {
+            self.start_outbound();
+        }
+        self
+    }
+
+    /// 替换内置通知器并重新读取 `notify_resolved`（config.json 热重载）
+    ///
+    /// 使用出站队列时先发完旧队列中的通知，再按新通知器重建队列。
+    pub fn reload_notifier(&mut self, notifier: Option<OpenclawNotifier>) {
+        self.notifier = notifier.map(Arc::new);
+        self.notify_resolved = notify_resolved_enabled();
+        if self.outbound.is_some() {
+            self.start_outbound();
+        }
+    }
+
+    fn start_outbound(&mut self) {
+        // 先停止旧队列，再按当前通知器重建
+        self.outbound = None;
+        if self.notifier.is_some() || !self.custom.is_empty() {
+            self.outbound = Some(OutboundQueue::for_notifiers(
+                self.notifier.clone(),
+                self.custom.clone(),
+            ));
+        }
+    }
+
+    pub fn watcher(&self) -> &AgentWatcher {
+        &self.watcher
+    }
+
+    pub fn watcher_mut(&mut self) -> &mut AgentWatcher {
+        &mut self.watcher
+    }
+
+    pub fn notifier(&self) -> Option<&OpenclawNotifier> {
+        self.notifier.as_deref()
+    }
+
+    /// 轮询一次，返回满足过滤条件的事件（已发送通知或已入队）
+    pub fn poll(&mut self) -> Result<Vec<WatchEvent>> {
+        let events: Vec<WatchEvent> = self
+            .watcher
+            .poll_once()?
+            .into_iter()
+            .filter(|event| self.filter.matches(event))
+            .collect();
+        for event in &events {
+            match self.outbound {
+                Some(ref queue) => self.enqueue(queue, event),
+                None => {
+                    self.dispatch(event);
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// 冷启动对账（daemon 启动时调用）：立即判断所有 agent 的等待状态，
+    /// 并报告 daemon 停止期间退出的 agent
+    ///
+    /// 通知去重状态保存在磁盘上，停止前已通知过的等待不会重复发送。
+    pub fn reconcile_cold_start(&mut self) -> Result<Vec<WatchEvent>> {
+        self.watcher.reconcile_on_next_poll();
+        self.poll()
+    }
+
+    /// 将事件的通知放入出站队列
+    fn enqueue(&self, queue: &OutboundQueue, event: &WatchEvent) {
+        let message = match event {
+            WatchEvent::ToolUse {
+                agent_id,
+                tool_name,
+                tool_target,
+                ..
+            } => Outbound::ToolUse {
+                agent_id: agent_id.clone(),
+                tool_name: tool_name.clone(),
+                context: tool_target.clone().unwrap_or_default(),
+            },
+            _ => {
+                let Some(notification) =
+                    to_notification(event, |id| self.project_path(id), self.notify_resolved)
+                else {
+                    return;
+                };
+                Outbound::Event(notification)
+            }
+        };
+        debug!(agent_id = %event.agent_id(), "Notification queued");
+        queue.push(message);
+    }
+
+    /// 为单个事件发送通知（没有 notifier 或事件不需要通知时返回 None）
+    ///
+    /// 自定义通知器的结果只记录日志。
+    pub fn dispatch(&self, event: &WatchEvent) -> Option<Result<SendResult>> {
+        self.dispatch_with(event, |notification| notification)
+    }
+
+    /// 同 `dispatch`，发送前可调整通知（如手动触发时跳过去重）
+    pub fn dispatch_with(
+        &self,
+        event: &WatchEvent,
+        customize: impl FnOnce(NotificationEvent) -> NotificationEvent,
+    ) -> Option<Result<SendResult>> {
+        if self.notifier.is_none() && self.custom.is_empty() {
+            return None;
+        }
+        let agent_id = event.agent_id();
+
+        if let WatchEvent::ToolUse {
+            tool_name,
+            tool_target,
+            ..
+        } = event
+        {
+            let notifier = self.notifier.as_ref()?;
+            debug!(agent_id = %agent_id, tool_name = %tool_name, "Tool use detected");
+            let context = tool_target.as_deref().unwrap_or("");
+            let result = notifier.send_event(agent_id, "ToolUse", tool_name, context);
+            match &result {
+                Ok(result) => debug!(agent_id = %agent_id, result = ?result, "Notification result"),
+                Err(e) => warn!(agent_id = %agent_id, error = %e, "Notification failed"),
+            }
+            return Some(result);
+        }
+
+        let notification = customize(to_notification(
+            event,
+            |id| self.project_path(id),
+            self.notify_resolved,
+        )?);
+        info!(agent_id = %agent_id, event_type = ?notification.event_type, "Sending notification");
+        for custom in &self.custom {
+            if let Err(e) = custom.send_notification_event(&notification) {
+                warn!(agent_id = %agent_id, notifier = custom.name(), error = %e, "Custom notifier failed");
+            }
+        }
+        let result = self
+            .notifier
+            .as_ref()?
+            .send_notification_event(&notification);
+        match &result {
+            Ok(result) => info!(agent_id = %agent_id, result = ?result, "Notification result"),
+            Err(e) => error!(agent_id = %agent_id, error = %e, "Notification failed"),
+        }
+        Some(result)
+    }
+
+    fn project_path(&self, agent_id: &str) -> String {
+        self.watcher
+            .agent_manager()
+            .get_agent(agent_id)
+            .ok()
+            .flatten()
+            .map(|a| a.project_path)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waiting(agent_id: &str) -> WatchEvent {
+        WatchEvent::WaitingForInput {
+            agent_id: agent_id.to_string(),
+            pattern_type: "Confirmation".to_string(),
+            context: "Continue? [y/n]".to_string(),
+            dedup_key: "abcdef123456".to_string(),
+            is_decision_required: false,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("cam-*", "cam-123"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("cam-?2*", "cam-123"));
+        assert!(glob_match("*-worker-*", "lead-worker-2"));
+        assert!(!glob_match("cam-*", "ext-123"));
+        assert!(!glob_match("cam-1", "cam-12"));
+    }
+
+    #[test]
+    fn test_filter_parse_and_match() {
+        let filter =
+            WatchFilter::parse(&["agent=cam-*".to_string()], Some("waiting, error")).unwrap();
+        assert_eq!(filter.agent.as_deref(), Some("cam-*"));
+        assert_eq!(
+            filter.events,
+            Some(vec![EventKind::Waiting, EventKind::Error])
+        );
+        assert!(filter.matches(&waiting("cam-1")));
+        assert!(!filter.matches(&waiting("ext-1")));
+        assert!(!filter.matches(&WatchEvent::AgentResumed {
+            agent_id: "cam-1".to_string()
+        }));
+
+        assert!(WatchFilter::default().matches(&waiting("ext-1")));
+        assert!(WatchFilter::parse(&["agent".to_string()], None).is_err());
+        assert!(WatchFilter::parse(&["project=x".to_string()], None).is_err());
+        assert!(WatchFilter::parse(&[], Some("waiting,bogus")).is_err());
+    }
+
+    #[test]
+    fn test_to_notification_routing() {
+        let notification =
+            to_notification(&waiting("cam-1"), |_| "/tmp/app".to_string(), false).unwrap();
+        assert_eq!(notification.project_path.as_deref(), Some("/tmp/app"));
+        assert_eq!(notification.dedup_key.as_deref(), Some("abcdef123456"));
+
+        let resumed = WatchEvent::AgentResumed {
+            agent_id: "cam-1".to_string(),
+        };
+        assert!(to_notification(&resumed, |_| unreachable!(), true).is_none());
+
+        let resolved = WatchEvent::ConfirmationResolved {
+            agent_id: "cam-1".to_string(),
+            confirmation_id: "c1".to_string(),
+            context: "Allow Bash?".to_string(),
+            resolution: PendingResolution::ResolvedInTerminal,
+        };
+        assert!(to_notification(&resolved, |_| String::new(), false).is_none());
+        assert!(to_notification(&resolved, |_| String::new(), true).is_some());
+    }
+}
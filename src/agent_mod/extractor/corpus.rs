@@ -0,0 +1,321 @@
+//! 终端快照语料与 golden 结果 - 在发布前发现本地识别规则的回归
+//!
+//! 语料目录（仓库中的 `examples/snapshots/`）每个条目两个文件：
+//! - `<name>.txt`：匿名化的终端快照
+//! - `<name>.json`：agent 类型、说明和期望结果（[`SnapshotGolden`]）
+//!
+//! 期望结果由只有本地级别（pattern、内置模板）的提取阶梯得出，不调用 API，结果稳定。
+//! `cam snapshot capture <agent_id>` 从运行中的 agent 添加条目，`tests/snapshot_golden_test.rs`
+//! 逐条比较；设置 `CAM_UPDATE_GOLDEN=1` 运行测试时改为重写期望结果。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::ladder::{FallbackLadder, LadderConfig, LadderStore};
+use super::{extract_message_with, MessageType};
+use crate::embedding::library::builtin_templates;
+use crate::embedding::{Embeddings, LexicalEmbedder, TemplateLibrary};
+use crate::infra::terminal::truncate_last_lines;
+use crate::notification::dedup_key::strip_ansi_codes;
+use crate::notification::{classify_waiting, ErrorCategory, NotificationSummarizer, WaitingKind};
+
+/// 设置后 golden 测试重写期望结果而不是比较
+pub const UPDATE_GOLDEN_ENV: &str = "CAM_UPDATE_GOLDEN";
+
+/// 错误分类只看最后这么多行
+const ERROR_LINES: usize = 15;
+
+/// 内置模板的匹配阈值（与 `embedding.threshold` 默认值一致）
+const TEMPLATE_THRESHOLD: f32 = 0.8;
+
+/// 编号选项行（提取结果中的格式 `1. xxx`）
+static OPTION_LINE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{1,2}\. \S").expect("Invalid option line regex"));
+
+static EMAIL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").expect("Invalid email regex")
+});
+
+/// 一个快照的期望结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotGolden {
+    /// 提取到的问题（不含选项），没有时为 None
+    pub question: Option<String>,
+    #[serde(default)]
+    pub options: Vec<String>,
+    pub message_type: Option<MessageType>,
+    #[serde(default)]
+    pub decision_required: bool,
+    pub waiting_kind: WaitingKind,
+    /// 通知紧急度（`HIGH`/`MEDIUM`/`LOW`）
+    pub urgency: String,
+    /// 屏幕底部的错误类别，无法识别时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_category: Option<ErrorCategory>,
+}
+
+/// `<name>.json` 的内容
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryFile {
+    agent_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    expected: SnapshotGolden,
+}
+
+/// 语料中的一个条目
+#[derive(Debug, Clone)]
+pub struct CorpusEntry {
+    pub name: String,
+    pub agent_type: String,
+    pub description: String,
+    pub snapshot: String,
+    pub expected: SnapshotGolden,
+}
+
+/// 快照语料目录
+#[derive(Debug, Clone)]
+pub struct SnapshotCorpus {
+    dir: PathBuf,
+}
+
+impl SnapshotCorpus {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 条目的快照文件路径
+    pub fn snapshot_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.txt", name))
+    }
+
+    fn entry_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// 按名称排序的条目名（有快照文件的）
+    pub fn names(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = fs::read_dir(&self.dir)
+            .with_context(|| format!("无法读取语料目录 {}", self.dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// 按名称排序的所有条目，快照缺少期望结果时报错
+    pub fn entries(&self) -> Result<Vec<CorpusEntry>> {
+        self.names()?
+            .into_iter()
+            .map(|name| self.load(&name))
+            .collect()
+    }
+
+    /// 读取一个条目
+    pub fn load(&self, name: &str) -> Result<CorpusEntry> {
+        let snapshot = fs::read_to_string(self.snapshot_path(name))?;
+        let path = self.entry_path(name);
+        let file: EntryFile = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str(&content)?))
+            .with_context(|| format!("{} 缺少有效的期望结果", path.display()))?;
+        Ok(CorpusEntry {
+            name: name.to_string(),
+            agent_type: file.agent_type,
+            description: file.description,
+            snapshot,
+            expected: file.expected,
+        })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.snapshot_path(name).exists()
+    }
+
+    /// 写入条目（快照和期望结果）
+    pub fn save(&self, entry: &CorpusEntry) -> Result<()> {
+        if entry.name.is_empty() || entry.name.contains(['/', '\\']) {
+            return Err(anyhow!("无效的条目名称: {}", entry.name));
+        }
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.snapshot_path(&entry.name), &entry.snapshot)?;
+        let file = EntryFile {
+            agent_type: entry.agent_type.clone(),
+            description: entry.description.clone(),
+            expected: entry.expected.clone(),
+        };
+        fs::write(
+            self.entry_path(&entry.name),
+            serde_json::to_string_pretty(&file)? + "\n",
+        )?;
+        Ok(())
+    }
+}
+
+/// 把提取结果拆成问题和编号选项（去掉末尾的回复提示）
+fn split_message(content: &str) -> (Option<String>, Vec<String>) {
+    let body = content.split("\n\n回复").next().unwrap_or(content);
+    let (options, question): (Vec<&str>, Vec<&str>) =
+        body.lines().partition(|line| OPTION_LINE_RE.is_match(line));
+    let question = question.join("\n").trim().to_string();
+    (
+        (!question.is_empty()).then_some(question),
+        options.into_iter().map(str::to_string).collect(),
+    )
+}
+
+/// 只用本地级别的提取阶梯，生成 [`SnapshotGolden`]
+pub struct LocalAnalyzer {
+    ladder: FallbackLadder,
+}
+
+impl LocalAnalyzer {
+    /// 阶梯状态和向量缓存写到 `state_dir`（不影响 `cam stats`）
+    pub fn new(state_dir: &Path) -> Result<Self> {
+        let config = LadderConfig {
+            haiku_per_minute: 0,
+            large_model: None,
+            ..LadderConfig::default()
+        };
+        let embeddings = Embeddings::new(Box::new(LexicalEmbedder), state_dir);
+        let library = TemplateLibrary::build(&embeddings, builtin_templates(), TEMPLATE_THRESHOLD)?;
+        let store = LadderStore::at(&state_dir.join("ai_ladder.json"));
+        Ok(Self {
+            ladder: FallbackLadder::new(config, store, None, None)
+                .with_embeddings(embeddings, Some(library)),
+        })
+    }
+
+    pub fn analyze(&self, snapshot: &str) -> SnapshotGolden {
+        let extracted = extract_message_with(&self.ladder, snapshot);
+        let (question, options) = extracted
+            .as_ref()
+            .map(|message| split_message(&message.content))
+            .unwrap_or_default();
+        let decision_required = extracted.as_ref().is_some_and(|m| m.is_decision_required);
+        let waiting_kind = classify_waiting(
+            "Other",
+            decision_required,
+            extracted.as_ref().map(|m| &m.message_type),
+            extracted.as_ref().map_or(snapshot, |m| m.content.as_str()),
+        );
+        let tail = truncate_last_lines(&strip_ansi_codes(snapshot), ERROR_LINES);
+        let error_category = Some(NotificationSummarizer::new().classify_error(&tail))
+            .filter(|category| *category != ErrorCategory::Other);
+
+        SnapshotGolden {
+            question,
+            options,
+            message_type: extracted.map(|m| m.message_type),
+            decision_required,
+            waiting_kind,
+            urgency: waiting_kind.urgency().as_str().to_string(),
+            error_category,
+        }
+    }
+}
+
+/// 匿名化快照：去掉 ANSI 转义，替换项目路径、home 目录、用户名、主机名和邮箱
+pub fn anonymize(snapshot: &str, project_path: &str) -> String {
+    let mut text = strip_ansi_codes(snapshot);
+    if !project_path.is_empty() {
+        text = text.replace(project_path, "/path/to/project");
+    }
+    if let Some(home) = dirs::home_dir() {
+        text = text.replace(&*home.to_string_lossy(), "~");
+    }
+    text = EMAIL_RE.replace_all(&text, "user@example.com").into_owned();
+    // 过短的名称容易误替换普通单词
+    if let Some(host) = sysinfo::System::host_name().filter(|host| host.len() >= 3) {
+        text = text.replace(&host, "host");
+    }
+    if let Some(user) = std::env::var("USER").ok().filter(|user| user.len() >= 3) {
+        text = text.replace(&user, "user");
+    }
+
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |i| i + 1);
+    let start = lines[..end]
+        .iter()
+        .position(|line| !line.is_empty())
+        .unwrap_or(end);
+    lines[start..end].join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_message() {
+        assert_eq!(
+            split_message("Which database?\n1. PostgreSQL\n2. SQLite\n\n回复数字选择"),
+            (
+                Some("Which database?".to_string()),
+                vec!["1. PostgreSQL".to_string(), "2. SQLite".to_string()]
+            )
+        );
+        assert_eq!(
+            split_message("Overwrite? [y/N]\n\n回复 y/n"),
+            (Some("Overwrite? [y/N]".to_string()), Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_corpus_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let corpus = SnapshotCorpus::new(&tmp.path().join("snapshots"));
+        let analyzer = LocalAnalyzer::new(tmp.path()).unwrap();
+        let snapshot = "Overwrite existing config? [y/N]\n";
+        let entry = CorpusEntry {
+            name: "confirm".to_string(),
+            agent_type: "claude".to_string(),
+            description: String::new(),
+            snapshot: snapshot.to_string(),
+            expected: analyzer.analyze(snapshot),
+        };
+        assert_eq!(entry.expected.message_type, Some(MessageType::Confirmation));
+        assert_eq!(entry.expected.waiting_kind, WaitingKind::NeedsDecision);
+
+        corpus.save(&entry).unwrap();
+        let entries = corpus.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].expected, entry.expected);
+        assert!(corpus
+            .save(&CorpusEntry {
+                name: "../escape".to_string(),
+                ..entry
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_anonymize() {
+        let home = dirs::home_dir().unwrap();
+        let snapshot = format!(
+            "\n\x1b[1m{}/work/app\x1b[0m $ git log\nAuthor: Jane <jane.doe@corp.io>   \n\n",
+            home.display()
+        );
+        assert_eq!(
+            anonymize(&snapshot, &format!("{}/work/app", home.display())),
+            "/path/to/project $ git log\nAuthor: Jane <user@example.com>\n"
+        );
+    }
+}
@@ -0,0 +1,734 @@
+//! 提取阶梯 - 先用本地方法提取消息，质量不够时才逐级调用 AI
+//!
+//! 按顺序尝试：
+//! 1. `pattern`：正则识别屏幕底部的常见提示（`[y/N]` 确认、编号选项），不调用 API
+//! 2. `embedding`：用 [`crate::embedding`] 的模型把快照匹配到已知的提示模板；匹配不到时与之前
+//!    AI 成功提取过的问题比较，足够相似时复用结果
+//! 3. `haiku`：默认模型（`AnthropicConfig` 中的 model）
+//! 4. `large`：config.json 配置的更大模型，未配置时跳过
+//!
+//! 每一级的结果用 [`assess_extracted_message`] 评分（`embedding` 的得分不超过相似度），
+//! 低于 `threshold` 才进入下一级；所有级别都不达标时使用得分最高的结果。API 级别按每分钟
+//! 调用次数限流，超出时跳过该级。各级的尝试、采纳、低分、未命中、限流次数和耗时记录在
+//! `~/.config/code-agent-monitor/ai_ladder.json`，`cam stats` 显示。
+//!
+//! config.json 示例：
+//! ```json
+//! {
+//!   "ai_ladder": {
+//!     "threshold": 0.8,
+//!     "similarity": 0.92,
+//!     "haiku_per_minute": 20,
+//!     "large_model": "claude-sonnet-4-5",
+//!     "large_per_minute": 5
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use anyhow::Result;
+use chrono::Utc;
+use fs2::FileExt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::traits::{ExtractedMessage, ExtractionResult, MessageExtractor, MessageType};
+use super::HaikuExtractor;
+use crate::ai::quality::{assess_extracted_message, thresholds};
+use crate::embedding::library::{clean_line, is_footer};
+use crate::embedding::{cosine, Embeddings, LexicalEmbedder, TemplateLibrary};
+use crate::infra::privacy::PrivacyConfig;
+use crate::infra::terminal::truncate_last_lines;
+use crate::notification::dedup_key::{generate_dedup_key, question_fingerprint, strip_ansi_codes};
+
+/// 正则识别只看最后这么多行
+const PATTERN_LINES: usize = 40;
+
+/// 缓存的已提取问题上限，超出时丢弃最旧的
+const MAX_CACHE_ENTRIES: usize = 200;
+
+/// 编号选项行：`1. xxx`、`❯ 2) xxx`（Codex 用 `›` 标记当前选项）
+static OPTION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[❯›>]\s*)?(\d{1,2})[.)]\s+(\S.*)$").expect("Invalid option regex")
+});
+
+/// 行尾的 y/n 确认提示
+static CONFIRM_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:\[y/n\]|\(y/n\)|\[yes/no\]|\(yes/no\))\s*[:?？]?\s*$")
+        .expect("Invalid confirmation regex")
+});
+
+/// 阶梯的一级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LadderStage {
+    Pattern,
+    Embedding,
+    Haiku,
+    Large,
+}
+
+impl LadderStage {
+    /// 按尝试顺序排列
+    pub const ALL: [LadderStage; 4] = [
+        LadderStage::Pattern,
+        LadderStage::Embedding,
+        LadderStage::Haiku,
+        LadderStage::Large,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pattern => "pattern",
+            Self::Embedding => "embedding",
+            Self::Haiku => "haiku",
+            Self::Large => "large",
+        }
+    }
+
+    /// 是否调用 API
+    pub fn uses_api(&self) -> bool {
+        matches!(self, Self::Haiku | Self::Large)
+    }
+}
+
+/// 阶梯配置（config.json 的 `ai_ladder`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LadderConfig {
+    /// 质量得分达到该值即采纳，不再升级
+    pub threshold: f32,
+    /// `embedding` 级复用缓存结果所需的最低相似度
+    pub similarity: f32,
+    /// `haiku` 级每分钟最多调用次数（0 关闭该级）
+    pub haiku_per_minute: u32,
+    /// `large` 级使用的模型，未设置时跳过该级
+    pub large_model: Option<String>,
+    /// `large` 级每分钟最多调用次数（0 关闭该级）
+    pub large_per_minute: u32,
+}
+
+impl Default for LadderConfig {
+    fn default() -> Self {
+        Self {
+            threshold: thresholds::HIGH,
+            similarity: 0.92,
+            haiku_per_minute: 20,
+            large_model: None,
+            large_per_minute: 5,
+        }
+    }
+}
+
+impl LadderConfig {
+    /// 从 config.json 加载，缺失或格式错误时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("ai_ladder").unwrap_or_default()
+    }
+
+    fn per_minute(&self, stage: LadderStage) -> u32 {
+        match stage {
+            LadderStage::Haiku => self.haiku_per_minute,
+            LadderStage::Large => self.large_per_minute,
+            _ => u32::MAX,
+        }
+    }
+}
+
+/// 一级的累计指标
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageMetrics {
+    /// 实际运行次数
+    pub attempts: u64,
+    /// 结果被采纳的次数
+    pub accepted: u64,
+    /// 有结果但得分低于阈值的次数
+    pub below_threshold: u64,
+    /// 没有结果（未识别、无缓存、API 失败）的次数
+    pub misses: u64,
+    /// 因限流跳过的次数
+    pub rate_limited: u64,
+    /// 运行总耗时（毫秒）
+    pub total_ms: u64,
+}
+
+impl StageMetrics {
+    /// 平均耗时（毫秒）
+    pub fn avg_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.attempts).unwrap_or(0)
+    }
+
+    fn add(&mut self, other: &StageMetrics) {
+        self.attempts += other.attempts;
+        self.accepted += other.accepted;
+        self.below_threshold += other.below_threshold;
+        self.misses += other.misses;
+        self.rate_limited += other.rate_limited;
+        self.total_ms += other.total_ms;
+    }
+}
+
+/// AI 成功提取过的问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 计算向量的模型，换模型后旧条目不再参与比较
+    #[serde(default = "default_cache_model")]
+    model: String,
+    vector: Vec<f32>,
+    message: ExtractedMessage,
+    /// 写入时间（Unix 秒）
+    at: i64,
+}
+
+fn default_cache_model() -> String {
+    crate::embedding::Embedder::model_id(&LexicalEmbedder)
+}
+
+/// 持久化状态
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LadderState {
+    #[serde(default)]
+    stages: BTreeMap<LadderStage, StageMetrics>,
+    /// API 级别最近一分钟的调用时间（Unix 秒）
+    #[serde(default)]
+    calls: BTreeMap<LadderStage, Vec<i64>>,
+    #[serde(default)]
+    cache: Vec<CacheEntry>,
+}
+
+/// 阶梯状态文件（指标、限流记录、嵌入缓存），watcher 和 hook 进程共享
+#[derive(Debug, Clone)]
+pub struct LadderStore {
+    path: PathBuf,
+}
+
+impl LadderStore {
+    /// 默认路径
+    pub fn new() -> Self {
+        Self::at(&crate::infra::paths::state_dir().join("ai_ladder.json"))
+    }
+
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn read(&self) -> LadderState {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 在文件锁内读取、修改并保存状态
+    fn update<T>(&self, operation: impl FnOnce(&mut LadderState) -> T) -> Result<T> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("lock"))?;
+        lock_file.lock_exclusive()?;
+
+        let mut state = self.read();
+        let result = operation(&mut state);
+        let saved = serde_json::to_string(&state)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(fs::write(&self.path, content)?));
+
+        let _ = lock_file.unlock();
+        saved.map(|_| result)
+    }
+
+    /// 各级累计指标
+    pub fn metrics(&self) -> BTreeMap<LadderStage, StageMetrics> {
+        self.read().stages
+    }
+
+    /// 占用一次 API 调用额度，最近一分钟已达上限时返回 false
+    fn try_acquire(&self, stage: LadderStage, per_minute: u32) -> bool {
+        let now = Utc::now().timestamp();
+        self.update(|state| {
+            let calls = state.calls.entry(stage).or_default();
+            calls.retain(|at| now - at < 60);
+            if calls.len() >= per_minute as usize {
+                return false;
+            }
+            calls.push(now);
+            true
+        })
+        // 状态文件不可写时不限流
+        .unwrap_or(true)
+    }
+
+    /// 同一模型的缓存中与 `vector` 最相似且达到 `min_similarity` 的结果
+    fn nearest(
+        &self,
+        model: &str,
+        vector: &[f32],
+        min_similarity: f32,
+    ) -> Option<(ExtractedMessage, f32)> {
+        self.read()
+            .cache
+            .into_iter()
+            .filter(|entry| entry.model == model)
+            .map(|entry| (cosine(&entry.vector, vector), entry.message))
+            .filter(|(similarity, _)| *similarity >= min_similarity)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(similarity, message)| (message, similarity))
+    }
+
+    /// 累加本次提取的指标，并缓存 AI 采纳的结果
+    fn commit(
+        &self,
+        metrics: &BTreeMap<LadderStage, StageMetrics>,
+        cached: Option<(String, Vec<f32>, ExtractedMessage)>,
+    ) -> Result<()> {
+        self.update(|state| {
+            for (stage, delta) in metrics {
+                state.stages.entry(*stage).or_default().add(delta);
+            }
+            if let Some((model, vector, message)) = cached {
+                // 同一个问题只保留最新结果
+                state
+                    .cache
+                    .retain(|entry| entry.model != model || cosine(&entry.vector, &vector) < 0.99);
+                state.cache.push(CacheEntry {
+                    model,
+                    vector,
+                    message,
+                    at: Utc::now().timestamp(),
+                });
+                let excess = state.cache.len().saturating_sub(MAX_CACHE_ENTRIES);
+                state.cache.drain(..excess);
+            }
+        })
+    }
+}
+
+impl Default for LadderStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 用正则识别屏幕底部的提示，识别不了时返回 None
+pub fn pattern_message(snapshot: &str) -> Option<ExtractedMessage> {
+    let stripped = strip_ansi_codes(&truncate_last_lines(snapshot, PATTERN_LINES));
+    let mut lines: Vec<&str> = stripped.lines().map(clean_line).collect();
+    while lines.last().is_some_and(|line| is_footer(line)) {
+        lines.pop();
+    }
+    let last = *lines.last()?;
+
+    if CONFIRM_RE.is_match(last) {
+        return Some(ExtractedMessage {
+            content: format!("{}\n\n回复 y/n", last),
+            fingerprint: format!("pattern-{}", generate_dedup_key(last)),
+            context_complete: true,
+            message_type: MessageType::Confirmation,
+            is_decision_required: false,
+        });
+    }
+
+    // 底部连续的编号选项，编号需从 1 开始递增
+    let mut options: Vec<&str> = Vec::new();
+    while let Some(caps) = lines.last().and_then(|line| OPTION_RE.captures(line)) {
+        options.push(caps.get(0).map(|m| m.as_str()).unwrap_or_default());
+        lines.pop();
+    }
+    options.reverse();
+    let numbers: Vec<usize> = options
+        .iter()
+        .filter_map(|line| OPTION_RE.captures(line)?[1].parse().ok())
+        .collect();
+    if options.len() < 2 || numbers != (1..=options.len()).collect::<Vec<_>>() {
+        return None;
+    }
+    let options: Vec<String> = options
+        .iter()
+        .filter_map(|line| {
+            let caps = OPTION_RE.captures(line)?;
+            Some(format!("{}. {}", &caps[1], caps[2].trim()))
+        })
+        .collect();
+
+    // 选项上方几行（不算空行）内的问题
+    let question = lines
+        .iter()
+        .rev()
+        .filter(|line| !line.is_empty())
+        .take(5)
+        .find(|line| line.ends_with(['?', '？', ':', '：']))?;
+    let lower = question.to_lowercase();
+    let permission = lower.starts_with("do you want") || options[0].starts_with("1. Yes");
+
+    let body = format!("{}\n{}", question, options.join("\n"));
+    Some(ExtractedMessage {
+        content: format!("{}\n\n回复数字选择", body),
+        fingerprint: format!("pattern-{}", generate_dedup_key(&body)),
+        context_complete: true,
+        message_type: MessageType::Choice,
+        is_decision_required: !permission,
+    })
+}
+
+/// 一级的运行结果
+enum StageRun {
+    /// 该级不可用
+    Skipped,
+    RateLimited,
+    Miss,
+    /// 提取到消息，得分不超过给定上限
+    Extracted(ExtractedMessage, f32),
+    /// AI 给出的最终结论（处理中、需要更多上下文、终端错误）
+    Final(ExtractionResult),
+}
+
+/// 提取阶梯，可替代 [`HaikuExtractor`] 用于 [`super::ReactExtractor`]
+pub struct FallbackLadder {
+    config: LadderConfig,
+    store: LadderStore,
+    haiku: Option<Box<dyn MessageExtractor>>,
+    large: Option<Box<dyn MessageExtractor>>,
+    embeddings: Embeddings,
+    library: Option<TemplateLibrary>,
+}
+
+impl FallbackLadder {
+    /// 读取配置并创建 AI 级别（API 未配置时只有本地级别）
+    ///
+    /// 快照隐私模式下 AI 级别只使用本机模型，问题向量改用字面相似度回退。
+    pub fn load() -> Self {
+        let config = LadderConfig::load();
+        let haiku = HaikuExtractor::new()
+            .ok()
+            .map(|e| Box::new(e) as Box<dyn MessageExtractor>);
+        let large = config.large_model.as_deref().and_then(|model| {
+            HaikuExtractor::with_model(model)
+                .inspect_err(|e| warn!(model, error = %e, "Failed to create large model extractor"))
+                .ok()
+                .map(|e| Box::new(e) as Box<dyn MessageExtractor>)
+        });
+        let embeddings = if PrivacyConfig::load().local_snapshots {
+            Embeddings::lexical()
+        } else {
+            Embeddings::load()
+        };
+        let library = TemplateLibrary::load_or_build(&embeddings)
+            .inspect_err(|e| warn!(error = %e, "Failed to build prompt template library"))
+            .ok();
+        Self::new(config, LadderStore::new(), haiku, large).with_embeddings(embeddings, library)
+    }
+
+    /// 创建时使用字面相似度回退、不带模板库
+    pub fn new(
+        config: LadderConfig,
+        store: LadderStore,
+        haiku: Option<Box<dyn MessageExtractor>>,
+        large: Option<Box<dyn MessageExtractor>>,
+    ) -> Self {
+        Self {
+            config,
+            store,
+            haiku,
+            large,
+            embeddings: Embeddings::lexical(),
+            library: None,
+        }
+    }
+
+    /// 指定嵌入模型和模板库
+    pub fn with_embeddings(
+        mut self,
+        embeddings: Embeddings,
+        library: Option<TemplateLibrary>,
+    ) -> Self {
+        self.embeddings = embeddings;
+        self.library = library;
+        self
+    }
+
+    /// 快照问题的向量，模型不可用时返回 None
+    fn question_vector(&self, snapshot: &str) -> Option<Vec<f32>> {
+        self.embeddings
+            .embed(&question_fingerprint(snapshot))
+            .inspect_err(|e| debug!(error = %e, "Embedding failed"))
+            .ok()
+    }
+
+    fn run_stage(&self, stage: LadderStage, snapshot: &str, lines: usize) -> StageRun {
+        match stage {
+            LadderStage::Pattern => match pattern_message(&truncate_last_lines(snapshot, lines)) {
+                Some(message) => StageRun::Extracted(message, 1.0),
+                None => StageRun::Miss,
+            },
+            LadderStage::Embedding => {
+                let template = self.library.as_ref().and_then(|library| {
+                    library
+                        .best_match(&self.embeddings, &truncate_last_lines(snapshot, lines))
+                        .inspect_err(|e| debug!(error = %e, "Template matching failed"))
+                        .ok()
+                        .flatten()
+                });
+                if let Some(found) = template {
+                    return StageRun::Extracted(found.message(), found.confidence);
+                }
+                let model = self.embeddings.model_id();
+                match self
+                    .question_vector(snapshot)
+                    .and_then(|vector| self.store.nearest(&model, &vector, self.config.similarity))
+                {
+                    Some((message, similarity)) => StageRun::Extracted(message, similarity),
+                    None => StageRun::Miss,
+                }
+            }
+            LadderStage::Haiku | LadderStage::Large => {
+                let extractor = if stage == LadderStage::Haiku {
+                    &self.haiku
+                } else {
+                    &self.large
+                };
+                let per_minute = self.config.per_minute(stage);
+                let Some(extractor) = extractor.as_ref().filter(|_| per_minute > 0) else {
+                    return StageRun::Skipped;
+                };
+                if !self.store.try_acquire(stage, per_minute) {
+                    return StageRun::RateLimited;
+                }
+                match extractor.extract(snapshot, lines) {
+                    ExtractionResult::Success(message) => StageRun::Extracted(message, 1.0),
+                    ExtractionResult::Failed(reason) => {
+                        debug!(stage = stage.as_str(), reason = %reason, "Ladder stage failed");
+                        StageRun::Miss
+                    }
+                    other => StageRun::Final(other),
+                }
+            }
+        }
+    }
+}
+
+impl MessageExtractor for FallbackLadder {
+    fn extract(&self, terminal_snapshot: &str, lines: usize) -> ExtractionResult {
+        let mut metrics: BTreeMap<LadderStage, StageMetrics> = BTreeMap::new();
+        let mut cached = None;
+        let mut best: Option<(f32, ExtractedMessage)> = None;
+        let mut result = None;
+
+        for stage in LadderStage::ALL {
+            let start = Instant::now();
+            let run = self.run_stage(stage, terminal_snapshot, lines);
+            let entry = metrics.entry(stage).or_default();
+            match run {
+                StageRun::Skipped => {
+                    metrics.remove(&stage);
+                    continue;
+                }
+                StageRun::RateLimited => {
+                    entry.rate_limited += 1;
+                    continue;
+                }
+                _ => {
+                    entry.attempts += 1;
+                    entry.total_ms += start.elapsed().as_millis() as u64;
+                }
+            }
+            match run {
+                StageRun::Miss => entry.misses += 1,
+                StageRun::Final(final_result) => {
+                    entry.accepted += 1;
+                    result = Some(final_result);
+                    break;
+                }
+                StageRun::Extracted(message, cap) => {
+                    let score = assess_extracted_message(&message).confidence.min(cap);
+                    debug!(
+                        stage = stage.as_str(),
+                        score, "Ladder stage extracted message"
+                    );
+                    if score >= self.config.threshold {
+                        entry.accepted += 1;
+                        if stage.uses_api()
+                            && !matches!(message.message_type, MessageType::Idle { .. })
+                        {
+                            cached = self.question_vector(terminal_snapshot).map(|vector| {
+                                (self.embeddings.model_id(), vector, message.clone())
+                            });
+                        }
+                        result = Some(ExtractionResult::Success(message));
+                        break;
+                    }
+                    entry.below_threshold += 1;
+                    if best
+                        .as_ref()
+                        .is_none_or(|(best_score, _)| score > *best_score)
+                    {
+                        best = Some((score, message));
+                    }
+                }
+                StageRun::Skipped | StageRun::RateLimited => {}
+            }
+        }
+
+        if let Err(e) = self.store.commit(&metrics, cached) {
+            debug!(error = %e, "Failed to save extraction ladder state");
+        }
+        result.unwrap_or_else(|| match best {
+            // 都不达标时用得分最高的结果
+            Some((_, message)) => ExtractionResult::Success(message),
+            None => ExtractionResult::Failed("No ladder stage produced a message".to_string()),
+        })
+    }
+
+    fn is_processing(&self, terminal_snapshot: &str) -> bool {
+        // 底部是可识别的提示时 agent 一定在等待输入，不需要调用 API
+        if pattern_message(terminal_snapshot).is_some() {
+            return false;
+        }
+        self.haiku
+            .as_ref()
+            .or(self.large.as_ref())
+            .is_some_and(|extractor| extractor.is_processing(terminal_snapshot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct StubExtractor {
+        content: &'static str,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl MessageExtractor for StubExtractor {
+        fn extract(&self, _snapshot: &str, _lines: usize) -> ExtractionResult {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            ExtractionResult::Success(ExtractedMessage {
+                content: self.content.to_string(),
+                fingerprint: "stub".to_string(),
+                context_complete: true,
+                message_type: MessageType::OpenEnded,
+                is_decision_required: false,
+            })
+        }
+
+        fn is_processing(&self, _snapshot: &str) -> bool {
+            false
+        }
+    }
+
+    fn stub(content: &'static str) -> (Box<dyn MessageExtractor>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let extractor = StubExtractor {
+            content,
+            calls: Arc::clone(&calls),
+        };
+        (Box::new(extractor), calls)
+    }
+
+    #[test]
+    fn test_pattern_message() {
+        let choice = "⏺ Which framework should we use?\n\n│ Which framework should we use? │\n│ ❯ 1. React │\n│   2. Vue │\n│   3. Svelte │\n\nEnter to confirm · Esc to cancel\n";
+        let message = pattern_message(choice).unwrap();
+        assert_eq!(message.message_type, MessageType::Choice);
+        assert!(message.is_decision_required);
+        assert!(message.content.contains("1. React\n2. Vue\n3. Svelte"));
+        assert!(assess_extracted_message(&message).confidence >= thresholds::HIGH);
+
+        let permission =
+            "Bash command\n  rm -rf build\nDo you want to proceed?\n❯ 1. Yes\n  2. No\n";
+        let message = pattern_message(permission).unwrap();
+        assert!(!message.is_decision_required);
+
+        let confirm = pattern_message("Overwrite config.json? [y/N]\n").unwrap();
+        assert_eq!(confirm.message_type, MessageType::Confirmation);
+
+        // 问题已被回答、选项不在底部或编号不连续时不识别
+        assert!(pattern_message("Pick one?\n1. A\n2. B\n❯ 1\n⏺ Using A").is_none());
+        assert!(pattern_message("Steps:\n1. build\n3. test").is_none());
+        assert!(pattern_message("✻ Thinking…").is_none());
+    }
+
+    #[test]
+    fn test_ladder_escalates_on_low_quality() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = LadderStore::at(&temp.path().join("ai_ladder.json"));
+        let (haiku, haiku_calls) = stub("⏺ working");
+        let (large, large_calls) = stub("要把缓存层放在哪个数据库？请回复名称");
+        let ladder = FallbackLadder::new(
+            LadderConfig::default(),
+            store.clone(),
+            Some(haiku),
+            Some(large),
+        );
+
+        // 正则识别的提示不调用 API
+        let choice = "Which framework?\n1. React\n2. Vue\n";
+        assert!(!ladder.is_processing(choice));
+        assert!(matches!(
+            ladder.extract(choice, 80),
+            ExtractionResult::Success(_)
+        ));
+        assert_eq!(haiku_calls.load(Ordering::SeqCst), 0);
+
+        // haiku 得分低于阈值时升级到 large，采纳结果写入缓存
+        let snapshot = "Some long discussion\n⏺ Where should the cache layer live?";
+        let ExtractionResult::Success(message) = ladder.extract(snapshot, 80) else {
+            panic!("expected message");
+        };
+        assert!(message.content.contains("数据库"));
+        assert_eq!(haiku_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(large_calls.load(Ordering::SeqCst), 1);
+
+        // 相同问题再次出现时由 embedding 级复用
+        assert!(matches!(
+            ladder.extract(snapshot, 80),
+            ExtractionResult::Success(_)
+        ));
+        assert_eq!(haiku_calls.load(Ordering::SeqCst), 1);
+
+        let metrics = store.metrics();
+        assert_eq!(metrics[&LadderStage::Pattern].accepted, 1);
+        assert_eq!(metrics[&LadderStage::Pattern].misses, 2);
+        assert_eq!(metrics[&LadderStage::Haiku].below_threshold, 1);
+        assert_eq!(metrics[&LadderStage::Large].accepted, 1);
+        assert_eq!(metrics[&LadderStage::Embedding].accepted, 1);
+    }
+
+    #[test]
+    fn test_ladder_rate_limit() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = LadderStore::at(&temp.path().join("ai_ladder.json"));
+        let (haiku, haiku_calls) = stub("⏺ noise");
+        let config: LadderConfig = serde_json::from_str(r#"{"haiku_per_minute": 1}"#).unwrap();
+        assert_eq!(config.threshold, thresholds::HIGH);
+        let ladder = FallbackLadder::new(config, store.clone(), Some(haiku), None);
+
+        // 低分结果在没有更高级别时仍然返回
+        assert!(matches!(
+            ladder.extract("first", 80),
+            ExtractionResult::Success(_)
+        ));
+        assert!(matches!(
+            ladder.extract("second", 80),
+            ExtractionResult::Failed(_)
+        ));
+        assert_eq!(haiku_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(store.metrics()[&LadderStage::Haiku].rate_limited, 1);
+        assert!(!store.metrics().contains_key(&LadderStage::Large));
+    }
+}
@@ -3,6 +3,8 @@
 //! 使用 ReAct (Reasoning + Acting) 循环从终端快照中提取消息。
 //! 通过迭代扩展上下文直到提取完整的消息内容。
 
+pub mod corpus;
+pub mod ladder;
 pub mod prompts;
 pub mod traits;
 
@@ -10,11 +12,13 @@ use anyhow::Result;
 use tracing::{debug, info, warn};
 
 use crate::agent::manager::AgentStatus;
-use crate::ai::client::AnthropicClient;
+use crate::ai::client::{AnthropicClient, AnthropicConfig};
 use crate::ai::extractor::is_agent_processing;
-use crate::infra::tmux::TmuxManager;
+use crate::ai::profile::AiTask;
+use crate::infra::multiplexer::Multiplexer;
 use crate::notification::dedup_key::generate_dedup_key;
 
+pub use ladder::{FallbackLadder, LadderConfig, LadderStage, LadderStore, StageMetrics};
 pub use prompts::{message_extraction_prompt, MESSAGE_EXTRACTION_SYSTEM};
 pub use traits::{
     ExtractedMessage, ExtractionResult, IterationConfig, MessageExtractor, MessageType,
@@ -22,24 +26,25 @@ pub use traits::{
 
 /// 从终端快照提取格式化消息的便捷函数
 ///
-/// 使用 ReAct 循环迭代扩展上下文，直到提取完整的消息。
-/// 这是供 `openclaw.rs` 等模块使用的高级 API。
+/// 使用 ReAct 循环迭代扩展上下文，直到提取完整的消息；每一轮由 [`FallbackLadder`]
+/// 先尝试本地识别，质量不够时才调用 AI。这是供 `openclaw.rs` 等模块使用的高级 API。
 ///
 /// # 参数
 /// - `terminal_snapshot`: 终端快照内容
 ///
 /// # 返回
-/// - `Some((message, fingerprint, is_decision_required))`: 成功提取到消息、指纹和决策标记
+/// - `Some(message)`: 成功提取到的消息（内容、指纹、消息类型和决策标记）；
+///   检测到终端错误时 `content` 以 `ERROR: ` 开头
 /// - `None`: Agent 正在处理中、空闲或提取失败
-pub fn extract_message_from_snapshot(terminal_snapshot: &str) -> Option<(String, String, bool)> {
-    let extractor = match HaikuExtractor::new() {
-        Ok(e) => e,
-        Err(e) => {
-            warn!(error = %e, "Failed to create HaikuExtractor");
-            return None;
-        }
-    };
+pub fn extract_message_from_snapshot(terminal_snapshot: &str) -> Option<ExtractedMessage> {
+    extract_message_with(&FallbackLadder::load(), terminal_snapshot)
+}
 
+/// 用指定提取器执行 [`extract_message_from_snapshot`] 的 ReAct 循环（`cam bench` 对比不同配置时使用）
+pub fn extract_message_with(
+    extractor: &dyn MessageExtractor,
+    terminal_snapshot: &str,
+) -> Option<ExtractedMessage> {
     // 先检查是否在处理中
     if extractor.is_processing(terminal_snapshot) {
         debug!("Agent is processing, skipping extraction");
@@ -69,7 +74,7 @@ pub fn extract_message_from_snapshot(terminal_snapshot: &str) -> Option<(String,
                     iterations = iteration + 1,
                     "Message extracted successfully"
                 );
-                return Some((message.content, message.fingerprint, message.is_decision_required));
+                return Some(message);
             }
             ExtractionResult::NeedMoreContext => {
                 debug!(lines = lines, "Need more context, expanding");
@@ -86,7 +91,13 @@ pub fn extract_message_from_snapshot(terminal_snapshot: &str) -> Option<(String,
                     fingerprint = %fingerprint,
                     "Terminal error detected"
                 );
-                return Some((format!("ERROR: {}", error_msg), fingerprint, false));
+                return Some(ExtractedMessage {
+                    content: format!("ERROR: {}", error_msg),
+                    fingerprint,
+                    context_complete: true,
+                    message_type: MessageType::OpenEnded,
+                    is_decision_required: false,
+                });
             }
             ExtractionResult::Failed(reason) => {
                 warn!(reason = %reason, "Extraction failed");
@@ -110,10 +121,22 @@ pub struct HaikuExtractor {
 impl HaikuExtractor {
     /// 创建新的 Haiku 提取器
     pub fn new() -> Result<Self> {
-        let client = AnthropicClient::from_config()?;
+        let client = AnthropicClient::for_task(AiTask::Extraction)?;
         Ok(Self { client })
     }
 
+    /// 使用指定模型创建提取器（所有 provider 都改用该模型）
+    pub fn with_model(model: &str) -> Result<Self> {
+        let mut config = AnthropicConfig::for_task(AiTask::Extraction)?;
+        config.model = model.to_string();
+        for provider in &mut config.providers {
+            provider.model = model.to_string();
+        }
+        Ok(Self {
+            client: AnthropicClient::new(config)?,
+        })
+    }
+
     /// 从 JSON 响应中提取 JSON 字符串
     fn extract_json(output: &str) -> Option<String> {
         let start = output.find('{')?;
@@ -252,8 +275,12 @@ impl MessageExtractor for HaikuExtractor {
                 _ => MessageType::OpenEnded,
             };
 
-            let is_decision_required = parsed.get("is_decision")
-                .and_then(|v| v.as_bool().or_else(|| v.as_str().map(|s| s.eq_ignore_ascii_case("true"))))
+            let is_decision_required = parsed
+                .get("is_decision")
+                .and_then(|v| {
+                    v.as_bool()
+                        .or_else(|| v.as_str().map(|s| s.eq_ignore_ascii_case("true")))
+                })
                 .unwrap_or(false);
 
             ExtractionResult::Success(ExtractedMessage {
@@ -334,7 +361,7 @@ impl ReactExtractor {
     pub fn extract_message(
         &self,
         session_id: &str,
-        tmux: &TmuxManager,
+        tmux: &dyn Multiplexer,
     ) -> Result<Option<ExtractedMessage>> {
         // 获取最大行数的终端快照（一次性获取，避免多次 tmux 调用）
         let max_lines = *self.config.context_sizes.last().unwrap_or(&800);
@@ -716,9 +743,9 @@ mod tests {
 
     #[test]
     fn test_react_handles_error_result() {
-        let extractor = MockExtractor::new(vec![
-            ExtractionResult::Error("Error editing file: permission denied".into()),
-        ]);
+        let extractor = MockExtractor::new(vec![ExtractionResult::Error(
+            "Error editing file: permission denied".into(),
+        )]);
 
         let react = ReactExtractor::new(Box::new(extractor));
 
@@ -0,0 +1,225 @@
+//! Agent 状态历史 - 记录启动、处理中、等待、出错、退出的时间点
+//!
+//! 历史随 AgentRecord 保存在 agents.json，由 AgentManager 在状态变化时追加，
+//! `cam info` 据此统计运行时长、处理时长和等待人工的累计时长。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentStatus;
+
+/// 每个 agent 最多保留的历史条目数（保留最早的 Started）
+const MAX_HISTORY_ENTRIES: usize = 200;
+
+/// 历史中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentState {
+    /// 启动（随后视为处理中）
+    Started,
+    Processing,
+    /// 等待用户输入或决策
+    Waiting,
+    /// 出现错误（只记录时间点，不改变当前状态）
+    Errored,
+    Exited,
+}
+
+impl AgentState {
+    /// 从 AgentStatus 转换，Unknown 不记录
+    pub fn from_status(status: &AgentStatus) -> Option<Self> {
+        if status.is_processing() {
+            Some(Self::Processing)
+        } else if status.is_waiting() {
+            Some(Self::Waiting)
+        } else {
+            None
+        }
+    }
+
+    /// 中文标签
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Started => "启动",
+            Self::Processing => "处理中",
+            Self::Waiting => "等待输入",
+            Self::Errored => "出错",
+            Self::Exited => "退出",
+        }
+    }
+}
+
+/// 一次状态变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub state: AgentState,
+    pub at: DateTime<Utc>,
+}
+
+/// 追加一次状态变化，与当前状态相同时忽略，返回是否追加
+pub fn push_transition(
+    history: &mut Vec<StateTransition>,
+    state: AgentState,
+    at: DateTime<Utc>,
+) -> bool {
+    let current = history
+        .iter()
+        .rev()
+        .find(|t| t.state != AgentState::Errored)
+        .map(|t| t.state);
+    let unchanged = match (current, state) {
+        (_, AgentState::Errored) => false,
+        (Some(AgentState::Started), AgentState::Processing) => true,
+        (Some(current), state) => current == state,
+        (None, _) => false,
+    };
+    if unchanged {
+        return false;
+    }
+    history.push(StateTransition { state, at });
+    if history.len() > MAX_HISTORY_ENTRIES {
+        let keep_first = history[0].state == AgentState::Started;
+        let start = usize::from(keep_first);
+        history.drain(start..start + history.len() - MAX_HISTORY_ENTRIES);
+    }
+    true
+}
+
+/// 根据历史计算的统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HistoryStats {
+    /// 从第一条记录到退出（或现在）的时长（秒）
+    pub uptime_secs: u64,
+    pub processing_secs: u64,
+    /// 等待人工的累计时长（秒）
+    pub waiting_secs: u64,
+    /// 进入等待的次数
+    pub waiting_count: u32,
+    pub error_count: u32,
+}
+
+impl HistoryStats {
+    /// 统计到 `now`（已退出时统计到退出时间）
+    pub fn compute(history: &[StateTransition], now: DateTime<Utc>) -> Self {
+        let mut stats = Self::default();
+        let Some(first) = history.first() else {
+            return stats;
+        };
+
+        let mut current: Option<(AgentState, DateTime<Utc>)> = None;
+        let mut end = now;
+        for transition in history {
+            match transition.state {
+                AgentState::Errored => {
+                    stats.error_count += 1;
+                    continue;
+                }
+                AgentState::Waiting => stats.waiting_count += 1,
+                _ => {}
+            }
+            if let Some((state, since)) = current {
+                stats.add(state, since, transition.at);
+            }
+            if transition.state == AgentState::Exited {
+                end = transition.at;
+                current = None;
+                break;
+            }
+            current = Some((transition.state, transition.at));
+        }
+        if let Some((state, since)) = current {
+            stats.add(state, since, end);
+        }
+        stats.uptime_secs = secs_between(first.at, end);
+        stats
+    }
+
+    fn add(&mut self, state: AgentState, from: DateTime<Utc>, to: DateTime<Utc>) {
+        let secs = secs_between(from, to);
+        match state {
+            AgentState::Started | AgentState::Processing => self.processing_secs += secs,
+            AgentState::Waiting => self.waiting_secs += secs,
+            AgentState::Errored | AgentState::Exited => {}
+        }
+    }
+}
+
+fn secs_between(from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+    (to - from).num_seconds().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_push_transition_skips_unchanged() {
+        let t0 = Utc::now();
+        let mut history = Vec::new();
+        assert!(push_transition(&mut history, AgentState::Started, t0));
+        // 启动后本来就是处理中
+        assert!(!push_transition(&mut history, AgentState::Processing, t0));
+        assert!(push_transition(&mut history, AgentState::Waiting, t0));
+        assert!(!push_transition(&mut history, AgentState::Waiting, t0));
+        // 出错不改变当前状态
+        assert!(push_transition(&mut history, AgentState::Errored, t0));
+        assert!(!push_transition(&mut history, AgentState::Waiting, t0));
+        assert_eq!(history.len(), 3);
+
+        for i in 0..MAX_HISTORY_ENTRIES {
+            let state = if i % 2 == 0 {
+                AgentState::Processing
+            } else {
+                AgentState::Waiting
+            };
+            push_transition(&mut history, state, t0);
+        }
+        assert_eq!(history.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history[0].state, AgentState::Started);
+    }
+
+    #[test]
+    fn test_compute_stats() {
+        let t0 = Utc::now();
+        let at = |mins: i64| t0 + Duration::minutes(mins);
+        let history = vec![
+            StateTransition {
+                state: AgentState::Started,
+                at: at(0),
+            },
+            StateTransition {
+                state: AgentState::Waiting,
+                at: at(10),
+            },
+            StateTransition {
+                state: AgentState::Processing,
+                at: at(40),
+            },
+            StateTransition {
+                state: AgentState::Errored,
+                at: at(45),
+            },
+            StateTransition {
+                state: AgentState::Waiting,
+                at: at(50),
+            },
+            StateTransition {
+                state: AgentState::Exited,
+                at: at(66),
+            },
+        ];
+
+        let stats = HistoryStats::compute(&history, at(120));
+        assert_eq!(stats.uptime_secs, 66 * 60);
+        assert_eq!(stats.processing_secs, 20 * 60);
+        assert_eq!(stats.waiting_secs, 46 * 60);
+        assert_eq!(stats.waiting_count, 2);
+        assert_eq!(stats.error_count, 1);
+
+        // 未退出时统计到现在
+        let stats = HistoryStats::compute(&history[..2], at(30));
+        assert_eq!(stats.waiting_secs, 20 * 60);
+        assert_eq!(HistoryStats::compute(&[], at(0)), HistoryStats::default());
+    }
+}
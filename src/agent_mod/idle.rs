@@ -0,0 +1,198 @@
+//! 空闲检测 - agent 完成任务后长时间停在空提示符时建议关闭
+//!
+//! 与等待输入不同，空闲指屏幕末尾只有一个空提示符（`❯`、`>`），之前的输出中没有待回答的
+//! 问题、选项或权限确认。Watcher 在 agent 判定为等待后继续计时，屏幕超过阈值未变化时发送
+//! 一条 MEDIUM 建议："myapp 已空闲 2 小时，回复 /kill 停止，/keep 不再提醒"。
+//!
+//! 阈值由 config.json 的 `idle_after_mins` 设置（默认 120 分钟，0 表示关闭）。
+//! 建议和 `/keep` 状态保存在 `~/.config/code-agent-monitor/idle.json`，供聊天命令使用。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::notification::waiting_kind::{classify_waiting, WaitingKind};
+
+/// 默认空闲阈值（秒）
+pub const IDLE_THRESHOLD_SECS: u64 = 2 * 60 * 60;
+
+/// 提示符之后允许的最多行数（输入框边框、快捷键提示）
+const MAX_FOOTER_LINES: usize = 3;
+/// 分类时查看提示符之前的行数
+const CONTEXT_LINES: usize = 10;
+
+/// 读取空闲阈值（秒），None 表示关闭
+pub fn idle_threshold_secs() -> Option<u64> {
+    let configured = crate::infra::paths::config_section::<u64>("idle_after_mins");
+    match configured {
+        Some(0) => None,
+        Some(mins) => Some(mins * 60),
+        None => Some(IDLE_THRESHOLD_SECS),
+    }
+}
+
+/// 去掉输入框两侧的边框
+fn strip_frame(line: &str) -> &str {
+    line.trim().trim_matches(['│', '|']).trim()
+}
+
+fn is_bare_prompt(line: &str) -> bool {
+    matches!(strip_frame(line), "❯" | ">" | "$" | "%")
+}
+
+/// 只由框线字符组成的行
+fn is_decoration(line: &str) -> bool {
+    line.chars()
+        .all(|c| c.is_whitespace() || ('\u{2500}'..='\u{257F}').contains(&c))
+}
+
+/// 屏幕是否停在空提示符且没有待回答的问题
+pub fn is_idle_prompt(screen: &str) -> bool {
+    let lines: Vec<&str> = screen
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let Some(prompt) = lines.iter().rposition(|line| is_bare_prompt(line)) else {
+        return false;
+    };
+    if lines.len() - prompt - 1 > MAX_FOOTER_LINES {
+        return false;
+    }
+
+    let before: Vec<&str> = lines[..prompt]
+        .iter()
+        .copied()
+        .filter(|line| !is_decoration(line))
+        .collect();
+    if before
+        .last()
+        .is_some_and(|line| line.ends_with('?') || line.ends_with('？'))
+    {
+        return false;
+    }
+    let context = before[before.len().saturating_sub(CONTEXT_LINES)..].join("\n");
+    classify_waiting("ClaudePrompt", false, None, &context) == WaitingKind::InformationalPause
+}
+
+/// 空闲建议状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleState {
+    /// 已发送空闲建议的 agent 及发送时间
+    pub suggested: BTreeMap<String, DateTime<Utc>>,
+    /// 回复过 `/keep` 的 agent，不再建议关闭
+    pub kept: BTreeSet<String>,
+}
+
+impl IdleState {
+    /// 状态文件路径
+    pub fn path() -> PathBuf {
+        crate::infra::paths::state_dir().join("idle.json")
+    }
+
+    /// 读取状态，缺失或无效时为空
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 记录一次空闲建议
+    pub fn record_suggestion(&mut self, agent_id: &str, at: DateTime<Utc>) {
+        self.suggested.insert(agent_id.to_string(), at);
+    }
+
+    /// 最近一次建议的 agent（`/kill`、`/keep` 未指定目标时使用）
+    pub fn latest_suggestion(&self) -> Option<&str> {
+        self.suggested
+            .iter()
+            .max_by_key(|(_, at)| **at)
+            .map(|(agent_id, _)| agent_id.as_str())
+    }
+
+    /// 保留 agent，不再建议关闭
+    pub fn keep(&mut self, agent_id: &str) {
+        self.suggested.remove(agent_id);
+        self.kept.insert(agent_id.to_string());
+    }
+
+    pub fn is_kept(&self, agent_id: &str) -> bool {
+        self.kept.contains(agent_id)
+    }
+
+    /// 清除 agent 的记录（已停止）
+    pub fn forget(&mut self, agent_id: &str) {
+        self.suggested.remove(agent_id);
+        self.kept.remove(agent_id);
+    }
+
+    /// 只保留仍在运行的 agent
+    pub fn retain_agents<'a>(&mut self, agent_ids: impl IntoIterator<Item = &'a str>) {
+        let alive: BTreeSet<&str> = agent_ids.into_iter().collect();
+        self.suggested.retain(|id, _| alive.contains(id.as_str()));
+        self.kept.retain(|id| alive.contains(id.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_idle_prompt() {
+        let done = "⏺ All tests pass. The refactor is complete.\n\n╭────────────╮\n│ >          │\n╰────────────╯\n  ? for shortcuts";
+        assert!(is_idle_prompt(done));
+        assert!(is_idle_prompt("Done.\n\n❯ \n"));
+
+        // 有待回答的问题、选项或权限确认时不算空闲
+        assert!(!is_idle_prompt("Should I also update the docs?\n\n❯ "));
+        assert!(!is_idle_prompt("Which framework?\n1. React\n2. Vue\n\n❯ "));
+        assert!(!is_idle_prompt(
+            "Do you want to proceed?\n❯ 1. Yes\n  2. No"
+        ));
+        assert!(!is_idle_prompt("Please describe the expected behavior\n>"));
+        // 提示符不在末尾（仍在输出）
+        assert!(!is_idle_prompt("❯ \nbuilding\ncompiling\nlinking\ntesting"));
+    }
+
+    #[test]
+    fn test_idle_state() {
+        let now = Utc::now();
+        let mut state = IdleState::default();
+        state.record_suggestion("cam-1", now - Duration::minutes(5));
+        state.record_suggestion("cam-2", now);
+        assert_eq!(state.latest_suggestion(), Some("cam-2"));
+
+        state.keep("cam-2");
+        assert!(state.is_kept("cam-2"));
+        assert_eq!(state.latest_suggestion(), Some("cam-1"));
+
+        state.retain_agents(["cam-1"]);
+        assert!(!state.is_kept("cam-2"));
+        state.forget("cam-1");
+        assert_eq!(state, IdleState::default());
+    }
+}
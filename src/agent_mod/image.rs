@@ -0,0 +1,160 @@
+//! 图片转发 - 把截图等图片交给 agent（`cam send-image`、MCP `agent_send_image`）
+//!
+//! 远程用户在聊天渠道中发送的截图由 plugin 下载到本机后转发给 agent。
+//! 图片先复制到 agent 可以读取的位置（容器中运行时放在挂载的项目目录下），
+//! 再按 adapter 的引用方式（Claude Code 的 `@路径`、Codex 粘贴路径）以粘贴方式输入。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::agent::adapter::get_adapter;
+use crate::agent::AgentManager;
+
+/// 图片大小上限（字节）
+pub const MAX_IMAGE_BYTES: u64 = 10 * 1024 * 1024;
+/// 支持的图片扩展名
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+
+/// 已发送的图片
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SentImage {
+    /// agent 读取的图片副本
+    pub path: PathBuf,
+    /// 输入 agent 的文本
+    pub input: String,
+}
+
+/// 按扩展名判断是否为支持的图片
+pub fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// 检查图片格式和大小
+pub fn validate_image(image: &Path) -> Result<()> {
+    if !is_image_file(image) {
+        return Err(anyhow!(
+            "不支持的图片格式: {}（支持 {}）",
+            image.display(),
+            IMAGE_EXTENSIONS.join(", ")
+        ));
+    }
+    let metadata =
+        fs::metadata(image).map_err(|e| anyhow!("无法读取图片 {}: {}", image.display(), e))?;
+    if metadata.len() > MAX_IMAGE_BYTES {
+        return Err(anyhow!(
+            "图片 {} 过大（{} MB），上限 {} MB",
+            image.display(),
+            metadata.len() / 1024 / 1024,
+            MAX_IMAGE_BYTES / 1024 / 1024
+        ));
+    }
+    Ok(())
+}
+
+/// 把图片复制到 `dir`，文件名见 [`staged_path`]
+pub fn stage_image(image: &Path, dir: &Path) -> Result<PathBuf> {
+    let name = image
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("无效的图片路径: {}", image.display()))?;
+    fs::create_dir_all(dir)?;
+    let staged = staged_path(dir, name);
+    fs::copy(image, &staged)?;
+    Ok(staged)
+}
+
+/// `dir` 下的副本路径：文件名加时间前缀、空白替换为 `-`（引用路径中不能有空格）
+pub fn staged_path(dir: &Path, name: &str) -> PathBuf {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .collect();
+    dir.join(format!(
+        "{}-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+        name
+    ))
+}
+
+/// 图片引用加上说明文字
+pub fn compose_image_input(reference: &str, message: Option<&str>) -> String {
+    match message.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(message) => format!("{} {}", reference, message),
+        None => reference.to_string(),
+    }
+}
+
+/// 把图片交给 agent，`message` 作为随图片一起输入的说明
+pub fn send_image(
+    agent_manager: &AgentManager,
+    agent_id: &str,
+    image: &Path,
+    message: Option<&str>,
+) -> Result<SentImage> {
+    let agent = agent_manager
+        .get_agent(agent_id)?
+        .ok_or_else(|| anyhow!("Agent not found: {}", agent_id))?;
+    let adapter = get_adapter(&agent.agent_type);
+    validate_image(image)?;
+
+    // 容器只挂载项目目录
+    let dir = if agent.container.is_some() {
+        Path::new(&agent.project_path).join(".cam").join("images")
+    } else {
+        agent_manager
+            .data_dir()
+            .join("images")
+            .join(&agent.agent_id)
+    };
+    let staged = stage_image(image, &dir)?;
+    let Some(reference) = adapter.image_reference(&staged) else {
+        let _ = fs::remove_file(&staged);
+        return Err(anyhow!("{} 不支持图片输入", agent.agent_type));
+    };
+
+    let input = compose_image_input(&reference, message);
+    agent_manager.tmux.paste_text(&agent.tmux_session, &input)?;
+    Ok(SentImage {
+        path: staged,
+        input,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_stage_image() {
+        let temp = tempfile::tempdir().unwrap();
+        let screenshot = temp.path().join("Screen Shot.PNG");
+        fs::write(&screenshot, b"\x89PNG\r\n\x1a\n").unwrap();
+        validate_image(&screenshot).unwrap();
+
+        let notes = temp.path().join("notes.txt");
+        fs::write(&notes, "text").unwrap();
+        let err = validate_image(&notes).unwrap_err().to_string();
+        assert!(err.contains("不支持的图片格式"), "{}", err);
+        assert!(validate_image(&temp.path().join("missing.png")).is_err());
+
+        let staged = stage_image(&screenshot, &temp.path().join("images")).unwrap();
+        let name = staged.file_name().unwrap().to_str().unwrap();
+        assert!(name.ends_with("-Screen-Shot.PNG"), "{}", name);
+        assert_eq!(fs::read(&staged).unwrap(), b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn test_compose_image_input() {
+        assert_eq!(
+            compose_image_input("@/tmp/a.png", Some(" 按钮错位了 ")),
+            "@/tmp/a.png 按钮错位了"
+        );
+        assert_eq!(compose_image_input("/tmp/a.png", Some("")), "/tmp/a.png");
+        assert_eq!(compose_image_input("/tmp/a.png", None), "/tmp/a.png");
+    }
+}
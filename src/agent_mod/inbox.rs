@@ -0,0 +1,222 @@
+//! 收件箱 - 接收用户在聊天渠道中回复通知时附带的图片和文件（`cam send-file`、MCP `agent_receive_attachment`）
+//!
+//! plugin 收到回复通知的图片或文件后，把下载地址（或已下载的本机路径）交给 CAM。
+//! 附件保存到 agent 工作区的 `.cam/inbox/`（容器中运行时同样可以读取），
+//! 再输入一条消息告诉 agent 新文件的路径；图片按 adapter 的引用方式输入，agent 可以直接看到。
+//! agent 正在处理时消息加入指令队列，空闲后再输入。
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::agent::adapter::get_adapter;
+use crate::agent::image::{is_image_file, staged_path};
+use crate::agent::prompt_queue::PromptQueue;
+use crate::agent::AgentManager;
+
+/// 附件大小上限（字节），与 Telegram Bot API 的下载上限一致
+pub const MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+/// 下载超时
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 已收下的附件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReceivedAttachment {
+    /// 保存在工作区收件箱中的文件
+    pub path: PathBuf,
+    /// 输入 agent 的消息
+    pub input: String,
+    /// agent 正在处理时加入队列的 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_id: Option<String>,
+}
+
+/// agent 工作区的收件箱目录
+pub fn inbox_dir(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".cam").join("inbox")
+}
+
+/// 附件的文件名：优先用渠道给出的名称，否则取来源的最后一段（去掉查询参数），
+/// 只保留文件名部分，避免写到收件箱以外
+pub fn attachment_name(source: &str, name: Option<&str>) -> String {
+    let candidate = name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| {
+            let source = source.split(['?', '#']).next().unwrap_or(source);
+            source.rsplit(['/', '\\']).next().unwrap_or(source)
+        });
+    Path::new(candidate)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.starts_with('.'))
+        .map(str::to_string)
+        .unwrap_or_else(|| "attachment".to_string())
+}
+
+/// 告诉 agent 新文件路径的消息，`reference` 为图片引用（非图片时为 None）
+pub fn compose_attachment_input(
+    path: &Path,
+    reference: Option<&str>,
+    caption: Option<&str>,
+) -> String {
+    let input = match reference {
+        Some(reference) => format!("用户发来了图片 {}", reference),
+        None => format!("用户发来了文件，已保存到 {}", path.display()),
+    };
+    match caption.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(caption) => format!("{}，说明: {}", input, caption),
+        None => input,
+    }
+}
+
+/// 把 `source`（http(s) 下载地址或本机路径）保存为 `dest`，超过大小上限时失败
+pub fn fetch_attachment(source: &str, dest: &Path) -> Result<()> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        download(source, dest)
+    } else {
+        let metadata =
+            fs::metadata(source).map_err(|e| anyhow!("无法读取附件 {}: {}", source, e))?;
+        check_size(metadata.len())?;
+        fs::copy(source, dest)?;
+        Ok(())
+    }
+}
+
+/// 下载附件；错误信息中不包含地址（Telegram 的文件地址带有 bot token）
+fn download(url: &str, dest: &Path) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| anyhow!("下载附件失败: {}", e.without_url()))?;
+    if !response.status().is_success() {
+        return Err(anyhow!("下载附件失败: HTTP {}", response.status()));
+    }
+    if let Some(length) = response.content_length() {
+        check_size(length)?;
+    }
+
+    let mut file = File::create(dest)?;
+    let written = io::copy(&mut response.take(MAX_ATTACHMENT_BYTES + 1), &mut file);
+    let result = match written {
+        Ok(bytes) => check_size(bytes),
+        Err(e) => Err(anyhow!("下载附件失败: {}", e)),
+    };
+    if result.is_err() {
+        let _ = fs::remove_file(dest);
+    }
+    result
+}
+
+fn check_size(bytes: u64) -> Result<()> {
+    if bytes > MAX_ATTACHMENT_BYTES {
+        return Err(anyhow!(
+            "附件过大，上限 {} MB",
+            MAX_ATTACHMENT_BYTES / 1024 / 1024
+        ));
+    }
+    Ok(())
+}
+
+/// 收下附件并告诉 agent，`name` 为渠道给出的文件名，`caption` 为随附件发来的文字
+pub fn receive_attachment(
+    agent_manager: &AgentManager,
+    agent_id: &str,
+    source: &str,
+    name: Option<&str>,
+    caption: Option<&str>,
+) -> Result<ReceivedAttachment> {
+    let agent = agent_manager
+        .get_agent(agent_id)?
+        .ok_or_else(|| anyhow!("Agent not found: {}", agent_id))?;
+
+    let dir = inbox_dir(&agent.project_path);
+    fs::create_dir_all(&dir)?;
+    let path = staged_path(&dir, &attachment_name(source, name));
+    fetch_attachment(source, &path)?;
+
+    let reference = if is_image_file(&path) {
+        get_adapter(&agent.agent_type).image_reference(&path)
+    } else {
+        None
+    };
+    let input = compose_attachment_input(&path, reference.as_deref(), caption);
+
+    let queue = PromptQueue::new();
+    let queue_id = if agent.status.is_processing() || queue.has_queued(agent_id) {
+        Some(queue.enqueue(agent_id, &input)?.id)
+    } else {
+        // 粘贴输入：Codex 只把粘贴的图片路径转为附件
+        agent_manager.tmux.paste_text(&agent.tmux_session, &input)?;
+        None
+    };
+    Ok(ReceivedAttachment {
+        path,
+        input,
+        queue_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_name() {
+        assert_eq!(
+            attachment_name(
+                "https://api.telegram.org/file/bot123:abc/photos/file_12.jpg?x=1",
+                None
+            ),
+            "file_12.jpg"
+        );
+        assert_eq!(
+            attachment_name("/tmp/dl/a.bin", Some("crash log.txt")),
+            "crash log.txt"
+        );
+        assert_eq!(
+            attachment_name("/tmp/dl/a.bin", Some("../../etc/passwd")),
+            "passwd"
+        );
+        assert_eq!(
+            attachment_name("https://example.com/", Some("  ")),
+            "attachment"
+        );
+        assert_eq!(attachment_name("/tmp/dl/..", None), "attachment");
+    }
+
+    #[test]
+    fn test_compose_attachment_input() {
+        let path = Path::new("/repo/.cam/inbox/20261015-trace.log");
+        assert_eq!(
+            compose_attachment_input(path, None, Some(" 点保存时崩溃 ")),
+            "用户发来了文件，已保存到 /repo/.cam/inbox/20261015-trace.log，说明: 点保存时崩溃"
+        );
+        assert_eq!(
+            compose_attachment_input(path, Some("@/repo/.cam/inbox/a.png"), None),
+            "用户发来了图片 @/repo/.cam/inbox/a.png"
+        );
+    }
+
+    #[test]
+    fn test_fetch_attachment() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("downloaded.log");
+        fs::write(&source, "panic at main.rs:3").unwrap();
+
+        let dest = temp.path().join("inbox.log");
+        fetch_attachment(source.to_str().unwrap(), &dest).unwrap();
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "panic at main.rs:3");
+
+        assert!(fetch_attachment("/nonexistent/file.log", &dest).is_err());
+        assert!(check_size(MAX_ATTACHMENT_BYTES).is_ok());
+        assert!(check_size(MAX_ATTACHMENT_BYTES + 1).is_err());
+    }
+}
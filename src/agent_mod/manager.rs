@@ -2,14 +2,24 @@
 
 use crate::agent::adapter::get_adapter;
 use crate::agent::daemon::WatcherDaemon;
+use crate::agent::history::{push_transition, AgentState, StateTransition};
+use crate::agent::preflight::PreflightReport;
+use crate::agent::prompt_delivery;
+use crate::agent::todos::TodoItem;
+use crate::infra::docker;
+use crate::infra::event_bus::EventBus;
+use crate::infra::multiplexer::{self, Multiplexer};
+use crate::infra::process::{ProcessNode, ProcessScanner};
+use crate::infra::schema::AGENTS_SCHEMA;
 use crate::infra::tmux::TmuxManager;
 use anyhow::{anyhow, Result};
 use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, OpenOptions};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 
 /// 全局计数器，确保 agent_id 唯一性（即使在同一毫秒内）
@@ -129,6 +139,34 @@ pub struct AgentRecord {
     pub last_output_hash: Option<String>,
     pub started_at: String,
     pub status: AgentStatus,
+    /// 状态变化历史
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<StateTransition>,
+    /// 在 Docker 容器中运行时的容器信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<AgentContainer>,
+    /// 上下文窗口使用率（百分比，watcher 从状态栏解析）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_usage: Option<u8>,
+    /// Claude Code 当前的 TODO 列表（watcher 从 TodoWrite 工具调用解析）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub todos: Vec<TodoItem>,
+    /// agent 进程树正在监听的 TCP 端口（watcher 定期检测）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<u16>,
+    /// 启动就绪检查结果（`cam start --wait-ready`），未检查时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready: Option<bool>,
+}
+
+/// agent 所在的 Docker 容器
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentContainer {
+    pub image: String,
+    pub name: String,
+    /// 容器 ID（启动时未能查询到则为空，docker 命令可直接使用 name）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
 }
 
 /// 启动 Agent 请求
@@ -147,6 +185,9 @@ pub struct StartAgentRequest {
     /// 可选：指定 tmux session 名称，用于外部系统传入已存在的 session
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tmux_session: Option<String>,
+    /// 可选：在该 Docker 镜像的容器中运行 agent（挂载项目目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
 }
 
 /// 启动 Agent 响应
@@ -154,35 +195,52 @@ pub struct StartAgentRequest {
 pub struct StartAgentResponse {
     pub agent_id: String,
     pub tmux_session: String,
+    /// 初始 prompt 是否确认送达（没有初始 prompt 时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_delivered: Option<bool>,
 }
 
 /// agents.json 结构
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct AgentsFile {
     agents: Vec<AgentRecord>,
+    /// 异常退出（tmux session 消失）的 agent，由 watcher 取出后处理
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exited: Vec<AgentRecord>,
 }
 
+/// 最多保留的异常退出记录数
+const MAX_EXITED_RECORDS: usize = 20;
+
 /// Agent 管理器
 pub struct AgentManager {
-    pub tmux: TmuxManager,
+    pub tmux: Arc<dyn Multiplexer>,
     data_dir: PathBuf,
 }
 
 impl AgentManager {
     pub fn new() -> Self {
-        let data_dir = dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".config/code-agent-monitor");
+        let data_dir = crate::infra::paths::state_dir();
 
+        Self::with_data_dir(data_dir)
+    }
+
+    /// 使用指定的数据目录（agents.json 所在目录）创建 AgentManager
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
         // 确保目录存在
         let _ = fs::create_dir_all(&data_dir);
 
         Self {
-            tmux: TmuxManager::new(),
+            tmux: multiplexer::from_config(),
             data_dir,
         }
     }
 
+    /// 数据目录（agents.json 所在目录）
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
     /// 创建用于测试的 AgentManager（每次调用创建独立的数据目录）
     pub fn new_for_test() -> Self {
         let counter = AGENT_ID_COUNTER.fetch_add(1, Ordering::SeqCst);
@@ -191,7 +249,7 @@ impl AgentManager {
         let _ = fs::create_dir_all(&data_dir);
 
         Self {
-            tmux: TmuxManager::new(),
+            tmux: Arc::new(TmuxManager::new()),
             data_dir,
         }
     }
@@ -211,7 +269,7 @@ impl AgentManager {
         let path = self.agents_file_path();
         if path.exists() {
             let content = fs::read_to_string(&path)?;
-            Ok(serde_json::from_str(&content)?)
+            AGENTS_SCHEMA.load_versioned(&content)
         } else {
             Ok(AgentsFile::default())
         }
@@ -220,7 +278,7 @@ impl AgentManager {
     /// 写入 agents.json（内部使用，不加锁）
     fn write_agents_file_internal(&self, file: &AgentsFile) -> Result<()> {
         let path = self.agents_file_path();
-        let content = serde_json::to_string_pretty(file)?;
+        let content = serde_json::to_string_pretty(&AGENTS_SCHEMA.to_versioned_value(file)?)?;
         fs::write(path, content)?;
         Ok(())
     }
@@ -308,6 +366,17 @@ impl AgentManager {
     }
 
     /// 启动 Agent
+    /// 启动前检查（工作目录、终端复用器、agent CLI、磁盘空间）
+    pub fn preflight(&self, request: &StartAgentRequest) -> Result<PreflightReport> {
+        let agent_type: AgentType = request.agent_type.as_deref().unwrap_or("claude").parse()?;
+        Ok(PreflightReport::run(
+            &request.project_path,
+            &agent_type,
+            request.container.as_deref(),
+            self.tmux.as_ref(),
+        ))
+    }
+
     pub fn start_agent(&self, request: StartAgentRequest) -> Result<StartAgentResponse> {
         let agent_type: AgentType = request.agent_type.as_deref().unwrap_or("claude").parse()?;
 
@@ -339,6 +408,22 @@ impl AgentManager {
             adapter.get_command().to_string()
         };
 
+        // 在容器中运行时，session 里执行的是前台 docker run
+        let container_name = request
+            .container
+            .as_ref()
+            .map(|_| docker::container_name(&agent_id));
+        let command = match (&request.container, &container_name) {
+            (Some(image), Some(name)) => {
+                // 清理同名的残留容器（如重启前未退出的容器）
+                docker::remove_container(name)?;
+                docker::run_command(image, name, &request.project_path, &command)
+            }
+            _ => command,
+        };
+        // 隔离实例：agent 触发的 hooks 写入同一状态目录
+        let command = crate::infra::paths::with_child_env(&command);
+
         // 检查 tmux session 是否已存在
         let session_exists = self.tmux.session_exists(&tmux_session);
 
@@ -350,9 +435,22 @@ impl AgentManager {
             info!(tmux_session = %tmux_session, "Tmux session already exists, reusing");
         }
 
+        let container = request
+            .container
+            .clone()
+            .zip(container_name)
+            .map(|(image, name)| {
+                let id = docker::wait_for_container(&name, Duration::from_secs(10));
+                if id.is_none() {
+                    warn!(agent_id = %agent_id, container = %name, "Container not created within 10s");
+                }
+                AgentContainer { image, name, id }
+            });
+
         // 立即保存到 agents.json（先于 Claude Code hook 触发）
         // 这样 session_start hook 触发时能正确匹配到 agent
         let agent_type_str = agent_type.to_string(); // 保存用于日志
+        let record_type = agent_type.clone();
         let record = AgentRecord {
             agent_id: agent_id.clone(),
             agent_type,
@@ -364,6 +462,12 @@ impl AgentManager {
             last_output_hash: None,
             started_at: chrono::Utc::now().to_rfc3339(),
             status: AgentStatus::Processing,
+            history: started_history(),
+            container,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         self.with_locked_agents_file(|file| {
@@ -371,29 +475,16 @@ impl AgentManager {
             Ok(())
         })?;
 
-        // 如果有初始 prompt，等待 agent 就绪后发送
-        if let Some(prompt) = &request.initial_prompt {
-            // 使用 adapter 的 detect_ready 方法检测就绪状态
-            let max_attempts = 30; // 最多等待 30 秒
-            let mut ready = false;
-            for _ in 0..max_attempts {
-                std::thread::sleep(std::time::Duration::from_secs(1));
-                if let Ok(output) = self.tmux.capture_pane(&tmux_session, 30) {
-                    if adapter.detect_ready(&output) {
-                        ready = true;
-                        // 额外等待 1 秒确保完全就绪
-                        std::thread::sleep(std::time::Duration::from_secs(1));
-                        break;
-                    }
-                }
-            }
-            if ready {
-                self.tmux.send_keys(&tmux_session, prompt)?;
-                debug!(agent_id = %agent_id, "Initial prompt sent");
+        // 如果有初始 prompt，等待 agent 就绪后发送并确认送达
+        let prompt_delivered = request.initial_prompt.as_ref().map(|prompt| {
+            let delivered = self.deliver_initial_prompt(&tmux_session, &record_type, prompt);
+            if delivered {
+                debug!(agent_id = %agent_id, "Initial prompt delivered");
             } else {
-                warn!(agent_id = %agent_id, agent_type = %agent_type_str, "Agent not ready within 30s, initial prompt not sent");
+                warn!(agent_id = %agent_id, agent_type = %agent_type_str, "Initial prompt not accepted after retries");
             }
-        }
+            delivered
+        });
 
         // 确保 watcher daemon 在运行
         let daemon = WatcherDaemon::new();
@@ -404,10 +495,87 @@ impl AgentManager {
         }
 
         info!(agent_id = %agent_id, tmux_session = %tmux_session, "Agent started successfully");
+        self.event_bus().publish(
+            "state",
+            Some(&agent_id),
+            serde_json::json!({"to": "started", "agent_type": agent_type_str}),
+        );
 
         Ok(StartAgentResponse {
             agent_id,
             tmux_session,
+            prompt_delivered,
+        })
+    }
+
+    /// 等待就绪后输入初始 prompt，用快照确认被接受，未接受时重试（见 [`prompt_delivery`]）
+    fn deliver_initial_prompt(
+        &self,
+        tmux_session: &str,
+        agent_type: &AgentType,
+        prompt: &str,
+    ) -> bool {
+        let adapter = get_adapter(agent_type);
+        for attempt in 1..=prompt_delivery::MAX_ATTEMPTS {
+            // 首次等待 agent 启动，之后只等提示符恢复
+            let timeout = Duration::from_secs(if attempt == 1 { 30 } else { 10 });
+            if !self.wait_until_ready(tmux_session, agent_type, timeout) {
+                warn!(tmux_session = %tmux_session, attempt, "Agent not ready, initial prompt not sent");
+                continue;
+            }
+            // 额外等待 1 秒确保完全就绪
+            std::thread::sleep(Duration::from_secs(1));
+            let before = self.tmux.capture_pane(tmux_session, 30).unwrap_or_default();
+            if let Err(e) = self.tmux.send_text(tmux_session, prompt) {
+                warn!(tmux_session = %tmux_session, attempt, error = %e, "Failed to send initial prompt");
+                continue;
+            }
+            std::thread::sleep(prompt_delivery::CONFIRM_DELAY);
+            let after = self.tmux.capture_pane(tmux_session, 30).unwrap_or_default();
+            if prompt_delivery::prompt_accepted(
+                &before,
+                &after,
+                prompt,
+                adapter.detect_ready(&after),
+            ) {
+                return true;
+            }
+            warn!(tmux_session = %tmux_session, attempt, "Initial prompt not accepted, retrying");
+        }
+        false
+    }
+
+    /// 轮询终端输出直到 adapter 判定就绪，超时返回 false
+    pub fn wait_until_ready(
+        &self,
+        tmux_session: &str,
+        agent_type: &AgentType,
+        timeout: Duration,
+    ) -> bool {
+        let adapter = get_adapter(agent_type);
+        let deadline = std::time::Instant::now() + timeout;
+        while std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_secs(1));
+            if self
+                .tmux
+                .capture_pane(tmux_session, 30)
+                .is_ok_and(|output| adapter.detect_ready(&output))
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 记录启动就绪检查结果
+    pub fn mark_ready(&self, agent_id: &str, ready: bool) -> Result<bool> {
+        self.with_locked_agents_file(|agents_file| {
+            Ok(agents_file
+                .agents
+                .iter_mut()
+                .find(|a| a.agent_id == agent_id)
+                .map(|agent| agent.ready = Some(ready))
+                .is_some())
         })
     }
 
@@ -436,6 +604,12 @@ impl AgentManager {
             last_output_hash: None,
             started_at: chrono::Utc::now().to_rfc3339(),
             status: AgentStatus::Processing,
+            history: started_history(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         self.with_locked_agents_file(|file| {
@@ -446,6 +620,7 @@ impl AgentManager {
         Ok(StartAgentResponse {
             agent_id,
             tmux_session,
+            prompt_delivered: None,
         })
     }
 
@@ -454,23 +629,34 @@ impl AgentManager {
         info!(agent_id = %agent_id, "Stopping agent");
 
         // 在锁保护下查找 agent 并获取 tmux_session
-        let tmux_session = self.with_locked_agents_file(|file| {
+        let (tmux_session, container) = self.with_locked_agents_file(|file| {
             let agent = file
                 .agents
                 .iter()
                 .find(|a| a.agent_id == agent_id)
                 .ok_or_else(|| anyhow!("Agent not found: {}", agent_id))?;
             let session = agent.tmux_session.clone();
+            let container = agent.container.clone();
 
             // 从记录中删除
             file.agents.retain(|a| a.agent_id != agent_id);
-            Ok(session)
+            Ok((session, container))
         })?;
 
         // 终止 tmux session（在锁外执行，避免长时间持有锁）
         let _ = self.tmux.kill_session(&tmux_session);
+        if let Some(container) = container {
+            if let Err(e) = docker::remove_container(&container.name) {
+                warn!(agent_id = %agent_id, error = %e, "Failed to remove container");
+            }
+        }
 
         info!(agent_id = %agent_id, "Agent stopped successfully");
+        self.event_bus().publish(
+            "state",
+            Some(agent_id),
+            serde_json::json!({"to": "stopped"}),
+        );
 
         Ok(())
     }
@@ -485,7 +671,7 @@ impl AgentManager {
             .find(|a| a.agent_id == agent_id)
             .ok_or_else(|| anyhow!("Agent not found: {}", agent_id))?;
 
-        self.tmux.send_keys(&agent.tmux_session, input)?;
+        self.tmux.send_text(&agent.tmux_session, input)?;
 
         Ok(())
     }
@@ -505,19 +691,69 @@ impl AgentManager {
 
     /// 列出所有 Agent（过滤已死亡的）
     pub fn list_agents(&self) -> Result<Vec<AgentRecord>> {
-        self.with_locked_agents_file(|file| {
+        let (live_agents, stale_containers) = self.with_locked_agents_file(|file| {
             // 过滤已死亡的 session
-            let live_agents: Vec<AgentRecord> = file
+            let (live_agents, exited): (Vec<AgentRecord>, Vec<AgentRecord>) = file
                 .agents
+                .drain(..)
+                .partition(|a| self.tmux.session_exists(&a.tmux_session));
+            let stale_containers: Vec<String> = exited
                 .iter()
-                .filter(|a| self.tmux.session_exists(&a.tmux_session))
-                .cloned()
+                .filter_map(|a| a.container.as_ref().map(|c| c.name.clone()))
                 .collect();
 
-            // 更新文件（只保留存活的）
+            // 更新文件（只保留存活的，退出的留给 watcher 处理）
             file.agents = live_agents.clone();
-            Ok(live_agents)
-        })
+            let now = chrono::Utc::now();
+            file.exited.extend(exited.into_iter().map(|mut agent| {
+                push_transition(&mut agent.history, AgentState::Exited, now);
+                agent
+            }));
+            let overflow = file.exited.len().saturating_sub(MAX_EXITED_RECORDS);
+            file.exited.drain(..overflow);
+            Ok((live_agents, stale_containers))
+        })?;
+
+        // session 已消失但容器可能仍在运行（在锁外执行）
+        for name in stale_containers {
+            if let Err(e) = docker::remove_container(&name) {
+                warn!(container = %name, error = %e, "Failed to remove container");
+            }
+        }
+        Ok(live_agents)
+    }
+
+    /// 取出 `list_agents` 发现的异常退出 agent（`stop_agent` 主动停止的不在其中）
+    pub fn take_exited_agents(&self) -> Result<Vec<AgentRecord>> {
+        self.with_locked_agents_file(|file| Ok(std::mem::take(&mut file.exited)))
+    }
+
+    /// 用原 agent_id 和 tmux session 重启已退出的 agent，有 session_id 时恢复会话
+    ///
+    /// 状态历史沿用退出前的记录。
+    pub fn restart_agent(&self, record: &AgentRecord) -> Result<StartAgentResponse> {
+        let response = self.start_agent(StartAgentRequest {
+            project_path: record.project_path.clone(),
+            agent_type: Some(record.agent_type.to_string()),
+            resume_session: record.session_id.clone(),
+            initial_prompt: None,
+            agent_id: Some(record.agent_id.clone()),
+            tmux_session: Some(record.tmux_session.clone()),
+            container: record.container.as_ref().map(|c| c.image.clone()),
+        })?;
+        self.with_locked_agents_file(|file| {
+            if let Some(agent) = file
+                .agents
+                .iter_mut()
+                .find(|a| a.agent_id == record.agent_id)
+            {
+                let restarted = std::mem::take(&mut agent.history);
+                agent.history = record.history.clone();
+                agent.history.extend(restarted);
+            }
+            Ok(())
+        })?;
+        Ok(response)
     }
 
     /// 获取单个 Agent
@@ -526,6 +762,16 @@ impl AgentManager {
         Ok(agents.into_iter().find(|a| a.agent_id == agent_id))
     }
 
+    /// agent 的进程树：session 顶层进程及其启动的所有子进程（开发服务器、测试 watcher 等）
+    ///
+    /// session 已不存在或复用器后端无法查询 PID 时为空。
+    pub fn process_tree(&self, agent: &AgentRecord, scanner: &ProcessScanner) -> Vec<ProcessNode> {
+        self.tmux
+            .session_pid(&agent.tmux_session)
+            .map(|pid| scanner.process_tree(pid))
+            .unwrap_or_default()
+    }
+
     /// 通过 session_id 查找 Agent
     pub fn find_agent_by_session_id(&self, session_id: &str) -> Result<Option<AgentRecord>> {
         let agents = self.list_agents()?;
@@ -599,6 +845,12 @@ impl AgentManager {
             last_output_hash: None,
             started_at: chrono::Utc::now().to_rfc3339(),
             status: AgentStatus::Processing,
+            history: started_history(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         self.with_locked_agents_file(|file| {
@@ -625,19 +877,92 @@ impl AgentManager {
 
     /// 更新 agent 状态
     pub fn update_agent_status(&self, agent_id: &str, status: AgentStatus) -> Result<bool> {
-        self.with_locked_agents_file(|agents_file| {
+        let previous = self.with_locked_agents_file(|agents_file| {
             if let Some(agent) = agents_file.agents.iter_mut().find(|a| a.agent_id == agent_id) {
                 if agent.status != status {
                     debug!(agent_id = %agent_id, old_status = ?agent.status, new_status = ?status, "Updating agent status");
-                    agent.status = status;
-                    return Ok(true);
+                    if let Some(state) = AgentState::from_status(&status) {
+                        push_transition(&mut agent.history, state, chrono::Utc::now());
+                    }
+                    return Ok(Some(std::mem::replace(&mut agent.status, status.clone())));
                 }
             }
-            Ok(false)
+            Ok(None)
+        })?;
+        let Some(previous) = previous else {
+            return Ok(false);
+        };
+        self.event_bus().publish(
+            "state",
+            Some(agent_id),
+            serde_json::json!({"from": previous, "to": status}),
+        );
+        Ok(true)
+    }
+
+    /// 共享事件总线（与 agents.json 同目录）
+    fn event_bus(&self) -> EventBus {
+        EventBus::in_dir(&self.data_dir)
+    }
+
+    /// 更新上下文窗口使用率
+    pub fn update_context_usage(&self, agent_id: &str, usage: Option<u8>) -> Result<bool> {
+        self.with_locked_agents_file(|agents_file| {
+            Ok(agents_file
+                .agents
+                .iter_mut()
+                .find(|a| a.agent_id == agent_id && a.context_usage != usage)
+                .map(|agent| agent.context_usage = usage)
+                .is_some())
+        })
+    }
+
+    /// 更新 TODO 列表
+    pub fn update_todos(&self, agent_id: &str, todos: Vec<TodoItem>) -> Result<bool> {
+        self.with_locked_agents_file(|agents_file| {
+            Ok(agents_file
+                .agents
+                .iter_mut()
+                .find(|a| a.agent_id == agent_id && a.todos != todos)
+                .map(|agent| agent.todos = todos)
+                .is_some())
+        })
+    }
+
+    /// 更新监听端口
+    pub fn update_ports(&self, agent_id: &str, ports: Vec<u16>) -> Result<bool> {
+        self.with_locked_agents_file(|agents_file| {
+            Ok(agents_file
+                .agents
+                .iter_mut()
+                .find(|a| a.agent_id == agent_id && a.ports != ports)
+                .map(|agent| agent.ports = ports)
+                .is_some())
+        })
+    }
+
+    /// 在状态历史中记录一个时间点（如出错）
+    pub fn record_agent_state(&self, agent_id: &str, state: AgentState) -> Result<bool> {
+        self.with_locked_agents_file(|agents_file| {
+            Ok(agents_file
+                .agents
+                .iter_mut()
+                .find(|a| a.agent_id == agent_id)
+                .is_some_and(|agent| {
+                    push_transition(&mut agent.history, state, chrono::Utc::now())
+                }))
         })
     }
 }
 
+/// 新记录的初始历史
+fn started_history() -> Vec<StateTransition> {
+    vec![StateTransition {
+        state: AgentState::Started,
+        at: chrono::Utc::now(),
+    }]
+}
+
 /// 规范化路径，解析符号链接
 /// 如果解析失败（路径不存在等），进行基本的路径规范化
 fn canonicalize_path(path: &str) -> String {
@@ -682,6 +1007,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_legacy_agents_file_round_trip() {
+        let manager = AgentManager::new_for_test();
+        let legacy = r#"{"agents":[{"agent_id":"cam-legacy","agent_type":"claude","project_path":"/tmp","tmux_session":"cam-legacy","started_at":"2026-01-01T00:00:00Z","status":"processing"}]}"#;
+        fs::write(manager.agents_file_path(), legacy).unwrap();
+
+        let file = manager.read_agents_file_internal().unwrap();
+        assert_eq!(file.agents.len(), 1);
+        assert_eq!(file.agents[0].agent_id, "cam-legacy");
+
+        manager.write_agents_file_internal(&file).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(manager.agents_file_path()).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], AGENTS_SCHEMA.version);
+
+        let reloaded = manager.read_agents_file_internal().unwrap();
+        assert_eq!(reloaded.agents[0].agent_id, "cam-legacy");
+        assert_eq!(reloaded.agents[0].status, AgentStatus::Processing);
+        assert!(reloaded.agents[0].container.is_none());
+    }
+
+    #[test]
+    fn test_container_record_round_trip() {
+        let json = r#"{"agent_id":"cam-box","agent_type":"claude","project_path":"/tmp","tmux_session":"cam-box","started_at":"2026-01-01T00:00:00Z","status":"processing","container":{"image":"node:20","name":"cam-box","id":"abc123"}}"#;
+        let record: AgentRecord = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            record.container,
+            Some(AgentContainer {
+                image: "node:20".to_string(),
+                name: "cam-box".to_string(),
+                id: Some("abc123".to_string()),
+            })
+        );
+        let saved = serde_json::to_value(&record).unwrap();
+        assert_eq!(saved["container"]["id"], "abc123");
+    }
+
+    #[test]
+    fn test_take_exited_agents() {
+        let manager = AgentManager::new_for_test();
+        let dead = r#"{"agents":[{"agent_id":"cam-dead","agent_type":"claude","project_path":"/tmp","tmux_session":"cam-test-dead-session","session_id":"sess-1","started_at":"2026-01-01T00:00:00Z","status":"processing"}]}"#;
+        fs::write(manager.agents_file_path(), dead).unwrap();
+
+        // 死亡的 session 从列表中移除，但保留在退出列表里
+        assert!(manager.list_agents().unwrap().is_empty());
+        let exited = manager.take_exited_agents().unwrap();
+        assert_eq!(exited.len(), 1);
+        assert_eq!(exited[0].session_id.as_deref(), Some("sess-1"));
+        assert!(manager.take_exited_agents().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_status_changes_recorded_in_history() {
+        let manager = AgentManager::new_for_test();
+        let record = r#"{"agents":[{"agent_id":"cam-history","agent_type":"claude","project_path":"/tmp","tmux_session":"cam-history","started_at":"2026-01-01T00:00:00Z","status":"processing"}]}"#;
+        fs::write(manager.agents_file_path(), record).unwrap();
+
+        assert!(manager
+            .update_agent_status("cam-history", AgentStatus::WaitingForInput)
+            .unwrap());
+        assert!(manager
+            .record_agent_state("cam-history", AgentState::Errored)
+            .unwrap());
+        // Unknown 不进入历史
+        manager
+            .update_agent_status("cam-history", AgentStatus::Unknown)
+            .unwrap();
+
+        let file = manager.read_agents_file_internal().unwrap();
+        let states: Vec<AgentState> = file.agents[0].history.iter().map(|t| t.state).collect();
+        assert_eq!(states, vec![AgentState::Waiting, AgentState::Errored]);
+    }
+
     #[test]
     fn test_start_agent_creates_tmux_session() {
         // Given: AgentManager
@@ -696,6 +1094,7 @@ mod tests {
             initial_prompt: None,
             agent_id: None,
             tmux_session: None,
+            container: None,
         });
 
         // Then: 返回 agent_id，tmux session 存在
@@ -723,6 +1122,7 @@ mod tests {
                 initial_prompt: None,
                 agent_id: None,
                 tmux_session: None,
+                container: None,
             })
             .unwrap();
 
@@ -748,6 +1148,7 @@ mod tests {
                 initial_prompt: None,
                 agent_id: None,
                 tmux_session: None,
+                container: None,
             })
             .unwrap();
 
@@ -801,6 +1202,7 @@ mod tests {
                 initial_prompt: None,
                 agent_id: None,
                 tmux_session: None,
+                container: None,
             })
             .unwrap();
 
@@ -1,24 +1,46 @@
 //! Agent 生命周期管理 - 启动、监控、停止
 
 pub mod adapter;
+pub mod context;
 pub mod daemon;
+pub mod engine;
 pub mod event_processor;
 pub mod extractor;
+pub mod history;
+pub mod idle;
+pub mod image;
+pub mod inbox;
 pub mod manager;
 pub mod monitor;
+pub mod pause;
+pub mod pipeline;
+pub mod preflight;
+pub mod prompt_delivery;
+pub mod prompt_queue;
+pub mod recording;
+pub mod recovery;
+pub mod rules;
+pub mod simulator;
 pub mod stability;
+pub mod stuck;
+pub mod todos;
+pub mod tool_batch;
+pub mod tool_timeline;
 pub mod watcher;
 
 pub use daemon::WatcherDaemon;
+pub use engine::{EventKind, WatchEngine, WatchFilter};
 pub use event_processor::EventProcessor;
 pub use extractor::{
-    extract_message_from_snapshot, ExtractedMessage, ExtractionResult, HaikuExtractor,
-    IterationConfig, MessageType, ReactExtractor,
+    extract_message_from_snapshot, ExtractedMessage, ExtractionResult, FallbackLadder,
+    HaikuExtractor, IterationConfig, LadderStage, MessageType, ReactExtractor,
 };
 pub use manager::{
-    AgentManager, AgentRecord, AgentStatus, AgentType, StartAgentRequest, StartAgentResponse,
+    AgentContainer, AgentManager, AgentRecord, AgentStatus, AgentType, StartAgentRequest,
+    StartAgentResponse,
 };
 pub use monitor::AgentMonitor;
+pub use preflight::{CheckLevel, PreflightIssue, PreflightReport};
 pub use stability::{StabilityDetector, StabilityState};
 pub use watcher::{format_watch_event, AgentSnapshot, AgentWatcher, WatchEvent};
 
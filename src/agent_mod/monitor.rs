@@ -1,18 +1,19 @@
 //! Agent lifecycle monitoring - tmux session health checks
 
 use crate::agent::manager::AgentRecord;
-use crate::infra::tmux::TmuxManager;
+use crate::infra::multiplexer::{self, Multiplexer};
 use anyhow::Result;
+use std::sync::Arc;
 
 /// Monitors agent tmux sessions for health
 pub struct AgentMonitor {
-    tmux: TmuxManager,
+    tmux: Arc<dyn Multiplexer>,
 }
 
 impl AgentMonitor {
     pub fn new() -> Self {
         Self {
-            tmux: TmuxManager::new(),
+            tmux: multiplexer::from_config(),
         }
     }
 
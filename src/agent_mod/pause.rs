@@ -0,0 +1,326 @@
+//! 全局暂停 - 会议前或 API 服务故障时中断所有 agent，之后统一继续
+//!
+//! `cam pause --all` 向每个运行中的 agent 发送适配器的安全中断按键（Claude Code、Codex、
+//! OpenCode 默认 Esc；通用适配器没有安全的中断方式，需要配置），并记录暂停前是否正在处理。
+//! `cam resume-all` 只向暂停前正在处理的 agent 发送继续输入（默认 "continue"），
+//! 其余只清除标记。暂停期间 watcher 不检测这些 agent 的等待状态，避免中断提示触发通知。
+//!
+//! 按键可在 config.json 的 `pause_keys` 中按 agent 类型覆盖：
+//! `{"pause_keys": {"claude": {"pause": ["Escape"], "resume": "continue"}}}`。
+//! 暂停状态保存在 `~/.config/code-agent-monitor/paused.json`。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::agent::adapter::{get_adapter, KeySequence};
+use crate::agent::{AgentManager, AgentRecord, AgentType};
+
+/// 暂停中的 agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PausedAgent {
+    pub paused_at: DateTime<Utc>,
+    /// 暂停前正在处理（恢复时需要发送继续输入）
+    pub was_processing: bool,
+}
+
+/// 暂停状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PauseState {
+    pub agents: BTreeMap<String, PausedAgent>,
+}
+
+impl PauseState {
+    /// 状态文件路径
+    pub fn path() -> PathBuf {
+        crate::infra::paths::state_dir().join("paused.json")
+    }
+
+    /// 读取状态，缺失或无效时为空
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn is_paused(&self, agent_id: &str) -> bool {
+        self.agents.contains_key(agent_id)
+    }
+
+    /// 只保留仍在运行的 agent
+    pub fn retain_agents<'a>(&mut self, agent_ids: impl IntoIterator<Item = &'a str>) {
+        let alive: BTreeSet<&str> = agent_ids.into_iter().collect();
+        self.agents.retain(|id, _| alive.contains(id.as_str()));
+    }
+}
+
+/// 单个 agent 类型的按键覆盖
+#[derive(Debug, Clone, Default, Deserialize)]
+struct PauseKeysOverride {
+    /// tmux 按键名（如 `Escape`、`C-c`）
+    pause: Option<Vec<String>>,
+    /// 继续时输入的文本（自动回车）
+    resume: Option<String>,
+}
+
+/// 各 agent 类型的暂停/继续按键（适配器默认值 + config.json 覆盖）
+#[derive(Debug, Clone, Default)]
+pub struct PauseKeys {
+    overrides: HashMap<String, PauseKeysOverride>,
+}
+
+impl PauseKeys {
+    /// 读取 config.json 的 `pause_keys`，缺失或无效时使用适配器默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("pause_keys")
+            .map(Self::from_value)
+            .unwrap_or_default()
+    }
+
+    pub fn from_value(value: serde_json::Value) -> Self {
+        Self {
+            overrides: serde_json::from_value(value).unwrap_or_default(),
+        }
+    }
+
+    fn override_for(&self, agent_type: &AgentType) -> Option<&PauseKeysOverride> {
+        self.overrides.get(&agent_type.to_string())
+    }
+
+    /// 中断按键，None 表示该类型没有安全的中断方式
+    pub fn pause_for(&self, agent_type: &AgentType) -> Option<KeySequence> {
+        match self.override_for(agent_type).and_then(|o| o.pause.clone()) {
+            Some(keys) => Some(KeySequence::Keys(keys)),
+            None => get_adapter(agent_type).pause_keys(),
+        }
+    }
+
+    /// 继续输入
+    pub fn resume_for(&self, agent_type: &AgentType) -> Option<KeySequence> {
+        match self.override_for(agent_type).and_then(|o| o.resume.clone()) {
+            Some(text) => Some(KeySequence::Text(text)),
+            None => get_adapter(agent_type).resume_keys(),
+        }
+    }
+}
+
+/// 单个 agent 的暂停/继续结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum PauseOutcome {
+    /// 已发送中断并标记暂停
+    Paused,
+    /// 之前已暂停
+    AlreadyPaused,
+    /// 没有安全的中断方式，未处理
+    Unsupported,
+    /// 已发送继续输入
+    Resumed,
+    /// 暂停前未在处理，只清除标记
+    Unmarked,
+    /// 发送按键失败
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PauseResult {
+    pub agent_id: String,
+    #[serde(flatten)]
+    pub outcome: PauseOutcome,
+}
+
+pub(crate) fn send_sequence(
+    manager: &AgentManager,
+    session: &str,
+    sequence: &KeySequence,
+) -> Result<()> {
+    match sequence {
+        KeySequence::Text(text) => manager.tmux.send_keys(session, text),
+        KeySequence::Raw(text) => manager.tmux.send_keys_raw(session, text),
+        KeySequence::Keys(keys) => {
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            manager.tmux.send_named_keys(session, &keys)
+        }
+    }
+}
+
+fn pause_one(
+    manager: &AgentManager,
+    keys: &PauseKeys,
+    state: &mut PauseState,
+    agent: &AgentRecord,
+) -> PauseOutcome {
+    if state.is_paused(&agent.agent_id) {
+        return PauseOutcome::AlreadyPaused;
+    }
+    let Some(sequence) = keys.pause_for(&agent.agent_type) else {
+        return PauseOutcome::Unsupported;
+    };
+    // 只有正在处理的 agent 需要中断，等待输入的 agent 只做标记
+    let was_processing = agent.status.is_processing();
+    if was_processing {
+        if let Err(e) = send_sequence(manager, &agent.tmux_session, &sequence) {
+            warn!(agent_id = %agent.agent_id, error = %e, "Failed to send pause keys");
+            return PauseOutcome::Failed {
+                error: e.to_string(),
+            };
+        }
+    }
+    state.agents.insert(
+        agent.agent_id.clone(),
+        PausedAgent {
+            paused_at: Utc::now(),
+            was_processing,
+        },
+    );
+    info!(agent_id = %agent.agent_id, was_processing, "Agent paused");
+    PauseOutcome::Paused
+}
+
+/// 暂停 agent（`target` 为 None 时暂停全部），结果写入 `state_path`
+pub fn pause_agents(
+    manager: &AgentManager,
+    keys: &PauseKeys,
+    state_path: &Path,
+    target: Option<&str>,
+) -> Result<Vec<PauseResult>> {
+    let agents = manager.list_agents()?;
+    let mut state = PauseState::load_from(state_path);
+    state.retain_agents(agents.iter().map(|a| a.agent_id.as_str()));
+
+    let results = agents
+        .iter()
+        .filter(|agent| target.is_none_or(|id| agent.agent_id == id))
+        .map(|agent| PauseResult {
+            agent_id: agent.agent_id.clone(),
+            outcome: pause_one(manager, keys, &mut state, agent),
+        })
+        .collect();
+    state.save_to(state_path)?;
+    Ok(results)
+}
+
+/// 继续所有暂停的 agent
+pub fn resume_agents(
+    manager: &AgentManager,
+    keys: &PauseKeys,
+    state_path: &Path,
+) -> Result<Vec<PauseResult>> {
+    let agents = manager.list_agents()?;
+    let mut state = PauseState::load_from(state_path);
+    state.retain_agents(agents.iter().map(|a| a.agent_id.as_str()));
+
+    let mut results = Vec::new();
+    for agent in &agents {
+        let Some(paused) = state.agents.get(&agent.agent_id) else {
+            continue;
+        };
+        let sequence = keys.resume_for(&agent.agent_type);
+        let outcome = match sequence.filter(|_| paused.was_processing) {
+            Some(sequence) => match send_sequence(manager, &agent.tmux_session, &sequence) {
+                Ok(()) => PauseOutcome::Resumed,
+                Err(e) => {
+                    warn!(agent_id = %agent.agent_id, error = %e, "Failed to send resume keys");
+                    PauseOutcome::Failed {
+                        error: e.to_string(),
+                    }
+                }
+            },
+            None => PauseOutcome::Unmarked,
+        };
+        // 发送失败时保留标记，可以重试
+        if !matches!(outcome, PauseOutcome::Failed { .. }) {
+            state.agents.remove(&agent.agent_id);
+        }
+        results.push(PauseResult {
+            agent_id: agent.agent_id.clone(),
+            outcome,
+        });
+    }
+    state.save_to(state_path)?;
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_keys_defaults_and_overrides() {
+        let keys = PauseKeys::default();
+        assert_eq!(
+            keys.pause_for(&AgentType::Claude),
+            Some(KeySequence::Keys(vec!["Escape".to_string()]))
+        );
+        assert_eq!(
+            keys.resume_for(&AgentType::Codex),
+            Some(KeySequence::Text("continue".to_string()))
+        );
+        // 通用适配器没有安全的中断方式
+        assert_eq!(keys.pause_for(&AgentType::GeminiCli), None);
+
+        let keys = PauseKeys::from_value(serde_json::json!({
+            "gemini-cli": {"pause": ["C-c"], "resume": "please continue"},
+            "claude": {"resume": "go on"}
+        }));
+        assert_eq!(
+            keys.pause_for(&AgentType::GeminiCli),
+            Some(KeySequence::Keys(vec!["C-c".to_string()]))
+        );
+        assert_eq!(
+            keys.pause_for(&AgentType::Claude),
+            Some(KeySequence::Keys(vec!["Escape".to_string()]))
+        );
+        assert_eq!(
+            keys.resume_for(&AgentType::Claude),
+            Some(KeySequence::Text("go on".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_pause_state_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("paused.json");
+        let mut state = PauseState::default();
+        state.agents.insert(
+            "cam-1".to_string(),
+            PausedAgent {
+                paused_at: Utc::now(),
+                was_processing: true,
+            },
+        );
+        state.agents.insert(
+            "cam-2".to_string(),
+            PausedAgent {
+                paused_at: Utc::now(),
+                was_processing: false,
+            },
+        );
+        state.save_to(&path).unwrap();
+
+        let mut loaded = PauseState::load_from(&path);
+        assert_eq!(loaded, state);
+        loaded.retain_agents(["cam-2"]);
+        assert!(!loaded.is_paused("cam-1"));
+        assert!(loaded.is_paused("cam-2"));
+    }
+}
@@ -0,0 +1,180 @@
+//! 轮询并行化 - 有界并发执行阻塞任务（tmux 截屏、AI 状态判断），每个任务单独超时
+//!
+//! 每个任务在独立线程中执行，同时运行的任务数不超过上限。超时的任务不再占用并发名额，
+//! 其线程自然结束后结果被丢弃，因此卡住的 AI 调用不会拖慢其余 agent。
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 默认最大并发数
+pub const DEFAULT_MAX_PARALLEL: usize = 8;
+
+/// 默认单个任务超时
+pub const DEFAULT_TASK_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 任务失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskError {
+    TimedOut,
+    Panicked,
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::TimedOut => write!(f, "timed out"),
+            TaskError::Panicked => write!(f, "panicked"),
+        }
+    }
+}
+
+/// 并发限制与超时
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineConfig {
+    pub max_parallel: usize,
+    pub task_timeout: Duration,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            max_parallel: DEFAULT_MAX_PARALLEL,
+            task_timeout: DEFAULT_TASK_TIMEOUT,
+        }
+    }
+}
+
+impl PipelineConfig {
+    /// 并发执行 `task`，按输入顺序返回 `(key, 结果)`
+    pub fn run<K, T, R, F>(&self, items: Vec<(K, T)>, task: F) -> Vec<(K, Result<R, TaskError>)>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        let total = items.len();
+        let max_parallel = self.max_parallel.max(1);
+        let task = Arc::new(task);
+        let (tx, rx) = mpsc::channel();
+
+        let mut queue = items.into_iter().enumerate();
+        let mut running: HashMap<usize, Instant> = HashMap::new();
+        let mut results: Vec<Option<Result<R, TaskError>>> = (0..total).map(|_| None).collect();
+        let mut keys = Vec::with_capacity(total);
+
+        loop {
+            while running.len() < max_parallel {
+                let Some((index, (key, item))) = queue.next() else {
+                    break;
+                };
+                keys.push(key);
+                running.insert(index, Instant::now());
+                let task = Arc::clone(&task);
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    let result = catch_unwind(AssertUnwindSafe(|| task(item)))
+                        .map_err(|_| TaskError::Panicked);
+                    // 超时后接收端可能已不再等待
+                    let _ = tx.send((index, result));
+                });
+            }
+            if running.is_empty() {
+                break;
+            }
+
+            let deadline = running.values().min().copied().unwrap() + self.task_timeout;
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok((index, result)) => {
+                    if running.remove(&index).is_some() {
+                        results[index] = Some(result);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    running.retain(|index, started| {
+                        let expired = now.duration_since(*started) >= self.task_timeout;
+                        if expired {
+                            results[*index] = Some(Err(TaskError::TimedOut));
+                        }
+                        !expired
+                    });
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        keys.into_iter()
+            .zip(results)
+            .map(|(key, result)| (key, result.unwrap_or(Err(TaskError::TimedOut))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_run_is_parallel_and_ordered() {
+        let config = PipelineConfig {
+            max_parallel: 4,
+            task_timeout: Duration::from_secs(5),
+        };
+        let items: Vec<(usize, u64)> = (0..8).map(|i| (i, 100)).collect();
+        let started = Instant::now();
+        let results = config.run(items, |ms| {
+            std::thread::sleep(Duration::from_millis(ms));
+            ms * 2
+        });
+        // 8 个 100ms 任务、4 并发，约 200ms
+        assert!(started.elapsed() < Duration::from_millis(700));
+        assert_eq!(results.len(), 8);
+        for (i, (key, result)) in results.into_iter().enumerate() {
+            assert_eq!(key, i);
+            assert_eq!(result, Ok(200));
+        }
+    }
+
+    #[test]
+    fn test_run_bounds_concurrency() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let config = PipelineConfig {
+            max_parallel: 2,
+            task_timeout: Duration::from_secs(5),
+        };
+        let (a, p) = (Arc::clone(&active), Arc::clone(&peak));
+        config.run((0..6).map(|i| (i, ())).collect(), move |_| {
+            let now = a.fetch_add(1, Ordering::SeqCst) + 1;
+            p.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(30));
+            a.fetch_sub(1, Ordering::SeqCst);
+        });
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_run_times_out_and_catches_panics() {
+        let config = PipelineConfig {
+            max_parallel: 1,
+            task_timeout: Duration::from_millis(100),
+        };
+        let started = Instant::now();
+        let results = config.run(vec![("slow", 2000u64), ("panic", 0), ("fast", 1)], |ms| {
+            if ms == 0 {
+                panic!("boom");
+            }
+            std::thread::sleep(Duration::from_millis(ms));
+            ms
+        });
+        // 超时任务不阻塞后续任务
+        assert!(started.elapsed() < Duration::from_millis(1000));
+        assert_eq!(results[0], ("slow", Err(TaskError::TimedOut)));
+        assert_eq!(results[1], ("panic", Err(TaskError::Panicked)));
+        assert_eq!(results[2], ("fast", Ok(1)));
+    }
+}
@@ -0,0 +1,407 @@
+//! 启动前检查 - 在创建 session 之前发现启动必然失败的问题
+//!
+//! 缺少 agent CLI、未登录、工作目录不存在时，tmux session 照样能创建，
+//! 只是里面的 agent 立即退出或停在登录页，要等 watcher 几分钟后才发现。
+//! `cam start` 和 MCP `agent_start` 在启动前逐项检查，错误直接返回并附带修复方法；
+//! 警告（未提交的修改、磁盘空间偏少等）只提示，不阻止启动。
+//! 同一项目中已有同类型 agent 在运行时，两个 agent 的修改会互相覆盖，`cam start` 默认拒绝启动。
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::agent::adapter::get_adapter;
+use crate::agent::{AgentRecord, AgentType};
+use crate::infra::docker;
+use crate::infra::multiplexer::Multiplexer;
+
+/// 可用磁盘空间低于该值时无法启动（MB）
+const MIN_FREE_DISK_MB: u64 = 256;
+/// 可用磁盘空间低于该值时提示（MB）
+const LOW_FREE_DISK_MB: u64 = 2048;
+
+/// 检查结果级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckLevel {
+    /// 提示，不阻止启动
+    Warning,
+    /// 启动必然失败
+    Error,
+}
+
+/// 单项检查发现的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreflightIssue {
+    pub level: CheckLevel,
+    pub message: String,
+    /// 修复方法
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl PreflightIssue {
+    fn warning(message: impl Into<String>, hint: Option<String>) -> Self {
+        Self {
+            level: CheckLevel::Warning,
+            message: message.into(),
+            hint,
+        }
+    }
+
+    fn error(message: impl Into<String>, hint: Option<String>) -> Self {
+        Self {
+            level: CheckLevel::Error,
+            message: message.into(),
+            hint,
+        }
+    }
+}
+
+impl fmt::Display for PreflightIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let icon = match self.level {
+            CheckLevel::Warning => "⚠️",
+            CheckLevel::Error => "✗",
+        };
+        write!(f, "{} {}", icon, self.message)?;
+        if let Some(ref hint) = self.hint {
+            write!(f, "\n    → {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// 启动前检查的全部结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PreflightReport {
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl PreflightReport {
+    /// 检查启动 agent 所需的环境
+    pub fn run(
+        project_path: &str,
+        agent_type: &AgentType,
+        container: Option<&str>,
+        multiplexer: &dyn Multiplexer,
+    ) -> Self {
+        let mut issues = check_project(Path::new(project_path));
+        if !multiplexer.is_available() {
+            issues.push(PreflightIssue::error(
+                format!("{} 未安装或不可用", multiplexer.name()),
+                Some(format!("安装 {0}: brew install {0}", multiplexer.name())),
+            ));
+        }
+        issues.extend(check_agent_cli(agent_type, container));
+        issues.extend(check_disk_space(available_disk_mb(Path::new(project_path))));
+        Self { issues }
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &PreflightIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.level == CheckLevel::Warning)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &PreflightIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.level == CheckLevel::Error)
+    }
+
+    /// 有错误时返回包含所有错误和修复方法的 Err
+    pub fn ensure_ok(&self) -> anyhow::Result<()> {
+        let errors: Vec<String> = self
+            .errors()
+            .map(|issue| format!("  {}", issue.to_string().replace('\n', "\n  ")))
+            .collect();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!("启动前检查未通过:\n{}", errors.join("\n"))
+    }
+}
+
+/// 工作目录存在且是目录；不是 git 仓库或有未提交修改时提示
+pub fn check_project(path: &Path) -> Vec<PreflightIssue> {
+    if !path.is_dir() {
+        return vec![PreflightIssue::error(
+            format!("工作目录不存在: {}", path.display()),
+            Some("检查路径是否正确，或先创建该目录".to_string()),
+        )];
+    }
+
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    if git(&["rev-parse", "--is-inside-work-tree"]).is_none() {
+        return vec![PreflightIssue::warning(
+            format!("{} 不是 git 仓库，agent 的修改无法回滚", path.display()),
+            Some(format!("git -C {} init", path.display())),
+        )];
+    }
+    let changed = git(&["status", "--porcelain"])
+        .map(|status| status.lines().count())
+        .unwrap_or(0);
+    if changed > 0 {
+        return vec![PreflightIssue::warning(
+            format!(
+                "工作区有 {} 个未提交的修改，会和 agent 的修改混在一起",
+                changed
+            ),
+            Some("先提交或 git stash".to_string()),
+        )];
+    }
+    Vec::new()
+}
+
+/// agent CLI 已安装且已登录（在容器中运行时改为检查 docker）
+pub fn check_agent_cli(agent_type: &AgentType, container: Option<&str>) -> Vec<PreflightIssue> {
+    if container.is_some() {
+        // agent CLI 在镜像中，只需要 docker
+        if docker::is_available() {
+            return Vec::new();
+        }
+        return vec![PreflightIssue::error(
+            "docker 未安装或 daemon 未运行",
+            Some("启动 Docker Desktop 或 dockerd".to_string()),
+        )];
+    }
+
+    let adapter = get_adapter(agent_type);
+    let command = adapter.get_command().to_string();
+    if !adapter.is_installed() {
+        let install_hint = match agent_type {
+            AgentType::Claude => "npm install -g @anthropic-ai/claude-code",
+            AgentType::Codex => "npm install -g @openai/codex",
+            _ => "请参考官方文档安装",
+        };
+        return vec![PreflightIssue::error(
+            format!("{} 命令未找到", command),
+            Some(format!("请先安装: {}", install_hint)),
+        )];
+    }
+    if adapter.is_authenticated() == Some(false) {
+        let login_hint = match agent_type {
+            AgentType::Claude => "运行 claude 并执行 /login，或设置 ANTHROPIC_API_KEY",
+            AgentType::Codex => "运行 codex login，或设置 OPENAI_API_KEY",
+            _ => "请先登录",
+        };
+        return vec![PreflightIssue::error(
+            format!("{} 未登录，agent 会停在登录页", command),
+            Some(login_hint.to_string()),
+        )];
+    }
+    Vec::new()
+}
+
+/// 同一项目中已在运行的同类型 agent：工作目录相同或互为上下级目录（会修改同一批文件）
+pub fn find_duplicate_agents<'a>(
+    project_path: &str,
+    agent_type: &AgentType,
+    agents: &'a [AgentRecord],
+) -> Vec<&'a AgentRecord> {
+    let project = normalize_path(Path::new(project_path));
+    agents
+        .iter()
+        .filter(|agent| agent.agent_type == *agent_type)
+        .filter(|agent| {
+            let other = normalize_path(Path::new(&agent.project_path));
+            project.starts_with(&other) || other.starts_with(&project)
+        })
+        .collect()
+}
+
+/// 同一项目已有同类型 agent 时报错，`force` 时只提示；提示中给出附着和继续已有 agent 的方法
+pub fn check_duplicate_agents(
+    project_path: &str,
+    agent_type: &AgentType,
+    agents: &[AgentRecord],
+    force: bool,
+) -> Vec<PreflightIssue> {
+    let duplicates = find_duplicate_agents(project_path, agent_type, agents);
+    let Some(first) = duplicates.first() else {
+        return Vec::new();
+    };
+    let listed = duplicates
+        .iter()
+        .map(|agent| format!("{}（{}）", agent.agent_id, agent.project_path))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!(
+        "该项目已有运行中的 {} agent: {}，同时修改会互相冲突",
+        agent_type, listed
+    );
+    if force {
+        return vec![PreflightIssue::warning(message, None)];
+    }
+    vec![PreflightIssue::error(
+        message,
+        Some(format!(
+            "查看: cam attach {0}；继续该 agent: cam reply --target {0} \"<prompt>\"；仍要启动请加 --force",
+            first.agent_id
+        )),
+    )]
+}
+
+/// 解析为绝对路径（不存在时按原样），用于比较工作目录
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 按可用磁盘空间（MB）判断，无法获取时不检查
+pub fn check_disk_space(available_mb: Option<u64>) -> Vec<PreflightIssue> {
+    match available_mb {
+        Some(mb) if mb < MIN_FREE_DISK_MB => vec![PreflightIssue::error(
+            format!("磁盘可用空间只有 {} MB，agent 无法写入会话和构建产物", mb),
+            Some("清理磁盘后重试".to_string()),
+        )],
+        Some(mb) if mb < LOW_FREE_DISK_MB => vec![PreflightIssue::warning(
+            format!("磁盘可用空间只有 {} MB", mb),
+            None,
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// 工作目录所在磁盘的可用空间（MB），取挂载点最长匹配的磁盘
+fn available_disk_mb(path: &Path) -> Option<u64> {
+    let path = path.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space() / 1024 / 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_check_project() {
+        let temp = tempfile::tempdir().unwrap();
+        let missing = check_project(&temp.path().join("missing"));
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].level, CheckLevel::Error);
+
+        let not_repo = check_project(temp.path());
+        assert_eq!(not_repo.len(), 1);
+        assert!(not_repo[0].message.contains("不是 git 仓库"));
+
+        git(temp.path(), &["init", "-q"]);
+        assert!(check_project(temp.path()).is_empty());
+
+        std::fs::write(temp.path().join("main.rs"), "fn main() {}").unwrap();
+        let dirty = check_project(temp.path());
+        assert_eq!(dirty.len(), 1);
+        assert_eq!(dirty[0].level, CheckLevel::Warning);
+        assert!(dirty[0].message.contains("1 个未提交的修改"));
+    }
+
+    #[test]
+    fn test_check_disk_space() {
+        assert!(check_disk_space(None).is_empty());
+        assert!(check_disk_space(Some(50_000)).is_empty());
+        assert_eq!(check_disk_space(Some(1024))[0].level, CheckLevel::Warning);
+        assert_eq!(check_disk_space(Some(100))[0].level, CheckLevel::Error);
+    }
+
+    #[test]
+    fn test_check_duplicate_agents() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(repo.join("web")).unwrap();
+        let agents: Vec<AgentRecord> = serde_json::from_value(serde_json::json!([
+            {"agent_id": "cam-1", "agent_type": "claude", "project_path": repo,
+             "tmux_session": "cam-1", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing"},
+            {"agent_id": "cam-2", "agent_type": "codex", "project_path": repo,
+             "tmux_session": "cam-2", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing"},
+            {"agent_id": "cam-3", "agent_type": "claude", "project_path": temp.path().join("repo-2"),
+             "tmux_session": "cam-3", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing"},
+        ]))
+        .unwrap();
+
+        // 相同目录（含末尾 `/`）和子目录都算同一项目，只比较同类型
+        let same =
+            find_duplicate_agents(&format!("{}/", repo.display()), &AgentType::Claude, &agents);
+        assert_eq!(
+            same.iter().map(|a| a.agent_id.as_str()).collect::<Vec<_>>(),
+            ["cam-1"]
+        );
+        let nested = find_duplicate_agents(
+            repo.join("web").to_str().unwrap(),
+            &AgentType::Codex,
+            &agents,
+        );
+        assert_eq!(
+            nested
+                .iter()
+                .map(|a| a.agent_id.as_str())
+                .collect::<Vec<_>>(),
+            ["cam-2"]
+        );
+        // `repo-2` 只是名称前缀相同
+        let other = temp.path().join("repo-2");
+        assert!(
+            find_duplicate_agents(other.to_str().unwrap(), &AgentType::Codex, &agents).is_empty()
+        );
+
+        let issues =
+            check_duplicate_agents(repo.to_str().unwrap(), &AgentType::Claude, &agents, false);
+        assert_eq!(issues[0].level, CheckLevel::Error);
+        assert!(issues[0].message.contains("cam-1"));
+        assert!(issues[0]
+            .hint
+            .as_deref()
+            .unwrap()
+            .contains("cam attach cam-1"));
+        let forced =
+            check_duplicate_agents(repo.to_str().unwrap(), &AgentType::Claude, &agents, true);
+        assert_eq!(forced[0].level, CheckLevel::Warning);
+    }
+
+    #[test]
+    fn test_ensure_ok() {
+        let mut report = PreflightReport {
+            issues: vec![PreflightIssue::warning("工作区有修改", None)],
+        };
+        assert!(report.ensure_ok().is_ok());
+
+        report.issues.push(PreflightIssue::error(
+            "claude 命令未找到",
+            Some("请先安装: npm install -g @anthropic-ai/claude-code".to_string()),
+        ));
+        let err = report.ensure_ok().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "启动前检查未通过:\n  ✗ claude 命令未找到\n      → 请先安装: npm install -g @anthropic-ai/claude-code"
+        );
+        assert_eq!(report.warnings().count(), 1);
+    }
+}
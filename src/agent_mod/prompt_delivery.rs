@@ -0,0 +1,75 @@
+//! 初始 prompt 送达确认 - 输入后用终端快照确认被接受，未送达时重试
+//!
+//! agent TUI 刚出现提示符时可能还没开始接收输入，此时输入的按键会丢失。
+//! 输入后比较前后快照：prompt 出现在屏幕上（输入框回显或对话记录），
+//! 或屏幕变化且不再停在提示符（已开始处理），视为已接受；否则重新等待就绪再输入。
+
+use std::time::Duration;
+
+/// 最多输入次数
+pub const MAX_ATTEMPTS: u32 = 3;
+/// 输入后等待屏幕刷新的时间
+pub const CONFIRM_DELAY: Duration = Duration::from_secs(2);
+/// 用于在屏幕上查找回显的 prompt 前缀长度（字符数），太长会被 TUI 折行截断
+const ECHO_PREFIX_CHARS: usize = 24;
+
+/// 比较输入前后的快照，判断 prompt 是否被接受
+///
+/// `ready_after` 为输入后 adapter 是否仍判定停在输入提示符。
+pub fn prompt_accepted(before: &str, after: &str, prompt: &str, ready_after: bool) -> bool {
+    let echo = echo_prefix(prompt);
+    if !echo.is_empty() {
+        let count = |snapshot: &str| collapse_whitespace(snapshot).matches(&echo).count();
+        if count(after) > count(before) {
+            return true;
+        }
+    }
+    // 已开始处理：屏幕有变化且不再停在提示符
+    after != before && !ready_after
+}
+
+/// prompt 首行的前缀（合并空白）
+fn echo_prefix(prompt: &str) -> String {
+    let first_line = prompt.trim().lines().next().unwrap_or_default();
+    collapse_whitespace(first_line)
+        .chars()
+        .take(ECHO_PREFIX_CHARS)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_accepted() {
+        let prompt = "Implement   the TODO app with a REST API and tests\nthen commit";
+        let idle = "Welcome to Claude Code\n\n❯ \n? for shortcuts";
+
+        // 按键丢失：屏幕没有变化
+        assert!(!prompt_accepted(idle, idle, prompt, true));
+
+        // 回显在输入框中
+        let echoed =
+            "Welcome to Claude Code\n\n❯ Implement the TODO app with a REST\n  API and tests";
+        assert!(prompt_accepted(idle, echoed, prompt, true));
+
+        // 已开始处理，回显已滚出屏幕
+        let processing = "✻ Thinking… (esc to interrupt)";
+        assert!(prompt_accepted(idle, processing, prompt, false));
+
+        // 屏幕变化但仍停在空提示符（如只刷新了状态栏）
+        let refreshed = "Welcome to Claude Code\n\n❯ \n? for shortcuts · 1 MCP server";
+        assert!(!prompt_accepted(idle, refreshed, prompt, true));
+
+        // 之前已有相同内容时要求出现新的回显
+        let history = "> Implement the TODO app with a REST API\n❯ ";
+        assert!(!prompt_accepted(history, history, prompt, true));
+    }
+}
@@ -0,0 +1,210 @@
+//! 指令队列 - agent 正在处理时暂存新指令，空闲后再输入
+//!
+//! 处理中的 agent 收到按键可能被当作流式输出中的输入。`cam reply --target <agent> <指令>`
+//! （agent 没有待处理确认时）和 MCP `agent_send` 在 agent 处理中时把指令加入队列；watcher
+//! 判定 agent 等待输入、屏幕停在空提示符且没有待处理确认时，每轮输入一条（先进先出）。
+//!
+//! 队列保存在 `~/.config/code-agent-monitor/prompt_queue.json`，用 `cam queue list/cancel` 管理。
+
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentManager;
+
+/// 一条排队的指令
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedPrompt {
+    /// 队列 ID（`q1`、`q2`…，用于 `cam queue cancel`）
+    pub id: String,
+    pub agent_id: String,
+    pub message: String,
+    pub queued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueState {
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    prompts: Vec<QueuedPrompt>,
+}
+
+/// 指令队列文件（CLI、MCP 和 watcher 通过文件锁共享）
+#[derive(Debug, Clone)]
+pub struct PromptQueue {
+    path: PathBuf,
+}
+
+impl PromptQueue {
+    /// 默认路径
+    pub fn new() -> Self {
+        Self::in_dir(&crate::infra::paths::state_dir())
+    }
+
+    /// 数据目录（agents.json 所在目录）中的队列
+    pub fn in_dir(data_dir: &Path) -> Self {
+        Self::at(&data_dir.join("prompt_queue.json"))
+    }
+
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn read(&self) -> QueueState {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 在文件锁内读取、修改并保存队列
+    fn update<T>(&self, operation: impl FnOnce(&mut QueueState) -> T) -> Result<T> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("lock"))?;
+        lock_file.lock_exclusive()?;
+
+        let mut state = self.read();
+        let result = operation(&mut state);
+        let saved = serde_json::to_string_pretty(&state)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(fs::write(&self.path, content)?));
+
+        let _ = lock_file.unlock();
+        saved.map(|_| result)
+    }
+
+    /// 所有排队的指令（按加入顺序）
+    pub fn list(&self) -> Vec<QueuedPrompt> {
+        self.read().prompts
+    }
+
+    /// agent 是否有排队的指令
+    pub fn has_queued(&self, agent_id: &str) -> bool {
+        self.list().iter().any(|p| p.agent_id == agent_id)
+    }
+
+    /// 加入队列
+    pub fn enqueue(&self, agent_id: &str, message: &str) -> Result<QueuedPrompt> {
+        self.update(|state| {
+            state.next_id += 1;
+            let prompt = QueuedPrompt {
+                id: format!("q{}", state.next_id),
+                agent_id: agent_id.to_string(),
+                message: message.to_string(),
+                queued_at: Utc::now(),
+            };
+            state.prompts.push(prompt.clone());
+            prompt
+        })
+    }
+
+    /// 取出 agent 最早的一条指令
+    pub fn pop(&self, agent_id: &str) -> Result<Option<QueuedPrompt>> {
+        self.update(|state| {
+            let index = state.prompts.iter().position(|p| p.agent_id == agent_id)?;
+            Some(state.prompts.remove(index))
+        })
+    }
+
+    /// 取消一条指令
+    pub fn cancel(&self, id: &str) -> Result<Option<QueuedPrompt>> {
+        self.update(|state| {
+            let index = state.prompts.iter().position(|p| p.id == id)?;
+            Some(state.prompts.remove(index))
+        })
+    }
+
+    /// 取消 agent 的所有指令（`agent_id` 为 None 时清空队列）
+    pub fn cancel_all(&self, agent_id: Option<&str>) -> Result<Vec<QueuedPrompt>> {
+        self.update(|state| {
+            let (cancelled, kept) = std::mem::take(&mut state.prompts)
+                .into_iter()
+                .partition(|p| agent_id.is_none_or(|id| p.agent_id == id));
+            state.prompts = kept;
+            cancelled
+        })
+    }
+}
+
+impl Default for PromptQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 发送新指令的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    /// 已输入 agent
+    Sent,
+    /// agent 正在处理，已加入队列
+    Queued(QueuedPrompt),
+}
+
+/// 向 agent 发送新指令：正在处理（或已有排队指令）时加入队列，否则立即输入
+pub fn send_or_queue(
+    agent_manager: &AgentManager,
+    queue: &PromptQueue,
+    agent_id: &str,
+    message: &str,
+) -> Result<Delivery> {
+    let busy = agent_manager
+        .get_agent(agent_id)?
+        .is_some_and(|agent| agent.status.is_processing());
+    if busy || queue.has_queued(agent_id) {
+        return Ok(Delivery::Queued(queue.enqueue(agent_id, message)?));
+    }
+    agent_manager.send_input(agent_id, message)?;
+    Ok(Delivery::Sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_queue_fifo_and_cancel() {
+        let tmp = TempDir::new().unwrap();
+        let queue = PromptQueue::at(&tmp.path().join("prompt_queue.json"));
+
+        let first = queue.enqueue("cam-1", "run the tests").unwrap();
+        let second = queue.enqueue("cam-2", "fix lint").unwrap();
+        let third = queue.enqueue("cam-1", "then commit").unwrap();
+        assert_eq!(
+            (first.id.as_str(), second.id.as_str(), third.id.as_str()),
+            ("q1", "q2", "q3")
+        );
+        assert!(queue.has_queued("cam-1"));
+
+        // 每个 agent 按加入顺序取出
+        assert_eq!(
+            queue.pop("cam-1").unwrap().unwrap().message,
+            "run the tests"
+        );
+        assert_eq!(queue.cancel("q3").unwrap().unwrap().message, "then commit");
+        assert!(queue.pop("cam-1").unwrap().is_none());
+        assert!(queue.cancel("q3").unwrap().is_none());
+
+        // ID 不复用
+        assert_eq!(queue.enqueue("cam-1", "again").unwrap().id, "q4");
+        let cancelled = queue.cancel_all(Some("cam-2")).unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(queue.list().len(), 1);
+        assert_eq!(queue.cancel_all(None).unwrap().len(), 1);
+        assert!(queue.list().is_empty());
+    }
+}
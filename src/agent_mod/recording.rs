@@ -0,0 +1,530 @@
+//! Record / Replay - 录制 agent 的终端快照与 hook 输入，离线重放通知流程
+//!
+//! 录制包（bundle）是 JSONL 文件，每行一个 `RecordFrame`：
+//! - `header`：agent 信息
+//! - `snapshot`：终端内容变化时的快照
+//! - `hook`：录制期间 `cam notify` 收到的 hook 输入
+//! - `end`：录制结束时间
+//!
+//! 录制期间在 `recordings/active/{agent_id}` 写入标记文件，`cam notify` 据此把 hook 输入追加到录制包。
+//! 重放时按录制时间模拟 watcher 的稳定性检测、hook 静默期和去重，生成与线上一致的 `NotificationEvent`。
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::agent::{AgentRecord, AgentWatcher};
+use crate::infra::input::InputWaitResult;
+use crate::notification::{
+    question_dedup_key, NotificationDeduplicator, NotificationEvent, NotifyAction,
+};
+
+/// 录制包格式版本
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// stdin 中终端快照的分隔标记（与 `cam notify` 一致）
+const SNAPSHOT_MARKER: &str = "\n\n--- 终端快照 ---\n";
+
+/// 录制包中的一帧
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordFrame {
+    /// 录制包头
+    Header {
+        format_version: u32,
+        agent_id: String,
+        agent_type: String,
+        project_path: String,
+        started_at: DateTime<Utc>,
+    },
+    /// 终端快照
+    Snapshot { at: DateTime<Utc>, content: String },
+    /// hook 输入
+    Hook {
+        at: DateTime<Utc>,
+        event: String,
+        /// stdin 原始内容
+        payload: String,
+        /// `cam notify` 实际使用的终端快照
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        terminal_snapshot: Option<String>,
+    },
+    /// 录制结束
+    End { at: DateTime<Utc> },
+}
+
+impl RecordFrame {
+    /// 帧时间
+    pub fn at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Header { started_at, .. } => *started_at,
+            Self::Snapshot { at, .. } | Self::Hook { at, .. } | Self::End { at } => *at,
+        }
+    }
+}
+
+/// 录制目录
+pub fn recordings_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("recordings")
+}
+
+/// agent 的录制标记文件
+fn active_marker_path(data_dir: &Path, agent_id: &str) -> PathBuf {
+    recordings_dir(data_dir).join("active").join(agent_id)
+}
+
+/// 查询 agent 是否正在录制，返回录制包路径
+pub fn active_recording(data_dir: &Path, agent_id: &str) -> Option<PathBuf> {
+    let content = fs::read_to_string(active_marker_path(data_dir, agent_id)).ok()?;
+    let path = PathBuf::from(content.trim());
+    path.exists().then_some(path)
+}
+
+/// 追加一帧到录制包（排他锁，`cam record` 与 `cam notify` 可能同时写入）
+pub fn append_frame(bundle: &Path, frame: &RecordFrame) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(bundle)?;
+    file.lock_exclusive()?;
+    let result = serde_json::to_string(frame)
+        .map_err(Into::into)
+        .and_then(|line| Ok(writeln!(file, "{}", line)?));
+    let _ = file.unlock();
+    result
+}
+
+/// 读取录制包
+pub fn load_bundle(bundle: &Path) -> Result<Vec<RecordFrame>> {
+    let content = fs::read_to_string(bundle)
+        .with_context(|| format!("读取录制包失败: {}", bundle.display()))?;
+    let frames = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| format!("录制包第 {} 行格式错误", i + 1))
+        })
+        .collect::<Result<Vec<RecordFrame>>>()?;
+
+    match frames.first() {
+        Some(RecordFrame::Header { format_version, .. })
+            if *format_version <= BUNDLE_FORMAT_VERSION =>
+        {
+            Ok(frames)
+        }
+        Some(RecordFrame::Header { format_version, .. }) => Err(anyhow!(
+            "录制包版本 {} 高于当前支持的版本 {}",
+            format_version,
+            BUNDLE_FORMAT_VERSION
+        )),
+        _ => Err(anyhow!("录制包缺少 header: {}", bundle.display())),
+    }
+}
+
+/// `cam notify` 调用：agent 正在录制时追加 hook 帧，失败不影响通知
+pub fn record_hook_if_active(
+    data_dir: &Path,
+    agent_id: &str,
+    event: &str,
+    payload: &str,
+    terminal_snapshot: Option<&str>,
+) {
+    if let Some(bundle) = active_recording(data_dir, agent_id) {
+        let frame = RecordFrame::Hook {
+            at: Utc::now(),
+            event: event.to_string(),
+            payload: payload.to_string(),
+            terminal_snapshot: terminal_snapshot.map(|s| s.to_string()),
+        };
+        if let Err(e) = append_frame(&bundle, &frame) {
+            tracing::warn!(agent_id = %agent_id, error = %e, "Failed to record hook payload");
+        }
+    }
+}
+
+/// 录制器
+pub struct Recorder {
+    bundle: PathBuf,
+    marker: PathBuf,
+    last_snapshot: Option<String>,
+}
+
+impl Recorder {
+    /// 开始录制：写入 header 并创建录制标记
+    pub fn start(data_dir: &Path, agent: &AgentRecord, bundle: PathBuf) -> Result<Self> {
+        if let Some(parent) = bundle.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if bundle.exists() {
+            return Err(anyhow!("录制包已存在: {}", bundle.display()));
+        }
+        append_frame(
+            &bundle,
+            &RecordFrame::Header {
+                format_version: BUNDLE_FORMAT_VERSION,
+                agent_id: agent.agent_id.clone(),
+                agent_type: agent.agent_type.to_string(),
+                project_path: agent.project_path.clone(),
+                started_at: Utc::now(),
+            },
+        )?;
+
+        let marker = active_marker_path(data_dir, &agent.agent_id);
+        if let Some(parent) = marker.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bundle = fs::canonicalize(&bundle)?;
+        fs::write(&marker, bundle.to_string_lossy().as_bytes())?;
+
+        Ok(Self {
+            bundle,
+            marker,
+            last_snapshot: None,
+        })
+    }
+
+    /// 录制包路径
+    pub fn bundle_path(&self) -> &Path {
+        &self.bundle
+    }
+
+    /// 记录终端快照（内容未变化时跳过），返回是否写入
+    pub fn record_snapshot(&mut self, content: &str) -> Result<bool> {
+        if self.last_snapshot.as_deref() == Some(content) {
+            return Ok(false);
+        }
+        append_frame(
+            &self.bundle,
+            &RecordFrame::Snapshot {
+                at: Utc::now(),
+                content: content.to_string(),
+            },
+        )?;
+        self.last_snapshot = Some(content.to_string());
+        Ok(true)
+    }
+
+    /// 结束录制：写入 end 帧并移除录制标记
+    pub fn finish(self) -> Result<PathBuf> {
+        let _ = fs::remove_file(&self.marker);
+        append_frame(&self.bundle, &RecordFrame::End { at: Utc::now() })?;
+        Ok(self.bundle)
+    }
+}
+
+/// 重放生成事件的来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplaySource {
+    /// watcher 检测到等待输入
+    Watcher,
+    /// hook 输入
+    Hook { event: String },
+}
+
+/// 重放产生的一个通知事件
+#[derive(Debug, Clone)]
+pub struct ReplayEvent {
+    /// 录制时间
+    pub at: DateTime<Utc>,
+    pub source: ReplaySource,
+    pub event: NotificationEvent,
+}
+
+/// 重放检测阶段：按录制时间模拟 watcher 与 hook 路径，生成会进入通知层的事件
+///
+/// `detect` 对应 watcher 的 AI 等待检测（`InputWaitDetector::detect_immediate`），测试中可替换。
+pub fn replay_events<F>(frames: &[RecordFrame], mut detect: F) -> Result<Vec<ReplayEvent>>
+where
+    F: FnMut(&str) -> InputWaitResult,
+{
+    let (agent_id, project_path) = match frames.first() {
+        Some(RecordFrame::Header {
+            agent_id,
+            project_path,
+            ..
+        }) => (agent_id.clone(), project_path.clone()),
+        _ => return Err(anyhow!("录制包缺少 header")),
+    };
+
+    let stability = chrono::Duration::seconds(AgentWatcher::STABILITY_THRESHOLD_SECS as i64);
+    let quiet_period = chrono::Duration::seconds(AgentWatcher::HOOK_QUIET_PERIOD_SECS as i64);
+    let hook_times: Vec<DateTime<Utc>> = frames
+        .iter()
+        .filter(|f| matches!(f, RecordFrame::Hook { .. }))
+        .map(|f| f.at())
+        .collect();
+
+    let mut deduplicator = NotificationDeduplicator::new_without_persistence();
+    let mut was_waiting = false;
+    let mut events = Vec::new();
+
+    for (i, frame) in frames.iter().enumerate() {
+        match frame {
+            RecordFrame::Snapshot { at, content } => {
+                // 快照只在内容变化时录制，下一个快照（或结束）之前内容保持不变
+                let Some(changed_at) = frames[i + 1..]
+                    .iter()
+                    .find(|f| matches!(f, RecordFrame::Snapshot { .. } | RecordFrame::End { .. }))
+                    .map(|f| f.at())
+                else {
+                    continue;
+                };
+                let check_at = *at + stability;
+                if changed_at < check_at {
+                    continue;
+                }
+                // hook 静默期内 watcher 不做 AI 检测
+                if hook_times
+                    .iter()
+                    .any(|h| *h <= check_at && check_at - *h < quiet_period)
+                {
+                    continue;
+                }
+
+                let result = detect(content);
+                if result.is_waiting {
+                    let dedup_key = question_dedup_key(&agent_id, &result.context);
+                    let pattern_type = result
+                        .pattern_type
+                        .as_ref()
+                        .map(|p| format!("{:?}", p))
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    let pattern_type = match deduplicator.should_send_at(
+                        &agent_id,
+                        &dedup_key,
+                        check_at.timestamp() as u64,
+                    ) {
+                        NotifyAction::Send => Some(pattern_type),
+                        NotifyAction::SendReminder => Some(format!("{} (提醒)", pattern_type)),
+                        NotifyAction::Suppressed(_) => None,
+                    };
+                    if let Some(pattern_type) = pattern_type {
+                        events.push(ReplayEvent {
+                            at: check_at,
+                            source: ReplaySource::Watcher,
+                            event: NotificationEvent::waiting_for_input_with_decision(
+                                agent_id.clone(),
+                                pattern_type,
+                                result.is_decision_required,
+                            )
+                            .with_project_path(project_path.clone())
+                            .with_terminal_snapshot(result.context.clone())
+                            .with_dedup_key(dedup_key),
+                        });
+                    }
+                } else if was_waiting {
+                    deduplicator.clear_lock(&agent_id);
+                }
+                was_waiting = result.is_waiting;
+            }
+            RecordFrame::Hook {
+                at,
+                event,
+                payload,
+                terminal_snapshot,
+            } => {
+                let raw = payload.split(SNAPSHOT_MARKER).next().unwrap_or(payload);
+                let json: Option<serde_json::Value> = serde_json::from_str(raw).ok();
+                events.push(ReplayEvent {
+                    at: *at,
+                    source: ReplaySource::Hook {
+                        event: event.clone(),
+                    },
+                    event: NotificationEvent::from_hook(
+                        agent_id.clone(),
+                        event,
+                        json.as_ref(),
+                        payload,
+                        terminal_snapshot.as_deref(),
+                    ),
+                });
+            }
+            RecordFrame::Header { .. } | RecordFrame::End { .. } => {}
+        }
+    }
+
+    // 事件时间使用录制时间，与线上发送时一致
+    for replayed in &mut events {
+        replayed.event.timestamp = replayed.at;
+    }
+    events.sort_by_key(|e| e.at);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{AgentStatus, AgentType};
+    use crate::infra::input::InputWaitPattern;
+    use crate::notification::NotificationEventType;
+    use tempfile::tempdir;
+
+    fn t(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_800_000_000 + secs, 0).unwrap()
+    }
+
+    fn header() -> RecordFrame {
+        RecordFrame::Header {
+            format_version: BUNDLE_FORMAT_VERSION,
+            agent_id: "cam-rec".to_string(),
+            agent_type: "claude".to_string(),
+            project_path: "/workspace/app".to_string(),
+            started_at: t(0),
+        }
+    }
+
+    fn snapshot(secs: i64, content: &str) -> RecordFrame {
+        RecordFrame::Snapshot {
+            at: t(secs),
+            content: content.to_string(),
+        }
+    }
+
+    /// 以 "?" 结尾的快照视为等待输入
+    fn fake_detect(content: &str) -> InputWaitResult {
+        let waiting = content.trim_end().ends_with('?');
+        InputWaitResult {
+            is_waiting: waiting,
+            is_decision_required: false,
+            pattern_type: waiting.then_some(InputWaitPattern::Confirmation),
+            context: content.to_string(),
+        }
+    }
+
+    fn test_agent() -> AgentRecord {
+        AgentRecord {
+            agent_id: "cam-rec".to_string(),
+            agent_type: AgentType::Claude,
+            project_path: "/workspace/app".to_string(),
+            tmux_session: "cam-rec".to_string(),
+            session_id: None,
+            jsonl_path: None,
+            jsonl_offset: 0,
+            last_output_hash: None,
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            status: AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
+        }
+    }
+
+    #[test]
+    fn test_recorder_round_trip() {
+        let dir = tempdir().unwrap();
+        let bundle = dir.path().join("out").join("session.jsonl");
+        let mut recorder = Recorder::start(dir.path(), &test_agent(), bundle).unwrap();
+        let bundle = recorder.bundle_path().to_path_buf();
+
+        assert_eq!(
+            active_recording(dir.path(), "cam-rec"),
+            Some(bundle.clone())
+        );
+        assert!(recorder.record_snapshot("screen 1").unwrap());
+        // 内容未变化不重复记录
+        assert!(!recorder.record_snapshot("screen 1").unwrap());
+        record_hook_if_active(dir.path(), "cam-rec", "stop", r#"{"cwd":"/x"}"#, None);
+        // 未录制的 agent 不写入
+        record_hook_if_active(dir.path(), "cam-other", "stop", "{}", None);
+
+        recorder.finish().unwrap();
+        assert_eq!(active_recording(dir.path(), "cam-rec"), None);
+
+        let frames = load_bundle(&bundle).unwrap();
+        assert_eq!(frames.len(), 4);
+        assert!(
+            matches!(frames[0], RecordFrame::Header { ref agent_id, .. } if agent_id == "cam-rec")
+        );
+        assert!(
+            matches!(frames[1], RecordFrame::Snapshot { ref content, .. } if content == "screen 1")
+        );
+        assert!(matches!(frames[2], RecordFrame::Hook { ref event, .. } if event == "stop"));
+        assert!(matches!(frames[3], RecordFrame::End { .. }));
+    }
+
+    #[test]
+    fn test_load_bundle_requires_header() {
+        let dir = tempdir().unwrap();
+        let bundle = dir.path().join("bad.jsonl");
+        append_frame(&bundle, &snapshot(0, "x")).unwrap();
+        assert!(load_bundle(&bundle).is_err());
+    }
+
+    #[test]
+    fn test_replay_detects_stable_waiting_snapshot() {
+        let frames = vec![
+            header(),
+            snapshot(0, "Running tests..."),
+            // 2 秒后内容变化：未稳定，不检测
+            snapshot(2, "Proceed with deploy?"),
+            RecordFrame::End { at: t(20) },
+        ];
+        let events = replay_events(&frames, fake_detect).unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source, ReplaySource::Watcher);
+        assert_eq!(
+            events[0].at,
+            t(2 + AgentWatcher::STABILITY_THRESHOLD_SECS as i64)
+        );
+        assert!(matches!(
+            events[0].event.event_type,
+            NotificationEventType::WaitingForInput { ref pattern_type, .. } if pattern_type == "Confirmation"
+        ));
+        assert_eq!(
+            events[0].event.project_path.as_deref(),
+            Some("/workspace/app")
+        );
+    }
+
+    #[test]
+    fn test_replay_skips_detection_during_hook_quiet_period() {
+        let frames = vec![
+            header(),
+            RecordFrame::Hook {
+                at: t(1),
+                event: "permission_request".to_string(),
+                payload: r#"{"tool_name":"Bash","tool_input":{"command":"ls"}}"#.to_string(),
+                terminal_snapshot: None,
+            },
+            snapshot(2, "Allow Bash?"),
+            RecordFrame::End { at: t(60) },
+        ];
+        let events = replay_events(&frames, fake_detect).unwrap();
+
+        // 只有 hook 事件，watcher 处于静默期
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].source,
+            ReplaySource::Hook {
+                event: "permission_request".to_string()
+            }
+        );
+        assert!(matches!(
+            events[0].event.event_type,
+            NotificationEventType::PermissionRequest { ref tool_name, .. } if tool_name == "Bash"
+        ));
+    }
+
+    #[test]
+    fn test_replay_dedups_repeated_prompt_until_resumed() {
+        let frames = vec![
+            header(),
+            snapshot(0, "Continue?"),
+            snapshot(100, "Continue? "),
+            snapshot(200, "working"),
+            snapshot(300, "Continue?"),
+            RecordFrame::End { at: t(400) },
+        ];
+        let events = replay_events(&frames, fake_detect).unwrap();
+
+        // 第二次相同提示被去重；恢复运行后再次出现则重新通知
+        let times: Vec<_> = events.iter().map(|e| e.at).collect();
+        assert_eq!(times, vec![t(6), t(306)]);
+    }
+}
@@ -0,0 +1,370 @@
+//! 自动恢复 - 对已知故障按策略执行恢复动作
+//!
+//! - agent 异常退出（Claude Code 崩溃、tmux session 消失）：退避后用 `--resume` 重启
+//! - API 过载/限流：退避后重新发送最后一条 prompt
+//!
+//! 只有退出时仍在处理中且有 session_id 的 agent 才会重启（空闲时退出多为用户主动退出）。
+//! 每个 agent 的重试次数有上限，超过 `RESET_AFTER_SECS` 没有再次故障时清零。
+//! 默认关闭，在 config.json 中配置：
+//! ```json
+//! { "recovery": { "restart_on_exit": true, "resend_on_overload": true, "max_retries": 3, "backoff_secs": 30 } }
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::agent::AgentRecord;
+
+/// 默认最大重试次数
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// 默认首次退避时间（秒），之后每次翻倍
+pub const DEFAULT_BACKOFF_SECS: u64 = 30;
+
+/// 退避上限（秒）
+const MAX_BACKOFF_SECS: u64 = 3600;
+
+/// 距上次恢复超过此时间（秒）后重试计数清零
+pub const RESET_AFTER_SECS: u64 = 1800;
+
+/// 恢复策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPolicy {
+    /// agent 异常退出后重启
+    pub restart_on_exit: bool,
+    /// API 过载/限流后重发最后一条 prompt
+    pub resend_on_overload: bool,
+    pub max_retries: u32,
+    pub backoff_secs: u64,
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self {
+            restart_on_exit: false,
+            resend_on_overload: false,
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff_secs: DEFAULT_BACKOFF_SECS,
+        }
+    }
+}
+
+impl RecoveryPolicy {
+    /// 读取 `~/.config/code-agent-monitor/config.json` 的 `recovery`，未启用时返回 None
+    pub fn load() -> Option<Self> {
+        let policy = Self::from_json(&crate::infra::paths::config_section::<Value>("recovery")?);
+        policy.is_enabled().then_some(policy)
+    }
+
+    /// 从 JSON 解析（缺失字段使用默认值）
+    pub fn from_json(value: &Value) -> Self {
+        let defaults = Self::default();
+        let flag = |key: &str| value.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        Self {
+            restart_on_exit: flag("restart_on_exit"),
+            resend_on_overload: flag("resend_on_overload"),
+            max_retries: value
+                .get("max_retries")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32)
+                .unwrap_or(defaults.max_retries),
+            backoff_secs: value
+                .get("backoff_secs")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(defaults.backoff_secs),
+        }
+    }
+
+    /// 是否启用了任何恢复动作
+    pub fn is_enabled(&self) -> bool {
+        self.restart_on_exit || self.resend_on_overload
+    }
+
+    /// 第 N 次（从 1 开始）重试前的退避时间
+    pub fn backoff(&self, attempt: u32) -> u64 {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        self.backoff_secs
+            .saturating_mul(factor)
+            .min(MAX_BACKOFF_SECS)
+    }
+}
+
+/// 恢复动作
+#[derive(Debug, Clone)]
+pub enum RecoveryAction {
+    /// 用 `--resume` 重启退出的 agent（沿用原 agent_id 和 tmux session）
+    Restart { record: Box<AgentRecord> },
+    /// 重新发送最后一条 prompt
+    ResendPrompt { prompt: String },
+}
+
+impl RecoveryAction {
+    /// 动作描述（用于通知）
+    pub fn describe(&self) -> String {
+        match self {
+            RecoveryAction::Restart { record } => match record.session_id {
+                Some(ref session_id) => format!("重启 agent 并恢复会话 {}", session_id),
+                None => "重启 agent".to_string(),
+            },
+            RecoveryAction::ResendPrompt { prompt } => {
+                format!(
+                    "重新发送最后一条 prompt: {}",
+                    crate::infra::truncate_str(prompt, 60)
+                )
+            }
+        }
+    }
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RecoveryOutcome {
+    /// 动作已执行
+    Succeeded,
+    /// 动作执行失败
+    Failed { error: String },
+    /// 重试次数已用完，不再自动恢复
+    Exhausted,
+}
+
+impl RecoveryOutcome {
+    /// 是否需要人工介入
+    pub fn needs_attention(&self) -> bool {
+        !matches!(self, RecoveryOutcome::Succeeded)
+    }
+}
+
+/// 恢复通知文案
+pub fn recovery_message(
+    action: &str,
+    attempt: u32,
+    max_attempts: u32,
+    outcome: &RecoveryOutcome,
+) -> String {
+    match outcome {
+        RecoveryOutcome::Succeeded => {
+            format!("自动恢复（第 {}/{} 次）：{}", attempt, max_attempts, action)
+        }
+        RecoveryOutcome::Failed { error } => format!(
+            "自动恢复失败（第 {}/{} 次）：{}，{}",
+            attempt, max_attempts, action, error
+        ),
+        RecoveryOutcome::Exhausted => format!(
+            "自动恢复已达上限（{} 次），需要手动处理：{}",
+            max_attempts, action
+        ),
+    }
+}
+
+/// 安排结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleResult {
+    /// 已安排，将在 delay_secs 秒后执行第 attempt 次
+    Scheduled { attempt: u32, delay_secs: u64 },
+    /// 已有待执行的恢复动作
+    AlreadyPending,
+    /// 重试次数已用完
+    Exhausted { attempts: u32 },
+}
+
+#[derive(Debug, Clone)]
+struct PendingRecovery {
+    action: RecoveryAction,
+    due_at: u64,
+    attempt: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AttemptHistory {
+    count: u32,
+    last_at: u64,
+}
+
+/// 待执行的恢复动作
+#[derive(Debug, Clone)]
+pub struct DueRecovery {
+    pub agent_id: String,
+    pub action: RecoveryAction,
+    pub attempt: u32,
+}
+
+/// 恢复编排：退避、重试计数
+pub struct RecoveryManager {
+    policy: RecoveryPolicy,
+    pending: HashMap<String, PendingRecovery>,
+    attempts: HashMap<String, AttemptHistory>,
+}
+
+impl RecoveryManager {
+    pub fn new(policy: RecoveryPolicy) -> Self {
+        Self {
+            policy,
+            pending: HashMap::new(),
+            attempts: HashMap::new(),
+        }
+    }
+
+    /// 替换策略，保留进行中的恢复和尝试记录
+    pub fn set_policy(&mut self, policy: RecoveryPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn policy(&self) -> &RecoveryPolicy {
+        &self.policy
+    }
+
+    /// 安排一次恢复动作
+    pub fn schedule(&mut self, agent_id: &str, action: RecoveryAction, now: u64) -> ScheduleResult {
+        if self.pending.contains_key(agent_id) {
+            return ScheduleResult::AlreadyPending;
+        }
+        let used = match self.attempts.get(agent_id) {
+            Some(history) if now.saturating_sub(history.last_at) < RESET_AFTER_SECS => {
+                history.count
+            }
+            _ => 0,
+        };
+        if used >= self.policy.max_retries {
+            return ScheduleResult::Exhausted { attempts: used };
+        }
+
+        let attempt = used + 1;
+        let delay_secs = self.policy.backoff(attempt);
+        self.attempts.insert(
+            agent_id.to_string(),
+            AttemptHistory {
+                count: attempt,
+                last_at: now,
+            },
+        );
+        self.pending.insert(
+            agent_id.to_string(),
+            PendingRecovery {
+                action,
+                due_at: now + delay_secs,
+                attempt,
+            },
+        );
+        ScheduleResult::Scheduled {
+            attempt,
+            delay_secs,
+        }
+    }
+
+    /// 取出到期的恢复动作
+    pub fn take_due(&mut self, now: u64) -> Vec<DueRecovery> {
+        let due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| p.due_at <= now)
+            .map(|(agent_id, _)| agent_id.clone())
+            .collect();
+        due.into_iter()
+            .filter_map(|agent_id| {
+                let pending = self.pending.remove(&agent_id)?;
+                // 执行时刻作为计数清零的起点
+                if let Some(history) = self.attempts.get_mut(&agent_id) {
+                    history.last_at = now;
+                }
+                Some(DueRecovery {
+                    agent_id,
+                    action: pending.action,
+                    attempt: pending.attempt,
+                })
+            })
+            .collect()
+    }
+
+    /// 取消 agent 的待执行动作（如 agent 已恢复或被手动停止）
+    pub fn cancel(&mut self, agent_id: &str) {
+        self.pending.remove(agent_id);
+    }
+
+    /// 是否有待执行的恢复动作
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resend() -> RecoveryAction {
+        RecoveryAction::ResendPrompt {
+            prompt: "继续".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_policy_from_json() {
+        let policy =
+            RecoveryPolicy::from_json(&json!({"restart_on_exit": true, "backoff_secs": 10}));
+        assert!(policy.is_enabled());
+        assert!(!policy.resend_on_overload);
+        assert_eq!(policy.max_retries, DEFAULT_MAX_RETRIES);
+        assert_eq!(policy.backoff(1), 10);
+        assert_eq!(policy.backoff(3), 40);
+        assert_eq!(
+            RecoveryPolicy {
+                backoff_secs: 1000,
+                ..policy
+            }
+            .backoff(5),
+            MAX_BACKOFF_SECS
+        );
+        assert!(!RecoveryPolicy::from_json(&json!({})).is_enabled());
+    }
+
+    #[test]
+    fn test_schedule_backoff_and_retry_limit() {
+        let mut manager = RecoveryManager::new(RecoveryPolicy {
+            resend_on_overload: true,
+            max_retries: 2,
+            backoff_secs: 10,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            manager.schedule("cam-1", resend(), 100),
+            ScheduleResult::Scheduled {
+                attempt: 1,
+                delay_secs: 10
+            }
+        );
+        assert_eq!(
+            manager.schedule("cam-1", resend(), 101),
+            ScheduleResult::AlreadyPending
+        );
+        assert!(manager.take_due(105).is_empty());
+        let due = manager.take_due(110);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].attempt, 1);
+
+        assert_eq!(
+            manager.schedule("cam-1", resend(), 120),
+            ScheduleResult::Scheduled {
+                attempt: 2,
+                delay_secs: 20
+            }
+        );
+        assert_eq!(manager.take_due(140).len(), 1);
+        assert_eq!(
+            manager.schedule("cam-1", resend(), 150),
+            ScheduleResult::Exhausted { attempts: 2 }
+        );
+        // 长时间没有再次故障后重新计数
+        assert!(matches!(
+            manager.schedule("cam-1", resend(), 140 + RESET_AFTER_SECS),
+            ScheduleResult::Scheduled { attempt: 1, .. }
+        ));
+        assert!(manager.has_pending());
+        manager.cancel("cam-1");
+        assert!(!manager.has_pending());
+        assert!(manager.take_due(u64::MAX).is_empty());
+    }
+}
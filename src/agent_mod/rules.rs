@@ -0,0 +1,227 @@
+//! 自定义监控规则 - 终端出现匹配正则的新内容时通知或标记任务完成
+//!
+//! 在 config.json 的 `watch_rules` 中配置：
+//!
+//! ```json
+//! "watch_rules": [
+//!   {"pattern": "DEPLOY COMPLETE", "urgency": "high"},
+//!   {"name": "tests", "pattern": "All tests passed", "action": "mark_done", "agent": "cam-*"}
+//! ]
+//! ```
+//!
+//! 规则只对新出现的行生效：watcher 记录每个 agent 上一轮屏幕各行的出现次数，首次看到的屏幕
+//! 只作为基线；每条规则每轮最多匹配一行。匹配结果作为 `WatchEvent::RuleMatched` 走通知管道，
+//! `mark_done` 还会把该 agent 对应的 Team 成员进行中的任务标记为完成。
+
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::agent::engine::glob_match;
+
+/// 匹配后的动作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// 发送通知
+    #[default]
+    Notify,
+    /// 标记 Team 成员进行中的任务为完成（并通知）
+    MarkDone,
+}
+
+/// 一条监控规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchRule {
+    /// 规则名（默认使用 pattern）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 正则表达式，逐行匹配
+    pub pattern: String,
+    /// 只对匹配该 glob 的 agent_id 生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// 通知紧急程度：high、medium（默认）、low
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub urgency: Option<String>,
+    #[serde(default)]
+    pub action: RuleAction,
+}
+
+impl WatchRule {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.pattern)
+    }
+
+    /// 通知类型，决定紧急程度（`rule_alert` 为 HIGH，`rule_match` 为 MEDIUM，`rule_info` 为 LOW）
+    pub fn notification_type(&self) -> &'static str {
+        match self.urgency.as_deref().map(str::to_lowercase).as_deref() {
+            Some("high") => "rule_alert",
+            Some("low") => "rule_info",
+            _ => "rule_match",
+        }
+    }
+}
+
+/// 一次规则匹配
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule: WatchRule,
+    /// 匹配到的行（已去掉首尾空白）
+    pub line: String,
+}
+
+/// 读取 config.json 的 `watch_rules`，缺失或无效时为空
+pub fn load_watch_rules() -> Vec<WatchRule> {
+    crate::infra::paths::config_section("watch_rules").unwrap_or_default()
+}
+
+/// 每个 agent 上一轮屏幕各行的出现次数
+type LineCounts = HashMap<String, usize>;
+
+fn line_counts(screen: &str) -> LineCounts {
+    let mut counts = HashMap::new();
+    for line in screen.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *counts.entry(line.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// 按 agent 跟踪屏幕新内容并匹配规则
+#[derive(Debug)]
+pub struct RuleTracker {
+    rules: Vec<(WatchRule, Regex)>,
+    previous: HashMap<String, LineCounts>,
+}
+
+impl RuleTracker {
+    /// 编译规则，无效的正则记录警告后跳过；没有可用规则时返回 None
+    pub fn new(rules: Vec<WatchRule>) -> Option<Self> {
+        let rules: Vec<(WatchRule, Regex)> = rules
+            .into_iter()
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some((rule, regex)),
+                Err(e) => {
+                    warn!(pattern = %rule.pattern, error = %e, "Invalid watch rule pattern");
+                    None
+                }
+            })
+            .collect();
+        (!rules.is_empty()).then(|| Self {
+            rules,
+            previous: HashMap::new(),
+        })
+    }
+
+    /// 从 config.json 加载
+    pub fn load() -> Option<Self> {
+        Self::new(load_watch_rules())
+    }
+
+    /// 匹配本轮新出现的行（首次看到 agent 时只记录基线）
+    pub fn check(&mut self, agent_id: &str, screen: &str) -> Vec<RuleMatch> {
+        let current = line_counts(screen);
+        let Some(previous) = self.previous.insert(agent_id.to_string(), current) else {
+            return Vec::new();
+        };
+        let current = &self.previous[agent_id];
+
+        // 按屏幕顺序取出比上一轮多出来的行
+        let mut remaining: HashMap<&str, usize> = current
+            .iter()
+            .map(|(line, count)| {
+                let before = previous.get(line).copied().unwrap_or(0);
+                (line.as_str(), count.saturating_sub(before))
+            })
+            .filter(|(_, added)| *added > 0)
+            .collect();
+        let mut new_lines = Vec::new();
+        for line in screen.lines().map(str::trim).rev() {
+            if let Some(added) = remaining.get_mut(line).filter(|n| **n > 0) {
+                *added -= 1;
+                new_lines.push(line);
+            }
+        }
+        new_lines.reverse();
+
+        self.rules
+            .iter()
+            .filter(|(rule, _)| {
+                rule.agent
+                    .as_deref()
+                    .is_none_or(|p| glob_match(p, agent_id))
+            })
+            .filter_map(|(rule, regex)| {
+                let line = new_lines.iter().find(|line| regex.is_match(line))?;
+                Some(RuleMatch {
+                    rule: rule.clone(),
+                    line: line.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// 清除 agent 的基线（已退出）
+    pub fn remove(&mut self, agent_id: &str) {
+        self.previous.remove(agent_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<WatchRule> {
+        serde_json::from_value(serde_json::json!([
+            {"pattern": "DEPLOY COMPLETE", "urgency": "high"},
+            {"name": "tests", "pattern": "All tests passed", "action": "mark_done", "agent": "cam-api*"},
+            {"pattern": "(unclosed"}
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_watch_rule_config() {
+        let rules = rules();
+        assert_eq!(rules[0].notification_type(), "rule_alert");
+        assert_eq!(rules[0].action, RuleAction::Notify);
+        assert_eq!(rules[1].display_name(), "tests");
+        assert_eq!(rules[1].notification_type(), "rule_match");
+        assert_eq!(rules[1].action, RuleAction::MarkDone);
+        // 无效正则被跳过
+        assert_eq!(RuleTracker::new(rules).unwrap().rules.len(), 2);
+        assert!(RuleTracker::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_rule_tracker_matches_only_new_lines() {
+        let mut tracker = RuleTracker::new(rules()).unwrap();
+
+        // 首次看到的屏幕只作为基线
+        let old = "$ ./deploy.sh\nDEPLOY COMPLETE\n❯ ";
+        assert!(tracker.check("cam-api", old).is_empty());
+        assert!(tracker.check("cam-api", old).is_empty());
+
+        let screen = format!(
+            "{}\nAll tests passed\n$ ./deploy.sh\nDEPLOY COMPLETE (v2)",
+            old
+        );
+        let matches = tracker.check("cam-api", &screen);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line, "DEPLOY COMPLETE (v2)");
+        assert_eq!(matches[1].rule.display_name(), "tests");
+
+        // 同一行再次出现（次数增加）也算新内容
+        let again = format!("{}\n❯ \nAll tests passed", screen);
+        let matches = tracker.check("cam-api", &again);
+        assert_eq!(matches.len(), 1);
+
+        // agent glob 不匹配时跳过
+        tracker.check("cam-web", "start");
+        let matches = tracker.check("cam-web", "start\nAll tests passed\nDEPLOY COMPLETE");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line, "DEPLOY COMPLETE");
+    }
+}
@@ -0,0 +1,367 @@
+//! Agent 模拟器 - 在 tmux 中运行脚本化的假 agent
+//!
+//! 按剧本定时输出预设内容（编号选项、确认提示、错误等），并从 stdin 读取回复，
+//! 让集成测试无需真实 AI CLI 即可覆盖 watcher、提取、通知和回复链路。
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::agent::{AgentManager, StartAgentResponse};
+use crate::infra::multiplexer::{self, Multiplexer};
+
+/// 内置剧本名称
+pub const BUILTIN_SCENARIOS: &[&str] = &["choices", "confirm", "error", "mixed"];
+
+/// 剧本步骤
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimStep {
+    /// 输出一段文本（模拟处理中的输出）
+    Output {
+        text: String,
+        #[serde(default)]
+        delay_ms: u64,
+    },
+    /// 输出提示并等待一行回复
+    Prompt {
+        text: String,
+        #[serde(default)]
+        delay_ms: u64,
+    },
+    /// 退出（不再等待输入）
+    Exit {
+        #[serde(default)]
+        delay_ms: u64,
+    },
+}
+
+impl SimStep {
+    fn delay(&self) -> Duration {
+        let ms = match self {
+            SimStep::Output { delay_ms, .. }
+            | SimStep::Prompt { delay_ms, .. }
+            | SimStep::Exit { delay_ms } => *delay_ms,
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+/// 模拟剧本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub steps: Vec<SimStep>,
+}
+
+fn output(text: &str, delay_ms: u64) -> SimStep {
+    SimStep::Output {
+        text: text.to_string(),
+        delay_ms,
+    }
+}
+
+fn prompt(text: &str, delay_ms: u64) -> SimStep {
+    SimStep::Prompt {
+        text: text.to_string(),
+        delay_ms,
+    }
+}
+
+const CHOICES_PROMPT: &str = "Do you want to create hello.txt?\n\
+❯ 1. Yes\n  \
+2. Yes, allow all edits during this session (shift+tab)\n  \
+3. No, and tell Claude what to do differently (esc)";
+
+const CONFIRM_PROMPT: &str = "Bash command\n\n  \
+rm -rf target/debug\n  \
+Clean build artifacts\n\n\
+Allow this command to run? [Y/n]";
+
+const ERROR_OUTPUT: &str = "⏺ Bash(cargo test)\n  \
+⎿  Error: test result: FAILED. 3 passed; 1 failed\n\n\
+⏺ 测试失败：tests::test_parse 断言不通过。";
+
+const IDLE_PROMPT: &str = "❯ ";
+
+impl Scenario {
+    /// 获取内置剧本
+    pub fn builtin(name: &str) -> Option<Scenario> {
+        let steps = match name {
+            "choices" => vec![
+                output("⏺ 正在分析项目结构...", 500),
+                prompt(CHOICES_PROMPT, 1500),
+                output("⏺ 已创建 hello.txt", 500),
+                prompt(IDLE_PROMPT, 500),
+            ],
+            "confirm" => vec![
+                output("⏺ 准备清理构建产物...", 500),
+                prompt(CONFIRM_PROMPT, 1500),
+                output("⏺ 已清理", 500),
+                prompt(IDLE_PROMPT, 500),
+            ],
+            "error" => vec![
+                output("⏺ 正在运行测试...", 500),
+                output(ERROR_OUTPUT, 2000),
+                prompt("需要我修复这个测试吗？", 500),
+                prompt(IDLE_PROMPT, 500),
+            ],
+            "mixed" => vec![
+                output("⏺ 正在分析项目结构...", 500),
+                prompt(CHOICES_PROMPT, 1500),
+                output("⏺ 已创建 hello.txt，正在运行测试...", 1000),
+                output(ERROR_OUTPUT, 2000),
+                prompt(CONFIRM_PROMPT, 1000),
+                output("⏺ 已清理，全部完成", 500),
+                prompt(IDLE_PROMPT, 500),
+            ],
+            _ => return None,
+        };
+        Some(Scenario {
+            name: name.to_string(),
+            steps,
+        })
+    }
+
+    /// 加载剧本：内置名称或 JSON 剧本文件路径
+    pub fn load(spec: &str) -> Result<Scenario> {
+        if let Some(scenario) = Self::builtin(spec) {
+            return Ok(scenario);
+        }
+        let path = Path::new(spec);
+        if !path.exists() {
+            return Err(anyhow!(
+                "未知剧本: {}（内置剧本: {}，或指定 JSON 剧本文件）",
+                spec,
+                BUILTIN_SCENARIOS.join(", ")
+            ));
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("读取剧本失败: {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("剧本格式错误: {}", path.display()))
+    }
+}
+
+/// 在当前终端运行剧本，返回收到的回复
+///
+/// 剧本结束后（未遇到 Exit）继续回显输入直到 EOF，保持 session 存活以便观察。
+pub fn run_scenario<R: BufRead, W: Write>(
+    scenario: &Scenario,
+    mut input: R,
+    mut out: W,
+) -> Result<Vec<String>> {
+    let mut replies = Vec::new();
+
+    let mut read_reply = |out: &mut W, replies: &mut Vec<String>| -> Result<bool> {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(false);
+        }
+        let reply = line.trim_end_matches(['\r', '\n']).to_string();
+        writeln!(out, "> {}", reply)?;
+        out.flush()?;
+        replies.push(reply);
+        Ok(true)
+    };
+
+    for step in &scenario.steps {
+        std::thread::sleep(step.delay());
+        match step {
+            SimStep::Output { text, .. } => {
+                writeln!(out, "{}", text)?;
+                out.flush()?;
+            }
+            SimStep::Prompt { text, .. } => {
+                writeln!(out, "{}", text)?;
+                out.flush()?;
+                if !read_reply(&mut out, &mut replies)? {
+                    return Ok(replies);
+                }
+            }
+            SimStep::Exit { .. } => return Ok(replies),
+        }
+    }
+
+    while read_reply(&mut out, &mut replies)? {}
+    Ok(replies)
+}
+
+/// 剧本文件转为绝对路径（tmux 中的工作目录可能不同），内置名称原样返回
+pub fn resolve_scenario_spec(spec: &str) -> Result<String> {
+    if Scenario::builtin(spec).is_some() {
+        return Ok(spec.to_string());
+    }
+    Ok(std::fs::canonicalize(spec)
+        .with_context(|| format!("未知剧本: {}", spec))?
+        .to_string_lossy()
+        .to_string())
+}
+
+/// 生成在 tmux 中运行模拟 agent 的命令
+pub fn simulate_command(exe: &Path, scenario_spec: &str) -> String {
+    format!(
+        "{} simulate --foreground --scenario {}",
+        shell_quote(&exe.to_string_lossy()),
+        shell_quote(scenario_spec)
+    )
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 运行中的模拟 agent（测试辅助）
+///
+/// drop 时自动停止 agent 并清理记录。
+pub struct SimulatedAgent {
+    pub agent_id: String,
+    pub tmux_session: String,
+    manager: AgentManager,
+    tmux: Arc<dyn Multiplexer>,
+}
+
+impl SimulatedAgent {
+    /// 在 tmux 中启动模拟 agent 并注册为 mock 类型
+    ///
+    /// `exe` 为 cam 可执行文件路径（集成测试中使用 `env!("CARGO_BIN_EXE_cam")`）。
+    pub fn spawn(
+        manager: AgentManager,
+        exe: &Path,
+        scenario_spec: &str,
+        project_path: &str,
+    ) -> Result<Self> {
+        let spec = resolve_scenario_spec(scenario_spec)?;
+        let StartAgentResponse {
+            agent_id,
+            tmux_session,
+            ..
+        } = manager
+            .start_agent_with_command(project_path.to_string(), &simulate_command(exe, &spec))?;
+        Ok(Self {
+            agent_id,
+            tmux_session,
+            manager,
+            tmux: multiplexer::from_config(),
+        })
+    }
+
+    /// 捕获当前终端输出
+    pub fn capture(&self) -> Result<String> {
+        self.tmux.capture_pane(&self.tmux_session, 50)
+    }
+
+    /// 等待终端输出包含指定文本
+    pub fn wait_for_output(&self, needle: &str, timeout: Duration) -> Result<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let content = self.capture()?;
+            if content.contains(needle) {
+                return Ok(content);
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!("等待输出超时: {:?}\n{}", needle, content));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// 直接向模拟 agent 发送一行输入
+    pub fn send(&self, text: &str) -> Result<()> {
+        self.tmux.send_keys(&self.tmux_session, text)
+    }
+
+    /// 模拟 agent 使用的 AgentManager
+    pub fn manager(&self) -> &AgentManager {
+        &self.manager
+    }
+}
+
+impl Drop for SimulatedAgent {
+    fn drop(&mut self) {
+        let _ = self.manager.stop_agent(&self.agent_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn scenario(steps: Vec<SimStep>) -> Scenario {
+        Scenario {
+            name: "test".to_string(),
+            steps,
+        }
+    }
+
+    #[test]
+    fn test_builtin_scenarios_exist() {
+        for name in BUILTIN_SCENARIOS {
+            let scenario = Scenario::builtin(name).unwrap();
+            assert!(scenario
+                .steps
+                .iter()
+                .any(|s| matches!(s, SimStep::Prompt { .. })));
+        }
+        assert!(Scenario::builtin("nope").is_none());
+        assert!(Scenario::load("nope").is_err());
+    }
+
+    #[test]
+    fn test_run_scenario_collects_replies() {
+        let scenario = scenario(vec![
+            output("working", 0),
+            prompt("Continue? [Y/n]", 0),
+            prompt("Pick 1/2", 0),
+        ]);
+        let mut out = Vec::new();
+        let replies = run_scenario(&scenario, Cursor::new("y\n2\nextra\n"), &mut out).unwrap();
+
+        assert_eq!(replies, vec!["y", "2", "extra"]);
+        let printed = String::from_utf8(out).unwrap();
+        assert!(printed.starts_with("working\nContinue? [Y/n]\n> y\n"));
+        assert!(printed.contains("Pick 1/2\n> 2\n"));
+    }
+
+    #[test]
+    fn test_run_scenario_exit_and_eof() {
+        let exits = scenario(vec![prompt("a?", 0), SimStep::Exit { delay_ms: 0 }]);
+        let replies = run_scenario(&exits, Cursor::new("1\n2\n"), Vec::new()).unwrap();
+        assert_eq!(replies, vec!["1"]);
+
+        // 输入提前结束时停止，不再输出后续步骤
+        let eof = scenario(vec![prompt("a?", 0), output("after", 0)]);
+        let mut out = Vec::new();
+        assert!(run_scenario(&eof, Cursor::new(""), &mut out)
+            .unwrap()
+            .is_empty());
+        assert!(!String::from_utf8(out).unwrap().contains("after"));
+    }
+
+    #[test]
+    fn test_load_scenario_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.json");
+        std::fs::write(
+            &path,
+            r#"{"name":"custom","steps":[{"kind":"output","text":"hi"},{"kind":"prompt","text":"ok?","delay_ms":10}]}"#,
+        )
+        .unwrap();
+
+        let scenario = Scenario::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(scenario.name, "custom");
+        assert_eq!(scenario.steps[1], prompt("ok?", 10));
+    }
+
+    #[test]
+    fn test_simulate_command_quotes_arguments() {
+        let command = simulate_command(Path::new("/opt/my cam/cam"), "/tmp/it's.json");
+        assert_eq!(
+            command,
+            r"'/opt/my cam/cam' simulate --foreground --scenario '/tmp/it'\''s.json'"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! 卡住检测 - agent 长时间处于 Processing 且屏幕无变化时提醒
+//!
+//! API 调用挂起时 agent 一直显示处理中，等待检测认为它在工作而保持沉默。
+//! Watcher 在 AI 判定为 Processing 后继续计时，屏幕内容（忽略动画行）超过阈值未变化时
+//! 发送一条 MEDIUM 通知，直到内容变化前不再重复。
+//!
+//! 阈值由 config.json 的 `stuck_after_mins` 设置（默认 10 分钟，0 表示关闭）。
+
+/// 默认卡住阈值（秒）
+pub const STUCK_THRESHOLD_SECS: u64 = 600;
+
+/// 最后操作的最大长度（字符数）
+const MAX_ACTION_CHARS: usize = 120;
+
+/// 读取卡住阈值（秒），None 表示关闭
+pub fn stuck_threshold_secs() -> Option<u64> {
+    let configured = crate::infra::paths::config_section::<u64>("stuck_after_mins");
+    match configured {
+        Some(0) => None,
+        Some(mins) => Some(mins * 60),
+        None => Some(STUCK_THRESHOLD_SECS),
+    }
+}
+
+/// 提取屏幕上最后一个可见操作
+///
+/// 优先取 Claude Code 的工具调用行（`⏺ Bash(cargo test)`），否则取最后一个非提示符行。
+pub fn last_visible_action(screen: &str) -> Option<String> {
+    let lines: Vec<&str> = screen
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let action = lines
+        .iter()
+        .rev()
+        .find_map(|line| line.strip_prefix('⏺'))
+        .or_else(|| {
+            lines.iter().rev().copied().find(|line| {
+                !line.starts_with('❯')
+                    && !line.starts_with('>')
+                    && line.chars().any(|c| c.is_alphanumeric())
+            })
+        })?
+        .trim();
+    if action.is_empty() {
+        return None;
+    }
+    Some(crate::infra::truncate_str(action, MAX_ACTION_CHARS))
+}
+
+/// 把秒数格式化为"12 分钟"、"1 小时 5 分钟"
+pub fn format_elapsed(secs: u64) -> String {
+    let mins = secs / 60;
+    if mins < 60 {
+        format!("{} 分钟", mins.max(1))
+    } else if mins.is_multiple_of(60) {
+        format!("{} 小时", mins / 60)
+    } else {
+        format!("{} 小时 {} 分钟", mins / 60, mins % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_visible_action() {
+        let screen = "⏺ Read(src/main.rs)\n  ⎿  Read 120 lines\n\n⏺ Bash(cargo test --workspace)\n  ⎿  Running…\n\n✻ Thinking… (esc to interrupt)\n";
+        assert_eq!(
+            last_visible_action(screen).as_deref(),
+            Some("Bash(cargo test --workspace)")
+        );
+        assert_eq!(
+            last_visible_action("building crate\n❯ \n").as_deref(),
+            Some("building crate")
+        );
+        assert_eq!(last_visible_action("\n❯ \n"), None);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(30), "1 分钟");
+        assert_eq!(format_elapsed(720), "12 分钟");
+        assert_eq!(format_elapsed(3600), "1 小时");
+        assert_eq!(format_elapsed(3900), "1 小时 5 分钟");
+    }
+}
@@ -0,0 +1,147 @@
+//! Claude Code TODO 列表 - 从 transcript 中的 TodoWrite 工具调用解析当前进度
+//!
+//! TodoWrite 每次调用都携带完整列表，最后一次调用即为当前状态。watcher 解析到新的
+//! TodoWrite 时写入 agent 记录（[`AgentRecord::todos`]），`cam info`、TUI 和完成类通知
+//! 据此显示 "4/6 todos done"，无需 attach 查看。
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentRecord;
+use crate::infra::jsonl::{JsonlEvent, JsonlParser};
+
+/// Claude Code 维护 TODO 列表的工具名
+pub const TODO_TOOL: &str = "TodoWrite";
+
+/// TODO 状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+impl TodoStatus {
+    /// 列表中的状态标记
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Completed => "[x]",
+        }
+    }
+}
+
+/// 一条 TODO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub content: String,
+    pub status: TodoStatus,
+    /// 进行中时显示的描述（TodoWrite 的 `activeForm`）
+    #[serde(
+        default,
+        rename = "activeForm",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_form: Option<String>,
+}
+
+/// TODO 完成进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl TodoProgress {
+    /// 列表为空时为 None
+    pub fn of(todos: &[TodoItem]) -> Option<Self> {
+        (!todos.is_empty()).then(|| Self {
+            done: todos
+                .iter()
+                .filter(|todo| todo.status == TodoStatus::Completed)
+                .count(),
+            total: todos.len(),
+        })
+    }
+}
+
+impl fmt::Display for TodoProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} todos done", self.done, self.total)
+    }
+}
+
+/// 解析 TodoWrite 的输入，格式不符时为 None
+pub fn parse_todo_write(input: &serde_json::Value) -> Option<Vec<TodoItem>> {
+    serde_json::from_value(input.get("todos")?.clone()).ok()
+}
+
+/// 事件中最后一次 TodoWrite 的列表
+pub fn latest_todos(events: &[JsonlEvent]) -> Option<Vec<TodoItem>> {
+    events.iter().rev().find_map(|event| match event {
+        JsonlEvent::ToolUse {
+            tool_name, input, ..
+        } if tool_name == TODO_TOOL => parse_todo_write(input),
+        _ => None,
+    })
+}
+
+/// agent 当前的 TODO 列表：优先使用 watcher 写入记录的列表，没有时读取 transcript
+pub fn agent_todos(agent: &AgentRecord) -> Vec<TodoItem> {
+    if !agent.todos.is_empty() {
+        return agent.todos.clone();
+    }
+    agent
+        .jsonl_path
+        .as_deref()
+        .and_then(|path| JsonlParser::new(path).read_all_events().ok())
+        .and_then(|events| latest_todos(&events))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_todos_and_progress() {
+        let todo_write = |todos: serde_json::Value| JsonlEvent::ToolUse {
+            tool_name: TODO_TOOL.to_string(),
+            tool_id: "t".to_string(),
+            input: serde_json::json!({ "todos": todos }),
+            timestamp: None,
+        };
+        let events = vec![
+            todo_write(serde_json::json!([
+                {"content": "写测试", "status": "pending", "activeForm": "正在写测试"}
+            ])),
+            todo_write(serde_json::json!([
+                {"content": "写测试", "status": "completed", "activeForm": "正在写测试"},
+                {"content": "修复登录", "status": "in_progress", "activeForm": "正在修复登录"},
+                {"content": "更新文档", "status": "pending"}
+            ])),
+            JsonlEvent::ToolUse {
+                tool_name: "Bash".to_string(),
+                tool_id: "b".to_string(),
+                input: serde_json::json!({"command": "cargo test"}),
+                timestamp: None,
+            },
+        ];
+
+        let todos = latest_todos(&events).unwrap();
+        assert_eq!(todos.len(), 3);
+        assert_eq!(todos[1].status, TodoStatus::InProgress);
+        assert_eq!(todos[1].active_form.as_deref(), Some("正在修复登录"));
+        assert!(todos[2].active_form.is_none());
+        assert_eq!(
+            TodoProgress::of(&todos).unwrap().to_string(),
+            "1/3 todos done"
+        );
+        assert!(TodoProgress::of(&[]).is_none());
+        assert!(latest_todos(&events[2..]).is_none());
+        assert!(parse_todo_write(&serde_json::json!({"todos": "bad"})).is_none());
+    }
+}
@@ -0,0 +1,349 @@
+//! 工具调用批次汇总 - 把连续的工具调用合并为一条摘要
+//!
+//! Watcher 从 JSONL 收集每个 agent 的工具调用和结果，每个批次最多每
+//! `TOOL_BATCH_INTERVAL_SECS` 秒汇总一次，例如"编辑 7 个文件（src/），运行测试 2 次（1 次失败）"。
+//! 需要在 config.json 中设置 `"tool_batch_summary": true` 开启。
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::infra::jsonl::JsonlEvent;
+
+/// 同一 agent 两次汇总的最小间隔（秒）
+pub const TOOL_BATCH_INTERVAL_SECS: u64 = 600;
+
+/// 视为"运行测试"的命令片段
+const TEST_COMMANDS: &[&str] = &[
+    "cargo test",
+    "cargo nextest",
+    "npm test",
+    "npm run test",
+    "pnpm test",
+    "yarn test",
+    "bun test",
+    "pytest",
+    "go test",
+    "jest",
+    "vitest",
+    "make test",
+];
+
+/// 判断 Bash 命令是否在运行测试
+pub fn is_test_command(command: &str) -> bool {
+    TEST_COMMANDS.iter().any(|t| command.contains(t))
+}
+
+/// 一个 agent 的工具调用批次
+#[derive(Debug, Clone, Default)]
+pub struct ToolBatch {
+    /// 批次开始时间（Unix 秒）
+    started_at: u64,
+    /// 工具调用总数
+    count: usize,
+    /// 按首次出现顺序的工具名
+    tools: Vec<String>,
+    edited_files: BTreeSet<String>,
+    read_files: BTreeSet<String>,
+    commands: usize,
+    test_runs: usize,
+    test_failures: usize,
+    other_failures: usize,
+    /// 等待结果的测试命令 tool_id
+    pending_tests: HashSet<String>,
+    /// 等待结果的其他工具 tool_id
+    pending_tools: HashSet<String>,
+}
+
+impl ToolBatch {
+    fn new(now: u64) -> Self {
+        Self {
+            started_at: now,
+            ..Default::default()
+        }
+    }
+
+    /// 工具调用总数
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// 是否没有工具调用
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// 按首次出现顺序的工具名
+    pub fn tools(&self) -> &[String] {
+        &self.tools
+    }
+
+    fn record_tool_use(&mut self, tool_name: &str, tool_id: &str, input: &serde_json::Value) {
+        self.count += 1;
+        if !self.tools.iter().any(|t| t == tool_name) {
+            self.tools.push(tool_name.to_string());
+        }
+        let file_path = input.get("file_path").and_then(|p| p.as_str());
+        match (tool_name, file_path) {
+            ("Edit" | "Write" | "MultiEdit" | "NotebookEdit", Some(path)) => {
+                self.edited_files.insert(path.to_string());
+            }
+            ("Read", Some(path)) => {
+                self.read_files.insert(path.to_string());
+            }
+            ("Bash", _) => {
+                let command = input.get("command").and_then(|c| c.as_str()).unwrap_or("");
+                if is_test_command(command) {
+                    self.test_runs += 1;
+                    self.pending_tests.insert(tool_id.to_string());
+                    return;
+                }
+                self.commands += 1;
+            }
+            _ => {}
+        }
+        self.pending_tools.insert(tool_id.to_string());
+    }
+
+    fn record_tool_result(&mut self, tool_id: &str, success: bool) {
+        if self.pending_tests.remove(tool_id) {
+            if !success {
+                self.test_failures += 1;
+            }
+        } else if self.pending_tools.remove(tool_id) && !success {
+            self.other_failures += 1;
+        }
+    }
+
+    /// 生成一行中文摘要，文件路径相对于 `project_path` 显示公共目录
+    pub fn summary(&self, project_path: &str) -> String {
+        let mut parts = Vec::new();
+
+        if !self.edited_files.is_empty() {
+            match common_dir(&self.edited_files, project_path) {
+                Some(dir) => parts.push(format!(
+                    "编辑 {} 个文件（{}/）",
+                    self.edited_files.len(),
+                    dir
+                )),
+                None => parts.push(format!("编辑 {} 个文件", self.edited_files.len())),
+            }
+        }
+        if self.test_runs > 0 {
+            if self.test_failures > 0 {
+                parts.push(format!(
+                    "运行测试 {} 次（{} 次失败）",
+                    self.test_runs, self.test_failures
+                ));
+            } else {
+                parts.push(format!("运行测试 {} 次", self.test_runs));
+            }
+        }
+        if self.commands > 0 {
+            parts.push(format!("执行 {} 条命令", self.commands));
+        }
+        if !self.read_files.is_empty() {
+            parts.push(format!("读取 {} 个文件", self.read_files.len()));
+        }
+        if self.other_failures > 0 {
+            parts.push(format!("{} 次工具调用失败", self.other_failures));
+        }
+        if parts.is_empty() {
+            parts.push(format!("使用 {}", self.tools.join(", ")));
+        }
+
+        format!("{}（共 {} 次工具调用）", parts.join("，"), self.count)
+    }
+}
+
+/// 文件的公共父目录（相对于项目路径），无公共目录时返回 None
+fn common_dir(files: &BTreeSet<String>, project_path: &str) -> Option<String> {
+    let prefix = format!("{}/", project_path.trim_end_matches('/'));
+    let mut common: Option<Vec<&str>> = None;
+    for file in files {
+        let relative = file.strip_prefix(&prefix).unwrap_or(file);
+        let mut dirs: Vec<&str> = relative.split('/').filter(|s| !s.is_empty()).collect();
+        dirs.pop();
+        common = Some(match common {
+            None => dirs,
+            Some(prev) => prev
+                .into_iter()
+                .zip(dirs)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    common
+        .filter(|dirs| !dirs.is_empty())
+        .map(|dirs| dirs.join("/"))
+}
+
+/// 所有 agent 的工具调用批次
+#[derive(Debug, Clone)]
+pub struct ToolBatchTracker {
+    batches: HashMap<String, ToolBatch>,
+    interval_secs: u64,
+}
+
+impl ToolBatchTracker {
+    pub fn new(interval_secs: u64) -> Self {
+        Self {
+            batches: HashMap::new(),
+            interval_secs,
+        }
+    }
+
+    /// 记录一条 JSONL 事件（只关心工具调用和结果）
+    pub fn record(&mut self, agent_id: &str, event: &JsonlEvent, now: u64) {
+        match event {
+            JsonlEvent::ToolUse {
+                tool_name,
+                tool_id,
+                input,
+                ..
+            } => self
+                .batches
+                .entry(agent_id.to_string())
+                .or_insert_with(|| ToolBatch::new(now))
+                .record_tool_use(tool_name, tool_id, input),
+            JsonlEvent::ToolResult {
+                tool_id, success, ..
+            } => {
+                if let Some(batch) = self.batches.get_mut(agent_id) {
+                    batch.record_tool_result(tool_id, *success);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 批次已满汇总间隔时取出
+    pub fn take_due(&mut self, agent_id: &str, now: u64) -> Option<ToolBatch> {
+        let due = self.batches.get(agent_id).is_some_and(|batch| {
+            !batch.is_empty() && now.saturating_sub(batch.started_at) >= self.interval_secs
+        });
+        if due {
+            self.batches.remove(agent_id)
+        } else {
+            None
+        }
+    }
+
+    /// agent 退出时丢弃批次
+    pub fn remove(&mut self, agent_id: &str) {
+        self.batches.remove(agent_id);
+    }
+}
+
+/// 是否开启工具调用批次汇总
+///
+/// 读取 `~/.config/code-agent-monitor/config.json` 的 `tool_batch_summary`（默认关闭）。
+pub fn tool_batch_summary_enabled() -> bool {
+    crate::infra::paths::config_section::<bool>("tool_batch_summary").unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(name: &str, id: &str, input: serde_json::Value) -> JsonlEvent {
+        JsonlEvent::ToolUse {
+            tool_name: name.to_string(),
+            tool_id: id.to_string(),
+            input,
+            timestamp: None,
+        }
+    }
+
+    fn tool_result(id: &str, success: bool) -> JsonlEvent {
+        JsonlEvent::ToolResult {
+            tool_id: id.to_string(),
+            success,
+            output: None,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_summary_collapses_tool_uses() {
+        let mut tracker = ToolBatchTracker::new(600);
+        let project = "/work/app";
+        for (i, file) in ["src/a.rs", "src/b.rs", "src/cli/c.rs", "src/a.rs"]
+            .iter()
+            .enumerate()
+        {
+            tracker.record(
+                "cam-1",
+                &tool_use(
+                    "Edit",
+                    &format!("e{}", i),
+                    json!({"file_path": format!("{}/{}", project, file)}),
+                ),
+                100,
+            );
+        }
+        tracker.record(
+            "cam-1",
+            &tool_use("Bash", "t1", json!({"command": "cargo test"})),
+            100,
+        );
+        tracker.record("cam-1", &tool_result("t1", false), 100);
+        tracker.record(
+            "cam-1",
+            &tool_use("Bash", "t2", json!({"command": "cargo test -q"})),
+            100,
+        );
+        tracker.record("cam-1", &tool_result("t2", true), 100);
+        tracker.record(
+            "cam-1",
+            &tool_use("Bash", "b1", json!({"command": "git status"})),
+            100,
+        );
+
+        // 未到间隔
+        assert!(tracker.take_due("cam-1", 300).is_none());
+
+        let batch = tracker.take_due("cam-1", 700).unwrap();
+        assert_eq!(batch.len(), 7);
+        assert_eq!(batch.tools(), ["Edit", "Bash"]);
+        assert_eq!(
+            batch.summary(project),
+            "编辑 3 个文件（src/），运行测试 2 次（1 次失败），执行 1 条命令（共 7 次工具调用）"
+        );
+        // 取出后重新开始
+        assert!(tracker.take_due("cam-1", 2000).is_none());
+    }
+
+    #[test]
+    fn test_summary_without_common_dir() {
+        let mut tracker = ToolBatchTracker::new(0);
+        tracker.record(
+            "cam-1",
+            &tool_use("Write", "w1", json!({"file_path": "/p/README.md"})),
+            0,
+        );
+        tracker.record(
+            "cam-1",
+            &tool_use("Write", "w2", json!({"file_path": "/p/src/lib.rs"})),
+            0,
+        );
+        tracker.record("cam-1", &tool_result("w2", false), 0);
+        tracker.record("cam-1", &tool_use("Glob", "g1", json!({"pattern": "*"})), 0);
+        let batch = tracker.take_due("cam-1", 0).unwrap();
+        assert_eq!(
+            batch.summary("/p"),
+            "编辑 2 个文件，1 次工具调用失败（共 3 次工具调用）"
+        );
+
+        tracker.record("cam-2", &tool_use("Glob", "g1", json!({"pattern": "*"})), 0);
+        let batch = tracker.take_due("cam-2", 0).unwrap();
+        assert_eq!(batch.summary("/p"), "使用 Glob（共 1 次工具调用）");
+    }
+
+    #[test]
+    fn test_is_test_command() {
+        assert!(is_test_command("cd app && npm test"));
+        assert!(is_test_command("python -m pytest tests/"));
+        assert!(!is_test_command("cargo build"));
+    }
+}
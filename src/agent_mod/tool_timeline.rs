@@ -0,0 +1,236 @@
+//! 工具调用时间线 - 记录 PreToolUse/PostToolUse/SubagentStop hook 的时间点
+//!
+//! JSONL 轮询只能看到工具调用被写入日志的时间，hook 在执行前后触发，
+//! 可以得到准确的开始时间和耗时。每个 agent 一个文件：
+//! `~/.config/code-agent-monitor/timelines/<agent_id>.jsonl`。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// 单个时间线文件超过此大小时只保留最近的条目
+const MAX_TIMELINE_BYTES: u64 = 512 * 1024;
+
+/// 截断时保留的条目数
+const KEEP_ENTRIES: usize = 1000;
+
+/// 时间线阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPhase {
+    /// PreToolUse
+    Started,
+    /// PostToolUse
+    Finished,
+    /// SubagentStop
+    SubagentStopped,
+}
+
+/// 时间线条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolTimelineEntry {
+    pub phase: ToolPhase,
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    pub at: DateTime<Utc>,
+    /// 工具目标（文件路径、命令等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    /// 与对应 Started 条目的间隔
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// 工具调用时间线存储
+pub struct ToolTimeline {
+    dir: PathBuf,
+}
+
+impl ToolTimeline {
+    pub fn new() -> Self {
+        let dir = crate::infra::paths::state_dir().join("timelines");
+        Self::with_dir(dir)
+    }
+
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, agent_id: &str) -> PathBuf {
+        let safe: String = agent_id
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.jsonl", safe))
+    }
+
+    /// 记录工具开始执行
+    pub fn record_started(
+        &self,
+        agent_id: &str,
+        tool: &str,
+        tool_use_id: Option<&str>,
+        input: &serde_json::Value,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.append(
+            agent_id,
+            &ToolTimelineEntry {
+                phase: ToolPhase::Started,
+                tool: tool.to_string(),
+                tool_use_id: tool_use_id.map(String::from),
+                at,
+                target: crate::infra::jsonl::extract_tool_target_from_input(tool, input),
+                success: None,
+                duration_ms: None,
+            },
+        )
+    }
+
+    /// 记录工具执行完成，返回耗时（找到对应的 Started 条目时）
+    ///
+    /// 有 tool_use_id 时按 id 匹配，否则匹配同名工具最近一次未完成的调用。
+    pub fn record_finished(
+        &self,
+        agent_id: &str,
+        tool: &str,
+        tool_use_id: Option<&str>,
+        success: bool,
+        at: DateTime<Utc>,
+    ) -> Result<Option<u64>> {
+        let entries = self.read(agent_id)?;
+        let started = entries.iter().rev().find(|e| {
+            e.phase == ToolPhase::Started
+                && match tool_use_id {
+                    Some(id) => e.tool_use_id.as_deref() == Some(id),
+                    None => e.tool == tool,
+                }
+        });
+        let duration_ms = started.and_then(|e| (at - e.at).num_milliseconds().try_into().ok());
+        self.append(
+            agent_id,
+            &ToolTimelineEntry {
+                phase: ToolPhase::Finished,
+                tool: tool.to_string(),
+                tool_use_id: tool_use_id.map(String::from),
+                at,
+                target: started.and_then(|e| e.target.clone()),
+                success: Some(success),
+                duration_ms,
+            },
+        )?;
+        Ok(duration_ms)
+    }
+
+    /// 记录子 agent 完成
+    pub fn record_subagent_stop(&self, agent_id: &str, at: DateTime<Utc>) -> Result<()> {
+        self.append(
+            agent_id,
+            &ToolTimelineEntry {
+                phase: ToolPhase::SubagentStopped,
+                tool: "Task".to_string(),
+                tool_use_id: None,
+                at,
+                target: None,
+                success: None,
+                duration_ms: None,
+            },
+        )
+    }
+
+    /// 读取 agent 的时间线（按时间顺序）
+    pub fn read(&self, agent_id: &str) -> Result<Vec<ToolTimelineEntry>> {
+        let path = self.path(agent_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn append(&self, agent_id: &str, entry: &ToolTimelineEntry) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(agent_id);
+        if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_TIMELINE_BYTES) {
+            let entries = self.read(agent_id)?;
+            let keep = &entries[entries.len().saturating_sub(KEEP_ENTRIES)..];
+            let mut content = String::new();
+            for entry in keep {
+                content.push_str(&serde_json::to_string(entry)?);
+                content.push('\n');
+            }
+            fs::write(&path, content)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+impl Default for ToolTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_finished_pairs_with_started() {
+        let dir = TempDir::new().unwrap();
+        let timeline = ToolTimeline::with_dir(dir.path());
+        let t0 = Utc::now();
+        let input = serde_json::json!({"command": "cargo test"});
+
+        timeline
+            .record_started("cam-1", "Bash", Some("toolu_1"), &input, t0)
+            .unwrap();
+        timeline
+            .record_started("cam-1", "Read", None, &serde_json::json!({}), t0)
+            .unwrap();
+        let duration = timeline
+            .record_finished(
+                "cam-1",
+                "Bash",
+                Some("toolu_1"),
+                false,
+                t0 + Duration::milliseconds(1500),
+            )
+            .unwrap();
+        assert_eq!(duration, Some(1500));
+        // 没有 id 时按工具名匹配
+        let duration = timeline
+            .record_finished("cam-1", "Read", None, true, t0 + Duration::seconds(2))
+            .unwrap();
+        assert_eq!(duration, Some(2000));
+        // 没有对应的开始记录
+        let duration = timeline
+            .record_finished("cam-1", "Glob", None, true, t0)
+            .unwrap();
+        assert_eq!(duration, None);
+
+        let entries = timeline.read("cam-1").unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[2].target.as_deref(), Some("cargo test"));
+        assert_eq!(entries[2].success, Some(false));
+        assert!(timeline.read("cam-2").unwrap().is_empty());
+    }
+}
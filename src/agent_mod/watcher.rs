@@ -6,20 +6,49 @@
 //! See `crate::agent::watcher::StabilityDetector` for terminal stability detection.
 
 use crate::agent::adapter::{get_adapter, DetectionStrategy};
-use crate::agent::extractor::{HaikuExtractor, MessageType, ReactExtractor};
+use crate::agent::context::context_alert_percent;
+use crate::agent::extractor::{FallbackLadder, MessageType, ReactExtractor};
+use crate::agent::history::AgentState;
+use crate::agent::idle::{idle_threshold_secs, is_idle_prompt, IdleState};
 use crate::agent::manager::AgentStatus;
 use crate::agent::monitor::AgentMonitor;
+use crate::agent::pause::PauseState;
+use crate::agent::pipeline::PipelineConfig;
+use crate::agent::prompt_queue::PromptQueue;
+use crate::agent::recovery::{
+    recovery_message, RecoveryAction, RecoveryManager, RecoveryOutcome, RecoveryPolicy,
+    ScheduleResult,
+};
+use crate::agent::rules::{RuleAction, RuleTracker, WatchRule};
+use crate::agent::stuck::{format_elapsed, last_visible_action, stuck_threshold_secs};
+use crate::agent::todos::{parse_todo_write, TODO_TOOL};
+use crate::agent::tool_batch::{
+    tool_batch_summary_enabled, ToolBatchTracker, TOOL_BATCH_INTERVAL_SECS,
+};
 use crate::agent::{AgentManager, AgentRecord};
-use crate::infra::input::{InputWaitDetector, InputWaitPattern, InputWaitResult};
+use crate::infra::event_bus::EventBus;
+use crate::infra::input::{detect_wait, InputWaitDetector, InputWaitPattern, InputWaitResult};
 use crate::infra::jsonl::{JsonlEvent, JsonlParser};
+use crate::infra::multiplexer::{self, Multiplexer, MultiplexerKind};
+use crate::infra::ports::listening_ports;
+use crate::infra::process::ProcessScanner;
 use crate::infra::terminal::truncate_for_status;
-use crate::infra::tmux::TmuxManager;
-use crate::notification::{generate_dedup_key, NotificationDeduplicator, NotifyAction};
+use crate::infra::tmux::{PaneCapture, TmuxManager};
+use crate::infra::tmux_control::tmux_control_mode_enabled;
+use crate::notification::{
+    question_dedup_key, AutoAnswerOutcome, AutoAnswerer, ErrorCategory, NotificationDeduplicator,
+    NotificationSummarizer, NotifyAction, AUTO_ANSWER_SENDER,
+};
+use crate::session::reconcile::{find_resolved, AgentScreen};
+use crate::session::{ConversationStateManager, PendingResolution, ReplyResult, ReplySender};
 // Import new watcher module for future migration
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tracing::{debug, error, info};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
 /// 监控事件类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +70,8 @@ pub enum WatchEvent {
     ToolUseBatch {
         agent_id: String,
         tools: Vec<String>,
+        /// 批次摘要（如"编辑 7 个文件（src/），运行测试 2 次"）
+        summary: String,
         timestamp: Option<String>,
     },
     /// 错误
@@ -61,6 +92,75 @@ pub enum WatchEvent {
     },
     /// Agent 恢复运行（从等待状态）
     AgentResumed { agent_id: String },
+    /// Agent 处于 Processing 但屏幕长时间无变化
+    AgentStuck {
+        agent_id: String,
+        /// 屏幕未变化的时长（秒）
+        elapsed_secs: u64,
+        /// 屏幕上最后一个可见操作
+        last_action: Option<String>,
+    },
+    /// Agent 停在空提示符（无待回答问题）很久，建议关闭
+    AgentIdle {
+        agent_id: String,
+        project_path: String,
+        /// 屏幕未变化的时长（秒）
+        idle_secs: u64,
+    },
+    /// 上下文窗口使用率超过阈值，建议 /compact
+    ContextHigh {
+        agent_id: String,
+        project_path: String,
+        /// 已用百分比
+        usage_percent: u8,
+    },
+    /// 终端新内容匹配了自定义监控规则（config.json 的 `watch_rules`）
+    RuleMatched {
+        agent_id: String,
+        project_path: String,
+        rule: WatchRule,
+        /// 匹配到的行
+        line: String,
+        /// `mark_done` 标记完成的任务 ID
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        completed_tasks: Vec<String>,
+    },
+    /// 执行了自动恢复动作（或重试次数已用完）
+    Recovery {
+        agent_id: String,
+        /// 动作描述
+        action: String,
+        attempt: u32,
+        max_attempts: u32,
+        outcome: RecoveryOutcome,
+    },
+    /// 待处理确认已在终端中处理或随 agent 退出而过期
+    ConfirmationResolved {
+        agent_id: String,
+        confirmation_id: String,
+        context: String,
+        resolution: PendingResolution,
+    },
+}
+
+impl WatchEvent {
+    /// 事件所属的 agent
+    pub fn agent_id(&self) -> &str {
+        match self {
+            WatchEvent::AgentExited { agent_id, .. }
+            | WatchEvent::ToolUse { agent_id, .. }
+            | WatchEvent::ToolUseBatch { agent_id, .. }
+            | WatchEvent::Error { agent_id, .. }
+            | WatchEvent::WaitingForInput { agent_id, .. }
+            | WatchEvent::AgentResumed { agent_id }
+            | WatchEvent::AgentStuck { agent_id, .. }
+            | WatchEvent::AgentIdle { agent_id, .. }
+            | WatchEvent::ContextHigh { agent_id, .. }
+            | WatchEvent::RuleMatched { agent_id, .. }
+            | WatchEvent::Recovery { agent_id, .. }
+            | WatchEvent::ConfirmationResolved { agent_id, .. } => agent_id,
+        }
+    }
 }
 
 /// Agent 状态快照
@@ -89,6 +189,10 @@ struct StabilityState {
     consecutive_count: u32,
     /// Whether AI check has been performed for this stable state
     ai_checked: bool,
+    /// Whether a stuck notification has been sent for this stable state
+    stuck_notified: bool,
+    /// Whether an idle suggestion has been sent for this stable state
+    idle_notified: bool,
 }
 
 impl StabilityState {
@@ -98,6 +202,8 @@ impl StabilityState {
             first_seen_at: now,
             consecutive_count: 1,
             ai_checked: false,
+            stuck_notified: false,
+            idle_notified: false,
         }
     }
 
@@ -111,6 +217,8 @@ impl StabilityState {
             self.first_seen_at = now;
             self.consecutive_count = 1;
             self.ai_checked = false;
+            self.stuck_notified = false;
+            self.idle_notified = false;
             true
         }
     }
@@ -159,7 +267,7 @@ pub struct AgentWatcher {
     /// Agent 管理器
     agent_manager: AgentManager,
     /// tmux 管理器
-    tmux: TmuxManager,
+    tmux: Arc<dyn Multiplexer>,
     /// 输入等待检测器
     input_detector: InputWaitDetector,
     /// 每个 agent 的 JSONL 解析器
@@ -176,26 +284,89 @@ pub struct AgentWatcher {
     agent_monitor: AgentMonitor,
     /// ReAct 消息提取器（可选，用于新的提取逻辑）
     react_extractor: Option<ReactExtractor>,
+    /// 对话状态（用于对账待处理确认）
+    conversation: ConversationStateManager,
+    /// 工具调用批次汇总（未开启时为 None）
+    tool_batches: Option<ToolBatchTracker>,
+    /// Processing 状态下屏幕无变化多久视为卡住（秒），None 表示关闭
+    stuck_threshold_secs: Option<u64>,
+    /// 空提示符停留多久建议关闭（秒），None 表示关闭
+    idle_threshold_secs: Option<u64>,
+    /// 空闲建议状态文件（聊天命令 /kill、/keep 读取）
+    idle_state_path: PathBuf,
+    /// 上下文使用率提醒阈值（百分比），None 表示关闭
+    context_alert_percent: Option<u8>,
+    /// 已发送上下文提醒的 agent（降回阈值以下后移除）
+    context_alerted: HashSet<String>,
+    /// 自定义监控规则（未配置时为 None）
+    rules: Option<RuleTracker>,
+    /// 暂停状态文件（`cam pause` 写入），暂停的 agent 不检测等待状态
+    pause_state_path: PathBuf,
+    /// 自动恢复（未开启时为 None）
+    recovery: Option<RecoveryManager>,
+    /// 每个 agent 最后一条用户 prompt（用于过载后重发）
+    last_prompts: HashMap<String, String>,
+    /// 回答记忆的自动回答（未开启时为 None）
+    auto_answer: Option<AutoAnswerer>,
+    /// 终端截取与 AI 判断的并发限制
+    pipeline: PipelineConfig,
+    /// 每个 agent 的增量截屏缓存
+    pane_captures: HashMap<String, PaneCapture>,
+    /// agent 处理中时排队的指令
+    prompt_queue: PromptQueue,
+    /// 共享事件总线（`cam events`）
+    event_bus: EventBus,
+    /// 下一轮轮询为冷启动对账：所有 agent 视为屏幕已稳定，立即判断等待状态
+    cold_start: bool,
+    /// 检测 agent 监听端口的间隔（None 表示不检测）
+    port_scan_interval: Option<Duration>,
+    /// 上次检测端口的时间
+    last_port_scan: Option<Instant>,
 }
 
 impl AgentWatcher {
     /// Terminal stability threshold (seconds)
-    const STABILITY_THRESHOLD_SECS: u64 = 6;
+    pub const STABILITY_THRESHOLD_SECS: u64 = 6;
     /// Hook quiet period - skip AI check if hook event within this window (seconds)
-    const HOOK_QUIET_PERIOD_SECS: u64 = 30;
+    pub const HOOK_QUIET_PERIOD_SECS: u64 = 30;
+    /// Scrollback lines captured per poll (in addition to the visible screen)
+    const CAPTURE_HISTORY_LINES: u32 = 50;
+    /// 检测 agent 监听端口的间隔
+    const PORT_SCAN_INTERVAL: Duration = Duration::from_secs(30);
     /// Hook inactive threshold - consider hooks inactive if no event for this duration (seconds)
     const HOOK_INACTIVE_THRESHOLD_SECS: u64 = 300; // 5 minutes
 
     /// 创建新的监控器
     pub fn new() -> Self {
-        // 尝试创建 ReactExtractor，失败时使用 None
-        let react_extractor = HaikuExtractor::new()
-            .ok()
-            .map(|e| ReactExtractor::new(Box::new(e)));
+        Self::with_agent_manager(AgentManager::new())
+    }
+
+    /// 使用指定的 AgentManager 创建监控器（用于自定义数据目录）
+    pub fn with_agent_manager(mut agent_manager: AgentManager) -> Self {
+        // 启用控制模式时截屏和发送按键共用一条 tmux 连接
+        let tmux = if tmux_control_mode_enabled()
+            && MultiplexerKind::from_config() == MultiplexerKind::Tmux
+        {
+            let tmux: Arc<dyn Multiplexer> = Arc::new(TmuxManager::with_control_mode());
+            agent_manager.tmux = Arc::clone(&tmux);
+            tmux
+        } else {
+            multiplexer::from_config()
+        };
+
+        // 提取阶梯先本地识别，API 未配置时只用本地级别
+        let react_extractor = Some(ReactExtractor::new(Box::new(FallbackLadder::load())));
+
+        let conversation = ConversationStateManager::with_agent_manager(
+            agent_manager.data_dir().join("conversation_state.json"),
+            AgentManager::with_data_dir(agent_manager.data_dir().to_path_buf()),
+        );
+        let prompt_queue = PromptQueue::in_dir(agent_manager.data_dir());
+        let event_bus = EventBus::in_dir(agent_manager.data_dir());
 
         Self {
-            agent_manager: AgentManager::new(),
-            tmux: TmuxManager::new(),
+            agent_manager,
+            tmux,
             input_detector: InputWaitDetector::new(),
             jsonl_parsers: HashMap::new(),
             deduplicator: NotificationDeduplicator::new_without_persistence(),
@@ -204,15 +375,45 @@ impl AgentWatcher {
             hook_tracker: HookEventTracker::default(),
             agent_monitor: AgentMonitor::new(),
             react_extractor,
+            conversation,
+            tool_batches: tool_batch_summary_enabled()
+                .then(|| ToolBatchTracker::new(TOOL_BATCH_INTERVAL_SECS)),
+            stuck_threshold_secs: stuck_threshold_secs(),
+            idle_threshold_secs: idle_threshold_secs(),
+            idle_state_path: IdleState::path(),
+            context_alert_percent: context_alert_percent(),
+            context_alerted: HashSet::new(),
+            rules: RuleTracker::load(),
+            pause_state_path: PauseState::path(),
+            recovery: RecoveryPolicy::load().map(RecoveryManager::new),
+            last_prompts: HashMap::new(),
+            auto_answer: AutoAnswerer::load(),
+            pipeline: PipelineConfig::default(),
+            pane_captures: HashMap::new(),
+            prompt_queue,
+            event_bus,
+            cold_start: false,
+            port_scan_interval: Some(Self::PORT_SCAN_INTERVAL),
+            last_port_scan: None,
         }
     }
 
     /// 创建用于测试的监控器
     #[cfg(test)]
     pub fn new_for_test() -> Self {
+        let agent_manager = AgentManager::new_for_test();
+        let idle_state_path = agent_manager.data_dir().join("idle.json");
+        let pause_state_path = agent_manager.data_dir().join("paused.json");
+        let conversation = ConversationStateManager::with_agent_manager(
+            agent_manager.data_dir().join("conversation_state.json"),
+            AgentManager::with_data_dir(agent_manager.data_dir().to_path_buf()),
+        );
+        let prompt_queue = PromptQueue::in_dir(agent_manager.data_dir());
+        let event_bus = EventBus::in_dir(agent_manager.data_dir());
+
         Self {
-            agent_manager: AgentManager::new_for_test(),
-            tmux: TmuxManager::new(),
+            agent_manager,
+            tmux: Arc::new(TmuxManager::new()),
             input_detector: InputWaitDetector::new(),
             jsonl_parsers: HashMap::new(),
             deduplicator: NotificationDeduplicator::new_without_persistence(),
@@ -221,9 +422,110 @@ impl AgentWatcher {
             hook_tracker: HookEventTracker::default(),
             agent_monitor: AgentMonitor::new(),
             react_extractor: None,
+            conversation,
+            tool_batches: None,
+            stuck_threshold_secs: None,
+            idle_threshold_secs: None,
+            idle_state_path,
+            context_alert_percent: None,
+            context_alerted: HashSet::new(),
+            rules: None,
+            pause_state_path,
+            recovery: None,
+            last_prompts: HashMap::new(),
+            auto_answer: None,
+            pipeline: PipelineConfig::default(),
+            pane_captures: HashMap::new(),
+            prompt_queue,
+            event_bus,
+            cold_start: false,
+            port_scan_interval: None,
+            last_port_scan: None,
         }
     }
 
+    /// 设置工具调用批次汇总间隔（秒），None 表示关闭
+    pub fn with_tool_batch_interval(mut self, interval_secs: Option<u64>) -> Self {
+        self.tool_batches = interval_secs.map(ToolBatchTracker::new);
+        self
+    }
+
+    /// 设置卡住检测阈值（秒），None 表示关闭
+    pub fn with_stuck_threshold(mut self, threshold_secs: Option<u64>) -> Self {
+        self.stuck_threshold_secs = threshold_secs;
+        self
+    }
+
+    /// 设置空闲建议阈值（秒），None 表示关闭
+    pub fn with_idle_threshold(mut self, threshold_secs: Option<u64>) -> Self {
+        self.idle_threshold_secs = threshold_secs;
+        self
+    }
+
+    /// 设置上下文使用率提醒阈值（百分比），None 表示关闭
+    pub fn with_context_alert(mut self, percent: Option<u8>) -> Self {
+        self.context_alert_percent = percent;
+        self
+    }
+
+    /// 设置自定义监控规则
+    pub fn with_watch_rules(mut self, rules: Vec<WatchRule>) -> Self {
+        self.rules = RuleTracker::new(rules);
+        self
+    }
+
+    /// 设置自动恢复策略，None 表示关闭
+    pub fn with_recovery_policy(mut self, policy: Option<RecoveryPolicy>) -> Self {
+        self.recovery = policy.map(RecoveryManager::new);
+        self
+    }
+
+    /// 设置自动回答，None 表示关闭
+    pub fn with_auto_answer(mut self, auto_answer: Option<AutoAnswerer>) -> Self {
+        self.auto_answer = auto_answer;
+        self
+    }
+
+    /// 设置轮询的并发数和单个 agent 的超时
+    pub fn with_pipeline(mut self, pipeline: PipelineConfig) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// 重新读取 config.json 中的阈值、监控规则、自动恢复、自动回答、提取阶梯（AI provider、
+    /// `ai_tasks`、`ai_ladder`、`embedding`、`privacy`）和回复授权/宏（config.json 热重载）
+    ///
+    /// 仍开启的功能保留运行状态；`multiplexer`、`tmux`、`tmux_control_mode` 在创建时选定，
+    /// 需要重启 daemon 才生效。
+    pub fn reload_config(&mut self) {
+        self.stuck_threshold_secs = stuck_threshold_secs();
+        self.idle_threshold_secs = idle_threshold_secs();
+        self.context_alert_percent = context_alert_percent();
+        if !tool_batch_summary_enabled() {
+            self.tool_batches = None;
+        } else if self.tool_batches.is_none() {
+            self.tool_batches = Some(ToolBatchTracker::new(TOOL_BATCH_INTERVAL_SECS));
+        }
+        self.rules = RuleTracker::load();
+        self.recovery = match (self.recovery.take(), RecoveryPolicy::load()) {
+            (Some(mut recovery), Some(policy)) => {
+                recovery.set_policy(policy);
+                Some(recovery)
+            }
+            (_, policy) => policy.map(RecoveryManager::new),
+        };
+        self.auto_answer = AutoAnswerer::load();
+        if self.react_extractor.is_some() {
+            self.react_extractor = Some(ReactExtractor::new(Box::new(FallbackLadder::load())));
+        }
+        self.conversation.reload_config();
+    }
+
+    /// 是否有待执行的自动恢复动作
+    pub fn has_pending_recovery(&self) -> bool {
+        self.recovery.as_ref().is_some_and(|r| r.has_pending())
+    }
+
     /// Check if agent is alive using new watcher module
     /// This method demonstrates the migration path to the new watcher module
     pub fn is_agent_alive(&self, agent: &AgentRecord) -> bool {
@@ -323,10 +625,7 @@ impl AgentWatcher {
 
     /// Load hook events from file (cross-process coordination)
     fn load_hook_events(&mut self) {
-        let hook_file = dirs::home_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join(".config/code-agent-monitor")
-            .join("last_hook_events.json");
+        let hook_file = crate::infra::paths::state_dir().join("last_hook_events.json");
 
         if hook_file.exists() {
             if let Ok(content) = std::fs::read_to_string(&hook_file) {
@@ -366,9 +665,18 @@ impl AgentWatcher {
         }
     }
 
+    /// 下一轮轮询做冷启动对账（daemon 启动时调用）
+    ///
+    /// daemon 停止期间 agent 可能早已停在问题上，屏幕不会再变化，不必等待稳定期；
+    /// 停止期间退出的 agent 由 [`AgentManager::take_exited_agents`] 照常报告。
+    pub fn reconcile_on_next_poll(&mut self) {
+        self.cold_start = true;
+    }
+
     /// 执行一次轮询，返回检测到的事件
     pub fn poll_once(&mut self) -> Result<Vec<WatchEvent>> {
         let mut events = Vec::new();
+        let cold_start = std::mem::take(&mut self.cold_start);
 
         // Load latest hook events for coordination
         self.load_hook_events();
@@ -379,11 +687,47 @@ impl AgentWatcher {
         for agent in &agents {
             debug!(agent_id = %agent.agent_id, "  - checking agent");
         }
+        self.refresh_ports(&agents);
+
+        // 每个 agent 的当前屏幕（用于对账待处理确认）
+        let mut screens = HashMap::new();
+
+        // list_agents 移出的异常退出 agent
+        for record in self.agent_manager.take_exited_agents()? {
+            screens.insert(record.agent_id.clone(), AgentScreen::Exited);
+            info!(agent_id = %record.agent_id, "Agent tmux session exited");
+            events.push(WatchEvent::AgentExited {
+                agent_id: record.agent_id.clone(),
+                project_path: record.project_path.clone(),
+            });
+            self.cleanup_agent(&record.agent_id);
+            let restart = self.recovery.as_ref().is_some_and(|r| {
+                r.policy().restart_on_exit
+                    && record.status.is_processing()
+                    && record.session_id.is_some()
+            });
+            if restart {
+                let agent_id = record.agent_id.clone();
+                events.extend(self.schedule_recovery(
+                    &agent_id,
+                    RecoveryAction::Restart {
+                        record: Box::new(record),
+                    },
+                ));
+            }
+        }
+
+        // 并行截取所有 agent 的终端（tmux 子进程互不依赖）
+        let captures = self.capture_panes(&agents);
+        let paused = PauseState::load_from(&self.pause_state_path);
+        // 需要 AI 判断等待状态的 agent（agents 下标, 终端内容）
+        let mut detections = Vec::new();
 
         // 检查每个 agent
-        for agent in &agents {
+        for (index, (agent, capture)) in agents.iter().zip(captures).enumerate() {
             // 1. 检查 tmux session 是否存活
             if !self.tmux.session_exists(&agent.tmux_session) {
+                screens.insert(agent.agent_id.clone(), AgentScreen::Exited);
                 info!(agent_id = %agent.agent_id, "Agent tmux session exited");
                 events.push(WatchEvent::AgentExited {
                     agent_id: agent.agent_id.clone(),
@@ -406,6 +750,9 @@ impl AgentWatcher {
 
                 if let Ok(new_events) = parser.read_new_events() {
                     for event in new_events {
+                        if let Some(ref mut batches) = self.tool_batches {
+                            batches.record(&agent.agent_id, &event, Self::current_timestamp());
+                        }
                         match &event {
                             JsonlEvent::ToolUse {
                                 tool_name,
@@ -413,6 +760,15 @@ impl AgentWatcher {
                                 timestamp,
                                 ..
                             } => {
+                                if tool_name == TODO_TOOL {
+                                    if let Some(todos) = parse_todo_write(input) {
+                                        if let Err(e) =
+                                            self.agent_manager.update_todos(&agent.agent_id, todos)
+                                        {
+                                            warn!(agent_id = %agent.agent_id, error = %e, "Failed to update todos");
+                                        }
+                                    }
+                                }
                                 let tool_target =
                                     crate::infra::jsonl::extract_tool_target_from_input(
                                         tool_name, input,
@@ -430,6 +786,31 @@ impl AgentWatcher {
                                     message: message.clone(),
                                     timestamp: timestamp.clone(),
                                 });
+                                if let Err(e) = self
+                                    .agent_manager
+                                    .record_agent_state(&agent.agent_id, AgentState::Errored)
+                                {
+                                    warn!(agent_id = %agent.agent_id, error = %e, "Failed to record error in history");
+                                }
+                                let resend = self
+                                    .recovery
+                                    .as_ref()
+                                    .is_some_and(|r| r.policy().resend_on_overload)
+                                    && NotificationSummarizer::new().classify_error(message)
+                                        == ErrorCategory::RateLimit;
+                                if let Some(prompt) = resend
+                                    .then(|| self.last_prompts.get(&agent.agent_id).cloned())
+                                    .flatten()
+                                {
+                                    events.extend(self.schedule_recovery(
+                                        &agent.agent_id,
+                                        RecoveryAction::ResendPrompt { prompt },
+                                    ));
+                                }
+                            }
+                            JsonlEvent::UserMessage { content, .. } => {
+                                self.last_prompts
+                                    .insert(agent.agent_id.clone(), content.clone());
                             }
                             _ => {}
                         }
@@ -437,8 +818,26 @@ impl AgentWatcher {
                 }
             }
 
+            if let Some(ref mut batches) = self.tool_batches {
+                if let Some(batch) = batches.take_due(&agent.agent_id, Self::current_timestamp()) {
+                    events.push(WatchEvent::ToolUseBatch {
+                        agent_id: agent.agent_id.clone(),
+                        tools: batch.tools().to_vec(),
+                        summary: batch.summary(&agent.project_path),
+                        timestamp: Some(chrono::Utc::now().to_rfc3339()),
+                    });
+                }
+            }
+
             // 3. 检测输入等待状态（带稳定性检测优化）
-            if let Ok(output) = self.tmux.capture_pane(&agent.tmux_session, 50) {
+            if let Some(output) = capture {
+                screens.insert(agent.agent_id.clone(), AgentScreen::Live(output.clone()));
+                events.extend(self.check_context(agent, &output));
+                events.extend(self.check_rules(agent, &output));
+                // 暂停中的 agent 停在中断提示，不算等待输入
+                if paused.is_paused(&agent.agent_id) {
+                    continue;
+                }
                 let now = Self::current_timestamp();
                 let content_hash = Self::content_fingerprint(&output);
                 let agent_id = agent.agent_id.clone();
@@ -452,7 +851,8 @@ impl AgentWatcher {
 
                 // Extract stability info for decision making
                 let ai_checked = stability.ai_checked;
-                let is_stable = stability.is_stable(now, Self::STABILITY_THRESHOLD_SECS);
+                let is_stable =
+                    cold_start || stability.is_stable(now, Self::STABILITY_THRESHOLD_SECS);
 
                 // Check if AI detection should be performed
                 let in_quiet_period = self.hook_tracker.is_in_quiet_period(
@@ -480,12 +880,22 @@ impl AgentWatcher {
                         "Skipping AI check (stability optimization)"
                     );
 
+                    // AI 已判定为 Processing，屏幕却一直没有变化
+                    if ai_checked && agent.status.is_processing() {
+                        events.extend(self.check_stuck(&agent_id, &output, now));
+                    }
+
                     // Still need to track waiting state for resume detection
                     let was_waiting = self
                         .last_waiting_state
                         .get(&agent_id)
                         .copied()
                         .unwrap_or(false);
+
+                    // 等待中且停在空提示符：长时间无变化时建议关闭
+                    if ai_checked && was_waiting {
+                        events.extend(self.check_idle(agent, &output, now));
+                    }
                     if was_waiting {
                         // Content changed while waiting - might have resumed
                         // Will be detected on next stable check
@@ -493,133 +903,546 @@ impl AgentWatcher {
                     continue;
                 }
 
-                // Perform AI detection
-                let wait_result = self.input_detector.detect_immediate(&output);
+                detections.push((index, output));
+            }
+        }
 
-                // Mark AI checked
-                if let Some(stability) = self.stability_states.get_mut(&agent_id) {
-                    stability.mark_ai_checked();
+        // AI 判断较慢，并行执行；失败或超时的 agent 下一轮重试
+        let results = self
+            .pipeline
+            .run(detections, |output: String| detect_wait(&output));
+        for (index, result) in results {
+            let agent = &agents[index];
+            match result {
+                Ok(wait_result) => self.apply_wait_result(agent, wait_result, &mut events),
+                Err(e) => {
+                    warn!(agent_id = %agent.agent_id, error = %e, "Input wait detection failed")
                 }
+            }
+        }
 
-                let was_waiting = self
-                    .last_waiting_state
-                    .get(&agent_id)
-                    .copied()
-                    .unwrap_or(false);
-
-                debug!(
-                    agent_id = %agent_id,
-                    is_waiting = wait_result.is_waiting,
-                    pattern = ?wait_result.pattern_type,
-                    was_waiting = was_waiting,
-                    "Input wait detection (AI called)"
-                );
+        // 4. 清理已在终端中处理的确认
+        events.extend(self.run_due_recoveries());
+        self.run_due_answers();
+        events.extend(self.reconcile_pending(&screens));
+        self.deliver_queued_prompts(&screens, &paused);
+
+        if !events.is_empty() {
+            info!(event_count = events.len(), "Poll generated events");
+            for event in &events {
+                info!(event = ?event, "  - event");
+                self.event_bus
+                    .publish("watch", Some(event.agent_id()), event);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// 并行增量截取终端，截取失败或超时为 None
+    fn capture_panes(&mut self, agents: &[AgentRecord]) -> Vec<Option<String>> {
+        let jobs = agents
+            .iter()
+            .map(|a| {
+                let cache = self
+                    .pane_captures
+                    .remove(&a.agent_id)
+                    .unwrap_or_else(|| PaneCapture::new(Self::CAPTURE_HISTORY_LINES));
+                (a.agent_id.clone(), (a.tmux_session.clone(), cache))
+            })
+            .collect();
+        let tmux = self.tmux.clone();
+        self.pipeline
+            .run(jobs, move |(session, mut cache): (String, PaneCapture)| {
+                let output = tmux
+                    .capture_pane_incremental(&session, &mut cache)
+                    .map(str::to_string);
+                if output.is_err() {
+                    cache.reset();
+                }
+                (output.ok(), cache)
+            })
+            .into_iter()
+            .map(|(agent_id, result)| match result {
+                Ok((output, cache)) => {
+                    self.pane_captures.insert(agent_id, cache);
+                    output
+                }
+                // 超时的缓存随任务丢弃，下次完整截取
+                Err(e) => {
+                    warn!(agent_id = %agent_id, error = %e, "Capture pane failed");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// 根据 AI 判断结果更新状态并生成等待/恢复事件
+    fn apply_wait_result(
+        &mut self,
+        agent: &AgentRecord,
+        wait_result: InputWaitResult,
+        events: &mut Vec<WatchEvent>,
+    ) {
+        let agent_id = agent.agent_id.clone();
+
+        // Mark AI checked
+        if let Some(stability) = self.stability_states.get_mut(&agent_id) {
+            stability.mark_ai_checked();
+        }
+
+        let was_waiting = self
+            .last_waiting_state
+            .get(&agent_id)
+            .copied()
+            .unwrap_or(false);
+
+        debug!(
+            agent_id = %agent_id,
+            is_waiting = wait_result.is_waiting,
+            pattern = ?wait_result.pattern_type,
+            was_waiting = was_waiting,
+            "Input wait detection (AI called)"
+        );
+
+        // Update agent status based on AI detection
+        let new_status = if wait_result.is_waiting {
+            AgentStatus::WaitingForInput
+        } else if wait_result.pattern_type == Some(InputWaitPattern::Unknown) {
+            AgentStatus::Unknown
+        } else {
+            AgentStatus::Processing
+        };
+
+        // Sync status to agents.json if changed
+        if agent.status != new_status {
+            if let Err(e) = self
+                .agent_manager
+                .update_agent_status(&agent_id, new_status.clone())
+            {
+                error!(agent_id = %agent_id, error = %e, "Failed to update agent status");
+            } else {
+                debug!(agent_id = %agent_id, old_status = ?agent.status, new_status = ?new_status, "Agent status updated");
+            }
+        }
 
-                // Update agent status based on AI detection
-                let new_status = if wait_result.is_waiting {
-                    AgentStatus::WaitingForInput
-                } else if wait_result.pattern_type == Some(InputWaitPattern::Unknown) {
-                    AgentStatus::Unknown
-                } else {
-                    AgentStatus::Processing
+        if wait_result.is_waiting {
+            // 检查是否应该发送通知（使用统一去重器）
+            // 问题指纹去重键与 hook 路径（cam notify）一致，同一问题只通知一次
+            let dedup_key = question_dedup_key(&agent_id, &wait_result.context);
+            let action = self.deduplicator.should_send(&agent_id, &dedup_key);
+
+            match action {
+                NotifyAction::Send => {
+                    let pattern_type = wait_result
+                        .pattern_type
+                        .as_ref()
+                        .map(|p| format!("{:?}", p))
+                        .unwrap_or_else(|| "Unknown".to_string());
+
+                    info!(
+                        agent_id = %agent_id,
+                        pattern_type = %pattern_type,
+                        is_decision_required = wait_result.is_decision_required,
+                        "Agent waiting for input, sending notification"
+                    );
+
+                    events.push(WatchEvent::WaitingForInput {
+                        agent_id: agent_id.clone(),
+                        pattern_type,
+                        context: wait_result.context.clone(),
+                        dedup_key: dedup_key.clone(),
+                        is_decision_required: wait_result.is_decision_required,
+                    });
+                }
+                NotifyAction::SendReminder if self.conversation.is_acknowledged(&agent_id) => {
+                    debug!(agent_id = %agent_id, "Reminder suppressed (acknowledged)");
+                }
+                NotifyAction::SendReminder => {
+                    let pattern_type = wait_result
+                        .pattern_type
+                        .as_ref()
+                        .map(|p| format!("{:?}", p))
+                        .unwrap_or_else(|| "Unknown".to_string());
+
+                    info!(
+                        agent_id = %agent_id,
+                        pattern_type = %pattern_type,
+                        is_decision_required = wait_result.is_decision_required,
+                        "Agent still waiting, sending reminder"
+                    );
+
+                    events.push(WatchEvent::WaitingForInput {
+                        agent_id: agent_id.clone(),
+                        pattern_type: format!("{} (提醒)", pattern_type),
+                        context: wait_result.context.clone(),
+                        dedup_key: dedup_key.clone(),
+                        is_decision_required: wait_result.is_decision_required,
+                    });
+                }
+                NotifyAction::Suppressed(reason) => {
+                    debug!(
+                        agent_id = %agent_id,
+                        reason = %reason,
+                        "Notification suppressed"
+                    );
+                }
+            }
+        } else {
+            // 不在等待状态
+            if was_waiting {
+                info!(agent_id = %agent_id, "Agent resumed from waiting state");
+                self.deduplicator.clear_lock(&agent_id);
+                if let Err(e) = self.conversation.clear_acknowledgement(&agent_id) {
+                    debug!(agent_id = %agent_id, error = %e, "Failed to clear acknowledgement");
+                }
+                events.push(WatchEvent::AgentResumed {
+                    agent_id: agent_id.clone(),
+                });
+            }
+        }
+
+        self.last_waiting_state
+            .insert(agent_id, wait_result.is_waiting);
+    }
+
+    /// 检查 Processing 状态的 agent 是否卡住（每个稳定状态只报告一次）
+    fn check_stuck(&mut self, agent_id: &str, screen: &str, now: u64) -> Option<WatchEvent> {
+        let threshold = self.stuck_threshold_secs?;
+        let stability = self.stability_states.get_mut(agent_id)?;
+        if stability.stuck_notified || !stability.is_stable(now, threshold) {
+            return None;
+        }
+        stability.stuck_notified = true;
+        let elapsed_secs = now.saturating_sub(stability.first_seen_at);
+        warn!(agent_id = %agent_id, elapsed_secs, "Agent may be stuck in processing");
+        Some(WatchEvent::AgentStuck {
+            agent_id: agent_id.to_string(),
+            elapsed_secs,
+            last_action: last_visible_action(screen),
+        })
+    }
+
+    /// 检查等待中的 agent 是否空闲在提示符（每个稳定状态只建议一次，`/keep` 过的跳过）
+    fn check_idle(&mut self, agent: &AgentRecord, screen: &str, now: u64) -> Option<WatchEvent> {
+        let threshold = self.idle_threshold_secs?;
+        let stability = self.stability_states.get_mut(&agent.agent_id)?;
+        if stability.idle_notified || !stability.is_stable(now, threshold) {
+            return None;
+        }
+        stability.idle_notified = true;
+        let idle_secs = now.saturating_sub(stability.first_seen_at);
+        if !is_idle_prompt(screen) {
+            return None;
+        }
+
+        let mut state = IdleState::load_from(&self.idle_state_path);
+        if state.is_kept(&agent.agent_id) {
+            debug!(agent_id = %agent.agent_id, "Idle suggestion skipped (kept)");
+            return None;
+        }
+        state.record_suggestion(&agent.agent_id, chrono::Utc::now());
+        if let Err(e) = state.save_to(&self.idle_state_path) {
+            warn!(agent_id = %agent.agent_id, error = %e, "Failed to save idle state");
+        }
+        info!(agent_id = %agent.agent_id, idle_secs, "Agent idle at prompt, suggesting shutdown");
+        Some(WatchEvent::AgentIdle {
+            agent_id: agent.agent_id.clone(),
+            project_path: agent.project_path.clone(),
+            idle_secs,
+        })
+    }
+
+    /// 定期检测各 agent 进程树监听的 TCP 端口，变化时写入记录（`cam list`、TUI 显示）
+    fn refresh_ports(&mut self, agents: &[AgentRecord]) {
+        let Some(interval) = self.port_scan_interval else {
+            return;
+        };
+        if agents.is_empty()
+            || self
+                .last_port_scan
+                .is_some_and(|at| at.elapsed() < interval)
+        {
+            return;
+        }
+        self.last_port_scan = Some(Instant::now());
+
+        let scanner = ProcessScanner::new();
+        for agent in agents {
+            let pids: Vec<u32> = self
+                .agent_manager
+                .process_tree(agent, &scanner)
+                .iter()
+                .map(|node| node.pid)
+                .collect();
+            let ports = listening_ports(&pids);
+            if ports == agent.ports {
+                continue;
+            }
+            debug!(agent_id = %agent.agent_id, ports = ?ports, "Agent listening ports changed");
+            if let Err(e) = self.agent_manager.update_ports(&agent.agent_id, ports) {
+                warn!(agent_id = %agent.agent_id, error = %e, "Failed to record listening ports");
+            }
+        }
+    }
+
+    /// 更新上下文使用率，首次超过阈值时返回提醒事件
+    fn check_context(&mut self, agent: &AgentRecord, screen: &str) -> Option<WatchEvent> {
+        let usage = get_adapter(&agent.agent_type).parse_context_usage(screen);
+        if usage != agent.context_usage {
+            if let Err(e) = self
+                .agent_manager
+                .update_context_usage(&agent.agent_id, usage)
+            {
+                warn!(agent_id = %agent.agent_id, error = %e, "Failed to record context usage");
+            }
+        }
+
+        let threshold = self.context_alert_percent?;
+        match usage {
+            Some(usage_percent) if usage_percent >= threshold => {
+                if !self.context_alerted.insert(agent.agent_id.clone()) {
+                    return None;
+                }
+                info!(agent_id = %agent.agent_id, usage_percent, "Context window nearly full");
+                Some(WatchEvent::ContextHigh {
+                    agent_id: agent.agent_id.clone(),
+                    project_path: agent.project_path.clone(),
+                    usage_percent,
+                })
+            }
+            _ => {
+                self.context_alerted.remove(&agent.agent_id);
+                None
+            }
+        }
+    }
+
+    /// 用自定义规则匹配屏幕新内容，`mark_done` 规则同时完成 Team 成员的进行中任务
+    fn check_rules(&mut self, agent: &AgentRecord, screen: &str) -> Vec<WatchEvent> {
+        let Some(rules) = self.rules.as_mut() else {
+            return Vec::new();
+        };
+        rules
+            .check(&agent.agent_id, screen)
+            .into_iter()
+            .map(|matched| {
+                info!(agent_id = %agent.agent_id, rule = %matched.rule.display_name(), "Watch rule matched");
+                let completed_tasks = match matched.rule.action {
+                    RuleAction::MarkDone => Self::complete_member_tasks(agent),
+                    RuleAction::Notify => Vec::new(),
                 };
+                WatchEvent::RuleMatched {
+                    agent_id: agent.agent_id.clone(),
+                    project_path: agent.project_path.clone(),
+                    rule: matched.rule,
+                    line: matched.line,
+                    completed_tasks,
+                }
+            })
+            .collect()
+    }
 
-                // Sync status to agents.json if changed
-                if agent.status != new_status {
-                    if let Err(e) = self
-                        .agent_manager
-                        .update_agent_status(&agent_id, new_status.clone())
-                    {
-                        error!(agent_id = %agent_id, error = %e, "Failed to update agent status");
-                    } else {
-                        debug!(agent_id = %agent_id, old_status = ?agent.status, new_status = ?new_status, "Agent status updated");
+    /// 完成 agent 所属 Team 成员的进行中任务（不是 Team 成员时为空）
+    fn complete_member_tasks(agent: &AgentRecord) -> Vec<String> {
+        let Some((team, member)) =
+            crate::team::TeamBridge::new().find_member_by_session(&agent.tmux_session)
+        else {
+            debug!(agent_id = %agent.agent_id, "mark_done rule matched but agent is not a team member");
+            return Vec::new();
+        };
+        crate::team::task_list::complete_member_tasks(&team, &member).unwrap_or_else(|e| {
+            warn!(agent_id = %agent.agent_id, team = %team, error = %e, "Failed to complete tasks");
+            Vec::new()
+        })
+    }
+
+    /// 安排一次恢复动作，重试次数已用完时返回 Exhausted 事件
+    fn schedule_recovery(&mut self, agent_id: &str, action: RecoveryAction) -> Option<WatchEvent> {
+        let recovery = self.recovery.as_mut()?;
+        let max_attempts = recovery.policy().max_retries;
+        let description = action.describe();
+        match recovery.schedule(agent_id, action, Self::current_timestamp()) {
+            ScheduleResult::Scheduled {
+                attempt,
+                delay_secs,
+            } => {
+                info!(agent_id = %agent_id, attempt, delay_secs, action = %description, "Recovery scheduled");
+                None
+            }
+            ScheduleResult::AlreadyPending => None,
+            ScheduleResult::Exhausted { attempts } => {
+                warn!(agent_id = %agent_id, attempts, "Recovery retries exhausted");
+                Some(WatchEvent::Recovery {
+                    agent_id: agent_id.to_string(),
+                    action: description,
+                    attempt: attempts,
+                    max_attempts,
+                    outcome: RecoveryOutcome::Exhausted,
+                })
+            }
+        }
+    }
+
+    /// 执行到期的恢复动作
+    fn run_due_recoveries(&mut self) -> Vec<WatchEvent> {
+        let Some(recovery) = self.recovery.as_mut() else {
+            return Vec::new();
+        };
+        let max_attempts = recovery.policy().max_retries;
+        let due = recovery.take_due(Self::current_timestamp());
+
+        due.into_iter()
+            .map(|due| {
+                let result = match &due.action {
+                    RecoveryAction::Restart { record } => {
+                        self.agent_manager.restart_agent(record).map(|_| ())
+                    }
+                    RecoveryAction::ResendPrompt { prompt } => {
+                        self.agent_manager.send_input(&due.agent_id, prompt)
+                    }
+                };
+                let outcome = match result {
+                    Ok(()) => {
+                        info!(agent_id = %due.agent_id, attempt = due.attempt, "Recovery action executed");
+                        RecoveryOutcome::Succeeded
+                    }
+                    Err(e) => {
+                        error!(agent_id = %due.agent_id, attempt = due.attempt, error = %e, "Recovery action failed");
+                        RecoveryOutcome::Failed {
+                            error: e.to_string(),
+                        }
                     }
+                };
+                WatchEvent::Recovery {
+                    agent_id: due.agent_id,
+                    action: due.action.describe(),
+                    attempt: due.attempt,
+                    max_attempts,
+                    outcome,
                 }
+            })
+            .collect()
+    }
 
-                if wait_result.is_waiting {
-                    // 检查是否应该发送通知（使用统一去重器）
-                    // 使用 truncated context 生成 dedup key，确保 watcher 和 hook 路径一致
-                    // wait_result.context 已经是 truncate_for_status() 处理过的 30 行内容
-                    let dedup_key = generate_dedup_key(&wait_result.context);
-                    let action = self.deduplicator.should_send(&agent_id, &dedup_key);
-
-                    match action {
-                        NotifyAction::Send => {
-                            let pattern_type = wait_result
-                                .pattern_type
-                                .as_ref()
-                                .map(|p| format!("{:?}", p))
-                                .unwrap_or_else(|| "Unknown".to_string());
-
-                            info!(
-                                agent_id = %agent_id,
-                                pattern_type = %pattern_type,
-                                is_decision_required = wait_result.is_decision_required,
-                                "Agent waiting for input, sending notification"
-                            );
-
-                            events.push(WatchEvent::WaitingForInput {
-                                agent_id: agent_id.clone(),
-                                pattern_type,
-                                context: wait_result.context.clone(),
-                                dedup_key: dedup_key.clone(),
-                                is_decision_required: wait_result.is_decision_required,
-                            });
-                        }
-                        NotifyAction::SendReminder => {
-                            let pattern_type = wait_result
-                                .pattern_type
-                                .as_ref()
-                                .map(|p| format!("{:?}", p))
-                                .unwrap_or_else(|| "Unknown".to_string());
-
-                            info!(
-                                agent_id = %agent_id,
-                                pattern_type = %pattern_type,
-                                is_decision_required = wait_result.is_decision_required,
-                                "Agent still waiting, sending reminder"
-                            );
-
-                            events.push(WatchEvent::WaitingForInput {
-                                agent_id: agent_id.clone(),
-                                pattern_type: format!("{} (提醒)", pattern_type),
-                                context: wait_result.context.clone(),
-                                dedup_key: dedup_key.clone(),
-                                is_decision_required: wait_result.is_decision_required,
-                            });
-                        }
-                        NotifyAction::Suppressed(reason) => {
-                            debug!(
-                                agent_id = %agent_id,
-                                reason = %reason,
-                                "Notification suppressed"
-                            );
-                        }
+    /// 发送到期的自动回答，agent 仍有该问题的待处理确认时才发送
+    fn run_due_answers(&self) {
+        let Some(auto_answer) = &self.auto_answer else {
+            return;
+        };
+        for due in auto_answer.take_due(chrono::Utc::now()) {
+            let (outcome, detail) = if due.expired {
+                (AutoAnswerOutcome::Expired, None)
+            } else {
+                match self.conversation.handle_reply_from(
+                    &due.answer.answer,
+                    Some(&due.agent_id),
+                    ReplySender::Remote(Some(AUTO_ANSWER_SENDER)),
+                ) {
+                    Ok(ReplyResult::Sent { verification, .. }) => {
+                        (AutoAnswerOutcome::Sent, Some(format!("{:?}", verification)))
                     }
-                } else {
-                    // 不在等待状态
-                    if was_waiting {
-                        info!(agent_id = %agent_id, "Agent resumed from waiting state");
-                        self.deduplicator.clear_lock(&agent_id);
-                        events.push(WatchEvent::AgentResumed {
-                            agent_id: agent_id.clone(),
-                        });
+                    Ok(ReplyResult::Denied { reason, .. }) => {
+                        (AutoAnswerOutcome::Denied, Some(reason))
                     }
+                    Ok(_) => (AutoAnswerOutcome::Skipped, None),
+                    Err(e) => (AutoAnswerOutcome::Failed, Some(e.to_string())),
                 }
-
-                self.last_waiting_state
-                    .insert(agent_id, wait_result.is_waiting);
+            };
+            info!(
+                agent_id = %due.agent_id,
+                answer = %due.answer.display(),
+                outcome = ?outcome,
+                "Auto answer"
+            );
+            if let Err(e) = auto_answer.record(&due, outcome, detail) {
+                warn!(agent_id = %due.agent_id, error = %e, "Failed to record auto answer");
             }
         }
+    }
 
-        if !events.is_empty() {
-            info!(event_count = events.len(), "Poll generated events");
-            for event in &events {
-                info!(event = ?event, "  - event");
+    /// 对账待处理确认：问题已离开屏幕或 agent 已退出时移除
+    fn reconcile_pending(&self, screens: &HashMap<String, AgentScreen>) -> Vec<WatchEvent> {
+        let pending = match self.conversation.get_pending_confirmations() {
+            Ok(pending) if !pending.is_empty() => pending,
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                warn!(error = %e, "Failed to load pending confirmations");
+                return Vec::new();
+            }
+        };
+
+        let mut events = Vec::new();
+        for (confirmation, resolution) in find_resolved(&pending, screens, chrono::Utc::now()) {
+            if let Err(e) = self.conversation.remove_pending(&confirmation.id) {
+                warn!(confirmation_id = %confirmation.id, error = %e, "Failed to remove pending confirmation");
+                continue;
             }
+            info!(
+                agent_id = %confirmation.agent_id,
+                confirmation_id = %confirmation.id,
+                resolution = ?resolution,
+                "Pending confirmation resolved outside cam"
+            );
+            events.push(WatchEvent::ConfirmationResolved {
+                agent_id: confirmation.agent_id,
+                confirmation_id: confirmation.id,
+                context: confirmation.context,
+                resolution,
+            });
         }
+        events
+    }
 
-        Ok(events)
+    /// 向空闲的 agent 输入一条排队的指令
+    ///
+    /// 要求 AI 已判定等待输入、屏幕停在空提示符且没有待处理确认（避免把指令当作问题的回答）。
+    /// 发送后清除等待状态，下一条指令等 agent 再次空闲。
+    fn deliver_queued_prompts(
+        &mut self,
+        screens: &HashMap<String, AgentScreen>,
+        paused: &PauseState,
+    ) {
+        let queued = self.prompt_queue.list();
+        if queued.is_empty() {
+            return;
+        }
+        let pending = self
+            .conversation
+            .get_pending_confirmations()
+            .unwrap_or_default();
+        let mut agent_ids: Vec<&str> = queued.iter().map(|p| p.agent_id.as_str()).collect();
+        agent_ids.sort_unstable();
+        agent_ids.dedup();
+        for agent_id in agent_ids {
+            let Some(AgentScreen::Live(screen)) = screens.get(agent_id) else {
+                continue;
+            };
+            let ready = self.last_waiting_state.get(agent_id) == Some(&true)
+                && is_idle_prompt(screen)
+                && !paused.is_paused(agent_id)
+                && !pending.iter().any(|c| c.agent_id == agent_id);
+            if !ready {
+                continue;
+            }
+            let prompt = match self.prompt_queue.pop(agent_id) {
+                Ok(Some(prompt)) => prompt,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!(agent_id = %agent_id, error = %e, "Failed to take queued prompt");
+                    continue;
+                }
+            };
+            match self.agent_manager.send_input(agent_id, &prompt.message) {
+                Ok(()) => {
+                    info!(agent_id = %agent_id, queue_id = %prompt.id, "Queued prompt delivered");
+                    self.last_waiting_state.insert(agent_id.to_string(), false);
+                }
+                Err(e) => {
+                    error!(agent_id = %agent_id, queue_id = %prompt.id, error = %e, "Failed to deliver queued prompt");
+                }
+            }
+        }
     }
 
     /// 手动触发一次输入等待检测（不受稳定性/去重影响）
@@ -676,7 +1499,7 @@ impl AgentWatcher {
         // Enhance is_decision_required using ReactExtractor for better AI-based detection
         let is_decision_required = if wait_result.is_waiting {
             if let Some(ref react_extractor) = self.react_extractor {
-                match react_extractor.extract_message(&agent.tmux_session, &self.tmux) {
+                match react_extractor.extract_message(&agent.tmux_session, self.tmux.as_ref()) {
                     Ok(Some(message)) => message.is_decision_required,
                     _ => is_decision_required,
                 }
@@ -687,7 +1510,7 @@ impl AgentWatcher {
             is_decision_required
         };
 
-        let dedup_key = generate_dedup_key(&context);
+        let dedup_key = question_dedup_key(agent_id, &context);
 
         Ok(Some(WatchEvent::WaitingForInput {
             agent_id: agent_id.to_string(),
@@ -768,6 +1591,26 @@ impl AgentWatcher {
         self.input_detector.clear_session(agent_id);
         self.stability_states.remove(agent_id);
         self.hook_tracker.clear(agent_id);
+        self.last_prompts.remove(agent_id);
+        self.pane_captures.remove(agent_id);
+        self.context_alerted.remove(agent_id);
+        if let Some(ref mut rules) = self.rules {
+            rules.remove(agent_id);
+        }
+        if let Some(ref mut batches) = self.tool_batches {
+            batches.remove(agent_id);
+        }
+        if let Some(ref mut recovery) = self.recovery {
+            recovery.cancel(agent_id);
+        }
+        if self.prompt_queue.has_queued(agent_id) {
+            match self.prompt_queue.cancel_all(Some(agent_id)) {
+                Ok(dropped) => {
+                    warn!(agent_id = %agent_id, count = dropped.len(), "Dropped queued prompts of exited agent")
+                }
+                Err(e) => warn!(agent_id = %agent_id, error = %e, "Failed to drop queued prompts"),
+            }
+        }
     }
 
     /// 获取 agent 管理器引用
@@ -792,6 +1635,7 @@ impl AgentWatcher {
                     WatchEvent::AgentExited { .. }
                         | WatchEvent::Error { .. }
                         | WatchEvent::WaitingForInput { .. }
+                        | WatchEvent::Recovery { .. }
                 )
             })
             .collect())
@@ -804,7 +1648,7 @@ impl AgentWatcher {
     pub fn check_waiting_with_react(&self, agent: &AgentRecord) -> Option<WatchEvent> {
         let react_extractor = self.react_extractor.as_ref()?;
 
-        match react_extractor.extract_message(&agent.tmux_session, &self.tmux) {
+        match react_extractor.extract_message(&agent.tmux_session, self.tmux.as_ref()) {
             Ok(Some(message)) => {
                 let is_decision_required = message.is_decision_required;
                 let pattern_type = match &message.message_type {
@@ -864,9 +1708,9 @@ pub fn format_watch_event(event: &WatchEvent) -> String {
             }
         }
         WatchEvent::ToolUseBatch {
-            agent_id, tools, ..
+            agent_id, summary, ..
         } => {
-            format!("🔧 {} 执行: {}", agent_id, tools.join(", "))
+            format!("🔧 {} {}", agent_id, summary)
         }
         WatchEvent::Error {
             agent_id, message, ..
@@ -895,6 +1739,85 @@ pub fn format_watch_event(event: &WatchEvent) -> String {
         WatchEvent::AgentResumed { agent_id } => {
             format!("▶️ {} 继续执行", agent_id)
         }
+        WatchEvent::AgentStuck {
+            agent_id,
+            elapsed_secs,
+            last_action,
+        } => match last_action {
+            Some(action) => format!(
+                "🐢 {} 可能卡住了：{} 无变化，最后操作: {}",
+                agent_id,
+                format_elapsed(*elapsed_secs),
+                action
+            ),
+            None => format!(
+                "🐢 {} 可能卡住了：{} 无变化",
+                agent_id,
+                format_elapsed(*elapsed_secs)
+            ),
+        },
+        WatchEvent::AgentIdle {
+            agent_id,
+            project_path,
+            idle_secs,
+        } => format!(
+            "💤 {} ({}) 已空闲 {}，回复 /kill 停止，/keep 不再提醒",
+            agent_id,
+            project_path,
+            format_elapsed(*idle_secs)
+        ),
+        WatchEvent::ContextHigh {
+            agent_id,
+            project_path,
+            usage_percent,
+        } => format!(
+            "🧠 {} ({}) 上下文已用 {}%，建议 /compact",
+            agent_id, project_path, usage_percent
+        ),
+        WatchEvent::RuleMatched {
+            agent_id,
+            rule,
+            line,
+            completed_tasks,
+            ..
+        } => {
+            let mut text = format!("📌 {} [{}] {}", agent_id, rule.display_name(), line);
+            if !completed_tasks.is_empty() {
+                text.push_str(&format!("（已完成任务 #{}）", completed_tasks.join(", #")));
+            }
+            text
+        }
+        WatchEvent::Recovery {
+            agent_id,
+            action,
+            attempt,
+            max_attempts,
+            outcome,
+        } => {
+            let icon = if outcome.needs_attention() {
+                "⚠️"
+            } else {
+                "🔄"
+            };
+            format!(
+                "{} {} {}",
+                icon,
+                agent_id,
+                recovery_message(action, *attempt, *max_attempts, outcome)
+            )
+        }
+        WatchEvent::ConfirmationResolved {
+            agent_id,
+            resolution,
+            ..
+        } => match resolution {
+            PendingResolution::ResolvedInTerminal => {
+                format!("✔️ {} 的确认已在终端中处理", agent_id)
+            }
+            PendingResolution::AgentExited => {
+                format!("⌛ {} 已退出，待处理确认已过期", agent_id)
+            }
+        },
     }
 }
 
@@ -928,6 +1851,22 @@ mod tests {
         assert!(formatted.contains("main.rs"));
     }
 
+    #[test]
+    fn test_format_watch_event_tool_use_batch() {
+        let event = WatchEvent::ToolUseBatch {
+            agent_id: "cam-123".to_string(),
+            tools: vec!["Edit".to_string(), "Bash".to_string()],
+            summary: "编辑 7 个文件（src/），运行测试 2 次（1 次失败）".to_string(),
+            timestamp: None,
+        };
+
+        let formatted = format_watch_event(&event);
+        assert_eq!(
+            formatted,
+            "🔧 cam-123 编辑 7 个文件（src/），运行测试 2 次（1 次失败）"
+        );
+    }
+
     #[test]
     fn test_format_watch_event_waiting() {
         let event = WatchEvent::WaitingForInput {
@@ -998,6 +1937,290 @@ mod tests {
         assert_eq!(critical.len(), 2);
     }
 
+    #[test]
+    fn test_check_stuck_reports_once() {
+        let mut watcher = AgentWatcher::new_for_test().with_stuck_threshold(Some(600));
+        watcher
+            .stability_states
+            .insert("cam-1".to_string(), StabilityState::new(42, 1000));
+        let screen = "⏺ Bash(cargo build)\n  ⎿  Running…";
+
+        assert!(watcher.check_stuck("cam-1", screen, 1300).is_none());
+        let event = watcher.check_stuck("cam-1", screen, 1700).unwrap();
+        match &event {
+            WatchEvent::AgentStuck {
+                elapsed_secs,
+                last_action,
+                ..
+            } => {
+                assert_eq!(*elapsed_secs, 700);
+                assert_eq!(last_action.as_deref(), Some("Bash(cargo build)"));
+            }
+            _ => panic!("Expected AgentStuck event"),
+        }
+        assert!(format_watch_event(&event).contains("11 分钟"));
+        // 同一稳定状态不重复报告，内容变化后重新计时
+        assert!(watcher.check_stuck("cam-1", screen, 2000).is_none());
+        let stability = watcher.stability_states.get_mut("cam-1").unwrap();
+        assert!(stability.update(43, 2000));
+        assert!(watcher.check_stuck("cam-1", screen, 2700).is_some());
+
+        let mut disabled = AgentWatcher::new_for_test();
+        disabled
+            .stability_states
+            .insert("cam-1".to_string(), StabilityState::new(42, 1000));
+        assert!(disabled.check_stuck("cam-1", screen, 5000).is_none());
+    }
+
+    #[test]
+    fn test_check_idle_suggests_once_and_honors_keep() {
+        use crate::agent::AgentType;
+
+        let mut watcher = AgentWatcher::new_for_test().with_idle_threshold(Some(3600));
+        let agent = AgentRecord {
+            agent_id: "cam-idle".to_string(),
+            agent_type: AgentType::Claude,
+            tmux_session: "cam-idle".to_string(),
+            project_path: "/work/myapp".to_string(),
+            session_id: None,
+            jsonl_path: None,
+            jsonl_offset: 0,
+            last_output_hash: None,
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            status: crate::agent::AgentStatus::WaitingForInput,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
+        };
+        let idle = "⏺ Done. All tests pass.\n\n❯ ";
+        let question = "Should I also update the README?\n\n❯ ";
+        watcher
+            .stability_states
+            .insert("cam-idle".to_string(), StabilityState::new(42, 1000));
+
+        assert!(watcher.check_idle(&agent, idle, 2000).is_none());
+        let event = watcher.check_idle(&agent, idle, 8200).unwrap();
+        assert!(format_watch_event(&event).contains("2 小时"));
+        assert!(watcher.check_idle(&agent, idle, 9000).is_none());
+        let state = IdleState::load_from(&watcher.idle_state_path);
+        assert_eq!(state.latest_suggestion(), Some("cam-idle"));
+
+        // 有待回答的问题不建议关闭
+        let stability = watcher.stability_states.get_mut("cam-idle").unwrap();
+        assert!(stability.update(43, 9000));
+        assert!(watcher.check_idle(&agent, question, 20000).is_none());
+
+        // /keep 之后不再建议
+        let mut state = IdleState::load_from(&watcher.idle_state_path);
+        state.keep("cam-idle");
+        state.save_to(&watcher.idle_state_path).unwrap();
+        let stability = watcher.stability_states.get_mut("cam-idle").unwrap();
+        assert!(stability.update(44, 20000));
+        assert!(watcher.check_idle(&agent, idle, 30000).is_none());
+    }
+
+    #[test]
+    fn test_check_context_alerts_once_until_compacted() {
+        use crate::agent::AgentType;
+
+        let mut watcher = AgentWatcher::new_for_test().with_context_alert(Some(90));
+        let agent = AgentRecord {
+            agent_id: "cam-ctx".to_string(),
+            agent_type: AgentType::Claude,
+            tmux_session: "cam-ctx".to_string(),
+            project_path: "/work/myapp".to_string(),
+            session_id: None,
+            jsonl_path: None,
+            jsonl_offset: 0,
+            last_output_hash: None,
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            status: crate::agent::AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
+        };
+        let low = "❯ \n  Context left until auto-compact: 30%";
+        let high = "❯ \n  Context left until auto-compact: 8%";
+
+        assert!(watcher.check_context(&agent, low).is_none());
+        let event = watcher.check_context(&agent, high).unwrap();
+        assert!(format_watch_event(&event).contains("92%"));
+        assert!(watcher.check_context(&agent, high).is_none());
+
+        // /compact 后指示消失，再次超过阈值时重新提醒
+        assert!(watcher.check_context(&agent, "❯ ").is_none());
+        assert!(watcher.check_context(&agent, high).is_some());
+    }
+
+    #[test]
+    fn test_exited_agent_schedules_restart() {
+        let policy = RecoveryPolicy {
+            restart_on_exit: true,
+            max_retries: 1,
+            backoff_secs: 600,
+            ..Default::default()
+        };
+        let mut watcher = AgentWatcher::new_for_test().with_recovery_policy(Some(policy));
+        let agents_file = watcher.agent_manager().data_dir().join("agents.json");
+        let record = r#"{"agents":[{"agent_id":"cam-crashed","agent_type":"claude","project_path":"/tmp","tmux_session":"cam-test-crashed-session","session_id":"sess-1","started_at":"2026-01-01T00:00:00Z","status":"processing"}]}"#;
+        std::fs::write(&agents_file, record).unwrap();
+
+        let events = watcher.poll_once().unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [WatchEvent::AgentExited { agent_id, .. }] if agent_id == "cam-crashed"
+        ));
+        assert!(watcher.has_pending_recovery());
+
+        // 再次退出时重试次数已用完，报告 Exhausted
+        std::fs::write(&agents_file, record).unwrap();
+        let events = watcher.poll_once().unwrap();
+        let exhausted = events
+            .iter()
+            .find(|e| matches!(e, WatchEvent::Recovery { .. }))
+            .unwrap();
+        assert!(matches!(
+            exhausted,
+            WatchEvent::Recovery {
+                outcome: RecoveryOutcome::Exhausted,
+                ..
+            }
+        ));
+        assert!(format_watch_event(exhausted).contains("自动恢复已达上限"));
+    }
+
+    #[test]
+    fn test_cold_start_reports_agents_exited_while_down() {
+        let mut watcher = AgentWatcher::new_for_test();
+        let agents_file = watcher.agent_manager().data_dir().join("agents.json");
+        // 另一个进程的 list_agents 已把退出的 agent 移到 exited，daemon 当时未运行
+        std::fs::write(
+            &agents_file,
+            r#"{"agents":[{"agent_id":"cam-gone","agent_type":"claude","project_path":"/tmp/app","tmux_session":"cam-test-gone-session","started_at":"2026-01-01T00:00:00Z","status":"waiting_for_input"}],"exited":[{"agent_id":"cam-done","agent_type":"claude","project_path":"/tmp/done","tmux_session":"cam-test-done-session","started_at":"2026-01-01T00:00:00Z","status":"processing"}]}"#,
+        )
+        .unwrap();
+
+        watcher.reconcile_on_next_poll();
+        let events = watcher.poll_once().unwrap();
+        let mut exited: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                WatchEvent::AgentExited { agent_id, .. } => Some(agent_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        exited.sort();
+        assert_eq!(exited, ["cam-done", "cam-gone"]);
+        assert!(!watcher.cold_start);
+        assert!(watcher.poll_once().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_due_auto_answer_skipped_without_pending() {
+        use crate::notification::{AnswerMemory, AnswerMemoryConfig};
+
+        let temp = tempfile::tempdir().unwrap();
+        let memory_path = temp.path().join("answer_memory.json");
+        let audit_log = temp.path().join("auto_answers.jsonl");
+        let mut memory = AnswerMemory::load_from(&memory_path).with_config(AnswerMemoryConfig {
+            enabled: true,
+            auto_answer: true,
+            auto_answer_delay_secs: 0,
+            ..Default::default()
+        });
+        memory
+            .record_question(
+                "cam-a",
+                "/proj",
+                "pm",
+                "Which package manager?\n1. npm\n2. pnpm",
+            )
+            .unwrap();
+        memory.record_reply("cam-a", "2").unwrap();
+        memory
+            .record_question(
+                "cam-b",
+                "/proj",
+                "pm",
+                "Which package manager?\n1. npm\n2. pnpm",
+            )
+            .unwrap();
+        assert_eq!(
+            memory
+                .schedule_auto_answer("cam-b", None, "MEDIUM")
+                .unwrap(),
+            Some(0)
+        );
+
+        let watcher = AgentWatcher::new_for_test()
+            .with_auto_answer(Some(AutoAnswerer::at(&memory_path, &audit_log)));
+        watcher.run_due_answers();
+
+        // 没有待处理确认（agent 已不在等待）时不发送，只记录
+        let log = std::fs::read_to_string(&audit_log).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains(r#""outcome":"skipped""#));
+        assert!(log.contains(r#""answer":"2""#));
+        watcher.run_due_answers();
+        assert_eq!(
+            std::fs::read_to_string(&audit_log).unwrap().lines().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_queued_prompt_waits_for_idle_prompt() {
+        use crate::session::ConfirmationType;
+
+        let mut watcher = AgentWatcher::new_for_test();
+        let paused = PauseState::default();
+        watcher
+            .prompt_queue
+            .enqueue("cam-q", "run the tests")
+            .unwrap();
+        let idle = "⏺ Done.\n\n❯ ";
+        let screens = HashMap::from([("cam-q".to_string(), AgentScreen::Live(idle.to_string()))]);
+
+        // 未判定为等待输入
+        watcher.deliver_queued_prompts(&screens, &paused);
+        assert_eq!(watcher.prompt_queue.list().len(), 1);
+
+        // 有待处理确认（指令会被当作回答）
+        watcher.last_waiting_state.insert("cam-q".to_string(), true);
+        watcher
+            .conversation
+            .register_pending(
+                "cam-q",
+                None,
+                ConfirmationType::OptionSelection { options: vec![] },
+                "Continue?",
+                Some("cam-q"),
+            )
+            .unwrap();
+        watcher.deliver_queued_prompts(&screens, &paused);
+        assert_eq!(watcher.prompt_queue.list().len(), 1);
+
+        // 屏幕不是空提示符
+        let busy = HashMap::from([(
+            "cam-q".to_string(),
+            AgentScreen::Live("✻ Thinking… (esc to interrupt)".to_string()),
+        )]);
+        let pending = watcher.conversation.get_pending_confirmations().unwrap();
+        watcher.conversation.remove_pending(&pending[0].id).unwrap();
+        watcher.deliver_queued_prompts(&busy, &paused);
+        assert_eq!(watcher.prompt_queue.list().len(), 1);
+
+        // 空闲：取出发送（测试中 agent 不存在，发送失败只记录日志）
+        watcher.deliver_queued_prompts(&screens, &paused);
+        assert!(watcher.prompt_queue.list().is_empty());
+    }
+
     // === StabilityState tests ===
 
     #[test]
@@ -1115,6 +2338,12 @@ mod tests {
             last_output_hash: None,
             started_at: "2024-01-01T00:00:00Z".to_string(),
             status: crate::agent::AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         // No hook events recorded - should poll (hooks seem inactive)
@@ -1137,6 +2366,12 @@ mod tests {
             last_output_hash: None,
             started_at: "2024-01-01T00:00:00Z".to_string(),
             status: crate::agent::AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         // Record recent hook event
@@ -1163,6 +2398,12 @@ mod tests {
             last_output_hash: None,
             started_at: "2024-01-01T00:00:00Z".to_string(),
             status: crate::agent::AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         // Record old hook event (more than 5 minutes ago)
@@ -1191,6 +2432,12 @@ mod tests {
             last_output_hash: None,
             started_at: "2024-01-01T00:00:00Z".to_string(),
             status: crate::agent::AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         // HookWithPolling - should always poll
@@ -1213,6 +2460,12 @@ mod tests {
             last_output_hash: None,
             started_at: "2024-01-01T00:00:00Z".to_string(),
             status: crate::agent::AgentStatus::Processing,
+            history: Vec::new(),
+            container: None,
+            context_usage: None,
+            todos: Vec::new(),
+            ports: Vec::new(),
+            ready: None,
         };
 
         // PollingOnly - should always poll
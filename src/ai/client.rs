@@ -16,6 +16,9 @@ use std::fs;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
+use super::profile::{local_providers_only, AiTask, TaskProfiles};
+use crate::infra::privacy::PrivacyConfig;
+
 // 清除代理环境变量，避免代理导致请求超时
 fn clear_proxy_env() {
     env::remove_var("HTTP_PROXY");
@@ -57,7 +60,7 @@ pub const DEFAULT_TIMEOUT_MS: u64 = 15000;
 pub const DEFAULT_MAX_TOKENS: u32 = 1500;
 
 /// 提供商配置
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct ProviderConfig {
     pub api_key: String,
     pub base_url: String,
@@ -78,7 +81,7 @@ impl ProviderConfig {
 }
 
 /// Anthropic 客户端配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AnthropicConfig {
     /// API 密钥
     pub api_key: String,
@@ -90,6 +93,8 @@ pub struct AnthropicConfig {
     pub timeout_ms: u64,
     /// 最大输出 tokens
     pub max_tokens: u32,
+    /// 采样温度，未设置时使用 API 默认值
+    pub temperature: Option<f32>,
     /// Webhook 配置
     pub webhook: Option<WebhookConfig>,
     /// 提供商列表（用于 fallback）
@@ -97,7 +102,7 @@ pub struct AnthropicConfig {
 }
 
 /// Webhook 配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct WebhookConfig {
     /// Gateway URL
     pub gateway_url: String,
@@ -115,6 +120,7 @@ impl Default for AnthropicConfig {
             model: DEFAULT_MODEL.to_string(),
             timeout_ms: DEFAULT_TIMEOUT_MS,
             max_tokens: DEFAULT_MAX_TOKENS,
+            temperature: None,
             webhook: None,
             providers: Vec::new(),
         }
@@ -140,6 +146,7 @@ impl AnthropicConfig {
                 model: primary.model.clone(),
                 timeout_ms,
                 max_tokens: DEFAULT_MAX_TOKENS,
+                temperature: None,
                 webhook,
                 providers,
             });
@@ -157,6 +164,7 @@ impl AnthropicConfig {
                 model,
                 timeout_ms,
                 max_tokens: DEFAULT_MAX_TOKENS,
+                temperature: None,
                 webhook,
                 providers: Vec::new(),
             });
@@ -171,128 +179,94 @@ impl AnthropicConfig {
             model,
             timeout_ms,
             max_tokens: DEFAULT_MAX_TOKENS,
+            temperature: None,
             webhook,
             providers: Vec::new(),
         })
     }
 
+    /// 加载配置并叠加指定任务的模型配置（config.json 的 `ai_tasks`）
+    pub fn for_task(task: AiTask) -> Result<Self> {
+        Self::auto_load()?.with_task(task)
+    }
+
+    /// 在当前配置上叠加指定任务的模型配置
+    ///
+    /// 快照隐私模式下读取快照的任务只能使用本机 provider，没有时返回错误。
+    pub fn with_task(self, task: AiTask) -> Result<Self> {
+        let config = TaskProfiles::load().apply(task, self);
+        if task.reads_snapshot() && PrivacyConfig::load().local_snapshots {
+            return local_providers_only(config);
+        }
+        Ok(config)
+    }
+
     /// 从配置文件加载 providers
     fn load_providers_from_config() -> Vec<ProviderConfig> {
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".config/code-agent-monitor/config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        if let Some(providers) = config.get("providers").and_then(|p| p.as_array())
-                        {
-                            let mut result = Vec::new();
-                            for p in providers {
-                                if let (Some(api_key), Some(base_url), Some(model)) = (
-                                    p.get("api_key").and_then(|k| k.as_str()),
-                                    p.get("base_url").and_then(|u| u.as_str()),
-                                    p.get("model").and_then(|m| m.as_str()),
-                                ) {
-                                    let api_type = p
-                                        .get("api_type")
-                                        .and_then(|t| t.as_str())
-                                        .unwrap_or("anthropic")
-                                        .to_string();
-                                    result.push(ProviderConfig {
-                                        api_key: api_key.to_string(),
-                                        base_url: base_url.to_string(),
-                                        model: model.to_string(),
-                                        api_type,
-                                    });
-                                }
-                            }
-                            if !result.is_empty() {
-                                debug!("Loaded {} providers from config", result.len());
-                                return result;
-                            }
-                        }
-                    }
-                }
+        let providers: Vec<serde_json::Value> =
+            crate::infra::paths::config_section("providers").unwrap_or_default();
+        let mut result = Vec::new();
+        for p in &providers {
+            if let (Some(api_key), Some(base_url), Some(model)) = (
+                p.get("api_key").and_then(|k| k.as_str()),
+                p.get("base_url").and_then(|u| u.as_str()),
+                p.get("model").and_then(|m| m.as_str()),
+            ) {
+                let api_type = p
+                    .get("api_type")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or("anthropic")
+                    .to_string();
+                result.push(ProviderConfig {
+                    api_key: api_key.to_string(),
+                    base_url: base_url.to_string(),
+                    model: model.to_string(),
+                    api_type,
+                });
             }
         }
-        Vec::new()
+        if !result.is_empty() {
+            debug!("Loaded {} providers from config", result.len());
+        }
+        result
     }
 
     /// 从配置文件加载模型
     fn load_model_from_config() -> Option<String> {
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".config/code-agent-monitor/config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        if let Some(model) = config.get("model").and_then(|m| m.as_str()) {
-                            if !model.is_empty() {
-                                debug!("Loaded model from config: {}", model);
-                                return Some(model.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        None
+        let model = crate::infra::paths::config_section::<String>("model")
+            .filter(|model| !model.is_empty())?;
+        debug!("Loaded model from config: {}", model);
+        Some(model)
     }
 
     /// 从配置文件加载超时设置（毫秒）
     fn load_timeout_from_config() -> Option<u64> {
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".config/code-agent-monitor/config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        if let Some(timeout) =
-                            config.get("extraction_timeout_ms").and_then(|t| t.as_u64())
-                        {
-                            if timeout > 0 {
-                                debug!("Loaded extraction timeout from config: {}ms", timeout);
-                                return Some(timeout);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        None
+        let timeout = crate::infra::paths::config_section::<u64>("extraction_timeout_ms")
+            .filter(|timeout| *timeout > 0)?;
+        debug!("Loaded extraction timeout from config: {}ms", timeout);
+        Some(timeout)
     }
 
     /// 加载 MiniMax 配置
     fn load_minimax_config() -> Result<Option<(String, String)>> {
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".config/code-agent-monitor/config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        let key = config.get("minimax_api_key").and_then(|k| k.as_str());
-                        let base_url = config.get("minimax_base_url").and_then(|u| u.as_str());
-
-                        if let Some(key) = key {
-                            if !key.is_empty() {
-                                let url = base_url
-                                    .filter(|u| !u.is_empty())
-                                    .map(|u| {
-                                        let u = u.trim_end_matches('/');
-                                        if u.ends_with("/v1/messages") {
-                                            u.to_string()
-                                        } else if u.ends_with("/v1") {
-                                            format!("{}/messages", u)
-                                        } else {
-                                            format!("{}/v1/messages", u)
-                                        }
-                                    })
-                                    .unwrap_or_else(|| {
-                                        "https://api.minimaxi.com/anthropic/v1/messages".to_string()
-                                    });
-                                debug!("Using MiniMax API: key=***, base_url={}", url);
-                                return Ok(Some((key.to_string(), url)));
-                            }
-                        }
+        let key = crate::infra::paths::config_section::<String>("minimax_api_key")
+            .filter(|key| !key.is_empty());
+        if let Some(key) = key {
+            let url = crate::infra::paths::config_section::<String>("minimax_base_url")
+                .filter(|u| !u.is_empty())
+                .map(|u| {
+                    let u = u.trim_end_matches('/');
+                    if u.ends_with("/v1/messages") {
+                        u.to_string()
+                    } else if u.ends_with("/v1") {
+                        format!("{}/messages", u)
+                    } else {
+                        format!("{}/v1/messages", u)
                     }
-                }
-            }
+                })
+                .unwrap_or_else(|| "https://api.minimaxi.com/anthropic/v1/messages".to_string());
+            debug!("Using MiniMax API: key=***, base_url={}", url);
+            return Ok(Some((key, url)));
         }
         Ok(None)
     }
@@ -302,36 +276,28 @@ impl AnthropicConfig {
         let default_url = ANTHROPIC_API_URL.to_string();
 
         // 1. CAM 配置文件 ~/.config/code-agent-monitor/config.json
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".config/code-agent-monitor/config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        let key = config.get("anthropic_api_key").and_then(|k| k.as_str());
-                        let base_url = config.get("anthropic_base_url").and_then(|u| u.as_str());
-
-                        if let Some(key) = key {
-                            if !key.is_empty() {
-                                let url = base_url
-                                    .filter(|u| !u.is_empty())
-                                    .map(|u| {
-                                        let u = u.trim_end_matches('/');
-                                        if u.ends_with("/v1/messages") {
-                                            u.to_string()
-                                        } else if u.ends_with("/v1") {
-                                            format!("{}/messages", u)
-                                        } else {
-                                            format!("{}/v1/messages", u)
-                                        }
-                                    })
-                                    .unwrap_or_else(|| default_url.clone());
-                                debug!("Using API key from ~/.config/code-agent-monitor/config.json, base_url: {}", url);
-                                return Ok((key.to_string(), url));
-                            }
-                        }
+        let key = crate::infra::paths::config_section::<String>("anthropic_api_key")
+            .filter(|key| !key.is_empty());
+        if let Some(key) = key {
+            let url = crate::infra::paths::config_section::<String>("anthropic_base_url")
+                .filter(|u| !u.is_empty())
+                .map(|u| {
+                    let u = u.trim_end_matches('/');
+                    if u.ends_with("/v1/messages") {
+                        u.to_string()
+                    } else if u.ends_with("/v1") {
+                        format!("{}/messages", u)
+                    } else {
+                        format!("{}/v1/messages", u)
                     }
-                }
-            }
+                })
+                .unwrap_or_else(|| default_url.clone());
+            debug!(
+                "Using API key from {}, base_url: {}",
+                crate::infra::paths::config_path().display(),
+                url
+            );
+            return Ok((key, url));
         }
 
         // 2. 环境变量
@@ -414,38 +380,32 @@ impl AnthropicConfig {
     /// 加载 Webhook 配置
     fn load_webhook_config() -> Option<WebhookConfig> {
         // 从 ~/.config/code-agent-monitor/config.json 加载
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".config/code-agent-monitor/config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        if let Some(webhook) = config.get("webhook") {
-                            let gateway_url = webhook
-                                .get("gateway_url")
-                                .and_then(|u| u.as_str())
-                                .unwrap_or("http://localhost:18789")
-                                .to_string();
-                            let hook_token = webhook
-                                .get("hook_token")
-                                .and_then(|t| t.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let timeout_secs = webhook
-                                .get("timeout_secs")
-                                .and_then(|t| t.as_u64())
-                                .unwrap_or(30);
-
-                            if !hook_token.is_empty() {
-                                debug!("Loaded webhook config from ~/.config/code-agent-monitor/config.json");
-                                return Some(WebhookConfig {
-                                    gateway_url,
-                                    hook_token,
-                                    timeout_secs,
-                                });
-                            }
-                        }
-                    }
-                }
+        if let Some(webhook) = crate::infra::paths::config_section::<serde_json::Value>("webhook") {
+            let gateway_url = webhook
+                .get("gateway_url")
+                .and_then(|u| u.as_str())
+                .unwrap_or("http://localhost:18789")
+                .to_string();
+            let hook_token = webhook
+                .get("hook_token")
+                .and_then(|t| t.as_str())
+                .unwrap_or("")
+                .to_string();
+            let timeout_secs = webhook
+                .get("timeout_secs")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(30);
+
+            if !hook_token.is_empty() {
+                debug!(
+                    "Loaded webhook config from {}",
+                    crate::infra::paths::config_path().display()
+                );
+                return Some(WebhookConfig {
+                    gateway_url,
+                    hook_token,
+                    timeout_secs,
+                });
             }
         }
 
@@ -472,6 +432,8 @@ pub(crate) struct MessagesRequest {
     pub model: String,
     pub max_tokens: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub system: Option<String>,
     pub messages: Vec<Message>,
 }
@@ -538,6 +500,11 @@ impl AnthropicClient {
         Self::new(config)
     }
 
+    /// 使用指定任务的模型配置创建客户端
+    pub fn for_task(task: AiTask) -> Result<Self> {
+        Self::new(AnthropicConfig::for_task(task)?)
+    }
+
     /// 发送消息并获取响应（支持 fallback）
     pub fn complete(&self, prompt: &str, system: Option<&str>) -> Result<String> {
         // 如果有多个 providers，尝试 fallback
@@ -555,6 +522,7 @@ impl AnthropicClient {
                     model: provider.model.clone(),
                     timeout_ms: self.config.timeout_ms,
                     max_tokens: self.config.max_tokens,
+                    temperature: self.config.temperature,
                     webhook: None,
                     providers: Vec::new(),
                 };
@@ -568,7 +536,12 @@ impl AnthropicClient {
                     }
                 };
 
-                match temp_client.send_anthropic_request(prompt, system) {
+                let result = if provider.api_type == "openai" {
+                    temp_client.send_openai_chat(&temp_client.config.base_url, prompt, system)
+                } else {
+                    temp_client.send_anthropic_request(prompt, system)
+                };
+                match result {
                     Ok(result) => {
                         info!(provider = i, model = %provider.model, "Provider succeeded");
                         return Ok(result);
@@ -602,6 +575,7 @@ impl AnthropicClient {
         let request = MessagesRequest {
             model: self.config.model.clone(),
             max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
             system: system.map(|s| s.to_string()),
             messages: vec![Message {
                 role: "user".to_string(),
@@ -693,16 +667,48 @@ impl AnthropicClient {
         Ok(text)
     }
 
-    /// 发送 OpenAI 格式请求 (用于 MiniMax)
-    fn send_openai_request(&self, prompt: &str) -> Result<String> {
-        // 构建 OpenAI 格式请求
-        let request = serde_json::json!({
+    /// 构建 OpenAI 格式请求体
+    fn openai_request(&self, prompt: &str, system: Option<&str>) -> serde_json::Value {
+        let mut messages = Vec::new();
+        if let Some(system) = system {
+            messages.push(serde_json::json!({"role": "system", "content": system}));
+        }
+        messages.push(serde_json::json!({"role": "user", "content": prompt}));
+        let mut request = serde_json::json!({
             "model": self.config.model,
             "max_tokens": self.config.max_tokens,
-            "messages": [
-                {"role": "user", "content": prompt}
-            ]
+            "messages": messages,
         });
+        if let Some(temperature) = self.config.temperature {
+            request["temperature"] = serde_json::json!(temperature);
+        }
+        request
+    }
+
+    /// 向单个 OpenAI 兼容端点发送请求（`api_type` 为 `openai` 的 provider）
+    fn send_openai_chat(&self, url: &str, prompt: &str, system: Option<&str>) -> Result<String> {
+        let response = self
+            .client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("content-type", "application/json")
+            .json(&self.openai_request(prompt, system))
+            .send()
+            .map_err(|e| anyhow!("Request failed: {}", e))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .map_err(|e| anyhow!("Failed to read response: {}", e))?;
+        if !status.is_success() {
+            return Err(anyhow!("API error ({}): {}", status, body));
+        }
+        parse_openai_content(&body)
+            .ok_or_else(|| anyhow!("Failed to parse OpenAI response: {}", body))
+    }
+
+    /// 发送 OpenAI 格式请求 (用于 MiniMax)
+    fn send_openai_request(&self, prompt: &str) -> Result<String> {
+        let request = self.openai_request(prompt, None);
 
         // 尝试多个可能的端点
         let endpoints = vec![
@@ -745,21 +751,12 @@ impl AnthropicClient {
                         .map_err(|e| anyhow!("Failed to read response: {}", e))?;
 
                     // 解析 OpenAI 格式响应
-                    if let Ok(resp) = serde_json::from_str::<serde_json::Value>(&body) {
-                        if let Some(content) = resp
-                            .get("choices")
-                            .and_then(|c| c.as_array())
-                            .and_then(|arr| arr.first())
-                            .and_then(|c| c.get("message"))
-                            .and_then(|m| m.get("content"))
-                            .and_then(|c| c.as_str())
-                        {
-                            debug!(
-                                elapsed_ms = start.elapsed().as_millis(),
-                                "OpenAI request succeeded"
-                            );
-                            return Ok(content.to_string());
-                        }
+                    if let Some(content) = parse_openai_content(&body) {
+                        debug!(
+                            elapsed_ms = start.elapsed().as_millis(),
+                            "OpenAI request succeeded"
+                        );
+                        return Ok(content);
                     }
 
                     last_error = Some(anyhow!("Failed to parse OpenAI response: {}", body));
@@ -774,6 +771,18 @@ impl AnthropicClient {
     }
 }
 
+/// 取出 OpenAI 格式响应中第一条回复的内容
+fn parse_openai_content(body: &str) -> Option<String> {
+    let resp: serde_json::Value = serde_json::from_str(body).ok()?;
+    resp.get("choices")?
+        .as_array()?
+        .first()?
+        .get("message")?
+        .get("content")?
+        .as_str()
+        .map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -784,5 +793,31 @@ mod tests {
         assert_eq!(config.model, DEFAULT_MODEL);
         assert_eq!(config.timeout_ms, DEFAULT_TIMEOUT_MS);
         assert_eq!(config.max_tokens, DEFAULT_MAX_TOKENS);
+        assert!(config.temperature.is_none());
+    }
+
+    #[test]
+    fn test_request_temperature() {
+        let client = AnthropicClient::new(AnthropicConfig {
+            temperature: Some(0.2),
+            ..AnthropicConfig::default()
+        })
+        .unwrap();
+        let request = client.openai_request("hi", Some("sys"));
+        assert_eq!(request["messages"][0]["role"], "system");
+        assert_eq!(request["messages"][1]["content"], "hi");
+        assert!((request["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+
+        let request = MessagesRequest {
+            model: DEFAULT_MODEL.to_string(),
+            max_tokens: 10,
+            temperature: None,
+            system: None,
+            messages: Vec::new(),
+        };
+        assert!(serde_json::to_value(&request)
+            .unwrap()
+            .get("temperature")
+            .is_none());
     }
 }
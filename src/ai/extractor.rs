@@ -7,9 +7,11 @@ use tracing::{debug, info, trace, warn};
 
 use crate::agent::manager::AgentStatus;
 use crate::ai::client::{AnthropicClient, AnthropicConfig};
+use crate::ai::profile::AiTask;
 use crate::ai::quality::{assess_question_extraction, assess_status_detection, thresholds};
 use crate::ai::types::{NotificationContent, QuestionType};
 use crate::infra::terminal::truncate_for_status;
+use crate::notification::summarizer::ErrorCategory;
 use crate::notification::webhook::{load_webhook_config_from_file, WebhookClient};
 
 /// 内容提取超时（毫秒）- 10 秒（本地代理可能需要更长时间）
@@ -61,7 +63,8 @@ pub fn extract_notification_content(terminal_snapshot: &str) -> Result<Notificat
     let config = AnthropicConfig {
         timeout_ms: EXTRACT_TIMEOUT_MS,
         ..AnthropicConfig::auto_load()?
-    };
+    }
+    .with_task(AiTask::Extraction)?;
     let client = AnthropicClient::new(config)?;
 
     // 截取最后 N 行，避免 token 过多
@@ -290,7 +293,7 @@ fn extract_question_with_context(
     terminal_snapshot: &str,
     lines: usize,
 ) -> Result<InternalResult, ExtractionError> {
-    let client = match AnthropicClient::from_config() {
+    let client = match AnthropicClient::for_task(AiTask::Extraction) {
         Ok(c) => c,
         Err(e) => {
             warn!(error = %e, "Failed to create Anthropic client");
@@ -464,12 +467,15 @@ pub fn is_agent_processing(terminal_snapshot: &str) -> AgentStatus {
     let start = std::time::Instant::now();
 
     // 创建带 3 秒超时的客户端
-    let config = match AnthropicConfig::auto_load() {
-        Ok(c) => AnthropicConfig {
+    let config = match AnthropicConfig::auto_load().and_then(|c| {
+        AnthropicConfig {
             timeout_ms: 15000,
             max_tokens: 50, // 只需要简短回答
             ..c
-        },
+        }
+        .with_task(AiTask::Status)
+    }) {
+        Ok(c) => c,
         Err(e) => {
             warn!(error = %e, "Failed to load Anthropic config for is_agent_processing");
             return AgentStatus::Unknown;
@@ -573,6 +579,70 @@ pub fn is_agent_processing(terminal_snapshot: &str) -> AgentStatus {
     status
 }
 
+// ============================================================================
+// 错误分类
+// ============================================================================
+
+/// 使用 Haiku 对关键字无法识别的错误分类
+///
+/// 关键字分类见 `NotificationSummarizer::classify_error`，这里只作为兜底。
+/// API 不可用或返回无法解析时返回 None。
+pub fn classify_error_with_ai(error_message: &str) -> Option<ErrorCategory> {
+    let config = match AnthropicConfig::auto_load().and_then(|c| {
+        AnthropicConfig {
+            timeout_ms: EXTRACT_TIMEOUT_MS,
+            max_tokens: 20,
+            ..c
+        }
+        .with_task(AiTask::Classification)
+    }) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!(error = %e, "Failed to load Anthropic config for classify_error_with_ai");
+            return None;
+        }
+    };
+    let client = AnthropicClient::new(config).ok()?;
+
+    let system = "你是一个错误日志分类专家。严格返回且只能返回以下之一：RATE_LIMIT / AUTH_FAILURE / NETWORK / COMPILE_FAILURE / TEST_FAILURE / CONTEXT_WINDOW / OTHER。禁止输出任何解释。";
+    let error_text: String = error_message.chars().take(2000).collect();
+    let prompt = format!(
+        r#"AI 编码助手报告了以下错误，判断类别：
+
+<error>
+{error_text}
+</error>
+
+- RATE_LIMIT: API 限流、过载、额度或用量上限
+- AUTH_FAILURE: API key 无效、登录过期、未授权
+- NETWORK: 连接失败、DNS、代理、超时等网络问题
+- COMPILE_FAILURE: 代码编译或类型检查失败
+- TEST_FAILURE: 测试用例失败
+- CONTEXT_WINDOW: 上下文或 prompt 超出模型长度限制
+- OTHER: 其他"#
+    );
+
+    match client.complete(&prompt, Some(system)) {
+        Ok(response) => {
+            let category = parse_error_category(&response);
+            debug!(response = %response.trim(), category = ?category, "classify_error_with_ai completed");
+            category
+        }
+        Err(e) => {
+            warn!(error = %e, "Haiku API call failed for classify_error_with_ai");
+            None
+        }
+    }
+}
+
+/// 解析 AI 返回的错误类别（如 `RATE_LIMIT`）
+fn parse_error_category(response: &str) -> Option<ErrorCategory> {
+    let normalized = response.trim().to_lowercase();
+    ErrorCategory::ALL
+        .into_iter()
+        .find(|c| normalized.contains(c.as_str()))
+}
+
 /// 检测终端快照是否包含等待用户输入的问题
 ///
 /// 用于 stop 事件处理：Claude Code 可能在输出问题后触发 stop 而非 idle_prompt。
@@ -715,7 +785,7 @@ fn extract_formatted_message_with_context(
     terminal_snapshot: &str,
     lines: usize,
 ) -> Result<SimpleExtractionResult, ExtractionError> {
-    let client = match AnthropicClient::from_config() {
+    let client = match AnthropicClient::for_task(AiTask::Extraction) {
         Ok(c) => c,
         Err(e) => {
             warn!(error = %e, "Failed to create Anthropic client");
@@ -887,6 +957,19 @@ fn extract_json_from_output(output: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_error_category() {
+        assert_eq!(
+            parse_error_category("RATE_LIMIT\n"),
+            Some(ErrorCategory::RateLimit)
+        );
+        assert_eq!(
+            parse_error_category("  context_window"),
+            Some(ErrorCategory::ContextWindow)
+        );
+        assert_eq!(parse_error_category("UNSURE"), None);
+    }
+
     #[test]
     fn test_extract_json_from_output() {
         let output = r#"Here is the JSON:
@@ -0,0 +1,203 @@
+//! 按任务选择模型 - 不同 AI 任务使用不同的模型、token 上限和温度
+//!
+//! 提取、分类这类高频小任务适合便宜快速的模型，总结和对比则值得用更强的模型。
+//! config.json 中按任务配置，未配置的任务沿用全局配置：
+//!
+//! ```json
+//! "ai_tasks": {
+//!   "extraction": {"model": "claude-haiku-4-5", "max_tokens": 800, "temperature": 0},
+//!   "summary": {"model": "claude-sonnet-4-5"},
+//!   "comparison": {"model": "gpt-4o", "api_type": "openai",
+//!                  "base_url": "https://api.openai.com/v1", "api_key": "sk-..."}
+//! }
+//! ```
+//!
+//! 只写 `model` 时所有 provider 都改用该模型；写了 `base_url` 时该任务优先使用独立的
+//! provider（`model` 只作用于它），`providers` 中原有的 provider 保持原模型作为 fallback。
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use super::client::{AnthropicConfig, ProviderConfig};
+use crate::infra::privacy::is_local_url;
+
+/// AI 任务类型（config.json `ai_tasks` 的键）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiTask {
+    /// 从终端快照提取问题和通知内容
+    Extraction,
+    /// 判断 agent 是否仍在处理
+    Status,
+    /// 错误分类兜底
+    Classification,
+    /// `cam summary` 的 agent 摘要
+    Summary,
+    /// `cam compare` 的方案对比
+    Comparison,
+}
+
+impl AiTask {
+    /// 输入中包含终端快照（隐私模式下只能交给本机模型）
+    pub fn reads_snapshot(&self) -> bool {
+        matches!(self, Self::Extraction | Self::Status | Self::Summary)
+    }
+}
+
+/// 单个任务的模型配置，未设置的字段沿用全局配置
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TaskProfile {
+    pub model: Option<String>,
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    pub timeout_ms: Option<u64>,
+    /// 独立 provider 的 API 地址
+    pub base_url: Option<String>,
+    /// 独立 provider 的 API 密钥，未设置时沿用全局密钥
+    pub api_key: Option<String>,
+    /// 独立 provider 的接口格式（`anthropic` / `openai`）
+    pub api_type: Option<String>,
+}
+
+impl TaskProfile {
+    /// 在全局配置上叠加任务配置
+    pub fn apply(&self, mut config: AnthropicConfig) -> AnthropicConfig {
+        if let Some(model) = &self.model {
+            config.model = model.clone();
+            if self.base_url.is_none() {
+                for provider in &mut config.providers {
+                    provider.model = model.clone();
+                }
+            }
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config.max_tokens = max_tokens;
+        }
+        if self.temperature.is_some() {
+            config.temperature = self.temperature;
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            config.timeout_ms = timeout_ms;
+        }
+        if let Some(base_url) = &self.base_url {
+            let provider = ProviderConfig {
+                api_key: self
+                    .api_key
+                    .clone()
+                    .unwrap_or_else(|| config.api_key.clone()),
+                base_url: base_url.clone(),
+                model: config.model.clone(),
+                api_type: self.api_type.clone().unwrap_or_default(),
+            };
+            config.providers.insert(0, provider);
+        }
+        config
+    }
+}
+
+/// 全部任务配置（config.json 的 `ai_tasks`）
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct TaskProfiles(HashMap<AiTask, TaskProfile>);
+
+impl TaskProfiles {
+    /// 读取 config.json，缺失或无效时为空
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("ai_tasks").unwrap_or_default()
+    }
+
+    pub fn get(&self, task: AiTask) -> Option<&TaskProfile> {
+        self.0.get(&task)
+    }
+
+    /// 按任务调整配置，任务未配置时原样返回
+    pub fn apply(&self, task: AiTask, config: AnthropicConfig) -> AnthropicConfig {
+        match self.get(task) {
+            Some(profile) => profile.apply(config),
+            None => config,
+        }
+    }
+}
+
+/// 只保留本机地址的 provider（`privacy.local_snapshots`），没有时返回错误
+///
+/// 旧式单一配置在 Anthropic 格式失败时会尝试外部端点，因此本机模型需要写在 `providers` 中。
+pub fn local_providers_only(mut config: AnthropicConfig) -> Result<AnthropicConfig> {
+    config
+        .providers
+        .retain(|provider| is_local_url(&provider.base_url));
+    let Some(primary) = config.providers.first() else {
+        bail!("privacy.local_snapshots is enabled and no local provider is configured");
+    };
+    config.api_key = primary.api_key.clone();
+    config.base_url = primary.base_url.clone();
+    config.model = primary.model.clone();
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_config() -> AnthropicConfig {
+        AnthropicConfig {
+            api_key: "global-key".to_string(),
+            base_url: "https://api.anthropic.com".to_string(),
+            providers: vec![ProviderConfig {
+                api_key: "global-key".to_string(),
+                base_url: "https://api.anthropic.com".to_string(),
+                model: "MiniMax/M2.5".to_string(),
+                api_type: String::new(),
+            }],
+            ..AnthropicConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_task_profiles_apply() {
+        let profiles: TaskProfiles = serde_json::from_value(serde_json::json!({
+            "extraction": {"model": "claude-haiku-4-5", "max_tokens": 800, "temperature": 0.0},
+            "comparison": {"model": "gpt-4o", "api_type": "openai", "base_url": "https://api.openai.com/v1"}
+        }))
+        .unwrap();
+
+        let config = profiles.apply(AiTask::Extraction, base_config());
+        assert_eq!(config.model, "claude-haiku-4-5");
+        assert_eq!(config.providers[0].model, "claude-haiku-4-5");
+        assert_eq!(config.max_tokens, 800);
+        assert_eq!(config.temperature, Some(0.0));
+
+        let config = profiles.apply(AiTask::Comparison, base_config());
+        assert_eq!(config.providers.len(), 2);
+        assert_eq!(config.providers[0].base_url, "https://api.openai.com/v1");
+        assert_eq!(config.providers[0].api_type, "openai");
+        assert_eq!(config.providers[0].api_key, "global-key");
+        assert_eq!(config.providers[1].model, "MiniMax/M2.5");
+
+        // 未配置的任务沿用全局配置
+        let config = profiles.apply(AiTask::Summary, base_config());
+        assert_eq!(config, base_config());
+    }
+
+    #[test]
+    fn test_local_providers_only() {
+        assert!(local_providers_only(base_config()).is_err());
+        assert!(AiTask::Summary.reads_snapshot());
+        assert!(!AiTask::Comparison.reads_snapshot());
+
+        let mut config = base_config();
+        config.providers.push(ProviderConfig {
+            api_key: String::new(),
+            base_url: "http://localhost:11434".to_string(),
+            model: "qwen2.5".to_string(),
+            api_type: "openai".to_string(),
+        });
+        let config = local_providers_only(config).unwrap();
+        assert_eq!(config.providers.len(), 1);
+        assert_eq!(config.model, "qwen2.5");
+        assert_eq!(config.base_url, "http://localhost:11434");
+    }
+}
@@ -6,6 +6,7 @@
 //! - 内容合理性评估
 //! - 置信度计算
 
+use crate::agent::extractor::{ExtractedMessage, MessageType};
 use crate::agent::manager::AgentStatus;
 use crate::ai::types::{NotificationContent, QuestionType};
 use tracing::warn;
@@ -153,6 +154,65 @@ pub fn assess_question_extraction(content: &NotificationContent) -> QualityAsses
     assessment
 }
 
+/// 评估 ReAct 提取消息的质量（用于提取阶梯决定是否升级到下一级）
+///
+/// # 参数
+/// - `message`: 提取的消息
+///
+/// # 返回
+/// 质量评估结果
+pub fn assess_extracted_message(message: &ExtractedMessage) -> QualityAssessment {
+    // 空闲判断本身就是完整结果
+    if matches!(message.message_type, MessageType::Idle { .. }) {
+        return QualityAssessment::valid(0.9);
+    }
+
+    let mut assessment = QualityAssessment::valid(1.0);
+    let content = message.content.trim();
+
+    if content.is_empty() {
+        return QualityAssessment::invalid(vec!["消息内容为空".to_string()]);
+    }
+    if content.chars().count() > 800 {
+        assessment = assessment.with_issue("消息内容过长", 0.1);
+    }
+    if message.fingerprint.trim().is_empty() {
+        assessment = assessment.with_issue("缺少语义指纹", 0.2);
+    }
+
+    // 选择题应该能看到编号选项
+    if message.message_type == MessageType::Choice {
+        let numbered = content
+            .lines()
+            .filter(|line| {
+                let line = line.trim_start_matches(['❯', '>', ' ']);
+                line.chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphanumeric())
+                    && matches!(line.chars().nth(1), Some('.' | ')' | '、'))
+            })
+            .count();
+        if numbered < 2 {
+            assessment = assessment.with_issue("选择题但没有编号选项", 0.3);
+        }
+    }
+
+    // 终端 UI 元素混入消息
+    let noise = ['⏺', '✻', '⎿', '│', '╭', '╰'];
+    if content.chars().any(|c| noise.contains(&c)) {
+        assessment = assessment.with_issue("消息包含终端 UI 元素", 0.2);
+    }
+
+    let indicators = [
+        "?", "？", "请", "是否", "选择", "确认", "输入", "回复", "y/n",
+    ];
+    if !indicators.iter().any(|i| content.contains(i)) {
+        assessment = assessment.with_issue("消息缺少问题标志词", 0.15);
+    }
+
+    assessment
+}
+
 /// 评估状态检测结果的质量
 ///
 /// # 参数
@@ -298,6 +358,30 @@ mod tests {
         assert!(result.confidence < 0.8);
     }
 
+    #[test]
+    fn test_assess_extracted_message() {
+        let message = |content: &str, message_type: MessageType| ExtractedMessage {
+            content: content.to_string(),
+            fingerprint: "deploy-target".to_string(),
+            context_complete: true,
+            message_type,
+            is_decision_required: false,
+        };
+        let good = message(
+            "部署到哪个环境？\n1. staging\n2. production\n\n回复数字选择",
+            MessageType::Choice,
+        );
+        assert!(assess_extracted_message(&good).confidence >= thresholds::HIGH);
+
+        let no_options = message("部署到哪个环境？", MessageType::Choice);
+        assert!(assess_extracted_message(&no_options).confidence < thresholds::HIGH);
+
+        let noisy = message("⏺ Bash(ls)\n⎿ done", MessageType::OpenEnded);
+        assert!(assess_extracted_message(&noisy).confidence < thresholds::HIGH);
+
+        assert!(!assess_extracted_message(&message("", MessageType::Confirmation)).is_valid);
+    }
+
     #[test]
     fn test_confidence_thresholds() {
         assert!(thresholds::HIGH > thresholds::MEDIUM);
@@ -0,0 +1,52 @@
+//! `cam attach` 命令 - 附着到 agent 所在的 session
+//!
+//! tmux / zellij / screen 后端执行各自的附着命令；PTY 后端没有外部复用器，
+//! 通过 host 的 tail 模式在当前终端回放并转发输出。
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::infra::{multiplexer, pty};
+
+#[derive(Args, Debug)]
+pub struct AttachArgs {
+    /// agent ID 或 session 名称
+    pub target: String,
+}
+
+/// `cam __pty-host` 参数（内部使用，由 PTY 后端启动）
+#[derive(Args, Debug)]
+pub struct PtyHostArgs {
+    pub session: String,
+    pub working_dir: String,
+    pub command: String,
+}
+
+/// 处理 `cam attach`
+pub fn handle_attach(args: AttachArgs) -> Result<()> {
+    let session = AgentManager::new()
+        .get_agent(&args.target)?
+        .map(|agent| agent.tmux_session)
+        .unwrap_or(args.target);
+
+    let mux = multiplexer::from_config();
+    if !mux.session_exists(&session) {
+        return Err(CliError::SessionNotFound(format!("未找到 session: {}", session)).into());
+    }
+
+    if mux.name() == "pty" {
+        return pty::attach(&session);
+    }
+    let status = mux.attach_command(&session).status()?;
+    if !status.success() {
+        return Err(CliError::Other(format!("附着 {} 失败: {}", session, status)).into());
+    }
+    Ok(())
+}
+
+/// 处理 `cam __pty-host`
+pub fn handle_pty_host(args: PtyHostArgs) -> Result<()> {
+    pty::run_host(&args.session, &args.working_dir, &args.command)
+}
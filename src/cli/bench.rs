@@ -0,0 +1,439 @@
+//! `cam bench` 命令 - 用录制的终端快照对比不同提取配置的延迟与 AI 调用次数
+//!
+//! 每个快照依次经过提取（ReAct 循环 + 提取阶梯）、格式化和试运行投递（只序列化，不发送），
+//! 与 `cam notify` 的通知流程一致。每个配置使用独立的临时阶梯状态，不影响 `cam stats`
+//! 的指标和嵌入缓存；API 级别的每分钟限流在基准测试中不生效。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::agent::extractor::{
+    extract_message_with, ExtractionResult, FallbackLadder, HaikuExtractor, LadderConfig,
+    LadderStage, LadderStore, MessageExtractor, StageMetrics,
+};
+use crate::agent::recording::{load_bundle, recordings_dir, RecordFrame};
+use crate::cli::CliError;
+use crate::embedding::{Embeddings, TemplateLibrary};
+use crate::infra::schema::default_data_dir;
+use crate::notification::latency::LatencyStats;
+use crate::notification::{classify_waiting, NotificationEvent, SystemEventPayload};
+
+/// 基准测试事件使用的 agent ID
+const BENCH_AGENT_ID: &str = "cam-bench";
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// 录制包或包含录制包的目录（默认 ~/.config/code-agent-monitor/recordings/）
+    pub paths: Vec<PathBuf>,
+    /// 要对比的配置，可重复：local（只用本地识别）、ladder（config.json 的 ai_ladder）、
+    /// haiku（只用 AI），或 ai_ladder 格式的 JSON 文件；默认 local 和 ladder
+    #[arg(long = "variant", value_name = "VARIANT")]
+    pub variants: Vec<String>,
+    /// 最多使用多少个快照
+    #[arg(long)]
+    pub limit: Option<usize>,
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 统计 API 调用次数（提取和处理中判断都算一次）
+struct CountingExtractor {
+    inner: Box<dyn MessageExtractor>,
+    calls: Arc<AtomicU64>,
+}
+
+impl MessageExtractor for CountingExtractor {
+    fn extract(&self, terminal_snapshot: &str, lines: usize) -> ExtractionResult {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.inner.extract(terminal_snapshot, lines)
+    }
+
+    fn is_processing(&self, terminal_snapshot: &str) -> bool {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.inner.is_processing(terminal_snapshot)
+    }
+}
+
+/// 参与对比的一个配置
+struct Candidate {
+    name: String,
+    extractor: Box<dyn MessageExtractor>,
+    calls: Arc<AtomicU64>,
+    /// 阶梯状态（只用 AI 时为 None）
+    store: Option<LadderStore>,
+}
+
+impl Candidate {
+    fn counting(
+        extractor: Box<dyn MessageExtractor>,
+        calls: &Arc<AtomicU64>,
+    ) -> Box<dyn MessageExtractor> {
+        Box::new(CountingExtractor {
+            inner: extractor,
+            calls: Arc::clone(calls),
+        })
+    }
+
+    /// 按配置创建阶梯，状态写到 `state_dir`
+    fn ladder(name: &str, mut config: LadderConfig, state_dir: &Path) -> Self {
+        for per_minute in [&mut config.haiku_per_minute, &mut config.large_per_minute] {
+            if *per_minute > 0 {
+                *per_minute = u32::MAX;
+            }
+        }
+        let calls = Arc::new(AtomicU64::new(0));
+        let haiku = (config.haiku_per_minute > 0)
+            .then(|| HaikuExtractor::new().ok())
+            .flatten()
+            .map(|e| Self::counting(Box::new(e), &calls));
+        let large = config
+            .large_model
+            .as_deref()
+            .and_then(|model| HaikuExtractor::with_model(model).ok())
+            .map(|e| Self::counting(Box::new(e), &calls));
+        let store = LadderStore::at(&state_dir.join(format!("{}.json", name)));
+        let embeddings = Embeddings::load();
+        let library = TemplateLibrary::load_or_build(&embeddings).ok();
+        let ladder = FallbackLadder::new(config, store.clone(), haiku, large)
+            .with_embeddings(embeddings, library);
+        Self {
+            name: name.to_string(),
+            extractor: Box::new(ladder),
+            calls,
+            store: Some(store),
+        }
+    }
+
+    /// 解析 `--variant` 的值
+    fn parse(value: &str, state_dir: &Path) -> Result<Self> {
+        match value {
+            "local" => Ok(Self::ladder(
+                value,
+                LadderConfig {
+                    haiku_per_minute: 0,
+                    large_model: None,
+                    ..LadderConfig::load()
+                },
+                state_dir,
+            )),
+            "ladder" => Ok(Self::ladder(value, LadderConfig::load(), state_dir)),
+            "haiku" => {
+                let haiku = HaikuExtractor::new().map_err(|e| {
+                    CliError::InvalidArgument(format!("haiku 配置需要可用的 AI API: {}", e))
+                })?;
+                let calls = Arc::new(AtomicU64::new(0));
+                Ok(Self {
+                    name: value.to_string(),
+                    extractor: Self::counting(Box::new(haiku), &calls),
+                    calls,
+                    store: None,
+                })
+            }
+            path => {
+                let path = Path::new(path);
+                let config: LadderConfig = fs::read_to_string(path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|content| Ok(serde_json::from_str(&content)?))
+                    .map_err(|e| {
+                        CliError::InvalidArgument(format!(
+                            "无效的配置 {}（应为 local、ladder、haiku 或 ai_ladder JSON 文件）: {}",
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "custom".to_string());
+                Ok(Self::ladder(&name, config, state_dir))
+            }
+        }
+    }
+}
+
+/// 流水线延迟百分位（微秒）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BenchLatency {
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// 一个配置的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub config: String,
+    pub snapshots: usize,
+    /// 提取到消息的快照数
+    pub extracted: usize,
+    /// API 调用次数
+    pub ai_calls: u64,
+    pub latency: Option<BenchLatency>,
+    /// 阶梯各级指标（只用 AI 时为空）
+    pub stages: BTreeMap<LadderStage, StageMetrics>,
+}
+
+/// 收集录制包路径：目录取其中的 `.jsonl` 文件（按文件名排序）
+fn bundle_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut bundles = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            bundles.push(path.clone());
+            continue;
+        }
+        let mut found: Vec<PathBuf> = fs::read_dir(path)
+            .with_context(|| format!("无法读取目录 {}", path.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        found.sort();
+        bundles.extend(found);
+    }
+    Ok(bundles)
+}
+
+/// 录制包中的终端快照（包括 hook 输入附带的快照），按录制顺序
+fn corpus_snapshots(frames: Vec<RecordFrame>) -> impl Iterator<Item = String> {
+    frames.into_iter().filter_map(|frame| match frame {
+        RecordFrame::Snapshot { content, .. } => Some(content),
+        RecordFrame::Hook {
+            terminal_snapshot, ..
+        } => terminal_snapshot,
+        _ => None,
+    })
+}
+
+/// 对一个快照执行提取 → 格式化 → 试运行投递，返回是否提取到消息
+fn run_pipeline(extractor: &dyn MessageExtractor, snapshot: &str) -> Result<bool> {
+    let extracted = extract_message_with(extractor, snapshot);
+
+    let event = NotificationEvent::waiting_for_input(BENCH_AGENT_ID, "Unknown")
+        .with_terminal_snapshot(snapshot);
+    let kind = classify_waiting(
+        "Unknown",
+        extracted.as_ref().is_some_and(|m| m.is_decision_required),
+        extracted.as_ref().map(|m| &m.message_type),
+        extracted.as_ref().map_or(snapshot, |m| m.content.as_str()),
+    );
+    let mut payload = SystemEventPayload::from_event(&event, kind.urgency());
+    payload.set_waiting_kind(kind);
+    let found = extracted.is_some();
+    if let Some(message) = extracted {
+        payload.set_extracted_message(message.content, message.fingerprint);
+    }
+
+    // 试运行投递：生成要发送的文本和请求体，不发送
+    std::hint::black_box(payload.to_message());
+    std::hint::black_box(serde_json::to_vec(&payload.to_json())?);
+    Ok(found)
+}
+
+fn run_candidate(candidate: &Candidate, corpus: &[String]) -> Result<BenchReport> {
+    let mut extracted = 0;
+    let mut durations = Vec::with_capacity(corpus.len());
+    for snapshot in corpus {
+        let start = Instant::now();
+        if run_pipeline(candidate.extractor.as_ref(), snapshot)? {
+            extracted += 1;
+        }
+        durations.push(start.elapsed().as_micros() as u64);
+    }
+    Ok(BenchReport {
+        config: candidate.name.clone(),
+        snapshots: corpus.len(),
+        extracted,
+        ai_calls: candidate.calls.load(Ordering::Relaxed),
+        latency: LatencyStats::compute(durations).map(|stats| BenchLatency {
+            p50_us: stats.p50_ms,
+            p90_us: stats.p90_ms,
+            p99_us: stats.p99_ms,
+            max_us: stats.max_ms,
+        }),
+        stages: candidate
+            .store
+            .as_ref()
+            .map(|store| store.metrics())
+            .unwrap_or_default(),
+    })
+}
+
+fn format_micros(us: u64) -> String {
+    match us {
+        0..1_000 => format!("{}µs", us),
+        1_000..1_000_000 => format!("{:.1}ms", us as f64 / 1000.0),
+        _ => format!("{:.1}s", us as f64 / 1_000_000.0),
+    }
+}
+
+fn report_row(report: &BenchReport) -> String {
+    let latency = |pick: fn(&BenchLatency) -> u64| {
+        report
+            .latency
+            .as_ref()
+            .map(|l| format_micros(pick(l)))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    format!(
+        "{:<12} {:>6} {:>6} {:>8} {:>8} {:>9} {:>9} {:>9} {:>9}",
+        report.config,
+        report.snapshots,
+        report.extracted,
+        report.ai_calls,
+        format!(
+            "{:.2}",
+            report.ai_calls as f64 / report.snapshots.max(1) as f64
+        ),
+        latency(|l| l.p50_us),
+        latency(|l| l.p90_us),
+        latency(|l| l.p99_us),
+        latency(|l| l.max_us)
+    )
+}
+
+/// 处理 `cam bench`
+pub fn handle_bench(args: BenchArgs) -> Result<()> {
+    let paths = if args.paths.is_empty() {
+        vec![recordings_dir(&default_data_dir())]
+    } else {
+        args.paths
+    };
+    let bundles = bundle_paths(&paths)?;
+    let mut corpus = Vec::new();
+    for bundle in &bundles {
+        corpus.extend(corpus_snapshots(load_bundle(bundle)?));
+    }
+    if let Some(limit) = args.limit {
+        corpus.truncate(limit);
+    }
+    if corpus.is_empty() {
+        return Err(CliError::InvalidArgument(
+            "没有可用的终端快照，先用 cam record 录制 agent 会话".to_string(),
+        )
+        .into());
+    }
+
+    let state_dir = std::env::temp_dir().join(format!("cam-bench-{}", std::process::id()));
+    let configs = if args.variants.is_empty() {
+        vec!["local".to_string(), "ladder".to_string()]
+    } else {
+        args.variants
+    };
+    let reports = configs
+        .iter()
+        .map(|value| {
+            let candidate = Candidate::parse(value, &state_dir)?;
+            if !args.json {
+                eprintln!("运行 {} ...", candidate.name);
+            }
+            run_candidate(&candidate, &corpus)
+        })
+        .collect::<Result<Vec<_>>>();
+    let _ = fs::remove_dir_all(&state_dir);
+    let reports = reports?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} 个录制包，{} 个快照（提取 → 格式化 → 试运行投递）\n",
+        bundles.len(),
+        corpus.len()
+    );
+    println!(
+        "{:<12} {:>6} {:>6} {:>8} {:>8} {:>9} {:>9} {:>9} {:>9}",
+        "配置", "快照", "提取", "AI 调用", "每快照", "p50", "p90", "p99", "最大"
+    );
+    for report in &reports {
+        println!("{}", report_row(report));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_corpus_snapshots() {
+        let frames = vec![
+            RecordFrame::Header {
+                format_version: 1,
+                agent_id: "cam-1".to_string(),
+                agent_type: "claude".to_string(),
+                project_path: "/tmp".to_string(),
+                started_at: Utc::now(),
+            },
+            RecordFrame::Snapshot {
+                at: Utc::now(),
+                content: "a".to_string(),
+            },
+            RecordFrame::Hook {
+                at: Utc::now(),
+                event: "stop".to_string(),
+                payload: "{}".to_string(),
+                terminal_snapshot: Some("b".to_string()),
+            },
+            RecordFrame::Hook {
+                at: Utc::now(),
+                event: "stop".to_string(),
+                payload: "{}".to_string(),
+                terminal_snapshot: None,
+            },
+            RecordFrame::End { at: Utc::now() },
+        ];
+        assert_eq!(corpus_snapshots(frames).collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_run_candidate_counts_ai_calls() {
+        let tmp = TempDir::new().unwrap();
+        let store = LadderStore::at(&tmp.path().join("local.json"));
+        let calls = Arc::new(AtomicU64::new(0));
+        let candidate = Candidate {
+            name: "local".to_string(),
+            extractor: Box::new(FallbackLadder::new(
+                LadderConfig {
+                    haiku_per_minute: 0,
+                    ..LadderConfig::default()
+                },
+                store.clone(),
+                None,
+                None,
+            )),
+            calls,
+            store: Some(store),
+        };
+        let corpus = vec![
+            "Overwrite existing config? [y/N]".to_string(),
+            "Compiling crate...".to_string(),
+        ];
+
+        let report = run_candidate(&candidate, &corpus).unwrap();
+        assert_eq!(report.snapshots, 2);
+        assert_eq!(report.extracted, 1);
+        assert_eq!(report.ai_calls, 0);
+        assert_eq!(report.latency.as_ref().map(|_| ()), Some(()));
+        assert_eq!(report.stages[&LadderStage::Pattern].accepted, 1);
+    }
+
+    #[test]
+    fn test_format_micros() {
+        assert_eq!(format_micros(850), "850µs");
+        assert_eq!(format_micros(12_300), "12.3ms");
+        assert_eq!(format_micros(1_500_000), "1.5s");
+    }
+}
@@ -11,6 +11,8 @@ use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::infra::schema::{CONFIG_SCHEMA, SCHEMA_VERSION_FIELD};
+
 /// Bootstrap 命令参数
 #[derive(Args)]
 pub struct BootstrapArgs {
@@ -47,13 +49,11 @@ struct ProviderEntry {
 }
 
 fn config_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".config/code-agent-monitor")
+    crate::infra::paths::config_dir()
 }
 
 fn config_path() -> PathBuf {
-    config_dir().join("config.json")
+    crate::infra::paths::config_path()
 }
 
 /// 处理 bootstrap 命令
@@ -585,6 +585,8 @@ fn run_setup(tool: &str) -> Result<()> {
         tool: tool.to_string(),
         yes: true,
         dry_run: false,
+        shim: false,
+        events: None,
     })
 }
 
@@ -634,6 +636,8 @@ fn write_config(config: &BootstrapConfig, existing: &Option<serde_json::Value>)
         obj.insert("providers".to_string(), json!(providers));
     }
 
+    obj.insert(SCHEMA_VERSION_FIELD.to_string(), json!(CONFIG_SCHEMA.version));
+
     let path = config_path();
     let content = serde_json::to_string_pretty(&output)?;
     fs::write(&path, &content).context("写入配置文件失败")?;
@@ -0,0 +1,530 @@
+//! `cam compare` 命令 - 对比两个 agent 对同一任务的结果
+//!
+//! 分别收集两个 agent 的文件改动（从 agent 启动时的提交到当前工作区的 git diff，含未跟踪文件）
+//! 和 transcript 中最后的结论，列出改动文件的异同，再请 AI 从方案、风险、测试覆盖三方面
+//! 给出结构化对比。AI 不可用或使用 `--no-ai` 时只输出改动对比。
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::agent::AgentManager;
+use crate::ai::client::AnthropicClient;
+use crate::ai::profile::AiTask;
+use crate::cli::CliError;
+use crate::infra::jsonl::{JsonlEvent, JsonlParser};
+
+/// 发给 AI 的每个 agent 的 diff 上限（字符）
+const MAX_DIFF_CHARS: usize = 12_000;
+/// 结论取 transcript 中最后几条助手消息
+const CONCLUSION_MESSAGES: usize = 3;
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    /// 第一个 agent_id（或项目目录）
+    pub agent_a: String,
+    /// 第二个 agent_id（或项目目录）
+    pub agent_b: String,
+    /// diff 的基准提交（默认取 agent 启动时的 HEAD）
+    #[arg(long)]
+    pub base: Option<String>,
+    /// 不调用 AI，只对比文件改动
+    #[arg(long)]
+    pub no_ai: bool,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+    /// 把报告写入文件（Markdown，`--json` 时为 JSON）
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// 单个文件的改动行数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub added: u64,
+    pub removed: u64,
+}
+
+/// 一个 agent 的工作结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentWork {
+    pub agent_id: String,
+    pub agent_type: Option<String>,
+    pub project_path: String,
+    /// diff 基准提交
+    pub base: Option<String>,
+    pub files: Vec<FileChange>,
+    /// 完整 diff（发给 AI，报告中不输出）
+    #[serde(skip)]
+    pub diff: String,
+    /// transcript 中最后的助手消息
+    pub conclusion: Option<String>,
+    /// 运行过的测试命令
+    pub test_commands: Vec<String>,
+}
+
+/// AI 对某一方面的对比
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AspectComparison {
+    pub a: String,
+    pub b: String,
+}
+
+/// AI 给出的结构化对比
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiComparison {
+    pub approach: AspectComparison,
+    pub risks: AspectComparison,
+    pub test_coverage: AspectComparison,
+    pub recommendation: String,
+}
+
+/// 对比报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareReport {
+    pub a: AgentWork,
+    pub b: AgentWork,
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+    pub both: Vec<String>,
+    pub ai: Option<AiComparison>,
+}
+
+impl CompareReport {
+    pub fn new(a: AgentWork, b: AgentWork) -> Self {
+        let files_a: BTreeSet<&str> = a.files.iter().map(|f| f.path.as_str()).collect();
+        let files_b: BTreeSet<&str> = b.files.iter().map(|f| f.path.as_str()).collect();
+        let to_vec = |set: BTreeSet<&&str>| set.into_iter().map(|s| s.to_string()).collect();
+        let only_a = to_vec(files_a.difference(&files_b).collect());
+        let only_b = to_vec(files_b.difference(&files_a).collect());
+        let both = to_vec(files_a.intersection(&files_b).collect());
+        Self {
+            a,
+            b,
+            only_a,
+            only_b,
+            both,
+            ai: None,
+        }
+    }
+
+    /// Markdown 报告
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {} vs {}\n", self.a.agent_id, self.b.agent_id);
+        for (label, work) in [("A", &self.a), ("B", &self.b)] {
+            let added: u64 = work.files.iter().map(|f| f.added).sum();
+            let removed: u64 = work.files.iter().map(|f| f.removed).sum();
+            out.push_str(&format!(
+                "\n## {}: {}{}\n\n- 项目: {}\n- 基准: {}\n- 改动: {} 个文件 (+{} -{})\n",
+                label,
+                work.agent_id,
+                work.agent_type
+                    .as_deref()
+                    .map(|t| format!(" ({})", t))
+                    .unwrap_or_default(),
+                work.project_path,
+                work.base.as_deref().unwrap_or("HEAD"),
+                work.files.len(),
+                added,
+                removed
+            ));
+            if work.test_commands.is_empty() {
+                out.push_str("- 测试: 未运行\n");
+            } else {
+                out.push_str(&format!("- 测试: {}\n", work.test_commands.join("; ")));
+            }
+            if let Some(ref conclusion) = work.conclusion {
+                out.push_str("\n结论:\n\n");
+                for line in conclusion.lines() {
+                    out.push_str(&format!("> {}\n", line));
+                }
+            }
+        }
+
+        out.push_str("\n## 文件改动\n\n");
+        for (label, files) in [
+            ("两者都改动", &self.both),
+            ("仅 A", &self.only_a),
+            ("仅 B", &self.only_b),
+        ] {
+            if files.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("{}:\n", label));
+            for file in files {
+                out.push_str(&format!("- {}\n", file));
+            }
+            out.push('\n');
+        }
+
+        if let Some(ref ai) = self.ai {
+            out.push_str("## AI 对比\n\n| | A | B |\n|---|---|---|\n");
+            for (label, aspect) in [
+                ("方案", &ai.approach),
+                ("风险", &ai.risks),
+                ("测试覆盖", &ai.test_coverage),
+            ] {
+                out.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    label,
+                    table_cell(&aspect.a),
+                    table_cell(&aspect.b)
+                ));
+            }
+            if !ai.recommendation.is_empty() {
+                out.push_str(&format!("\n建议: {}\n", ai.recommendation));
+            }
+        }
+        out
+    }
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to execute git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 解析 `git diff --numstat`（二进制文件记为 0 行）
+pub fn parse_numstat(output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let added = parts.next()?.parse().unwrap_or(0);
+            let removed = parts.next()?.parse().unwrap_or(0);
+            let path = parts.next()?.trim();
+            (!path.is_empty()).then(|| FileChange {
+                path: path.to_string(),
+                added,
+                removed,
+            })
+        })
+        .collect()
+}
+
+/// agent 启动时的提交（找不到时为 HEAD）
+fn base_commit(repo: &Path, started_at: Option<&str>) -> Option<String> {
+    if let Some(started_at) = started_at {
+        let before = format!("--before={}", started_at);
+        if let Ok(commit) = git(repo, &["rev-list", "-1", &before, "HEAD"]) {
+            let commit = commit.trim();
+            if !commit.is_empty() {
+                return Some(commit.to_string());
+            }
+        }
+    }
+    git(repo, &["rev-parse", "HEAD"])
+        .ok()
+        .map(|commit| commit.trim().to_string())
+}
+
+/// 收集工作区相对基准提交的改动（含未跟踪文件）
+fn collect_changes(repo: &Path, base: &str) -> Result<(Vec<FileChange>, String)> {
+    let mut files = parse_numstat(&git(repo, &["diff", "--numstat", base])?);
+    let mut diff = git(repo, &["diff", base])?;
+    let untracked = git(repo, &["ls-files", "--others", "--exclude-standard"])?;
+    for path in untracked.lines().filter(|p| !p.is_empty()) {
+        let content = fs::read_to_string(repo.join(path)).unwrap_or_default();
+        files.push(FileChange {
+            path: path.to_string(),
+            added: content.lines().count() as u64,
+            removed: 0,
+        });
+        diff.push_str(&format!("\n--- /dev/null\n+++ b/{}\n", path));
+        for line in content.lines() {
+            diff.push('+');
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    Ok((files, diff))
+}
+
+/// transcript 中最后的助手消息和运行过的测试命令
+fn read_transcript(path: &Path) -> (Option<String>, Vec<String>) {
+    let Ok(events) = JsonlParser::new(path).read_all_events() else {
+        return (None, Vec::new());
+    };
+    let texts: Vec<&str> = events
+        .iter()
+        .filter_map(|event| match event {
+            JsonlEvent::AssistantText { content, .. } if !content.trim().is_empty() => {
+                Some(content.trim())
+            }
+            _ => None,
+        })
+        .collect();
+    let start = texts.len().saturating_sub(CONCLUSION_MESSAGES);
+    let conclusion = (!texts.is_empty()).then(|| texts[start..].join("\n\n"));
+
+    let test_commands = events
+        .iter()
+        .filter_map(|event| match event {
+            JsonlEvent::ToolUse {
+                tool_name, input, ..
+            } if tool_name == "Bash" => input.get("command").and_then(|c| c.as_str()),
+            _ => None,
+        })
+        .filter(|command| is_test_command(command))
+        .map(|command| command.to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    (conclusion, test_commands)
+}
+
+fn is_test_command(command: &str) -> bool {
+    [
+        "cargo test",
+        "npm test",
+        "pytest",
+        "go test",
+        "yarn test",
+        "pnpm test",
+        "jest",
+        "vitest",
+    ]
+    .iter()
+    .any(|pattern| command.contains(pattern))
+}
+
+/// 按 agent_id（或项目目录）收集工作结果
+pub(crate) fn collect_work(
+    manager: &AgentManager,
+    target: &str,
+    base: Option<&str>,
+) -> Result<AgentWork> {
+    let (agent_id, agent_type, project_path, started_at, jsonl_path) =
+        match manager.get_agent(target)? {
+            Some(agent) => (
+                agent.agent_id,
+                Some(agent.agent_type.to_string()),
+                agent.project_path,
+                Some(agent.started_at),
+                agent.jsonl_path,
+            ),
+            None if Path::new(target).is_dir() => {
+                (target.to_string(), None, target.to_string(), None, None)
+            }
+            None => return Err(CliError::AgentNotFound(format!("未找到 agent: {}", target)).into()),
+        };
+
+    let repo = Path::new(&project_path);
+    let base = base
+        .map(|b| b.to_string())
+        .or_else(|| base_commit(repo, started_at.as_deref()));
+    let (files, diff) = match base {
+        Some(ref base) => collect_changes(repo, base)
+            .with_context(|| format!("无法读取 {} 的 git 改动", project_path))?,
+        None => {
+            warn!(project = %project_path, "Not a git repository, skipping diff");
+            (Vec::new(), String::new())
+        }
+    };
+    let (conclusion, test_commands) = jsonl_path
+        .as_deref()
+        .map(|path| read_transcript(Path::new(path)))
+        .unwrap_or_default();
+
+    Ok(AgentWork {
+        agent_id,
+        agent_type,
+        project_path,
+        base,
+        files,
+        diff,
+        conclusion,
+        test_commands,
+    })
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max).collect();
+    truncated.push_str("\n…（已截断）");
+    truncated
+}
+
+fn comparison_prompt(a: &AgentWork, b: &AgentWork) -> String {
+    let section = |label: &str, work: &AgentWork| {
+        format!(
+            "<agent_{label} id=\"{}\">\n<conclusion>\n{}\n</conclusion>\n<tests>\n{}\n</tests>\n<diff>\n{}\n</diff>\n</agent_{label}>",
+            work.agent_id,
+            work.conclusion.as_deref().unwrap_or("（无）"),
+            if work.test_commands.is_empty() {
+                "（未运行测试）".to_string()
+            } else {
+                work.test_commands.join("\n")
+            },
+            truncate_chars(&work.diff, MAX_DIFF_CHARS),
+        )
+    };
+    format!(
+        "两个 AI 编码 agent 独立完成了同一个任务。对比它们的改动和结论。\n\n{}\n\n{}\n\n\
+         只输出 JSON，不要其他内容：\n\
+         {{\"approach\": {{\"a\": \"A 的实现方案\", \"b\": \"B 的实现方案\"}}, \
+         \"risks\": {{\"a\": \"A 的风险\", \"b\": \"B 的风险\"}}, \
+         \"test_coverage\": {{\"a\": \"A 的测试覆盖\", \"b\": \"B 的测试覆盖\"}}, \
+         \"recommendation\": \"推荐哪个及原因\"}}\n\
+         每项不超过两句话，使用中文。",
+        section("a", a),
+        section("b", b)
+    )
+}
+
+/// 解析 AI 输出中的 JSON 对比
+pub fn parse_ai_comparison(output: &str) -> Option<AiComparison> {
+    let start = output.find('{')?;
+    let end = output.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&output[start..=end]).ok()
+}
+
+fn ai_compare(a: &AgentWork, b: &AgentWork) -> Option<AiComparison> {
+    let client = match AnthropicClient::for_task(AiTask::Comparison) {
+        Ok(client) => client,
+        Err(e) => {
+            warn!(error = %e, "AI backend unavailable, skipping comparison");
+            return None;
+        }
+    };
+    match client.complete(&comparison_prompt(a, b), None) {
+        Ok(output) => parse_ai_comparison(&output),
+        Err(e) => {
+            warn!(error = %e, "AI comparison failed");
+            None
+        }
+    }
+}
+
+/// 处理 `cam compare`
+pub fn handle_compare(args: CompareArgs) -> Result<()> {
+    let manager = AgentManager::new();
+    let a = collect_work(&manager, &args.agent_a, args.base.as_deref())?;
+    let b = collect_work(&manager, &args.agent_b, args.base.as_deref())?;
+
+    let mut report = CompareReport::new(a, b);
+    if !args.no_ai {
+        report.ai = ai_compare(&report.a, &report.b);
+        if report.ai.is_none() {
+            eprintln!("⚠️ AI 对比不可用，仅输出文件改动对比");
+        }
+    }
+
+    let content = if args.json {
+        serde_json::to_string_pretty(&report)?
+    } else {
+        report.to_markdown()
+    };
+    match args.output {
+        Some(path) => {
+            fs::write(&path, &content).with_context(|| format!("无法写入 {}", path.display()))?;
+            println!("✓ 报告已写入 {}", path.display());
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work(id: &str, files: &[&str]) -> AgentWork {
+        AgentWork {
+            agent_id: id.to_string(),
+            agent_type: Some("claude".to_string()),
+            project_path: format!("/tmp/{}", id),
+            base: None,
+            files: files
+                .iter()
+                .map(|path| FileChange {
+                    path: path.to_string(),
+                    added: 3,
+                    removed: 1,
+                })
+                .collect(),
+            diff: String::new(),
+            conclusion: Some("Done: added login form".to_string()),
+            test_commands: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let files = parse_numstat("10\t2\tsrc/main.rs\n-\t-\tlogo.png\n");
+        assert_eq!(
+            files,
+            vec![
+                FileChange {
+                    path: "src/main.rs".to_string(),
+                    added: 10,
+                    removed: 2
+                },
+                FileChange {
+                    path: "logo.png".to_string(),
+                    added: 0,
+                    removed: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_splits_files_and_renders_ai() {
+        let mut report = CompareReport::new(
+            work("cam-a", &["src/app.rs", "src/login.rs"]),
+            work("cam-b", &["src/app.rs", "tests/login.rs"]),
+        );
+        assert_eq!(report.both, vec!["src/app.rs"]);
+        assert_eq!(report.only_a, vec!["src/login.rs"]);
+        assert_eq!(report.only_b, vec!["tests/login.rs"]);
+
+        report.ai = parse_ai_comparison(
+            "```json\n{\"approach\": {\"a\": \"新模块\", \"b\": \"改 app | 加测试\"}, \"recommendation\": \"B\"}\n```",
+        );
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# cam-a vs cam-b"));
+        assert!(markdown.contains("- 改动: 2 个文件 (+6 -2)"));
+        assert!(markdown.contains("| 方案 | 新模块 | 改 app \\| 加测试 |"));
+        assert!(markdown.contains("建议: B"));
+        assert!(markdown.contains("> Done: added login form"));
+    }
+
+    #[test]
+    fn test_is_test_command() {
+        assert!(is_test_command("cd api && cargo test --lib"));
+        assert!(is_test_command("npx vitest run"));
+        assert!(!is_test_command("cargo build"));
+    }
+}
@@ -0,0 +1,331 @@
+//! `cam completions` 命令 - 生成 shell 补全脚本
+//!
+//! 静态部分（子命令、参数）由 clap_complete 生成；agent_id、team 名称、
+//! session id 等动态值在补全时通过隐藏的 `cam __complete` 子命令实时查询。
+
+use anyhow::Result;
+use clap::{Args, Command, ValueEnum};
+use std::io::Write;
+
+use crate::agent::AgentManager;
+use crate::session::SessionManager;
+use crate::team::list_team_names;
+
+/// 支持的 shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Completions 命令参数
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// 目标 shell
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+}
+
+/// `cam __complete` 参数（内部使用，由补全脚本调用）
+#[derive(Args, Debug)]
+pub struct CompleteArgs {
+    /// 当前已输入的参数（不含 `cam` 本身和正在补全的词）
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub words: Vec<String>,
+}
+
+/// 动态补全值的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    AgentId,
+    TeamName,
+    SessionId,
+}
+
+/// 取值为 agent_id 的选项：(子命令, 选项)
+const AGENT_ID_OPTIONS: &[(&str, &str)] = &[
+    ("watch-trigger", "--agent-id"),
+    ("notify", "--agent-id"),
+    ("reply", "--target"),
+    ("reply", "-t"),
+    ("send-file", "--target"),
+];
+
+/// 第一个位置参数为 agent_id 的子命令
+const AGENT_POSITIONAL: &[&str] = &["record", "attach", "ack", "pause", "kill", "send-image"];
+
+/// 第一个位置参数为 team 名称的子命令
+const TEAM_POSITIONAL: &[&str] = &[
+    "team-members",
+    "tasks",
+    "team-delete",
+    "team-status",
+    "inbox",
+    "inbox-send",
+    "team-watch",
+    "team-spawn",
+    "team-progress",
+    "team-shutdown",
+    "team-merge",
+];
+
+/// 第一个位置参数为 session id 的子命令
+const SESSION_POSITIONAL: &[&str] = &["resume", "logs"];
+
+/// 取值为 session id 的选项：(子命令, 选项)
+const SESSION_OPTIONS: &[(&str, &str)] = &[("start", "--resume"), ("start", "-r")];
+
+/// 根据已输入的参数判断当前位置需要哪种动态值
+///
+/// 通过 clap `Command` 区分布尔 flag 与需要取值的选项。返回 None 表示交给静态补全处理。
+pub fn completion_kind_for(cmd: &Command, words: &[String]) -> Option<CompletionKind> {
+    let (subcommand, rest) = words.split_first()?;
+    let subcommand = subcommand.as_str();
+    let sub = cmd.find_subcommand(subcommand)?;
+
+    let takes_value = |flag: &str| -> bool {
+        sub.get_arguments().any(|arg| {
+            let matches = if let Some(long) = flag.strip_prefix("--") {
+                arg.get_long() == Some(long)
+            } else if let Some(short) = flag.strip_prefix('-') {
+                short.len() == 1 && arg.get_short() == short.chars().next()
+            } else {
+                false
+            };
+            matches && arg.get_action().takes_values()
+        })
+    };
+
+    // 1. 上一个词是需要取值的选项
+    if let Some(prev) = rest.last() {
+        let prev = prev.as_str();
+        if AGENT_ID_OPTIONS.contains(&(subcommand, prev)) {
+            return Some(CompletionKind::AgentId);
+        }
+        if SESSION_OPTIONS.contains(&(subcommand, prev)) {
+            return Some(CompletionKind::SessionId);
+        }
+        if prev.starts_with('-') && takes_value(prev) {
+            return None;
+        }
+    }
+
+    // 2. 统计已输入的位置参数（跳过选项及其取值）
+    let mut positional = 0;
+    let mut iter = rest.iter();
+    while let Some(word) = iter.next() {
+        if word.starts_with('-') {
+            if !word.contains('=') && takes_value(word) {
+                iter.next();
+            }
+        } else {
+            positional += 1;
+        }
+    }
+    if positional > 0 {
+        return None;
+    }
+
+    if AGENT_POSITIONAL.contains(&subcommand) {
+        return Some(CompletionKind::AgentId);
+    }
+    if TEAM_POSITIONAL.contains(&subcommand) {
+        return Some(CompletionKind::TeamName);
+    }
+    if SESSION_POSITIONAL.contains(&subcommand) {
+        return Some(CompletionKind::SessionId);
+    }
+    None
+}
+
+/// 查询指定类型的候选值
+pub fn completion_candidates(kind: CompletionKind) -> Vec<String> {
+    match kind {
+        CompletionKind::AgentId => AgentManager::new()
+            .list_agents()
+            .map(|agents| agents.into_iter().map(|a| a.agent_id).collect())
+            .unwrap_or_default(),
+        CompletionKind::TeamName => list_team_names(),
+        CompletionKind::SessionId => SessionManager::new()
+            .list_sessions()
+            .map(|sessions| sessions.into_iter().map(|s| s.id).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// 处理 `cam __complete`：每行输出一个候选值
+pub fn handle_complete(args: CompleteArgs, cmd: &Command) -> Result<()> {
+    if let Some(kind) = completion_kind_for(cmd, &args.words) {
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        for candidate in completion_candidates(kind) {
+            writeln!(out, "{}", candidate)?;
+        }
+    }
+    Ok(())
+}
+
+/// 处理 `cam completions <shell>`
+pub fn handle_completions(args: CompletionsArgs, cmd: &mut Command) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    write_completions(args.shell, cmd, &mut out)
+}
+
+/// 生成补全脚本（静态部分 + 动态补全包装）
+pub fn write_completions(
+    shell: CompletionShell,
+    cmd: &mut Command,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let bin_name = cmd.get_name().to_string();
+    let generator = match shell {
+        CompletionShell::Bash => clap_complete::Shell::Bash,
+        CompletionShell::Zsh => clap_complete::Shell::Zsh,
+        CompletionShell::Fish => clap_complete::Shell::Fish,
+    };
+    // 先生成到缓冲区：clap_complete 直接写 stdout 时遇到 broken pipe 会 panic
+    let mut script = Vec::new();
+    clap_complete::generate(generator, cmd, &bin_name, &mut script);
+    script.extend_from_slice(dynamic_wrapper(shell, &bin_name).as_bytes());
+    out.write_all(&script)?;
+    Ok(())
+}
+
+/// 各 shell 的动态补全包装：先询问 `__complete`，无结果时回退到静态补全
+fn dynamic_wrapper(shell: CompletionShell, bin: &str) -> String {
+    match shell {
+        CompletionShell::Bash => format!(
+            r#"
+_{bin}_dynamic() {{
+    local candidates
+    candidates="$({bin} __complete -- "${{COMP_WORDS[@]:1:$((COMP_CWORD-1))}}" 2>/dev/null)"
+    if [[ -n "$candidates" ]]; then
+        COMPREPLY=( $(compgen -W "$candidates" -- "${{COMP_WORDS[COMP_CWORD]}}") )
+        return 0
+    fi
+    _{bin} "$@"
+}}
+complete -F _{bin}_dynamic -o nosort -o bashdefault -o default {bin}
+"#
+        ),
+        CompletionShell::Zsh => format!(
+            r#"
+_{bin}_dynamic() {{
+    local -a candidates
+    candidates=("${{(@f)$({bin} __complete -- "${{(@)words[2,CURRENT-1]}}" 2>/dev/null)}}")
+    if [[ -n "${{candidates[1]}}" ]]; then
+        compadd -a candidates
+        return
+    fi
+    _{bin} "$@"
+}}
+compdef _{bin}_dynamic {bin}
+"#
+        ),
+        CompletionShell::Fish => format!(
+            r#"
+function __{bin}_dynamic
+    set -l tokens (commandline -opc)
+    {bin} __complete -- $tokens[2..-1] 2>/dev/null
+end
+complete -c {bin} -a '(__{bin}_dynamic)'
+"#
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    fn test_cmd() -> Command {
+        Command::new("cam")
+            .subcommand(Command::new("list"))
+            .subcommand(
+                Command::new("watch-trigger")
+                    .arg(Arg::new("agent_id").long("agent-id").num_args(1)),
+            )
+            .subcommand(
+                Command::new("reply")
+                    .arg(Arg::new("reply"))
+                    .arg(Arg::new("target").long("target").short('t').num_args(1)),
+            )
+            .subcommand(
+                Command::new("inbox")
+                    .arg(Arg::new("team"))
+                    .arg(Arg::new("member").long("member").short('m').num_args(1))
+                    .arg(
+                        Arg::new("unread")
+                            .long("unread")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new("inbox-send")
+                    .arg(Arg::new("team"))
+                    .arg(Arg::new("member")),
+            )
+            .subcommand(Command::new("resume").arg(Arg::new("session_id")))
+            .subcommand(Command::new("record").arg(Arg::new("agent_id")))
+            .subcommand(Command::new("start").arg(Arg::new("resume").long("resume").num_args(1)))
+    }
+
+    fn kind(s: &str) -> Option<CompletionKind> {
+        let words: Vec<String> = s.split_whitespace().map(String::from).collect();
+        completion_kind_for(&test_cmd(), &words)
+    }
+
+    #[test]
+    fn test_completion_kind_agent_option() {
+        assert_eq!(
+            kind("watch-trigger --agent-id"),
+            Some(CompletionKind::AgentId)
+        );
+        assert_eq!(kind("reply y --target"), Some(CompletionKind::AgentId));
+        assert_eq!(kind("record"), Some(CompletionKind::AgentId));
+    }
+
+    #[test]
+    fn test_completion_kind_team_positional() {
+        assert_eq!(kind("inbox"), Some(CompletionKind::TeamName));
+        // 布尔 flag 之后仍在补全第一个位置参数
+        assert_eq!(kind("inbox --unread"), Some(CompletionKind::TeamName));
+        // 选项取值不计入位置参数
+        assert_eq!(kind("inbox -m dev"), Some(CompletionKind::TeamName));
+        // 第一个位置参数已输入
+        assert_eq!(kind("inbox-send my-team"), None);
+    }
+
+    #[test]
+    fn test_completion_kind_session() {
+        assert_eq!(kind("resume"), Some(CompletionKind::SessionId));
+        assert_eq!(kind("start --resume"), Some(CompletionKind::SessionId));
+    }
+
+    #[test]
+    fn test_completion_kind_static_fallback() {
+        assert_eq!(kind(""), None);
+        assert_eq!(kind("list"), None);
+        assert_eq!(kind("unknown-command"), None);
+        // 其他选项的值交给静态补全
+        assert_eq!(kind("inbox --member"), None);
+    }
+
+    #[test]
+    fn test_write_completions_includes_dynamic_wrapper() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+        ] {
+            let mut cmd = test_cmd();
+            let mut buf = Vec::new();
+            write_completions(shell, &mut cmd, &mut buf).unwrap();
+            let script = String::from_utf8(buf).unwrap();
+            assert!(script.contains("cam __complete"), "{:?}", shell);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! `cam dashboard` 命令 - 立即发布一次团队看板快照
+//!
+//! 发布目标在 config.json 的 `dashboard_export` 中配置，watcher daemon 会按
+//! `interval_secs` 定期发布；此命令用于验证配置或手动刷新。
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::cli::CliError;
+use crate::service::{publish, DashboardExport, DashboardSnapshot};
+
+#[derive(Args, Debug)]
+pub struct DashboardArgs {
+    /// 只输出快照 JSON，不发布
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// 处理 `cam dashboard`
+pub fn handle_dashboard(args: DashboardArgs) -> Result<()> {
+    let config = DashboardExport::load();
+    if args.dry_run {
+        let member = config.as_ref().map(|c| c.member()).unwrap_or_default();
+        let snapshot = DashboardSnapshot::collect(member)?;
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+        return Ok(());
+    }
+
+    let Some(config) = config else {
+        return Err(CliError::InvalidArgument(
+            "未配置 dashboard_export（见 config.json 说明）".to_string(),
+        )
+        .into());
+    };
+    let snapshot = DashboardSnapshot::collect(config.member())?;
+    publish(&config.target, &snapshot)
+        .map_err(|e| CliError::Other(format!("看板发布失败: {}", e)))?;
+    println!(
+        "✓ 已发布 {}（{} 个 agent，{} 个待确认）",
+        snapshot.file_name(),
+        snapshot.agents.len(),
+        snapshot.pending_confirmations.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! `cam embeddings` 命令 - 嵌入模型与提示模板库管理
+//!
+//! 配置见 [`crate::embedding`]。
+
+use std::io::Read;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use serde_json::json;
+
+use crate::cli::CliError;
+use crate::embedding::library::{load_templates, user_templates_path};
+use crate::embedding::{EmbeddingConfig, Embeddings, TemplateLibrary};
+
+#[derive(Args, Debug)]
+pub struct EmbeddingsArgs {
+    #[command(subcommand)]
+    pub action: Option<EmbeddingsAction>,
+    /// JSON 格式输出
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EmbeddingsAction {
+    /// 显示模型、向量缓存和模板库（默认）
+    Status,
+    /// 下载配置的模型并重建模板索引
+    Pull,
+    /// 重建模板索引
+    Rebuild,
+    /// 删除向量缓存和模板索引
+    Clear,
+    /// 从 stdin 读取终端快照，显示最相似的模板
+    Match,
+}
+
+fn build_library(embeddings: &Embeddings) -> Result<TemplateLibrary> {
+    TemplateLibrary::load_or_build(embeddings)
+        .map_err(|e| CliError::Other(format!("模板索引构建失败: {}", e)).into())
+}
+
+pub fn handle_embeddings(args: EmbeddingsArgs) -> Result<()> {
+    let config = EmbeddingConfig::load();
+    let embeddings = Embeddings::new(config.embedder(), &crate::embedding::embeddings_dir());
+
+    match args.action.unwrap_or(EmbeddingsAction::Status) {
+        EmbeddingsAction::Status => {
+            let status = json!({
+                "model": embeddings.model_id(),
+                "dir": embeddings.dir(),
+                "cached_vectors": embeddings.cache_len(),
+                "templates": load_templates().len(),
+                "user_templates": user_templates_path(),
+                "threshold": config.threshold,
+            });
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                println!("模型: {}", embeddings.model_id());
+                println!("数据目录: {}", embeddings.dir().display());
+                println!("缓存向量: {}", embeddings.cache_len());
+                println!(
+                    "模板: {} 个（用户模板 {}）",
+                    load_templates().len(),
+                    user_templates_path().display()
+                );
+                println!("匹配阈值: {:.2}", config.threshold);
+            }
+        }
+        EmbeddingsAction::Pull => {
+            let pulled = embeddings
+                .pull()
+                .map_err(|e| CliError::Other(format!("模型下载失败: {}", e)))?;
+            let library = build_library(&embeddings)?;
+            if args.json {
+                println!(
+                    "{}",
+                    json!({"model": embeddings.model_id(), "pulled": pulled, "templates": library.len()})
+                );
+            } else if pulled {
+                println!(
+                    "✅ 已下载 {}，索引 {} 个模板",
+                    embeddings.model_id(),
+                    library.len()
+                );
+            } else {
+                println!(
+                    "✅ {} 无需下载，索引 {} 个模板",
+                    embeddings.model_id(),
+                    library.len()
+                );
+            }
+        }
+        EmbeddingsAction::Rebuild => {
+            embeddings.clear_cache()?;
+            let library = build_library(&embeddings)?;
+            if args.json {
+                println!(
+                    "{}",
+                    json!({"model": embeddings.model_id(), "templates": library.len()})
+                );
+            } else {
+                println!(
+                    "✅ 已用 {} 重建 {} 个模板的索引",
+                    embeddings.model_id(),
+                    library.len()
+                );
+            }
+        }
+        EmbeddingsAction::Clear => {
+            let removed = embeddings.clear_cache()?;
+            if args.json {
+                println!("{}", json!({"removed": removed}));
+            } else {
+                println!("✅ 已删除 {} 个缓存文件", removed);
+            }
+        }
+        EmbeddingsAction::Match => {
+            let mut snapshot = String::new();
+            std::io::stdin().read_to_string(&mut snapshot)?;
+            let library = build_library(&embeddings)?;
+            let found = library
+                .nearest(&embeddings, &snapshot)
+                .map_err(|e| CliError::Other(format!("嵌入计算失败: {}", e)))?;
+            if args.json {
+                let result = found.map(|m| {
+                    let matched = m.confidence >= library.threshold();
+                    json!({
+                        "template": m.template.name,
+                        "confidence": m.confidence,
+                        "matched": matched,
+                        "message": matched.then(|| m.message()),
+                    })
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                match found {
+                    Some(m) if m.confidence >= library.threshold() => {
+                        println!("✅ {} (置信度 {:.2})", m.template.name, m.confidence);
+                        println!("{}", m.message().content);
+                    }
+                    Some(m) => println!(
+                        "未匹配：最接近 {} (置信度 {:.2}，阈值 {:.2})",
+                        m.template.name,
+                        m.confidence,
+                        library.threshold()
+                    ),
+                    None => println!("未匹配：输入中没有可识别的问题"),
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! CLI 错误类型与退出码
+//!
+//! 每种失败原因对应一个固定的退出码，脚本/CI 可据此分支处理；
+//! `--error-format json` 时错误以 JSON 输出到 stderr。
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// 错误输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// 人类可读文本（默认）
+    #[default]
+    Text,
+    /// JSON：`{"error": {"kind", "code", "message"}}`
+    Json,
+}
+
+/// 结构化 CLI 错误，每个变体携带面向用户的错误信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// 参数无效
+    InvalidArgument(String),
+    /// 找不到指定的 agent / 进程 / 确认目标
+    AgentNotFound(String),
+    /// 没有待处理的确认请求
+    NoPending,
+    /// 通知渠道发送失败
+    ChannelFailure(String),
+    /// Team 不存在
+    TeamNotFound(String),
+    /// 会话不存在
+    SessionNotFound(String),
+    /// 系统服务（launchd）操作失败
+    ServiceFailure(String),
+    /// 回复者无权执行该操作（reply_auth）
+    PermissionDenied(String),
+    /// 其他错误
+    Other(String),
+}
+
+impl CliError {
+    /// 通用失败
+    pub const EXIT_OTHER: i32 = 1;
+    /// 参数无效（与 clap 用法错误一致）
+    pub const EXIT_INVALID_ARGUMENT: i32 = 2;
+    pub const EXIT_AGENT_NOT_FOUND: i32 = 3;
+    pub const EXIT_NO_PENDING: i32 = 4;
+    pub const EXIT_CHANNEL_FAILURE: i32 = 5;
+    pub const EXIT_TEAM_NOT_FOUND: i32 = 6;
+    pub const EXIT_SESSION_NOT_FOUND: i32 = 7;
+    pub const EXIT_SERVICE_FAILURE: i32 = 8;
+    pub const EXIT_PERMISSION_DENIED: i32 = 9;
+
+    /// 进程退出码
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::InvalidArgument(_) => Self::EXIT_INVALID_ARGUMENT,
+            Self::AgentNotFound(_) => Self::EXIT_AGENT_NOT_FOUND,
+            Self::NoPending => Self::EXIT_NO_PENDING,
+            Self::ChannelFailure(_) => Self::EXIT_CHANNEL_FAILURE,
+            Self::TeamNotFound(_) => Self::EXIT_TEAM_NOT_FOUND,
+            Self::SessionNotFound(_) => Self::EXIT_SESSION_NOT_FOUND,
+            Self::ServiceFailure(_) => Self::EXIT_SERVICE_FAILURE,
+            Self::PermissionDenied(_) => Self::EXIT_PERMISSION_DENIED,
+            Self::Other(_) => Self::EXIT_OTHER,
+        }
+    }
+
+    /// 机器可读的错误类型标识
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::AgentNotFound(_) => "agent_not_found",
+            Self::NoPending => "no_pending",
+            Self::ChannelFailure(_) => "channel_failure",
+            Self::TeamNotFound(_) => "team_not_found",
+            Self::SessionNotFound(_) => "session_not_found",
+            Self::ServiceFailure(_) => "service_failure",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// 从 anyhow 错误中提取 CliError；未分类的错误归为 Other
+    pub fn from_anyhow(err: &anyhow::Error) -> Self {
+        match err.downcast_ref::<CliError>() {
+            Some(cli_err) => cli_err.clone(),
+            None => Self::Other(format!("{:#}", err)),
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoPending => write!(f, "没有待处理的确认请求"),
+            Self::InvalidArgument(msg)
+            | Self::AgentNotFound(msg)
+            | Self::ChannelFailure(msg)
+            | Self::TeamNotFound(msg)
+            | Self::SessionNotFound(msg)
+            | Self::ServiceFailure(msg)
+            | Self::PermissionDenied(msg)
+            | Self::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[derive(Serialize)]
+struct JsonErrorBody<'a> {
+    kind: &'a str,
+    code: i32,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct JsonError<'a> {
+    error: JsonErrorBody<'a>,
+}
+
+/// 按指定格式渲染错误（不含换行）
+pub fn render_error(err: &anyhow::Error, format: ErrorFormat) -> String {
+    let cli_err = CliError::from_anyhow(err);
+    match format {
+        ErrorFormat::Text => match err.downcast_ref::<CliError>() {
+            Some(_) => cli_err.to_string(),
+            None => format!("Error: {:?}", err),
+        },
+        ErrorFormat::Json => serde_json::to_string(&JsonError {
+            error: JsonErrorBody {
+                kind: cli_err.kind(),
+                code: cli_err.exit_code(),
+                message: cli_err.to_string(),
+            },
+        })
+        .unwrap_or_else(|_| "{}".to_string()),
+    }
+}
+
+/// 输出错误到 stderr 并以对应退出码退出
+pub fn exit_with_error(err: &anyhow::Error, format: ErrorFormat) -> ! {
+    eprintln!("{}", render_error(err, format));
+    std::process::exit(CliError::from_anyhow(err).exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            CliError::InvalidArgument(String::new()),
+            CliError::AgentNotFound(String::new()),
+            CliError::NoPending,
+            CliError::ChannelFailure(String::new()),
+            CliError::TeamNotFound(String::new()),
+            CliError::SessionNotFound(String::new()),
+            CliError::ServiceFailure(String::new()),
+            CliError::PermissionDenied(String::new()),
+            CliError::Other(String::new()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert_eq!(CliError::AgentNotFound(String::new()).exit_code(), 3);
+        assert_eq!(CliError::NoPending.exit_code(), 4);
+        assert_eq!(CliError::ChannelFailure(String::new()).exit_code(), 5);
+    }
+
+    #[test]
+    fn test_from_anyhow_downcasts() {
+        let err: anyhow::Error = CliError::AgentNotFound("未找到 agent: cam-1".into()).into();
+        assert_eq!(
+            CliError::from_anyhow(&err),
+            CliError::AgentNotFound("未找到 agent: cam-1".into())
+        );
+
+        let other = anyhow::anyhow!("boom");
+        assert_eq!(CliError::from_anyhow(&other).exit_code(), 1);
+    }
+
+    #[test]
+    fn test_render_error_json() {
+        let err: anyhow::Error = CliError::NoPending.into();
+        let rendered = render_error(&err, ErrorFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value["error"]["kind"], "no_pending");
+        assert_eq!(value["error"]["code"], 4);
+        assert_eq!(value["error"]["message"], "没有待处理的确认请求");
+    }
+
+    #[test]
+    fn test_render_error_text() {
+        let err: anyhow::Error = CliError::TeamNotFound("未找到 Team: x".into()).into();
+        assert_eq!(render_error(&err, ErrorFormat::Text), "未找到 Team: x");
+    }
+}
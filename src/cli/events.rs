@@ -0,0 +1,147 @@
+//! `cam events` 命令 - 输出共享事件总线中的内部事件，`--follow` 持续跟踪
+//!
+//! 事件来源见 [`crate::infra::event_bus`]。`--json` 每行输出一个 JSON 对象，
+//! 可直接接入 `jq`、vector 等工具。
+
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Local;
+use clap::Args;
+
+use crate::infra::event_bus::{BusEvent, EventBus};
+use crate::infra::text::truncate_width;
+
+/// 跟踪模式轮询间隔
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug)]
+pub struct EventsArgs {
+    /// 先输出最近 N 条事件
+    #[arg(long, short = 'n', default_value = "20")]
+    pub limit: usize,
+    /// 持续输出新事件（Ctrl-C 退出）
+    #[arg(long, short)]
+    pub follow: bool,
+    /// 只输出指定来源（watch/notification/reply/ack/state，可重复）
+    #[arg(long = "source", value_name = "SOURCE")]
+    pub sources: Vec<String>,
+    /// 只输出指定 agent 的事件
+    #[arg(long)]
+    pub agent: Option<String>,
+    /// 每行输出一个 JSON 对象
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl EventsArgs {
+    fn matches(&self, event: &BusEvent) -> bool {
+        (self.sources.is_empty() || self.sources.contains(&event.source))
+            && self
+                .agent
+                .as_ref()
+                .is_none_or(|agent| event.agent_id.as_ref() == Some(agent))
+    }
+}
+
+/// 单行文本格式：时间、来源、agent 和事件内容
+pub fn format_event(event: &BusEvent) -> String {
+    let data = match &event.data {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| match value {
+                serde_json::Value::String(text) => format!("{}={}", key, text),
+                other => format!("{}={}", key, other),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        other => other.to_string(),
+    };
+    format!(
+        "{} {:<12} {:<20} {}",
+        event.ts.with_timezone(&Local).format("%m-%d %H:%M:%S"),
+        event.source,
+        event.agent_id.as_deref().unwrap_or("-"),
+        truncate_width(&data.replace('\n', " "), 160)
+    )
+}
+
+/// 输出事件，下游管道已关闭（如 `| head`）时返回 false
+fn print_events(args: &EventsArgs, events: &[BusEvent]) -> Result<bool> {
+    let mut stdout = std::io::stdout().lock();
+    for event in events.iter().filter(|event| args.matches(event)) {
+        let line = if args.json {
+            serde_json::to_string(event)?
+        } else {
+            format_event(event)
+        };
+        if writeln!(stdout, "{}", line).is_err() {
+            return Ok(false);
+        }
+    }
+    Ok(stdout.flush().is_ok())
+}
+
+/// 处理 `cam events`
+pub fn handle_events(args: EventsArgs) -> Result<()> {
+    let bus = EventBus::new();
+    // 先取跟踪起点，避免读取历史和开始跟踪之间的事件丢失
+    let mut follower = bus.follow();
+
+    let history: Vec<BusEvent> = bus
+        .load()
+        .into_iter()
+        .filter(|event| args.matches(event))
+        .collect();
+    let start = history.len().saturating_sub(args.limit);
+    if !print_events(&args, &history[start..])? {
+        return Ok(());
+    }
+    if !args.follow {
+        if history.is_empty() && !args.json {
+            println!("暂无事件（{}）", bus.path().display());
+        }
+        return Ok(());
+    }
+
+    while print_events(&args, &follower.read_new())? {
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_event_and_filter() {
+        let event: BusEvent = serde_json::from_value(serde_json::json!({
+            "ts": "2026-03-01T12:00:00Z",
+            "source": "notification",
+            "agent_id": "cam-1",
+            "data": {"event": "WaitingForInput", "channel": "telegram", "message_id": null}
+        }))
+        .unwrap();
+        let line = format_event(&event);
+        assert!(line.contains("notification"));
+        assert!(line.ends_with("cam-1                event=WaitingForInput channel=telegram"));
+
+        let args = EventsArgs {
+            limit: 20,
+            follow: false,
+            sources: vec!["reply".to_string()],
+            agent: None,
+            json: true,
+        };
+        assert!(!args.matches(&event));
+        let args = EventsArgs {
+            sources: Vec::new(),
+            agent: Some("cam-1".to_string()),
+            ..args
+        };
+        assert!(args.matches(&event));
+    }
+}
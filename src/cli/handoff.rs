@@ -0,0 +1,318 @@
+//! `cam handoff` 命令 - 把会话转交给另一种 agent
+//!
+//! 一个模型卡住时，导出源 agent 的上下文（transcript 中最近的用户指令、最后的助手消息、
+//! 运行过的测试和工作区改动），写入 `~/.config/code-agent-monitor/handoffs/`，
+//! 中断并停止源 agent，在同一项目启动目标类型的 agent，并用交接摘要作为初始指令。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::Args;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::agent::adapter::get_adapter;
+use crate::agent::pause::{send_sequence, PauseKeys};
+use crate::agent::{AgentManager, AgentRecord, AgentType, StartAgentRequest};
+use crate::cli::compare::{collect_work, AgentWork};
+use crate::cli::CliError;
+use crate::infra::jsonl::{JsonlEvent, JsonlParser};
+use crate::infra::text::truncate_width;
+
+/// 摘要中保留的最近用户指令数
+const RECENT_PROMPTS: usize = 3;
+/// 单条用户指令在摘要中的最大长度（显示宽度）
+const MAX_PROMPT_WIDTH: usize = 500;
+/// 中断源 agent 后等待的时间
+const INTERRUPT_GRACE: Duration = Duration::from_secs(2);
+
+#[derive(Args, Debug)]
+pub struct HandoffArgs {
+    /// 源 agent_id
+    pub agent_id: String,
+    /// 目标 agent 类型: claude-code, codex
+    #[arg(long)]
+    pub to: String,
+    /// 只输出交接摘要，不停止或启动 agent
+    #[arg(long)]
+    pub dry_run: bool,
+    /// 保留源 agent（不停止）
+    #[arg(long)]
+    pub keep: bool,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 交接摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct HandoffSummary {
+    pub source_agent: String,
+    pub source_type: Option<String>,
+    pub target_type: String,
+    pub project_path: String,
+    /// 最近的用户指令（旧的在前）
+    pub recent_prompts: Vec<String>,
+    /// 最后的助手消息
+    pub conclusion: Option<String>,
+    pub test_commands: Vec<String>,
+    /// 相对 agent 启动时提交的改动文件
+    pub changed_files: Vec<String>,
+}
+
+impl HandoffSummary {
+    pub fn new(work: AgentWork, target_type: &AgentType, recent_prompts: Vec<String>) -> Self {
+        Self {
+            source_agent: work.agent_id,
+            source_type: work.agent_type,
+            target_type: target_type.to_string(),
+            project_path: work.project_path,
+            recent_prompts,
+            conclusion: work.conclusion,
+            test_commands: work.test_commands,
+            changed_files: work.files.into_iter().map(|f| f.path).collect(),
+        }
+    }
+
+    /// Markdown 格式的交接摘要
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# 交接: {}{} → {}\n\n项目: {}\n",
+            self.source_agent,
+            self.source_type
+                .as_deref()
+                .map(|t| format!(" ({})", t))
+                .unwrap_or_default(),
+            self.target_type,
+            self.project_path
+        );
+        out.push_str("\n## 最近的任务\n\n");
+        if self.recent_prompts.is_empty() {
+            out.push_str("（transcript 中没有用户指令）\n");
+        }
+        for prompt in &self.recent_prompts {
+            for line in prompt.lines() {
+                out.push_str(&format!("> {}\n", line));
+            }
+            out.push('\n');
+        }
+        if let Some(ref conclusion) = self.conclusion {
+            out.push_str("\n## 上一个 agent 最后的消息\n\n");
+            out.push_str(conclusion);
+            out.push('\n');
+        }
+        out.push_str("\n## 已改动的文件\n\n");
+        if self.changed_files.is_empty() {
+            out.push_str("（无）\n");
+        }
+        for file in &self.changed_files {
+            out.push_str(&format!("- {}\n", file));
+        }
+        if !self.test_commands.is_empty() {
+            out.push_str("\n## 运行过的测试\n\n");
+            for command in &self.test_commands {
+                out.push_str(&format!("- `{}`\n", command));
+            }
+        }
+        out
+    }
+
+    /// 目标 agent 的初始指令（单行，避免多行输入被逐行提交）
+    pub fn seed_prompt(&self, summary_path: &Path) -> String {
+        let task = self
+            .recent_prompts
+            .last()
+            .map(|prompt| {
+                format!(
+                    " 最近的任务: {}",
+                    truncate_width(
+                        &prompt.split_whitespace().collect::<Vec<_>>().join(" "),
+                        200
+                    )
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            "你接手另一个 agent（{}）未完成的工作。先阅读交接摘要 {}，检查已改动的文件，然后继续完成任务。{}",
+            self.source_type.as_deref().unwrap_or("unknown"),
+            summary_path.display(),
+            task
+        )
+    }
+}
+
+/// transcript 中最近的用户指令（旧的在前）
+fn recent_prompts(jsonl_path: Option<&str>) -> Vec<String> {
+    let Some(events) = jsonl_path.and_then(|path| JsonlParser::new(path).read_all_events().ok())
+    else {
+        return Vec::new();
+    };
+    let prompts: Vec<String> = events
+        .into_iter()
+        .filter_map(|event| match event {
+            JsonlEvent::UserMessage { content, .. } if !content.trim().is_empty() => {
+                Some(truncate_width(content.trim(), MAX_PROMPT_WIDTH))
+            }
+            _ => None,
+        })
+        .collect();
+    let start = prompts.len().saturating_sub(RECENT_PROMPTS);
+    prompts[start..].to_vec()
+}
+
+/// 保存交接摘要，返回文件路径
+fn save_summary(summary: &HandoffSummary) -> Result<PathBuf> {
+    let dir = crate::infra::paths::state_dir().join("handoffs");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{}.md",
+        summary.source_agent,
+        Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, summary.to_markdown())
+        .with_context(|| format!("无法写入 {}", path.display()))?;
+    Ok(path)
+}
+
+/// 中断源 agent 当前的处理，再停止它
+fn stop_gracefully(manager: &AgentManager, agent: &AgentRecord) -> Result<()> {
+    if agent.status.is_processing() {
+        if let Some(keys) = PauseKeys::load().pause_for(&agent.agent_type) {
+            let sent = send_sequence(manager, &agent.tmux_session, &keys);
+            match sent {
+                Ok(()) => std::thread::sleep(INTERRUPT_GRACE),
+                Err(e) => {
+                    warn!(agent_id = %agent.agent_id, error = %e, "Failed to interrupt agent")
+                }
+            }
+        }
+    }
+    manager.stop_agent(&agent.agent_id)
+}
+
+#[derive(Debug, Serialize)]
+struct HandoffOutput {
+    summary: HandoffSummary,
+    summary_path: PathBuf,
+    source_stopped: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tmux_session: Option<String>,
+}
+
+/// 处理 `cam handoff`
+pub fn handle_handoff(args: HandoffArgs) -> Result<()> {
+    let target_type: AgentType = args
+        .to
+        .parse()
+        .map_err(|_| CliError::InvalidArgument(format!("不支持的 agent 类型: {}", args.to)))?;
+    let manager = AgentManager::new();
+    let source = manager
+        .get_agent(&args.agent_id)?
+        .ok_or_else(|| CliError::AgentNotFound(format!("未找到 agent: {}", args.agent_id)))?;
+    if !args.dry_run && source.container.is_none() && !get_adapter(&target_type).is_installed() {
+        return Err(CliError::InvalidArgument(format!("{} 命令未找到", args.to)).into());
+    }
+
+    let work = collect_work(&manager, &source.agent_id, None)?;
+    let summary = HandoffSummary::new(
+        work,
+        &target_type,
+        recent_prompts(source.jsonl_path.as_deref()),
+    );
+    let summary_path = save_summary(&summary)?;
+
+    let mut output = HandoffOutput {
+        summary,
+        summary_path,
+        source_stopped: false,
+        agent_id: None,
+        tmux_session: None,
+    };
+    if !args.dry_run {
+        if !args.keep {
+            stop_gracefully(&manager, &source)?;
+            output.source_stopped = true;
+        }
+        let response = manager.start_agent(StartAgentRequest {
+            project_path: source.project_path.clone(),
+            agent_type: Some(target_type.to_string()),
+            resume_session: None,
+            initial_prompt: Some(output.summary.seed_prompt(&output.summary_path)),
+            agent_id: None,
+            tmux_session: None,
+            container: source.container.as_ref().map(|c| c.image.clone()),
+        })?;
+        output.agent_id = Some(response.agent_id);
+        output.tmux_session = Some(response.tmux_session);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+    if args.dry_run {
+        println!("{}", output.summary.to_markdown());
+        println!(
+            "交接摘要已写入 {}（--dry-run，未停止或启动 agent）",
+            output.summary_path.display()
+        );
+        return Ok(());
+    }
+    if output.source_stopped {
+        println!("已停止 {}", source.agent_id);
+    }
+    println!(
+        "已启动 {} agent {} 接手 {}",
+        output.summary.target_type,
+        output.agent_id.as_deref().unwrap_or_default(),
+        output.summary.project_path
+    );
+    println!("  交接摘要: {}", output.summary_path.display());
+    println!("  改动文件: {} 个", output.summary.changed_files.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::compare::FileChange;
+
+    #[test]
+    fn test_summary_markdown_and_seed_prompt() {
+        let work = AgentWork {
+            agent_id: "cam-1".to_string(),
+            agent_type: Some("claude".to_string()),
+            project_path: "/work/api".to_string(),
+            base: None,
+            files: vec![FileChange {
+                path: "src/login.rs".to_string(),
+                added: 10,
+                removed: 2,
+            }],
+            diff: String::new(),
+            conclusion: Some("登录测试仍然失败".to_string()),
+            test_commands: vec!["cargo test".to_string()],
+        };
+        let summary = HandoffSummary::new(
+            work,
+            &AgentType::Codex,
+            vec!["add login\nwith tests".to_string()],
+        );
+
+        let markdown = summary.to_markdown();
+        assert!(markdown.starts_with("# 交接: cam-1 (claude) → codex"));
+        assert!(markdown.contains("> add login\n> with tests\n"));
+        assert!(markdown.contains("- src/login.rs"));
+        assert!(markdown.contains("- `cargo test`"));
+
+        let prompt = summary.seed_prompt(Path::new("/state/handoffs/cam-1.md"));
+        assert!(!prompt.contains('\n'));
+        assert!(prompt.contains("/state/handoffs/cam-1.md"));
+        assert!(prompt.ends_with("最近的任务: add login with tests"));
+    }
+}
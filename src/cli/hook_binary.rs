@@ -0,0 +1,338 @@
+//! hooks 中 cam 二进制路径的记录与迁移检测
+//!
+//! `cam setup` 把写入 hooks 的 cam 路径记录到 `~/.config/code-agent-monitor/hook_binaries.json`。
+//! cam 被移动（brew upgrade、cargo install 路径变化）后 hooks 会静默失效，watcher daemon
+//! 启动时与当前二进制比对：config.json 中 `"hooks": {"auto_repair": true}` 时自动改写
+//! hooks，否则发送 HIGH 通知提示重新运行 `cam setup`。
+//!
+//! `cam setup --shim` 让 hooks 调用固定路径 `~/.config/code-agent-monitor/bin/cam`，
+//! 迁移时只需更新 shim 指向的二进制。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::cli::setup::{get_cam_binary_path, rewrite_hooks};
+use crate::notification::{NotificationEvent, OpenclawNotifier};
+
+/// 一个工具的 hooks 安装记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookBinaryRecord {
+    /// 安装时的 cam 二进制路径（使用 shim 时为 shim 指向的路径）
+    pub binary: String,
+    pub version: String,
+    #[serde(default)]
+    pub shim: bool,
+    pub installed_at: DateTime<Utc>,
+    /// `cam setup --events` 选择的事件（`cam notify --event` 参数），None 表示全部事件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<String>>,
+}
+
+impl HookBinaryRecord {
+    pub fn new(binary: String, shim: bool) -> Self {
+        Self {
+            binary,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            shim,
+            installed_at: Utc::now(),
+            events: None,
+        }
+    }
+
+    /// 记录选择的 hook 事件
+    pub fn with_events(mut self, events: Option<Vec<String>>) -> Self {
+        self.events = events;
+        self
+    }
+}
+
+/// hooks 记录的 cam 路径与当前二进制不一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookBinaryMismatch {
+    pub tool: String,
+    pub recorded: String,
+    pub current: String,
+    pub shim: bool,
+    /// 记录的二进制已不存在（hooks 必然失效）
+    pub missing: bool,
+}
+
+impl HookBinaryMismatch {
+    pub fn message(&self) -> String {
+        let state = if self.missing {
+            "已不存在，hooks 已失效"
+        } else {
+            "与当前版本不一致"
+        };
+        format!(
+            "{} hooks 使用的 cam {}（{}），当前为 {}。运行 cam setup {}{} 更新",
+            self.tool,
+            state,
+            self.recorded,
+            self.current,
+            self.tool,
+            if self.shim { " --shim" } else { "" }
+        )
+    }
+}
+
+/// hooks 迁移配置（config.json 的 `hooks`）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HookRepairConfig {
+    /// 检测到路径变化时自动改写 hooks
+    pub auto_repair: bool,
+}
+
+impl HookRepairConfig {
+    /// 读取 config.json，缺失或无效时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("hooks").unwrap_or_default()
+    }
+}
+
+fn state_dir() -> PathBuf {
+    crate::infra::paths::state_dir()
+}
+
+fn records_path() -> PathBuf {
+    state_dir().join("hook_binaries.json")
+}
+
+/// shim 的固定路径
+pub fn shim_path() -> PathBuf {
+    state_dir().join("bin/cam")
+}
+
+fn load_from(path: &Path) -> BTreeMap<String, HookBinaryRecord> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_to(path: &Path, tool: &str, record: &HookBinaryRecord) -> Result<()> {
+    let mut records = load_from(path);
+    records.insert(tool.to_string(), record.clone());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&records)?)?;
+    Ok(())
+}
+
+/// 读取所有工具的 hooks 安装记录
+pub fn load_hook_binaries() -> BTreeMap<String, HookBinaryRecord> {
+    load_from(&records_path())
+}
+
+/// 记录工具 hooks 使用的 cam 路径
+pub fn save_hook_binary(tool: &str, record: &HookBinaryRecord) -> Result<()> {
+    save_to(&records_path(), tool, record)
+}
+
+/// 工具上次 setup 选择的 hook 事件，未记录或安装了全部事件时为 None
+pub fn recorded_hook_events(tool: &str) -> Option<Vec<String>> {
+    load_hook_binaries().remove(tool)?.events
+}
+
+/// shim 脚本：优先执行记录的路径，不存在时回退到 PATH 中的 cam
+fn shim_script(target: &str) -> String {
+    format!(
+        "#!/bin/sh\n\
+         # 由 cam setup --shim 生成，cam 移动后运行 cam setup <tool> --shim 更新\n\
+         CAM='{}'\n\
+         [ -x \"$CAM\" ] || CAM=\"$(command -v cam)\"\n\
+         exec \"$CAM\" \"$@\"\n",
+        target.replace('\'', r"'\''")
+    )
+}
+
+fn install_shim_at(path: &Path, target: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, shim_script(target))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+/// 写入（或更新）指向 `target` 的 shim，返回 shim 路径
+pub fn install_shim(target: &str) -> Result<PathBuf> {
+    let path = shim_path();
+    install_shim_at(&path, target)?;
+    Ok(path)
+}
+
+/// 找出记录路径与当前二进制不一致的工具
+pub fn detect_mismatches(
+    records: &BTreeMap<String, HookBinaryRecord>,
+    current: &str,
+) -> Vec<HookBinaryMismatch> {
+    // 无法解析出绝对路径时不做判断
+    if !Path::new(current).is_absolute() {
+        return Vec::new();
+    }
+    records
+        .iter()
+        .filter(|(_, record)| record.binary != current)
+        .map(|(tool, record)| HookBinaryMismatch {
+            tool: tool.clone(),
+            recorded: record.binary.clone(),
+            current: current.to_string(),
+            shim: record.shim,
+            missing: !Path::new(&record.binary).exists(),
+        })
+        .collect()
+}
+
+/// 当前二进制与 hooks 记录的比对结果
+pub fn hook_binary_mismatches() -> Vec<HookBinaryMismatch> {
+    detect_mismatches(&load_hook_binaries(), &get_cam_binary_path())
+}
+
+fn repair(mismatch: &HookBinaryMismatch) -> Result<()> {
+    if mismatch.shim {
+        install_shim(&mismatch.current)?;
+    } else {
+        rewrite_hooks(&mismatch.tool, &mismatch.current)?;
+    }
+    save_hook_binary(
+        &mismatch.tool,
+        &HookBinaryRecord::new(mismatch.current.clone(), mismatch.shim)
+            .with_events(recorded_hook_events(&mismatch.tool)),
+    )
+}
+
+/// 启动时检查 hooks 的 cam 路径：开启 `auto_repair` 时自动改写，否则（或改写失败时）发送通知
+pub fn reconcile_hook_binaries(notifier: Option<&OpenclawNotifier>) {
+    let mismatches = hook_binary_mismatches();
+    if mismatches.is_empty() {
+        return;
+    }
+    let config = HookRepairConfig::load();
+    for mismatch in mismatches {
+        warn!(
+            tool = %mismatch.tool,
+            recorded = %mismatch.recorded,
+            current = %mismatch.current,
+            missing = mismatch.missing,
+            "Hook binary path changed"
+        );
+        if config.auto_repair {
+            match repair(&mismatch) {
+                Ok(()) => {
+                    info!(tool = %mismatch.tool, current = %mismatch.current, "Hooks rewritten");
+                    continue;
+                }
+                Err(e) => warn!(tool = %mismatch.tool, error = %e, "Hook repair failed"),
+            }
+        }
+        let Some(notifier) = notifier else {
+            continue;
+        };
+        let event = NotificationEvent::error("cam-watcher", mismatch.message())
+            .with_dedup_key(format!("hook-binary:{}", mismatch.tool));
+        if let Err(e) = notifier.send_notification_event(&event) {
+            warn!(tool = %mismatch.tool, error = %e, "Failed to send hook binary notification");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_records_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("hook_binaries.json");
+        assert!(load_from(&path).is_empty());
+
+        save_to(
+            &path,
+            "claude",
+            &HookBinaryRecord::new("/a/cam".into(), false)
+                .with_events(Some(vec!["permission_request".into(), "stop".into()])),
+        )
+        .unwrap();
+        save_to(
+            &path,
+            "codex",
+            &HookBinaryRecord::new("/b/cam".into(), true),
+        )
+        .unwrap();
+        let records = load_from(&path);
+        assert_eq!(records["claude"].binary, "/a/cam");
+        assert!(records["codex"].shim);
+        assert_eq!(
+            records["claude"].events.as_deref(),
+            Some(&["permission_request".to_string(), "stop".to_string()][..])
+        );
+        assert!(records["codex"].events.is_none());
+        assert!(!fs::read_to_string(&path)
+            .unwrap()
+            .contains("\"events\": null"));
+    }
+
+    #[test]
+    fn test_detect_mismatches() {
+        let tmp = TempDir::new().unwrap();
+        let old = tmp.path().join("old-cam");
+        fs::write(&old, "").unwrap();
+        let current = tmp.path().join("cam").to_string_lossy().to_string();
+
+        let mut records = BTreeMap::new();
+        records.insert(
+            "claude".to_string(),
+            HookBinaryRecord::new(current.clone(), false),
+        );
+        records.insert(
+            "codex".to_string(),
+            HookBinaryRecord::new(old.to_string_lossy().to_string(), false),
+        );
+        records.insert(
+            "opencode".to_string(),
+            HookBinaryRecord::new("/gone/cam".to_string(), true),
+        );
+
+        let mismatches = detect_mismatches(&records, &current);
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].tool, "codex");
+        assert!(!mismatches[0].missing);
+        assert_eq!(mismatches[1].tool, "opencode");
+        assert!(mismatches[1].missing);
+        assert!(mismatches[1]
+            .message()
+            .contains("cam setup opencode --shim"));
+
+        assert!(detect_mismatches(&records, "cam").is_empty());
+    }
+
+    #[test]
+    fn test_shim_script_quotes_target() {
+        let tmp = TempDir::new().unwrap();
+        let shim = tmp.path().join("bin/cam");
+        install_shim_at(&shim, "/Users/o'neil/bin/cam").unwrap();
+        let script = fs::read_to_string(&shim).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(r"CAM='/Users/o'\''neil/bin/cam'"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&shim).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+    }
+}
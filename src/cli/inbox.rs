@@ -0,0 +1,98 @@
+//! `cam inbox prune` 命令 - 按保留策略清理 Team inbox
+//!
+//! 保留策略见 [`crate::team::retention`]，命令行参数覆盖 config.json 中的配置。
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::cli::CliError;
+use crate::team::{InboxRetention, PruneResult, TeamBridge};
+
+#[derive(Subcommand, Debug)]
+pub enum InboxAction {
+    /// 按保留策略把旧的已读消息归档到 archive/{member}.jsonl
+    Prune(InboxPruneArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct InboxPruneArgs {
+    /// Team 名称
+    pub team: String,
+    /// 只清理该成员的 inbox
+    #[arg(long, short)]
+    pub member: Option<String>,
+    /// 每个 inbox 最多保留的已读消息数（0 表示不限制）
+    #[arg(long)]
+    pub max_messages: Option<usize>,
+    /// 已读消息最长保留天数（0 表示不限制）
+    #[arg(long)]
+    pub max_age_days: Option<u32>,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 清理结果摘要
+pub fn format_prune_results(team: &str, results: &[PruneResult]) -> String {
+    let archived: usize = results.iter().map(|r| r.archived).sum();
+    if archived == 0 {
+        return format!("{} 的 inbox 无需清理", team);
+    }
+    let mut lines = vec![format!("已归档 {} 条消息:", archived)];
+    for result in results.iter().filter(|r| r.archived > 0) {
+        lines.push(format!(
+            "  {}@{}: 归档 {}，保留 {}",
+            result.member, team, result.archived, result.kept
+        ));
+    }
+    lines.join("\n")
+}
+
+/// 处理 `cam inbox prune`
+pub fn handle_inbox_prune(args: InboxPruneArgs) -> Result<()> {
+    let mut policy = InboxRetention::load();
+    if let Some(max_messages) = args.max_messages {
+        policy.max_messages = max_messages;
+    }
+    if let Some(max_age_days) = args.max_age_days {
+        policy.max_age_days = max_age_days;
+    }
+
+    let bridge = TeamBridge::new();
+    if !bridge.team_exists(&args.team) {
+        return Err(CliError::InvalidArgument(format!("Team 不存在: {}", args.team)).into());
+    }
+    let results = match args.member {
+        Some(ref member) => vec![bridge.prune_inbox(&args.team, member, &policy)?],
+        None => bridge.prune_team(&args.team, &policy)?,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        println!("{}", format_prune_results(&args.team, &results));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_prune_results() {
+        let result = |member: &str, archived, kept| PruneResult {
+            member: member.to_string(),
+            archived,
+            kept,
+        };
+        assert_eq!(
+            format_prune_results("app", &[result("dev", 0, 4)]),
+            "app 的 inbox 无需清理"
+        );
+        assert_eq!(
+            format_prune_results("app", &[result("dev", 3, 2), result("qa", 0, 1)]),
+            "已归档 3 条消息:\n  dev@app: 归档 3，保留 2"
+        );
+    }
+}
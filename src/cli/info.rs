@@ -0,0 +1,196 @@
+//! `cam info` 命令 - 查看进程或 CAM agent 的详细信息与状态历史
+//!
+//! 参数为数字时按 PID 查找进程（对应 CAM agent 时附带状态历史），否则按 agent_id 查找。
+
+use anyhow::Result;
+use chrono::Utc;
+use clap::Args;
+
+use crate::agent::history::HistoryStats;
+use crate::agent::stuck::format_elapsed;
+use crate::agent::todos::{agent_todos, TodoItem, TodoProgress, TodoStatus};
+use crate::agent::{AgentManager, AgentRecord};
+use crate::cli::CliError;
+use crate::infra::ports::format_ports;
+use crate::infra::text::truncate_width;
+use crate::infra::{ProcessNode, ProcessScanner};
+
+/// 文本输出时显示的最近历史条数
+const HISTORY_DISPLAY_LIMIT: usize = 20;
+/// 进程树中命令行的最大显示宽度
+const PROCESS_COMMAND_WIDTH: usize = 80;
+
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// 进程 PID 或 agent_id
+    pub target: String,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 处理 `cam info`
+pub fn handle_info(args: InfoArgs) -> Result<()> {
+    let manager = AgentManager::new();
+    let Ok(pid) = args.target.parse::<u32>() else {
+        let mut agent = manager
+            .get_agent(&args.target)?
+            .ok_or_else(|| CliError::AgentNotFound(format!("未找到 agent: {}", args.target)))?;
+        agent.todos = agent_todos(&agent);
+        let processes = manager.process_tree(&agent, &ProcessScanner::new());
+        if args.json {
+            let stats = HistoryStats::compute(&agent.history, Utc::now());
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "agent": agent,
+                    "stats": stats,
+                    "processes": processes,
+                }))?
+            );
+        } else {
+            print_agent(&agent);
+            print_process_tree(&processes);
+        }
+        return Ok(());
+    };
+
+    let scanner = ProcessScanner::new();
+    let Some(process) = scanner.get_agent_info(pid)? else {
+        return Err(CliError::AgentNotFound(format!("未找到 PID {} 的代理进程", pid)).into());
+    };
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&process)?);
+        return Ok(());
+    }
+
+    println!("进程信息:");
+    println!("  PID: {}", process.pid);
+    println!("  类型: {}", process.agent_type);
+    println!("  命令: {}", process.command);
+    println!("  工作目录: {}", process.working_dir);
+    println!("  会话 ID: {:?}", process.session_id);
+    print_process_tree(&scanner.process_tree(pid));
+
+    // 对应 CAM 管理的 agent 时显示状态历史
+    let managed = match process.session_id {
+        Some(ref session_id) => manager.find_agent_by_session_id(session_id)?,
+        None => None,
+    };
+    let managed = match managed {
+        Some(agent) => Some(agent),
+        None => manager.find_agent_by_cwd(&process.working_dir)?,
+    };
+    if let Some(mut agent) = managed {
+        agent.todos = agent_todos(&agent);
+        println!();
+        print_agent(&agent);
+    }
+    Ok(())
+}
+
+/// 进程树文本（缩进表示父子关系），只有根进程时为空
+pub fn format_process_tree(tree: &[ProcessNode]) -> Vec<String> {
+    if tree.len() <= 1 {
+        return Vec::new();
+    }
+    tree.iter()
+        .map(|node| {
+            let command = if node.command.is_empty() {
+                &node.name
+            } else {
+                &node.command
+            };
+            format!(
+                "{}{} {}",
+                "  ".repeat(node.depth),
+                node.pid,
+                truncate_width(command, PROCESS_COMMAND_WIDTH)
+            )
+        })
+        .collect()
+}
+
+fn print_process_tree(tree: &[ProcessNode]) {
+    let lines = format_process_tree(tree);
+    if lines.is_empty() {
+        return;
+    }
+    println!("\n进程树（cam kill <agent_id> --tree 全部终止）:");
+    for line in lines {
+        println!("  {}", line);
+    }
+}
+
+fn print_todos(todos: &[TodoItem]) {
+    let Some(progress) = TodoProgress::of(todos) else {
+        return;
+    };
+    println!("  TODO: {}", progress);
+    for todo in todos {
+        let text = match (todo.status, &todo.active_form) {
+            (TodoStatus::InProgress, Some(active)) => active,
+            _ => &todo.content,
+        };
+        println!("    {} {}", todo.status.marker(), text);
+    }
+}
+
+fn print_agent(agent: &AgentRecord) {
+    println!("Agent 信息:");
+    println!("  agent_id: {}", agent.agent_id);
+    println!("  类型: {}", agent.agent_type);
+    println!("  项目: {}", agent.project_path);
+    println!("  tmux: {}", agent.tmux_session);
+    if let Some(ref session_id) = agent.session_id {
+        println!("  会话 ID: {}", session_id);
+    }
+    if let Some(ref container) = agent.container {
+        println!(
+            "  容器: {} ({})",
+            container.id.as_deref().unwrap_or(&container.name),
+            container.image
+        );
+    }
+    println!("  当前状态: {} {:?}", agent.status.icon(), agent.status);
+    if agent.ready == Some(false) {
+        println!("  启动检查: ✗ 启动后未就绪（cam start --wait-ready）");
+    }
+    if !agent.ports.is_empty() {
+        println!("  监听端口: {}", format_ports(&agent.ports));
+    }
+    print_todos(&agent.todos);
+
+    if agent.history.is_empty() {
+        println!("  （暂无状态历史）");
+        return;
+    }
+
+    let stats = HistoryStats::compute(&agent.history, Utc::now());
+    println!("  运行时长: {}", format_elapsed(stats.uptime_secs));
+    println!("  处理中: {}", format_elapsed(stats.processing_secs));
+    println!(
+        "  等待人工: {}（{} 次）",
+        format_elapsed(stats.waiting_secs),
+        stats.waiting_count
+    );
+    if stats.error_count > 0 {
+        println!("  错误: {} 次", stats.error_count);
+    }
+
+    println!("\n状态历史:");
+    let skip = agent.history.len().saturating_sub(HISTORY_DISPLAY_LIMIT);
+    if skip > 0 {
+        println!("  …（省略更早的 {} 条）", skip);
+    }
+    for transition in &agent.history[skip..] {
+        println!(
+            "  {}  {}",
+            transition
+                .at
+                .with_timezone(&chrono::Local)
+                .format("%m-%d %H:%M:%S"),
+            transition.state.label()
+        );
+    }
+}
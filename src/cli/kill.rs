@@ -0,0 +1,67 @@
+//! `cam kill` 命令 - 终止进程或 CAM agent，`--tree` 时连同其子进程
+//!
+//! agent 常在 session 里启动开发服务器、测试 watcher，只结束 agent 会留下占用端口的孤儿进程。
+//! `--tree` 在结束前先记录整棵进程树（session 结束后子进程会被挂到 init 下，无法再按父子关系找到），
+//! 再由子到父逐个终止。
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::infra::ProcessScanner;
+
+#[derive(Args, Debug)]
+pub struct KillArgs {
+    /// 进程 PID 或 agent_id
+    pub target: String,
+    /// 同时终止所有子进程（开发服务器、测试 watcher 等）
+    #[arg(long)]
+    pub tree: bool,
+}
+
+/// 处理 `cam kill`
+pub fn handle_kill(args: KillArgs) -> Result<()> {
+    let scanner = ProcessScanner::new();
+
+    let Ok(pid) = args.target.parse::<u32>() else {
+        let manager = AgentManager::new();
+        let agent = manager
+            .get_agent(&args.target)?
+            .ok_or_else(|| CliError::AgentNotFound(format!("未找到 agent: {}", args.target)))?;
+        let tree = if args.tree {
+            manager.process_tree(&agent, &scanner)
+        } else {
+            Vec::new()
+        };
+        manager.stop_agent(&agent.agent_id)?;
+        println!("已停止 agent: {}", agent.agent_id);
+        // session 结束时已退出的进程不再计入，PID 被复用的进程不会误杀
+        let killed = scanner.kill_processes(&tree);
+        if !killed.is_empty() {
+            println!("已终止残留的子进程: {}", join_pids(&killed));
+        }
+        return Ok(());
+    };
+
+    if !args.tree {
+        scanner.kill_agent(pid)?;
+        println!("已终止进程: {}", pid);
+        return Ok(());
+    }
+
+    let tree = scanner.process_tree(pid);
+    if tree.is_empty() {
+        return Err(CliError::AgentNotFound(format!("进程 {} 不存在", pid)).into());
+    }
+    let killed = scanner.kill_processes(&tree);
+    println!("已终止进程树: {}", join_pids(&killed));
+    Ok(())
+}
+
+fn join_pids(pids: &[u32]) -> String {
+    pids.iter()
+        .map(|pid| pid.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
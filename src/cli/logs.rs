@@ -0,0 +1,326 @@
+//! `cam logs` 命令 - 查看会话消息，`--follow` 持续跟踪
+//!
+//! 会话 ID 或 agent ID 均可：优先跟踪 JSONL，agent 没有 JSONL 时回退到 tmux 终端输出。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::agent::AgentManager;
+use crate::infra::jsonl::{format_tool_use, JsonlEvent, JsonlParser};
+use crate::infra::multiplexer::{self, Multiplexer};
+use crate::session::SessionManager;
+
+/// 跟踪模式轮询间隔
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 跟踪 tmux 终端时捕获的行数
+const PANE_CAPTURE_LINES: u32 = 200;
+
+#[derive(Args, Debug)]
+pub struct LogsArgs {
+    /// 会话 ID 或 agent ID
+    pub session_id: String,
+    /// 显示最近 N 条消息
+    #[arg(long, short, default_value = "5")]
+    pub limit: usize,
+    /// 持续输出新消息（Ctrl-C 退出）
+    #[arg(long, short)]
+    pub follow: bool,
+    /// 只显示指定角色（user/assistant/tool/error，可重复）
+    #[arg(long = "role", value_name = "ROLE")]
+    pub roles: Vec<String>,
+    /// 只显示指定工具的调用和结果（如 Bash、Edit，可重复）
+    #[arg(long = "tool", value_name = "TOOL")]
+    pub tools: Vec<String>,
+}
+
+/// 日志来源
+enum LogSource {
+    Jsonl(PathBuf),
+    Tmux(String),
+}
+
+/// 处理 `cam logs`
+pub fn handle_logs(args: LogsArgs) -> Result<()> {
+    let filter = LogFilter::new(&args.roles, &args.tools);
+
+    // 未使用新选项时保持原有输出
+    if !args.follow && filter.is_empty() {
+        let messages = SessionManager::new().get_session_logs(&args.session_id, args.limit)?;
+        if messages.is_empty() {
+            println!("未找到会话 {} 的消息", args.session_id);
+        } else {
+            println!(
+                "会话 {} 的最近 {} 条消息:\n",
+                args.session_id,
+                messages.len()
+            );
+            for (i, msg) in messages.iter().enumerate() {
+                println!("--- 消息 {} ({}) ---", i + 1, msg.role);
+                println!("{}\n", msg.content);
+            }
+        }
+        return Ok(());
+    }
+
+    match resolve_source(&args.session_id)? {
+        Some(LogSource::Jsonl(path)) => follow_jsonl(path, &filter, args.limit, args.follow),
+        Some(LogSource::Tmux(session)) => {
+            if !filter.is_empty() {
+                eprintln!("⚠️ 没有 JSONL 会话文件，--role/--tool 过滤不生效");
+            }
+            follow_tmux(&session, args.follow)
+        }
+        None => {
+            println!("未找到会话 {} 的消息", args.session_id);
+            Ok(())
+        }
+    }
+}
+
+/// 按 agent ID 或会话 ID 查找日志来源
+fn resolve_source(id: &str) -> Result<Option<LogSource>> {
+    let sessions = SessionManager::new();
+    if let Some(agent) = AgentManager::new().get_agent(id)? {
+        if let Some(path) = agent.jsonl_path.as_deref().map(PathBuf::from) {
+            if path.exists() {
+                return Ok(Some(LogSource::Jsonl(path)));
+            }
+        }
+        if let Some(session_id) = agent.session_id.as_deref() {
+            if let Some(path) = sessions.find_session_file(session_id)? {
+                return Ok(Some(LogSource::Jsonl(path)));
+            }
+        }
+        return Ok(Some(LogSource::Tmux(agent.tmux_session)));
+    }
+    Ok(sessions.find_session_file(id)?.map(LogSource::Jsonl))
+}
+
+/// 先输出最近 `limit` 条匹配的消息，`follow` 时继续输出新增内容
+fn follow_jsonl(path: PathBuf, filter: &LogFilter, limit: usize, follow: bool) -> Result<()> {
+    let mut parser = JsonlParser::new(&path);
+    let mut formatter = EventFormatter::default();
+
+    let lines: Vec<String> = parser
+        .read_complete_events()?
+        .iter()
+        .filter_map(|event| formatter.format(event, filter))
+        .collect();
+    for line in &lines[lines.len().saturating_sub(limit)..] {
+        println!("{}", line);
+    }
+
+    if !follow {
+        return Ok(());
+    }
+    loop {
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+        for event in parser.read_complete_events()? {
+            if let Some(line) = formatter.format(&event, filter) {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+/// 输出 tmux 终端内容，`follow` 时持续输出新增行
+fn follow_tmux(session: &str, follow: bool) -> Result<()> {
+    let tmux = multiplexer::from_config();
+    let capture = |tmux: &dyn Multiplexer| -> Result<Vec<String>> {
+        Ok(tmux
+            .capture_pane(session, PANE_CAPTURE_LINES)?
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect())
+    };
+
+    let mut previous = capture(tmux.as_ref())?;
+    for line in &previous {
+        println!("{}", line);
+    }
+    if !follow {
+        return Ok(());
+    }
+    loop {
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+        if !tmux.session_exists(session) {
+            println!("--- tmux session {} 已结束 ---", session);
+            return Ok(());
+        }
+        let current = capture(tmux.as_ref())?;
+        for line in new_pane_lines(&previous, &current) {
+            println!("{}", line);
+        }
+        previous = current;
+    }
+}
+
+/// 终端两次捕获之间新增的行：找到上次末尾与本次开头的最长重叠
+fn new_pane_lines<'a>(previous: &[String], current: &'a [String]) -> &'a [String] {
+    let max_overlap = previous.len().min(current.len());
+    let overlap = (0..=max_overlap)
+        .rev()
+        .find(|&k| previous[previous.len() - k..] == current[..k])
+        .unwrap_or(0);
+    &current[overlap..]
+}
+
+/// 角色与工具过滤
+#[derive(Debug, Default)]
+struct LogFilter {
+    roles: Vec<String>,
+    tools: Vec<String>,
+}
+
+impl LogFilter {
+    fn new(roles: &[String], tools: &[String]) -> Self {
+        Self {
+            roles: roles.iter().map(|r| r.to_ascii_lowercase()).collect(),
+            tools: tools.iter().map(|t| t.to_ascii_lowercase()).collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.roles.is_empty() && self.tools.is_empty()
+    }
+
+    /// 只指定 `--tool` 时隐含只看工具消息
+    fn matches(&self, role: &str, tool: Option<&str>) -> bool {
+        let role_ok = if self.roles.is_empty() {
+            self.tools.is_empty() || role == "tool"
+        } else {
+            self.roles.iter().any(|r| r == role)
+        };
+        let tool_ok = match tool {
+            Some(tool) => {
+                self.tools.is_empty() || self.tools.iter().any(|t| t == &tool.to_ascii_lowercase())
+            }
+            None => true,
+        };
+        role_ok && tool_ok
+    }
+}
+
+/// 把 JSONL 事件格式化为一行输出（记录 tool_id 以便显示结果对应的工具）
+#[derive(Debug, Default)]
+struct EventFormatter {
+    tool_names: HashMap<String, String>,
+}
+
+impl EventFormatter {
+    fn format(&mut self, event: &JsonlEvent, filter: &LogFilter) -> Option<String> {
+        let (role, tool, text, timestamp) = match event {
+            JsonlEvent::UserMessage { content, timestamp } => {
+                ("user", None, content.clone(), timestamp)
+            }
+            JsonlEvent::AssistantText { content, timestamp } => {
+                ("assistant", None, content.clone(), timestamp)
+            }
+            JsonlEvent::ToolUse {
+                tool_name,
+                tool_id,
+                timestamp,
+                ..
+            } => {
+                self.tool_names.insert(tool_id.clone(), tool_name.clone());
+                (
+                    "tool",
+                    Some(tool_name.clone()),
+                    format_tool_use(event).unwrap_or_else(|| tool_name.clone()),
+                    timestamp,
+                )
+            }
+            JsonlEvent::ToolResult {
+                tool_id,
+                success,
+                timestamp,
+                ..
+            } => {
+                let name = self.tool_names.get(tool_id).cloned();
+                let status = if *success { "✓" } else { "✗" };
+                let text = format!("{} {}", status, name.as_deref().unwrap_or("result"));
+                ("tool", name, text, timestamp)
+            }
+            JsonlEvent::Error { message, timestamp } => ("error", None, message.clone(), timestamp),
+            JsonlEvent::Progress { .. } => return None,
+        };
+
+        if !filter.matches(role, tool.as_deref()) {
+            return None;
+        }
+        // RFC3339 时间只取 HH:MM:SS
+        let time = timestamp
+            .as_deref()
+            .and_then(|ts| ts.get(11..19))
+            .map(|t| format!("[{}] ", t))
+            .unwrap_or_default();
+        Some(format!("{}{}: {}", time, role, text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_new_pane_lines() {
+        let prev = lines(&["a", "b", "c"]);
+        assert!(new_pane_lines(&prev, &prev).is_empty());
+        assert_eq!(
+            new_pane_lines(&prev, &lines(&["b", "c", "d", "e"])),
+            ["d", "e"]
+        );
+        assert_eq!(new_pane_lines(&prev, &lines(&["x", "y"])), ["x", "y"]);
+    }
+
+    #[test]
+    fn test_event_formatter_filters() {
+        let tool_use = JsonlEvent::ToolUse {
+            tool_name: "Bash".to_string(),
+            tool_id: "t1".to_string(),
+            input: serde_json::json!({"command": "cargo test"}),
+            timestamp: Some("2026-02-01T10:00:05Z".to_string()),
+        };
+        let result = JsonlEvent::ToolResult {
+            tool_id: "t1".to_string(),
+            success: false,
+            output: None,
+            timestamp: None,
+        };
+        let text = JsonlEvent::AssistantText {
+            content: "done".to_string(),
+            timestamp: None,
+        };
+
+        let all = LogFilter::default();
+        let mut formatter = EventFormatter::default();
+        assert_eq!(
+            formatter.format(&tool_use, &all).unwrap(),
+            "[10:00:05] tool: Bash cargo test"
+        );
+        assert_eq!(formatter.format(&result, &all).unwrap(), "tool: ✗ Bash");
+
+        // --tool 隐含只显示工具消息
+        let bash = LogFilter::new(&[], &["bash".to_string()]);
+        assert!(formatter.format(&tool_use, &bash).is_some());
+        assert!(formatter.format(&text, &bash).is_none());
+        let edit = LogFilter::new(&[], &["Edit".to_string()]);
+        assert!(formatter.format(&tool_use, &edit).is_none());
+
+        let assistant = LogFilter::new(&["assistant".to_string()], &[]);
+        assert!(formatter.format(&tool_use, &assistant).is_none());
+        assert_eq!(
+            formatter.format(&text, &assistant).unwrap(),
+            "assistant: done"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! `cam migrate` 命令 - 升级持久化文件的 schema 版本
+//!
+//! watcher daemon 启动时会自动静默执行一次；手动运行可查看各文件的版本状态。
+//! 升级前先把旧版本目录（`~/.claude-monitor` 等）中的文件移入状态目录。
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::infra::paths::{consolidate_default_legacy, LegacyStatus};
+use crate::infra::schema::{default_data_dir, migrate_all, MigrationStatus};
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// 只检查需要迁移的文件，不写入
+    #[arg(long)]
+    pub dry_run: bool,
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 处理 `cam migrate`
+pub fn handle_migrate(args: MigrateArgs) -> Result<()> {
+    let data_dir = default_data_dir();
+    let legacy = consolidate_default_legacy(args.dry_run);
+    let results = migrate_all(&data_dir, args.dry_run);
+
+    if args.json {
+        let files: Vec<serde_json::Value> = results
+            .iter()
+            .map(|(schema, result)| {
+                let mut entry = json!({
+                    "file": schema.file_name,
+                    "current_version": schema.version,
+                });
+                match result {
+                    Ok(MigrationStatus::Missing) => entry["status"] = json!("missing"),
+                    Ok(MigrationStatus::UpToDate { version }) => {
+                        entry["status"] = json!("up_to_date");
+                        entry["version"] = json!(version);
+                    }
+                    Ok(MigrationStatus::Migrated { from, to, backup }) => {
+                        entry["status"] = json!("migrated");
+                        entry["from"] = json!(from);
+                        entry["to"] = json!(to);
+                        entry["backup"] = json!(backup);
+                    }
+                    Ok(MigrationStatus::TooNew { version }) => {
+                        entry["status"] = json!("too_new");
+                        entry["version"] = json!(version);
+                    }
+                    Err(e) => {
+                        entry["status"] = json!("error");
+                        entry["error"] = json!(format!("{:#}", e));
+                    }
+                }
+                entry
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "dry_run": args.dry_run,
+                "legacy": legacy,
+                "files": files,
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!("数据目录: {}", data_dir.display());
+    for entry in &legacy {
+        match &entry.status {
+            LegacyStatus::Moved => println!(
+                "  → {}: 从 {} 移入{}",
+                entry.to.display(),
+                entry.from.display(),
+                if args.dry_run { "（dry-run）" } else { "" }
+            ),
+            LegacyStatus::Kept => println!(
+                "  ⚠️ {}: 状态目录已有同名文件，旧文件保留",
+                entry.from.display()
+            ),
+            LegacyStatus::Failed { error } => {
+                println!("  ❌ {}: 移动失败: {}", entry.from.display(), error)
+            }
+        }
+    }
+    for (schema, result) in &results {
+        match result {
+            Ok(MigrationStatus::Missing) => println!("  - {}: 不存在", schema.file_name),
+            Ok(MigrationStatus::UpToDate { version }) => {
+                println!("  ✓ {}: v{}（已是最新）", schema.file_name, version)
+            }
+            Ok(MigrationStatus::Migrated { from, to, backup }) => match backup {
+                Some(backup) => println!(
+                    "  ↑ {}: v{} → v{}（备份: {}）",
+                    schema.file_name,
+                    from,
+                    to,
+                    backup.display()
+                ),
+                None => println!("  ↑ {}: v{} → v{}（dry-run）", schema.file_name, from, to),
+            },
+            Ok(MigrationStatus::TooNew { version }) => println!(
+                "  ⚠️ {}: v{} 高于当前支持的 v{}，请升级 cam",
+                schema.file_name, version, schema.version
+            ),
+            Err(e) => println!("  ❌ {}: {:#}", schema.file_name, e),
+        }
+    }
+    Ok(())
+}
+
+/// 合并旧版本目录，只记录日志不打断命令
+///
+/// 每个命令执行前调用（只检查几个目录是否存在），避免命令先在状态目录写入同名文件导致旧文件被保留。
+pub fn auto_consolidate_legacy() {
+    for entry in consolidate_default_legacy(false) {
+        match entry.status {
+            LegacyStatus::Moved => {
+                info!(from = %entry.from.display(), to = %entry.to.display(), "Moved legacy state")
+            }
+            LegacyStatus::Kept => {}
+            LegacyStatus::Failed { error } => {
+                warn!(from = %entry.from.display(), error = %error, "Legacy state move failed")
+            }
+        }
+    }
+}
+
+/// watcher daemon 启动时升级持久化文件，只记录日志不打断启动
+///
+/// 其他命令读取时在内存中升级（`load_versioned`），不在每次调用时改写文件。
+pub fn auto_migrate() {
+    auto_consolidate_legacy();
+    for (schema, result) in migrate_all(&default_data_dir(), false) {
+        match result {
+            Ok(MigrationStatus::Migrated { from, to, .. }) => {
+                info!(file = schema.file_name, from, to, "Migrated state file")
+            }
+            Ok(MigrationStatus::TooNew { version }) => warn!(
+                file = schema.file_name,
+                version,
+                supported = schema.version,
+                "State file was written by a newer cam"
+            ),
+            Ok(_) => {}
+            Err(e) => warn!(file = schema.file_name, error = %e, "State file migration failed"),
+        }
+    }
+}
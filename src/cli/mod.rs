@@ -1,15 +1,75 @@
 //! CLI command handling
 
+pub mod attach;
+pub mod bench;
 pub mod bootstrap;
 pub mod codex_notify;
+pub mod compare;
+pub mod completions;
+pub mod dashboard;
+pub mod embeddings;
+pub mod error;
+pub mod events;
+pub mod handoff;
+pub mod hook_binary;
+pub mod inbox;
+pub mod info;
+pub mod kill;
+pub mod logs;
+pub mod migrate;
+pub mod notify_spool;
 pub mod output;
+pub mod pause;
+pub mod prompt_file;
+pub mod purge;
+pub mod queue;
+pub mod record;
+pub mod send_file;
+pub mod send_image;
+pub mod sessions;
 pub mod setup;
+pub mod show;
+pub mod simulate;
+pub mod snapshot;
 pub mod start;
+pub mod stats;
+pub mod status;
 pub mod summary;
+pub mod tool_hook;
 
+pub use attach::*;
+pub use bench::*;
 pub use bootstrap::*;
 pub use codex_notify::*;
+pub use compare::*;
+pub use completions::*;
+pub use dashboard::*;
+pub use embeddings::*;
+pub use error::*;
+pub use events::*;
+pub use handoff::*;
+pub use hook_binary::*;
+pub use inbox::*;
+pub use info::*;
+pub use kill::*;
+pub use logs::*;
+pub use migrate::*;
+pub use notify_spool::*;
 pub use output::*;
+pub use pause::*;
+pub use prompt_file::*;
+pub use purge::*;
+pub use queue::*;
+pub use record::*;
+pub use send_file::*;
+pub use send_image::*;
+pub use sessions::*;
 pub use setup::*;
+pub use show::*;
+pub use simulate::*;
+pub use snapshot::*;
 pub use start::*;
+pub use stats::*;
+pub use status::*;
 pub use summary::*;
+pub use tool_hook::*;
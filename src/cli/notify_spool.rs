@@ -0,0 +1,629 @@
+//! cam notify 快速路径 - hook 只把原始事件写入 spool 目录，由 watcher daemon 异步处理
+//!
+//! 完整的 `cam notify` 会读取 agents.json、抓取终端快照、可能调用 AI 提取并同步发送通知，
+//! 每次 hook 都给 Claude Code 增加数秒延迟。config.json 中 `"notify": {"spool": true}` 时，
+//! hook 只把原始输入写入 `~/.config/code-agent-monitor/spool/` 后立即返回（daemon 未运行时
+//! 顺便启动它）；daemon 的后台线程按写入顺序取出，以 `CAM_NOTIFY_SYNC=1 cam notify` 走完整
+//! 流程（解析 agent、提取、发送）。PreToolUse 需要同步返回拦截决定，始终走完整流程。
+//!
+//! 处理保证至少一次：
+//! - 每个事件一个文件 `<入队纳秒>-<pid>.json`，处理前改名为 `.processing` 认领
+//! - 成功后先把事件键写入 `acked.log` 再删除文件；daemon 启动时把崩溃遗留的 `.processing`
+//!   放回队列，已确认的事件按键跳过，不会重复发送
+//! - 失败按指数退避重试，超过 [`MAX_ATTEMPTS`] 次移到 `failed/`
+//!
+//! daemon 停止期间入队的事件在它重新启动后补发。`cam spool status` 查看积压，
+//! `cam spool flush` 立即处理（`--failed` 同时重试失败的事件）。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::agent::WatcherDaemon;
+use crate::notification::dedup_key::hash_content;
+
+/// 设置后 `cam notify` 不走快速路径（daemon 处理 spool 时使用）
+pub const NOTIFY_SYNC_ENV: &str = "CAM_NOTIFY_SYNC";
+
+/// 最多尝试次数，之后移到 `failed/`
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// 第一次重试的等待时间，之后每次翻倍
+const RETRY_BASE_SECS: i64 = 5;
+
+/// 重试等待时间上限
+const RETRY_MAX_SECS: i64 = 300;
+
+/// `acked.log` 保留的事件键数量
+const ACK_LEDGER_MAX: usize = 1000;
+
+/// daemon 检查 spool 的间隔
+pub const SPOOL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 快速路径配置（config.json 的 `notify`）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NotifySpoolConfig {
+    /// hook 只入队，由 watcher daemon 异步处理
+    pub spool: bool,
+}
+
+impl NotifySpoolConfig {
+    /// 读取 config.json，缺失或无效时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("notify").unwrap_or_default()
+    }
+}
+
+/// 入队的 hook 事件（`cam notify` 的参数和 stdin 原文）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpooledHook {
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    pub context: String,
+    #[serde(default)]
+    pub no_ai: bool,
+    pub queued_at: DateTime<Utc>,
+    /// 已失败的次数
+    #[serde(default)]
+    pub attempts: u32,
+    /// 下次重试时间，None 表示立即处理
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl SpooledHook {
+    pub fn new(event: &str, agent_id: Option<String>, context: String, no_ai: bool) -> Self {
+        Self {
+            event: event.to_string(),
+            agent_id,
+            context,
+            no_ai,
+            queued_at: Utc::now(),
+            attempts: 0,
+            next_attempt_at: None,
+            last_error: None,
+        }
+    }
+
+    /// 事件键（内容 + 入队时间），用于确认记录去重
+    pub fn key(&self) -> String {
+        let content = format!(
+            "{}\n{}\n{}\n{}",
+            self.event,
+            self.agent_id.as_deref().unwrap_or_default(),
+            self.queued_at.to_rfc3339(),
+            self.context
+        );
+        format!("{:016x}", hash_content(&content))
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_attempt_at.is_none_or(|at| at <= now)
+    }
+
+    /// 以同步模式运行 `cam notify` 处理该事件
+    pub fn process(&self) -> Result<()> {
+        let mut command = Command::new(std::env::current_exe()?);
+        command.args(["notify", "--event", &self.event]);
+        if let Some(agent_id) = &self.agent_id {
+            command.args(["--agent-id", agent_id]);
+        }
+        if self.no_ai {
+            command.arg("--no-ai");
+        }
+        let mut child = command
+            .env(NOTIFY_SYNC_ENV, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(self.context.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(anyhow!(
+            "cam notify exited with {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        ))
+    }
+}
+
+/// 第 `attempts` 次失败后的重试等待时间
+fn retry_delay(attempts: u32) -> chrono::Duration {
+    let secs = RETRY_BASE_SECS.saturating_mul(1 << attempts.saturating_sub(1).min(16));
+    chrono::Duration::seconds(secs.min(RETRY_MAX_SECS))
+}
+
+/// 一次处理的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DrainReport {
+    /// 处理成功
+    pub processed: usize,
+    /// 已确认过，跳过
+    pub duplicates: usize,
+    /// 失败，等待重试
+    pub retrying: usize,
+    /// 超过重试次数或无法解析，移到 `failed/`
+    pub failed: usize,
+}
+
+/// spool 积压情况
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SpoolStatus {
+    /// 待处理（含等待重试）
+    pub pending: usize,
+    /// 其中等待重试的
+    pub retrying: usize,
+    /// 处理中（daemon 崩溃时遗留）
+    pub processing: usize,
+    pub failed: usize,
+    /// 最早入队的待处理事件
+    pub oldest: Option<DateTime<Utc>>,
+}
+
+/// spool 目录，每个事件一个文件，文件名按入队时间排序
+#[derive(Debug, Clone)]
+pub struct NotifySpool {
+    dir: PathBuf,
+}
+
+impl Default for NotifySpool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotifySpool {
+    pub fn new() -> Self {
+        Self::with_dir(crate::infra::paths::state_dir().join("spool"))
+    }
+
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn failed_dir(&self) -> PathBuf {
+        self.dir.join("failed")
+    }
+
+    fn ack_path(&self) -> PathBuf {
+        self.dir.join("acked.log")
+    }
+
+    /// 写入事件（先写临时文件再改名，不会读到写了一半的文件）
+    pub fn push(&self, hook: &SpooledHook) -> Result<PathBuf> {
+        let nanos = hook.queued_at.timestamp_nanos_opt().unwrap_or_default();
+        let path = self
+            .dir
+            .join(format!("{:020}-{}.json", nanos, std::process::id()));
+        write_atomic(&path, hook)?;
+        Ok(path)
+    }
+
+    fn files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files
+    }
+
+    /// 待处理的事件文件（按入队顺序）
+    pub fn pending(&self) -> Vec<PathBuf> {
+        Self::files(&self.dir, "json")
+    }
+
+    /// 没有待处理和处理中的事件
+    pub fn is_empty(&self) -> bool {
+        self.pending().is_empty() && Self::files(&self.dir, "processing").is_empty()
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> SpoolStatus {
+        let hooks: Vec<SpooledHook> = self.pending().iter().filter_map(|p| read_hook(p)).collect();
+        SpoolStatus {
+            pending: hooks.len(),
+            retrying: hooks.iter().filter(|hook| !hook.is_due(now)).count(),
+            processing: Self::files(&self.dir, "processing").len(),
+            failed: Self::files(&self.failed_dir(), "json").len(),
+            oldest: hooks.iter().map(|hook| hook.queued_at).min(),
+        }
+    }
+
+    /// 把处理中断（daemon 崩溃）遗留的事件放回队列，只应在没有其他进程处理时调用
+    pub fn recover(&self) -> usize {
+        Self::files(&self.dir, "processing")
+            .into_iter()
+            .filter(|path| fs::rename(path, path.with_extension("json")).is_ok())
+            .count()
+    }
+
+    /// 把 `failed/` 中的事件重新入队（重置重试次数）
+    pub fn requeue_failed(&self) -> Result<usize> {
+        let mut requeued = 0;
+        for path in Self::files(&self.failed_dir(), "json") {
+            if let Some(mut hook) = read_hook(&path) {
+                hook.attempts = 0;
+                hook.next_attempt_at = None;
+                write_atomic(&self.dir.join(path.file_name().unwrap_or_default()), &hook)?;
+                requeued += 1;
+            }
+            fs::remove_file(&path)?;
+        }
+        Ok(requeued)
+    }
+
+    /// 事件是否已确认处理
+    pub fn is_acked(&self, key: &str) -> bool {
+        fs::read_to_string(self.ack_path())
+            .map(|content| content.lines().any(|line| line == key))
+            .unwrap_or(false)
+    }
+
+    /// 记录已处理的事件键（文件锁内读写，只保留最近的 [`ACK_LEDGER_MAX`] 条）
+    fn ack(&self, key: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.dir.join("acked.lock"))?;
+        lock_file.lock_exclusive()?;
+        let content = fs::read_to_string(self.ack_path()).unwrap_or_default();
+        let mut keys: Vec<&str> = content.lines().collect();
+        keys.push(key);
+        let keep = &keys[keys.len().saturating_sub(ACK_LEDGER_MAX)..];
+        let tmp = self.dir.join(".acked.tmp");
+        fs::write(&tmp, format!("{}\n", keep.join("\n")))?;
+        fs::rename(&tmp, self.ack_path())?;
+        Ok(())
+    }
+
+    /// 把已认领的事件移到 `failed/`（无法解析时原样复制）
+    fn fail(&self, name: &Path, hook: Option<&SpooledHook>, claimed: &Path) -> Result<()> {
+        let target = self.failed_dir().join(name);
+        match hook {
+            Some(hook) => write_atomic(&target, hook)?,
+            None => {
+                fs::create_dir_all(self.failed_dir())?;
+                fs::copy(claimed, &target)?;
+            }
+        }
+        fs::remove_file(claimed)?;
+        Ok(())
+    }
+
+    /// 按顺序处理到期的事件；`force` 时忽略重试等待
+    pub fn drain_at(
+        &self,
+        now: DateTime<Utc>,
+        force: bool,
+        mut process: impl FnMut(&SpooledHook) -> Result<()>,
+    ) -> DrainReport {
+        let mut report = DrainReport::default();
+        for path in self.pending() {
+            let hook = read_hook(&path);
+            if hook
+                .as_ref()
+                .is_some_and(|hook| !force && !hook.is_due(now))
+            {
+                continue;
+            }
+            // 改名认领，失败说明已被其他进程取走
+            let claimed = path.with_extension("processing");
+            if fs::rename(&path, &claimed).is_err() {
+                continue;
+            }
+            let name = PathBuf::from(path.file_name().unwrap_or_default());
+            let result = match hook {
+                None => {
+                    warn!(path = %path.display(), "Unreadable spooled hook moved to failed");
+                    report.failed += 1;
+                    self.fail(&name, None, &claimed)
+                }
+                Some(hook) if self.is_acked(&hook.key()) => {
+                    report.duplicates += 1;
+                    fs::remove_file(&claimed).map_err(Into::into)
+                }
+                Some(mut hook) => match process(&hook) {
+                    Ok(()) => {
+                        report.processed += 1;
+                        self.ack(&hook.key())
+                            .and_then(|_| fs::remove_file(&claimed).map_err(Into::into))
+                    }
+                    Err(e) => {
+                        hook.attempts += 1;
+                        hook.last_error = Some(e.to_string());
+                        warn!(
+                            event = %hook.event,
+                            attempts = hook.attempts,
+                            error = %e,
+                            "Spooled hook failed"
+                        );
+                        if hook.attempts >= MAX_ATTEMPTS {
+                            report.failed += 1;
+                            self.fail(&name, Some(&hook), &claimed)
+                        } else {
+                            report.retrying += 1;
+                            hook.next_attempt_at = Some(now + retry_delay(hook.attempts));
+                            write_atomic(&path, &hook)
+                                .and_then(|_| fs::remove_file(&claimed).map_err(Into::into))
+                        }
+                    }
+                },
+            };
+            if let Err(e) = result {
+                warn!(path = %path.display(), error = %e, "Failed to update spool");
+            }
+        }
+        report
+    }
+
+    /// 持续处理 spool（daemon 后台线程），先恢复上次中断的事件
+    pub fn run(&self) -> ! {
+        let recovered = self.recover();
+        if recovered > 0 {
+            debug!(recovered, "Recovered interrupted spooled hooks");
+        }
+        loop {
+            let report = self.drain_at(Utc::now(), false, SpooledHook::process);
+            if report != DrainReport::default() {
+                debug!(?report, "Spooled hooks drained");
+            }
+            std::thread::sleep(SPOOL_POLL_INTERVAL);
+        }
+    }
+}
+
+fn read_hook(path: &Path) -> Option<SpooledHook> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// 先写临时文件再改名
+fn write_atomic(path: &Path, hook: &SpooledHook) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dir.join(format!(".{}.tmp", name));
+    fs::write(&tmp, serde_json::to_vec(hook)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// 快速路径：配置开启时把事件写入 spool 并返回 true，调用方直接返回
+///
+/// 设置了 [`NOTIFY_SYNC_ENV`]、PreToolUse、dry-run 或写入失败时返回 false，走完整流程。
+pub fn try_spool_hook(
+    event: &str,
+    agent_id: Option<&str>,
+    context: &str,
+    dry_run: bool,
+    no_ai: bool,
+) -> bool {
+    if dry_run || event == "pre_tool_use" || std::env::var_os(NOTIFY_SYNC_ENV).is_some() {
+        return false;
+    }
+    if !NotifySpoolConfig::load().spool {
+        return false;
+    }
+    let hook = SpooledHook::new(
+        event,
+        agent_id.map(str::to_string),
+        context.to_string(),
+        no_ai,
+    );
+    if let Err(e) = NotifySpool::new().push(&hook) {
+        warn!(event, error = %e, "Failed to spool hook, handling inline");
+        return false;
+    }
+    // 启动失败时事件留在 spool 中，daemon 下次启动时补发
+    if let Err(e) = WatcherDaemon::new().ensure_started() {
+        warn!(event, error = %e, "Failed to start watcher for spooled hook");
+    }
+    true
+}
+
+#[derive(Args, Debug)]
+pub struct SpoolArgs {
+    #[command(subcommand)]
+    pub action: Option<SpoolAction>,
+    /// JSON 格式输出
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SpoolAction {
+    /// 显示待处理、重试中和失败的事件（默认）
+    Status,
+    /// 立即处理所有待处理事件（忽略重试等待）
+    Flush {
+        /// 同时重新处理 failed/ 中的事件
+        #[arg(long)]
+        failed: bool,
+    },
+}
+
+pub fn handle_spool(args: SpoolArgs) -> Result<()> {
+    let spool = NotifySpool::new();
+    let enabled = NotifySpoolConfig::load().spool;
+    let daemon_running = WatcherDaemon::new().is_running();
+
+    match args.action.unwrap_or(SpoolAction::Status) {
+        SpoolAction::Status => {
+            let status = spool.status(Utc::now());
+            if args.json {
+                let mut json = serde_json::to_value(&status)?;
+                json["dir"] = serde_json::json!(spool.dir());
+                json["enabled"] = serde_json::json!(enabled);
+                json["daemon_running"] = serde_json::json!(daemon_running);
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
+            println!("目录: {}", spool.dir().display());
+            println!(
+                "快速路径: {}",
+                if enabled {
+                    "开启"
+                } else {
+                    "关闭（config.json 的 notify.spool）"
+                }
+            );
+            println!(
+                "Watcher: {}",
+                if daemon_running {
+                    "运行中"
+                } else {
+                    "未运行"
+                }
+            );
+            println!("待处理: {}（等待重试 {}）", status.pending, status.retrying);
+            if status.processing > 0 {
+                println!("处理中: {}", status.processing);
+            }
+            println!("失败: {}", status.failed);
+            if let Some(oldest) = status.oldest {
+                println!(
+                    "最早入队: {}",
+                    oldest
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+        }
+        SpoolAction::Flush { failed } => {
+            let requeued = if failed { spool.requeue_failed()? } else { 0 };
+            // daemon 运行时 .processing 可能正在处理，不能放回队列
+            if !daemon_running {
+                spool.recover();
+            }
+            let report = spool.drain_at(Utc::now(), true, SpooledHook::process);
+            if args.json {
+                let mut json = serde_json::to_value(&report)?;
+                json["requeued"] = serde_json::json!(requeued);
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
+            if requeued > 0 {
+                println!("重新入队: {}", requeued);
+            }
+            println!(
+                "已处理 {}，重复跳过 {}，等待重试 {}，失败 {}",
+                report.processed, report.duplicates, report.retrying, report.failed
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn hook_at(event: &str, queued_at: DateTime<Utc>) -> SpooledHook {
+        let mut hook = SpooledHook::new(event, None, "{}".into(), false);
+        hook.queued_at = queued_at;
+        hook
+    }
+
+    #[test]
+    fn test_spool_drains_in_order_and_acks() {
+        let tmp = TempDir::new().unwrap();
+        let spool = NotifySpool::with_dir(tmp.path());
+        assert!(spool.is_empty());
+
+        let now = Utc::now();
+        let first = hook_at("session_start", now - chrono::Duration::seconds(2));
+        let second = hook_at("stop", now - chrono::Duration::seconds(1));
+        // 乱序写入，按入队时间处理
+        spool.push(&second).unwrap();
+        spool.push(&first).unwrap();
+        fs::write(tmp.path().join("99999999999999999999-1.json"), "not json").unwrap();
+        assert_eq!(spool.status(now).pending, 2);
+        assert_eq!(spool.status(now).oldest, Some(first.queued_at));
+
+        let mut seen = Vec::new();
+        let report = spool.drain_at(now, false, |hook| {
+            seen.push(hook.event.clone());
+            Ok(())
+        });
+        assert_eq!(seen, ["session_start", "stop"]);
+        assert_eq!(
+            report,
+            DrainReport {
+                processed: 2,
+                failed: 1,
+                ..Default::default()
+            }
+        );
+        assert!(spool.is_empty());
+        assert!(spool.is_acked(&first.key()));
+        assert_eq!(spool.status(now).failed, 1);
+
+        // 处理成功后、删除文件前崩溃：恢复后按确认记录跳过，不重复处理
+        let path = spool.push(&first).unwrap();
+        fs::rename(&path, path.with_extension("processing")).unwrap();
+        assert!(!spool.is_empty());
+        assert_eq!(spool.recover(), 1);
+        let report = spool.drain_at(now, false, |_| panic!("already acked"));
+        assert_eq!(report.duplicates, 1);
+        assert!(spool.is_empty());
+    }
+
+    #[test]
+    fn test_spool_retries_then_fails() {
+        let tmp = TempDir::new().unwrap();
+        let spool = NotifySpool::with_dir(tmp.path());
+        let mut now = Utc::now();
+        spool.push(&hook_at("notification", now)).unwrap();
+
+        let fail = |_: &SpooledHook| Err(anyhow!("channel down"));
+        assert_eq!(spool.drain_at(now, false, fail).retrying, 1);
+        // 未到重试时间
+        assert_eq!(spool.drain_at(now, false, fail), DrainReport::default());
+        assert_eq!(spool.status(now).retrying, 1);
+        for _ in 1..MAX_ATTEMPTS {
+            now += chrono::Duration::seconds(RETRY_MAX_SECS);
+            spool.drain_at(now, false, fail);
+        }
+        assert!(spool.is_empty());
+        assert_eq!(spool.status(now).failed, 1);
+
+        assert_eq!(spool.requeue_failed().unwrap(), 1);
+        assert_eq!(spool.drain_at(now, true, |_| Ok(())).processed, 1);
+        assert_eq!(spool.status(now), SpoolStatus::default());
+
+        assert_eq!(retry_delay(1).num_seconds(), RETRY_BASE_SECS);
+        assert_eq!(retry_delay(2).num_seconds(), RETRY_BASE_SECS * 2);
+        assert_eq!(retry_delay(30).num_seconds(), RETRY_MAX_SECS);
+    }
+}
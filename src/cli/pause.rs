@@ -0,0 +1,87 @@
+//! `cam pause` / `cam resume-all` 命令 - 中断所有 agent，稍后统一继续
+//!
+//! 按键与状态见 [`crate::agent::pause`]。
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::agent::pause::{
+    pause_agents, resume_agents, PauseKeys, PauseOutcome, PauseResult, PauseState,
+};
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+
+#[derive(Args, Debug)]
+pub struct PauseArgs {
+    /// 只暂停该 agent
+    #[arg(conflicts_with = "all")]
+    pub agent_id: Option<String>,
+
+    /// 暂停所有运行中的 agent
+    #[arg(long)]
+    pub all: bool,
+
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ResumeAllArgs {
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+fn describe(outcome: &PauseOutcome) -> String {
+    match outcome {
+        PauseOutcome::Paused => "⏸ 已暂停".to_string(),
+        PauseOutcome::AlreadyPaused => "⏸ 已处于暂停".to_string(),
+        PauseOutcome::Unsupported => {
+            "⚠️ 没有安全的中断按键（config.json 的 pause_keys 可配置）".to_string()
+        }
+        PauseOutcome::Resumed => "▶ 已继续".to_string(),
+        PauseOutcome::Unmarked => "▶ 暂停前未在处理，已清除标记".to_string(),
+        PauseOutcome::Failed { error } => format!("❌ 发送按键失败: {}", error),
+    }
+}
+
+fn print_results(results: &[PauseResult], json: bool, empty: &str) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(results)?);
+    } else if results.is_empty() {
+        println!("{}", empty);
+    } else {
+        for result in results {
+            println!("{:<24} {}", result.agent_id, describe(&result.outcome));
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_pause(args: PauseArgs) -> Result<()> {
+    if !args.all && args.agent_id.is_none() {
+        return Err(CliError::InvalidArgument("请指定 agent_id 或 --all".to_string()).into());
+    }
+    let results = pause_agents(
+        &AgentManager::new(),
+        &PauseKeys::load(),
+        &PauseState::path(),
+        args.agent_id.as_deref(),
+    )?;
+    if let Some(agent_id) = &args.agent_id {
+        if results.is_empty() {
+            return Err(CliError::AgentNotFound(format!("未找到 agent: {}", agent_id)).into());
+        }
+    }
+    print_results(&results, args.json, "没有运行中的 agent")
+}
+
+pub fn handle_resume_all(args: ResumeAllArgs) -> Result<()> {
+    let results = resume_agents(
+        &AgentManager::new(),
+        &PauseKeys::load(),
+        &PauseState::path(),
+    )?;
+    print_results(&results, args.json, "没有暂停中的 agent")
+}
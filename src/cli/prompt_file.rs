@@ -0,0 +1,87 @@
+//! 从文件读取 prompt - `cam start --prompt-file`、`cam reply --file`
+//!
+//! 多行内容通过粘贴发送（见 [`crate::infra::multiplexer::Multiplexer::send_text`]），
+//! 这里负责读取、校验大小和编码，并给出发送进度提示。
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::cli::CliError;
+
+/// prompt 文件大小上限（字节），过大的粘贴会让 agent TUI 长时间无响应
+pub const MAX_PROMPT_FILE_BYTES: u64 = 100 * 1024;
+
+/// 读取 prompt 文件，去掉末尾空白
+pub fn read_prompt_file(path: &Path) -> Result<String> {
+    let metadata = fs::metadata(path).map_err(|e| {
+        CliError::InvalidArgument(format!("无法读取 prompt 文件 {}: {}", path.display(), e))
+    })?;
+    if metadata.len() > MAX_PROMPT_FILE_BYTES {
+        return Err(CliError::InvalidArgument(format!(
+            "prompt 文件 {} 过大（{}），上限 {}",
+            path.display(),
+            format_size(metadata.len()),
+            format_size(MAX_PROMPT_FILE_BYTES)
+        ))
+        .into());
+    }
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8(bytes).map_err(|_| {
+        CliError::InvalidArgument(format!("prompt 文件 {} 不是 UTF-8 文本", path.display()))
+    })?;
+    let text = text.trim_end().to_string();
+    if text.trim().is_empty() {
+        return Err(
+            CliError::InvalidArgument(format!("prompt 文件 {} 为空", path.display())).into(),
+        );
+    }
+    Ok(text)
+}
+
+/// prompt 大小摘要，如 `3.2 KB，84 行`
+pub fn describe_prompt(text: &str) -> String {
+    format!(
+        "{}，{} 行",
+        format_size(text.len() as u64),
+        text.lines().count()
+    )
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_prompt_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let plan = temp.path().join("plan.md");
+        fs::write(&plan, "# Plan\n\n1. Add the API\n2. Write tests\n\n").unwrap();
+        let text = read_prompt_file(&plan).unwrap();
+        assert_eq!(text, "# Plan\n\n1. Add the API\n2. Write tests");
+        assert_eq!(describe_prompt(&text), "37 B，4 行");
+
+        let empty = temp.path().join("empty.md");
+        fs::write(&empty, "  \n\n").unwrap();
+        assert!(read_prompt_file(&empty)
+            .unwrap_err()
+            .to_string()
+            .contains("为空"));
+
+        let large = temp.path().join("large.md");
+        fs::write(&large, "x".repeat(MAX_PROMPT_FILE_BYTES as usize + 1)).unwrap();
+        let err = read_prompt_file(&large).unwrap_err().to_string();
+        assert!(err.contains("过大（100.0 KB），上限 100.0 KB"), "{}", err);
+
+        assert!(read_prompt_file(&temp.path().join("missing.md")).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! `cam purge` 命令 - 卸载 CAM 留下的所有痕迹
+//!
+//! 依次卸载 launchd 服务、停止 watcher daemon、停止 agent 并清理 `cam-*` tmux session、
+//! 移除 Claude/Codex 配置中的 CAM hooks，最后删除（或用 `--archive` 归档）状态目录。
+//! 先列出完整计划，`--dry-run` 只打印不执行。
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use clap::Args;
+
+use crate::agent::adapter::config_manager::BackupManager;
+use crate::agent::{AgentManager, WatcherDaemon};
+use crate::cli::setup::{hook_config_path, line_diff, remove_hook_config};
+use crate::infra::TmuxManager;
+use crate::service::LaunchdService;
+
+/// 需要移除 hooks 的工具
+const HOOK_TOOLS: [&str; 2] = ["claude", "codex"];
+
+#[derive(Args, Debug)]
+pub struct PurgeArgs {
+    /// 只列出将要执行的操作
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// 跳过确认
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// 把状态目录移动到该目录下归档，而不是删除
+    #[arg(long, value_name = "DIR")]
+    pub archive: Option<PathBuf>,
+}
+
+/// 清理计划中的一步
+#[derive(Debug)]
+enum PurgeStep {
+    StopWatcher {
+        pid: Option<u32>,
+    },
+    StopAgent {
+        agent_id: String,
+        session: String,
+    },
+    KillSession {
+        session: String,
+    },
+    RemoveHooks {
+        tool: &'static str,
+        path: PathBuf,
+        existing: String,
+        content: String,
+    },
+    UninstallService,
+    RemoveState {
+        path: PathBuf,
+        archive: Option<PathBuf>,
+    },
+}
+
+impl PurgeStep {
+    fn describe(&self) -> String {
+        match self {
+            PurgeStep::StopWatcher { pid: Some(pid) } => {
+                format!("停止 watcher daemon (PID {})", pid)
+            }
+            PurgeStep::StopWatcher { pid: None } => "停止 watcher daemon".to_string(),
+            PurgeStep::StopAgent { agent_id, session } => {
+                format!("停止 agent {}（session {}）", agent_id, session)
+            }
+            PurgeStep::KillSession { session } => format!("关闭 tmux session {}", session),
+            PurgeStep::RemoveHooks { tool, path, .. } => {
+                format!("移除 {} hooks: {}", tool, path.display())
+            }
+            PurgeStep::UninstallService => "卸载 launchd 服务 com.cam.watcher".to_string(),
+            PurgeStep::RemoveState {
+                path,
+                archive: Some(archive),
+            } => format!("归档 {} → {}", path.display(), archive.display()),
+            PurgeStep::RemoveState {
+                path,
+                archive: None,
+            } => format!("删除 {}", path.display()),
+        }
+    }
+
+    fn execute(&self) -> Result<()> {
+        match self {
+            PurgeStep::StopWatcher { .. } => {
+                WatcherDaemon::new().stop()?;
+            }
+            PurgeStep::StopAgent { agent_id, .. } => {
+                AgentManager::new().stop_agent(agent_id)?;
+            }
+            PurgeStep::KillSession { session } => {
+                TmuxManager::new().kill_session(session)?;
+            }
+            PurgeStep::RemoveHooks {
+                tool,
+                path,
+                content,
+                ..
+            } => {
+                let backup_path = BackupManager::new().backup(tool, path)?;
+                println!("  ✓ 已备份到 {}", backup_path.display());
+                fs::write(path, content)?;
+            }
+            PurgeStep::UninstallService => {
+                LaunchdService::new()?.uninstall()?;
+            }
+            PurgeStep::RemoveState { path, archive } => match archive {
+                Some(archive) => move_dir(path, archive)?,
+                None => fs::remove_dir_all(path)
+                    .with_context(|| format!("无法删除 {}", path.display()))?,
+            },
+        }
+        Ok(())
+    }
+}
+
+/// 移动目录，跨文件系统时退化为复制后删除
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_dir(from, to).with_context(|| format!("无法归档到 {}", to.display()))?;
+    fs::remove_dir_all(from)?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// CAM 状态目录（`~/.config/code-agent-monitor` 或 `--state-dir`，以及平台配置目录下的备份等）
+fn state_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let candidates =
+        std::iter::once(crate::infra::paths::state_dir()).chain(crate::infra::paths::legacy_dirs());
+    for dir in candidates {
+        if dir.exists() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// 归档目标路径：`<archive>/code-agent-monitor-<时间戳>[-<序号>]`
+fn archive_target(archive: &Path, index: usize, timestamp: &str) -> PathBuf {
+    let name = if index == 0 {
+        format!("code-agent-monitor-{}", timestamp)
+    } else {
+        format!("code-agent-monitor-{}-{}", timestamp, index)
+    };
+    archive.join(name)
+}
+
+/// 收集需要执行的清理步骤
+fn build_plan(archive: Option<&Path>) -> Result<Vec<PurgeStep>> {
+    let mut steps = Vec::new();
+
+    // 先卸载服务，避免 launchd 重新拉起 watcher
+    if LaunchdService::new()
+        .and_then(|service| service.status())
+        .is_ok_and(|status| status.installed)
+    {
+        steps.push(PurgeStep::UninstallService);
+    }
+
+    let daemon = WatcherDaemon::new();
+    if daemon.is_running() {
+        steps.push(PurgeStep::StopWatcher {
+            pid: daemon.read_pid().ok().flatten(),
+        });
+    }
+
+    let agents = AgentManager::new().list_agents().unwrap_or_default();
+    for agent in &agents {
+        steps.push(PurgeStep::StopAgent {
+            agent_id: agent.agent_id.clone(),
+            session: agent.tmux_session.clone(),
+        });
+    }
+    // 没有记录在 agents.json 中的 cam-* session
+    let tmux = TmuxManager::new();
+    if tmux.is_available() {
+        for session in tmux.list_cam_sessions().unwrap_or_default() {
+            if !agents.iter().any(|a| a.tmux_session == session) {
+                steps.push(PurgeStep::KillSession { session });
+            }
+        }
+    }
+
+    for tool in HOOK_TOOLS {
+        let Some(path) = hook_config_path(tool)? else {
+            continue;
+        };
+        let Ok(existing) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(content) = remove_hook_config(tool, &existing)
+            .with_context(|| format!("无法解析 {}", path.display()))?
+        {
+            steps.push(PurgeStep::RemoveHooks {
+                tool,
+                path,
+                existing,
+                content,
+            });
+        }
+    }
+
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    for (index, path) in state_dirs().into_iter().enumerate() {
+        steps.push(PurgeStep::RemoveState {
+            path,
+            archive: archive.map(|dir| archive_target(dir, index, &timestamp)),
+        });
+    }
+    Ok(steps)
+}
+
+/// 处理 `cam purge`
+pub fn handle_purge(args: PurgeArgs) -> Result<()> {
+    let steps = build_plan(args.archive.as_deref())?;
+    if steps.is_empty() {
+        println!("没有需要清理的 CAM 内容");
+        return Ok(());
+    }
+
+    println!("将执行以下清理:");
+    for (index, step) in steps.iter().enumerate() {
+        println!("  {}. {}", index + 1, step.describe());
+        if args.dry_run {
+            if let PurgeStep::RemoveHooks {
+                existing, content, ..
+            } = step
+            {
+                for line in line_diff(existing, content).lines() {
+                    println!("     {}", line);
+                }
+            }
+        }
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    if !args.yes {
+        print!("\n继续？[y/N] ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("已取消");
+            return Ok(());
+        }
+    }
+
+    let mut failed = 0;
+    for step in &steps {
+        match step.execute() {
+            Ok(()) => println!("✓ {}", step.describe()),
+            Err(e) => {
+                failed += 1;
+                eprintln!("✗ {}: {}", step.describe(), e);
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} 项清理失败", failed);
+    }
+    println!("CAM 已清理完成");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_archive_target_is_unique_per_dir() {
+        let archive = Path::new("/tmp/cam-archive");
+        assert_eq!(
+            archive_target(archive, 0, "20260101-120000"),
+            archive.join("code-agent-monitor-20260101-120000")
+        );
+        assert_eq!(
+            archive_target(archive, 1, "20260101-120000"),
+            archive.join("code-agent-monitor-20260101-120000-1")
+        );
+    }
+
+    #[test]
+    fn test_move_dir_keeps_contents() {
+        let tmp = TempDir::new().unwrap();
+        let from = tmp.path().join("state");
+        fs::create_dir_all(from.join("logs")).unwrap();
+        fs::write(from.join("agents.json"), "{}").unwrap();
+        fs::write(from.join("logs/cam-1.log"), "line").unwrap();
+
+        let to = tmp.path().join("archive/code-agent-monitor-1");
+        move_dir(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("agents.json")).unwrap(), "{}");
+        assert_eq!(
+            fs::read_to_string(to.join("logs/cam-1.log")).unwrap(),
+            "line"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! `cam queue` 命令 - 查看和取消 agent 处理中时排队的指令
+//!
+//! 队列见 [`crate::agent::prompt_queue`]。
+
+use anyhow::Result;
+use chrono::Local;
+use clap::{Args, Subcommand};
+
+use crate::agent::prompt_queue::{PromptQueue, QueuedPrompt};
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::infra::text::truncate_width;
+
+#[derive(Args, Debug)]
+pub struct QueueArgs {
+    #[command(subcommand)]
+    pub action: Option<QueueAction>,
+    /// JSON 格式输出
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QueueAction {
+    /// 列出排队的指令（默认）
+    List {
+        /// 只显示该 agent 的指令
+        #[arg(long)]
+        agent: Option<String>,
+    },
+    /// 加入一条指令，agent 空闲后由 watcher 发送
+    Add {
+        /// 目标 agent
+        agent_id: String,
+        /// 指令内容
+        message: String,
+    },
+    /// 取消排队的指令
+    Cancel {
+        /// 队列 ID（如 q3）
+        #[arg(required_unless_present_any = ["agent", "all"], conflicts_with_all = ["agent", "all"])]
+        id: Option<String>,
+        /// 取消该 agent 的所有指令
+        #[arg(long, conflicts_with = "all")]
+        agent: Option<String>,
+        /// 清空队列
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+fn print_prompts(prompts: &[QueuedPrompt], json: bool, empty: &str) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(prompts)?);
+    } else if prompts.is_empty() {
+        println!("{}", empty);
+    } else {
+        for prompt in prompts {
+            println!(
+                "{:<5} {:<24} {}  {}",
+                prompt.id,
+                prompt.agent_id,
+                prompt.queued_at.with_timezone(&Local).format("%m-%d %H:%M"),
+                truncate_width(&prompt.message, 60)
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_queue(args: QueueArgs) -> Result<()> {
+    let queue = PromptQueue::new();
+    match args.action.unwrap_or(QueueAction::List { agent: None }) {
+        QueueAction::List { agent } => {
+            let prompts: Vec<_> = queue
+                .list()
+                .into_iter()
+                .filter(|p| agent.as_ref().is_none_or(|id| &p.agent_id == id))
+                .collect();
+            print_prompts(&prompts, args.json, "没有排队的指令")
+        }
+        QueueAction::Add { agent_id, message } => {
+            if AgentManager::new().get_agent(&agent_id)?.is_none() {
+                return Err(CliError::AgentNotFound(format!("未找到 agent: {}", agent_id)).into());
+            }
+            let prompt = queue.enqueue(&agent_id, &message)?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&prompt)?);
+            } else {
+                println!(
+                    "已加入队列 {}，{} 空闲后发送（需要 watcher 运行）",
+                    prompt.id, agent_id
+                );
+            }
+            Ok(())
+        }
+        QueueAction::Cancel { id, agent, all } => {
+            let cancelled = match id {
+                Some(id) => match queue.cancel(&id)? {
+                    Some(prompt) => vec![prompt],
+                    None => {
+                        return Err(
+                            CliError::InvalidArgument(format!("未找到排队的指令: {}", id)).into(),
+                        );
+                    }
+                },
+                None if all => queue.cancel_all(None)?,
+                None => queue.cancel_all(agent.as_deref())?,
+            };
+            if !args.json && !cancelled.is_empty() {
+                println!("已取消 {} 条指令：", cancelled.len());
+            }
+            print_prompts(&cancelled, args.json, "没有排队的指令")
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! `cam record` / `cam replay` 命令 - 录制 agent 会话并离线重放通知流程
+//!
+//! 用于排查漏发/误发的通知，以及在修改检测或提取逻辑后做回归对比。
+
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::agent::recording::{
+    load_bundle, recordings_dir, replay_events, RecordFrame, Recorder, ReplaySource,
+};
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::infra::input::InputWaitDetector;
+use crate::infra::multiplexer;
+use crate::infra::schema::default_data_dir;
+use crate::notification::{load_webhook_config_from_file, OpenclawNotifier, SendResult};
+
+#[derive(Args, Debug)]
+pub struct RecordArgs {
+    /// 要录制的 agent ID
+    pub agent_id: String,
+    /// 录制包输出路径（默认 ~/.config/code-agent-monitor/recordings/<agent_id>-<时间>.jsonl）
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// 录制时长（秒），默认直到 Ctrl-C 或 agent 退出
+    #[arg(long)]
+    pub duration: Option<u64>,
+    /// 终端快照间隔（秒）
+    #[arg(long, default_value = "1")]
+    pub interval: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// 录制包路径
+    pub bundle: PathBuf,
+    /// 只打印将要发送的内容，不实际发送
+    #[arg(long)]
+    pub dry_run: bool,
+    /// 禁用 AI 消息提取
+    #[arg(long)]
+    pub no_ai: bool,
+}
+
+/// 处理 `cam record`
+pub async fn handle_record(args: RecordArgs) -> Result<()> {
+    let data_dir = default_data_dir();
+    let agent = AgentManager::new()
+        .get_agent(&args.agent_id)?
+        .ok_or_else(|| CliError::AgentNotFound(format!("未找到 agent: {}", args.agent_id)))?;
+
+    let output = args.output.unwrap_or_else(|| {
+        recordings_dir(&data_dir).join(format!(
+            "{}-{}.jsonl",
+            agent.agent_id,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+    let mut recorder = Recorder::start(&data_dir, &agent, output)?;
+    eprintln!(
+        "开始录制 {} → {}（Ctrl-C 结束）",
+        agent.agent_id,
+        recorder.bundle_path().display()
+    );
+
+    let tmux = multiplexer::from_config();
+    let deadline = args
+        .duration
+        .map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs));
+    let interval = Duration::from_secs(args.interval.max(1));
+    let mut snapshots = 0;
+
+    let result = async {
+        loop {
+            if !tmux.session_exists(&agent.tmux_session) {
+                eprintln!("agent 已退出，录制结束");
+                break;
+            }
+            if let Ok(content) = tmux.capture_pane(&agent.tmux_session, 50) {
+                if recorder.record_snapshot(&content)? {
+                    snapshots += 1;
+                }
+            }
+            if deadline.is_some_and(|d| tokio::time::Instant::now() >= d) {
+                break;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+
+    // 无论是否出错都移除录制标记
+    let bundle = recorder.finish()?;
+    result?;
+
+    let hooks = load_bundle(&bundle)?
+        .iter()
+        .filter(|f| matches!(f, RecordFrame::Hook { .. }))
+        .count();
+    println!(
+        "录制完成: {}（{} 个快照，{} 个 hook 事件）",
+        bundle.display(),
+        snapshots,
+        hooks
+    );
+    Ok(())
+}
+
+/// 处理 `cam replay`
+pub fn handle_replay(args: ReplayArgs) -> Result<()> {
+    let frames = load_bundle(&args.bundle)?;
+    let detector = InputWaitDetector::new();
+    let events = replay_events(&frames, |content| detector.detect_immediate(content))?;
+
+    let clock = Arc::new(AtomicU64::new(0));
+    let notifier = match load_webhook_config_from_file() {
+        Some(config) => {
+            OpenclawNotifier::with_webhook(config).unwrap_or_else(|_| OpenclawNotifier::new())
+        }
+        None => OpenclawNotifier::new(),
+    }
+    .with_dry_run(args.dry_run)
+    .with_no_ai(args.no_ai)
+    .with_replay_clock(Arc::clone(&clock));
+
+    let (mut sent, mut skipped) = (0, 0);
+    for replayed in &events {
+        clock.store(replayed.at.timestamp() as u64, Ordering::SeqCst);
+        let source = match &replayed.source {
+            ReplaySource::Watcher => "watcher".to_string(),
+            ReplaySource::Hook { event } => format!("hook:{}", event),
+        };
+        let time = replayed.at.with_timezone(&chrono::Local).format("%H:%M:%S");
+
+        match notifier.send_notification_event(&replayed.event)? {
+            SendResult::Sent => {
+                sent += 1;
+                println!("[{}] {} → 发送", time, source);
+            }
+            SendResult::Skipped(reason) => {
+                skipped += 1;
+                println!("[{}] {} → 跳过（{}）", time, source, reason);
+            }
+            SendResult::Failed(error) => {
+                println!("[{}] {} → 失败（{}）", time, source, error);
+            }
+        }
+    }
+
+    println!(
+        "\n重放完成: {} 帧，{} 个事件，{} 个{}，{} 个跳过",
+        frames.len(),
+        events.len(),
+        sent,
+        if args.dry_run {
+            "将发送"
+        } else {
+            "已发送"
+        },
+        skipped
+    );
+    Ok(())
+}
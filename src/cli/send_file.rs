@@ -0,0 +1,78 @@
+//! `cam send-file` 命令 - 把聊天渠道中收到的图片、文件交给 agent
+//!
+//! 用户回复通知时附带的附件由 plugin 转交，`--reply-to` 按被回复的通知找到 agent，
+//! 见 [`crate::agent::inbox`]。
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::agent::inbox::receive_attachment;
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::notification::NotificationStore;
+
+#[derive(Args, Debug)]
+pub struct SendFileArgs {
+    /// 附件的下载地址（http/https）或本机路径
+    pub source: String,
+    /// 目标 agent_id
+    #[arg(
+        long,
+        required_unless_present = "reply_to",
+        conflicts_with = "reply_to"
+    )]
+    pub target: Option<String>,
+    /// 被回复的通知消息 ID，按通知线程找到 agent
+    #[arg(long)]
+    pub reply_to: Option<String>,
+    /// 渠道给出的文件名（默认取地址的最后一段）
+    #[arg(long)]
+    pub name: Option<String>,
+    /// 随附件发来的文字
+    #[arg(long, short)]
+    pub message: Option<String>,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 处理 `cam send-file`
+pub fn handle_send_file(args: SendFileArgs) -> Result<()> {
+    let agent_id = match (args.target, args.reply_to) {
+        (Some(target), _) => target,
+        (None, Some(message_id)) => {
+            NotificationStore::agent_for_message(&message_id).ok_or_else(|| {
+                CliError::InvalidArgument(format!("未找到消息 {} 对应的 agent 线程", message_id))
+            })?
+        }
+        (None, None) => {
+            return Err(CliError::InvalidArgument("需要 --target 或 --reply-to".to_string()).into())
+        }
+    };
+
+    let manager = AgentManager::new();
+    if manager.get_agent(&agent_id)?.is_none() {
+        return Err(CliError::AgentNotFound(format!("未找到 agent: {}", agent_id)).into());
+    }
+    let received = receive_attachment(
+        &manager,
+        &agent_id,
+        &args.source,
+        args.name.as_deref(),
+        args.message.as_deref(),
+    )
+    .map_err(|e| CliError::InvalidArgument(e.to_string()))?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&received)?);
+    } else if received.queue_id.is_some() {
+        println!(
+            "{} 正在处理，已保存附件，通知已排队: {}",
+            agent_id,
+            received.path.display()
+        );
+    } else {
+        println!("已保存附件并通知 {}: {}", agent_id, received.path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! `cam send-image` 命令 - 把截图等图片转发给 agent
+//!
+//! 图片复制到 agent 可读取的位置后按 adapter 的方式引用，见 [`crate::agent::image`]。
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::agent::image::send_image;
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+
+#[derive(Args, Debug)]
+pub struct SendImageArgs {
+    /// 目标 agent_id
+    pub agent_id: String,
+    /// 图片路径（png、jpg、gif、webp）
+    pub path: PathBuf,
+    /// 随图片一起输入的说明
+    #[arg(long, short)]
+    pub message: Option<String>,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 处理 `cam send-image`
+pub fn handle_send_image(args: SendImageArgs) -> Result<()> {
+    let manager = AgentManager::new();
+    if manager.get_agent(&args.agent_id)?.is_none() {
+        return Err(CliError::AgentNotFound(format!("未找到 agent: {}", args.agent_id)).into());
+    }
+    let sent = send_image(
+        &manager,
+        &args.agent_id,
+        &args.path,
+        args.message.as_deref(),
+    )
+    .map_err(|e| CliError::InvalidArgument(e.to_string()))?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&sent)?);
+    } else {
+        println!("已发送图片到 {}: {}", args.agent_id, sent.path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! `cam sessions` 命令 - 按项目、时间、状态列出会话，`prune` 归档旧会话
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::infra::text::format_bytes;
+use crate::session::archive::{default_archive_dir, prune_sessions};
+use crate::session::{PruneOptions, SessionFilter, SessionManager, SessionSort, SessionStatus};
+
+#[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub action: Option<SessionsAction>,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+    /// 按项目路径过滤（部分匹配）
+    #[arg(long)]
+    pub project: Option<String>,
+    /// 只显示最近修改的会话（如 30m、12h、2d、1w）
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<i64>,
+    /// 按状态过滤
+    #[arg(long, value_enum)]
+    pub status: Option<StatusArg>,
+    /// 排序方式
+    #[arg(long, value_enum, default_value = "recent")]
+    pub sort: SortArg,
+    /// 最多显示 N 个会话
+    #[arg(long, short)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsAction {
+    /// 压缩归档旧会话并从 ~/.claude/projects 移除
+    Prune(PruneArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// 归档超过此时间未修改的会话（如 60d、8w，最少 1d）
+    #[arg(long, value_parser = parse_since, default_value = "60d")]
+    pub older_than: i64,
+    /// 归档目录（默认 ~/.config/code-agent-monitor/session-archive）
+    #[arg(long)]
+    pub archive: Option<PathBuf>,
+    /// 只显示将要归档的会话
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusArg {
+    Active,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortArg {
+    Recent,
+    Oldest,
+    Messages,
+}
+
+/// 解析时间范围（`30m`、`12h`、`2d`、`1w`，纯数字按天），返回秒数
+pub fn parse_since(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("无效的时间范围: {}（示例: 30m、12h、2d）", value))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "" | "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(format!("无效的时间单位: {}（支持 s/m/h/d/w）", unit)),
+    };
+    Ok(number * unit_secs)
+}
+
+/// 处理 `cam sessions`
+pub fn handle_sessions(args: SessionsArgs) -> Result<()> {
+    if let Some(SessionsAction::Prune(prune)) = args.action {
+        return handle_prune(prune);
+    }
+
+    let filter = SessionFilter {
+        project_path: args.project,
+        since_secs: args.since,
+        status: args.status.map(|status| match status {
+            StatusArg::Active => SessionStatus::Active,
+            StatusArg::Completed => SessionStatus::Completed,
+        }),
+        sort: match args.sort {
+            SortArg::Recent => SessionSort::Recent,
+            SortArg::Oldest => SessionSort::Oldest,
+            SortArg::Messages => SessionSort::Messages,
+        },
+        limit: args.limit,
+        ..Default::default()
+    };
+    let sessions = SessionManager::new().list_sessions_filtered(Some(filter))?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&sessions)?);
+    } else {
+        println!("发现 {} 个会话:\n", sessions.len());
+        for session in sessions {
+            println!(
+                "  ID: {} | 项目: {} | 状态: {}",
+                session.id, session.project_path, session.status
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 处理 `cam sessions prune`
+fn handle_prune(args: PruneArgs) -> Result<()> {
+    let options = PruneOptions {
+        older_than_secs: args.older_than.max(0) as u64,
+        archive_dir: args.archive.unwrap_or_else(default_archive_dir),
+        dry_run: args.dry_run,
+    };
+    let report = prune_sessions(SessionManager::new().projects_dir(), &options)?;
+
+    if report.archived.is_empty() && report.failed.is_empty() {
+        println!("没有需要归档的会话");
+        return Ok(());
+    }
+    for session in &report.archived {
+        println!(
+            "  {} | {} | {}",
+            session.session_id,
+            session
+                .project_path
+                .as_deref()
+                .unwrap_or(&session.project_dir),
+            format_bytes(session.original_bytes)
+        );
+    }
+    for (path, error) in &report.failed {
+        eprintln!("  ❌ {}: {}", path.display(), error);
+    }
+    if options.dry_run {
+        println!(
+            "\n将归档 {} 个会话，可释放 {}（dry-run，未修改）",
+            report.archived.len(),
+            format_bytes(report.reclaimed_bytes())
+        );
+    } else {
+        println!(
+            "\n已归档 {} 个会话到 {}，释放 {}（归档占用 {}）",
+            report.archived.len(),
+            options.archive_dir.display(),
+            format_bytes(report.reclaimed_bytes()),
+            format_bytes(report.archived_bytes())
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("30m"), Ok(1800));
+        assert_eq!(parse_since("2d"), Ok(172800));
+        assert_eq!(parse_since("3"), Ok(259200));
+        assert_eq!(parse_since("1w"), Ok(604800));
+        assert!(parse_since("2y").is_err());
+        assert!(parse_since("d").is_err());
+    }
+}
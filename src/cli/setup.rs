@@ -5,11 +5,15 @@
 
 use crate::agent::adapter::{config_manager::BackupManager, get_adapter};
 use crate::agent::AgentType;
+use crate::cli::hook_binary::{
+    install_shim, recorded_hook_events, save_hook_binary, shim_path, HookBinaryRecord,
+};
 use anyhow::Result;
 use clap::Args;
+use serde::Serialize;
 use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 /// Setup 命令参数
@@ -25,6 +29,15 @@ pub struct SetupArgs {
     /// Show changes without applying
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Point hooks at a stable shim (~/.config/code-agent-monitor/bin/cam) instead of the cam binary
+    #[arg(long)]
+    pub shim: bool,
+
+    /// Hook events to install, comma-separated (e.g. permission,notification,stop; `all` for every event).
+    /// Defaults to the selection recorded by the previous setup
+    #[arg(long, value_delimiter = ',')]
+    pub events: Option<Vec<String>>,
 }
 
 /// 处理 setup 命令
@@ -48,15 +61,54 @@ pub fn handle_setup(args: SetupArgs) -> Result<()> {
         );
     }
 
-    // 生成新配置
-    let new_config = generate_hook_config(&args.tool)?;
+    // 生成新配置并计算合并结果
+    let binary = get_cam_binary_path();
+    let hook_binary = if args.shim {
+        let shim = shim_path();
+        println!("Hooks will call shim: {} → {}", shim.display(), binary);
+        shim.to_string_lossy().to_string()
+    } else {
+        binary.clone()
+    };
+    let events = match &args.events {
+        Some(names) => parse_hook_events(&args.tool, names)?,
+        None => recorded_hook_events(&args.tool),
+    };
+    if let Some(events) = &events {
+        println!("Hook events: {}", events.join(", "));
+    }
+    let new_config = generate_hook_config_for(&args.tool, &hook_binary, events.as_deref())?;
+    let existing = if config_path.exists() {
+        fs::read_to_string(&config_path)?
+    } else {
+        String::new()
+    };
+    let base = strip_stale_hooks(&args.tool, &existing, events.as_deref())?;
+    let rendered = render_hook_config(&args.tool, &base, &new_config)?
+        .or((base != existing).then_some(base))
+        .filter(|content| *content != existing);
 
     if args.dry_run {
-        println!("\n--- Changes to apply ---");
-        println!("{}", new_config);
+        match &rendered {
+            Some(content) => {
+                println!("\n--- Changes to apply ---");
+                print!("{}", line_diff(&existing, content));
+            }
+            None => println!("✓ CAM hooks already up to date"),
+        }
         return Ok(());
     }
 
+    let record = HookBinaryRecord::new(binary, args.shim).with_events(events);
+    let Some(content) = rendered else {
+        println!("✓ CAM hooks already up to date");
+        if args.shim {
+            install_shim(&record.binary)?;
+        }
+        save_hook_binary(&args.tool, &record)?;
+        return Ok(());
+    };
+
     // 确认
     if !args.yes {
         print!("\nApply changes? [y/N] ");
@@ -82,15 +134,41 @@ pub fn handle_setup(args: SetupArgs) -> Result<()> {
     }
 
     // 应用配置
-    apply_hook_config(&args.tool, &config_path, &new_config)?;
+    if args.shim {
+        let shim = install_shim(&record.binary)?;
+        println!("✓ Installed shim {}", shim.display());
+    }
+    fs::write(&config_path, content)?;
+    save_hook_binary(&args.tool, &record)?;
     println!("✓ Updated {}", config_path.display());
 
     Ok(())
 }
 
+/// 把工具的 hooks 改写为指向 `cam_path`（不交互、不打印），配置有变化时返回 true
+///
+/// 沿用上次 setup 记录的事件选择。
+pub fn rewrite_hooks(tool: &str, cam_path: &str) -> Result<bool> {
+    let config_path =
+        hook_config_path(tool)?.ok_or_else(|| anyhow::anyhow!("No config path for {}", tool))?;
+    let events = recorded_hook_events(tool);
+    let existing = fs::read_to_string(&config_path).unwrap_or_default();
+    let base = strip_stale_hooks(tool, &existing, events.as_deref())?;
+    let new_config = generate_hook_config_for(tool, cam_path, events.as_deref())?;
+    let content = render_hook_config(tool, &base, &new_config)?.unwrap_or(base);
+    if content == existing {
+        return Ok(false);
+    }
+    if config_path.exists() {
+        BackupManager::new().backup(tool, &config_path)?;
+    }
+    fs::write(&config_path, content)?;
+    Ok(true)
+}
+
 /// 解析 cam 二进制的绝对路径
 /// 优先级: plugin 位置 > current_exe > which > fallback
-fn get_cam_binary_path() -> String {
+pub fn get_cam_binary_path() -> String {
     // 1. Check plugin location (~/.claude/plugins/cam/bin/cam)
     if let Some(home) = dirs::home_dir() {
         let plugin_path = home.join(".claude/plugins/cam/bin/cam");
@@ -110,21 +188,263 @@ fn get_cam_binary_path() -> String {
     "cam".to_string()
 }
 
-/// 生成 hook 配置
-fn generate_hook_config(tool: &str) -> Result<String> {
-    let cam_path = get_cam_binary_path();
+/// CAM 为 Claude Code 配置的 hook 事件（事件名，`cam notify --event` 参数）
+const CLAUDE_HOOK_EVENTS: [(&str, &str); 8] = [
+    ("Notification", "notification"),
+    ("PermissionRequest", "permission_request"),
+    ("PostToolUse", "post_tool_use"),
+    ("PreToolUse", "pre_tool_use"),
+    ("SessionEnd", "session_end"),
+    ("SessionStart", "session_start"),
+    ("Stop", "stop"),
+    ("SubagentStop", "subagent_stop"),
+];
+
+/// Codex 只有 notify 一个回调（agent-turn-complete），对应 `stop` 事件
+const CODEX_HOOK_EVENTS: [(&str, &str); 1] = [("agent-turn-complete", "stop")];
+
+/// 工具支持的 hook 事件（事件名，`cam notify --event` 参数）
+fn hook_events_for(tool: &str) -> Result<&'static [(&'static str, &'static str)]> {
+    match tool {
+        "claude" => Ok(&CLAUDE_HOOK_EVENTS),
+        "codex" => Ok(&CODEX_HOOK_EVENTS),
+        "opencode" => Err(anyhow::anyhow!(
+            "OpenCode hook configuration is not yet supported. Please configure manually."
+        )),
+        _ => Err(anyhow::anyhow!("Unsupported tool: {}", tool)),
+    }
+}
+
+/// 解析 `--events`，返回选中事件的 `cam notify --event` 参数（按事件表顺序）
+///
+/// 接受事件参数（`session_start`）、事件名（`SessionStart`）和简写 `permission`；
+/// 包含 `all` 时返回 None，即安装全部事件。
+pub fn parse_hook_events(tool: &str, names: &[String]) -> Result<Option<Vec<String>>> {
+    let available = hook_events_for(tool)?;
+    let names: Vec<&str> = names
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if names.iter().any(|name| name.eq_ignore_ascii_case("all")) {
+        return Ok(None);
+    }
+    let mut selected = Vec::new();
+    for name in names {
+        let name = if name.eq_ignore_ascii_case("permission") {
+            "permission_request"
+        } else {
+            name
+        };
+        let Some((_, event_arg)) = available.iter().find(|(event_name, event_arg)| {
+            event_arg.eq_ignore_ascii_case(name) || event_name.eq_ignore_ascii_case(name)
+        }) else {
+            let choices: Vec<&str> = available.iter().map(|(_, event_arg)| *event_arg).collect();
+            return Err(anyhow::anyhow!(
+                "Unknown hook event '{}' for {} (available: {})",
+                name,
+                tool,
+                choices.join(", ")
+            ));
+        };
+        selected.push(*event_arg);
+    }
+    if selected.is_empty() {
+        return Err(anyhow::anyhow!("No hook events selected"));
+    }
+    Ok(Some(
+        available
+            .iter()
+            .filter(|(_, event_arg)| selected.contains(event_arg))
+            .map(|(_, event_arg)| event_arg.to_string())
+            .collect(),
+    ))
+}
+
+/// Claude 事件是否在选择中（None 表示全部事件）
+fn claude_event_selected(event_name: &str, events: Option<&[String]>) -> bool {
+    CLAUDE_HOOK_EVENTS
+        .iter()
+        .find(|(name, _)| *name == event_name)
+        .is_some_and(|(_, event_arg)| {
+            events.is_none_or(|events| events.iter().any(|e| e == event_arg))
+        })
+}
+
+/// CAM hooks 的安装状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum HookState {
+    /// 全部配置
+    Configured,
+    /// 部分事件已配置
+    Partial { configured: usize, expected: usize },
+    /// 未配置（或配置文件不存在）
+    Missing,
+}
+
+/// 检查工具配置文件中的 CAM hooks（claude、codex）
+pub fn hook_state(tool: &str) -> Result<HookState> {
+    let Some(config_path) = hook_config_path(tool)? else {
+        return Ok(HookState::Missing);
+    };
+    let content = fs::read_to_string(&config_path).unwrap_or_default();
+    match tool {
+        "claude" => Ok(claude_hook_state(
+            &content,
+            recorded_hook_events(tool).as_deref(),
+        )),
+        "codex" => Ok(
+            if has_toplevel_notify(&content) && content.contains("codex-notify") {
+                HookState::Configured
+            } else {
+                HookState::Missing
+            },
+        ),
+        _ => Err(anyhow::anyhow!("Unsupported tool: {}", tool)),
+    }
+}
+
+/// 写入前移除过期的 CAM hooks
+///
+/// - codex 已有顶层 notify 时不会覆盖，先移除旧的 CAM notify 行以便更新 cam 路径
+/// - claude 选择了部分事件时，移除未选中事件下的 CAM hook
+fn strip_stale_hooks(tool: &str, existing: &str, events: Option<&[String]>) -> Result<String> {
+    Ok(match (tool, events) {
+        ("codex", _) => remove_hook_config(tool, existing)?.unwrap_or_else(|| existing.to_string()),
+        ("claude", Some(_)) => {
+            remove_claude_hooks(existing, |event| !claude_event_selected(event, events))?
+                .unwrap_or_else(|| existing.to_string())
+        }
+        _ => existing.to_string(),
+    })
+}
+
+/// 工具的 hook 配置文件路径
+pub fn hook_config_path(tool: &str) -> Result<Option<PathBuf>> {
+    let agent_type = AgentType::from_str(tool)?;
+    Ok(get_adapter(&agent_type).paths().config)
+}
+
+/// 移除配置中的 CAM hooks，其他内容保持不变；没有 CAM hooks 时返回 None
+pub fn remove_hook_config(tool: &str, existing: &str) -> Result<Option<String>> {
+    match tool {
+        "codex" => {
+            let mut removed = false;
+            let kept: Vec<&str> = existing
+                .lines()
+                .filter(|line| {
+                    let trimmed = line.trim_start();
+                    let is_cam = trimmed.starts_with("notify") && trimmed.contains("codex-notify");
+                    removed |= is_cam;
+                    !is_cam
+                })
+                .collect();
+            if !removed {
+                return Ok(None);
+            }
+            let mut content = kept.join("\n");
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            Ok(Some(content))
+        }
+        "claude" => remove_claude_hooks(existing, |_| true),
+        _ => Err(anyhow::anyhow!("Unsupported tool: {}", tool)),
+    }
+}
+
+/// 移除 Claude settings 中 `remove_from` 选中事件下的 CAM hooks；没有 CAM hooks 被移除时返回 None
+fn remove_claude_hooks(
+    existing: &str,
+    remove_from: impl Fn(&str) -> bool,
+) -> Result<Option<String>> {
+    if existing.trim().is_empty() {
+        return Ok(None);
+    }
+    let mut json: serde_json::Value = serde_json::from_str(existing).map_err(|e| {
+        anyhow::anyhow!(
+            "Existing settings is not valid JSON ({}), refusing to edit",
+            e
+        )
+    })?;
+    let Some(hooks) = json.get_mut("hooks").and_then(|h| h.as_object_mut()) else {
+        return Ok(None);
+    };
+    let mut removed = false;
+    for (event, entries) in hooks.iter_mut() {
+        if !remove_from(event) {
+            continue;
+        }
+        let Some(entries) = entries.as_array_mut() else {
+            continue;
+        };
+        entries.retain_mut(|entry| {
+            let Some(inner) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) else {
+                return true;
+            };
+            let before = inner.len();
+            inner.retain(|hook| !hook["command"].as_str().is_some_and(is_cam_hook_command));
+            removed |= inner.len() < before;
+            // 只移除因删除 CAM hook 而变空的条目
+            !(inner.is_empty() && before > 0)
+        });
+    }
+    if !removed {
+        return Ok(None);
+    }
+    hooks.retain(|_, entries| entries.as_array().is_none_or(|e| !e.is_empty()));
+    if hooks.is_empty() {
+        if let Some(root) = json.as_object_mut() {
+            root.remove("hooks");
+        }
+    }
+    let mut content = serde_json::to_string_pretty(&json)?;
+    content.push('\n');
+    Ok(Some(content))
+}
+
+/// 统计 Claude settings.json 中指向 `cam notify` 的 hook 事件（只计选中的事件）
+fn claude_hook_state(content: &str, events: Option<&[String]>) -> HookState {
+    let json: serde_json::Value =
+        serde_json::from_str(content).unwrap_or_else(|_| serde_json::json!({}));
+    let expected: Vec<&str> = CLAUDE_HOOK_EVENTS
+        .iter()
+        .map(|(event_name, _)| *event_name)
+        .filter(|event_name| claude_event_selected(event_name, events))
+        .collect();
+    let configured = expected
+        .iter()
+        .filter(|event_name| {
+            json["hooks"][event_name]
+                .to_string()
+                .contains(" notify --event ")
+        })
+        .count();
+    match configured {
+        0 => HookState::Missing,
+        n if n == expected.len() => HookState::Configured,
+        n => HookState::Partial {
+            configured: n,
+            expected: expected.len(),
+        },
+    }
+}
+
+/// 生成指向指定 cam 路径的 hook 配置，`events` 为 None 时包含全部事件
+fn generate_hook_config_for(
+    tool: &str,
+    cam_path: &str,
+    events: Option<&[String]>,
+) -> Result<String> {
     match tool {
         "codex" => Ok(format!(r#"notify = ["{}", "codex-notify"]"#, cam_path)),
         "claude" => {
-            let events = [
-                ("Notification", "notification"),
-                ("PermissionRequest", "permission_request"),
-                ("SessionEnd", "session_end"),
-                ("SessionStart", "session_start"),
-                ("Stop", "stop"),
-            ];
             let mut hooks = serde_json::Map::new();
-            for (event_name, event_arg) in &events {
+            for (event_name, event_arg) in CLAUDE_HOOK_EVENTS {
+                if !claude_event_selected(event_name, events) {
+                    continue;
+                }
                 let command = format!(
                     "\"{}\" notify --event {} --agent-id ${{SESSION_ID:-unknown}}",
                     cam_path, event_arg
@@ -162,109 +482,219 @@ fn has_toplevel_notify(content: &str) -> bool {
     false
 }
 
-/// 应用 hook 配置
-fn apply_hook_config(tool: &str, config_path: &Path, new_config: &str) -> Result<()> {
+/// 计算写入 hook 后的配置内容，已是最新时返回 None
+fn render_hook_config(tool: &str, existing: &str, new_config: &str) -> Result<Option<String>> {
     match tool {
         "codex" => {
             // 写入 TOML（必须放在顶层，不能在任何 [section] 下面）
-            let mut content = if config_path.exists() {
-                fs::read_to_string(config_path)?
-            } else {
-                String::new()
-            };
+            let mut content = existing.to_string();
             if has_toplevel_notify(&content) {
                 println!("⚠️  notify already configured at top level, skipping");
-            } else {
-                // 移除嵌套在 section 内的错误 notify 行
-                if content.contains("notify = [") {
-                    println!("⚠️  Found notify nested inside a [section], moving to top level");
-                    let lines: Vec<&str> = content.lines().collect();
-                    let filtered: Vec<&str> = lines
-                        .into_iter()
-                        .filter(|line| !line.trim_start().starts_with("notify = ["))
-                        .collect();
-                    content = filtered.join("\n");
-                    if !content.ends_with('\n') {
-                        content.push('\n');
-                    }
-                }
-                // 插入到顶层（第一个 [section] 之前）
-                let insert_line = format!("{}\n", new_config);
-                if content.starts_with('[') {
-                    content.insert_str(0, &insert_line);
-                } else if let Some(pos) = content.find("\n[") {
-                    content.insert_str(pos + 1, &insert_line);
-                } else {
-                    if !content.is_empty() && !content.ends_with('\n') {
-                        content.push('\n');
-                    }
-                    content.push_str(new_config);
+                return Ok(None);
+            }
+            // 移除嵌套在 section 内的错误 notify 行
+            if content.contains("notify = [") {
+                println!("⚠️  Found notify nested inside a [section], moving to top level");
+                let lines: Vec<&str> = content.lines().collect();
+                let filtered: Vec<&str> = lines
+                    .into_iter()
+                    .filter(|line| !line.trim_start().starts_with("notify = ["))
+                    .collect();
+                content = filtered.join("\n");
+                if !content.ends_with('\n') {
                     content.push('\n');
                 }
             }
-            fs::write(config_path, content)?;
-        }
-        "claude" => {
-            // 合并 JSON
-            if config_path.exists() {
-                let existing = fs::read_to_string(config_path)?;
-                let merged = merge_claude_config(&existing, new_config)?;
-                fs::write(config_path, merged)?;
+            // 插入到顶层（第一个 [section] 之前）
+            let insert_line = format!("{}\n", new_config);
+            if content.starts_with('[') {
+                content.insert_str(0, &insert_line);
+            } else if let Some(pos) = content.find("\n[") {
+                content.insert_str(pos + 1, &insert_line);
             } else {
-                fs::write(config_path, new_config)?;
+                if !content.is_empty() && !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push_str(new_config);
+                content.push('\n');
             }
+            Ok(Some(content))
         }
-        "opencode" => {
-            return Err(anyhow::anyhow!(
-                "OpenCode hook configuration is not yet supported. Please configure manually."
-            ));
-        }
-        _ => {
-            return Err(anyhow::anyhow!("Unsupported tool: {}", tool));
+        "claude" => {
+            let merged = merge_claude_config(existing, new_config)?;
+            let unchanged = serde_json::from_str::<serde_json::Value>(existing).is_ok_and(|old| {
+                serde_json::from_str::<serde_json::Value>(&merged).ok() == Some(old)
+            });
+            Ok((!unchanged).then_some(merged))
         }
+        "opencode" => Err(anyhow::anyhow!(
+            "OpenCode hook configuration is not yet supported. Please configure manually."
+        )),
+        _ => Err(anyhow::anyhow!("Unsupported tool: {}", tool)),
     }
-    Ok(())
 }
 
-/// 合并 Claude 配置（保留现有配置，添加 CAM hooks）
+/// 是否是 CAM 生成的 hook 命令（`"<cam 路径>" notify --event <事件> --agent-id ${SESSION_ID...}`）
+///
+/// 只按命令形态识别，cam 路径可能随安装位置变化。
+fn is_cam_hook_command(command: &str) -> bool {
+    command.contains(" notify --event ") && command.contains(" --agent-id ${SESSION_ID")
+}
+
+/// 合并 Claude 配置
+///
+/// - 保留所有未知字段（及其顺序）和用户自己的 hook
+/// - 每个事件只保留一条 CAM 命令：已存在时原地更新（如 cam 路径变化），否则追加
+/// - 现有内容不是合法 JSON 对象、或 hooks 结构异常时报错，不覆盖
 fn merge_claude_config(existing: &str, new_config: &str) -> Result<String> {
-    let mut existing_json: serde_json::Value =
-        serde_json::from_str(existing).unwrap_or_else(|_| serde_json::json!({}));
+    let mut existing_json: serde_json::Value = if existing.trim().is_empty() {
+        serde_json::json!({})
+    } else {
+        serde_json::from_str(existing).map_err(|e| {
+            anyhow::anyhow!(
+                "Existing settings is not valid JSON ({}), refusing to overwrite",
+                e
+            )
+        })?
+    };
     let new_json: serde_json::Value = serde_json::from_str(new_config)?;
 
-    // 获取或创建 hooks 对象
     let hooks = existing_json
         .as_object_mut()
-        .ok_or_else(|| anyhow::anyhow!("Invalid existing config"))?
+        .ok_or_else(|| anyhow::anyhow!("Existing settings is not a JSON object"))?
         .entry("hooks")
-        .or_insert_with(|| serde_json::json!({}));
-
-    // 如果 hooks 不是对象（null、数组等），替换为空对象
-    if !hooks.is_object() {
-        println!("⚠️  Existing 'hooks' value is not an object, replacing");
-        *hooks = serde_json::json!({});
-    }
-
-    // 合并新的 hooks
-    if let (Some(hooks_obj), Some(new_hooks)) = (
-        hooks.as_object_mut(),
-        new_json.get("hooks").and_then(|h| h.as_object()),
-    ) {
-        for (key, value) in new_hooks {
-            if !hooks_obj.contains_key(key) {
-                hooks_obj.insert(key.clone(), value.clone());
-            } else {
-                println!("⚠️  Hook '{}' already configured, skipping", key);
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("Existing 'hooks' value is not an object"))?;
+
+    if let Some(new_hooks) = new_json.get("hooks").and_then(|h| h.as_object()) {
+        for (event, cam_entries) in new_hooks {
+            let Some(command) = cam_entries[0]["hooks"][0]["command"].as_str() else {
+                continue;
+            };
+            let entries = hooks
+                .entry(event.clone())
+                .or_insert_with(|| serde_json::json!([]))
+                .as_array_mut()
+                .ok_or_else(|| anyhow::anyhow!("Existing hook '{}' is not an array", event))?;
+            merge_cam_hook(entries, cam_entries, command);
+        }
+    }
+
+    let mut merged = serde_json::to_string_pretty(&existing_json)?;
+    merged.push('\n');
+    Ok(merged)
+}
+
+/// 在事件的 hook 列表中放入 CAM 命令：第一条 CAM 命令更新为最新，重复的移除，没有则追加
+fn merge_cam_hook(
+    entries: &mut Vec<serde_json::Value>,
+    cam_entries: &serde_json::Value,
+    command: &str,
+) {
+    let mut found = false;
+    for entry in entries.iter_mut() {
+        let Some(hooks) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) else {
+            continue;
+        };
+        hooks.retain_mut(|hook| {
+            let is_cam = hook["command"].as_str().is_some_and(is_cam_hook_command);
+            if !is_cam {
+                return true;
             }
+            if found {
+                return false;
+            }
+            found = true;
+            hook["command"] = serde_json::Value::from(command);
+            true
+        });
+    }
+    // 去重后变空的条目一并移除
+    entries.retain(|entry| {
+        entry["hooks"]
+            .as_array()
+            .is_none_or(|hooks| !hooks.is_empty())
+    });
+    if !found {
+        if let Some(cam_entries) = cam_entries.as_array() {
+            entries.extend(cam_entries.iter().cloned());
         }
     }
+}
 
-    Ok(serde_json::to_string_pretty(&existing_json)?)
+/// 逐行比较，输出 `-`/`+` 标记的差异（未变化的行只保留前后 2 行上下文）
+pub fn line_diff(old: &str, new: &str) -> String {
+    const CONTEXT: usize = 2;
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // 最长公共子序列
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut ops: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', old[i]));
+            i += 1;
+        } else {
+            ops.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let near_change = |k: usize| changed.iter().any(|&c| c.abs_diff(k) <= CONTEXT);
+    let mut out = String::new();
+    let mut skipped = false;
+    for (k, (mark, line)) in ops.iter().enumerate() {
+        if *mark == ' ' && !near_change(k) {
+            if !skipped {
+                out.push_str("  ...\n");
+                skipped = true;
+            }
+            continue;
+        }
+        skipped = false;
+        out.push_str(&format!("{} {}\n", mark, line));
+    }
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
+
+    fn generate_hook_config(tool: &str) -> Result<String> {
+        generate_hook_config_for(tool, &get_cam_binary_path(), None)
+    }
+
+    /// 写入 hook 配置（内容无变化时不写入）
+    fn apply_hook_config(tool: &str, config_path: &Path, new_config: &str) -> Result<()> {
+        let existing = if config_path.exists() {
+            fs::read_to_string(config_path)?
+        } else {
+            String::new()
+        };
+        if let Some(content) = render_hook_config(tool, &existing, new_config)? {
+            fs::write(config_path, content)?;
+        }
+        Ok(())
+    }
 
     #[test]
     fn test_generate_codex_config() {
@@ -292,11 +722,7 @@ mod tests {
             "Stop",
         ];
         for event in &expected_events {
-            assert!(
-                hooks.get(event).is_some(),
-                "missing event: {}",
-                event
-            );
+            assert!(hooks.get(event).is_some(), "missing event: {}", event);
         }
 
         // Each event has correct structure: array of objects with matcher and hooks array
@@ -309,11 +735,105 @@ mod tests {
             assert_eq!(hook_list.len(), 1);
             assert_eq!(hook_list[0]["type"], "command");
             let cmd = hook_list[0]["command"].as_str().unwrap();
-            assert!(cmd.contains("notify --event"), "command missing 'notify --event': {}", cmd);
-            assert!(cmd.contains("--agent-id ${SESSION_ID:-unknown}"), "command missing agent-id: {}", cmd);
+            assert!(
+                cmd.contains("notify --event"),
+                "command missing 'notify --event': {}",
+                cmd
+            );
+            assert!(
+                cmd.contains("--agent-id ${SESSION_ID:-unknown}"),
+                "command missing agent-id: {}",
+                cmd
+            );
         }
     }
 
+    #[test]
+    fn test_claude_hook_state() {
+        let config = generate_hook_config("claude").unwrap();
+        assert_eq!(claude_hook_state(&config, None), HookState::Configured);
+        assert_eq!(claude_hook_state("", None), HookState::Missing);
+
+        let mut json: serde_json::Value = serde_json::from_str(&config).unwrap();
+        json["hooks"].as_object_mut().unwrap().remove("Stop");
+        assert_eq!(
+            claude_hook_state(&json.to_string(), None),
+            HookState::Partial {
+                configured: 7,
+                expected: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_hook_events() {
+        let names = |list: &str| -> Vec<String> { list.split(',').map(String::from).collect() };
+        assert_eq!(
+            parse_hook_events("claude", &names("stop, permission,Notification")).unwrap(),
+            Some(vec![
+                "notification".to_string(),
+                "permission_request".to_string(),
+                "stop".to_string()
+            ])
+        );
+        assert_eq!(
+            parse_hook_events("claude", &names("stop,all")).unwrap(),
+            None
+        );
+        assert_eq!(
+            parse_hook_events("codex", &names("stop")).unwrap(),
+            Some(vec!["stop".to_string()])
+        );
+        let err = parse_hook_events("codex", &names("permission")).unwrap_err();
+        assert!(err.to_string().contains("available: stop"));
+        assert!(parse_hook_events("claude", &names("bogus")).is_err());
+        assert!(parse_hook_events("claude", &names(" ,")).is_err());
+    }
+
+    #[test]
+    fn test_selected_events_replace_deselected_hooks() {
+        let full = merge_claude_config(
+            REAL_WORLD_SETTINGS,
+            &generate_hook_config_for("claude", "/usr/local/bin/cam", None).unwrap(),
+        )
+        .unwrap();
+        let events = vec!["permission_request".to_string(), "stop".to_string()];
+        let new_config =
+            generate_hook_config_for("claude", "/usr/local/bin/cam", Some(&events)).unwrap();
+        let generated: serde_json::Value = serde_json::from_str(&new_config).unwrap();
+        let generated: Vec<&String> = generated["hooks"].as_object().unwrap().keys().collect();
+        assert_eq!(generated, ["PermissionRequest", "Stop"]);
+
+        let base = strip_stale_hooks("claude", &full, Some(&events)).unwrap();
+        let content = render_hook_config("claude", &base, &new_config)
+            .unwrap()
+            .unwrap_or(base);
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let hooks: Vec<&String> = json["hooks"].as_object().unwrap().keys().collect();
+        // 用户自己的 PostToolUse hook 保留，只移除 CAM 的
+        assert_eq!(hooks, ["PostToolUse", "Stop", "PermissionRequest"]);
+        assert!(cam_commands(&json, "PostToolUse").is_empty());
+        assert_eq!(cam_commands(&json, "Stop").len(), 1);
+        assert_eq!(
+            json["hooks"]["Stop"][0]["hooks"][0]["command"],
+            "afplay /System/Library/Sounds/Glass.aiff"
+        );
+        assert_eq!(
+            claude_hook_state(&content, Some(&events)),
+            HookState::Configured
+        );
+        assert_eq!(
+            claude_hook_state(&content, None),
+            HookState::Partial {
+                configured: 2,
+                expected: 8
+            }
+        );
+
+        // 未选择事件时不移除任何 hook
+        assert_eq!(strip_stale_hooks("claude", &full, None).unwrap(), full);
+    }
+
     #[test]
     fn test_generate_opencode_config() {
         let result = generate_hook_config("opencode");
@@ -344,8 +864,7 @@ mod tests {
 
     #[test]
     fn test_merge_claude_config_existing_hooks() {
-        let existing =
-            r#"{"hooks": {"PreToolUse": [{"matcher": ".*", "hooks": ["echo test"]}]}}"#;
+        let existing = r#"{"hooks": {"PreToolUse": [{"matcher": ".*", "hooks": ["echo test"]}]}}"#;
         let new_config = generate_hook_config("claude").unwrap();
         let merged = merge_claude_config(existing, &new_config).unwrap();
         let json: serde_json::Value = serde_json::from_str(&merged).unwrap();
@@ -369,6 +888,180 @@ mod tests {
         assert!(json["hooks"].get("Notification").is_some());
     }
 
+    /// 真实的 Claude Code settings.json：权限、环境变量、状态栏、插件，以及用户自己的 hook
+    /// 和一条旧路径的 CAM hook
+    const REAL_WORLD_SETTINGS: &str = r#"{
+  "permissions": {
+    "allow": ["Bash(npm run test:*)", "Read(~/.zshrc)"],
+    "deny": ["Bash(curl:*)"]
+  },
+  "env": { "CLAUDE_CODE_ENABLE_TELEMETRY": "0" },
+  "model": "opus",
+  "statusLine": { "type": "command", "command": "~/.claude/statusline.sh" },
+  "hooks": {
+    "PostToolUse": [
+      {
+        "matcher": "Edit|Write",
+        "hooks": [{ "type": "command", "command": "npx prettier --write \"$CLAUDE_FILE_PATHS\"" }]
+      }
+    ],
+    "Stop": [
+      {
+        "matcher": "",
+        "hooks": [
+          { "type": "command", "command": "afplay /System/Library/Sounds/Glass.aiff" },
+          { "type": "command", "command": "\"/usr/local/bin/cam\" notify --event stop --agent-id ${SESSION_ID:-unknown}" }
+        ]
+      }
+    ]
+  },
+  "enabledPlugins": { "cam@cam-marketplace": true }
+}
+"#;
+
+    fn cam_commands(json: &serde_json::Value, event: &str) -> Vec<String> {
+        json["hooks"][event]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|entry| entry["hooks"].as_array().unwrap().clone())
+            .filter_map(|hook| hook["command"].as_str().map(|s| s.to_string()))
+            .filter(|command| is_cam_hook_command(command))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_real_world_settings() {
+        let new_config = generate_hook_config("claude").unwrap();
+        let merged = merge_claude_config(REAL_WORLD_SETTINGS, &new_config).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&merged).unwrap();
+
+        // 未知字段原样保留，且顺序不变
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(
+            keys,
+            [
+                "permissions",
+                "env",
+                "model",
+                "statusLine",
+                "hooks",
+                "enabledPlugins"
+            ]
+        );
+        assert_eq!(json["permissions"]["deny"][0], "Bash(curl:*)");
+        assert_eq!(json["enabledPlugins"]["cam@cam-marketplace"], true);
+
+        // 用户自己的 hook 保留，CAM hook 追加到同一事件
+        let post_tool_use = json["hooks"]["PostToolUse"].as_array().unwrap();
+        assert_eq!(post_tool_use.len(), 2);
+        assert_eq!(post_tool_use[0]["matcher"], "Edit|Write");
+        assert_eq!(cam_commands(&json, "PostToolUse").len(), 1);
+
+        // 旧路径的 CAM hook 原地更新，不重复添加
+        let stop_hooks = json["hooks"]["Stop"][0]["hooks"].as_array().unwrap();
+        assert_eq!(json["hooks"]["Stop"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            stop_hooks[0]["command"],
+            "afplay /System/Library/Sounds/Glass.aiff"
+        );
+        let stop = cam_commands(&json, "Stop");
+        assert_eq!(stop.len(), 1);
+        assert!(!stop[0].contains("/usr/local/bin/cam"));
+    }
+
+    #[test]
+    fn test_merge_is_idempotent() {
+        let new_config = generate_hook_config("claude").unwrap();
+        let once = merge_claude_config(REAL_WORLD_SETTINGS, &new_config).unwrap();
+        let twice = merge_claude_config(&once, &new_config).unwrap();
+        assert_eq!(once, twice);
+        assert!(render_hook_config("claude", &once, &new_config)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_merge_removes_duplicate_cam_hooks() {
+        let cam = r#"{"type": "command", "command": "\"/opt/cam\" notify --event stop --agent-id ${SESSION_ID:-unknown}"}"#;
+        let existing = format!(
+            r#"{{"hooks": {{"Stop": [{{"matcher": "", "hooks": [{cam}]}}, {{"matcher": "", "hooks": [{cam}]}}]}}}}"#
+        );
+        let new_config = generate_hook_config("claude").unwrap();
+        let merged = merge_claude_config(&existing, &new_config).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(json["hooks"]["Stop"].as_array().unwrap().len(), 1);
+        assert_eq!(cam_commands(&json, "Stop").len(), 1);
+    }
+
+    #[test]
+    fn test_merge_refuses_invalid_settings() {
+        let new_config = generate_hook_config("claude").unwrap();
+        // 带注释的 JSON 不能被当作空配置覆盖
+        assert!(merge_claude_config("{ // comment\n}", &new_config).is_err());
+        assert!(merge_claude_config("[]", &new_config).is_err());
+        assert!(merge_claude_config(r#"{"hooks": []}"#, &new_config).is_err());
+        assert!(merge_claude_config(r#"{"hooks": {"Stop": {}}}"#, &new_config).is_err());
+        assert!(merge_claude_config("  ", &new_config).is_ok());
+    }
+
+    #[test]
+    fn test_remove_claude_hooks_keeps_user_config() {
+        let new_config = generate_hook_config("claude").unwrap();
+        let merged = merge_claude_config(REAL_WORLD_SETTINGS, &new_config).unwrap();
+        let removed = remove_hook_config("claude", &merged).unwrap().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&removed).unwrap();
+
+        assert_eq!(json["model"], "opus");
+        let hooks = json["hooks"].as_object().unwrap();
+        let events: Vec<&String> = hooks.keys().collect();
+        assert_eq!(events, ["PostToolUse", "Stop"]);
+        assert_eq!(
+            json["hooks"]["Stop"][0]["hooks"][0]["command"],
+            "afplay /System/Library/Sounds/Glass.aiff"
+        );
+        assert!(cam_commands(&json, "Stop").is_empty());
+        assert!(remove_hook_config("claude", &removed).unwrap().is_none());
+
+        // 只有 CAM hooks 时整个 hooks 字段被移除
+        let only_cam = remove_hook_config("claude", &new_config).unwrap().unwrap();
+        assert_eq!(only_cam, "{}\n");
+    }
+
+    #[test]
+    fn test_remove_codex_notify() {
+        let existing = "notify = [\"/usr/local/bin/cam\", \"codex-notify\"]\nmodel = \"gpt-5\"\n";
+        assert_eq!(
+            remove_hook_config("codex", existing).unwrap().as_deref(),
+            Some("model = \"gpt-5\"\n")
+        );
+        assert!(remove_hook_config("codex", "model = \"gpt-5\"\n")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_codex_notify_follows_binary_move() {
+        let existing = "notify = [\"/old/cam\", \"codex-notify\"]\nmodel = \"gpt-5\"\n";
+        let base = strip_stale_hooks("codex", existing, None).unwrap();
+        let new_config = generate_hook_config_for("codex", "/new/cam", None).unwrap();
+        let content = render_hook_config("codex", &base, &new_config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            content,
+            "model = \"gpt-5\"\nnotify = [\"/new/cam\", \"codex-notify\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nc\nd\nX\nf\ng\n";
+        assert_eq!(line_diff(old, new), "  ...\n  c\n  d\n- e\n+ X\n  f\n  g\n");
+        assert_eq!(line_diff("", "x\n"), "+ x\n");
+    }
+
     #[test]
     fn test_apply_codex_config_before_sections() {
         // notify 必须放在 [section] 之前，否则会被嵌套到 section 内部
@@ -506,12 +1199,18 @@ notify = ["cam", "codex-notify"]
     #[test]
     fn test_has_toplevel_notify() {
         // 顶层 notify
-        assert!(has_toplevel_notify("notify = [\"/path\"]\n[section]\nfoo=1"));
-        assert!(has_toplevel_notify("model = \"x\"\nnotify = [\"/path\"]\n[section]"));
+        assert!(has_toplevel_notify(
+            "notify = [\"/path\"]\n[section]\nfoo=1"
+        ));
+        assert!(has_toplevel_notify(
+            "model = \"x\"\nnotify = [\"/path\"]\n[section]"
+        ));
 
         // section 内的 notify 不算顶层
         assert!(!has_toplevel_notify("[section]\nnotify = [\"/path\"]"));
-        assert!(!has_toplevel_notify("model = \"x\"\n[section]\nnotify = [\"/path\"]"));
+        assert!(!has_toplevel_notify(
+            "model = \"x\"\n[section]\nnotify = [\"/path\"]"
+        ));
 
         // 空文件
         assert!(!has_toplevel_notify(""));
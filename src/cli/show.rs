@@ -0,0 +1,85 @@
+//! `cam show` 命令 - 在本机查看通知的完整终端快照
+//!
+//! 快照隐私模式（[`crate::infra::privacy`]）下通知只附带 ID，完整快照保存在本地通知记录中。
+
+use anyhow::Result;
+use chrono::Local;
+use clap::Args;
+
+use crate::cli::CliError;
+use crate::notification::store::{NotificationRecord, NotificationStore};
+
+#[derive(Args, Debug)]
+pub struct ShowArgs {
+    /// 通知 ID（通知中 `cam show <id>` 的 ID）
+    pub notification_id: String,
+    /// 输出 JSON 格式
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 通知标题和完整快照
+pub fn format_record(record: &NotificationRecord) -> String {
+    let mut lines = vec![format!(
+        "{} {} {} {}",
+        record.ts.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+        record.agent_id,
+        record.event,
+        record.summary
+    )];
+    if let Some(ref project) = record.project {
+        lines.push(format!("项目: {}", project));
+    }
+    lines.push(String::new());
+    lines.push(
+        record
+            .terminal_snapshot
+            .clone()
+            .unwrap_or_else(|| "（该通知没有终端快照）".to_string()),
+    );
+    lines.join("\n")
+}
+
+/// 处理 `cam show`
+pub fn handle_show(args: ShowArgs) -> Result<()> {
+    let record = NotificationStore::find(&args.notification_id).ok_or_else(|| {
+        CliError::InvalidArgument(format!(
+            "未找到通知: {}（只保留最近的通知记录）",
+            args.notification_id
+        ))
+    })?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    } else {
+        println!("{}", format_record(&record));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::urgency::Urgency;
+
+    #[test]
+    fn test_format_record() {
+        let mut record: NotificationRecord = serde_json::from_value(serde_json::json!({
+            "ts": "2026-03-01T12:00:00Z",
+            "id": "0a1b2c3d",
+            "agent_id": "cam-1",
+            "urgency": Urgency::High,
+            "event": "WaitingForInput",
+            "summary": "Waiting: Confirmation",
+            "project": "/workspace/app",
+            "terminal_snapshot": "$ cargo test\nContinue? [y/n]"
+        }))
+        .unwrap();
+        let text = format_record(&record);
+        assert!(text.contains("cam-1 WaitingForInput Waiting: Confirmation"));
+        assert!(text.contains("项目: /workspace/app"));
+        assert!(text.ends_with("$ cargo test\nContinue? [y/n]"));
+
+        record.terminal_snapshot = None;
+        assert!(format_record(&record).ends_with("（该通知没有终端快照）"));
+    }
+}
@@ -0,0 +1,101 @@
+//! `cam simulate` 命令 - 启动脚本化的模拟 agent
+//!
+//! 模拟 agent 注册为 mock 类型，可用 `cam reply`、watcher 和通知链路直接测试。
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::json;
+use std::io::{self, Write};
+
+use crate::agent::simulator::{
+    resolve_scenario_spec, run_scenario, simulate_command, Scenario, SimStep, BUILTIN_SCENARIOS,
+};
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::infra::multiplexer::attach_hint;
+use crate::infra::tmux_config::TmuxConfig;
+
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// 剧本：内置名称（choices/confirm/error/mixed）或 JSON 剧本文件路径
+    #[arg(long, short, default_value = "mixed")]
+    pub scenario: String,
+    /// 工作目录（默认当前目录）
+    #[arg(long, short = 'c')]
+    pub cwd: Option<String>,
+    /// 列出内置剧本
+    #[arg(long)]
+    pub list: bool,
+    /// 在当前终端运行剧本（tmux 中的模拟 agent 使用此模式）
+    #[arg(long, hide = true)]
+    pub foreground: bool,
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// 处理 `cam simulate`
+pub fn handle_simulate(args: SimulateArgs) -> Result<()> {
+    if args.list {
+        for name in BUILTIN_SCENARIOS {
+            if let Some(scenario) = Scenario::builtin(name) {
+                let prompts = scenario
+                    .steps
+                    .iter()
+                    .filter(|s| matches!(s, SimStep::Prompt { .. }))
+                    .count();
+                println!(
+                    "{:<8} {} 步，{} 个等待输入",
+                    name,
+                    scenario.steps.len(),
+                    prompts
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let scenario = Scenario::load(&args.scenario)
+        .map_err(|e| CliError::InvalidArgument(format!("{:#}", e)))?;
+
+    if args.foreground {
+        let stdin = io::stdin();
+        run_scenario(&scenario, stdin.lock(), io::stdout())?;
+        return Ok(());
+    }
+
+    let cwd = match args.cwd {
+        Some(cwd) => cwd,
+        None => std::env::current_dir()?.to_string_lossy().to_string(),
+    };
+    let spec = resolve_scenario_spec(&args.scenario)?;
+    let command = simulate_command(&std::env::current_exe()?, &spec);
+    let response = AgentManager::new().start_agent_with_command(cwd.clone(), &command)?;
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "agent_id": response.agent_id,
+                "tmux_session": response.tmux_session,
+                "agent_type": "mock",
+                "project_path": cwd,
+                "scenario": scenario.name,
+            }))?
+        );
+    } else {
+        println!(
+            "已启动模拟 agent: {}（剧本: {}）",
+            response.agent_id, scenario.name
+        );
+        println!("查看: {}", attach_hint(&response.tmux_session));
+        println!("回复: cam reply <回复> --agent {}", response.agent_id);
+        println!(
+            "停止: {} kill-session -t {}",
+            TmuxConfig::global().shell_prefix(),
+            response.tmux_session
+        );
+    }
+    io::stdout().flush()?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! `cam snapshot` 命令 - 维护终端快照语料
+//!
+//! 语料格式与 golden 测试见 [`crate::agent::extractor::corpus`]。
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::agent::extractor::corpus::{anonymize, CorpusEntry, LocalAnalyzer, SnapshotCorpus};
+use crate::agent::AgentManager;
+use crate::cli::CliError;
+use crate::infra::multiplexer;
+
+#[derive(Args, Debug)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub action: SnapshotAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotAction {
+    /// 截取 agent 当前屏幕，匿名化后加入语料并生成期望结果
+    Capture {
+        /// 要截取的 agent ID
+        agent_id: String,
+        /// 条目名称（默认 <agent 类型>-<时间>）
+        #[arg(long)]
+        name: Option<String>,
+        /// 条目说明（如 "plan mode 退出确认"）
+        #[arg(long)]
+        description: Option<String>,
+        /// 语料目录
+        #[arg(long, default_value = "examples/snapshots")]
+        dir: PathBuf,
+        /// 截取的行数
+        #[arg(long, default_value = "80")]
+        lines: u32,
+        /// 覆盖同名条目
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+pub fn handle_snapshot(args: SnapshotArgs) -> Result<()> {
+    match args.action {
+        SnapshotAction::Capture {
+            agent_id,
+            name,
+            description,
+            dir,
+            lines,
+            force,
+        } => {
+            let agent = AgentManager::new()
+                .get_agent(&agent_id)?
+                .ok_or_else(|| CliError::AgentNotFound(format!("未找到 agent: {}", agent_id)))?;
+            let content = multiplexer::from_config()
+                .capture_pane(&agent.tmux_session, lines)
+                .map_err(|e| CliError::Other(format!("截取终端失败: {}", e)))?;
+
+            let corpus = SnapshotCorpus::new(&dir);
+            let name = name.unwrap_or_else(|| {
+                format!(
+                    "{}-{}",
+                    agent.agent_type,
+                    chrono::Local::now().format("%Y%m%d-%H%M%S")
+                )
+            });
+            if corpus.contains(&name) && !force {
+                return Err(CliError::InvalidArgument(format!(
+                    "条目 {} 已存在（--force 覆盖）",
+                    name
+                ))
+                .into());
+            }
+
+            let snapshot = anonymize(&content, &agent.project_path);
+            let state_dir =
+                std::env::temp_dir().join(format!("cam-snapshot-{}", std::process::id()));
+            let analyzed = LocalAnalyzer::new(&state_dir).map(|a| a.analyze(&snapshot));
+            let _ = std::fs::remove_dir_all(&state_dir);
+            let entry = CorpusEntry {
+                name,
+                agent_type: agent.agent_type.to_string(),
+                description: description.unwrap_or_default(),
+                snapshot,
+                expected: analyzed?,
+            };
+            corpus.save(&entry)?;
+
+            println!("已添加 {}", corpus.snapshot_path(&entry.name).display());
+            println!("{}", serde_json::to_string_pretty(&entry.expected)?);
+            println!("\n提交前请检查快照是否还有敏感信息，并确认期望结果正确（可直接编辑 .json）");
+        }
+    }
+    Ok(())
+}
@@ -3,13 +3,21 @@
 //!
 //! 启动 Claude Code 或 Codex agent，并自动注册到 CAM 进行监控。
 
-use crate::agent::adapter::get_adapter;
-use crate::agent::{AgentManager, AgentType, StartAgentRequest};
-use crate::infra::tmux::TmuxManager;
+use crate::agent::preflight::check_duplicate_agents;
+use crate::agent::prompt_delivery;
+use crate::agent::{
+    AgentManager, AgentRecord, AgentType, PreflightIssue, PreflightReport, StartAgentRequest,
+};
+use crate::cli::{describe_prompt, read_prompt_file, CliError};
+use crate::infra::multiplexer;
+use crate::infra::ports::format_ports;
+use crate::notification::{load_webhook_config_from_file, NotificationEvent, OpenclawNotifier};
 use anyhow::{anyhow, Result};
 use clap::Args;
 use serde::Serialize;
-use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
 
 /// Start 命令参数
 #[derive(Args)]
@@ -30,10 +38,39 @@ pub struct StartArgs {
     #[arg(long, short, conflicts_with = "prompt")]
     pub resume: Option<String>,
 
+    /// 在该 Docker 镜像的容器中运行 agent（挂载工作目录，镜像中需已安装 agent CLI）
+    #[arg(long, value_name = "IMAGE")]
+    pub container: Option<String>,
+
     /// 输出 JSON 格式
     #[arg(long)]
     pub json: bool,
 
+    /// 跳过启动前检查（git 状态、agent CLI 安装与登录、磁盘空间等）
+    #[arg(long)]
+    pub skip_preflight: bool,
+
+    /// 项目中已有同类型 agent 在运行时仍然启动（默认拒绝，避免两个 agent 的修改冲突）
+    #[arg(long)]
+    pub force: bool,
+
+    /// 等待 agent 就绪（出现输入提示符），超时未就绪时通知并返回错误
+    #[arg(long)]
+    pub wait_ready: bool,
+
+    /// `--wait-ready` 的超时时间（秒）
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 60,
+        requires = "wait_ready"
+    )]
+    pub timeout: u64,
+
+    /// 从文件读取初始 prompt（多行内容以粘贴方式输入）
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["prompt", "resume"])]
+    pub prompt_file: Option<PathBuf>,
+
     /// 初始 prompt
     pub prompt: Option<String>,
 }
@@ -45,6 +82,17 @@ pub struct StartOutput {
     pub tmux_session: String,
     pub agent_type: String,
     pub project_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// 启动前检查的警告
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<PreflightIssue>,
+    /// 是否已就绪（仅 `--wait-ready`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ready: Option<bool>,
+    /// 初始 prompt 是否确认送达（仅指定了初始 prompt 时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_delivered: Option<bool>,
 }
 
 /// 处理 start 命令
@@ -76,31 +124,41 @@ pub fn handle_start(args: StartArgs) -> Result<()> {
                 .unwrap_or_else(|_| ".".to_string())
         });
 
-    // 验证工作目录存在
-    if !Path::new(&cwd).exists() {
-        return Err(anyhow!("工作目录不存在: {}", cwd));
-    }
-
-    // 2. 检查依赖
-    let tmux = TmuxManager::new();
-    if !tmux.is_available() {
-        return Err(anyhow!(
-            "tmux 未安装或不可用\n请先安装 tmux: brew install tmux"
-        ));
+    // 2. 启动前检查
+    let tmux = multiplexer::from_config();
+    let agent_manager = AgentManager::new();
+    let mut report = if args.skip_preflight {
+        PreflightReport::default()
+    } else {
+        PreflightReport::run(&cwd, &agent_type, args.container.as_deref(), tmux.as_ref())
+    };
+    // 同一项目已有同类型 agent 时不受 --skip-preflight 影响，只能用 --force 跳过
+    let agents = agent_manager.list_agents().unwrap_or_default();
+    report.issues.extend(check_duplicate_agents(
+        &cwd,
+        &agent_type,
+        &agents,
+        args.force,
+    ));
+    report.ensure_ok()?;
+    let warnings: Vec<PreflightIssue> = report.warnings().cloned().collect();
+    if !args.json {
+        for warning in &warnings {
+            eprintln!("{}", warning);
+        }
     }
 
-    let adapter = get_adapter(&agent_type);
-    if !adapter.is_installed() {
-        let install_hint = match agent_type {
-            AgentType::Claude => "npm install -g @anthropic-ai/claude-code",
-            AgentType::Codex => "npm install -g @openai/codex",
-            _ => "请参考官方文档安装",
-        };
-        return Err(anyhow!(
-            "{} 命令未找到\n请先安装: {}",
-            args.agent,
-            install_hint
-        ));
+    let initial_prompt = match args.prompt_file {
+        Some(ref path) => Some(read_prompt_file(path)?),
+        None => args.prompt,
+    };
+    if let Some(ref prompt) = initial_prompt {
+        if !args.json {
+            eprintln!(
+                "启动后等待 agent 就绪并输入初始 prompt（{}）...",
+                describe_prompt(prompt)
+            );
+        }
     }
 
     // 3. 构建启动请求
@@ -108,21 +166,44 @@ pub fn handle_start(args: StartArgs) -> Result<()> {
         project_path: cwd.clone(),
         agent_type: Some(agent_type.to_string()),
         resume_session: args.resume,
-        initial_prompt: args.prompt,
+        initial_prompt,
         agent_id: None,
         tmux_session: args.name,
+        container: args.container.clone(),
     };
 
     // 4. 启动 agent
-    let agent_manager = AgentManager::new();
     let response = agent_manager.start_agent(request)?;
 
-    // 5. 输出结果
+    // 5. 等待就绪
+    let ready = if args.wait_ready {
+        if !args.json {
+            eprintln!("等待 agent 就绪（最多 {} 秒）...", args.timeout);
+        }
+        let ready = agent_manager.wait_until_ready(
+            &response.tmux_session,
+            &agent_type,
+            Duration::from_secs(args.timeout),
+        );
+        agent_manager.mark_ready(&response.agent_id, ready)?;
+        if !ready {
+            notify_not_ready(&agent_manager, &response.agent_id, &cwd, args.timeout);
+        }
+        Some(ready)
+    } else {
+        None
+    };
+
+    // 6. 输出结果
     let output = StartOutput {
         agent_id: response.agent_id.clone(),
         tmux_session: response.tmux_session.clone(),
         agent_type: agent_type.to_string(),
         project_path: cwd,
+        container: args.container,
+        warnings,
+        ready,
+        prompt_delivered: response.prompt_delivered,
     };
 
     if args.json {
@@ -137,13 +218,87 @@ pub fn handle_start(args: StartArgs) -> Result<()> {
         println!("  agent_id: {}", output.agent_id);
         println!("  tmux_session: {}", output.tmux_session);
         println!("  工作目录: {}", output.project_path);
+        if let Some(ref image) = output.container {
+            println!("  容器镜像: {}", image);
+        }
+        match output.ready {
+            Some(true) => println!("  就绪: ✓"),
+            Some(false) => println!("  就绪: ✗ {} 秒内未出现输入提示符", args.timeout),
+            None => {}
+        }
+        if output.prompt_delivered == Some(false) {
+            println!(
+                "  初始 prompt: ✗ 输入 {} 次均未被接受",
+                prompt_delivery::MAX_ATTEMPTS
+            );
+        }
+        // 提示其他 agent 已占用的端口，避免开发服务器冲突
+        let agents = agent_manager.list_agents().unwrap_or_default();
+        let in_use = format_ports_in_use(&agents, &output.agent_id);
+        if !in_use.is_empty() {
+            println!("  已占用端口: {}", in_use);
+        }
         println!();
-        println!("查看输出: tmux attach -t {}", output.tmux_session);
+        println!("查看输出: {}", tmux.attach_hint(&output.tmux_session));
     }
 
+    if output.prompt_delivered == Some(false) {
+        return Err(CliError::Other(format!(
+            "初始 prompt 未送达 {}，请用 cam reply --target {} \"<prompt>\" 重新发送",
+            output.agent_id, output.agent_id
+        ))
+        .into());
+    }
+    if output.ready == Some(false) {
+        return Err(CliError::Other(format!(
+            "{} 在 {} 秒内未就绪，可能停在登录或信任确认页，请用 {} 查看",
+            output.agent_id,
+            args.timeout,
+            tmux.attach_hint(&output.tmux_session)
+        ))
+        .into());
+    }
     Ok(())
 }
 
+/// agent 未能就绪时发送通知（附带终端快照，便于看出是登录页还是报错）
+fn notify_not_ready(manager: &AgentManager, agent_id: &str, project_path: &str, timeout: u64) {
+    let Ok(Some(agent)) = manager.get_agent(agent_id) else {
+        return;
+    };
+    let mut event = NotificationEvent::error(
+        agent_id,
+        format!(
+            "agent 启动后 {} 秒内未就绪，可能停在登录或信任确认页",
+            timeout
+        ),
+    )
+    .with_project_path(project_path)
+    .with_dedup_key(format!("not-ready:{}", agent_id));
+    if let Ok(snapshot) = manager.tmux.capture_pane(&agent.tmux_session, 30) {
+        event = event.with_terminal_snapshot(snapshot);
+    }
+    let notifier = match load_webhook_config_from_file() {
+        Some(config) => {
+            OpenclawNotifier::with_webhook(config).unwrap_or_else(|_| OpenclawNotifier::new())
+        }
+        None => OpenclawNotifier::new(),
+    };
+    if let Err(e) = notifier.send_notification_event(&event) {
+        warn!(agent_id = %agent_id, error = %e, "Failed to send not-ready notification");
+    }
+}
+
+/// 其他 agent 监听的端口，如 `cam-1 :3000, cam-2 :5173 :8080`
+fn format_ports_in_use(agents: &[AgentRecord], except: &str) -> String {
+    agents
+        .iter()
+        .filter(|a| a.agent_id != except && !a.ports.is_empty())
+        .map(|a| format!("{} {}", a.agent_id, format_ports(&a.ports)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,7 +311,13 @@ mod tests {
             cwd: None,
             name: None,
             resume: None,
+            container: None,
             json: false,
+            skip_preflight: false,
+            force: false,
+            wait_ready: false,
+            timeout: 60,
+            prompt_file: None,
             prompt: None,
         };
         assert_eq!(args.agent, "claude-code");
@@ -195,6 +356,28 @@ mod tests {
         assert!(!expanded.starts_with("~"));
     }
 
+    #[test]
+    fn test_format_ports_in_use() {
+        let agents: Vec<AgentRecord> = serde_json::from_value(serde_json::json!([
+            {"agent_id": "cam-1", "agent_type": "claude", "project_path": "/work/a",
+             "tmux_session": "cam-1", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing", "ports": [3000]},
+            {"agent_id": "cam-2", "agent_type": "claude", "project_path": "/work/b",
+             "tmux_session": "cam-2", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing", "ports": [5173, 8080]},
+            {"agent_id": "cam-3", "agent_type": "codex", "project_path": "/work/c",
+             "tmux_session": "cam-3", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing"},
+        ]))
+        .unwrap();
+        assert_eq!(
+            format_ports_in_use(&agents, "cam-3"),
+            "cam-1 :3000, cam-2 :5173 :8080"
+        );
+        assert_eq!(format_ports_in_use(&agents, "cam-2"), "cam-1 :3000");
+        assert!(format_ports_in_use(&[], "cam-1").is_empty());
+    }
+
     #[test]
     fn test_start_output_serialization() {
         let output = StartOutput {
@@ -202,6 +385,10 @@ mod tests {
             tmux_session: "cam-123".to_string(),
             agent_type: "claude".to_string(),
             project_path: "/tmp".to_string(),
+            container: None,
+            warnings: Vec::new(),
+            ready: None,
+            prompt_delivered: None,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("cam-123"));
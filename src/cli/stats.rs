@@ -0,0 +1,241 @@
+//! `cam stats` 命令 - 通知投递延迟与 AI 提取阶梯统计
+//!
+//! 样本与 SLO 配置见 [`crate::notification::latency`]，提取阶梯见
+//! [`crate::agent::extractor::ladder`]。`--notifications` 显示各渠道的响应统计，
+//! 见 [`crate::notification::engagement`]。
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Args;
+use serde::Serialize;
+
+use crate::agent::extractor::{LadderStage, LadderStore, StageMetrics};
+use crate::notification::engagement::{
+    compute_engagement, ChannelEngagement, HourRange, ResponseLog,
+};
+use crate::notification::latency::{
+    format_latency, LatencyLog, LatencySample, LatencySlo, LatencyStats,
+};
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// 只统计最近 N 小时的投递
+    #[arg(long)]
+    pub hours: Option<u64>,
+
+    /// 按渠道统计通知的响应时间和未响应率
+    #[arg(long)]
+    pub notifications: bool,
+
+    /// 只统计在该本地时段送达的通知（如 22-8），需配合 --notifications
+    #[arg(long, value_name = "START-END", requires = "notifications")]
+    pub between: Option<HourRange>,
+
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct StatsReport {
+    overall: Option<LatencyStats>,
+    channels: BTreeMap<String, LatencyStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slo: Option<LatencySlo>,
+    slo_breached: bool,
+    /// 提取阶梯各级指标
+    extraction: BTreeMap<LadderStage, StageMetrics>,
+}
+
+fn build_report(samples: &[LatencySample], slo: Option<LatencySlo>) -> StatsReport {
+    let mut by_channel: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for sample in samples {
+        by_channel
+            .entry(sample.channel.clone())
+            .or_default()
+            .push(sample.latency_ms);
+    }
+    StatsReport {
+        overall: LatencyStats::compute(samples.iter().map(|s| s.latency_ms)),
+        channels: by_channel
+            .into_iter()
+            .filter_map(|(channel, latencies)| Some((channel, LatencyStats::compute(latencies)?)))
+            .collect(),
+        slo_breached: slo.as_ref().is_some_and(|slo| slo.breached(samples)),
+        slo,
+        extraction: BTreeMap::new(),
+    }
+}
+
+fn stats_row(label: &str, stats: &LatencyStats) -> String {
+    format!(
+        "{:<16} {:>6} {:>8} {:>8} {:>8} {:>8}",
+        label,
+        stats.count,
+        format_latency(stats.p50_ms),
+        format_latency(stats.p90_ms),
+        format_latency(stats.p99_ms),
+        format_latency(stats.max_ms)
+    )
+}
+
+pub fn handle_stats(args: StatsArgs) -> Result<()> {
+    let mut samples = LatencyLog::new().load();
+    if let Some(hours) = args.hours {
+        let since = Utc::now() - Duration::hours(hours as i64);
+        samples.retain(|s| s.ts >= since);
+    }
+    if args.notifications {
+        let engagement = compute_engagement(
+            &samples,
+            &ResponseLog::new().load(),
+            args.between,
+            Utc::now(),
+        );
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&engagement)?);
+        } else {
+            print_engagement(&engagement, args.between);
+        }
+        return Ok(());
+    }
+    let mut report = build_report(&samples, LatencySlo::load());
+    report.extraction = LadderStore::new().metrics();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    print_latency(&report);
+    if !report.extraction.is_empty() {
+        println!("\nAI 提取阶梯（本地识别 → AI，得分不足才升级）");
+        println!(
+            "{:<10} {:>6} {:>6} {:>6} {:>6} {:>6} {:>8}",
+            "阶段", "尝试", "采纳", "低分", "未命中", "限流", "平均耗时"
+        );
+        for (stage, metrics) in &report.extraction {
+            println!("{}", ladder_row(*stage, metrics));
+        }
+    }
+    Ok(())
+}
+
+fn ladder_row(stage: LadderStage, metrics: &StageMetrics) -> String {
+    format!(
+        "{:<10} {:>6} {:>6} {:>6} {:>6} {:>6} {:>8}",
+        stage.as_str(),
+        metrics.attempts,
+        metrics.accepted,
+        metrics.below_threshold,
+        metrics.misses,
+        metrics.rate_limited,
+        format_latency(metrics.avg_ms())
+    )
+}
+
+fn print_latency(report: &StatsReport) {
+    let Some(overall) = &report.overall else {
+        println!("暂无通知投递记录");
+        return;
+    };
+    println!("通知投递延迟（事件检测 → 渠道确认）");
+    println!(
+        "{:<16} {:>6} {:>8} {:>8} {:>8} {:>8}",
+        "渠道", "次数", "p50", "p90", "p99", "最大"
+    );
+    for (channel, stats) in &report.channels {
+        println!("{}", stats_row(channel, stats));
+    }
+    if report.channels.len() > 1 {
+        println!("{}", stats_row("全部", overall));
+    }
+    if let Some(slo) = &report.slo {
+        let status = if report.slo_breached {
+            format!("⚠️ 最近 {} 条全部超标", slo.window.max(1))
+        } else {
+            "✅ 正常".to_string()
+        };
+        println!("\nSLO: ≤ {}s — {}", slo.max_secs, status);
+    }
+}
+
+fn engagement_row(channel: &str, stats: &ChannelEngagement) -> String {
+    let percentile = |ms: Option<u64>| ms.map(format_latency).unwrap_or_else(|| "-".to_string());
+    format!(
+        "{:<16} {:>6} {:>6} {:>6} {:>6} {:>6} {:>8} {:>8} {:>8}",
+        channel,
+        stats.notified,
+        stats.replied,
+        stats.acked,
+        stats.unanswered,
+        stats.open,
+        stats
+            .unanswered_rate()
+            .map(|rate| format!("{:.0}%", rate * 100.0))
+            .unwrap_or_else(|| "-".to_string()),
+        percentile(stats.response_time.as_ref().map(|t| t.p50_ms)),
+        percentile(stats.response_time.as_ref().map(|t| t.p90_ms))
+    )
+}
+
+fn print_engagement(engagement: &BTreeMap<String, ChannelEngagement>, between: Option<HourRange>) {
+    if engagement.is_empty() {
+        println!("暂无需要回复的通知记录");
+        return;
+    }
+    match between {
+        Some(range) => println!(
+            "通知响应（送达 → 回复或 ack，{:02}:00-{:02}:00 送达的通知）",
+            range.start, range.end
+        ),
+        None => println!("通知响应（送达 → 回复或 ack）"),
+    }
+    println!(
+        "{:<16} {:>6} {:>6} {:>6} {:>6} {:>6} {:>8} {:>8} {:>8}",
+        "渠道", "通知", "回复", "ack", "未响应", "等待中", "未响应率", "p50", "p90"
+    );
+    for (channel, stats) in engagement {
+        println!("{}", engagement_row(channel, stats));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(channel: &str, latency_ms: u64) -> LatencySample {
+        LatencySample {
+            ts: Utc::now(),
+            agent_id: "cam-1".to_string(),
+            event: "WaitingForInput".to_string(),
+            channel: channel.to_string(),
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn test_build_report_per_channel() {
+        let samples = vec![
+            sample("telegram", 1000),
+            sample("openclaw", 3000),
+            sample("telegram", 15_000),
+        ];
+        let slo = LatencySlo {
+            max_secs: 10,
+            window: 1,
+        };
+        let report = build_report(&samples, Some(slo));
+        assert_eq!(report.overall.as_ref().unwrap().count, 3);
+        assert_eq!(report.channels.len(), 2);
+        assert_eq!(report.channels["telegram"].p50_ms, 1000);
+        assert_eq!(report.channels["telegram"].max_ms, 15_000);
+        assert!(report.slo_breached);
+
+        let empty = build_report(&[], None);
+        assert!(empty.overall.is_none());
+        assert!(!empty.slo_breached);
+    }
+}
@@ -0,0 +1,340 @@
+//! `cam status` 命令 - 一次检查整套链路是否就绪
+//!
+//! 汇总 watcher daemon、agent 状态分布、待处理确认、最近一条通知、通知渠道配置、
+//! AI 后端连通性、hooks 安装状态（含 cam 路径变化）与通知限流。
+
+use std::collections::BTreeMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::agent::{AgentManager, AgentRecord, AgentStatus, WatcherDaemon};
+use crate::ai::client::AnthropicConfig;
+use crate::cli::hook_binary::hook_binary_mismatches;
+use crate::cli::setup::{hook_state, HookState};
+use crate::notification::{load_webhook_config_from_file, NotificationStore, RateLimiter};
+use crate::session::ConversationStateManager;
+
+/// AI 后端连通性检查的超时
+const AI_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 检查 hooks 的工具
+const HOOK_TOOLS: [&str; 2] = ["claude", "codex"];
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    /// JSON 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// AI 后端状态
+#[derive(Debug, Clone, Serialize)]
+struct AiBackendStatus {
+    configured: bool,
+    model: Option<String>,
+    endpoint: Option<String>,
+    /// 能否建立 TCP 连接（未配置时为 None）
+    reachable: Option<bool>,
+    error: Option<String>,
+}
+
+/// 单个工具的 hooks 状态
+#[derive(Debug, Clone, Serialize)]
+struct ToolHookStatus {
+    tool: &'static str,
+    installed: bool,
+    hooks: HookState,
+}
+
+/// 按状态统计 agent（`running` 计入 `processing`）
+fn count_by_status(agents: &[AgentRecord]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for agent in agents {
+        let status = match agent.status {
+            AgentStatus::Running => AgentStatus::Processing,
+            ref other => other.clone(),
+        };
+        let name = serde_json::to_value(&status)
+            .ok()
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "unknown".to_string());
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// 从 API URL 中取出 `host:port`（缺省端口按协议推断）
+fn endpoint_address(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split('/').next()?.rsplit('@').next()?;
+    if authority.is_empty() {
+        return None;
+    }
+    let has_port = authority
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    if has_port {
+        return Some(authority.to_string());
+    }
+    let port = if scheme == "http" { 80 } else { 443 };
+    Some(format!("{}:{}", authority, port))
+}
+
+/// 检查 AI 后端：读取配置并尝试 TCP 连接（不发送 API 请求）
+fn check_ai_backend() -> AiBackendStatus {
+    let config = match AnthropicConfig::auto_load() {
+        Ok(config) => config,
+        Err(e) => {
+            return AiBackendStatus {
+                configured: false,
+                model: None,
+                endpoint: None,
+                reachable: None,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+    let address = endpoint_address(&config.base_url);
+    let connect = address.as_deref().map(|address| {
+        let addr = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::other("无法解析地址"))?;
+        TcpStream::connect_timeout(&addr, AI_CONNECT_TIMEOUT).map(|_| ())
+    });
+    AiBackendStatus {
+        configured: true,
+        model: Some(config.model),
+        endpoint: address,
+        reachable: connect.as_ref().map(|r| r.is_ok()),
+        error: match connect {
+            Some(Err(e)) => Some(e.to_string()),
+            None => Some(format!("无效的 base_url: {}", config.base_url)),
+            Some(Ok(())) => None,
+        },
+    }
+}
+
+fn hook_statuses() -> Vec<ToolHookStatus> {
+    HOOK_TOOLS
+        .iter()
+        .map(|tool| ToolHookStatus {
+            tool,
+            installed: which::which(tool).is_ok(),
+            hooks: hook_state(tool).unwrap_or(HookState::Missing),
+        })
+        .collect()
+}
+
+fn hook_label(state: &HookState) -> String {
+    match state {
+        HookState::Configured => "✅ 已配置".to_string(),
+        HookState::Partial {
+            configured,
+            expected,
+        } => format!("⚠️  部分配置 ({}/{})", configured, expected),
+        HookState::Missing => "❌ 未配置".to_string(),
+    }
+}
+
+/// 处理 `cam status`
+pub fn handle_status(args: StatusArgs) -> Result<()> {
+    let daemon = WatcherDaemon::new();
+    let watcher_running = daemon.is_running();
+    let watcher_pid = if watcher_running {
+        daemon.read_pid().ok().flatten()
+    } else {
+        None
+    };
+    let agents = AgentManager::new().list_agents()?;
+    let by_status = count_by_status(&agents);
+    let pending = ConversationStateManager::new()
+        .get_pending_confirmations()?
+        .len();
+    let last_notification = NotificationStore::read_recent(1).pop();
+    let webhook = load_webhook_config_from_file();
+    let openclaw_cli = which::which("openclaw").is_ok();
+    let ai = check_ai_backend();
+    let hooks = hook_statuses();
+    let hook_binaries = hook_binary_mismatches();
+    let throttle = RateLimiter::new().status();
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "watcher": {
+                    "running": watcher_running,
+                    "pid": watcher_pid,
+                },
+                "agents": agents.len(),
+                "agents_by_status": by_status,
+                "pending_confirmations": pending,
+                "last_notification": last_notification.as_ref().map(|n| json!({
+                    "ts": n.ts,
+                    "agent_id": n.agent_id,
+                    "event": n.event,
+                    "summary": n.summary,
+                })),
+                "channels": {
+                    "webhook": webhook.as_ref().map(|w| &w.gateway_url),
+                    "openclaw_cli": openclaw_cli,
+                },
+                "ai_backend": ai,
+                "hooks": hooks,
+                "hook_binary_mismatches": hook_binaries,
+                "throttle": throttle,
+            }))?
+        );
+        return Ok(());
+    }
+
+    match watcher_pid {
+        Some(pid) => println!("Watcher: 运行中 (PID {})", pid),
+        None if watcher_running => println!("Watcher: 运行中"),
+        None => println!("Watcher: 未运行"),
+    }
+    if by_status.is_empty() {
+        println!("Agents: 0");
+    } else {
+        let parts: Vec<String> = by_status
+            .iter()
+            .map(|(status, count)| format!("{} {}", status, count))
+            .collect();
+        println!("Agents: {}（{}）", agents.len(), parts.join(", "));
+    }
+    println!("待处理确认: {}", pending);
+    match &last_notification {
+        Some(n) => println!(
+            "最近通知: {} {} {} - {}",
+            n.ts.with_timezone(&chrono::Local).format("%m-%d %H:%M:%S"),
+            n.agent_id,
+            n.event,
+            crate::infra::truncate_width(&n.summary, 60)
+        ),
+        None => println!("最近通知: 无"),
+    }
+
+    println!("通知渠道:");
+    match &webhook {
+        Some(w) => println!("  webhook      ✅ {}", w.gateway_url),
+        None => println!("  webhook      ❌ 未配置（运行 cam bootstrap）"),
+    }
+    println!(
+        "  openclaw CLI {}",
+        if openclaw_cli {
+            "✅ 已安装"
+        } else {
+            "❌ 未找到"
+        }
+    );
+
+    match (&ai.reachable, &ai.endpoint) {
+        (Some(true), Some(endpoint)) => println!(
+            "AI 后端: ✅ {} ({})",
+            endpoint,
+            ai.model.as_deref().unwrap_or("-")
+        ),
+        (Some(false), Some(endpoint)) => println!(
+            "AI 后端: ❌ 无法连接 {}: {}",
+            endpoint,
+            ai.error.as_deref().unwrap_or("-")
+        ),
+        _ => println!("AI 后端: ❌ {}", ai.error.as_deref().unwrap_or("未配置")),
+    }
+
+    println!("Hooks:");
+    for status in &hooks {
+        let installed = if status.installed {
+            ""
+        } else {
+            "（未安装）"
+        };
+        println!(
+            "  {:<12} {}{}",
+            status.tool,
+            hook_label(&status.hooks),
+            installed
+        );
+    }
+    for mismatch in &hook_binaries {
+        println!("  ⚠️  {}", mismatch.message());
+    }
+
+    if !throttle.enabled {
+        println!("通知限流: 已关闭");
+        return Ok(());
+    }
+    if throttle.buckets.is_empty() {
+        println!("通知限流: 所有令牌桶已满");
+    } else {
+        println!("通知限流:");
+        for bucket in &throttle.buckets {
+            println!(
+                "  {:<8} {} {:.1}/{}",
+                bucket.scope,
+                crate::infra::pad_width(&bucket.key, 40),
+                bucket.tokens,
+                bucket.capacity
+            );
+        }
+    }
+    if throttle.digest.is_empty() {
+        println!("摘要队列: 空");
+    } else {
+        println!("摘要队列: {} 条", throttle.digest.len());
+        for entry in &throttle.digest {
+            println!("  ({}) {}", entry.channel, entry.line());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_address() {
+        assert_eq!(
+            endpoint_address("https://api.anthropic.com/v1/messages").as_deref(),
+            Some("api.anthropic.com:443")
+        );
+        assert_eq!(
+            endpoint_address("http://localhost:8080/v1").as_deref(),
+            Some("localhost:8080")
+        );
+        assert_eq!(
+            endpoint_address("http://proxy.local").as_deref(),
+            Some("proxy.local:80")
+        );
+        assert!(endpoint_address("api.anthropic.com").is_none());
+    }
+
+    #[test]
+    fn test_count_by_status_merges_running() {
+        let record = |status: AgentStatus| -> AgentRecord {
+            serde_json::from_value(json!({
+                "agent_id": "cam-1",
+                "agent_type": "claude",
+                "project_path": "/tmp",
+                "tmux_session": "cam-1",
+                "started_at": "2026-01-01T00:00:00Z",
+                "status": status,
+            }))
+            .unwrap()
+        };
+        let counts = count_by_status(&[
+            record(AgentStatus::Running),
+            record(AgentStatus::Processing),
+            record(AgentStatus::WaitingForInput),
+        ]);
+        assert_eq!(counts.get("processing"), Some(&2));
+        assert_eq!(counts.get("waiting_for_input"), Some(&1));
+    }
+}
@@ -7,6 +7,7 @@ use tracing::warn;
 use crate::agent::extractor::prompts::{blocking_context_prompt, progress_summary_prompt};
 use crate::agent::{AgentManager, AgentStatus};
 use crate::ai::client::AnthropicClient;
+use crate::ai::profile::AiTask;
 use crate::notification::store::NotificationStore;
 use crate::notification::webhook::{load_webhook_config_from_file, WebhookClient};
 
@@ -150,7 +151,7 @@ pub fn generate_summary() -> Result<Option<String>> {
     }
 
     // 创建 Haiku 客户端（可选，失败时回退到默认文本）
-    let haiku = AnthropicClient::from_config().ok();
+    let haiku = AnthropicClient::for_task(AiTask::Summary).ok();
 
     let mut blocking: Vec<AgentSummaryItem> = Vec::new();
     let mut running: Vec<AgentSummaryItem> = Vec::new();
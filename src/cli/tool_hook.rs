@@ -0,0 +1,313 @@
+//! `cam notify` 的工具 hook 处理 - PreToolUse / PostToolUse / SubagentStop
+//!
+//! 这些 hook 每次工具调用都会触发，只记录时间线和执行风险策略，
+//! 不走常规的终端快照与 AI 提取流程。PreToolUse 的判定以权限决定 JSON 写到 stdout，
+//! 策略要求远程确认时在 hook 进程内等待 `cam reply`；高风险 Bash 配置了沙箱时，
+//! 回复 `s` 会在 hook 进程内沙箱执行命令并把结果作为拒绝原因反馈给 agent。
+//! 多个 agent 同时请求相同的工具调用时合并为一条通知（见 `notification::permission_batch`）。
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::agent::adapter::{get_adapter, HookEvent};
+use crate::agent::tool_timeline::ToolTimeline;
+use crate::agent::AgentType;
+use crate::infra::sandbox::SandboxProfile;
+use crate::notification::permission_batch::{batch_key, BatchJoin, BatchMember, PermissionBatcher};
+use crate::notification::tool_policy::{
+    is_sandbox_reply, permission_output, reply_output, PolicyDecision, ToolPolicy,
+};
+use crate::notification::{
+    NotificationEvent, NotificationEventType, OpenclawNotifier, TranscriptContextConfig,
+};
+use crate::session::{ConfirmationType, ConversationStateManager};
+
+/// 等待远程回复时的轮询间隔
+const ASK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 把 Claude Code hook 名（PreToolUse）和 CLI 事件名（pre_tool_use）统一为 CLI 事件名
+pub fn normalize_hook_event_name(event: &str) -> &str {
+    match event {
+        "PreToolUse" => "pre_tool_use",
+        "PostToolUse" => "post_tool_use",
+        "SubagentStop" => "subagent_stop",
+        "PermissionRequest" => "permission_request",
+        "SessionStart" => "session_start",
+        "SessionEnd" => "session_end",
+        "Stop" => "stop",
+        "Notification" => "notification",
+        other => other,
+    }
+}
+
+/// 是否是工具 hook 事件
+pub fn is_tool_hook_event(event: &str) -> bool {
+    matches!(event, "pre_tool_use" | "post_tool_use" | "subagent_stop")
+}
+
+fn hook_name(event: &str) -> &'static str {
+    match event {
+        "pre_tool_use" => "PreToolUse",
+        "post_tool_use" => "PostToolUse",
+        _ => "SubagentStop",
+    }
+}
+
+/// 处理工具 hook，返回需要写到 stdout 的 hook 输出（PreToolUse 拦截决定）
+pub fn handle_tool_hook(
+    event: &str,
+    agent_id: &str,
+    payload: Option<&Value>,
+    notifier: &OpenclawNotifier,
+) -> Result<Option<Value>> {
+    // 原生输入自带 hook_event_name；兼容只传 --event 的调用
+    let mut payload = payload.cloned().unwrap_or_else(|| serde_json::json!({}));
+    if payload.get("hook_event_name").is_none() {
+        payload["hook_event_name"] = Value::String(hook_name(event).to_string());
+    }
+    let Some(hook_event) = get_adapter(&AgentType::Claude).parse_hook_event(&payload.to_string())
+    else {
+        return Ok(None);
+    };
+    let tool_use_id = payload.get("tool_use_id").and_then(|v| v.as_str());
+    let timeline = ToolTimeline::new();
+    let now = Utc::now();
+
+    match hook_event {
+        HookEvent::ToolStarting {
+            tool, input, cwd, ..
+        } => {
+            timeline.record_started(agent_id, &tool, tool_use_id, &input, now)?;
+
+            let policy = ToolPolicy::load();
+            let decision = policy.evaluate_in(&tool, &input, Some(cwd.as_str()));
+            if let PolicyDecision::Ask { risk, timeout_secs } = decision {
+                let sandbox = policy.sandbox_for(&tool, risk);
+                return ask_remote(
+                    agent_id,
+                    &tool,
+                    &input,
+                    &cwd,
+                    timeout_secs,
+                    sandbox,
+                    notifier,
+                );
+            }
+            let (notification_type, message) = match &decision {
+                PolicyDecision::Allow
+                | PolicyDecision::Approve { .. }
+                | PolicyDecision::Ask { .. } => return Ok(decision.hook_output()),
+                PolicyDecision::Notify { risk } => (
+                    "risky_tool",
+                    format!(
+                        "即将执行 {}（{}）",
+                        describe(&tool, &input),
+                        risk.description()
+                    ),
+                ),
+                PolicyDecision::Block { reason, .. } => ("tool_blocked", reason.clone()),
+            };
+            let mut notification = NotificationEvent::new(
+                agent_id,
+                NotificationEventType::Notification {
+                    notification_type: notification_type.to_string(),
+                    message,
+                },
+            )
+            .with_skip_dedup(true);
+            if !cwd.is_empty() {
+                notification = notification.with_project_path(cwd);
+            }
+            if let Err(e) = notifier.send_notification_event(&notification) {
+                tracing::error!(agent_id = %agent_id, error = %e, "Tool policy notification failed");
+            }
+            Ok(decision.hook_output())
+        }
+        HookEvent::ToolExecuted { tool, success, .. } => {
+            let duration_ms =
+                timeline.record_finished(agent_id, &tool, tool_use_id, success, now)?;
+            tracing::debug!(agent_id = %agent_id, tool = %tool, success, ?duration_ms, "Tool finished");
+            Ok(None)
+        }
+        HookEvent::SubagentStop { .. } => {
+            timeline.record_subagent_stop(agent_id, now)?;
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 发送权限请求并等待 `cam reply`，超时后不输出决定（交回 Claude Code 自身的权限确认）
+fn ask_remote(
+    agent_id: &str,
+    tool: &str,
+    input: &Value,
+    cwd: &str,
+    timeout_secs: u64,
+    sandbox: Option<&SandboxProfile>,
+    notifier: &OpenclawNotifier,
+) -> Result<Option<Value>> {
+    // 合并等待时间也计入超时，保证 hook 总耗时不超过 ask_timeout_secs
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    let state = ConversationStateManager::new();
+    let confirmation_id = state.register_pending(
+        agent_id,
+        None,
+        ConfirmationType::HookDecision {
+            tool: tool.to_string(),
+            input: input.clone(),
+        },
+        &describe(tool, input),
+        None,
+    )?;
+    let recent_messages = TranscriptContextConfig::load().recent_for_agent(agent_id);
+    if !recent_messages.is_empty() {
+        state.set_pending_recent_messages(&confirmation_id, recent_messages)?;
+    }
+
+    let notification = match join_batch(agent_id, tool, input, cwd, &confirmation_id) {
+        BatchOutcome::Joined => None,
+        BatchOutcome::Combined(message) => Some(NotificationEvent::notification(
+            agent_id,
+            "permission_batch",
+            message,
+        )),
+        BatchOutcome::Single => Some(
+            NotificationEvent::new(
+                agent_id,
+                NotificationEventType::PermissionRequest {
+                    tool_name: tool.to_string(),
+                    tool_input: input.clone(),
+                },
+            )
+            .with_sandbox_option(sandbox.is_some()),
+        ),
+    };
+    if let Some(notification) = notification {
+        let mut notification = notification.with_skip_dedup(true);
+        if !cwd.is_empty() {
+            notification = notification.with_project_path(cwd);
+        }
+        if let Err(e) = notifier.send_notification_event(&notification) {
+            tracing::error!(agent_id = %agent_id, error = %e, "Tool policy notification failed");
+        }
+    }
+
+    let decide = |reply: String| decide_reply(&reply, input, cwd, sandbox);
+    while Instant::now() < deadline {
+        if let Some(reply) = state.take_hook_decision(&confirmation_id)? {
+            return Ok(Some(decide(reply)));
+        }
+        std::thread::sleep(ASK_POLL_INTERVAL);
+    }
+
+    tracing::info!(agent_id = %agent_id, tool = %tool, timeout_secs, "No remote reply, falling back");
+    state.remove_pending(&confirmation_id)?;
+    // 超时与移除之间可能刚好收到回复
+    Ok(state.take_hook_decision(&confirmation_id)?.map(decide))
+}
+
+/// 权限请求的通知方式
+enum BatchOutcome {
+    /// 没有其他 agent 加入（或未开启合并），发送普通权限请求
+    Single,
+    /// 由本进程发送列出所有 agent 的合并通知
+    Combined(String),
+    /// 已加入其他 agent 的批次，由对方发送通知
+    Joined,
+}
+
+/// 加入相同请求的批次；新建批次时等待窗口结束再决定通知方式
+fn join_batch(
+    agent_id: &str,
+    tool: &str,
+    input: &Value,
+    cwd: &str,
+    confirmation_id: &str,
+) -> BatchOutcome {
+    let batcher = PermissionBatcher::new();
+    if !batcher.enabled() {
+        return BatchOutcome::Single;
+    }
+    let member = BatchMember {
+        agent_id: agent_id.to_string(),
+        confirmation_id: confirmation_id.to_string(),
+        project: (!cwd.is_empty()).then(|| cwd.to_string()),
+    };
+    let batch_id = match batcher.join(&batch_key(tool, input), &describe(tool, input), member) {
+        Ok(BatchJoin::Leader(batch_id)) => batch_id,
+        Ok(BatchJoin::Follower(batch_id)) => {
+            tracing::info!(agent_id = %agent_id, batch_id = %batch_id, "Joined permission batch");
+            return BatchOutcome::Joined;
+        }
+        Err(e) => {
+            tracing::warn!(agent_id = %agent_id, error = %e, "Permission batch unavailable");
+            return BatchOutcome::Single;
+        }
+    };
+    std::thread::sleep(batcher.window());
+    match batcher.close(&batch_id) {
+        Ok(Some(batch)) if batch.members.len() > 1 => {
+            tracing::info!(batch_id = %batch_id, agents = batch.members.len(), "Sending combined permission request");
+            BatchOutcome::Combined(batch.message())
+        }
+        Ok(_) => BatchOutcome::Single,
+        Err(e) => {
+            tracing::warn!(agent_id = %agent_id, error = %e, "Failed to close permission batch");
+            BatchOutcome::Single
+        }
+    }
+}
+
+/// 把远程回复转换为权限决定；选择沙箱时在此执行命令，结果作为拒绝原因反馈
+fn decide_reply(reply: &str, input: &Value, cwd: &str, sandbox: Option<&SandboxProfile>) -> Value {
+    let Some(profile) = sandbox.filter(|_| is_sandbox_reply(reply)) else {
+        return reply_output(reply);
+    };
+    let command = input.get("command").and_then(|v| v.as_str()).unwrap_or("");
+    let cwd = if cwd.is_empty() {
+        std::env::current_dir()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| ".".to_string())
+    } else {
+        cwd.to_string()
+    };
+    tracing::info!(command = %command, sandbox = %profile.describe(), "Running command in sandbox");
+    match profile.run(command, &cwd) {
+        Ok(outcome) => permission_output("deny", &outcome.feedback(profile, command)),
+        Err(e) => permission_output(
+            "deny",
+            &format!(
+                "用户选择在沙箱中执行，但沙箱执行失败（{}），原调用未执行",
+                e
+            ),
+        ),
+    }
+}
+
+fn describe(tool: &str, input: &Value) -> String {
+    match crate::infra::jsonl::extract_tool_target_from_input(tool, input) {
+        Some(target) => format!("{} {}", tool, target),
+        None => tool.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_hook_event_name() {
+        assert_eq!(normalize_hook_event_name("PreToolUse"), "pre_tool_use");
+        assert_eq!(normalize_hook_event_name("post_tool_use"), "post_tool_use");
+        assert_eq!(
+            normalize_hook_event_name("WaitingForInput"),
+            "WaitingForInput"
+        );
+        assert!(is_tool_hook_event("subagent_stop"));
+        assert!(!is_tool_hook_event("stop"));
+    }
+}
@@ -0,0 +1,222 @@
+//! 嵌入模型后端：字面相似度回退（字符三元组哈希）、Ollama、OpenAI 兼容接口
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+
+use super::Embedder;
+use crate::notification::dedup_key::hash_content;
+
+/// 字面回退的向量维度
+pub const LEXICAL_DIMS: usize = 256;
+
+/// 字面回退的标识
+pub const LEXICAL_MODEL: &str = "char-trigram-256";
+
+/// Ollama 下载模型的超时
+const PULL_TIMEOUT_SECS: u64 = 600;
+
+/// 字符三元组哈希向量（L2 归一化）
+///
+/// 不理解语义，只能判断两段文本在字面上是否接近；哈希使用 FNV 以保证跨进程一致。
+pub fn hash_embed(text: &str) -> Vec<f32> {
+    let chars: Vec<char> = text
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    let mut vector = vec![0.0f32; LEXICAL_DIMS];
+    for gram in chars.windows(3.min(chars.len()).max(1)) {
+        let gram: String = gram.iter().collect();
+        vector[(hash_content(&gram) % LEXICAL_DIMS as u64) as usize] += 1.0;
+    }
+    normalize(vector)
+}
+
+/// L2 归一化，之后余弦相似度即点积
+pub fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// 两个归一化向量的余弦相似度，维度不同时为 0
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// 字面相似度回退：字符三元组哈希，不是语义嵌入模型
+///
+/// 离线可用、无需下载，只能识别措辞相近的文本；需要语义匹配时配置 `ollama` 或 `openai`。
+#[derive(Debug, Clone, Default)]
+pub struct LexicalEmbedder;
+
+impl Embedder for LexicalEmbedder {
+    fn model_id(&self) -> String {
+        format!("lexical:{}", LEXICAL_MODEL)
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| hash_embed(text)).collect())
+    }
+
+    fn cacheable(&self) -> bool {
+        false
+    }
+}
+
+fn http_client(timeout_secs: u64) -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()
+        .map_err(|e| anyhow!("Cannot create HTTP client: {}", e))
+}
+
+fn parse_vectors(values: Option<&Value>) -> Option<Vec<Vec<f32>>> {
+    values?
+        .as_array()?
+        .iter()
+        .map(|vector| {
+            vector
+                .as_array()?
+                .iter()
+                .map(|v| v.as_f64().map(|v| v as f32))
+                .collect::<Option<Vec<f32>>>()
+                .map(normalize)
+        })
+        .collect()
+}
+
+/// 本机 Ollama 中的嵌入模型（如 `nomic-embed-text`）
+#[derive(Debug, Clone)]
+pub struct OllamaEmbedder {
+    url: String,
+    model: String,
+    timeout_secs: u64,
+}
+
+impl OllamaEmbedder {
+    pub fn new(url: &str, model: &str, timeout_secs: u64) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            timeout_secs,
+        }
+    }
+}
+
+impl Embedder for OllamaEmbedder {
+    fn model_id(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let response: Value = http_client(self.timeout_secs)?
+            .post(format!("{}/api/embed", self.url))
+            .json(&json!({"model": self.model, "input": texts}))
+            .send()
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Ollama embed request to {} failed", self.url))?
+            .json()?;
+        parse_vectors(response.get("embeddings"))
+            .filter(|vectors| vectors.len() == texts.len())
+            .ok_or_else(|| anyhow!("Unexpected Ollama embed response"))
+    }
+
+    fn pull(&self) -> Result<bool> {
+        let response: Value = http_client(PULL_TIMEOUT_SECS)?
+            .post(format!("{}/api/pull", self.url))
+            .json(&json!({"model": self.model, "stream": false}))
+            .send()
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Ollama pull request to {} failed", self.url))?
+            .json()?;
+        match response.get("status").and_then(Value::as_str) {
+            Some("success") => Ok(true),
+            _ => Err(anyhow!("Ollama pull failed: {}", response)),
+        }
+    }
+}
+
+/// OpenAI 兼容的 `/embeddings` 接口
+#[derive(Debug, Clone)]
+pub struct OpenAiEmbedder {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    timeout_secs: u64,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(url: &str, model: &str, api_key: Option<&str>, timeout_secs: u64) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: api_key.map(str::to_string),
+            timeout_secs,
+        }
+    }
+}
+
+impl Embedder for OpenAiEmbedder {
+    fn model_id(&self) -> String {
+        format!("openai:{}", self.model)
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut request = http_client(self.timeout_secs)?
+            .post(format!("{}/embeddings", self.url))
+            .json(&json!({"model": self.model, "input": texts}));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response: Value = request
+            .send()
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Embedding request to {} failed", self.url))?
+            .json()?;
+
+        // 按 index 排序，兼容乱序返回
+        let mut data: Vec<&Value> = response
+            .get("data")
+            .and_then(Value::as_array)
+            .map(|data| data.iter().collect())
+            .unwrap_or_default();
+        data.sort_by_key(|item| item.get("index").and_then(Value::as_u64).unwrap_or(0));
+        let vectors: Vec<Value> = data
+            .into_iter()
+            .filter_map(|item| item.get("embedding").cloned())
+            .collect();
+        parse_vectors(Some(&Value::Array(vectors)))
+            .filter(|vectors| vectors.len() == texts.len())
+            .ok_or_else(|| anyhow!("Unexpected embeddings response"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_embed_similarity() {
+        let a = hash_embed("Which database should I use for the cache layer?");
+        let b = hash_embed("Which database should I use for the cache layer ?");
+        let c = hash_embed("Deploy the service to production now");
+        assert!(cosine(&a, &b) > 0.9);
+        assert!(cosine(&a, &c) < 0.5);
+        assert_eq!(hash_embed("").len(), LEXICAL_DIMS);
+        assert_eq!(cosine(&a, &[1.0]), 0.0);
+
+        let vectors = parse_vectors(Some(&json!([[3.0, 4.0], [0.0, 2.0]]))).unwrap();
+        assert_eq!(vectors, vec![vec![0.6, 0.8], vec![0.0, 1.0]]);
+        assert!(parse_vectors(Some(&json!([["x"]]))).is_none());
+    }
+}
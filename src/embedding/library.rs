@@ -0,0 +1,380 @@
+//! 提示模板库 - 把终端快照匹配到已知的 agent 提示，离线提取问题
+//!
+//! 内置 Claude Code 和 Codex 的常见提示（权限确认、信任目录、计划审批等），用户可在
+//! `~/.config/code-agent-monitor/prompt_templates.json` 追加：
+//!
+//! ```json
+//! [
+//!   {"name": "deploy", "text": "Deploy to production?\n1. Yes\n2. No", "message_type": "choice"}
+//! ]
+//! ```
+//!
+//! 模板向量按模型缓存在 `embeddings/templates.json`，模板或模型变化时重建。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{cosine, EmbeddingConfig, Embeddings};
+use crate::agent::extractor::{ExtractedMessage, MessageType};
+use crate::infra::terminal::truncate_last_lines;
+use crate::notification::dedup_key::{generate_dedup_key, hash_content, strip_ansi_codes};
+
+/// 参与匹配的问题行数
+const QUESTION_LINES: usize = 12;
+
+/// 提示框底部的操作说明（不属于问题）
+const FOOTER_HINTS: [&str; 5] = ["esc to", "enter to", "tab to", "for shortcuts", "ctrl+"];
+
+/// 行首的状态标记
+const LEADING_MARKERS: [char; 3] = ['⏺', '⎿', '✻'];
+
+/// 已知的提示
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    /// 提示的典型文本（不含命令、路径等变化部分）
+    pub text: String,
+    pub message_type: MessageType,
+    /// 是否需要用户做决策（区别于权限确认）
+    #[serde(default)]
+    pub is_decision: bool,
+}
+
+impl PromptTemplate {
+    fn new(name: &str, text: &str, message_type: MessageType, is_decision: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            text: text.to_string(),
+            message_type,
+            is_decision,
+        }
+    }
+}
+
+/// 内置模板
+pub fn builtin_templates() -> Vec<PromptTemplate> {
+    use MessageType::{Choice, Confirmation};
+    vec![
+        PromptTemplate::new(
+            "claude-bash",
+            "Bash command\nDo you want to proceed?\n❯ 1. Yes\n2. Yes, and don't ask again for this command\n3. No, and tell Claude what to do differently (esc)",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "claude-edit",
+            "Edit file\nDo you want to make this edit?\n❯ 1. Yes\n2. Yes, allow all edits during this session (shift+tab)\n3. No, and tell Claude what to do differently (esc)",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "claude-create",
+            "Create file\nDo you want to create this file?\n❯ 1. Yes\n2. Yes, allow all edits during this session (shift+tab)\n3. No, and tell Claude what to do differently (esc)",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "claude-fetch",
+            "Fetch\nDo you want to allow Claude to fetch this content?\n❯ 1. Yes\n2. Yes, and don't ask again for this domain\n3. No, and tell Claude what to do differently (esc)",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "claude-mcp",
+            "Tool use\n(MCP)\nDo you want to proceed?\n❯ 1. Yes\n2. Yes, and don't ask again for this tool\n3. No, and tell Claude what to do differently (esc)",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "claude-trust",
+            "Do you trust the files in this folder?\nClaude Code may read, write, or execute files contained in this directory. This can pose security risks, so only use files from trusted sources.\n❯ 1. Yes, proceed\n2. No, exit",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "claude-plan",
+            "Would you like to proceed?\n❯ 1. Yes, and auto-accept edits\n2. Yes, and manually approve edits\n3. No, keep planning",
+            Choice,
+            true,
+        ),
+        PromptTemplate::new(
+            "codex-approval",
+            "Would you like to run the following command?\n❯ 1. Yes, proceed\n2. Yes, and don't ask again for this command\n3. No, and tell Codex what to do differently",
+            Choice,
+            false,
+        ),
+        PromptTemplate::new(
+            "confirm-yn",
+            "Do you want to continue? [y/N]",
+            Confirmation,
+            false,
+        ),
+    ]
+}
+
+/// 用户模板文件
+pub fn user_templates_path() -> PathBuf {
+    crate::infra::paths::config_dir().join("prompt_templates.json")
+}
+
+/// 内置模板加上用户模板（同名时用户模板覆盖内置）
+pub fn load_templates() -> Vec<PromptTemplate> {
+    let user: Vec<PromptTemplate> = fs::read_to_string(user_templates_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let mut templates: Vec<PromptTemplate> = builtin_templates()
+        .into_iter()
+        .filter(|builtin| !user.iter().any(|t| t.name == builtin.name))
+        .collect();
+    templates.extend(user);
+    templates
+}
+
+/// 模板索引文件
+pub(crate) fn index_path(dir: &Path) -> PathBuf {
+    dir.join("templates.json")
+}
+
+/// 去掉提示框边框后的行内容
+pub(crate) fn clean_line(line: &str) -> &str {
+    line.trim().trim_matches(['│', '┃', '|']).trim()
+}
+
+pub(crate) fn is_footer(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    !line.chars().any(char::is_alphanumeric) || FOOTER_HINTS.iter().any(|h| lower.contains(h))
+}
+
+/// 快照底部的问题文本：去掉 ANSI、边框、底部操作说明和空行，保留最后几行
+pub fn question_text(snapshot: &str) -> String {
+    let stripped = strip_ansi_codes(&truncate_last_lines(snapshot, QUESTION_LINES * 3));
+    let mut lines: Vec<&str> = stripped
+        .lines()
+        .map(|line| clean_line(line).trim_start_matches(LEADING_MARKERS).trim())
+        .filter(|line| line.chars().any(char::is_alphanumeric))
+        .collect();
+    while lines.last().is_some_and(|line| is_footer(line)) {
+        lines.pop();
+    }
+    lines[lines.len().saturating_sub(QUESTION_LINES)..].join("\n")
+}
+
+/// 匹配结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateMatch {
+    pub template: PromptTemplate,
+    /// 相似度，即置信度
+    pub confidence: f32,
+    /// 快照中的问题文本
+    pub question: String,
+}
+
+impl TemplateMatch {
+    /// 转换为提取结果
+    pub fn message(&self) -> ExtractedMessage {
+        let hint = match self.template.message_type {
+            MessageType::Choice => "\n\n回复数字选择",
+            MessageType::Confirmation => "\n\n回复 y/n",
+            _ => "",
+        };
+        ExtractedMessage {
+            content: format!("{}{}", self.question, hint),
+            fingerprint: format!(
+                "template-{}-{}",
+                self.template.name,
+                generate_dedup_key(&self.question)
+            ),
+            context_complete: true,
+            message_type: self.template.message_type.clone(),
+            is_decision_required: self.template.is_decision,
+        }
+    }
+}
+
+/// 模板索引
+#[derive(Debug, Serialize, Deserialize)]
+struct TemplateIndex {
+    model: String,
+    digest: u64,
+    entries: Vec<(PromptTemplate, Vec<f32>)>,
+}
+
+/// 已计算向量的模板库
+#[derive(Debug)]
+pub struct TemplateLibrary {
+    model: String,
+    threshold: f32,
+    entries: Vec<(PromptTemplate, Vec<f32>)>,
+}
+
+impl TemplateLibrary {
+    /// 读取模板和 `embedding.threshold`，索引有效时复用，否则重建并保存
+    pub fn load_or_build(embeddings: &Embeddings) -> Result<Self> {
+        let threshold = EmbeddingConfig::load().threshold;
+        Self::load_or_build_with(embeddings, load_templates(), threshold)
+    }
+
+    pub fn load_or_build_with(
+        embeddings: &Embeddings,
+        templates: Vec<PromptTemplate>,
+        threshold: f32,
+    ) -> Result<Self> {
+        let model = embeddings.model_id();
+        let digest = hash_content(&serde_json::to_string(&templates)?);
+        let path = index_path(embeddings.dir());
+        let cached = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<TemplateIndex>(&content).ok())
+            .filter(|index| index.model == model && index.digest == digest);
+        if let Some(index) = cached {
+            return Ok(Self {
+                model,
+                threshold,
+                entries: index.entries,
+            });
+        }
+
+        debug!(model = %model, count = templates.len(), "Building prompt template index");
+        let library = Self::build(embeddings, templates, threshold)?;
+        let index = TemplateIndex {
+            model: library.model.clone(),
+            digest,
+            entries: library.entries.clone(),
+        };
+        fs::create_dir_all(embeddings.dir())?;
+        fs::write(&path, serde_json::to_string(&index)?)?;
+        Ok(library)
+    }
+
+    /// 计算所有模板的向量（不读写索引）
+    pub fn build(
+        embeddings: &Embeddings,
+        templates: Vec<PromptTemplate>,
+        threshold: f32,
+    ) -> Result<Self> {
+        let texts: Vec<String> = templates.iter().map(|t| question_text(&t.text)).collect();
+        let vectors =
+            embeddings.embed_many(&texts.iter().map(String::as_str).collect::<Vec<_>>())?;
+        Ok(Self {
+            model: embeddings.model_id(),
+            threshold,
+            entries: templates.into_iter().zip(vectors).collect(),
+        })
+    }
+
+    pub fn model_id(&self) -> &str {
+        &self.model
+    }
+
+    /// 匹配的最低相似度
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn templates(&self) -> impl Iterator<Item = &PromptTemplate> {
+        self.entries.iter().map(|(template, _)| template)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 与快照最相似的模板（不论是否达到阈值）
+    pub fn nearest(
+        &self,
+        embeddings: &Embeddings,
+        snapshot: &str,
+    ) -> Result<Option<TemplateMatch>> {
+        let question = question_text(snapshot);
+        if question.is_empty() || self.entries.is_empty() {
+            return Ok(None);
+        }
+        let vector = embeddings.embed(&question)?;
+        Ok(self
+            .entries
+            .iter()
+            .map(|(template, candidate)| (cosine(candidate, &vector), template))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(confidence, template)| TemplateMatch {
+                template: template.clone(),
+                confidence,
+                question,
+            }))
+    }
+
+    /// 达到阈值的最佳匹配
+    pub fn best_match(
+        &self,
+        embeddings: &Embeddings,
+        snapshot: &str,
+    ) -> Result<Option<TemplateMatch>> {
+        Ok(self
+            .nearest(embeddings, snapshot)?
+            .filter(|m| m.confidence >= self.threshold))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::LexicalEmbedder;
+
+    #[test]
+    fn test_question_text() {
+        let snapshot = "\x1b[1m⏺ Running tests\x1b[0m\n╭──────╮\n│ Continue? [y/N] │\n╰──────╯\n  ? for shortcuts\n";
+        assert_eq!(question_text(snapshot), "Running tests\nContinue? [y/N]");
+        assert_eq!(question_text("───\n"), "");
+    }
+
+    #[test]
+    fn test_template_matching() {
+        let temp = tempfile::tempdir().unwrap();
+        let embeddings = Embeddings::new(Box::new(LexicalEmbedder), temp.path());
+        let library = TemplateLibrary::load_or_build_with(
+            &embeddings,
+            builtin_templates(),
+            EmbeddingConfig::default().threshold,
+        )
+        .unwrap();
+        assert_eq!(library.len(), builtin_templates().len());
+        assert!(index_path(temp.path()).exists());
+
+        let bash = "⏺ Cleaning up\n\n╭──────────────────────────╮\n│ Bash command             │\n│                          │\n│   rm -rf build           │\n│   Remove build directory │\n│                          │\n│ Do you want to proceed?  │\n│ ❯ 1. Yes                 │\n│   2. Yes, and don't ask again for rm commands in /tmp/app │\n│   3. No, and tell Claude what to do differently (esc) │\n╰──────────────────────────╯\n";
+        let found = library.best_match(&embeddings, bash).unwrap().unwrap();
+        assert_eq!(found.template.name, "claude-bash");
+        let message = found.message();
+        assert_eq!(message.message_type, MessageType::Choice);
+        assert!(!message.is_decision_required);
+        assert!(message.content.contains("rm -rf build"));
+        assert!(message.fingerprint.starts_with("template-claude-bash-"));
+
+        let plan = "Here is the plan:\n1. Add cache\n2. Add tests\n\nWould you like to proceed?\n❯ 1. Yes, and auto-accept edits\n  2. Yes, and manually approve edits\n  3. No, keep planning\n";
+        let found = library.best_match(&embeddings, plan).unwrap().unwrap();
+        assert_eq!(found.template.name, "claude-plan");
+        assert!(found.message().is_decision_required);
+
+        // 普通输出不匹配任何模板
+        let output = "⏺ I updated src/main.rs to add the new command and ran cargo test.\nAll 42 tests passed.\n";
+        assert!(library.best_match(&embeddings, output).unwrap().is_none());
+
+        // 模板变化时重建索引
+        let custom = vec![PromptTemplate::new(
+            "deploy",
+            "Deploy to production?\n1. Yes\n2. No",
+            MessageType::Choice,
+            true,
+        )];
+        let rebuilt = TemplateLibrary::load_or_build_with(&embeddings, custom, 0.8).unwrap();
+        assert_eq!(rebuilt.len(), 1);
+        assert_eq!(rebuilt.model_id(), "lexical:char-trigram-256");
+    }
+}
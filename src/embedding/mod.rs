@@ -0,0 +1,367 @@
+//! 嵌入模型管理 - 模型选择、下载、向量缓存，以及离线的提示模板匹配
+//!
+//! 后端由 config.json 的 `embedding` 配置：
+//! - `lexical`（默认，旧名 `local`）：字符三元组哈希的字面相似度回退，完全离线、无需下载，
+//!   只识别措辞相近的文本，不是语义嵌入
+//! - `ollama`：本机 Ollama 的语义嵌入模型，`cam embeddings pull` 下载
+//! - `openai`：OpenAI 兼容的 `/embeddings` 接口
+//!
+//! ```json
+//! {
+//!   "embedding": {
+//!     "backend": "ollama",
+//!     "model": "nomic-embed-text",
+//!     "url": "http://localhost:11434",
+//!     "threshold": 0.8
+//!   }
+//! }
+//! ```
+//!
+//! 远程后端的向量缓存在 `~/.config/code-agent-monitor/embeddings/vectors.json`，按模型区分，
+//! 切换模型后旧缓存自动失效。[`TemplateLibrary`] 用这些向量把终端快照匹配到已知的提示模板。
+
+pub mod backend;
+pub mod library;
+
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+
+use crate::notification::dedup_key::hash_content;
+
+pub use backend::{cosine, LexicalEmbedder, OllamaEmbedder, OpenAiEmbedder};
+pub use library::{question_text, PromptTemplate, TemplateLibrary, TemplateMatch};
+
+/// 向量缓存上限，超出时丢弃最旧的
+const MAX_CACHED_VECTORS: usize = 2000;
+
+const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+const DEFAULT_OLLAMA_MODEL: &str = "nomic-embed-text";
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+
+/// 嵌入后端
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingBackend {
+    /// 字面相似度回退（[`LexicalEmbedder`]）
+    #[default]
+    #[serde(alias = "local")]
+    Lexical,
+    Ollama,
+    Openai,
+}
+
+/// config.json 的 `embedding` 配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    pub backend: EmbeddingBackend,
+    /// 模型名，未设置时用后端的默认模型
+    pub model: Option<String>,
+    /// 服务地址，未设置时用后端的默认地址
+    pub url: Option<String>,
+    /// `openai` 的 API key，未设置时读取 `OPENAI_API_KEY`
+    pub api_key: Option<String>,
+    pub timeout_secs: u64,
+    /// 模板匹配的最低相似度
+    pub threshold: f32,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            backend: EmbeddingBackend::Lexical,
+            model: None,
+            url: None,
+            api_key: None,
+            timeout_secs: 5,
+            threshold: 0.8,
+        }
+    }
+}
+
+impl EmbeddingConfig {
+    /// 读取 config.json 的 `embedding`，缺失或无效时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("embedding").unwrap_or_default()
+    }
+
+    /// 按配置创建后端
+    pub fn embedder(&self) -> Box<dyn Embedder> {
+        let model = |default: &str| self.model.clone().unwrap_or_else(|| default.to_string());
+        let url = |default: &str| self.url.clone().unwrap_or_else(|| default.to_string());
+        match self.backend {
+            EmbeddingBackend::Lexical => Box::new(LexicalEmbedder),
+            EmbeddingBackend::Ollama => Box::new(OllamaEmbedder::new(
+                &url(DEFAULT_OLLAMA_URL),
+                &model(DEFAULT_OLLAMA_MODEL),
+                self.timeout_secs,
+            )),
+            EmbeddingBackend::Openai => {
+                let api_key = self
+                    .api_key
+                    .clone()
+                    .or_else(|| std::env::var("OPENAI_API_KEY").ok());
+                Box::new(OpenAiEmbedder::new(
+                    &url(DEFAULT_OPENAI_URL),
+                    &model(DEFAULT_OPENAI_MODEL),
+                    api_key.as_deref(),
+                    self.timeout_secs,
+                ))
+            }
+        }
+    }
+}
+
+/// 嵌入模型
+pub trait Embedder: Send + Sync {
+    /// 模型标识（`<backend>:<model>`），用于区分缓存
+    fn model_id(&self) -> String;
+
+    /// 批量计算 L2 归一化的向量，顺序与输入一致
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>>;
+
+    /// 下载模型，返回是否下载了；不需要下载的后端返回 false
+    fn pull(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// 向量是否值得缓存到磁盘（本地计算比读缓存还快时返回 false）
+    fn cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// 嵌入数据目录
+pub fn embeddings_dir() -> PathBuf {
+    crate::infra::paths::state_dir().join("embeddings")
+}
+
+/// 磁盘向量缓存
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VectorCache {
+    model: String,
+    /// (文本哈希, 向量)，按写入顺序
+    #[serde(default)]
+    vectors: Vec<(u64, Vec<f32>)>,
+}
+
+/// 带磁盘缓存的嵌入模型
+pub struct Embeddings {
+    embedder: Box<dyn Embedder>,
+    dir: PathBuf,
+}
+
+impl Embeddings {
+    /// 按 config.json 创建，数据放在 [`embeddings_dir`]
+    pub fn load() -> Self {
+        Self::new(EmbeddingConfig::load().embedder(), &embeddings_dir())
+    }
+
+    /// 字面相似度回退
+    pub fn lexical() -> Self {
+        Self::new(Box::new(LexicalEmbedder), &embeddings_dir())
+    }
+
+    pub fn new(embedder: Box<dyn Embedder>, dir: &Path) -> Self {
+        Self {
+            embedder,
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    pub fn model_id(&self) -> String {
+        self.embedder.model_id()
+    }
+
+    /// 数据目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 下载模型
+    pub fn pull(&self) -> Result<bool> {
+        self.embedder.pull()
+    }
+
+    fn cache_path(&self) -> PathBuf {
+        self.dir.join("vectors.json")
+    }
+
+    /// 当前模型的缓存，模型不一致时为空
+    fn read_cache(&self) -> VectorCache {
+        let model = self.model_id();
+        fs::read_to_string(self.cache_path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<VectorCache>(&content).ok())
+            .filter(|cache| cache.model == model)
+            .unwrap_or(VectorCache {
+                model,
+                vectors: Vec::new(),
+            })
+    }
+
+    /// 在文件锁内修改并保存缓存
+    fn update_cache(&self, operation: impl FnOnce(&mut VectorCache)) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.cache_path().with_extension("lock"))?;
+        lock_file.lock_exclusive()?;
+
+        let mut cache = self.read_cache();
+        operation(&mut cache);
+        let saved = serde_json::to_string(&cache)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(fs::write(self.cache_path(), content)?));
+
+        let _ = lock_file.unlock();
+        saved
+    }
+
+    /// 单个文本的向量
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_many(&[text])?
+            .pop()
+            .ok_or_else(|| anyhow!("Embedder returned no vector"))
+    }
+
+    /// 批量计算向量，只为缓存中没有的文本调用模型
+    pub fn embed_many(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if !self.embedder.cacheable() {
+            return self.embedder.embed_batch(texts);
+        }
+
+        let keys: Vec<u64> = texts.iter().map(|text| hash_content(text)).collect();
+        let cache = self.read_cache();
+        let lookup = |key: u64| {
+            cache
+                .vectors
+                .iter()
+                .find(|(cached, _)| *cached == key)
+                .map(|(_, vector)| vector.clone())
+        };
+        let mut vectors: Vec<Option<Vec<f32>>> = keys.iter().map(|key| lookup(*key)).collect();
+
+        let missing: Vec<usize> = (0..texts.len()).filter(|i| vectors[*i].is_none()).collect();
+        if !missing.is_empty() {
+            let batch: Vec<&str> = missing.iter().map(|i| texts[*i]).collect();
+            let computed = self.embedder.embed_batch(&batch)?;
+            let new_entries: Vec<(u64, Vec<f32>)> = missing
+                .iter()
+                .zip(&computed)
+                .map(|(i, vector)| (keys[*i], vector.clone()))
+                .collect();
+            for (i, vector) in missing.into_iter().zip(computed) {
+                vectors[i] = Some(vector);
+            }
+            // 缓存写入失败不影响结果
+            let _ = self.update_cache(|cache| {
+                cache
+                    .vectors
+                    .retain(|(key, _)| !new_entries.iter().any(|(new, _)| new == key));
+                cache.vectors.extend(new_entries);
+                let excess = cache.vectors.len().saturating_sub(MAX_CACHED_VECTORS);
+                cache.vectors.drain(..excess);
+            });
+        }
+        Ok(vectors.into_iter().flatten().collect())
+    }
+
+    /// 当前模型缓存的向量数
+    pub fn cache_len(&self) -> usize {
+        self.read_cache().vectors.len()
+    }
+
+    /// 删除向量缓存和模板索引，返回删除的文件数
+    pub fn clear_cache(&self) -> Result<usize> {
+        let mut removed = 0;
+        for path in [self.cache_path(), library::index_path(&self.dir)] {
+            if path.exists() {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// 计数调用次数的远程模型替身
+    struct CountingEmbedder {
+        model: &'static str,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Embedder for CountingEmbedder {
+        fn model_id(&self) -> String {
+            format!("test:{}", self.model)
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            self.calls.fetch_add(texts.len(), Ordering::SeqCst);
+            Ok(texts.iter().map(|text| backend::hash_embed(text)).collect())
+        }
+    }
+
+    #[test]
+    fn test_vector_cache_per_model() {
+        let temp = tempfile::tempdir().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let embeddings = |model| {
+            let embedder = CountingEmbedder {
+                model,
+                calls: Arc::clone(&calls),
+            };
+            Embeddings::new(Box::new(embedder), temp.path())
+        };
+
+        let first = embeddings("a");
+        let vectors = first.embed_many(&["one", "two"]).unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(first.embed("two").unwrap(), vectors[1]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(first.cache_len(), 2);
+
+        // 换模型后缓存失效
+        let second = embeddings("b");
+        assert_eq!(second.cache_len(), 0);
+        second.embed("one").unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(first.cache_len(), 0);
+
+        assert_eq!(second.clear_cache().unwrap(), 1);
+        assert_eq!(second.cache_len(), 0);
+
+        // 字面回退不写缓存
+        let lexical = Embeddings::new(Box::new(LexicalEmbedder), temp.path());
+        lexical.embed("one").unwrap();
+        assert_eq!(lexical.cache_len(), 0);
+    }
+
+    #[test]
+    fn test_config_embedder() {
+        assert_eq!(
+            EmbeddingConfig::default().embedder().model_id(),
+            "lexical:char-trigram-256"
+        );
+        // 旧配置名 `local` 仍然有效
+        let config: EmbeddingConfig = serde_json::from_str(r#"{"backend": "local"}"#).unwrap();
+        assert_eq!(config.backend, EmbeddingBackend::Lexical);
+        let config: EmbeddingConfig =
+            serde_json::from_str(r#"{"backend": "ollama", "threshold": 0.9}"#).unwrap();
+        assert_eq!(config.timeout_secs, 5);
+        assert_eq!(config.embedder().model_id(), "ollama:nomic-embed-text");
+    }
+}
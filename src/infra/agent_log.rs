@@ -0,0 +1,305 @@
+//! 按 agent 分文件的结构化日志
+//!
+//! watcher daemon 的 tracing 事件如果带有 `agent_id` 字段，会额外以 JSON Lines 写入
+//! `~/.config/code-agent-monitor/logs/<agent_id>.log`，超过大小上限时轮转为
+//! `<agent_id>.log.1`、`.log.2`…，超出保留数量的旧文件被删除。
+//!
+//! config.json 示例（以下为默认值）：
+//! ```json
+//! { "agent_logs": { "enabled": true, "max_bytes": 5242880, "max_files": 3 } }
+//! ```
+//!
+//! 用 `cam service logs --agent <id>` 查看。
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// 默认单个文件大小上限（5 MB）
+pub const DEFAULT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// 默认保留的轮转文件数（不含当前文件）
+pub const DEFAULT_MAX_FILES: usize = 3;
+
+/// 日志配置（config.json 的 `agent_logs`）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AgentLogConfig {
+    pub enabled: bool,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl Default for AgentLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+impl AgentLogConfig {
+    /// 读取 config.json，缺失或无效时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("agent_logs").unwrap_or_default()
+    }
+}
+
+/// 日志目录 `~/.config/code-agent-monitor/logs`
+pub fn default_log_dir() -> PathBuf {
+    crate::infra::paths::state_dir().join("logs")
+}
+
+/// agent 日志文件路径（agent ID 中的路径分隔符等字符替换为 `_`）
+pub fn log_path(dir: &Path, agent_id: &str) -> PathBuf {
+    let name: String = agent_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '@' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    dir.join(format!("{}.log", if name.is_empty() { "_" } else { name }))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// 按 agent 写入并轮转日志文件
+#[derive(Debug)]
+pub struct AgentLogWriter {
+    dir: PathBuf,
+    config: AgentLogConfig,
+    /// 串行化写入与轮转
+    lock: Mutex<()>,
+}
+
+impl AgentLogWriter {
+    pub fn new(dir: PathBuf, config: AgentLogConfig) -> Self {
+        Self {
+            dir,
+            config,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 追加一行，写入前文件将超过上限时先轮转
+    pub fn append(&self, agent_id: &str, line: &str) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        fs::create_dir_all(&self.dir)?;
+        let path = log_path(&self.dir, agent_id);
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 + 1 > self.config.max_bytes {
+            self.rotate(&path)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// `.log` → `.log.1` → … → `.log.<max_files>`，最旧的删除
+    fn rotate(&self, path: &Path) -> Result<()> {
+        if self.config.max_files == 0 {
+            fs::remove_file(path)?;
+            return Ok(());
+        }
+        let _ = fs::remove_file(rotated_path(path, self.config.max_files));
+        for index in (1..self.config.max_files).rev() {
+            let from = rotated_path(path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(path, index + 1))?;
+            }
+        }
+        fs::rename(path, rotated_path(path, 1))?;
+        Ok(())
+    }
+}
+
+/// 读取 agent 日志的最后 `lines` 行（含轮转文件，按时间顺序）
+pub fn read_tail(dir: &Path, agent_id: &str, lines: usize) -> Vec<String> {
+    let path = log_path(dir, agent_id);
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|index| rotated_path(&path, index))
+        .take_while(|p| p.exists())
+        .collect();
+    files.reverse();
+    files.push(path);
+
+    let mut all: Vec<String> = Vec::new();
+    for file in files {
+        if let Ok(content) = fs::read_to_string(&file) {
+            all.extend(content.lines().map(|l| l.to_string()));
+        }
+    }
+    let skip = all.len().saturating_sub(lines);
+    all.split_off(skip)
+}
+
+/// 收集事件字段
+#[derive(Default)]
+struct JsonVisitor {
+    fields: BTreeMap<String, Value>,
+}
+
+impl Visit for JsonVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields
+            .insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields
+            .insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields
+            .insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields
+            .insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields.insert(
+            field.name().to_string(),
+            Value::from(format!("{:?}", value)),
+        );
+    }
+}
+
+/// tracing layer：把带 `agent_id` 字段的事件写入对应 agent 的日志文件
+pub struct AgentLogLayer {
+    writer: AgentLogWriter,
+}
+
+impl AgentLogLayer {
+    pub fn new(writer: AgentLogWriter) -> Self {
+        Self { writer }
+    }
+
+    /// 按 config.json 创建，关闭时返回 None
+    pub fn from_config() -> Option<Self> {
+        let config = AgentLogConfig::load();
+        config
+            .enabled
+            .then(|| Self::new(AgentLogWriter::new(default_log_dir(), config)))
+    }
+}
+
+impl<S: Subscriber> Layer<S> for AgentLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        let Some(agent_id) = visitor
+            .fields
+            .remove("agent_id")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+        else {
+            return;
+        };
+
+        let mut record = serde_json::Map::new();
+        record.insert("ts".to_string(), Value::from(Utc::now().to_rfc3339()));
+        record.insert(
+            "level".to_string(),
+            Value::from(event.metadata().level().as_str()),
+        );
+        record.insert("target".to_string(), Value::from(event.metadata().target()));
+        record.extend(visitor.fields);
+        // 日志失败不能影响 daemon，也不能再写 tracing（避免递归）
+        let _ = self
+            .writer
+            .append(&agent_id, &Value::Object(record).to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn writer(dir: &Path, max_bytes: u64, max_files: usize) -> AgentLogWriter {
+        AgentLogWriter::new(
+            dir.to_path_buf(),
+            AgentLogConfig {
+                enabled: true,
+                max_bytes,
+                max_files,
+            },
+        )
+    }
+
+    #[test]
+    fn test_log_path_sanitizes_agent_id() {
+        let dir = Path::new("/logs");
+        assert_eq!(log_path(dir, "cam-123"), dir.join("cam-123.log"));
+        assert_eq!(log_path(dir, "lead@dev"), dir.join("lead@dev.log"));
+        assert_eq!(log_path(dir, "../etc/passwd"), dir.join("_etc_passwd.log"));
+    }
+
+    #[test]
+    fn test_rotation_and_retention() {
+        let tmp = TempDir::new().unwrap();
+        let writer = writer(tmp.path(), 30, 2);
+        for i in 0..8 {
+            writer
+                .append("cam-1", &format!("line-{:02}-xxxxxxxx", i))
+                .unwrap();
+        }
+        let path = log_path(tmp.path(), "cam-1");
+        assert!(path.exists());
+        assert!(rotated_path(&path, 2).exists());
+        assert!(!rotated_path(&path, 3).exists());
+
+        // 保留 3 个文件，每个一行
+        let tail = read_tail(tmp.path(), "cam-1", 10);
+        assert_eq!(
+            tail,
+            ["line-05-xxxxxxxx", "line-06-xxxxxxxx", "line-07-xxxxxxxx"]
+        );
+        assert_eq!(read_tail(tmp.path(), "cam-1", 1), ["line-07-xxxxxxxx"]);
+        assert!(read_tail(tmp.path(), "cam-404", 10).is_empty());
+    }
+
+    #[test]
+    fn test_layer_writes_only_agent_events() {
+        let tmp = TempDir::new().unwrap();
+        let layer = AgentLogLayer::new(writer(tmp.path(), DEFAULT_MAX_BYTES, 1));
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(agent_id = %"cam-7", result = ?Some(1), "Notification result");
+            tracing::info!("Daemon started");
+        });
+
+        let lines = read_tail(tmp.path(), "cam-7", 10);
+        assert_eq!(lines.len(), 1);
+        let record: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(record["level"], "INFO");
+        assert_eq!(record["message"], "Notification result");
+        assert_eq!(record["result"], "Some(1)");
+        assert!(record.get("agent_id").is_none());
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,246 @@
+//! config.json 变更检测 - watcher daemon 每轮检查修改时间，内容变化时返回差异
+//!
+//! 修改后的内容不是合法 JSON 时保留旧配置并记录警告，修正后再与最后一次有效配置比较。
+//! 差异按点分路径逐项列出，名称像密钥的字段（token、secret、key、password）不打印值。
+//! [`RESTART_REQUIRED_KEYS`] 中的配置在 daemon 启动时选定，修改后需要重启才生效，其余均就地重载。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::{Map, Value};
+use tracing::warn;
+
+/// 修改后需要重启 daemon 才生效的顶层配置（多路复用器与 tmux 连接、日志层在启动时创建）
+pub const RESTART_REQUIRED_KEYS: &[&str] =
+    &["multiplexer", "tmux", "tmux_control_mode", "agent_logs"];
+
+/// config.json 变更检测器
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    current: Value,
+}
+
+impl Default for ConfigWatcher {
+    fn default() -> Self {
+        Self::new(&crate::infra::paths::config_path())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 读取配置，文件不存在时为空对象，不是合法 JSON 时为 None
+fn read_config(path: &Path) -> Option<Value> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).ok(),
+        Err(_) => Some(Value::Object(Map::new())),
+    }
+}
+
+impl ConfigWatcher {
+    /// 以当前内容为基线
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified(path),
+            current: read_config(path).unwrap_or_else(|| Value::Object(Map::new())),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 文件修改时间变化且内容有效、有差异时返回差异（并更新基线）
+    pub fn poll(&mut self) -> Option<Vec<String>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let Some(config) = read_config(&self.path) else {
+            warn!(path = %self.path.display(), "Config is not valid JSON, keeping previous config");
+            return None;
+        };
+        let changes = config_diff(&self.current, &config);
+        self.current = config;
+        (!changes.is_empty()).then_some(changes)
+    }
+}
+
+fn is_secret(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["token", "secret", "key", "password"]
+        .iter()
+        .any(|word| key.contains(word))
+}
+
+/// 隐藏密钥字段的值（包括嵌套对象中的）
+fn redact(key: &str, value: &Value) -> Value {
+    match value {
+        _ if is_secret(key) => Value::String("***".to_string()),
+        Value::Object(map) => {
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), redact(k, v))).collect())
+        }
+        _ => value.clone(),
+    }
+}
+
+fn render(key: &str, value: &Value) -> String {
+    match redact(key, value) {
+        Value::String(s) if s == "***" => s,
+        value => value.to_string(),
+    }
+}
+
+fn diff_into(path: &str, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    let key = path.rsplit('.').next().unwrap_or(path);
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let join = |k: &str| {
+                if path.is_empty() {
+                    k.to_string()
+                } else {
+                    format!("{}.{}", path, k)
+                }
+            };
+            for (k, old_value) in old {
+                match new.get(k) {
+                    Some(new_value) => diff_into(&join(k), old_value, new_value, changes),
+                    None => changes.push(format!("-{}", join(k))),
+                }
+            }
+            for (k, new_value) in new {
+                if !old.contains_key(k) {
+                    changes.push(format!("+{} = {}", join(k), render(k, new_value)));
+                }
+            }
+        }
+        _ if old == new => {}
+        _ => changes.push(format!(
+            "~{}: {} -> {}",
+            path,
+            render(key, old),
+            render(key, new)
+        )),
+    }
+}
+
+/// 差异中涉及 [`RESTART_REQUIRED_KEYS`] 的顶层配置（按出现顺序去重）
+pub fn restart_required(changes: &[String]) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    for change in changes {
+        let path = change.trim_start_matches(['+', '-', '~']);
+        let top = path.split(['.', ':', ' ']).next().unwrap_or(path);
+        if let Some(key) = RESTART_REQUIRED_KEYS.iter().find(|k| **k == top) {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+    }
+    keys
+}
+
+/// 两份配置的差异：`+路径 = 值`、`-路径`、`~路径: 旧值 -> 新值`（数组整体比较）
+pub fn config_diff(old: &Value, new: &Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    diff_into("", old, new, &mut changes);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_config_diff() {
+        let old = json!({
+            "webhook": {"gateway_url": "http://a", "hook_token": "t1", "default_channel": "telegram"},
+            "watch_rules": [{"pattern": "ERROR"}],
+            "stuck_threshold_secs": 300
+        });
+        let new = json!({
+            "webhook": {"gateway_url": "http://b", "hook_token": "t2"},
+            "watch_rules": [{"pattern": "ERROR", "urgency": "high"}],
+            "idle_threshold_secs": 600
+        });
+        assert_eq!(
+            config_diff(&old, &new),
+            [
+                "~webhook.gateway_url: \"http://a\" -> \"http://b\"",
+                "~webhook.hook_token: *** -> ***",
+                "-webhook.default_channel",
+                "~watch_rules: [{\"pattern\":\"ERROR\"}] -> [{\"pattern\":\"ERROR\",\"urgency\":\"high\"}]",
+                "-stuck_threshold_secs",
+                "+idle_threshold_secs = 600",
+            ]
+        );
+        assert!(config_diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_restart_required() {
+        let old = json!({"tmux": {"history_limit": 1000}, "stuck_after_mins": 5});
+        let new = json!({
+            "tmux": {"history_limit": 5000},
+            "tmux_control_mode": true,
+            "stuck_after_mins": 10,
+            "ai_tasks": {"extraction": {"model": "m"}}
+        });
+        assert_eq!(
+            restart_required(&config_diff(&old, &new)),
+            ["tmux", "tmux_control_mode"]
+        );
+        assert!(restart_required(&["~stuck_after_mins: 5 -> 10".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_config_watcher_keeps_last_valid() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config.json");
+        let mut watcher = ConfigWatcher::new(&path);
+        assert_eq!(watcher.poll(), None);
+
+        fs::write(
+            &path,
+            r#"{"webhook": {"default_channel": "slack", "hook_token": "t"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            watcher.poll(),
+            Some(vec![
+                "+webhook = {\"default_channel\":\"slack\",\"hook_token\":\"***\"}".to_string()
+            ])
+        );
+        assert_eq!(watcher.poll(), None);
+
+        // 无效内容不替换基线；修正后与最后一次有效配置比较
+        let mut bump = 0;
+        let mut touch = |content: &str| {
+            // 显式推后修改时间，避免同一时间粒度内的写入被忽略
+            bump += 1;
+            fs::write(&path, content).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(SystemTime::now() + std::time::Duration::from_secs(bump))
+                .unwrap();
+        };
+        touch("{not json");
+        assert_eq!(watcher.poll(), None);
+        touch(r#"{"webhook": {"default_channel": "discord", "hook_token": "t"}}"#);
+        assert_eq!(
+            watcher.poll(),
+            Some(vec![
+                "~webhook.default_channel: \"slack\" -> \"discord\"".to_string()
+            ])
+        );
+    }
+}
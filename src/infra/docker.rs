@@ -0,0 +1,134 @@
+//! Docker 容器 - 在容器中运行 agent（`cam start --container <image>`）
+//!
+//! 复用器 session 中运行前台的 `docker run -it --rm`，容器的终端就是 pane 内容，
+//! 截屏和回复照常经过复用器。项目目录以相同的绝对路径挂载，agent CLI 需已安装在镜像中。
+//! 容器名由 agent_id 生成，停止 agent 或 session 消失时强制删除容器。
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use tracing::debug;
+
+use crate::infra::multiplexer::run_checked;
+
+/// 传入容器的 API key 环境变量（宿主机已设置时才传入）
+const PASSTHROUGH_ENV: &[&str] = &["ANTHROPIC_API_KEY", "OPENAI_API_KEY"];
+
+/// docker 是否可用（daemon 可连接）
+pub fn is_available() -> bool {
+    Command::new("docker")
+        .args(["version", "--format", "{{.Server.Version}}"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// agent 对应的容器名（docker 只允许 `[a-zA-Z0-9_.-]`）
+pub fn container_name(agent_id: &str) -> String {
+    agent_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "_.-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// 在容器中运行 `command` 的 `docker run` 命令行
+pub fn run_command(image: &str, container_name: &str, project_path: &str, command: &str) -> String {
+    let mount = format!("{0}:{0}", project_path);
+    let mut args = vec![
+        "docker",
+        "run",
+        "-it",
+        "--rm",
+        "--name",
+        container_name,
+        "-v",
+        &mount,
+        "-w",
+        project_path,
+    ];
+    for var in PASSTHROUGH_ENV {
+        if std::env::var_os(var).is_some() {
+            args.extend(["-e", var]);
+        }
+    }
+    args.extend([image, "sh", "-c", command]);
+    args.into_iter()
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@".contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// 查询容器 ID，容器不存在时返回 None
+pub fn container_id(container_name: &str) -> Option<String> {
+    run_checked(
+        Command::new("docker").args(["inspect", "--format", "{{.Id}}", container_name]),
+        "inspect container",
+    )
+    .ok()
+    .map(|id| id.trim().to_string())
+    .filter(|id| !id.is_empty())
+}
+
+/// 等待 `docker run` 创建容器（拉取镜像时可能较慢）
+pub fn wait_for_container(container_name: &str, timeout: Duration) -> Option<String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(id) = container_id(container_name) {
+            return Some(id);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// 强制删除容器（运行中也会停止），容器不存在时视为成功
+pub fn remove_container(container: &str) -> Result<()> {
+    if container_id(container).is_none() {
+        return Ok(());
+    }
+    debug!(container = %container, "Removing container");
+    run_checked(
+        Command::new("docker").args(["rm", "-f", container]),
+        &format!("remove container {}", container),
+    )
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_name() {
+        assert_eq!(container_name("cam-123-0"), "cam-123-0");
+        assert_eq!(container_name("team/dev 1"), "team-dev-1");
+    }
+
+    #[test]
+    fn test_run_command_quotes_arguments() {
+        let command = run_command("node:20", "cam-1", "/tmp/my project", "claude --resume 'x'");
+        assert!(command.starts_with("docker run -it --rm --name cam-1 -v '/tmp/my project:/tmp/my project' -w '/tmp/my project' "));
+        assert!(command.ends_with(r#" node:20 sh -c 'claude --resume '\''x'\'''"#));
+    }
+}
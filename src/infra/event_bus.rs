@@ -0,0 +1,210 @@
+//! 事件总线 - 跨进程共享的内部事件流
+//!
+//! watcher 事件、通知投递、回复/ack 和 agent 状态变化都追加到
+//! `~/.config/code-agent-monitor/events.jsonl`（每行一个 [`BusEvent`]），
+//! 由 `cam events --follow` 跟踪输出，便于接入 jq、vector 或其他数据管道。
+//!
+//! 文件超过 [`MAX_BYTES`] 时轮转为 `events.jsonl.1`（只保留一个旧文件），
+//! 跟踪方检测到文件变小后从头读取新文件。写入失败不影响调用方。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// 事件文件轮转阈值
+pub const MAX_BYTES: u64 = 8 * 1024 * 1024;
+
+/// 一条总线事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusEvent {
+    pub ts: DateTime<Utc>,
+    /// 事件来源：`watch`、`notification`、`reply`、`ack`、`state`
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// 事件内容（各来源自己的结构）
+    pub data: serde_json::Value,
+}
+
+/// 事件文件（各进程追加写入）
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    path: PathBuf,
+}
+
+impl EventBus {
+    /// 默认路径
+    pub fn new() -> Self {
+        Self::in_dir(&crate::infra::paths::state_dir())
+    }
+
+    /// 数据目录中的事件文件
+    pub fn in_dir(dir: &Path) -> Self {
+        Self::at(&dir.join("events.jsonl"))
+    }
+
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 发布一条事件，失败只记日志
+    pub fn publish(&self, source: &str, agent_id: Option<&str>, data: impl Serialize) {
+        if let Err(e) = self.try_publish(source, agent_id, data) {
+            debug!(source = source, error = %e, "Failed to publish bus event");
+        }
+    }
+
+    fn try_publish(
+        &self,
+        source: &str,
+        agent_id: Option<&str>,
+        data: impl Serialize,
+    ) -> Result<()> {
+        let event = BusEvent {
+            ts: Utc::now(),
+            source: source.to_string(),
+            agent_id: agent_id.map(str::to_string),
+            data: serde_json::to_value(data)?,
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::metadata(&self.path).is_ok_and(|m| m.len() >= MAX_BYTES) {
+            fs::rename(&self.path, self.path.with_extension("jsonl.1"))?;
+        }
+        // 整行一次写入，O_APPEND 保证多进程写入的行不交错
+        let mut line = serde_json::to_string(&event)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// 读取当前文件中的全部事件，跳过无法解析的行
+    pub fn load(&self) -> Vec<BusEvent> {
+        fs::read_to_string(&self.path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 从文件末尾开始跟踪新事件
+    pub fn follow(&self) -> EventFollower {
+        EventFollower {
+            path: self.path.clone(),
+            offset: fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0),
+            partial: String::new(),
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 增量读取事件文件
+#[derive(Debug)]
+pub struct EventFollower {
+    path: PathBuf,
+    offset: u64,
+    /// 写到一半的行
+    partial: String,
+}
+
+impl EventFollower {
+    /// 读取上次之后新增的完整事件
+    pub fn read_new(&mut self) -> Vec<BusEvent> {
+        let Ok(mut file) = File::open(&self.path) else {
+            return Vec::new();
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.offset {
+            // 已轮转，从新文件开头读取
+            self.offset = 0;
+            self.partial.clear();
+        }
+        let mut buf = String::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err()
+            || file.read_to_string(&mut buf).is_err()
+        {
+            return Vec::new();
+        }
+        self.offset += buf.len() as u64;
+        self.partial.push_str(&buf);
+
+        let Some(end) = self.partial.rfind('\n') else {
+            return Vec::new();
+        };
+        let complete: String = self.partial.drain(..=end).collect();
+        complete
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_and_follow() {
+        let temp = tempfile::tempdir().unwrap();
+        let bus = EventBus::in_dir(temp.path());
+        bus.publish(
+            "state",
+            Some("cam-1"),
+            serde_json::json!({"to": "processing"}),
+        );
+
+        let mut follower = bus.follow();
+        assert!(follower.read_new().is_empty());
+
+        bus.publish("reply", Some("cam-1"), serde_json::json!({"reply": "y"}));
+        // 写到一半的行等写完再读
+        let mut file = OpenOptions::new().append(true).open(bus.path()).unwrap();
+        write!(
+            file,
+            "{{\"ts\":\"2026-03-01T12:00:00Z\",\"source\":\"ack\","
+        )
+        .unwrap();
+        let events = follower.read_new();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source, "reply");
+        assert_eq!(events[0].data["reply"], "y");
+
+        writeln!(file, "\"data\":{{}}}}").unwrap();
+        let events = follower.read_new();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source, "ack");
+        assert!(events[0].agent_id.is_none());
+        assert_eq!(bus.load().len(), 3);
+
+        // 轮转后从新文件开头读取
+        fs::rename(bus.path(), bus.path().with_extension("jsonl.1")).unwrap();
+        bus.publish("watch", None, serde_json::json!({}));
+        let events = follower.read_new();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source, "watch");
+    }
+}
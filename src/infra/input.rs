@@ -125,34 +125,7 @@ impl InputWaitDetector {
     /// 使用 AI 判断 Agent 是否在等待用户输入。
     /// 这种方式比硬编码模式更灵活，可以兼容不同的 AI 编码工具。
     pub fn detect_immediate(&self, output: &str) -> InputWaitResult {
-        let context = truncate_for_status(output);
-
-        match is_agent_processing(&context) {
-            AgentStatus::DecisionRequired => InputWaitResult {
-                is_waiting: true,
-                is_decision_required: true,
-                pattern_type: Some(InputWaitPattern::Other),
-                context,
-            },
-            AgentStatus::WaitingForInput => InputWaitResult {
-                is_waiting: true,
-                is_decision_required: false,
-                pattern_type: Some(InputWaitPattern::Other),
-                context,
-            },
-            AgentStatus::Processing | AgentStatus::Running => InputWaitResult {
-                is_waiting: false,
-                is_decision_required: false,
-                pattern_type: None,
-                context,
-            },
-            AgentStatus::Unknown => InputWaitResult {
-                is_waiting: false,
-                is_decision_required: false,
-                pattern_type: Some(InputWaitPattern::Unknown),
-                context,
-            },
-        }
+        detect_wait(output)
     }
 
     /// 清除 session 的状态
@@ -162,6 +135,38 @@ impl InputWaitDetector {
     }
 }
 
+/// 用 AI 判断 agent 是否在等待用户输入（无状态，可在任意线程调用）
+pub fn detect_wait(output: &str) -> InputWaitResult {
+    let context = truncate_for_status(output);
+
+    match is_agent_processing(&context) {
+        AgentStatus::DecisionRequired => InputWaitResult {
+            is_waiting: true,
+            is_decision_required: true,
+            pattern_type: Some(InputWaitPattern::Other),
+            context,
+        },
+        AgentStatus::WaitingForInput => InputWaitResult {
+            is_waiting: true,
+            is_decision_required: false,
+            pattern_type: Some(InputWaitPattern::Other),
+            context,
+        },
+        AgentStatus::Processing | AgentStatus::Running => InputWaitResult {
+            is_waiting: false,
+            is_decision_required: false,
+            pattern_type: None,
+            context,
+        },
+        AgentStatus::Unknown => InputWaitResult {
+            is_waiting: false,
+            is_decision_required: false,
+            pattern_type: Some(InputWaitPattern::Unknown),
+            context,
+        },
+    }
+}
+
 impl Default for InputWaitDetector {
     fn default() -> Self {
         Self::new()
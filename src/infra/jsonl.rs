@@ -3,7 +3,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::PathBuf;
 
 /// JSONL 事件类型
@@ -147,6 +147,38 @@ impl JsonlParser {
         Ok(events)
     }
 
+    /// 读取新增的完整行（末尾尚未写完的行留到下次读取，用于持续跟踪）
+    pub fn read_complete_events(&mut self) -> Result<Vec<JsonlEvent>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut file = File::open(&self.path)?;
+        let file_len = file.metadata()?.len();
+        // 文件被截断或重写，从头读
+        if file_len < self.position {
+            self.position = 0;
+        }
+        if file_len == self.position {
+            return Ok(Vec::new());
+        }
+
+        file.seek(SeekFrom::Start(self.position))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        let Some(last_newline) = buf.iter().rposition(|&b| b == b'\n') else {
+            return Ok(Vec::new());
+        };
+
+        let events = String::from_utf8_lossy(&buf[..last_newline])
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(Self::parse_line)
+            .collect();
+        self.position += last_newline as u64 + 1;
+        Ok(events)
+    }
+
     /// 转换原始消息为事件
     fn convert_raw_message(raw: &RawJsonlMessage) -> Option<JsonlEvent> {
         let msg_type = raw.msg_type.as_deref()?;
@@ -504,4 +536,26 @@ mod tests {
         assert!(JsonlParser::is_error_text("permission denied"));
         assert!(!JsonlParser::is_error_text("This is normal text"));
     }
+
+    #[test]
+    fn test_read_complete_events_waits_for_newline() {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let line = r#"{"type":"user","userMessage":{"content":"hi"}}"#;
+        let mut file = File::create(&path).unwrap();
+        write!(file, "{}\n{}", line, &line[..10]).unwrap();
+
+        let mut parser = JsonlParser::new(&path);
+        assert_eq!(parser.read_complete_events().unwrap().len(), 1);
+        // 写到一半的行不消费
+        assert!(parser.read_complete_events().unwrap().is_empty());
+
+        writeln!(file, "{}", &line[10..]).unwrap();
+        let events = parser.read_complete_events().unwrap();
+        assert!(
+            matches!(&events[..], [JsonlEvent::UserMessage { content, .. }] if content == "hi")
+        );
+    }
 }
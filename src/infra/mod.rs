@@ -1,29 +1,49 @@
 //! 基础设施层 - tmux、进程、终端、解析器
 
+pub mod agent_log;
+pub mod config_watch;
+pub mod docker;
+pub mod event_bus;
 pub mod input;
 pub mod jsonl;
+pub mod multiplexer;
+pub mod paths;
+pub mod ports;
+pub mod privacy;
 pub mod process;
+pub mod pty;
+pub mod sandbox;
+pub mod schema;
+pub mod screen;
 pub mod terminal;
+pub mod text;
 pub mod tmux;
+pub mod tmux_config;
+pub mod tmux_control;
+pub mod zellij;
 
 pub use input::{InputWaitDetector, InputWaitPattern, InputWaitResult};
 pub use jsonl::{extract_tool_target_from_input, format_tool_use, JsonlEvent, JsonlParser};
-pub use process::ProcessScanner;
-pub use tmux::TmuxManager;
+pub use multiplexer::{Multiplexer, MultiplexerKind};
+pub use process::{ProcessNode, ProcessScanner};
+pub use text::{display_width, pad_width, truncate_width};
+pub use tmux::{PaneCapture, TmuxManager};
+pub use tmux_config::TmuxConfig;
+pub use tmux_control::TmuxControl;
 
-/// 安全截断 UTF-8 字符串，避免在多字节字符中间截断
+/// 安全截断 UTF-8 字符串，避免在多字节字符或 emoji 字素簇中间截断
 ///
 /// # Arguments
 /// * `s` - 要截断的字符串
 /// * `max_chars` - 最大字符数（不是字节数）
 ///
 /// # Returns
-/// 如果字符串超过 max_chars，返回截断后的字符串加 "..."，否则返回原字符串
+/// 如果字符串超过 max_chars，返回截断后的字符串加 "..."，否则返回原字符串。
+/// 需要按终端显示宽度截断时使用 `truncate_width`。
 pub fn truncate_str(s: &str, max_chars: usize) -> String {
     let char_count = s.chars().count();
     if char_count > max_chars {
-        let truncated: String = s.chars().take(max_chars).collect();
-        format!("{}...", truncated)
+        format!("{}...", text::take_chars(s, max_chars))
     } else {
         s.to_string()
     }
@@ -0,0 +1,281 @@
+//! 终端复用器抽象 - tmux / zellij / GNU screen / 无复用器 PTY
+//!
+//! agent 运行在复用器的 session 中，watcher、reply、resume 等只通过 `Multiplexer` 操作 session，
+//! 不关心具体后端。在 config.json 中选择后端（默认 tmux）：
+//! ```json
+//! { "multiplexer": "zellij" }
+//! ```
+//! 命名按键统一使用 tmux 键名（`Enter`、`Down`、`C-c` 等），其他后端转换为对应的字节序列。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use crate::infra::pty::PtyManager;
+use crate::infra::screen::ScreenManager;
+use crate::infra::tmux::{PaneCapture, TmuxManager};
+use crate::infra::zellij::ZellijManager;
+
+/// 终端复用器 session 操作
+pub trait Multiplexer: Send + Sync {
+    /// 后端名称（`tmux` / `zellij` / `screen` / `pty`）
+    fn name(&self) -> &'static str;
+
+    /// 复用器是否已安装可用
+    fn is_available(&self) -> bool;
+
+    /// 创建后台 session，在 `working_dir` 中运行 `command`
+    fn create_session(&self, session_name: &str, working_dir: &str, command: &str) -> Result<()>;
+
+    fn session_exists(&self, session_name: &str) -> bool;
+
+    fn rename_session(&self, old_name: &str, new_name: &str) -> Result<()>;
+
+    /// 发送字面文本并回车
+    fn send_keys(&self, session_name: &str, keys: &str) -> Result<()>;
+
+    /// 发送字面文本（不回车）
+    fn send_keys_raw(&self, session_name: &str, keys: &str) -> Result<()>;
+
+    /// 发送命名按键（tmux 键名）
+    fn send_named_keys(&self, session_name: &str, keys: &[&str]) -> Result<()>;
+
+    /// 粘贴多行文本并回车（bracketed paste，换行不会被当作逐行提交）
+    fn paste_text(&self, session_name: &str, text: &str) -> Result<()> {
+        self.send_keys_raw(session_name, &bracketed_paste(text))?;
+        self.send_named_keys(session_name, &["Enter"])
+    }
+
+    /// 发送文本并回车：多行文本粘贴，单行直接输入
+    fn send_text(&self, session_name: &str, text: &str) -> Result<()> {
+        if text.contains('\n') {
+            self.paste_text(session_name, text)
+        } else {
+            self.send_keys(session_name, text)
+        }
+    }
+
+    /// 截取可见区域及之前 `lines` 行 history
+    fn capture_pane(&self, session_name: &str, lines: u32) -> Result<String>;
+
+    /// 增量截取，不支持增量的后端每次完整截取
+    fn capture_pane_incremental<'a>(
+        &self,
+        session_name: &str,
+        cache: &'a mut PaneCapture,
+    ) -> Result<&'a str> {
+        let output = self.capture_pane(session_name, cache.history_lines())?;
+        cache.replace(output);
+        Ok(cache.as_str())
+    }
+
+    fn kill_session(&self, session_name: &str) -> Result<()>;
+
+    /// session 中运行的顶层进程 PID（agent 进程树的根），后端不支持时为 None
+    fn session_pid(&self, _session_name: &str) -> Option<u32> {
+        None
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>>;
+
+    /// 列出所有 cam- 前缀的 session
+    fn list_cam_sessions(&self) -> Result<Vec<String>> {
+        Ok(self
+            .list_sessions()?
+            .into_iter()
+            .filter(|s| s.starts_with("cam-"))
+            .collect())
+    }
+
+    /// 附着到 session 的命令
+    fn attach_command(&self, session_name: &str) -> Command;
+
+    /// 打印给用户的附着提示
+    fn attach_hint(&self, session_name: &str) -> String;
+}
+
+/// 用 bracketed paste 控制序列包裹文本，去掉文本中可能提前结束粘贴的结束序列
+pub fn bracketed_paste(text: &str) -> String {
+    format!("\x1b[200~{}\x1b[201~", text.replace("\x1b[201~", ""))
+}
+
+/// 复用器后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultiplexerKind {
+    #[default]
+    Tmux,
+    Zellij,
+    Screen,
+    /// CAM 自己持有的 PTY，不依赖任何复用器
+    Pty,
+}
+
+impl MultiplexerKind {
+    /// 解析后端名称
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "tmux" => Some(Self::Tmux),
+            "zellij" => Some(Self::Zellij),
+            "screen" | "gnu-screen" => Some(Self::Screen),
+            "pty" | "headless" => Some(Self::Pty),
+            _ => None,
+        }
+    }
+
+    /// 读取 config.json 的 `multiplexer`，未配置或无法识别时为 tmux
+    pub fn from_config() -> Self {
+        crate::infra::paths::config_section::<String>("multiplexer")
+            .and_then(|name| Self::parse(&name))
+            .unwrap_or_default()
+    }
+
+    /// 创建该后端的复用器
+    pub fn create(self) -> Arc<dyn Multiplexer> {
+        match self {
+            Self::Tmux => Arc::new(TmuxManager::new()),
+            Self::Zellij => Arc::new(ZellijManager::new()),
+            Self::Screen => Arc::new(ScreenManager::new()),
+            Self::Pty => Arc::new(PtyManager::new()),
+        }
+    }
+}
+
+/// 按配置创建复用器
+pub fn from_config() -> Arc<dyn Multiplexer> {
+    MultiplexerKind::from_config().create()
+}
+
+/// 按配置生成附着提示
+pub fn attach_hint(session_name: &str) -> String {
+    from_config().attach_hint(session_name)
+}
+
+/// tmux 键名对应的终端输入字节，无法识别时返回 None
+pub fn named_key_bytes(key: &str) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match key {
+        "Enter" => b"\r",
+        "Escape" => b"\x1b",
+        "Tab" => b"\t",
+        "BTab" => b"\x1b[Z",
+        "BSpace" => b"\x7f",
+        "Space" => b" ",
+        "Up" => b"\x1b[A",
+        "Down" => b"\x1b[B",
+        "Right" => b"\x1b[C",
+        "Left" => b"\x1b[D",
+        "Home" => b"\x1b[H",
+        "End" => b"\x1b[F",
+        _ => {
+            // C-a ~ C-z
+            let letter = key.strip_prefix("C-")?;
+            let &[c] = letter.as_bytes() else {
+                return None;
+            };
+            if !c.is_ascii_alphabetic() {
+                return None;
+            }
+            return Some(vec![c.to_ascii_lowercase() - b'a' + 1]);
+        }
+    };
+    Some(bytes.to_vec())
+}
+
+/// 取 `full` 的最后 `visible_rows + history_lines` 行（与 tmux `capture-pane -S -N` 一致）
+pub(crate) fn tail_lines(full: &str, visible_rows: usize, history_lines: u32) -> String {
+    let lines: Vec<&str> = full.lines().collect();
+    let keep = visible_rows + history_lines as usize;
+    let start = lines.len().saturating_sub(keep);
+    let mut output = lines[start..].join("\n");
+    output.push('\n');
+    output
+}
+
+/// 截屏临时文件路径（进程内唯一）
+pub(crate) fn dump_path(backend: &str) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    std::env::temp_dir().join(format!(
+        "cam-{}-{}-{}.txt",
+        backend,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// 等待复用器写出截屏文件后读取并删除（写出可能是异步的）
+pub(crate) fn read_dump(path: &Path) -> Result<String> {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        if let Ok(bytes) = std::fs::read(path) {
+            if !bytes.is_empty() || Instant::now() >= deadline {
+                let _ = std::fs::remove_file(path);
+                return Ok(String::from_utf8_lossy(&bytes).into_owned());
+            }
+        }
+        if Instant::now() >= deadline {
+            return Err(anyhow!("Screen dump not written: {}", path.display()));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// 执行命令，失败时返回带 stderr 的错误
+pub(crate) fn run_checked(command: &mut Command, what: &str) -> Result<String> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(anyhow!(
+            "Failed to {}: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracketed_paste() {
+        assert_eq!(
+            bracketed_paste("line 1\nline 2"),
+            "\x1b[200~line 1\nline 2\x1b[201~"
+        );
+        assert_eq!(bracketed_paste("a\x1b[201~b"), "\x1b[200~ab\x1b[201~");
+    }
+
+    #[test]
+    fn test_kind_and_named_keys() {
+        assert_eq!(
+            MultiplexerKind::parse("Zellij"),
+            Some(MultiplexerKind::Zellij)
+        );
+        assert_eq!(
+            MultiplexerKind::parse("screen"),
+            Some(MultiplexerKind::Screen)
+        );
+        assert_eq!(
+            MultiplexerKind::parse("headless"),
+            Some(MultiplexerKind::Pty)
+        );
+        assert_eq!(MultiplexerKind::parse("byobu"), None);
+        assert_eq!(MultiplexerKind::Tmux.create().name(), "tmux");
+
+        assert_eq!(named_key_bytes("Enter"), Some(b"\r".to_vec()));
+        assert_eq!(named_key_bytes("Down"), Some(b"\x1b[B".to_vec()));
+        assert_eq!(named_key_bytes("C-c"), Some(vec![3]));
+        assert_eq!(named_key_bytes("C-"), None);
+        assert_eq!(named_key_bytes("F13"), None);
+    }
+
+    #[test]
+    fn test_tail_lines() {
+        assert_eq!(tail_lines("a\nb\nc\nd\n", 2, 1), "b\nc\nd\n");
+        assert_eq!(tail_lines("a\nb\n", 5, 5), "a\nb\n");
+    }
+}
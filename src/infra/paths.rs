@@ -0,0 +1,275 @@
+//! 配置与状态目录 - 支持用 `--config`/`--state-dir` 或环境变量运行隔离的 CAM 实例
+//!
+//! 默认 config.json 与所有状态文件（agents.json、去重状态、日志等）都在
+//! `~/.config/code-agent-monitor`。
+//!
+//! - `--state-dir <DIR>` / `CAM_STATE_DIR`：状态目录；单独指定时 config.json 也从该目录读取
+//! - `--config <FILE>` / `CAM_CONFIG`：config.json 路径，模板等用户配置放在同一目录
+//!
+//! 命令行参数在启动时写入对应的环境变量，子进程（watcher daemon 等）自动继承；
+//! tmux 中启动的 agent 不继承 cam 的环境，由 [`with_child_env`] 在命令前导出。
+//!
+//! 各模块读取 config.json 中自己的一节都通过 [`config_section`]，保证 `--config` 覆盖对所有配置生效。
+//!
+//! 旧版本把状态放在 `~/.claude-monitor`，配置备份放在平台配置目录（macOS 为
+//! `~/Library/Application Support/code-agent-monitor`）。默认实例启动时由
+//! [`consolidate_legacy`] 把其中由 CAM 写入的文件（[`LEGACY_ENTRIES`]）移入状态目录；状态目录中
+//! 已有同名文件时保留新文件，旧文件留在原处。`~/.claude-monitor` 这个名字也可能被其他工具使用，
+//! 其余文件一律不动。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// 覆盖 config.json 路径的环境变量
+pub const CONFIG_ENV: &str = "CAM_CONFIG";
+/// 覆盖状态目录的环境变量
+pub const STATE_DIR_ENV: &str = "CAM_STATE_DIR";
+
+fn env_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 默认目录 `~/.config/code-agent-monitor`
+pub fn default_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config/code-agent-monitor")
+}
+
+/// 状态目录
+pub fn state_dir() -> PathBuf {
+    env_path(STATE_DIR_ENV).unwrap_or_else(default_dir)
+}
+
+/// config.json 路径
+pub fn config_path() -> PathBuf {
+    env_path(CONFIG_ENV).unwrap_or_else(|| state_dir().join("config.json"))
+}
+
+/// 读取 config.json 中 `key` 一节并反序列化
+///
+/// 文件不存在、不是有效 JSON、缺少该节或格式不符时返回 None，默认值由调用方决定。
+pub fn config_section<T: DeserializeOwned>(key: &str) -> Option<T> {
+    config_section_in(&config_path(), key)
+}
+
+/// 从指定配置文件读取一节
+pub fn config_section_in<T: DeserializeOwned>(path: &Path, key: &str) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    serde_json::from_value(json.get(key)?.clone()).ok()
+}
+
+/// 用户配置目录（config.json 所在目录，存放 templates 等）
+pub fn config_dir() -> PathBuf {
+    config_path()
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_else(default_dir)
+}
+
+/// 在 shell 命令前导出已设置的覆盖变量，未设置时原样返回
+pub fn with_child_env(command: &str) -> String {
+    let exports: Vec<String> = [STATE_DIR_ENV, CONFIG_ENV]
+        .into_iter()
+        .filter_map(|name| {
+            let path = env_path(name)?;
+            Some(format!(
+                "export {}='{}'; ",
+                name,
+                path.to_string_lossy().replace('\'', r"'\''")
+            ))
+        })
+        .collect();
+    format!("{}{}", exports.concat(), command)
+}
+
+/// 旧版本的状态目录（存在与否都返回，不含当前状态目录）
+pub fn legacy_dirs() -> Vec<PathBuf> {
+    let current = default_dir();
+    let candidates = [
+        dirs::home_dir().map(|home| home.join(".claude-monitor")),
+        dirs::config_dir().map(|dir| dir.join("code-agent-monitor")),
+    ];
+    let mut legacy: Vec<PathBuf> = Vec::new();
+    for dir in candidates.into_iter().flatten() {
+        if dir != current && !legacy.contains(&dir) {
+            legacy.push(dir);
+        }
+    }
+    legacy
+}
+
+/// 旧目录中由 CAM 写入、需要迁移的文件和目录，其余条目不动
+pub const LEGACY_ENTRIES: &[&str] = &[
+    "agents.json",
+    "conversation_state.json",
+    "dedup_state.json",
+    "throttle_state.json",
+    "notifications.jsonl",
+    "config.json",
+    "backups",
+];
+
+/// 旧文件的处理结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum LegacyStatus {
+    /// 已移入状态目录（dry-run 时表示将移动）
+    Moved,
+    /// 状态目录已有同名文件，旧文件保留在原处
+    Kept,
+    Failed {
+        error: String,
+    },
+}
+
+/// 一个旧文件（或目录）的迁移
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LegacyEntry {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    #[serde(flatten)]
+    pub status: LegacyStatus,
+}
+
+/// 把旧目录中 [`LEGACY_ENTRIES`] 列出的条目移入 `state_dir`，移空的旧目录会被删除
+pub fn consolidate_legacy(state_dir: &Path, legacy: &[PathBuf], dry_run: bool) -> Vec<LegacyEntry> {
+    let mut entries = Vec::new();
+    for dir in legacy.iter().filter(|dir| dir.is_dir()) {
+        for name in LEGACY_ENTRIES {
+            let from = dir.join(name);
+            if fs::symlink_metadata(&from).is_err() {
+                continue;
+            }
+            let to = state_dir.join(name);
+            let status = if to.exists() {
+                LegacyStatus::Kept
+            } else if dry_run {
+                LegacyStatus::Moved
+            } else {
+                match fs::create_dir_all(state_dir).and_then(|_| fs::rename(&from, &to)) {
+                    Ok(()) => LegacyStatus::Moved,
+                    Err(e) => LegacyStatus::Failed {
+                        error: e.to_string(),
+                    },
+                }
+            };
+            entries.push(LegacyEntry { from, to, status });
+        }
+        if !dry_run {
+            // 只删除空目录
+            let _ = fs::remove_dir(dir);
+        }
+    }
+    entries
+}
+
+/// 默认实例启动时合并旧目录（隔离实例不读取旧目录）
+pub fn consolidate_default_legacy(dry_run: bool) -> Vec<LegacyEntry> {
+    if state_dir() != default_dir() {
+        return Vec::new();
+    }
+    consolidate_legacy(&state_dir(), &legacy_dirs(), dry_run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_paths() {
+        // 测试进程未设置覆盖时使用默认目录
+        if env_path(STATE_DIR_ENV).is_none() && env_path(CONFIG_ENV).is_none() {
+            assert_eq!(state_dir(), default_dir());
+            assert_eq!(config_path(), default_dir().join("config.json"));
+            assert_eq!(config_dir(), default_dir());
+            assert_eq!(with_child_env("claude"), "claude");
+        }
+    }
+
+    #[test]
+    fn test_config_section_in() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("config.json");
+        assert_eq!(config_section_in::<bool>(&path, "tool_batch_summary"), None);
+
+        fs::write(
+            &path,
+            r#"{"tool_batch_summary": true, "stuck_after_mins": "soon"}"#,
+        )
+        .unwrap();
+        assert_eq!(config_section_in(&path, "tool_batch_summary"), Some(true));
+        // 格式不符和缺失的节都返回 None
+        assert_eq!(config_section_in::<u64>(&path, "stuck_after_mins"), None);
+        assert_eq!(config_section_in::<u64>(&path, "idle_after_mins"), None);
+
+        fs::write(&path, "{not json").unwrap();
+        assert_eq!(config_section_in::<bool>(&path, "tool_batch_summary"), None);
+    }
+
+    #[test]
+    fn test_consolidate_legacy() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = temp.path().join("state");
+        let old = temp.path().join(".claude-monitor");
+        let backups = temp.path().join("platform");
+        fs::create_dir_all(&state).unwrap();
+        fs::create_dir_all(old.join("logs")).unwrap();
+        fs::write(old.join("other-tool.db"), "not ours").unwrap();
+        fs::create_dir_all(backups.join("backups/claude")).unwrap();
+        fs::write(old.join("agents.json"), "old agents").unwrap();
+        fs::write(old.join("dedup_state.json"), "old dedup").unwrap();
+        fs::write(state.join("dedup_state.json"), "new dedup").unwrap();
+        let legacy = [old.clone(), backups.clone(), temp.path().join("missing")];
+
+        // dry-run 不改动文件
+        let planned = consolidate_legacy(&state, &legacy, true);
+        assert_eq!(planned.len(), 3);
+        assert!(old.join("agents.json").exists());
+
+        let entries = consolidate_legacy(&state, &legacy, false);
+        let status = |name: &str| {
+            entries
+                .iter()
+                .find(|e| e.from.file_name().unwrap() == name)
+                .map(|e| e.status.clone())
+                .unwrap()
+        };
+        assert_eq!(status("agents.json"), LegacyStatus::Moved);
+        assert_eq!(status("backups"), LegacyStatus::Moved);
+        assert_eq!(status("dedup_state.json"), LegacyStatus::Kept);
+        assert_eq!(
+            fs::read_to_string(state.join("agents.json")).unwrap(),
+            "old agents"
+        );
+        assert_eq!(
+            fs::read_to_string(state.join("dedup_state.json")).unwrap(),
+            "new dedup"
+        );
+        assert!(state.join("backups/claude").is_dir());
+        // 不是 CAM 写入的条目留在原处
+        assert!(entries
+            .iter()
+            .all(|e| e.from.file_name().unwrap() != "logs"));
+        assert!(old.join("logs").is_dir());
+        assert_eq!(
+            fs::read_to_string(old.join("other-tool.db")).unwrap(),
+            "not ours"
+        );
+        assert!(!state.join("other-tool.db").exists());
+        // 有冲突文件或其他条目的旧目录保留，移空的旧目录删除
+        assert!(old.join("dedup_state.json").exists());
+        assert!(!backups.exists());
+
+        // 再次运行只剩冲突文件
+        let again = consolidate_legacy(&state, &legacy, false);
+        assert_eq!(again.len(), 1);
+        assert_eq!(again[0].status, LegacyStatus::Kept);
+    }
+}
@@ -0,0 +1,140 @@
+//! 监听端口检测 - 找出 agent 进程树正在监听的 TCP 端口
+//!
+//! agent 常启动开发服务器，多个 agent 同时运行时难以分辨哪个端口属于谁。
+//! Linux 上读取 `/proc/net/tcp{,6}` 中 LISTEN 状态的 socket，再按 `/proc/<pid>/fd` 匹配 inode；
+//! 其他平台（macOS）使用 `lsof`。
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// `/proc/net/tcp` 中 LISTEN 的状态码
+const TCP_LISTEN: &str = "0A";
+
+/// 这些进程正在监听的 TCP 端口（升序去重），检测失败时为空
+pub fn listening_ports(pids: &[u32]) -> Vec<u16> {
+    if pids.is_empty() {
+        return Vec::new();
+    }
+    if Path::new("/proc/net/tcp").exists() {
+        listening_ports_proc(pids)
+    } else {
+        listening_ports_lsof(pids)
+    }
+}
+
+fn listening_ports_proc(pids: &[u32]) -> Vec<u16> {
+    let mut inodes = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(content) = fs::read_to_string(table) {
+            inodes.extend(parse_proc_net_tcp(&content));
+        }
+    }
+
+    let mut ports = BTreeSet::new();
+    for pid in pids {
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(link) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = link
+                .to_str()
+                .and_then(|l| l.strip_prefix("socket:["))
+                .and_then(|l| l.strip_suffix(']'))
+                .and_then(|l| l.parse::<u64>().ok());
+            if let Some(port) = inode.and_then(|inode| inodes.get(&inode)) {
+                ports.insert(*port);
+            }
+        }
+    }
+    ports.into_iter().collect()
+}
+
+fn listening_ports_lsof(pids: &[u32]) -> Vec<u16> {
+    let pid_list = pids
+        .iter()
+        .map(|pid| pid.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    Command::new("lsof")
+        .args(["-nP", "-a", "-iTCP", "-sTCP:LISTEN", "-p", &pid_list, "-Fn"])
+        .output()
+        .map(|output| parse_lsof_ports(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// 解析 `/proc/net/tcp` 格式，返回 LISTEN socket 的 inode → 本地端口
+pub fn parse_proc_net_tcp(content: &str) -> HashMap<u64, u16> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(3) != Some(&TCP_LISTEN) {
+                return None;
+            }
+            let port = fields.get(1)?.rsplit_once(':')?.1;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((inode, port))
+        })
+        .collect()
+}
+
+/// 解析 `lsof -Fn` 输出中的端口（`n*:3000`、`n[::1]:8080`），升序去重
+pub fn parse_lsof_ports(output: &str) -> Vec<u16> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix('n')?.rsplit_once(':')?.1.parse().ok())
+        .collect::<BTreeSet<u16>>()
+        .into_iter()
+        .collect()
+}
+
+/// 端口列表文本，如 `:3000 :5173`
+pub fn format_ports(ports: &[u16]) -> String {
+    ports
+        .iter()
+        .map(|port| format!(":{}", port))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1435 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41240 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:A1B2 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 41300 1 0000000000000000 20 4 30 10 -1";
+        let inodes = parse_proc_net_tcp(content);
+        assert_eq!(inodes.len(), 2);
+        assert_eq!(inodes[&41234], 3000);
+        assert_eq!(inodes[&41240], 5173);
+    }
+
+    #[test]
+    fn test_parse_lsof_ports() {
+        let output = "p4242\nf21\nn*:3000\nf22\nn[::1]:8080\np4243\nf7\nn127.0.0.1:3000\n";
+        assert_eq!(parse_lsof_ports(output), vec![3000, 8080]);
+        assert!(parse_lsof_ports("").is_empty());
+        assert_eq!(format_ports(&[3000, 8080]), ":3000 :8080");
+    }
+
+    #[test]
+    fn test_listening_ports_of_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        if Path::new("/proc/net/tcp").exists() {
+            assert!(listening_ports(&[std::process::id()]).contains(&port));
+        }
+        assert!(listening_ports(&[]).is_empty());
+    }
+}
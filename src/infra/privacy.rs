@@ -0,0 +1,57 @@
+//! 快照隐私模式 - 终端原始快照不离开本机
+//!
+//! config.json 中 `"privacy": {"local_snapshots": true}` 开启后：
+//! - 通知 payload 和消息不附带终端快照，只包含本地提取的摘要，并提示用 `cam show <id>` 在本机查看
+//! - 读取快照的 AI 任务（提取、状态判断、摘要）只使用 `providers` 中本机地址的模型，
+//!   没有时退回本地规则；提取阶梯改用字面相似度回退嵌入
+//! - 反复错误提交的 issue 不附带快照
+
+use std::net::IpAddr;
+
+use serde::Deserialize;
+
+/// 隐私配置（config.json 的 `privacy`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// 终端快照只留在本机
+    pub local_snapshots: bool,
+}
+
+impl PrivacyConfig {
+    /// 读取 config.json，缺失或无效时关闭
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("privacy").unwrap_or_default()
+    }
+}
+
+/// URL 是否指向本机（localhost、127.0.0.0/8、::1）
+pub fn is_local_url(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://localhost:11434/v1"));
+        assert!(is_local_url("http://127.0.0.1:8080"));
+        assert!(is_local_url("http://[::1]:8080/v1/messages"));
+        assert!(is_local_url("http://user:pw@LOCALHOST/v1"));
+        assert!(!is_local_url("https://api.anthropic.com/v1/messages"));
+        assert!(!is_local_url("https://localhost.example.com"));
+        assert!(!is_local_url("http://10.0.0.2:11434"));
+    }
+}
@@ -1,9 +1,10 @@
 //! 进程扫描模块 - 扫描系统中的 AI 编码代理进程
 
-use crate::agent::AgentType;
+use crate::agent::{AgentRecord, AgentType};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sysinfo::{Pid, Process, System};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessStatus, ProcessesToUpdate, Signal, System};
 
 /// 代理进程信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +20,82 @@ pub struct AgentInfo {
     pub cpu_usage: f32,
     pub memory_mb: u64,
     pub start_time: u64,
+    /// 上下文窗口使用率（仅 CAM 管理的 agent，见 [`attach_agent_records`]）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_usage: Option<u8>,
+    /// 进程树正在监听的 TCP 端口（仅 CAM 管理的 agent）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<u16>,
+}
+
+/// 进程树中的一个进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessNode {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub command: String,
+    /// 相对树根的层级（根为 0）
+    pub depth: usize,
+    /// 启动时间（Unix 秒），终止前用来确认 PID 没有被复用
+    #[serde(default)]
+    pub start_time: u64,
+}
+
+/// 从进程列表中取出以 `root` 为根的子树，按深度优先排列（同一层按 PID 排序）
+pub fn build_process_tree(processes: &[ProcessNode], root: u32) -> Vec<ProcessNode> {
+    let Some(root_node) = processes.iter().find(|p| p.pid == root) else {
+        return Vec::new();
+    };
+    let mut tree = Vec::new();
+    let mut stack = vec![(root_node, 0)];
+    while let Some((node, depth)) = stack.pop() {
+        tree.push(ProcessNode {
+            depth,
+            ..node.clone()
+        });
+        let mut children: Vec<&ProcessNode> = processes
+            .iter()
+            .filter(|p| p.parent == Some(node.pid) && p.pid != node.pid)
+            .collect();
+        children.sort_by_key(|p| std::cmp::Reverse(p.pid));
+        stack.extend(children.into_iter().map(|child| (child, depth + 1)));
+    }
+    tree
+}
+
+/// 从 CAM 的 agent 记录补充上下文使用率和监听端口（按 session ID 匹配，其次按类型和工作目录）
+pub fn attach_agent_records(agents: &mut [AgentInfo], records: &[AgentRecord]) {
+    for agent in agents {
+        let record = records
+            .iter()
+            .find(|r| r.session_id.is_some() && r.session_id == agent.session_id)
+            .or_else(|| {
+                records.iter().find(|r| {
+                    r.agent_type == agent.agent_type && r.project_path == agent.working_dir
+                })
+            });
+        agent.context_usage = record.and_then(|r| r.context_usage);
+        agent.ports = record.map(|r| r.ports.clone()).unwrap_or_default();
+    }
+}
+
+/// `kill_processes` 发送 SIGTERM 后等待进程退出的时间，超时再 SIGKILL
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// 重新读取这些 PID，返回仍是同一进程（启动时间一致、不是僵尸进程）的
+fn same_processes<'a>(tree: &'a [ProcessNode], pids: &[Pid]) -> (System, Vec<&'a ProcessNode>) {
+    let mut current = System::new();
+    current.refresh_processes(ProcessesToUpdate::Some(pids));
+    let alive = tree
+        .iter()
+        .filter(|node| {
+            current.process(Pid::from_u32(node.pid)).is_some_and(|p| {
+                p.start_time() == node.start_time && p.status() != ProcessStatus::Zombie
+            })
+        })
+        .collect();
+    (current, alive)
 }
 
 /// 进程扫描器
@@ -72,6 +149,75 @@ impl ProcessScanner {
         }
     }
 
+    /// 以 `root` 为根的进程树（包括 agent 启动的开发服务器、测试 watcher 等子进程）
+    pub fn process_tree(&self, root: u32) -> Vec<ProcessNode> {
+        let processes: Vec<ProcessNode> = self
+            .system
+            .processes()
+            .iter()
+            // Linux 上线程也作为进程列出
+            .filter(|(_, process)| process.thread_kind().is_none())
+            .map(|(pid, process)| ProcessNode {
+                pid: pid.as_u32(),
+                parent: process.parent().map(|p| p.as_u32()),
+                name: process.name().to_string_lossy().to_string(),
+                command: process
+                    .cmd()
+                    .iter()
+                    .map(|s| s.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                depth: 0,
+                start_time: process.start_time(),
+            })
+            .collect();
+        build_process_tree(&processes, root)
+    }
+
+    /// 终止这些进程（先子后父，避免子进程被重新挂到 init 下），返回实际终止的 PID
+    ///
+    /// 终止前重新读取每个 PID，已退出或启动时间与记录不同（PID 已被复用）的进程跳过。
+    /// 先发送 SIGTERM，[`KILL_GRACE`] 内未退出的再 SIGKILL。
+    pub fn kill_processes(&self, tree: &[ProcessNode]) -> Vec<u32> {
+        let pids: Vec<Pid> = tree.iter().map(|node| Pid::from_u32(node.pid)).collect();
+        let (current, alive) = same_processes(tree, &pids);
+        let killed: Vec<&ProcessNode> = alive
+            .into_iter()
+            .rev()
+            .filter(|node| {
+                current
+                    .process(Pid::from_u32(node.pid))
+                    .is_some_and(|process| {
+                        process
+                            .kill_with(Signal::Term)
+                            .unwrap_or_else(|| process.kill())
+                    })
+            })
+            .collect();
+
+        let deadline = Instant::now() + KILL_GRACE;
+        loop {
+            let (current, remaining) = same_processes(tree, &pids);
+            let remaining: Vec<_> = remaining
+                .into_iter()
+                .filter(|node| killed.iter().any(|k| k.pid == node.pid))
+                .collect();
+            if remaining.is_empty() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                for node in remaining.into_iter().rev() {
+                    if let Some(process) = current.process(Pid::from_u32(node.pid)) {
+                        process.kill();
+                    }
+                }
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        killed.into_iter().map(|node| node.pid).collect()
+    }
+
     /// 解析进程信息，判断是否为 AI 代理
     fn parse_agent_process(&self, pid: &Pid, process: &Process) -> Option<AgentInfo> {
         let name = process.name().to_string_lossy().to_lowercase();
@@ -121,6 +267,8 @@ impl ProcessScanner {
             cpu_usage: process.cpu_usage(),
             memory_mb: process.memory() / 1024 / 1024,
             start_time: process.start_time(),
+            context_usage: None,
+            ports: Vec::new(),
         })
     }
 
@@ -162,4 +310,94 @@ mod tests {
         // 测试不会崩溃
         println!("Found {} agents", agents.len());
     }
+
+    #[test]
+    fn test_build_process_tree() {
+        let node = |pid, parent| ProcessNode {
+            pid,
+            parent,
+            name: format!("p{}", pid),
+            command: String::new(),
+            depth: 0,
+            start_time: 0,
+        };
+        let processes = vec![
+            node(1, None),
+            node(10, Some(1)),
+            node(30, Some(10)),
+            node(20, Some(10)),
+            node(21, Some(20)),
+            node(40, Some(1)),
+        ];
+
+        let tree = build_process_tree(&processes, 10);
+        let shape: Vec<_> = tree.iter().map(|n| (n.pid, n.depth)).collect();
+        assert_eq!(shape, vec![(10, 0), (20, 1), (21, 2), (30, 1)]);
+        assert!(build_process_tree(&processes, 99).is_empty());
+
+        let scanner = ProcessScanner::new();
+        let own = scanner.process_tree(std::process::id());
+        assert_eq!(own.first().map(|n| n.pid), Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_kill_processes_checks_start_time() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let scanner = ProcessScanner::new();
+        let tree = scanner.process_tree(child.id());
+        assert_eq!(tree.len(), 1);
+
+        // 启动时间不同视为 PID 已被复用，不终止
+        let reused = ProcessNode {
+            start_time: tree[0].start_time + 1,
+            ..tree[0].clone()
+        };
+        assert!(scanner.kill_processes(&[reused]).is_empty());
+        assert!(child.try_wait().unwrap().is_none());
+
+        assert_eq!(scanner.kill_processes(&tree), vec![child.id()]);
+        assert!(child.wait().is_ok());
+    }
+
+    #[test]
+    fn test_attach_agent_records() {
+        let info = |session_id: Option<&str>, working_dir: &str| AgentInfo {
+            pid: 1,
+            agent_type: AgentType::Claude,
+            command: "claude".to_string(),
+            args: Vec::new(),
+            working_dir: working_dir.to_string(),
+            session_id: session_id.map(str::to_string),
+            model: None,
+            status: "Run".to_string(),
+            cpu_usage: 0.0,
+            memory_mb: 0,
+            start_time: 0,
+            context_usage: None,
+            ports: Vec::new(),
+        };
+        let records: Vec<AgentRecord> = serde_json::from_value(serde_json::json!([
+            {"agent_id": "cam-1", "agent_type": "claude", "project_path": "/work/a",
+             "tmux_session": "cam-1", "session_id": "s-1", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing", "context_usage": 92},
+            {"agent_id": "cam-2", "agent_type": "claude", "project_path": "/work/b",
+             "tmux_session": "cam-2", "started_at": "2026-01-01T00:00:00Z",
+             "status": "processing", "context_usage": 40, "ports": [3000, 8080]},
+        ]))
+        .unwrap();
+
+        let mut agents = vec![
+            info(Some("s-1"), "/elsewhere"),
+            info(None, "/work/b"),
+            info(None, "/work/c"),
+        ];
+        attach_agent_records(&mut agents, &records);
+        let usage: Vec<_> = agents.iter().map(|a| a.context_usage).collect();
+        assert_eq!(usage, vec![Some(92), Some(40), None]);
+        assert!(agents[0].ports.is_empty());
+        assert_eq!(agents[1].ports, vec![3000, 8080]);
+    }
 }
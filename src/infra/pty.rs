@@ -0,0 +1,579 @@
+//! 无复用器的 PTY 后端 - agent 运行在 CAM 自己持有的伪终端中，适合没有 tmux 的服务器/CI
+//!
+//! `create_session` 启动一个脱离终端的 `cam __pty-host` 进程：它用 `portable-pty` 运行命令，
+//! 把输出写入内存环形缓冲区，并在 `~/.config/code-agent-monitor/pty/<session>.sock` 上
+//! 接受单行 JSON 请求。截屏从缓冲区渲染为纯文本，回复直接写入 PTY master；
+//! `cam attach` 使用 tail 模式，先回放缓冲区再持续转发新输出。
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::infra::multiplexer::{named_key_bytes, tail_lines, Multiplexer};
+use crate::infra::schema::default_data_dir;
+
+/// PTY 行数，即截屏的可见区域
+pub const PTY_ROWS: u16 = 50;
+/// PTY 列数
+pub const PTY_COLS: u16 = 200;
+/// 环形缓冲区容量（字节）
+const BUFFER_CAPACITY: usize = 1024 * 1024;
+/// attach 时断开连接的按键（Ctrl-]）
+const DETACH_KEY: u8 = 0x1d;
+
+/// 发给 PTY host 的请求（每个连接一行 JSON）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum PtyRequest {
+    /// 截取可见区域及之前 `lines` 行
+    Capture {
+        lines: u32,
+    },
+    /// 写入 PTY
+    Write {
+        data: String,
+    },
+    Rename {
+        name: String,
+    },
+    Kill,
+    /// 回放缓冲区后持续输出原始字节，直到连接断开
+    Tail,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum PtyResponse {
+    Ok {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// PTY 输出环形缓冲区，超出容量时丢弃最旧的字节
+#[derive(Debug)]
+pub struct PtyBuffer {
+    bytes: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl PtyBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            bytes: VecDeque::with_capacity(capacity.min(64 * 1024)),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend(chunk);
+        let excess = self.bytes.len().saturating_sub(self.capacity);
+        self.bytes.drain(..excess);
+    }
+
+    pub fn contents(&self) -> Vec<u8> {
+        let (front, back) = self.bytes.as_slices();
+        [front, back].concat()
+    }
+}
+
+/// 把终端输出渲染为纯文本
+///
+/// 丢弃 CSI/OSC 等转义序列；`\r` 回到行首覆盖写，退格左移一列，`ESC [ K` 清除到行尾。
+/// 不处理光标上下移动，全屏 TUI 的重绘会按输出顺序依次出现。
+pub fn render_text(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let mut lines: Vec<String> = Vec::new();
+    let mut line: Vec<char> = Vec::new();
+    let mut col = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                if skip_escape(&mut chars) == Some('K') {
+                    line.truncate(col);
+                }
+            }
+            '\n' => {
+                lines.push(line.drain(..).collect());
+                col = 0;
+            }
+            '\r' => col = 0,
+            '\x08' => col = col.saturating_sub(1),
+            c if c.is_control() && c != '\t' => {}
+            c => {
+                if col < line.len() {
+                    line[col] = c;
+                } else {
+                    line.push(c);
+                }
+                col += 1;
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line.into_iter().collect());
+    }
+
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+/// 跳过 ESC 之后的转义序列，返回 CSI 序列的结束字符
+fn skip_escape(chars: &mut std::str::Chars<'_>) -> Option<char> {
+    match chars.next()? {
+        '[' => chars.find(|c| ('@'..='~').contains(c)),
+        ']' => {
+            // OSC 以 BEL 或 ESC \ 结束
+            while let Some(c) = chars.next() {
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' {
+                    chars.next();
+                    break;
+                }
+            }
+            None
+        }
+        '(' | ')' => {
+            chars.next();
+            None
+        }
+        _ => None,
+    }
+}
+
+fn pty_dir() -> PathBuf {
+    default_data_dir().join("pty")
+}
+
+fn socket_path(session_name: &str) -> PathBuf {
+    pty_dir().join(format!("{}.sock", session_name))
+}
+
+fn connect(session_name: &str) -> Result<UnixStream> {
+    UnixStream::connect(socket_path(session_name))
+        .map_err(|e| anyhow!("PTY session {} not reachable: {}", session_name, e))
+}
+
+fn write_request(stream: &mut UnixStream, request: &PtyRequest) -> Result<()> {
+    serde_json::to_writer(&mut *stream, request)?;
+    stream.write_all(b"\n")?;
+    Ok(())
+}
+
+/// 发送请求并读取响应
+fn request(session_name: &str, request: &PtyRequest) -> Result<Option<String>> {
+    let mut stream = connect(session_name)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    write_request(&mut stream, request)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    match serde_json::from_str(&line)? {
+        PtyResponse::Ok { output } => Ok(output),
+        PtyResponse::Error { message } => Err(anyhow!(
+            "PTY session {} request failed: {}",
+            session_name,
+            message
+        )),
+    }
+}
+
+/// PTY 后端管理器
+#[derive(Debug, Clone, Default)]
+pub struct PtyManager;
+
+impl PtyManager {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn write(session_name: &str, data: String) -> Result<()> {
+        request(session_name, &PtyRequest::Write { data }).map(|_| ())
+    }
+}
+
+impl Multiplexer for PtyManager {
+    fn name(&self) -> &'static str {
+        "pty"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn create_session(&self, session_name: &str, working_dir: &str, command: &str) -> Result<()> {
+        debug!(session = %session_name, working_dir = %working_dir, "Creating PTY session");
+        std::fs::create_dir_all(pty_dir())?;
+
+        let mut host = Command::new(std::env::current_exe()?);
+        host.args(["__pty-host", session_name, working_dir, command])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // 独立进程组，不随当前终端的 Ctrl-C/挂断退出，cam 退出后 host 继续运行
+        host.process_group(0);
+        let mut child = host.spawn()?;
+
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while connect(session_name).is_err() {
+            if let Some(status) = child.try_wait()? {
+                return Err(anyhow!(
+                    "PTY host for session {} exited: {}",
+                    session_name,
+                    status
+                ));
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Timed out waiting for PTY session {}",
+                    session_name
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        info!(session = %session_name, "PTY session created");
+        Ok(())
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        connect(session_name).is_ok()
+    }
+
+    fn rename_session(&self, old_name: &str, new_name: &str) -> Result<()> {
+        request(
+            old_name,
+            &PtyRequest::Rename {
+                name: new_name.to_string(),
+            },
+        )
+        .map(|_| ())
+    }
+
+    fn send_keys(&self, session_name: &str, keys: &str) -> Result<()> {
+        Self::write(session_name, format!("{}\r", keys))
+    }
+
+    fn send_keys_raw(&self, session_name: &str, keys: &str) -> Result<()> {
+        Self::write(session_name, keys.to_string())
+    }
+
+    fn send_named_keys(&self, session_name: &str, keys: &[&str]) -> Result<()> {
+        let mut bytes = Vec::new();
+        for key in keys {
+            bytes.extend(named_key_bytes(key).ok_or_else(|| anyhow!("Unsupported key: {}", key))?);
+        }
+        Self::write(session_name, String::from_utf8(bytes)?)
+    }
+
+    fn capture_pane(&self, session_name: &str, lines: u32) -> Result<String> {
+        Ok(request(session_name, &PtyRequest::Capture { lines })?.unwrap_or_default())
+    }
+
+    fn kill_session(&self, session_name: &str) -> Result<()> {
+        request(session_name, &PtyRequest::Kill).map(|_| ())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(pty_dir()) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let mut sessions = Vec::new();
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("sock") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if UnixStream::connect(&path).is_ok() {
+                sessions.push(name.to_string());
+            } else {
+                // host 被强制结束时留下的 socket
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    fn attach_command(&self, session_name: &str) -> Command {
+        let exe = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("cam"));
+        let mut command = Command::new(exe);
+        command.args(["attach", session_name]);
+        command
+    }
+
+    fn attach_hint(&self, session_name: &str) -> String {
+        format!("cam attach {}", session_name)
+    }
+}
+
+/// PTY host 进程共享状态
+struct PtyHost {
+    buffer: Mutex<PtyBuffer>,
+    /// tail 模式的连接，输出时逐个转发，写失败即移除
+    subscribers: Mutex<Vec<UnixStream>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    socket: Mutex<PathBuf>,
+    /// 子进程 PID，也是它的进程组 ID（portable-pty 让子进程成为会话首进程）
+    pid: u32,
+}
+
+impl PtyHost {
+    fn handle_client(&self, mut stream: UnixStream) -> Result<()> {
+        let mut line = String::new();
+        BufReader::new(stream.try_clone()?).read_line(&mut line)?;
+        let request: PtyRequest = serde_json::from_str(&line)?;
+
+        if let PtyRequest::Tail = request {
+            // 持有缓冲区锁，保证回放与后续转发之间不丢字节
+            let buffer = self.buffer.lock().unwrap();
+            stream.set_write_timeout(Some(Duration::from_secs(1)))?;
+            stream.write_all(&buffer.contents())?;
+            self.subscribers.lock().unwrap().push(stream);
+            return Ok(());
+        }
+
+        let response = match self.respond(request) {
+            Ok(output) => PtyResponse::Ok { output },
+            Err(e) => PtyResponse::Error {
+                message: e.to_string(),
+            },
+        };
+        serde_json::to_writer(&mut stream, &response)?;
+        stream.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn respond(&self, request: PtyRequest) -> Result<Option<String>> {
+        match request {
+            PtyRequest::Capture { lines } => {
+                let text = render_text(&self.buffer.lock().unwrap().contents());
+                Ok(Some(tail_lines(&text, PTY_ROWS as usize, lines)))
+            }
+            PtyRequest::Write { data } => {
+                let mut writer = self.writer.lock().unwrap();
+                writer.write_all(data.as_bytes())?;
+                writer.flush()?;
+                Ok(None)
+            }
+            PtyRequest::Rename { name } => {
+                let new_path = socket_path(&name);
+                if UnixStream::connect(&new_path).is_ok() {
+                    return Err(anyhow!("session {} already exists", name));
+                }
+                let mut socket = self.socket.lock().unwrap();
+                std::fs::rename(&*socket, &new_path)?;
+                *socket = new_path;
+                Ok(None)
+            }
+            PtyRequest::Kill => {
+                // 子进程是会话首进程，向整个进程组发送 SIGHUP，2 秒后仍存活则 SIGKILL
+                signal_process_group(self.pid, "HUP");
+                let pid = self.pid;
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_secs(2));
+                    signal_process_group(pid, "KILL");
+                });
+                Ok(None)
+            }
+            PtyRequest::Tail => unreachable!("handled in handle_client"),
+        }
+    }
+
+    /// 读取 PTY 输出直到子进程关闭终端
+    fn pump_output(&self, mut reader: Box<dyn Read + Send>) {
+        let mut chunk = [0u8; 8192];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let mut buffer = self.buffer.lock().unwrap();
+                    buffer.push(&chunk[..n]);
+                    self.subscribers
+                        .lock()
+                        .unwrap()
+                        .retain_mut(|s| s.write_all(&chunk[..n]).is_ok());
+                }
+            }
+        }
+    }
+}
+
+/// 向进程组发送信号（`kill -<signal> -- -<pgid>`）
+fn signal_process_group(pgid: u32, signal: &str) {
+    let _ = Command::new("kill")
+        .args([&format!("-{}", signal), "--", &format!("-{}", pgid)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// PTY host 进程入口（`cam __pty-host`），阻塞到命令退出
+pub fn run_host(session_name: &str, working_dir: &str, command: &str) -> Result<()> {
+    std::fs::create_dir_all(pty_dir())?;
+    let socket = socket_path(session_name);
+    let _ = std::fs::remove_file(&socket);
+    let listener = UnixListener::bind(&socket)?;
+
+    let pair = native_pty_system().openpty(PtySize {
+        rows: PTY_ROWS,
+        cols: PTY_COLS,
+        pixel_width: 0,
+        pixel_height: 0,
+    })?;
+    let mut cmd = CommandBuilder::new("sh");
+    cmd.args(["-c", command]);
+    cmd.cwd(working_dir);
+    cmd.env("TERM", "xterm-256color");
+    // 子进程在新会话中运行，PTY 为其控制终端
+    let mut child = pair.slave.spawn_command(cmd)?;
+    drop(pair.slave);
+    let pid = child
+        .process_id()
+        .ok_or_else(|| anyhow!("PTY child for session {} has no PID", session_name))?;
+
+    let reader = pair.master.try_clone_reader()?;
+    let host = Arc::new(PtyHost {
+        buffer: Mutex::new(PtyBuffer::new(BUFFER_CAPACITY)),
+        subscribers: Mutex::new(Vec::new()),
+        writer: Mutex::new(pair.master.take_writer()?),
+        socket: Mutex::new(socket),
+        pid,
+    });
+    info!(session = %session_name, pid, "PTY host started");
+
+    let output_host = Arc::clone(&host);
+    std::thread::spawn(move || output_host.pump_output(reader));
+
+    let accept_host = Arc::clone(&host);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let host = Arc::clone(&accept_host);
+            std::thread::spawn(move || {
+                if let Err(e) = host.handle_client(stream) {
+                    warn!(error = %e, "PTY host request failed");
+                }
+            });
+        }
+    });
+
+    let status = child.wait()?;
+    info!(session = %session_name, status = %status, "PTY session exited");
+    let _ = std::fs::remove_file(&*host.socket.lock().unwrap());
+    Ok(())
+}
+
+/// 附着到 PTY session：回放缓冲区并持续输出，标准输入为终端时转发按键（Ctrl-] 断开）
+pub fn attach(session_name: &str) -> Result<()> {
+    let mut stream = connect(session_name)?;
+    write_request(&mut stream, &PtyRequest::Tail)?;
+
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        eprintln!("已附着到 {}（Ctrl-] 断开）\r", session_name);
+        crossterm::terminal::enable_raw_mode()?;
+    }
+
+    let output = std::thread::spawn(move || {
+        let _ = std::io::copy(&mut stream, &mut FlushingStdout);
+    });
+
+    if interactive {
+        let session = session_name.to_string();
+        std::thread::spawn(move || {
+            let mut stdin = std::io::stdin();
+            let mut chunk = [0u8; 1024];
+            while let Ok(n) = stdin.read(&mut chunk) {
+                let input = &chunk[..n];
+                let end = input.iter().position(|&b| b == DETACH_KEY);
+                let keys = &input[..end.unwrap_or(n)];
+                if !keys.is_empty() {
+                    let data = String::from_utf8_lossy(keys).into_owned();
+                    if PtyManager::write(&session, data).is_err() {
+                        break;
+                    }
+                }
+                if n == 0 || end.is_some() {
+                    break;
+                }
+            }
+            let _ = crossterm::terminal::disable_raw_mode();
+            std::process::exit(0);
+        });
+    }
+
+    let _ = output.join();
+    if interactive {
+        crossterm::terminal::disable_raw_mode()?;
+        eprintln!("\nsession {} 已结束", session_name);
+    }
+    Ok(())
+}
+
+/// 每次写入后立即 flush 的 stdout（tail 输出需要实时显示）
+struct FlushingStdout;
+
+impl Write for FlushingStdout {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(buf)?;
+        stdout.flush()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_keeps_newest_bytes() {
+        let mut buffer = PtyBuffer::new(8);
+        buffer.push(b"hello ");
+        buffer.push(b"world");
+        assert_eq!(buffer.contents(), b"lo world");
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(
+            render_text(b"\x1b[32mok\x1b[0m\r\nline2\r\n"),
+            "ok\nline2\n"
+        );
+        // 进度条用 \r 覆盖，ESC [ K 清除行尾
+        assert_eq!(render_text(b"50%\r100%\r\nab\rx\x1b[K"), "100%\nx\n");
+        assert_eq!(render_text(b"\x1b]0;title\x07abc\x08d"), "abd\n");
+    }
+
+    #[test]
+    fn test_request_wire_format() {
+        let json = serde_json::to_string(&PtyRequest::Capture { lines: 10 }).unwrap();
+        assert_eq!(json, r#"{"op":"capture","lines":10}"#);
+        let response: PtyResponse = serde_json::from_str(r#"{"status":"ok"}"#).unwrap();
+        assert!(matches!(response, PtyResponse::Ok { output: None }));
+    }
+}
@@ -0,0 +1,392 @@
+//! 沙箱执行 - 在受限环境中代为运行高风险 Bash 命令
+//!
+//! tool_policy 远程确认高风险 Bash 调用时，除了允许/拒绝，用户还可以回复 `s`：
+//! CAM 在沙箱中执行命令，拒绝原调用，并把退出码和输出作为拒绝原因反馈给 agent。
+//!
+//! config.json 示例：
+//! ```json
+//! { "tool_policy": { "notify": "HIGH", "ask_timeout_secs": 120,
+//!     "sandbox": { "backend": "docker", "image": "ubuntu:24.04", "timeout_secs": 60 } } }
+//! ```
+//!
+//! - `docker`：一次性容器，无网络、丢弃全部 capability，项目目录只读挂载，`/tmp` 可写
+//! - `sandbox-exec`（macOS）：禁止网络和写文件（临时目录除外）
+//!
+//! 超时后终止的是命令本身：Docker 按容器名 `docker kill`（只杀 `docker run` 客户端时容器会继续运行），
+//! sandbox-exec 在独立进程组中运行，终止整个进程组。
+
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// 反馈给 agent 的输出上限（字符，保留末尾）
+const MAX_FEEDBACK_CHARS: usize = 4000;
+
+/// 输出中 stderr 部分的标记
+const STDERR_LABEL: &str = "[stderr]";
+/// 默认执行超时
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// macOS sandbox-exec 配置：禁止网络和写文件，临时目录除外
+const SANDBOX_EXEC_PROFILE: &str = r#"(version 1)
+(allow default)
+(deny network*)
+(deny file-write*)
+(allow file-write* (subpath "/private/tmp") (subpath "/private/var/folders") (literal "/dev/null"))"#;
+
+/// 沙箱后端
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxBackend {
+    /// 一次性 Docker 容器
+    Docker { image: String },
+    /// macOS `sandbox-exec`
+    SandboxExec,
+}
+
+/// 沙箱配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxProfile {
+    pub backend: SandboxBackend,
+    pub timeout_secs: u64,
+}
+
+/// 沙箱执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxOutcome {
+    /// 退出码（被信号终止或超时为 None）
+    pub exit_code: Option<i32>,
+    /// 输出：先 stdout，stderr 非空时跟在 `[stderr]` 标记之后
+    pub output: String,
+    pub timed_out: bool,
+}
+
+impl SandboxProfile {
+    /// 从 `tool_policy.sandbox` 解析，缺少必需字段或后端未知时返回 None
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let backend = match value.get("backend")?.as_str()? {
+            "docker" => SandboxBackend::Docker {
+                image: value.get("image")?.as_str()?.to_string(),
+            },
+            "sandbox-exec" => SandboxBackend::SandboxExec,
+            _ => return None,
+        };
+        Some(Self {
+            backend,
+            timeout_secs: value
+                .get("timeout_secs")
+                .and_then(|v| v.as_u64())
+                .filter(|secs| *secs > 0)
+                .unwrap_or(DEFAULT_TIMEOUT_SECS),
+        })
+    }
+
+    /// 后端描述（用于反馈给 agent）
+    pub fn describe(&self) -> String {
+        match &self.backend {
+            SandboxBackend::Docker { image } => {
+                format!("Docker 容器 {}，无网络，项目目录只读", image)
+            }
+            SandboxBackend::SandboxExec => "sandbox-exec，无网络，禁止写文件".to_string(),
+        }
+    }
+
+    /// 在沙箱中运行命令的进程，`name` 为 Docker 容器名
+    fn build_command(&self, command: &str, cwd: &str, name: &str) -> Command {
+        match &self.backend {
+            SandboxBackend::Docker { image } => {
+                let mut docker = Command::new("docker");
+                docker
+                    .args([
+                        "run",
+                        "--rm",
+                        "--name",
+                        name,
+                        "--network",
+                        "none",
+                        "--cap-drop",
+                        "ALL",
+                        "--security-opt",
+                        "no-new-privileges",
+                        "--tmpfs",
+                        "/tmp",
+                        "--mount",
+                        &format!(
+                            "type=bind,{},{},readonly",
+                            mount_field("src", cwd),
+                            mount_field("dst", cwd)
+                        ),
+                        "-w",
+                        cwd,
+                        image,
+                        "sh",
+                        "-c",
+                        command,
+                    ])
+                    .stdin(Stdio::null());
+                docker
+            }
+            SandboxBackend::SandboxExec => {
+                let mut sandbox = Command::new("sandbox-exec");
+                sandbox
+                    .args(["-p", SANDBOX_EXEC_PROFILE, "sh", "-c", command])
+                    .current_dir(cwd)
+                    .stdin(Stdio::null())
+                    .process_group(0);
+                sandbox
+            }
+        }
+    }
+
+    /// 在沙箱中执行命令，超时后终止
+    pub fn run(&self, command: &str, cwd: &str) -> Result<SandboxOutcome> {
+        let name = container_name();
+        let child = self
+            .build_command(command, cwd, &name)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("启动沙箱失败（{}）: {}", self.describe(), e))?;
+        wait_with_timeout(
+            child,
+            Duration::from_secs(self.timeout_secs),
+            |child| match self.backend {
+                SandboxBackend::Docker { .. } => {
+                    let _ = Command::new("docker")
+                        .args(["kill", &name])
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .status();
+                }
+                SandboxBackend::SandboxExec => kill_process_group(child.id()),
+            },
+        )
+    }
+}
+
+/// 本次执行的容器名
+fn container_name() -> String {
+    let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    format!("cam-sandbox-{}-{:x}", std::process::id(), nanos)
+}
+
+/// 终止进程组（进程以 `process_group(0)` 启动，组 ID 即其 PID）
+fn kill_process_group(pgid: u32) {
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pgid)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// 等待进程结束并收集输出，超时先调用 `terminate` 终止命令再结束进程
+fn wait_with_timeout(
+    mut child: Child,
+    timeout: Duration,
+    terminate: impl FnOnce(&mut Child),
+) -> Result<SandboxOutcome> {
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (Some(status), false);
+        }
+        if Instant::now() >= deadline {
+            terminate(&mut child);
+            let _ = child.kill();
+            let _ = child.wait();
+            break (None, true);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    Ok(SandboxOutcome {
+        exit_code: status.and_then(|s| s.code()),
+        output: label_streams(&stdout, &stderr),
+        timed_out,
+    })
+}
+
+/// 两个流分别读取，无法还原交错顺序：stdout 在前，stderr 单独列在 `[stderr]` 之后
+fn label_streams(stdout: &[u8], stderr: &[u8]) -> String {
+    let mut output = String::from_utf8_lossy(stdout).into_owned();
+    if !stderr.is_empty() {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(STDERR_LABEL);
+        output.push('\n');
+        output.push_str(&String::from_utf8_lossy(stderr));
+    }
+    output
+}
+
+/// `--mount` 的一个字段；docker 按 CSV 解析，含 `,` 或 `"` 的值需要加引号
+fn mount_field(key: &str, value: &str) -> String {
+    let field = format!("{}={}", key, value);
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+impl SandboxOutcome {
+    /// 反馈给 agent 的文本（输出过长时保留末尾）
+    pub fn feedback(&self, profile: &SandboxProfile, command: &str) -> String {
+        let status = match (self.timed_out, self.exit_code) {
+            (true, _) => format!("超时（{} 秒）被终止", profile.timeout_secs),
+            (false, Some(code)) => format!("退出码 {}", code),
+            (false, None) => "被信号终止".to_string(),
+        };
+        let chars: Vec<char> = self.output.trim_end().chars().collect();
+        let output: String = if chars.len() > MAX_FEEDBACK_CHARS {
+            let tail: String = chars[chars.len() - MAX_FEEDBACK_CHARS..].iter().collect();
+            format!("...(已截断)\n{}", tail)
+        } else {
+            chars.into_iter().collect()
+        };
+        format!(
+            "用户选择在沙箱中执行（{}），原调用未执行。命令: {}\n{}，输出（stdout 在前，stderr 分开列在 {} 之后）:\n{}",
+            profile.describe(),
+            command,
+            status,
+            STDERR_LABEL,
+            if output.is_empty() {
+                "(无输出)"
+            } else {
+                &output
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_profile_from_json() {
+        let profile =
+            SandboxProfile::from_json(&json!({"backend": "docker", "image": "alpine:3"})).unwrap();
+        assert_eq!(
+            profile.backend,
+            SandboxBackend::Docker {
+                image: "alpine:3".to_string()
+            }
+        );
+        assert_eq!(profile.timeout_secs, DEFAULT_TIMEOUT_SECS);
+
+        assert!(SandboxProfile::from_json(&json!({"backend": "docker"})).is_none());
+        assert!(SandboxProfile::from_json(&json!({"backend": "firejail"})).is_none());
+        let profile =
+            SandboxProfile::from_json(&json!({"backend": "sandbox-exec", "timeout_secs": 5}))
+                .unwrap();
+        assert_eq!(profile.timeout_secs, 5);
+    }
+
+    #[test]
+    fn test_docker_container_is_named() {
+        let profile = SandboxProfile {
+            backend: SandboxBackend::Docker {
+                image: "alpine:3".to_string(),
+            },
+            timeout_secs: 5,
+        };
+        let command = profile.build_command("ls", "/repo", "cam-sandbox-1-a");
+        let args: Vec<_> = command.get_args().filter_map(|a| a.to_str()).collect();
+        assert!(args.windows(2).any(|w| w == ["--name", "cam-sandbox-1-a"]));
+        assert!(container_name().starts_with("cam-sandbox-"));
+    }
+
+    #[test]
+    fn test_docker_mount_handles_special_paths() {
+        let profile = SandboxProfile {
+            backend: SandboxBackend::Docker {
+                image: "alpine:3".to_string(),
+            },
+            timeout_secs: 5,
+        };
+        let command = profile.build_command("ls", "/work/a:b", "cam-sandbox-1-a");
+        let args: Vec<_> = command.get_args().filter_map(|a| a.to_str()).collect();
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["--mount", "type=bind,src=/work/a:b,dst=/work/a:b,readonly"]));
+        assert!(!args.contains(&"-v"));
+
+        assert_eq!(mount_field("src", "/a,b"), "\"src=/a,b\"");
+        assert_eq!(mount_field("src", "/a\"b"), "\"src=/a\"\"b\"");
+    }
+
+    #[test]
+    fn test_stderr_is_labeled() {
+        assert_eq!(label_streams(b"out\n", b""), "out\n");
+        assert_eq!(label_streams(b"out", b"err\n"), "out\n[stderr]\nerr\n");
+        assert_eq!(label_streams(b"", b"err"), "[stderr]\nerr");
+    }
+
+    #[test]
+    fn test_timeout_kills_process_group() {
+        let child = Command::new("sh")
+            .args(["-c", "sleep 30 & echo started; sleep 30"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let started = Instant::now();
+        let outcome = wait_with_timeout(child, Duration::from_millis(500), |child| {
+            kill_process_group(child.id())
+        })
+        .unwrap();
+        assert!(outcome.timed_out);
+        assert_eq!(outcome.exit_code, None);
+        assert_eq!(outcome.output.trim(), "started");
+        // 后台的 sleep 也被终止，输出管道随之关闭，不会等到 30 秒
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_feedback_truncates_output() {
+        let profile = SandboxProfile {
+            backend: SandboxBackend::SandboxExec,
+            timeout_secs: 5,
+        };
+        let outcome = SandboxOutcome {
+            exit_code: Some(1),
+            output: format!("{}END", "x".repeat(MAX_FEEDBACK_CHARS)),
+            timed_out: false,
+        };
+        let feedback = outcome.feedback(&profile, "make clean");
+        assert!(feedback.contains("退出码 1"));
+        assert!(feedback.contains("...(已截断)"));
+        assert!(feedback.ends_with("END"));
+
+        let timed_out = SandboxOutcome {
+            exit_code: None,
+            output: String::new(),
+            timed_out: true,
+        };
+        assert!(timed_out
+            .feedback(&profile, "sleep 100")
+            .contains("超时（5 秒）被终止"));
+    }
+}
@@ -0,0 +1,463 @@
+//! 持久化文件的 schema 版本与迁移
+//!
+//! 每个持久化 JSON 文件顶层带有 `schema_version` 字段（缺失视为版本 0，即加入版本号之前的格式）。
+//! 迁移函数按顺序逐级升级：`migrations[n]` 把版本 n 升级为 n+1。
+//!
+//! - 读取时通过 `load_versioned` 在内存中升级，保证旧文件可被当前版本解析
+//! - `cam migrate`（以及 watcher daemon 启动时）把升级结果写回磁盘，写回前保留 `.v{旧版本}.bak` 备份
+//! - 版本号高于当前程序支持的文件不会被改写
+
+use anyhow::{anyhow, Context, Result};
+use fs2::FileExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// 版本字段名
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// agents.json 当前版本
+pub const AGENTS_SCHEMA_VERSION: u32 = 1;
+/// conversation_state.json 当前版本
+pub const CONVERSATION_STATE_SCHEMA_VERSION: u32 = 1;
+/// config.json 当前版本
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+/// dedup_state.json 当前版本
+pub const DEDUP_STATE_SCHEMA_VERSION: u32 = 1;
+/// throttle_state.json 当前版本
+pub const THROTTLE_STATE_SCHEMA_VERSION: u32 = 1;
+
+/// 单步迁移：把上一版本的 JSON 原地升级为下一版本
+pub type Migration = fn(&mut Value) -> Result<()>;
+
+/// 写回迁移结果时持有的锁，与所属存储正常读写时一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaLock {
+    /// 独立的锁文件（数据目录中的文件名）
+    LockFile(&'static str),
+    /// 直接锁数据文件本身（迁移结果通过持锁的句柄原地写回，不替换文件）
+    DataFile,
+    /// 所属存储不加锁，只依赖原子替换
+    Unlocked,
+}
+
+/// 持久化文件的 schema 描述
+#[derive(Debug)]
+pub struct Schema {
+    /// 显示名称
+    pub name: &'static str,
+    /// 数据目录中的文件名
+    pub file_name: &'static str,
+    /// 读写该文件时使用的锁
+    pub lock: SchemaLock,
+    /// 当前版本
+    pub version: u32,
+    /// 迁移链，长度必须等于 `version`
+    pub migrations: &'static [Migration],
+}
+
+/// agents.json
+pub const AGENTS_SCHEMA: Schema = Schema {
+    name: "agents",
+    file_name: "agents.json",
+    lock: SchemaLock::LockFile("agents.json.lock"),
+    version: AGENTS_SCHEMA_VERSION,
+    migrations: &[migrate_agents_v0_to_v1],
+};
+
+/// conversation_state.json（待处理确认）
+pub const CONVERSATION_STATE_SCHEMA: Schema = Schema {
+    name: "conversation_state",
+    file_name: "conversation_state.json",
+    lock: SchemaLock::Unlocked,
+    version: CONVERSATION_STATE_SCHEMA_VERSION,
+    migrations: &[migrate_conversation_state_v0_to_v1],
+};
+
+/// config.json（webhook / AI providers）
+pub const CONFIG_SCHEMA: Schema = Schema {
+    name: "config",
+    file_name: "config.json",
+    lock: SchemaLock::Unlocked,
+    version: CONFIG_SCHEMA_VERSION,
+    migrations: &[migrate_object_v0_to_v1],
+};
+
+/// dedup_state.json
+pub const DEDUP_STATE_SCHEMA: Schema = Schema {
+    name: "dedup_state",
+    file_name: "dedup_state.json",
+    lock: SchemaLock::DataFile,
+    version: DEDUP_STATE_SCHEMA_VERSION,
+    migrations: &[migrate_dedup_state_v0_to_v1],
+};
+
+/// throttle_state.json（通知令牌桶与摘要队列）
+pub const THROTTLE_STATE_SCHEMA: Schema = Schema {
+    name: "throttle_state",
+    file_name: "throttle_state.json",
+    lock: SchemaLock::DataFile,
+    version: THROTTLE_STATE_SCHEMA_VERSION,
+    migrations: &[migrate_object_v0_to_v1],
+};
+
+/// 所有受版本管理的持久化文件
+pub const ALL_SCHEMAS: &[&Schema] = &[
+    &AGENTS_SCHEMA,
+    &CONVERSATION_STATE_SCHEMA,
+    &CONFIG_SCHEMA,
+    &DEDUP_STATE_SCHEMA,
+    &THROTTLE_STATE_SCHEMA,
+];
+
+/// 单个文件的迁移结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationStatus {
+    /// 文件不存在
+    Missing,
+    /// 已是当前版本
+    UpToDate { version: u32 },
+    /// 已迁移（dry-run 时 backup 为 None）
+    Migrated {
+        from: u32,
+        to: u32,
+        backup: Option<PathBuf>,
+    },
+    /// 文件版本高于当前程序支持的版本，未改动
+    TooNew { version: u32 },
+}
+
+/// CAM 默认数据目录
+pub fn default_data_dir() -> PathBuf {
+    crate::infra::paths::state_dir()
+}
+
+/// 读取 JSON 的 schema 版本（缺失为 0）
+pub fn schema_version(value: &Value) -> u32 {
+    value
+        .get(SCHEMA_VERSION_FIELD)
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// 迁移函数辅助：写入版本号
+fn set_version(value: &mut Value, version: u32) -> Result<()> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("顶层不是 JSON 对象"))?;
+    obj.insert(SCHEMA_VERSION_FIELD.to_string(), Value::from(version));
+    Ok(())
+}
+
+impl Schema {
+    /// 文件在数据目录中的路径
+    pub fn path_in(&self, data_dir: &Path) -> PathBuf {
+        data_dir.join(self.file_name)
+    }
+
+    /// 在内存中把 JSON 升级到当前版本，返回原版本
+    ///
+    /// 版本高于当前版本时返回错误，调用方决定是否继续按当前格式解析。
+    pub fn upgrade(&self, value: &mut Value) -> Result<u32> {
+        let from = schema_version(value);
+        if from > self.version {
+            return Err(anyhow!(
+                "{} 的 schema 版本 {} 高于当前支持的版本 {}，请升级 cam",
+                self.file_name,
+                from,
+                self.version
+            ));
+        }
+        for (step, migration) in self.migrations.iter().enumerate().skip(from as usize) {
+            migration(value).with_context(|| {
+                format!("{} 从 v{} 迁移到 v{} 失败", self.file_name, step, step + 1)
+            })?;
+            set_version(value, step as u32 + 1)?;
+        }
+        Ok(from)
+    }
+
+    /// 解析文件内容并升级到当前版本后反序列化
+    pub fn load_versioned<T: DeserializeOwned>(&self, content: &str) -> Result<T> {
+        let mut value: Value = serde_json::from_str(content)?;
+        if let Err(e) = self.upgrade(&mut value) {
+            // 更新版本的文件：尽量按当前格式读取，未知字段会被忽略
+            warn!(file = self.file_name, error = %e, "Reading newer schema version");
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// 序列化为带当前版本号的 JSON 值
+    pub fn to_versioned_value<T: Serialize>(&self, data: &T) -> Result<Value> {
+        let mut value = serde_json::to_value(data)?;
+        set_version(&mut value, self.version)?;
+        Ok(value)
+    }
+
+    /// 迁移磁盘上的文件
+    pub fn migrate_file(&self, data_dir: &Path, dry_run: bool) -> Result<MigrationStatus> {
+        let path = self.path_in(data_dir);
+        if !path.exists() {
+            return Ok(MigrationStatus::Missing);
+        }
+
+        // 与正常读写路径使用相同的锁，避免与运行中的 watcher 竞争
+        let lock = match self.lock {
+            SchemaLock::LockFile(lock_name) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .truncate(true)
+                    .write(true)
+                    .open(data_dir.join(lock_name))?,
+            ),
+            // 不能截断：数据文件本身就是锁
+            SchemaLock::DataFile => Some(OpenOptions::new().read(true).write(true).open(&path)?),
+            SchemaLock::Unlocked => None,
+        };
+        if let Some(lock) = &lock {
+            lock.lock_exclusive()?;
+        }
+
+        let result = (|| {
+            let content = fs::read_to_string(&path)?;
+            let mut value: Value = serde_json::from_str(&content)
+                .with_context(|| format!("解析 {} 失败", path.display()))?;
+
+            let from = schema_version(&value);
+            if from > self.version {
+                return Ok(MigrationStatus::TooNew { version: from });
+            }
+            if from == self.version {
+                return Ok(MigrationStatus::UpToDate { version: from });
+            }
+
+            self.upgrade(&mut value)?;
+            if dry_run {
+                return Ok(MigrationStatus::Migrated {
+                    from,
+                    to: self.version,
+                    backup: None,
+                });
+            }
+
+            let backup = path.with_file_name(format!("{}.v{}.bak", self.file_name, from));
+            fs::copy(&path, &backup)?;
+            let content = serde_json::to_string_pretty(&value)?;
+            match (self.lock, lock.as_ref()) {
+                // 等待该锁的进程持有的是同一个 inode，改名替换会让它们之后的写入丢失，
+                // 只能持锁原地写回（有备份兜底）
+                (SchemaLock::DataFile, Some(mut file)) => {
+                    file.set_len(0)?;
+                    file.rewind()?;
+                    file.write_all(content.as_bytes())?;
+                    file.sync_all()?;
+                }
+                // 先写临时文件再替换，中途失败不会留下半个文件
+                _ => {
+                    let tmp = path.with_file_name(format!("{}.tmp", self.file_name));
+                    fs::write(&tmp, content)?;
+                    fs::rename(&tmp, &path)?;
+                }
+            }
+
+            Ok(MigrationStatus::Migrated {
+                from,
+                to: self.version,
+                backup: Some(backup),
+            })
+        })();
+
+        if let Some(lock) = lock {
+            let _ = lock.unlock();
+        }
+        result
+    }
+}
+
+/// 迁移数据目录中的所有持久化文件
+pub fn migrate_all(
+    data_dir: &Path,
+    dry_run: bool,
+) -> Vec<(&'static Schema, Result<MigrationStatus>)> {
+    ALL_SCHEMAS
+        .iter()
+        .map(|schema| (*schema, schema.migrate_file(data_dir, dry_run)))
+        .collect()
+}
+
+/// v0 → v1：仅加入版本号
+fn migrate_object_v0_to_v1(value: &mut Value) -> Result<()> {
+    if !value.is_object() {
+        return Err(anyhow!("顶层不是 JSON 对象"));
+    }
+    Ok(())
+}
+
+/// agents.json v0 → v1：补齐缺失的 agents 数组
+fn migrate_agents_v0_to_v1(value: &mut Value) -> Result<()> {
+    migrate_object_v0_to_v1(value)?;
+    let obj = value.as_object_mut().unwrap();
+    obj.entry("agents").or_insert_with(|| Value::Array(vec![]));
+    Ok(())
+}
+
+/// conversation_state.json v0 → v1：补齐缺失的 pending_confirmations 数组
+fn migrate_conversation_state_v0_to_v1(value: &mut Value) -> Result<()> {
+    migrate_object_v0_to_v1(value)?;
+    let obj = value.as_object_mut().unwrap();
+    obj.entry("pending_confirmations")
+        .or_insert_with(|| Value::Array(vec![]));
+    Ok(())
+}
+
+/// dedup_state.json v0 → v1：补齐缺失的 locks 对象
+fn migrate_dedup_state_v0_to_v1(value: &mut Value) -> Result<()> {
+    migrate_object_v0_to_v1(value)?;
+    let obj = value.as_object_mut().unwrap();
+    obj.entry("locks")
+        .or_insert_with(|| Value::Object(Default::default()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migration_chain_matches_version() {
+        for schema in ALL_SCHEMAS {
+            assert_eq!(
+                schema.migrations.len() as u32,
+                schema.version,
+                "{}",
+                schema.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_upgrade_unversioned_agents() {
+        let mut value = json!({});
+        assert_eq!(AGENTS_SCHEMA.upgrade(&mut value).unwrap(), 0);
+        assert_eq!(value, json!({"schema_version": 1, "agents": []}));
+
+        // 再次升级是空操作
+        let before = value.clone();
+        assert_eq!(AGENTS_SCHEMA.upgrade(&mut value).unwrap(), 1);
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn test_upgrade_rejects_newer_version() {
+        let mut value = json!({"schema_version": 99, "agents": []});
+        assert!(AGENTS_SCHEMA.upgrade(&mut value).is_err());
+        assert_eq!(schema_version(&value), 99);
+    }
+
+    #[test]
+    fn test_upgrade_rejects_non_object() {
+        let mut value = json!([1, 2, 3]);
+        assert!(CONFIG_SCHEMA.upgrade(&mut value).is_err());
+    }
+
+    #[test]
+    fn test_migrate_file_writes_backup() {
+        let dir = tempdir().unwrap();
+        let legacy = r#"{"webhook":{"gateway_url":"http://localhost:18789"}}"#;
+        fs::write(dir.path().join("config.json"), legacy).unwrap();
+
+        let status = CONFIG_SCHEMA.migrate_file(dir.path(), false).unwrap();
+        let backup = dir.path().join("config.json.v0.bak");
+        assert_eq!(
+            status,
+            MigrationStatus::Migrated {
+                from: 0,
+                to: 1,
+                backup: Some(backup.clone())
+            }
+        );
+        assert_eq!(fs::read_to_string(&backup).unwrap(), legacy);
+
+        let migrated: Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("config.json")).unwrap())
+                .unwrap();
+        assert_eq!(migrated["schema_version"], 1);
+        assert_eq!(migrated["webhook"]["gateway_url"], "http://localhost:18789");
+
+        assert_eq!(
+            CONFIG_SCHEMA.migrate_file(dir.path(), false).unwrap(),
+            MigrationStatus::UpToDate { version: 1 }
+        );
+        assert!(!dir.path().join("config.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_migrate_file_dry_run_and_too_new() {
+        let dir = tempdir().unwrap();
+        let legacy = r#"{"locks":{}}"#;
+        fs::write(dir.path().join("dedup_state.json"), legacy).unwrap();
+        let newer = r#"{"schema_version":7,"agents":[]}"#;
+        fs::write(dir.path().join("agents.json"), newer).unwrap();
+
+        let results = migrate_all(dir.path(), true);
+        let status = |name: &str| {
+            results
+                .iter()
+                .find(|(schema, _)| schema.name == name)
+                .map(|(_, r)| r.as_ref().unwrap().clone())
+                .unwrap()
+        };
+        assert_eq!(
+            status("dedup_state"),
+            MigrationStatus::Migrated {
+                from: 0,
+                to: 1,
+                backup: None
+            }
+        );
+        assert_eq!(status("agents"), MigrationStatus::TooNew { version: 7 });
+        assert_eq!(status("config"), MigrationStatus::Missing);
+
+        // dry-run 与版本过高都不改动文件
+        assert_eq!(
+            fs::read_to_string(dir.path().join("dedup_state.json")).unwrap(),
+            legacy
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("agents.json")).unwrap(),
+            newer
+        );
+    }
+
+    #[test]
+    fn test_migrate_file_locking_data_file() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("throttle_state.json");
+        fs::write(&path, r#"{"digest":[]}"#).unwrap();
+        let inode = fs::metadata(&path).unwrap().ino();
+
+        assert!(matches!(
+            THROTTLE_STATE_SCHEMA
+                .migrate_file(dir.path(), false)
+                .unwrap(),
+            MigrationStatus::Migrated { from: 0, to: 1, .. }
+        ));
+        let migrated: Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("throttle_state.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(migrated, json!({"schema_version": 1, "digest": []}));
+        assert!(!dir.path().join("throttle_state.json.tmp").exists());
+        // 原地写回，持有旧句柄等待锁的进程仍写到同一个文件
+        assert_eq!(fs::metadata(&path).unwrap().ino(), inode);
+    }
+}
@@ -0,0 +1,174 @@
+//! GNU screen 后端 - 通过 `screen -S <name> -X <command>` 操作后台 session
+//!
+//! 文本用 `stuff` 发送（转义 screen 会解释的 `\`、`^`、`$`，控制字符写成八进制），
+//! 截屏用 `hardcopy -h` 写出临时文件。history 行数受 screen 的 `defscrollback` 限制。
+
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+use tracing::{debug, info};
+
+use crate::infra::multiplexer::{
+    dump_path, named_key_bytes, read_dump, run_checked, tail_lines, Multiplexer,
+};
+
+/// GNU screen 管理器
+#[derive(Debug, Clone, Default)]
+pub struct ScreenManager;
+
+impl ScreenManager {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn execute(session_name: &str, args: &[&str]) -> Result<()> {
+        run_checked(
+            Command::new("screen")
+                .args(["-S", session_name, "-X"])
+                .args(args),
+            &format!("run screen {} in session {}", args[0], session_name),
+        )
+        .map(|_| ())
+    }
+
+    fn stuff(session_name: &str, bytes: &[u8]) -> Result<()> {
+        Self::execute(session_name, &["stuff", &escape_stuff(bytes)])
+    }
+
+    fn hardcopy(session_name: &str, scrollback: bool) -> Result<String> {
+        let path = dump_path("screen");
+        let path_arg = path.to_string_lossy();
+        let mut args = vec!["hardcopy"];
+        if scrollback {
+            args.push("-h");
+        }
+        args.push(&path_arg);
+        Self::execute(session_name, &args)?;
+        read_dump(&path)
+    }
+}
+
+/// 转义 `stuff` 参数
+fn escape_stuff(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '^' | '$' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\{:03o}", c as u8)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 解析 `screen -ls` 输出中的 session 名称（去掉 `<pid>.` 前缀）
+fn parse_sessions(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with('\t'))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(|id| id.split_once('.').map(|(_, name)| name.to_string()))
+        .collect()
+}
+
+impl Multiplexer for ScreenManager {
+    fn name(&self) -> &'static str {
+        "screen"
+    }
+
+    fn is_available(&self) -> bool {
+        // screen -v 的退出码不固定，只检查能否执行
+        Command::new("screen").arg("-v").output().is_ok()
+    }
+
+    fn create_session(&self, session_name: &str, working_dir: &str, command: &str) -> Result<()> {
+        debug!(session = %session_name, working_dir = %working_dir, "Creating screen session");
+        run_checked(
+            Command::new("screen")
+                .args(["-dmS", session_name, "sh", "-c", command])
+                .current_dir(working_dir),
+            &format!("create screen session {}", session_name),
+        )?;
+        info!(session = %session_name, "Screen session created");
+        Ok(())
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        self.list_sessions()
+            .map(|sessions| sessions.iter().any(|s| s == session_name))
+            .unwrap_or(false)
+    }
+
+    fn rename_session(&self, old_name: &str, new_name: &str) -> Result<()> {
+        Self::execute(old_name, &["sessionname", new_name])
+    }
+
+    fn send_keys(&self, session_name: &str, keys: &str) -> Result<()> {
+        let mut bytes = keys.as_bytes().to_vec();
+        bytes.push(b'\r');
+        Self::stuff(session_name, &bytes)
+    }
+
+    fn send_keys_raw(&self, session_name: &str, keys: &str) -> Result<()> {
+        Self::stuff(session_name, keys.as_bytes())
+    }
+
+    fn send_named_keys(&self, session_name: &str, keys: &[&str]) -> Result<()> {
+        let mut bytes = Vec::new();
+        for key in keys {
+            bytes.extend(named_key_bytes(key).ok_or_else(|| anyhow!("Unsupported key: {}", key))?);
+        }
+        Self::stuff(session_name, &bytes)
+    }
+
+    fn capture_pane(&self, session_name: &str, lines: u32) -> Result<String> {
+        let visible_rows = Self::hardcopy(session_name, false)?.lines().count();
+        let full = Self::hardcopy(session_name, true)?;
+        Ok(tail_lines(&full, visible_rows, lines))
+    }
+
+    fn kill_session(&self, session_name: &str) -> Result<()> {
+        Self::execute(session_name, &["quit"])
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        // 没有 session 时 screen -ls 返回非零
+        let output = Command::new("screen").arg("-ls").output()?;
+        Ok(parse_sessions(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn attach_command(&self, session_name: &str) -> Command {
+        let mut command = Command::new("screen");
+        command.args(["-r", session_name]);
+        command
+    }
+
+    fn attach_hint(&self, session_name: &str) -> String {
+        format!("screen -r {}", session_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_and_parse() {
+        assert_eq!(
+            escape_stuff(b"echo $HOME ^C \\n\r"),
+            "echo \\$HOME \\^C \\\\n\\015"
+        );
+        assert_eq!(escape_stuff(b"\x1b[B"), "\\033[B");
+
+        let ls = "There are screens on:\n\t4242.cam-1\t(Detached)\n\t99.cam-2.x\t(Attached)\n2 Sockets in /run/screen/S-me.\n";
+        assert_eq!(
+            parse_sessions(ls),
+            vec!["cam-1".to_string(), "cam-2.x".to_string()]
+        );
+        assert!(parse_sessions("No Sockets found in /run/screen/S-me.\n").is_empty());
+    }
+}
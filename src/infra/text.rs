@@ -0,0 +1,130 @@
+//! 文本宽度工具 - 按显示宽度截断和对齐，不拆分字素簇；字节数格式化
+//!
+//! CJK 字符和大部分 emoji 在终端中占两列，按 `chars()` 计数会让表格错位；
+//! `👍🏽`、`🇨🇳` 等由多个码点组成，按字符截断会留下半个 emoji。
+//! 这里统一按字素簇（grapheme cluster）切分，按 `unicode-width` 计算显示宽度。
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// 截断后追加的省略号（宽度 1）
+pub const ELLIPSIS: &str = "…";
+
+/// 字符串的终端显示宽度
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// 按 `measure` 计量，返回不超过 `budget` 的最长前缀（字素簇边界）
+fn take_prefix(s: &str, budget: usize, measure: impl Fn(&str) -> usize) -> &str {
+    let mut used = 0;
+    let mut end = 0;
+    for (index, grapheme) in s.grapheme_indices(true) {
+        used += measure(grapheme);
+        if used > budget {
+            break;
+        }
+        end = index + grapheme.len();
+    }
+    &s[..end]
+}
+
+/// 显示宽度不超过 `max_width` 的最长前缀
+pub fn take_width(s: &str, max_width: usize) -> &str {
+    take_prefix(s, max_width, display_width)
+}
+
+/// 显示宽度不超过 `max_width` 的最长后缀
+pub fn take_width_end(s: &str, max_width: usize) -> &str {
+    let mut used = 0;
+    let mut start = s.len();
+    for (index, grapheme) in s.grapheme_indices(true).rev() {
+        used += display_width(grapheme);
+        if used > max_width {
+            break;
+        }
+        start = index;
+    }
+    &s[start..]
+}
+
+/// 字符数不超过 `max_chars` 的最长前缀（不拆分字素簇）
+pub fn take_chars(s: &str, max_chars: usize) -> &str {
+    take_prefix(s, max_chars, |g| g.chars().count())
+}
+
+/// UTF-16 长度不超过 `max_units` 的最长前缀（Telegram 等渠道按 UTF-16 计算消息长度）
+pub fn take_utf16(s: &str, max_units: usize) -> &str {
+    take_prefix(s, max_units, |g| g.encode_utf16().count())
+}
+
+/// 超过 `max_width` 时截断并追加 `…`，结果显示宽度不超过 `max_width`
+pub fn truncate_width(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    format!("{}{}", take_width(s, max_width - 1), ELLIPSIS)
+}
+
+/// 截断到 `width` 后在右侧补空格，使显示宽度恰好为 `width`（用于表格列对齐）
+pub fn pad_width(s: &str, width: usize) -> String {
+    let truncated = truncate_width(s, width);
+    let padding = width.saturating_sub(display_width(&truncated));
+    format!("{}{}", truncated, " ".repeat(padding))
+}
+
+/// 字节数转为可读大小
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width_of_cjk_and_emoji() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("中文"), 4);
+        assert_eq!(display_width("🚀"), 2);
+
+        assert_eq!(truncate_width("hello world", 8), "hello w…");
+        assert_eq!(truncate_width("中文消息很长", 7), "中文消…");
+        assert_eq!(truncate_width("short", 10), "short");
+        assert_eq!(pad_width("中文", 6), "中文  ");
+        assert_eq!(pad_width("中文消息", 5), "中文…");
+        assert_eq!(take_width_end("src/中文/main.rs", 7), "main.rs");
+    }
+
+    #[test]
+    fn test_never_splits_graphemes() {
+        // 肤色修饰 emoji 和国旗都由两个码点组成
+        let text = "ok 👍🏽🇨🇳 done";
+        assert_eq!(take_chars(text, 4), "ok ");
+        assert_eq!(take_chars(text, 5), "ok 👍🏽");
+        assert_eq!(take_utf16(text, 6), "ok ");
+        assert_eq!(take_utf16(text, 7), "ok 👍🏽");
+        assert_eq!(truncate_width("👍🏽👍🏽👍🏽", 5), "👍🏽👍🏽…");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
@@ -1,20 +1,146 @@
 //! tmux 管理模块 - 封装 tmux 操作
 
 use anyhow::{anyhow, Result};
-use std::process::Command;
-use tracing::{debug, error, info};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::process::Stdio;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+use crate::infra::multiplexer::Multiplexer;
+use crate::infra::tmux_config::{tmux_command, TmuxConfig};
+use crate::infra::tmux_control::TmuxControl;
+
+/// 增量截屏缓存（每个 session 一个），内容与 `capture_pane(session, history_lines)` 一致
+///
+/// 已滚入 history 的行不会再变化，只需缓存一次；每次轮询只截取可见区域，
+/// 以及上次轮询后新滚入 history 的行。输出缓冲区在多次截取间复用。
+#[derive(Debug, Clone)]
+pub struct PaneCapture {
+    /// 保留的 history 行数（对应 `capture_pane` 的 lines 参数）
+    history_lines: u32,
+    /// 上次截取时 pane 的 history_size，None 表示需要完整截取
+    history_size: Option<u64>,
+    /// 最近的 history 行
+    history: VecDeque<String>,
+    /// history + 可见区域
+    output: String,
+}
+
+impl PaneCapture {
+    pub fn new(history_lines: u32) -> Self {
+        Self {
+            history_lines,
+            history_size: None,
+            history: VecDeque::new(),
+            output: String::new(),
+        }
+    }
+
+    /// 最近一次截取的内容
+    pub fn as_str(&self) -> &str {
+        &self.output
+    }
+
+    /// 保留的 history 行数
+    pub fn history_lines(&self) -> u32 {
+        self.history_lines
+    }
+
+    /// 用完整截取的内容替换缓存（不支持增量截取的后端使用）
+    pub fn replace(&mut self, output: String) {
+        self.reset();
+        self.output = output;
+    }
+
+    /// 丢弃缓存，下次完整截取
+    pub fn reset(&mut self) {
+        self.history_size = None;
+        self.history.clear();
+    }
+
+    fn push_history<'a>(&mut self, lines: impl Iterator<Item = &'a str>) {
+        for line in lines {
+            self.history.push_back(line.to_string());
+        }
+        let overflow = self
+            .history
+            .len()
+            .saturating_sub(self.history_lines as usize);
+        self.history.drain(..overflow);
+    }
+
+    fn rebuild(&mut self, visible: &str) {
+        self.output.clear();
+        for line in &self.history {
+            self.output.push_str(line);
+            self.output.push('\n');
+        }
+        self.output.push_str(visible);
+    }
+}
+
+/// agent 所在窗口的 target（已含窗口或 pane 的 target 原样返回）
+fn pane_target(session_name: &str) -> String {
+    if session_name.contains(':') {
+        session_name.to_string()
+    } else {
+        TmuxConfig::global().window_target(session_name)
+    }
+}
 
 /// tmux 管理器
-pub struct TmuxManager;
+///
+/// 默认每个操作启动一个 tmux 子进程；`with_control_mode` 时截屏、发送按键和 session 检查
+/// 走 tmux 控制模式长连接，连接不可用或参数无法发送时回退为子进程。
+#[derive(Clone, Default)]
+pub struct TmuxManager {
+    control: Option<Arc<TmuxControl>>,
+}
 
 impl TmuxManager {
     pub fn new() -> Self {
-        Self
+        Self { control: None }
+    }
+
+    /// 使用 tmux 控制模式（连接失败时回退为子进程模式）
+    pub fn with_control_mode() -> Self {
+        match TmuxControl::connect() {
+            Ok(control) => Self {
+                control: Some(control),
+            },
+            Err(e) => {
+                warn!(error = %e, "tmux control mode unavailable, using subprocesses");
+                Self::new()
+            }
+        }
+    }
+
+    /// 是否正在使用控制模式
+    pub fn is_control_mode(&self) -> bool {
+        self.control.as_ref().is_some_and(|c| c.is_connected())
+    }
+
+    /// 通过控制模式执行命令，未启用或连接已断开时返回 None
+    fn control_run(&self, commands: &[&[&str]]) -> Option<Result<Vec<String>>> {
+        let control = self.control.as_ref().filter(|c| c.is_connected())?;
+        match control.run(commands) {
+            // 连接在执行中断开，回退为子进程
+            Err(_) if !control.is_connected() => None,
+            Err(e) if e.to_string().contains("multi-line") => None,
+            result => Some(result),
+        }
+    }
+
+    fn invalidate_sessions(&self) {
+        if let Some(ref control) = self.control {
+            control.invalidate_sessions();
+        }
     }
 
     /// 检查 tmux 是否可用
     pub fn is_available(&self) -> bool {
-        Command::new("tmux")
+        tmux_command()
             .arg("-V")
             .output()
             .map(|o| o.status.success())
@@ -30,7 +156,7 @@ impl TmuxManager {
     ) -> Result<()> {
         debug!(session = %session_name, working_dir = %working_dir, "Creating tmux session");
 
-        let status = Command::new("tmux")
+        let status = tmux_command()
             .args([
                 "new-session",
                 "-d", // detached
@@ -42,6 +168,7 @@ impl TmuxManager {
             ])
             .status()?;
 
+        self.invalidate_sessions();
         if status.success() {
             info!(session = %session_name, "Tmux session created");
             Ok(())
@@ -51,9 +178,35 @@ impl TmuxManager {
         }
     }
 
+    /// session 首个 pane 中运行的进程 PID
+    pub fn pane_pid(&self, session_name: &str) -> Option<u32> {
+        let output = tmux_command()
+            .args([
+                "display-message",
+                "-p",
+                "-t",
+                &pane_target(session_name),
+                "#{pane_pid}",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
     /// 检查 session 是否存在
     pub fn session_exists(&self, session_name: &str) -> bool {
-        Command::new("tmux")
+        if let Some(names) = self
+            .control
+            .as_ref()
+            .filter(|c| c.is_connected())
+            .and_then(|c| c.session_names().ok())
+        {
+            return names.contains(session_name);
+        }
+        tmux_command()
             .args(["has-session", "-t", session_name])
             .status()
             .map(|s| s.success())
@@ -64,9 +217,10 @@ impl TmuxManager {
     pub fn rename_session(&self, old_name: &str, new_name: &str) -> Result<()> {
         debug!(old = %old_name, new = %new_name, "Renaming tmux session");
 
-        let status = Command::new("tmux")
+        let status = tmux_command()
             .args(["rename-session", "-t", old_name, new_name])
             .status()?;
+        self.invalidate_sessions();
 
         if status.success() {
             info!(old = %old_name, new = %new_name, "Tmux session renamed");
@@ -84,11 +238,21 @@ impl TmuxManager {
     /// 向 session 发送按键
     /// 使用 -l 标志确保文本被字面解释，避免 "Enter" 等特殊字符串被解释为按键
     pub fn send_keys(&self, session_name: &str, keys: &str) -> Result<()> {
+        let target = pane_target(session_name);
         info!(session = %session_name, keys_len = keys.len(), "Sending keys to tmux session");
 
+        if let Some(result) = self.control_run(&[
+            &["send-keys", "-t", &target, "-l", keys],
+            &["send-keys", "-t", &target, "Enter"],
+        ]) {
+            return result
+                .map(|_| ())
+                .map_err(|e| anyhow!("Failed to send keys to session {}: {}", session_name, e));
+        }
+
         // 使用 -l 标志发送字面文本，避免特殊字符被解释
-        let status = Command::new("tmux")
-            .args(["send-keys", "-t", session_name, "-l", keys])
+        let status = tmux_command()
+            .args(["send-keys", "-t", &target, "-l", keys])
             .status()?;
 
         if !status.success() {
@@ -99,8 +263,8 @@ impl TmuxManager {
         debug!(session = %session_name, "Text sent, now sending Enter");
 
         // 单独发送 Enter（不使用 -l，因为这里需要解释为按键）
-        let status = Command::new("tmux")
-            .args(["send-keys", "-t", session_name, "Enter"])
+        let status = tmux_command()
+            .args(["send-keys", "-t", &target, "Enter"])
             .status()?;
 
         if status.success() {
@@ -115,8 +279,68 @@ impl TmuxManager {
     /// 向 session 发送按键（不自动添加 Enter）
     /// 使用 -l 标志确保文本被字面解释
     pub fn send_keys_raw(&self, session_name: &str, keys: &str) -> Result<()> {
-        let status = Command::new("tmux")
-            .args(["send-keys", "-t", session_name, "-l", keys])
+        let target = pane_target(session_name);
+        if let Some(result) = self.control_run(&[&["send-keys", "-t", &target, "-l", keys]]) {
+            return result
+                .map(|_| ())
+                .map_err(|e| anyhow!("Failed to send keys to session {}: {}", session_name, e));
+        }
+        let status = tmux_command()
+            .args(["send-keys", "-t", &target, "-l", keys])
+            .status()?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!("Failed to send keys to session: {}", session_name))
+        }
+    }
+
+    /// 通过 tmux buffer 粘贴多行文本并回车
+    ///
+    /// `load-buffer` 从 stdin 读入（不受命令行长度限制），`paste-buffer -p` 在应用开启时使用
+    /// bracketed paste，换行作为文本内容而不是逐行提交。
+    pub fn paste_text(&self, session_name: &str, text: &str) -> Result<()> {
+        let target = pane_target(session_name);
+        let buffer = format!("cam-paste-{}", session_name.replace(':', "-"));
+        let mut child = tmux_command()
+            .args(["load-buffer", "-b", &buffer, "-"])
+            .stdin(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if !child.wait()?.success() {
+            return Err(anyhow!(
+                "Failed to load paste buffer for session: {}",
+                session_name
+            ));
+        }
+        let status = tmux_command()
+            .args(["paste-buffer", "-p", "-d", "-b", &buffer, "-t", &target])
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("Failed to paste into session: {}", session_name));
+        }
+        info!(session = %session_name, bytes = text.len(), "Pasted text into tmux session");
+        // 等待应用处理完粘贴内容再回车，否则回车可能落在粘贴内容中间
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        self.send_named_keys(session_name, &["Enter"])
+    }
+
+    /// 向 session 发送命名按键（如 Down、Enter、Escape），按 tmux 键名解释
+    pub fn send_named_keys(&self, session_name: &str, keys: &[&str]) -> Result<()> {
+        let target = pane_target(session_name);
+        let mut command = vec!["send-keys", "-t", &target];
+        command.extend_from_slice(keys);
+        if let Some(result) = self.control_run(&[&command]) {
+            return result
+                .map(|_| ())
+                .map_err(|e| anyhow!("Failed to send keys to session {}: {}", session_name, e));
+        }
+        let status = tmux_command()
+            .args(["send-keys", "-t", &target])
+            .args(keys)
             .status()?;
 
         if status.success() {
@@ -128,11 +352,24 @@ impl TmuxManager {
 
     /// 捕获 session 的终端输出
     pub fn capture_pane(&self, session_name: &str, lines: u32) -> Result<String> {
-        let output = Command::new("tmux")
+        let target = pane_target(session_name);
+        let start = format!("-{}", lines);
+        if let Some(result) =
+            self.control_run(&[&["capture-pane", "-t", &target, "-p", "-S", &start]])
+        {
+            return result.map(|mut outputs| outputs.remove(0)).map_err(|e| {
+                anyhow!(
+                    "Failed to capture pane from session {}: {}",
+                    session_name,
+                    e
+                )
+            });
+        }
+        let output = tmux_command()
             .args([
                 "capture-pane",
                 "-t",
-                session_name,
+                &target,
                 "-p", // print to stdout
                 "-S",
                 &format!("-{}", lines), // start from N lines back
@@ -149,13 +386,145 @@ impl TmuxManager {
         }
     }
 
+    /// 增量捕获 session 的终端输出，返回最新内容
+    ///
+    /// 通常只需一次 tmux 调用截取可见区域；有新行滚入 history 时再补截这部分，
+    /// history 被清空或滚动过多时回退为完整截取。
+    pub fn capture_pane_incremental<'a>(
+        &self,
+        session_name: &str,
+        cache: &'a mut PaneCapture,
+    ) -> Result<&'a str> {
+        let full = cache.history_size.is_none();
+        let start = if full {
+            format!("-{}", cache.history_lines)
+        } else {
+            "0".to_string()
+        };
+        let (history_size, content) = self.capture_with_history_size(session_name, &start, None)?;
+
+        match cache.history_size {
+            None => {
+                // 前 min(history_lines, history_size) 行是 history
+                let history_count = history_size.min(cache.history_lines as u64) as usize;
+                let split = match history_count {
+                    0 => 0,
+                    n => content
+                        .match_indices('\n')
+                        .nth(n - 1)
+                        .map_or(content.len(), |(i, _)| i + 1),
+                };
+                cache.history.clear();
+                cache.push_history(content[..split].lines());
+                cache.history_size = Some(history_size);
+                cache.rebuild(&content[split..]);
+            }
+            Some(previous)
+                if history_size >= previous
+                    && history_size - previous <= cache.history_lines as u64 =>
+            {
+                let scrolled = history_size - previous;
+                if scrolled > 0 {
+                    // 上次可见、现已滚入 history 的行
+                    let (now_size, gap) = self.capture_with_history_size(
+                        session_name,
+                        &format!("-{}", scrolled),
+                        Some("-1"),
+                    )?;
+                    if now_size != history_size {
+                        // 两次调用之间又有输出，完整重取
+                        cache.reset();
+                        return self.capture_pane_incremental(session_name, cache);
+                    }
+                    cache.push_history(gap.lines());
+                }
+                cache.history_size = Some(history_size);
+                cache.rebuild(&content);
+            }
+            Some(_) => {
+                debug!(session = %session_name, "Pane history reset, capturing in full");
+                cache.reset();
+                return self.capture_pane_incremental(session_name, cache);
+            }
+        }
+        Ok(cache.as_str())
+    }
+
+    /// 一次 tmux 调用中同时读取 history_size 和截屏内容
+    fn capture_with_history_size(
+        &self,
+        session_name: &str,
+        start: &str,
+        end: Option<&str>,
+    ) -> Result<(u64, String)> {
+        let target = pane_target(session_name);
+        let mut capture = vec!["capture-pane", "-p", "-t", &target, "-S", start];
+        if let Some(end) = end {
+            capture.extend(["-E", end]);
+        }
+        if let Some(result) = self.control_run(&[
+            &["display-message", "-p", "-t", &target, "#{history_size}"],
+            &capture,
+        ]) {
+            let mut outputs = result.map_err(|e| {
+                anyhow!(
+                    "Failed to capture pane from session {}: {}",
+                    session_name,
+                    e
+                )
+            })?;
+            let content = outputs.pop().unwrap_or_default();
+            let meta = outputs.pop().unwrap_or_default();
+            let history_size = meta
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("Unexpected tmux history_size: {}", meta))?;
+            return Ok((history_size, content));
+        }
+
+        let mut command = tmux_command();
+        command.args([
+            "display-message",
+            "-p",
+            "-t",
+            &target,
+            "#{history_size}",
+            ";",
+            "capture-pane",
+            "-p",
+            "-t",
+            &target,
+            "-S",
+            start,
+        ]);
+        if let Some(end) = end {
+            command.args(["-E", end]);
+        }
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to capture pane from session: {}",
+                session_name
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (meta, content) = stdout.split_once('\n').unwrap_or((&stdout, ""));
+        let history_size = meta
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Unexpected tmux history_size: {}", meta))?;
+        Ok((history_size, content.to_string()))
+    }
+
     /// 终止 session
     pub fn kill_session(&self, session_name: &str) -> Result<()> {
         debug!(session = %session_name, "Killing tmux session");
 
-        let status = Command::new("tmux")
+        let status = tmux_command()
             .args(["kill-session", "-t", session_name])
             .status()?;
+        self.invalidate_sessions();
 
         if status.success() {
             info!(session = %session_name, "Tmux session killed");
@@ -168,7 +537,7 @@ impl TmuxManager {
 
     /// 列出所有 tmux sessions
     pub fn list_sessions(&self) -> Result<Vec<String>> {
-        let output = Command::new("tmux")
+        let output = tmux_command()
             .args(["list-sessions", "-F", "#{session_name}"])
             .output()?;
 
@@ -186,7 +555,7 @@ impl TmuxManager {
 
     /// 列出所有 cam- 前缀的 session
     pub fn list_cam_sessions(&self) -> Result<Vec<String>> {
-        let output = Command::new("tmux")
+        let output = tmux_command()
             .args(["list-sessions", "-F", "#{session_name}"])
             .output()?;
 
@@ -204,9 +573,79 @@ impl TmuxManager {
     }
 }
 
-impl Default for TmuxManager {
-    fn default() -> Self {
-        Self::new()
+impl Multiplexer for TmuxManager {
+    fn name(&self) -> &'static str {
+        "tmux"
+    }
+
+    fn is_available(&self) -> bool {
+        TmuxManager::is_available(self)
+    }
+
+    fn create_session(&self, session_name: &str, working_dir: &str, command: &str) -> Result<()> {
+        TmuxManager::create_session(self, session_name, working_dir, command)
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        TmuxManager::session_exists(self, session_name)
+    }
+
+    fn rename_session(&self, old_name: &str, new_name: &str) -> Result<()> {
+        TmuxManager::rename_session(self, old_name, new_name)
+    }
+
+    fn send_keys(&self, session_name: &str, keys: &str) -> Result<()> {
+        TmuxManager::send_keys(self, session_name, keys)
+    }
+
+    fn send_keys_raw(&self, session_name: &str, keys: &str) -> Result<()> {
+        TmuxManager::send_keys_raw(self, session_name, keys)
+    }
+
+    fn send_named_keys(&self, session_name: &str, keys: &[&str]) -> Result<()> {
+        TmuxManager::send_named_keys(self, session_name, keys)
+    }
+
+    fn paste_text(&self, session_name: &str, text: &str) -> Result<()> {
+        TmuxManager::paste_text(self, session_name, text)
+    }
+
+    fn capture_pane(&self, session_name: &str, lines: u32) -> Result<String> {
+        TmuxManager::capture_pane(self, session_name, lines)
+    }
+
+    fn capture_pane_incremental<'a>(
+        &self,
+        session_name: &str,
+        cache: &'a mut PaneCapture,
+    ) -> Result<&'a str> {
+        TmuxManager::capture_pane_incremental(self, session_name, cache)
+    }
+
+    fn kill_session(&self, session_name: &str) -> Result<()> {
+        TmuxManager::kill_session(self, session_name)
+    }
+
+    fn session_pid(&self, session_name: &str) -> Option<u32> {
+        TmuxManager::pane_pid(self, session_name)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        TmuxManager::list_sessions(self)
+    }
+
+    fn list_cam_sessions(&self) -> Result<Vec<String>> {
+        TmuxManager::list_cam_sessions(self)
+    }
+
+    fn attach_command(&self, session_name: &str) -> std::process::Command {
+        let mut command = tmux_command();
+        command.args(["attach-session", "-t", session_name]);
+        command
+    }
+
+    fn attach_hint(&self, session_name: &str) -> String {
+        TmuxConfig::global().attach_hint(session_name)
     }
 }
 
@@ -281,6 +720,76 @@ mod tests {
         manager.kill_session(&session_name).unwrap();
     }
 
+    #[test]
+    fn test_capture_pane_incremental_matches_full_capture() {
+        // Given: 一个运行 cat 的 session，已有超过一屏的输出
+        let manager = TmuxManager::new();
+        let session_name = unique_session_name("cam-test");
+        manager
+            .create_session(&session_name, "/tmp", "seq 1 80; cat")
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let mut cache = PaneCapture::new(20);
+
+        // When/Then: 首次截取与完整截取一致
+        let output = manager
+            .capture_pane_incremental(&session_name, &mut cache)
+            .unwrap()
+            .to_string();
+        assert_eq!(output, manager.capture_pane(&session_name, 20).unwrap());
+        assert!(output.contains("80"));
+
+        // 新输出把可见行滚入 history 后仍一致
+        for i in 0..5 {
+            manager
+                .send_keys(&session_name, &format!("line-{}", i))
+                .unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let output = manager
+            .capture_pane_incremental(&session_name, &mut cache)
+            .unwrap()
+            .to_string();
+        assert_eq!(output, manager.capture_pane(&session_name, 20).unwrap());
+        assert!(output.contains("line-4"));
+
+        // Cleanup
+        manager.kill_session(&session_name).unwrap();
+    }
+
+    #[test]
+    fn test_control_mode_matches_subprocess() {
+        // Given: 控制模式与子进程模式的两个管理器
+        let manager = TmuxManager::new();
+        let control = TmuxManager::with_control_mode();
+        assert!(control.is_control_mode());
+        let session_name = unique_session_name("cam-test");
+        control
+            .create_session(&session_name, "/tmp", "seq 1 80; cat")
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+
+        // When/Then: 截屏结果一致，session 检查可见新建的 session
+        assert!(control.session_exists(&session_name));
+        assert_eq!(
+            control.capture_pane(&session_name, 20).unwrap(),
+            manager.capture_pane(&session_name, 20).unwrap()
+        );
+        control.send_keys(&session_name, "via-control").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let mut cache = PaneCapture::new(20);
+        let output = control
+            .capture_pane_incremental(&session_name, &mut cache)
+            .unwrap()
+            .to_string();
+        assert_eq!(output, manager.capture_pane(&session_name, 20).unwrap());
+        assert!(output.contains("via-control"));
+
+        // Cleanup
+        control.kill_session(&session_name).unwrap();
+        assert!(!control.session_exists(&session_name));
+    }
+
     #[test]
     fn test_session_exists_false_for_nonexistent() {
         // Given: 一个不存在的 session 名
@@ -0,0 +1,170 @@
+//! tmux 调用配置 - 可执行文件路径、socket（`-L`）和窗口起始编号（base-index）
+//!
+//! 在 config.json 中配置：
+//! ```json
+//! { "tmux": { "binary": "/opt/homebrew/bin/tmux", "socket": "cam", "base_index": 1 } }
+//! ```
+//! 未配置 `binary` 时通过 `which tmux` 自动查找，找不到则使用 PATH 中的 `tmux`；
+//! 未配置 `base_index` 时读取 tmux 全局选项 `base-index`。
+//! 所有 tmux 子进程和打印的 attach 提示都使用同一份配置。
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+/// tmux 调用配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxConfig {
+    /// tmux 可执行文件
+    pub binary: PathBuf,
+    /// `-L` socket 名称，None 使用默认 server
+    pub socket: Option<String>,
+    /// 窗口起始编号，None 时从 tmux 读取
+    pub base_index: Option<u32>,
+}
+
+impl Default for TmuxConfig {
+    fn default() -> Self {
+        Self {
+            binary: PathBuf::from("tmux"),
+            socket: None,
+            base_index: None,
+        }
+    }
+}
+
+impl TmuxConfig {
+    /// 进程内共享的配置（首次调用时读取 config.json）
+    pub fn global() -> &'static TmuxConfig {
+        static CONFIG: OnceLock<TmuxConfig> = OnceLock::new();
+        CONFIG.get_or_init(Self::load)
+    }
+
+    /// 读取 `~/.config/code-agent-monitor/config.json` 的 `tmux`
+    pub fn load() -> Self {
+        let json = crate::infra::paths::config_section::<Value>("tmux");
+        let mut config = Self::from_json(json.as_ref());
+        if json.as_ref().and_then(|j| j.get("binary")).is_none() {
+            if let Ok(path) = which::which("tmux") {
+                config.binary = path;
+            }
+        }
+        config
+    }
+
+    /// 从 JSON 解析（缺失字段使用默认值，不做自动查找）
+    pub fn from_json(value: Option<&Value>) -> Self {
+        let Some(value) = value else {
+            return Self::default();
+        };
+        let string = |key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        Self {
+            binary: string("binary")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("tmux")),
+            socket: string("socket"),
+            base_index: value
+                .get("base_index")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32),
+        }
+    }
+
+    /// 创建 tmux 命令（已带上 `-L socket`）
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.binary);
+        if let Some(ref socket) = self.socket {
+            command.args(["-L", socket]);
+        }
+        command
+    }
+
+    /// 窗口起始编号（未配置时读取 tmux 全局选项，读取失败为 0）
+    pub fn base_index(&self) -> u32 {
+        if let Some(index) = self.base_index {
+            return index;
+        }
+        // tmux server 未启动时读取失败，不缓存失败结果
+        static DETECTED: OnceLock<u32> = OnceLock::new();
+        if let Some(index) = DETECTED.get() {
+            return *index;
+        }
+        let detected = self
+            .command()
+            .args(["show-options", "-gv", "base-index"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse().ok());
+        match detected {
+            Some(index) => *DETECTED.get_or_init(|| index),
+            None => 0,
+        }
+    }
+
+    /// session 第一个窗口的 target（agent 运行在 session 创建时的窗口中）
+    pub fn window_target(&self, session_name: &str) -> String {
+        format!("{}:{}", session_name, self.base_index())
+    }
+
+    /// 打印给用户的 shell 命令前缀（如 `/opt/homebrew/bin/tmux -L cam`）
+    pub fn shell_prefix(&self) -> String {
+        let mut prefix = self.binary.display().to_string();
+        if let Some(ref socket) = self.socket {
+            prefix.push_str(" -L ");
+            prefix.push_str(socket);
+        }
+        prefix
+    }
+
+    /// attach 提示命令
+    pub fn attach_hint(&self, session_name: &str) -> String {
+        format!("{} attach -t {}", self.shell_prefix(), session_name)
+    }
+}
+
+/// 使用全局配置创建 tmux 命令
+pub fn tmux_command() -> Command {
+    TmuxConfig::global().command()
+}
+
+/// 使用全局配置生成 attach 提示命令
+pub fn attach_hint(session_name: &str) -> String {
+    TmuxConfig::global().attach_hint(session_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_json_and_hints() {
+        let config = TmuxConfig::from_json(Some(&json!({
+            "binary": "/opt/homebrew/bin/tmux",
+            "socket": "cam",
+            "base_index": 1
+        })));
+        assert_eq!(config.binary, PathBuf::from("/opt/homebrew/bin/tmux"));
+        assert_eq!(config.window_target("cam-1"), "cam-1:1");
+        assert_eq!(
+            config.attach_hint("cam-1"),
+            "/opt/homebrew/bin/tmux -L cam attach -t cam-1"
+        );
+        let args: Vec<_> = config.command().get_args().map(|a| a.to_owned()).collect();
+        assert_eq!(args, ["-L", "cam"]);
+
+        let default = TmuxConfig::from_json(None);
+        assert_eq!(default, TmuxConfig::default());
+        assert_eq!(default.attach_hint("cam-1"), "tmux attach -t cam-1");
+        assert_eq!(default.command().get_args().count(), 0);
+    }
+}
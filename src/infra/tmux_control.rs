@@ -0,0 +1,388 @@
+//! tmux control mode（`tmux -C`）连接 - 在一个长连接上复用 tmux 命令
+//!
+//! 每次截屏/发送按键都启动一个 tmux 子进程，agent 多时开销明显。控制模式下命令写入 stdin，
+//! 结果以 `%begin` / `%end`（或 `%error`）块按发送顺序返回，块外的行是服务端推送的通知。
+//!
+//! 控制客户端只能收到所附着 session 的 `%output`，因此连接附着在一个隐藏的
+//! `_cam-control-<pid>` session 上；agent 的终端内容仍按轮询截取，
+//! session 列表则缓存并在收到 `%sessions-changed` 推送时失效。
+//!
+//! 在 config.json 中设置 `"tmux_control_mode": true` 启用（仅 watcher 使用）。
+
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tracing::{debug, warn};
+
+use crate::infra::tmux_config::tmux_command;
+
+/// 单条命令等待结果的超时
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 是否在 config.json 中启用了 tmux 控制模式
+pub fn tmux_control_mode_enabled() -> bool {
+    crate::infra::paths::config_section::<bool>("tmux_control_mode").unwrap_or(false)
+}
+
+/// 服务端推送的通知
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlNotification {
+    /// 附着 session 中 pane 的输出（已解码转义）
+    Output { pane_id: String, data: String },
+    /// session 被创建、销毁或重命名
+    SessionsChanged,
+    /// 连接关闭
+    Exit,
+    /// 其他通知（原样保留）
+    Other(String),
+}
+
+type Reply = std::result::Result<String, String>;
+
+struct Shared {
+    /// 等待结果的命令，按发送顺序排列
+    pending: Mutex<VecDeque<Sender<Reply>>>,
+    connected: AtomicBool,
+    /// 每次 `%sessions-changed` 递增
+    sessions_epoch: AtomicU64,
+    subscribers: Mutex<Vec<Sender<ControlNotification>>>,
+}
+
+impl Shared {
+    fn notify(&self, notification: ControlNotification) {
+        if notification == ControlNotification::SessionsChanged {
+            self.sessions_epoch.fetch_add(1, Ordering::SeqCst);
+        }
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(notification.clone()).is_ok());
+    }
+
+    fn disconnect(&self) {
+        self.connected.store(false, Ordering::SeqCst);
+        for tx in self.pending.lock().unwrap().drain(..) {
+            let _ = tx.send(Err("tmux control connection closed".to_string()));
+        }
+        self.notify(ControlNotification::Exit);
+    }
+}
+
+/// tmux 控制模式连接
+pub struct TmuxControl {
+    session: String,
+    stdin: Mutex<ChildStdin>,
+    child: Mutex<Child>,
+    shared: Arc<Shared>,
+    /// (sessions_epoch, session 名) 缓存
+    sessions: Mutex<Option<(u64, HashSet<String>)>>,
+}
+
+impl TmuxControl {
+    /// 启动控制客户端（附着到本进程专用的隐藏 session）
+    pub fn connect() -> Result<Arc<Self>> {
+        let session = format!("_cam-control-{}", std::process::id());
+        let mut child = tmux_command()
+            .args(["-C", "new-session", "-A", "-s", &session])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("tmux control stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("tmux control stdout unavailable"))?;
+
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(VecDeque::new()),
+            connected: AtomicBool::new(true),
+            sessions_epoch: AtomicU64::new(0),
+            subscribers: Mutex::new(Vec::new()),
+        });
+        let reader_shared = Arc::clone(&shared);
+        std::thread::spawn(move || read_loop(BufReader::new(stdout), &reader_shared));
+
+        let control = Arc::new(Self {
+            session,
+            stdin: Mutex::new(stdin),
+            child: Mutex::new(child),
+            shared,
+            sessions: Mutex::new(None),
+        });
+        // 确认连接可用
+        control.run(&[&["display-message", "-p", "ok"]])?;
+        debug!(session = %control.session, "tmux control mode connected");
+        Ok(control)
+    }
+
+    /// 连接是否仍然可用
+    pub fn is_connected(&self) -> bool {
+        self.shared.connected.load(Ordering::SeqCst)
+    }
+
+    /// 订阅推送通知
+    pub fn subscribe(&self) -> Receiver<ControlNotification> {
+        let (tx, rx) = mpsc::channel();
+        self.shared.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// 执行一组命令（以 `;` 连接在同一行发送），按顺序返回每条命令的输出
+    ///
+    /// 参数包含换行时无法通过控制模式发送，返回错误。
+    pub fn run(&self, commands: &[&[&str]]) -> Result<Vec<String>> {
+        if !self.is_connected() {
+            return Err(anyhow!("tmux control connection closed"));
+        }
+        let mut line = String::new();
+        for (i, command) in commands.iter().enumerate() {
+            if i > 0 {
+                line.push_str(" ; ");
+            }
+            for (j, arg) in command.iter().enumerate() {
+                if arg.contains('\n') || arg.contains('\r') {
+                    return Err(anyhow!(
+                        "tmux control mode cannot send multi-line arguments"
+                    ));
+                }
+                if j > 0 {
+                    line.push(' ');
+                }
+                line.push_str(&quote_arg(arg));
+            }
+        }
+        line.push('\n');
+
+        let receivers: Vec<Receiver<Reply>> = {
+            // 写入与登记在同一把锁内完成，保证结果顺序与命令一致
+            let mut stdin = self.stdin.lock().unwrap();
+            let mut pending = self.shared.pending.lock().unwrap();
+            let receivers = commands
+                .iter()
+                .map(|_| {
+                    let (tx, rx) = mpsc::channel();
+                    pending.push_back(tx);
+                    rx
+                })
+                .collect();
+            if let Err(e) = stdin.write_all(line.as_bytes()).and_then(|_| stdin.flush()) {
+                drop(pending);
+                self.shared.disconnect();
+                return Err(e.into());
+            }
+            receivers
+        };
+
+        receivers
+            .into_iter()
+            .map(|rx| match rx.recv_timeout(COMMAND_TIMEOUT) {
+                Ok(Ok(output)) => Ok(output),
+                Ok(Err(message)) => Err(anyhow!("tmux: {}", message.trim())),
+                Err(_) => Err(anyhow!("tmux control command timed out")),
+            })
+            .collect()
+    }
+
+    /// 当前所有 session 名（在 `%sessions-changed` 推送前使用缓存）
+    pub fn session_names(&self) -> Result<HashSet<String>> {
+        let epoch = self.shared.sessions_epoch.load(Ordering::SeqCst);
+        if let Some((cached_epoch, ref names)) = *self.sessions.lock().unwrap() {
+            if cached_epoch == epoch {
+                return Ok(names.clone());
+            }
+        }
+        let output = self
+            .run(&[&["list-sessions", "-F", "#{session_name}"]])?
+            .remove(0);
+        let names: HashSet<String> = output.lines().map(String::from).collect();
+        *self.sessions.lock().unwrap() = Some((epoch, names.clone()));
+        Ok(names)
+    }
+
+    /// 让 session 缓存失效（本进程创建/销毁 session 后调用）
+    pub fn invalidate_sessions(&self) {
+        *self.sessions.lock().unwrap() = None;
+    }
+}
+
+impl Drop for TmuxControl {
+    fn drop(&mut self) {
+        let _ = tmux_command()
+            .args(["kill-session", "-t", &self.session])
+            .status();
+        if let Ok(mut child) = self.child.lock() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// 读取控制模式输出，分发命令结果和通知
+fn read_loop(reader: impl BufRead, shared: &Shared) {
+    // 当前块：(begin 行中的 "时间 编号"，是否是本客户端的命令，输出行)
+    let mut block: Option<(String, bool, Vec<String>)> = None;
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if let Some((ref id, from_client, ref mut lines)) = block {
+            let end = line
+                .strip_prefix("%end ")
+                .map(|rest| (rest, true))
+                .or_else(|| line.strip_prefix("%error ").map(|rest| (rest, false)));
+            match end {
+                Some((rest, ok)) if block_id(rest) == id => {
+                    let output = lines.iter().map(|l| format!("{}\n", l)).collect();
+                    if from_client {
+                        let tx = shared.pending.lock().unwrap().pop_front();
+                        if let Some(tx) = tx {
+                            let _ = tx.send(if ok { Ok(output) } else { Err(output) });
+                        }
+                    }
+                    block = None;
+                }
+                _ => lines.push(line),
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%begin ") {
+            // flags 为 1 表示本客户端发送的命令，0 为附着时的初始命令
+            let from_client = rest.split(' ').nth(2).is_some_and(|flags| flags != "0");
+            block = Some((block_id(rest).to_string(), from_client, Vec::new()));
+        } else if line == "%exit" || line.starts_with("%exit ") {
+            break;
+        } else {
+            shared.notify(parse_notification(&line));
+        }
+    }
+    warn!("tmux control connection closed");
+    shared.disconnect();
+}
+
+/// `%begin`/`%end` 行中用于配对的 "时间 编号" 部分
+fn block_id(rest: &str) -> &str {
+    match rest.match_indices(' ').nth(1) {
+        Some((i, _)) => &rest[..i],
+        None => rest,
+    }
+}
+
+fn parse_notification(line: &str) -> ControlNotification {
+    if let Some(rest) = line.strip_prefix("%output ") {
+        let (pane_id, data) = rest.split_once(' ').unwrap_or((rest, ""));
+        return ControlNotification::Output {
+            pane_id: pane_id.to_string(),
+            data: decode_output(data),
+        };
+    }
+    if line == "%sessions-changed" {
+        return ControlNotification::SessionsChanged;
+    }
+    ControlNotification::Other(line.to_string())
+}
+
+/// 解码 `%output` 中的八进制转义（`\\033`、`\\\\`）
+fn decode_output(data: &str) -> String {
+    let bytes = data.as_bytes();
+    let octal = |i: usize| {
+        bytes
+            .get(i)
+            .filter(|b| (b'0'..=b'7').contains(b))
+            .map(|b| u32::from(b - b'0'))
+    };
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if let (Some(a), Some(b), Some(c)) = (octal(i + 1), octal(i + 2), octal(i + 3)) {
+                decoded.push((a * 64 + b * 8 + c) as u8);
+                i += 4;
+                continue;
+            }
+            if bytes.get(i + 1) == Some(&b'\\') {
+                decoded.push(b'\\');
+                i += 2;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// 按 tmux 命令语法给参数加单引号
+fn quote_arg(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\"'\"'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_parse_helpers() {
+        assert_eq!(quote_arg("it's #{x}; y"), "'it'\"'\"'s #{x}; y'");
+        assert_eq!(block_id("1792005773 15044 1"), "1792005773 15044");
+        assert_eq!(
+            parse_notification(r"%output %3 a\033[0m\\b\015\012"),
+            ControlNotification::Output {
+                pane_id: "%3".to_string(),
+                data: "a\u{1b}[0m\\b\r\n".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_notification("%sessions-changed"),
+            ControlNotification::SessionsChanged
+        );
+    }
+
+    #[test]
+    fn test_control_mode_commands() {
+        let control = TmuxControl::connect().unwrap();
+        let session = format!("cam-test-control-{}", std::process::id());
+        Command::new("tmux")
+            .args(["new-session", "-d", "-s", &session, "cat"])
+            .status()
+            .unwrap();
+
+        // 同一行的多条命令按顺序返回
+        let outputs = control
+            .run(&[
+                &["display-message", "-p", "-t", &session, "#{session_name}"],
+                &["send-keys", "-t", &session, "-l", "it's ok; #{x}"],
+            ])
+            .unwrap();
+        assert_eq!(outputs[0], format!("{}\n", session));
+        assert!(control
+            .run(&[&["has-session", "-t", "cam-no-such"]])
+            .is_err());
+
+        // 推送的 %sessions-changed 使缓存失效
+        let notifications = control.subscribe();
+        assert!(control.session_names().unwrap().contains(&session));
+        Command::new("tmux")
+            .args(["kill-session", "-t", &session])
+            .status()
+            .unwrap();
+        let changed = notifications
+            .recv_timeout(Duration::from_secs(2))
+            .into_iter()
+            .chain(notifications.try_iter())
+            .any(|n| n == ControlNotification::SessionsChanged);
+        assert!(changed);
+        assert!(!control.session_names().unwrap().contains(&session));
+    }
+}
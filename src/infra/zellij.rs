@@ -0,0 +1,187 @@
+//! zellij 后端 - 通过 `zellij --session <name> action ...` 操作后台 session
+//!
+//! session 用 `attach --create-background` 创建，命令写入默认 shell 并 `exec`，
+//! 命令退出即关闭 pane。所有操作作用于 session 当前聚焦的 pane。需要 zellij 0.39+。
+
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+use tracing::{debug, info};
+
+use crate::infra::multiplexer::{
+    dump_path, named_key_bytes, read_dump, run_checked, tail_lines, Multiplexer,
+};
+
+/// zellij 管理器
+#[derive(Debug, Clone, Default)]
+pub struct ZellijManager;
+
+impl ZellijManager {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn action(session_name: &str) -> Command {
+        let mut command = Command::new("zellij");
+        command.args(["--session", session_name, "action"]);
+        command
+    }
+
+    fn write_bytes(session_name: &str, bytes: &[u8]) -> Result<()> {
+        run_checked(
+            Self::action(session_name)
+                .arg("write")
+                .args(bytes.iter().map(|b| b.to_string())),
+            &format!("send keys to session {}", session_name),
+        )
+        .map(|_| ())
+    }
+
+    fn write_chars(session_name: &str, text: &str) -> Result<()> {
+        run_checked(
+            Self::action(session_name).args(["write-chars", text]),
+            &format!("send keys to session {}", session_name),
+        )
+        .map(|_| ())
+    }
+
+    fn dump_screen(session_name: &str, full: bool) -> Result<String> {
+        let path = dump_path("zellij");
+        let mut command = Self::action(session_name);
+        command.arg("dump-screen");
+        if full {
+            command.arg("--full");
+        }
+        run_checked(
+            command.arg(&path),
+            &format!("capture pane from session {}", session_name),
+        )?;
+        read_dump(&path)
+    }
+}
+
+/// 解析 `zellij list-sessions --short --no-formatting` 输出
+fn parse_sessions(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl Multiplexer for ZellijManager {
+    fn name(&self) -> &'static str {
+        "zellij"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("zellij")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn create_session(&self, session_name: &str, working_dir: &str, command: &str) -> Result<()> {
+        debug!(session = %session_name, working_dir = %working_dir, "Creating zellij session");
+        run_checked(
+            Command::new("zellij")
+                .args(["attach", "--create-background", session_name])
+                .current_dir(working_dir),
+            &format!("create zellij session {}", session_name),
+        )?;
+        Self::write_chars(session_name, &format!("exec {}", command))?;
+        Self::write_bytes(session_name, b"\r")?;
+        info!(session = %session_name, "Zellij session created");
+        Ok(())
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        self.list_sessions()
+            .map(|sessions| sessions.iter().any(|s| s == session_name))
+            .unwrap_or(false)
+    }
+
+    fn rename_session(&self, old_name: &str, new_name: &str) -> Result<()> {
+        run_checked(
+            Self::action(old_name).args(["rename-session", new_name]),
+            &format!("rename session {} to {}", old_name, new_name),
+        )
+        .map(|_| ())
+    }
+
+    fn send_keys(&self, session_name: &str, keys: &str) -> Result<()> {
+        Self::write_chars(session_name, keys)?;
+        Self::write_bytes(session_name, b"\r")
+    }
+
+    fn send_keys_raw(&self, session_name: &str, keys: &str) -> Result<()> {
+        Self::write_chars(session_name, keys)
+    }
+
+    fn send_named_keys(&self, session_name: &str, keys: &[&str]) -> Result<()> {
+        let mut bytes = Vec::new();
+        for key in keys {
+            bytes.extend(named_key_bytes(key).ok_or_else(|| anyhow!("Unsupported key: {}", key))?);
+        }
+        Self::write_bytes(session_name, &bytes)
+    }
+
+    fn capture_pane(&self, session_name: &str, lines: u32) -> Result<String> {
+        let visible_rows = Self::dump_screen(session_name, false)?.lines().count();
+        let full = Self::dump_screen(session_name, true)?;
+        Ok(tail_lines(&full, visible_rows, lines))
+    }
+
+    fn kill_session(&self, session_name: &str) -> Result<()> {
+        run_checked(
+            Command::new("zellij").args(["kill-session", session_name]),
+            &format!("kill session {}", session_name),
+        )?;
+        // 避免已退出的 session 留在列表中被恢复
+        let _ = Command::new("zellij")
+            .args(["delete-session", session_name])
+            .output();
+        Ok(())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<String>> {
+        let output = Command::new("zellij")
+            .args(["list-sessions", "--short", "--no-formatting"])
+            .output()?;
+        // 没有 session 时 zellij 返回非零
+        Ok(parse_sessions(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn attach_command(&self, session_name: &str) -> Command {
+        let mut command = Command::new("zellij");
+        command.args(["attach", session_name]);
+        command
+    }
+
+    fn attach_hint(&self, session_name: &str) -> String {
+        format!("zellij attach {}", session_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sessions_and_attach() {
+        assert_eq!(
+            parse_sessions("cam-1\n  cam-2 \n\n"),
+            vec!["cam-1".to_string(), "cam-2".to_string()]
+        );
+        let manager = ZellijManager::new();
+        let args: Vec<_> = manager
+            .attach_command("cam-1")
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(args, ["attach", "cam-1"]);
+        assert_eq!(manager.attach_hint("cam-1"), "zellij attach cam-1");
+    }
+}
@@ -5,9 +5,11 @@ pub mod agent;
 pub mod ai;
 pub mod anthropic;
 pub mod cli;
+pub mod embedding;
 pub mod infra;
 #[path = "mcp_mod/mod.rs"]
 pub mod mcp;
+pub mod monitor;
 pub mod notification;
 pub mod service;
 #[path = "session_mod/mod.rs"]
@@ -18,19 +20,23 @@ pub mod tui;
 // Re-exports from infra (backwards compatibility)
 pub use infra::input::{InputWaitDetector, InputWaitPattern, InputWaitResult};
 pub use infra::jsonl::{extract_tool_target_from_input, format_tool_use, JsonlEvent, JsonlParser};
-pub use infra::{truncate_str, ProcessScanner, TmuxManager};
+pub use infra::{truncate_str, Multiplexer, MultiplexerKind, ProcessScanner, TmuxManager};
 
 // Re-exports from agent (backwards compatibility)
-pub use agent::WatcherDaemon;
+pub use agent::{WatchEngine, WatchFilter, WatcherDaemon};
 pub use agent::{format_watch_event, AgentSnapshot, AgentWatcher, WatchEvent};
 pub use agent::{
     AgentManager, AgentRecord, AgentStatus, AgentType, StartAgentRequest, StartAgentResponse,
 };
 
+// 库接口
+pub use monitor::{Monitor, MonitorBuilder};
+
 // Re-exports from session (backwards compatibility)
 pub use session::{
-    AgentContext, BatchFilter, BatchReplyResult, ConfirmationType, ConversationState,
-    ConversationStateManager, PendingConfirmation, ReplyResult,
+    AckResult, AgentContext, BatchFilter, BatchReplyResult, ConfirmationType, ConversationState,
+    ConversationStateManager, PendingConfirmation, PendingResolution, ReplyResult,
+    ReplyVerification,
 };
 pub use session::{SessionFilter, SessionManager};
 
@@ -42,12 +48,14 @@ pub use mcp::{McpError, McpRequest, McpResponse, McpTool};
 pub use notification::deduplicator::NotificationDeduplicator;
 pub use notification::event::{NotificationEvent, NotificationEventBuilder, NotificationEventType};
 pub use notification::OpenclawNotifier;
-pub use notification::SendResult;
+pub use notification::{Notifier, SendResult};
 pub use notification::{
-    CompletionSummary, ErrorSummary, NotificationSummarizer, PermissionSummary, RiskLevel,
+    CompletionSummary, ErrorCategory, ErrorSummary, NotificationSummarizer, PermissionSummary,
+    RiskLevel,
 };
 pub use notification::{MergedNotification, NotifyThrottle, ThrottledEvent};
-pub use notification::{Notifier, NotifyEvent, Watcher};
+#[allow(deprecated)]
+pub use notification::{NotifyEvent, Watcher};
 
 // Re-exports from team (backwards compatibility)
 pub use team::{
@@ -3,23 +3,62 @@
 //! 监控和管理 AI 编码代理进程 (Claude Code, OpenCode, Codex)
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use code_agent_monitor::{
-    cli::{BootstrapArgs, CodexNotifyArgs, SetupArgs, StartArgs},
-    discover_teams, get_team_members, list_tasks, list_team_names, AgentManager, AgentWatcher,
-    BatchFilter, ConversationStateManager, InboxMessage, LaunchdService, McpServer,
-    NotificationEvent, NotificationEventType, OpenclawNotifier, ProcessScanner, ReplyResult,
+    agent::prompt_queue::{send_or_queue, Delivery, PromptQueue},
+    cli::{
+        AttachArgs, BenchArgs, BootstrapArgs, CliError, CodexNotifyArgs, CompareArgs, CompleteArgs,
+        CompletionsArgs, DashboardArgs, EmbeddingsArgs, ErrorFormat, EventsArgs, HandoffArgs,
+        InboxAction, InfoArgs, KillArgs, LogsArgs, MigrateArgs, PauseArgs, PtyHostArgs, PurgeArgs,
+        QueueArgs, RecordArgs, ReplayArgs, ResumeAllArgs, SendFileArgs, SendImageArgs,
+        SessionsArgs, SetupArgs, ShowArgs, SimulateArgs, SnapshotArgs, SpoolArgs, StartArgs,
+        StatsArgs, StatusArgs,
+    },
+    discover_teams, format_watch_event, get_team_members,
+    infra::agent_log::{self, AgentLogLayer},
+    infra::multiplexer::attach_hint,
+    infra::ports::format_ports,
+    list_tasks, list_team_names,
+    notification::{parse_batch_reply, BatchSelection, PermissionBatcher},
+    session::{reply_macro, ReplySender},
+    team::{MessageFields, MessageKind, MessagePriority},
+    AckResult, AgentManager, BatchFilter, ConversationStateManager, InboxMessage, LaunchdService,
+    McpServer, NotificationEvent, OpenclawNotifier, ProcessScanner, ReplyResult, ReplyVerification,
     RiskLevel, SendResult, SessionManager, StartAgentRequest, TeamBridge, TeamOrchestrator,
-    TmuxManager, WatchEvent, Watcher, WatcherDaemon,
+    TmuxManager, WatchEngine, WatchEvent, WatchFilter, WatcherDaemon,
 };
 use tracing::{debug, error, info, warn};
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 #[derive(Parser)]
 #[command(name = "cam")]
 #[command(about = "Code Agent Monitor - 监控和管理 AI 编码代理进程")]
 #[command(version)]
+#[command(after_help = "常用示例:
+  cam start \"实现 TODO 应用\"        启动 Claude Code 并发送初始 prompt
+  cam list                          列出所有代理进程
+  cam pending-confirmations         查看待处理确认
+  cam reply y --target <agent_id>   回复指定 agent
+  cam reply @approve-tests          执行回复宏（config.json 的 reply_macros）
+  cam queue list                    查看 agent 处理中时排队的指令
+
+启用 shell 补全:
+  bash: eval \"$(cam completions bash)\"
+  zsh:  eval \"$(cam completions zsh)\"
+  fish: cam completions fish | source")]
 struct Cli {
+    /// 错误输出格式（json 便于脚本解析，退出码见 cli::error）
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    error_format: ErrorFormat,
+
+    /// config.json 路径（也可用环境变量 CAM_CONFIG），用于运行隔离实例
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<std::path::PathBuf>,
+
+    /// 状态目录（也可用环境变量 CAM_STATE_DIR），默认 ~/.config/code-agent-monitor
+    #[arg(long, global = true, value_name = "DIR")]
+    state_dir: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -34,20 +73,18 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
-    /// 获取指定进程的详细信息
-    Info {
-        /// 进程 PID
-        pid: u32,
-        /// 输出 JSON 格式
-        #[arg(long)]
-        json: bool,
-    },
-    /// 列出所有会话
-    Sessions {
-        /// 输出 JSON 格式
-        #[arg(long)]
-        json: bool,
-    },
+    /// 获取指定进程或 agent 的详细信息与状态历史
+    Info(InfoArgs),
+    /// 对比两个 agent 的改动和结论（AI 生成方案、风险、测试覆盖对比）
+    Compare(CompareArgs),
+    /// 把会话转交给另一种 agent（导出上下文、停止源 agent、启动目标 agent 并注入交接摘要）
+    Handoff(HandoffArgs),
+    /// 输出内部事件流（watch 事件、通知、回复、状态变化），--follow 持续跟踪，--json 每行一个 JSON
+    Events(EventsArgs),
+    /// 在本机查看通知的完整终端快照（快照隐私模式下通知只附带 ID）
+    Show(ShowArgs),
+    /// 列出会话（按项目、时间、状态过滤）
+    Sessions(SessionsArgs),
     /// 在 tmux 中恢复指定会话
     Resume {
         /// 会话 ID
@@ -57,33 +94,37 @@ enum Commands {
         name: Option<String>,
     },
     /// 终止指定进程
-    Kill {
-        /// 进程 PID
-        pid: u32,
-    },
+    Kill(KillArgs),
+    /// 把截图等图片转发给 agent
+    SendImage(SendImageArgs),
+    /// 把聊天中收到的图片、文件保存到 agent 工作区并通知 agent
+    SendFile(SendFileArgs),
     /// 启动 MCP Server 模式
     Serve {
         /// 监听端口
         #[arg(long, default_value = "3000")]
         port: u16,
+        /// 同时在该端口（127.0.0.1）接收签名的远程回复（需配置 webhook.signing_secret）
+        #[arg(long)]
+        webhook_port: Option<u16>,
     },
-    /// 监控代理进程状态并发送通知
+    /// 前台监控 agent 事件（可按 agent 与事件类型过滤）
     Watch {
         /// 轮询间隔（秒）
         #[arg(long, short, default_value = "5")]
         interval: u64,
-        /// 使用 OpenClaw 发送通知
-        #[arg(long)]
+        /// 同时发送通知（与 watch-daemon 相同的通知管道）
+        #[arg(long, alias = "notify")]
         openclaw: bool,
+        /// 过滤条件，可多次指定（如 agent=cam-*）
+        #[arg(long, value_name = "KEY=GLOB")]
+        filter: Vec<String>,
+        /// 只显示这些事件类型，逗号分隔（waiting,exited,error,tool,stuck,idle,recovery,resolved,resumed）
+        #[arg(long)]
+        events: Option<String>,
     },
-    /// 查看会话的最近消息
-    Logs {
-        /// 会话 ID
-        session_id: String,
-        /// 显示最近 N 条消息
-        #[arg(long, short, default_value = "5")]
-        limit: usize,
-    },
+    /// 查看会话的最近消息（--follow 持续跟踪）
+    Logs(LogsArgs),
     /// 后台监控 daemon（内部使用，由 agent_start 自动启动）
     WatchDaemon {
         /// 轮询间隔（秒）
@@ -158,6 +199,9 @@ enum Commands {
         /// 项目路径
         #[arg(long, short)]
         project: Option<String>,
+        /// 每个成员在独立的 git worktree 和分支中工作
+        #[arg(long)]
+        worktree: bool,
     },
     /// 删除 Agent Team
     TeamDelete {
@@ -172,10 +216,14 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
-    /// 读取成员 inbox
+    /// 读取成员 inbox（`cam inbox prune <team>` 按保留策略归档旧消息）
+    #[command(args_conflicts_with_subcommands = true)]
     Inbox {
+        #[command(subcommand)]
+        action: Option<InboxAction>,
         /// Team 名称
-        team: String,
+        #[arg(required = true)]
+        team: Option<String>,
         /// 成员名称
         #[arg(long, short)]
         member: Option<String>,
@@ -197,6 +245,18 @@ enum Commands {
         /// 发送者名称
         #[arg(long, default_value = "cam")]
         from: String,
+        /// 消息类型
+        #[arg(long = "type", value_enum, default_value = "text")]
+        kind: MessageKind,
+        /// 关联的任务 ID
+        #[arg(long = "task")]
+        task_ref: Option<String>,
+        /// 附件路径（可重复）
+        #[arg(long = "attach")]
+        attachments: Vec<std::path::PathBuf>,
+        /// 优先级
+        #[arg(long, value_enum, default_value = "normal")]
+        priority: MessagePriority,
     },
     /// 实时监控 Team inbox
     TeamWatch {
@@ -226,6 +286,9 @@ enum Commands {
     TeamProgress {
         /// Team 名称
         team: String,
+        /// 显示历史快照和待处理任务的燃尽趋势
+        #[arg(long)]
+        history: bool,
         /// 输出 JSON 格式
         #[arg(long)]
         json: bool,
@@ -235,6 +298,14 @@ enum Commands {
         /// Team 名称
         team: String,
     },
+    /// 依次合并成员 worktree 分支到项目当前分支，冲突时发送通知
+    TeamMerge {
+        /// Team 名称
+        team: String,
+        /// 输出 JSON 格式
+        #[arg(long)]
+        json: bool,
+    },
     /// 获取待处理的确认请求
     PendingConfirmations {
         /// 输出 JSON 格式
@@ -243,8 +314,12 @@ enum Commands {
     },
     /// 回复待处理的确认请求
     Reply {
-        /// 回复内容（y/n/1/2/3 或自定义文本）
-        reply: String,
+        /// 回复内容（y/n/1/2/3 或自定义文本；合并权限通知可回复 "y all" / "y 1,3"；`@name` 执行回复宏）
+        #[arg(required_unless_present = "file")]
+        reply: Option<String>,
+        /// 从文件读取回复内容（多行内容以粘贴方式输入）
+        #[arg(long, value_name = "PATH", conflicts_with = "reply")]
+        file: Option<std::path::PathBuf>,
         /// 目标 agent_id 或 confirmation_id（可选）
         #[arg(long, short)]
         target: Option<String>,
@@ -257,6 +332,17 @@ enum Commands {
         /// 批量回复指定风险等级的请求 (low/medium/high)
         #[arg(long, conflicts_with_all = ["target", "all", "agent"])]
         risk: Option<String>,
+        /// 回复者 ID（按 reply_auth 授权，如 tg:123456；未指定时配置了列表的等级会拒绝）
+        #[arg(long)]
+        from: Option<String>,
+        /// 用户在渠道中回复的消息 ID，按通知线程找到目标 agent
+        #[arg(long, value_name = "MESSAGE_ID", conflicts_with_all = ["target", "all", "agent", "risk"])]
+        reply_to: Option<String>,
+    },
+    /// 确认已看到通知，停止提醒（稍后再处理）
+    Ack {
+        /// 目标 agent_id 或 confirmation_id（只有一个待处理确认时可省略）
+        target: Option<String>,
     },
     /// 启动 TUI 仪表盘
     Tui {
@@ -289,6 +375,46 @@ enum Commands {
     },
     /// 卸载 watcher 服务（cam service uninstall 的快捷方式）
     Uninstall,
+    /// 生成 shell 补全脚本（支持 agent_id、team、session id 动态补全）
+    Completions(CompletionsArgs),
+    /// 升级持久化文件的 schema 版本（启动时会自动执行）
+    Migrate(MigrateArgs),
+    /// 录制 agent 的终端快照与 hook 输入，用于排查通知问题
+    Record(RecordArgs),
+    /// 离线重放录制包，重新执行检测→提取→格式化流程
+    Replay(ReplayArgs),
+    /// 在 tmux 中启动脚本化的模拟 agent（无需真实 AI CLI 即可测试）
+    Simulate(SimulateArgs),
+    /// 显示 watcher、agent 数量与通知限流状态（令牌桶、摘要队列）
+    Status(StatusArgs),
+    /// 卸载 CAM：移除 hooks、服务、tmux session 与状态目录
+    Purge(PurgeArgs),
+    /// 立即发布一次团队看板快照（config.json 的 dashboard_export）
+    Dashboard(DashboardArgs),
+    /// 中断运行中的 agent 并标记暂停（会议、API 故障时使用）
+    Pause(PauseArgs),
+    /// 继续所有暂停的 agent
+    ResumeAll(ResumeAllArgs),
+    /// 查看和取消 agent 处理中时排队的指令
+    Queue(QueueArgs),
+    /// 显示通知投递延迟统计（p50/p90/p99，按渠道）与 SLO 状态
+    Stats(StatsArgs),
+    /// 管理嵌入模型与提示模板库（status/pull/rebuild/clear/match）
+    Embeddings(EmbeddingsArgs),
+    /// 查看或立即处理 cam notify 快速路径积压的 hook 事件（status/flush）
+    Spool(SpoolArgs),
+    /// 用录制的终端快照对比提取配置的延迟百分位与 AI 调用次数
+    Bench(BenchArgs),
+    /// 维护终端快照语料（capture：截取 agent 屏幕加入 examples/snapshots/）
+    Snapshot(SnapshotArgs),
+    /// 附着到 agent 的 session（PTY 后端在当前终端回放并转发输出，Ctrl-] 断开）
+    Attach(AttachArgs),
+    /// 动态补全查询（内部使用，由补全脚本调用）
+    #[command(name = "__complete", hide = true)]
+    Complete(CompleteArgs),
+    /// PTY 后端的 session host 进程（内部使用）
+    #[command(name = "__pty-host", hide = true)]
+    PtyHost(PtyHostArgs),
 }
 
 #[derive(Subcommand)]
@@ -313,18 +439,28 @@ enum ServiceAction {
         /// 持续跟踪日志
         #[arg(long, short)]
         follow: bool,
+        /// 只看指定 agent 的结构化日志（~/.config/code-agent-monitor/logs/<agent_id>.log）
+        #[arg(long)]
+        agent: Option<String>,
     },
 }
 
+/// 按 config.json 的 webhook 配置创建通知器
+fn load_notifier() -> OpenclawNotifier {
+    match code_agent_monitor::notification::load_webhook_config_from_file() {
+        Some(config) => {
+            OpenclawNotifier::with_webhook(config).unwrap_or_else(|_| OpenclawNotifier::new())
+        }
+        None => OpenclawNotifier::new(),
+    }
+}
+
 /// Record hook event timestamp for cross-process coordination with watcher
 fn record_hook_event(agent_id: &str) -> Result<()> {
     use std::collections::HashMap;
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    let hook_file = dirs::home_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join(".config/code-agent-monitor")
-        .join("last_hook_events.json");
+    let hook_file = code_agent_monitor::infra::paths::state_dir().join("last_hook_events.json");
 
     // Read existing events
     let mut events: HashMap<String, u64> = if hook_file.exists() {
@@ -352,7 +488,7 @@ fn record_hook_event(agent_id: &str) -> Result<()> {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     // 清除代理环境变量，避免 API 请求超时
     use std::env;
     env::remove_var("HTTP_PROXY");
@@ -365,20 +501,110 @@ async fn main() -> Result<()> {
     env::set_var("NO_PROXY", "*");
     env::set_var("no_proxy", "*");
 
+    let cli = Cli::parse();
+
+    // 隔离实例：写入环境变量（绝对路径），子进程（daemon、hooks）使用同一配置与状态目录
+    for (name, path) in [
+        (code_agent_monitor::infra::paths::CONFIG_ENV, &cli.config),
+        (
+            code_agent_monitor::infra::paths::STATE_DIR_ENV,
+            &cli.state_dir,
+        ),
+    ] {
+        if let Some(path) = path {
+            env::set_var(
+                name,
+                std::path::absolute(path).unwrap_or_else(|_| path.clone()),
+            );
+        }
+    }
+
     // 初始化 tracing 日志系统
     // 通过 RUST_LOG 环境变量控制日志级别，默认为 info
     // 例如: RUST_LOG=debug cam watch-daemon
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("code_agent_monitor=info,cam=info"));
-
-    fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(filter)
-        .with_target(false)
-        .with_thread_ids(false)
+    // watcher daemon 额外按 agent 写入 JSON Lines 日志（见 infra::agent_log）
+    let agent_log = matches!(cli.command, Commands::WatchDaemon { .. })
+        .then(AgentLogLayer::from_config)
+        .flatten();
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_target(false)
+                .with_thread_ids(false),
+        )
+        .with(agent_log)
         .init();
 
-    let cli = Cli::parse();
+    let error_format = cli.error_format;
+
+    if let Err(e) = run(cli).await {
+        code_agent_monitor::cli::exit_with_error(&e, error_format);
+    }
+}
+
+/// `cam notify` 快速路径：只入队，由 watcher daemon 异步解析、提取和发送
+fn spool_notify_hook(
+    event: &str,
+    agent_id: Option<&str>,
+    context: &str,
+    dry_run: bool,
+    no_ai: bool,
+) -> bool {
+    use std::io::Write;
+
+    let event = code_agent_monitor::cli::normalize_hook_event_name(event);
+    if !code_agent_monitor::cli::try_spool_hook(event, agent_id, context, dry_run, no_ai) {
+        return false;
+    }
+    let log_dir = code_agent_monitor::infra::paths::state_dir();
+    let _ = std::fs::create_dir_all(&log_dir);
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_dir.join("hook.log"))
+    {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        let _ = writeln!(file, "[{}] Hook spooled: event={}", timestamp, event);
+    }
+    true
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    // 补全脚本在每次按 Tab 时调用，跳过后续初始化以保持响应速度
+    if let Commands::Complete(args) = cli.command {
+        return code_agent_monitor::cli::handle_complete(args, &Cli::command());
+    }
+    if let Commands::PtyHost(args) = cli.command {
+        return code_agent_monitor::cli::handle_pty_host(args);
+    }
+
+    // hook 每次触发都会调用 cam notify：能入队时直接返回，跳过后续初始化
+    let mut hook_input = None;
+    if let Commands::Notify {
+        event,
+        agent_id,
+        dry_run,
+        no_ai,
+        ..
+    } = &cli.command
+    {
+        // 从 stdin 读取 hook 输入（Claude Code 通过 stdin 传递 JSON）
+        let context = std::io::read_to_string(std::io::stdin()).unwrap_or_default();
+        if spool_notify_hook(event, agent_id.as_deref(), &context, *dry_run, *no_ai) {
+            return Ok(());
+        }
+        hook_input = Some(context);
+    }
+
+    // 合并旧版本目录（cam migrate 自行输出结果）
+    if !matches!(cli.command, Commands::Migrate(_)) {
+        code_agent_monitor::cli::auto_consolidate_legacy();
+    }
 
     match cli.command {
         Commands::Start(args) => {
@@ -386,52 +612,48 @@ async fn main() -> Result<()> {
         }
         Commands::List { json } => {
             let scanner = ProcessScanner::new();
-            let agents = scanner.scan_agents()?;
+            let mut agents = scanner.scan_agents()?;
+            let records = AgentManager::new().list_agents().unwrap_or_default();
+            code_agent_monitor::infra::process::attach_agent_records(&mut agents, &records);
 
             if json {
                 println!("{}", serde_json::to_string_pretty(&agents)?);
             } else {
                 println!("发现 {} 个代理进程:\n", agents.len());
                 for agent in agents {
+                    let context = agent
+                        .context_usage
+                        .map(|usage| format!(" | 上下文: {}%", usage))
+                        .unwrap_or_default();
+                    let ports = if agent.ports.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" | 端口: {}", format_ports(&agent.ports))
+                    };
                     println!(
-                        "  PID: {} | 类型: {} | 工作目录: {}",
-                        agent.pid, agent.agent_type, agent.working_dir
+                        "  PID: {} | 类型: {} | 工作目录: {}{}{}",
+                        agent.pid, agent.agent_type, agent.working_dir, context, ports
                     );
                 }
             }
         }
-        Commands::Info { pid, json } => {
-            let scanner = ProcessScanner::new();
-            if let Some(agent) = scanner.get_agent_info(pid)? {
-                if json {
-                    println!("{}", serde_json::to_string_pretty(&agent)?);
-                } else {
-                    println!("进程信息:");
-                    println!("  PID: {}", agent.pid);
-                    println!("  类型: {}", agent.agent_type);
-                    println!("  命令: {}", agent.command);
-                    println!("  工作目录: {}", agent.working_dir);
-                    println!("  会话 ID: {:?}", agent.session_id);
-                }
-            } else {
-                eprintln!("未找到 PID {} 的代理进程", pid);
-            }
+        Commands::Info(args) => {
+            code_agent_monitor::cli::handle_info(args)?;
         }
-        Commands::Sessions { json } => {
-            let manager = SessionManager::new();
-            let sessions = manager.list_sessions()?;
-
-            if json {
-                println!("{}", serde_json::to_string_pretty(&sessions)?);
-            } else {
-                println!("发现 {} 个会话:\n", sessions.len());
-                for session in sessions {
-                    println!(
-                        "  ID: {} | 项目: {} | 状态: {}",
-                        session.id, session.project_path, session.status
-                    );
-                }
-            }
+        Commands::Compare(args) => {
+            code_agent_monitor::cli::handle_compare(args)?;
+        }
+        Commands::Handoff(args) => {
+            code_agent_monitor::cli::handle_handoff(args)?;
+        }
+        Commands::Events(args) => {
+            code_agent_monitor::cli::handle_events(args)?;
+        }
+        Commands::Show(args) => {
+            code_agent_monitor::cli::handle_show(args)?;
+        }
+        Commands::Sessions(args) => {
+            code_agent_monitor::cli::handle_sessions(args)?;
         }
         Commands::Resume { session_id, name } => {
             let session_manager = SessionManager::new();
@@ -439,7 +661,7 @@ async fn main() -> Result<()> {
             // 获取会话信息以获取 project_path
             let session = session_manager
                 .get_session(&session_id)?
-                .ok_or_else(|| anyhow::anyhow!("会话 {} 不存在", session_id))?;
+                .ok_or_else(|| CliError::SessionNotFound(format!("会话 {} 不存在", session_id)))?;
 
             let project_path = if session.project_path.is_empty() {
                 ".".to_string()
@@ -456,6 +678,7 @@ async fn main() -> Result<()> {
                 initial_prompt: None,
                 agent_id: None,
                 tmux_session: None,
+                container: None,
             })?;
 
             // 如果用户指定了自定义名称，重命名 tmux session
@@ -471,62 +694,142 @@ async fn main() -> Result<()> {
             println!("已在 tmux 中恢复会话");
             println!("agent_id: {}", response.agent_id);
             println!("tmux_session: {}", final_tmux_session);
-            println!(
-                "查看输出: /opt/homebrew/bin/tmux attach -t {}",
-                final_tmux_session
-            );
+            println!("查看输出: {}", attach_hint(&final_tmux_session));
         }
-        Commands::Kill { pid } => {
-            let scanner = ProcessScanner::new();
-            scanner.kill_agent(pid)?;
-            println!("已终止进程: {}", pid);
+        Commands::Kill(args) => {
+            code_agent_monitor::cli::handle_kill(args)?;
+        }
+        Commands::SendImage(args) => {
+            code_agent_monitor::cli::handle_send_image(args)?;
         }
-        Commands::Serve { port } => {
+        Commands::SendFile(args) => {
+            code_agent_monitor::cli::handle_send_file(args)?;
+        }
+        Commands::Serve { port, webhook_port } => {
+            let receiver = match webhook_port {
+                Some(webhook_port) => {
+                    let verifier =
+                        code_agent_monitor::notification::load_webhook_config_from_file()
+                            .and_then(|config| config.signing_secret)
+                            .map(code_agent_monitor::notification::WebhookVerifier::new)
+                            .ok_or_else(|| {
+                                CliError::InvalidArgument(
+                                    "--webhook-port requires webhook.signing_secret in config.json"
+                                        .to_string(),
+                                )
+                            })?;
+                    Some(tokio::spawn(
+                        code_agent_monitor::service::run_webhook_receiver(webhook_port, verifier),
+                    ))
+                }
+                None => None,
+            };
             let server = McpServer::new(port);
             server.run().await?;
+            // stdin 关闭后继续接收 webhook
+            if let Some(receiver) = receiver {
+                receiver.await??;
+            }
         }
-        Commands::Watch { interval, openclaw } => {
-            let mut watcher = Watcher::new(interval, openclaw);
-            watcher.watch().await?;
-        }
-        Commands::Logs { session_id, limit } => {
-            let manager = SessionManager::new();
-            let messages = manager.get_session_logs(&session_id, limit)?;
+        Commands::Watch {
+            interval,
+            openclaw,
+            filter,
+            events,
+        } => {
+            use std::time::Duration;
 
-            if messages.is_empty() {
-                println!("未找到会话 {} 的消息", session_id);
-            } else {
-                println!("会话 {} 的最近 {} 条消息:\n", session_id, messages.len());
-                for (i, msg) in messages.iter().enumerate() {
-                    println!("--- 消息 {} ({}) ---", i + 1, msg.role);
-                    println!("{}\n", msg.content);
+            let filter = WatchFilter::parse(&filter, events.as_deref())
+                .map_err(|e| CliError::InvalidArgument(e.to_string()))?;
+            let notifier = openclaw.then(load_notifier);
+            let mut engine = WatchEngine::new(notifier, filter);
+            eprintln!(
+                "开始监控 agent 事件，轮询间隔: {}秒 (Ctrl+C 退出)",
+                interval
+            );
+            loop {
+                match engine.poll() {
+                    Ok(events) => {
+                        for event in &events {
+                            println!(
+                                "[{}] {}",
+                                chrono::Local::now().format("%H:%M:%S"),
+                                format_watch_event(event)
+                            );
+                        }
+                    }
+                    Err(e) => eprintln!("❌ 轮询失败: {}", e),
                 }
+                tokio::time::sleep(Duration::from_secs(interval)).await;
             }
         }
+        Commands::Logs(args) => {
+            code_agent_monitor::cli::handle_logs(args)?;
+        }
         Commands::WatchDaemon { interval } => {
             use std::time::Duration;
             use tokio::time::sleep;
 
             let daemon = WatcherDaemon::new();
-            let notifier = match code_agent_monitor::notification::load_webhook_config_from_file() {
-                Some(config) => OpenclawNotifier::with_webhook(config)
-                    .unwrap_or_else(|_| OpenclawNotifier::new()),
-                None => OpenclawNotifier::new(),
-            };
-            let mut watcher = AgentWatcher::new();
+            let mut engine = WatchEngine::new(Some(load_notifier()), WatchFilter::default())
+                .with_outbound_queue();
+            let mut prune_schedule = code_agent_monitor::session::PruneSchedule::load();
+            let mut last_prune: Option<std::time::Instant> = None;
+            let mut dashboard_export = code_agent_monitor::service::DashboardExport::load();
+            let mut config_watch =
+                code_agent_monitor::infra::config_watch::ConfigWatcher::default();
+            let mut last_dashboard: Option<std::time::Instant> = None;
+            let mut last_team_sample: Option<std::time::Instant> = None;
+            let mut dashboard_task: Option<tokio::task::JoinHandle<()>> = None;
 
             // 写入当前进程 PID
             daemon.write_pid(std::process::id())?;
 
+            // 升级旧版本的状态文件
+            code_agent_monitor::cli::auto_migrate();
+
+            // cam 二进制移动后 hooks 会静默失效，启动时检查
+            code_agent_monitor::cli::reconcile_hook_binaries(engine.notifier());
+
+            // cam notify 快速路径写入的 hook 事件由后台线程处理（config.json 的 notify.spool）
+            let spool = code_agent_monitor::cli::NotifySpool::new();
+            {
+                let spool = spool.clone();
+                std::thread::spawn(move || spool.run());
+            }
+
             eprintln!("CAM Watcher Daemon 启动，轮询间隔: {}秒", interval);
 
+            // 冷启动对账：停止期间已在等待输入或已退出的 agent 立即通知（已通知过的会被去重）
+            match engine.reconcile_cold_start() {
+                Ok(events) => info!(events = events.len(), "Cold-start reconciliation done"),
+                Err(e) => error!(error = %e, "Cold-start reconciliation failed"),
+            }
+
             // 连续错误计数器
             let mut consecutive_errors = 0;
             const MAX_CONSECUTIVE_ERRORS: u32 = 10;
 
             loop {
+                // config.json 修改后就地生效（通知渠道、紧急度规则、AI 提取、各类间隔），无需重启
+                if let Some(changes) = config_watch.poll() {
+                    info!(changes = %changes.join("; "), "Config reloaded");
+                    let restart =
+                        code_agent_monitor::infra::config_watch::restart_required(&changes);
+                    if !restart.is_empty() {
+                        warn!(
+                            keys = %restart.join(", "),
+                            "Config changes need `cam service restart` to take effect"
+                        );
+                    }
+                    engine.reload_notifier(Some(load_notifier()));
+                    engine.watcher_mut().reload_config();
+                    prune_schedule = code_agent_monitor::session::PruneSchedule::load();
+                    dashboard_export = code_agent_monitor::service::DashboardExport::load();
+                }
+
                 // 检查是否还有 agent 在运行
-                let agents = match watcher.agent_manager().list_agents() {
+                let agents = match engine.watcher().agent_manager().list_agents() {
                     Ok(agents) => {
                         consecutive_errors = 0; // 重置错误计数
                         agents
@@ -547,127 +850,94 @@ async fn main() -> Result<()> {
                     }
                 };
 
-                if agents.is_empty() {
+                // 轮询一次（事件在引擎内发送通知）
+                if let Err(e) = engine.poll() {
+                    consecutive_errors += 1;
+                    error!(
+                        error = %e,
+                        consecutive = consecutive_errors,
+                        max = MAX_CONSECUTIVE_ERRORS,
+                        "Poll failed"
+                    );
+                    if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
+                        error!("Too many consecutive errors, watcher stopping");
+                        daemon.remove_pid()?;
+                        break;
+                    }
+                    sleep(Duration::from_secs(interval)).await;
+                    continue;
+                }
+                consecutive_errors = 0;
+
+                // 退出的 agent 已在本轮处理；等待自动恢复或还有未处理的 hook 事件时继续运行
+                if agents.is_empty() && !engine.watcher().has_pending_recovery() && spool.is_empty()
+                {
                     info!("All agents exited, watcher stopping");
                     daemon.remove_pid()?;
                     break;
                 }
 
-                // 轮询一次
-                let events = match watcher.poll_once() {
-                    Ok(events) => {
-                        consecutive_errors = 0; // 重置错误计数
-                        events
+                // 发送积压过久的限流摘要
+                if let Some(notifier) = engine.notifier() {
+                    match notifier.flush_digest() {
+                        Ok(SendResult::Sent) => info!("Rate limit digest flushed"),
+                        Ok(_) => {}
+                        Err(e) => error!(error = %e, "Digest flush failed"),
                     }
-                    Err(e) => {
-                        consecutive_errors += 1;
-                        error!(
-                            error = %e,
-                            consecutive = consecutive_errors,
-                            max = MAX_CONSECUTIVE_ERRORS,
-                            "Poll failed"
-                        );
-                        if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                            error!("Too many consecutive errors, watcher stopping");
-                            daemon.remove_pid()?;
-                            break;
+                }
+
+                // 定期归档旧会话（config.json 的 session_prune）
+                if let Some(ref schedule) = prune_schedule {
+                    if last_prune.is_none_or(|at| at.elapsed() >= schedule.interval()) {
+                        last_prune = Some(std::time::Instant::now());
+                        match code_agent_monitor::session::archive::prune_sessions(
+                            SessionManager::new().projects_dir(),
+                            &schedule.options(),
+                        ) {
+                            Ok(report) if !report.archived.is_empty() => info!(
+                                archived = report.archived.len(),
+                                reclaimed_bytes = report.reclaimed_bytes(),
+                                "Old sessions archived"
+                            ),
+                            Ok(_) => {}
+                            Err(e) => error!(error = %e, "Session prune failed"),
                         }
-                        sleep(Duration::from_secs(interval)).await;
-                        continue;
                     }
-                };
+                }
 
-                // 只处理关键事件
-                for event in events {
-                    match &event {
-                        WatchEvent::AgentExited {
-                            agent_id,
-                            project_path,
-                        } => {
-                            info!(agent_id = %agent_id, "Agent exited, sending notification");
-                            let notification_event = NotificationEvent::agent_exited(agent_id)
-                                .with_project_path(project_path.clone());
-                            match notifier.send_notification_event(&notification_event) {
-                                Ok(result) => {
-                                    info!(agent_id = %agent_id, result = ?result, "Notification result")
-                                }
-                                Err(e) => {
-                                    error!(agent_id = %agent_id, error = %e, "Notification failed")
-                                }
-                            }
-                        }
-                        WatchEvent::Error {
-                            agent_id, message, ..
-                        } => {
-                            info!(agent_id = %agent_id, message = %message, "Error detected, sending notification");
-                            let notification_event = NotificationEvent::error(agent_id, message);
-                            match notifier.send_notification_event(&notification_event) {
-                                Ok(result) => {
-                                    info!(agent_id = %agent_id, result = ?result, "Notification result")
-                                }
-                                Err(e) => {
-                                    error!(agent_id = %agent_id, error = %e, "Notification failed")
-                                }
-                            }
-                        }
-                        WatchEvent::WaitingForInput {
-                            agent_id,
-                            pattern_type,
-                            context,
-                            dedup_key,
-                            is_decision_required,
-                        } => {
-                            info!(
-                                agent_id = %agent_id,
-                                pattern_type = %pattern_type,
-                                is_decision_required = is_decision_required,
-                                context_len = context.len(),
-                                "Waiting for input detected, sending notification"
-                            );
-                            // 从 agent_manager 获取项目路径
-                            let project_path = watcher
-                                .agent_manager()
-                                .get_agent(agent_id)
-                                .ok()
-                                .flatten()
-                                .map(|a| a.project_path)
-                                .unwrap_or_default();
-                            let notification_event =
-                                NotificationEvent::waiting_for_input_with_decision(
-                                    agent_id,
-                                    pattern_type,
-                                    *is_decision_required,
-                                )
-                                .with_project_path(project_path)
-                                .with_terminal_snapshot(context.clone())
-                                .with_dedup_key(dedup_key.clone());
-                            match notifier.send_notification_event(&notification_event) {
-                                Ok(result) => {
-                                    info!(agent_id = %agent_id, result = ?result, "Notification result")
-                                }
-                                Err(e) => {
-                                    error!(agent_id = %agent_id, error = %e, "Notification failed")
-                                }
-                            }
-                        }
-                        WatchEvent::ToolUse {
-                            agent_id,
-                            tool_name,
-                            tool_target,
-                            ..
-                        } => {
-                            debug!(agent_id = %agent_id, tool_name = %tool_name, "Tool use detected");
-                            let context = tool_target.as_deref().unwrap_or("");
-                            match notifier.send_event(agent_id, "ToolUse", tool_name, context) {
-                                Ok(result) => {
-                                    debug!(agent_id = %agent_id, result = ?result, "Notification result")
-                                }
-                                Err(e) => {
-                                    warn!(agent_id = %agent_id, error = %e, "Notification failed")
-                                }
+                // 定期记录 Team 进度快照（cam team-progress --history）
+                if last_team_sample.is_none_or(|at| {
+                    at.elapsed().as_secs()
+                        >= code_agent_monitor::team::progress_history::SAMPLE_INTERVAL_SECS as u64
+                }) {
+                    last_team_sample = Some(std::time::Instant::now());
+                    let recorded = code_agent_monitor::team::progress_history::record_all_teams(
+                        chrono::Utc::now(),
+                    );
+                    if recorded > 0 {
+                        debug!(teams = recorded, "Team progress sampled");
+                    }
+                }
+
+                // 定期发布团队看板快照（config.json 的 dashboard_export），上一次未完成时跳过
+                if let Some(ref export) = dashboard_export {
+                    let idle = dashboard_task
+                        .as_ref()
+                        .is_none_or(|task| task.is_finished());
+                    if idle && last_dashboard.is_none_or(|at| at.elapsed() >= export.interval()) {
+                        last_dashboard = Some(std::time::Instant::now());
+                        let export = export.clone();
+                        dashboard_task = Some(tokio::task::spawn_blocking(move || {
+                            let result = code_agent_monitor::service::DashboardSnapshot::collect(
+                                export.member(),
+                            )
+                            .and_then(|snapshot| {
+                                code_agent_monitor::service::publish(&export.target, &snapshot)
+                            });
+                            if let Err(e) = result {
+                                error!(error = %e, "Dashboard publish failed");
                             }
-                        }
-                        _ => {} // 忽略其他事件 (ToolUseBatch, AgentResumed)
+                        }));
                     }
                 }
 
@@ -679,44 +949,33 @@ async fn main() -> Result<()> {
             force,
             no_dedup,
         } => {
-            let notifier = match code_agent_monitor::notification::load_webhook_config_from_file() {
-                Some(config) => OpenclawNotifier::with_webhook(config)
-                    .unwrap_or_else(|_| OpenclawNotifier::new()),
-                None => OpenclawNotifier::new(),
-            };
-            let mut watcher = AgentWatcher::new();
-            match watcher.trigger_wait_check(&agent_id, force)? {
-                Some(WatchEvent::WaitingForInput {
-                    agent_id,
-                    pattern_type,
-                    context,
-                    dedup_key,
-                    is_decision_required,
-                }) => {
-                    let project_path = watcher
-                        .agent_manager()
-                        .get_agent(&agent_id)
-                        .ok()
-                        .flatten()
-                        .map(|a| a.project_path)
-                        .unwrap_or_default();
-                    let event = NotificationEvent::waiting_for_input_with_decision(
-                        &agent_id,
-                        &pattern_type,
-                        is_decision_required,
-                    )
-                    .with_project_path(project_path)
-                    .with_terminal_snapshot(context)
-                    .with_dedup_key(dedup_key);
+            let mut engine = WatchEngine::new(Some(load_notifier()), WatchFilter::default());
+            if engine
+                .watcher()
+                .agent_manager()
+                .get_agent(&agent_id)?
+                .is_none()
+            {
+                return Err(CliError::AgentNotFound(format!("未找到 agent: {}", agent_id)).into());
+            }
+            match engine.watcher_mut().trigger_wait_check(&agent_id, force)? {
+                Some(event @ WatchEvent::WaitingForInput { .. }) => {
                     // --force 或 --no-dedup 都跳过去重，避免手动触发创建 lock 影响 watcher 自动检测
-                    let notification_event = if force || no_dedup {
-                        event.with_skip_dedup(true)
+                    let result = if force || no_dedup {
+                        engine.dispatch_with(&event, |e| e.with_skip_dedup(true))
                     } else {
-                        event
+                        engine.dispatch(&event)
                     };
-                    match notifier.send_notification_event(&notification_event) {
-                        Ok(result) => println!("Notification sent: {:?}", result),
-                        Err(e) => eprintln!("Notification failed: {}", e),
+                    match result {
+                        Some(Ok(result)) => println!("Notification sent: {:?}", result),
+                        Some(Err(e)) => {
+                            return Err(CliError::ChannelFailure(format!(
+                                "Notification failed: {}",
+                                e
+                            ))
+                            .into());
+                        }
+                        None => {}
                     }
                 }
                 _ => {
@@ -735,9 +994,8 @@ async fn main() -> Result<()> {
             use std::fs::{create_dir_all, OpenOptions};
             use std::io::Write;
 
-            let log_dir = dirs::home_dir()
-                .unwrap_or_else(|| std::path::PathBuf::from("."))
-                .join(".config/code-agent-monitor");
+            let event = code_agent_monitor::cli::normalize_hook_event_name(&event).to_string();
+            let log_dir = code_agent_monitor::infra::paths::state_dir();
             let log_path = log_dir.join("hook.log");
 
             // 确保日志目录存在
@@ -747,8 +1005,8 @@ async fn main() -> Result<()> {
 
             let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
 
-            // 从 stdin 读取 hook 输入（Claude Code 通过 stdin 传递 JSON）
-            let context = std::io::read_to_string(std::io::stdin()).unwrap_or_default();
+            // 未能入队时 run 开头已读取的 hook 输入
+            let context = hook_input.take().unwrap_or_default();
 
             // 分离终端快照部分，确保 JSON 解析成功
             // 测试命令可能通过管道传入 JSON + 终端快照
@@ -877,6 +1135,33 @@ async fn main() -> Result<()> {
                 let _ = writeln!(file, "[{}] Context: {}", timestamp, context.trim());
             }
 
+            // PreToolUse/PostToolUse/SubagentStop：记录时间线、执行风险策略后直接返回
+            if code_agent_monitor::cli::is_tool_hook_event(&event) {
+                let notifier =
+                    match code_agent_monitor::notification::load_webhook_config_from_file() {
+                        Some(config) => OpenclawNotifier::with_webhook(config)
+                            .unwrap_or_else(|_| OpenclawNotifier::new()),
+                        None => OpenclawNotifier::new(),
+                    }
+                    .with_dry_run(dry_run)
+                    .with_no_ai(no_ai);
+                if let Some(output) = code_agent_monitor::cli::handle_tool_hook(
+                    &event,
+                    &resolved_agent_id,
+                    json.as_ref(),
+                    &notifier,
+                )? {
+                    if let Ok(mut file) =
+                        OpenOptions::new().create(true).append(true).open(&log_path)
+                    {
+                        let _ =
+                            writeln!(file, "[{}] Tool decision by policy: {}", timestamp, output);
+                    }
+                    println!("{}", output);
+                }
+                return Ok(());
+            }
+
             // 判断是否需要获取终端快照
             // 注意：permission_request 不需要终端快照，因为 stdin 已包含完整的 tool_name 和 tool_input
             let needs_snapshot = match event.as_str() {
@@ -945,72 +1230,37 @@ async fn main() -> Result<()> {
                 }
             }
 
+            // 正在录制时保存 hook 输入（cam record）
+            code_agent_monitor::agent::recording::record_hook_if_active(
+                &log_dir,
+                &resolved_agent_id,
+                &event,
+                &context,
+                terminal_snapshot.as_deref(),
+            );
+
             // 构建统一的 NotificationEvent
-            let notification_event = {
-                // 解析事件类型
-                let event_type = match event.as_str() {
-                    "WaitingForInput" => NotificationEventType::WaitingForInput {
-                        pattern_type: "unknown".to_string(),
-                        is_decision_required: false,
-                    },
-                    "permission_request" => {
-                        let tool_name = json
-                            .as_ref()
-                            .and_then(|j| j.get("tool_name"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string();
-                        let tool_input = json
-                            .as_ref()
-                            .and_then(|j| j.get("tool_input"))
-                            .cloned()
-                            .unwrap_or(serde_json::json!({}));
-                        NotificationEventType::PermissionRequest {
-                            tool_name,
-                            tool_input,
-                        }
-                    }
-                    "notification" => {
-                        let notification_type = json
-                            .as_ref()
-                            .and_then(|j| j.get("notification_type"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        let message = json
-                            .as_ref()
-                            .and_then(|j| j.get("message"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        NotificationEventType::Notification {
-                            notification_type,
-                            message,
-                        }
+            let notification_event = NotificationEvent::from_hook(
+                resolved_agent_id.clone(),
+                &event,
+                json.as_ref(),
+                &context,
+                terminal_snapshot.as_deref(),
+            );
+            // permission_request 不带快照：用当前屏幕生成与 watcher 相同的问题指纹去重键
+            let notification_event = match (&terminal_snapshot, event.as_str()) {
+                (None, "permission_request") => {
+                    match agent_manager.get_logs(&resolved_agent_id, 50) {
+                        Ok(screen) => notification_event.with_dedup_key(
+                            code_agent_monitor::notification::question_dedup_key(
+                                &resolved_agent_id,
+                                &screen,
+                            ),
+                        ),
+                        Err(_) => notification_event,
                     }
-                    "AgentExited" => NotificationEventType::AgentExited,
-                    "Error" => NotificationEventType::Error {
-                        message: context.clone(),
-                    },
-                    "stop" => NotificationEventType::Stop,
-                    "session_start" => NotificationEventType::SessionStart,
-                    "session_end" => NotificationEventType::SessionEnd,
-                    _ => NotificationEventType::Notification {
-                        notification_type: event.clone(),
-                        message: String::new(),
-                    },
-                };
-
-                let mut evt = NotificationEvent::new(resolved_agent_id.clone(), event_type);
-                // 设置项目路径（从 cwd 获取）
-                if let Some(ref cwd_path) = cwd {
-                    evt = evt.with_project_path(cwd_path.clone());
                 }
-                // 设置终端快照
-                if let Some(ref snapshot) = terminal_snapshot {
-                    evt = evt.with_terminal_snapshot(snapshot.clone());
-                }
-                evt
+                _ => notification_event,
             };
 
             let notifier = match code_agent_monitor::notification::load_webhook_config_from_file() {
@@ -1113,8 +1363,7 @@ async fn main() -> Result<()> {
                     {
                         let _ = writeln!(file, "[{}] ❌ Notification failed: {}", err_timestamp, e);
                     }
-                    eprintln!("通知发送失败: {}", e);
-                    return Err(e);
+                    return Err(CliError::ChannelFailure(format!("通知发送失败: {}", e)).into());
                 }
             }
         }
@@ -1158,8 +1407,7 @@ async fn main() -> Result<()> {
                 }
             }
             None => {
-                eprintln!("未找到 Team: {}", team);
-                std::process::exit(1);
+                return Err(CliError::TeamNotFound(format!("未找到 Team: {}", team)).into());
             }
         },
         Commands::Tasks { team, json } => {
@@ -1216,20 +1464,32 @@ async fn main() -> Result<()> {
             name,
             description,
             project,
+            worktree,
         } => {
             let bridge = TeamBridge::new();
             let desc = description.as_deref().unwrap_or("Created by CAM");
             let proj = project.as_deref().unwrap_or(".");
 
-            match bridge.create_team(&name, desc, proj) {
+            let result = bridge.create_team(&name, desc, proj).and_then(|_| {
+                if worktree {
+                    bridge.set_worktree_config(
+                        &name,
+                        &code_agent_monitor::team::WorktreeConfig::default(),
+                    )?;
+                }
+                Ok(())
+            });
+            match result {
                 Ok(_) => {
                     println!("已创建 Team: {}", name);
                     println!("  描述: {}", desc);
                     println!("  项目路径: {}", proj);
+                    if worktree {
+                        println!("  成员隔离: git worktree（分支 cam/{}/<member>）", name);
+                    }
                 }
                 Err(e) => {
-                    eprintln!("创建 Team 失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("创建 Team 失败: {}", e)).into());
                 }
             }
         }
@@ -1241,8 +1501,7 @@ async fn main() -> Result<()> {
                     println!("已删除 Team: {}", name);
                 }
                 Err(e) => {
-                    eprintln!("删除 Team 失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("删除 Team 失败: {}", e)).into());
                 }
             }
         }
@@ -1277,17 +1536,24 @@ async fn main() -> Result<()> {
                     }
                 }
                 Err(e) => {
-                    eprintln!("获取 Team 状态失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("获取 Team 状态失败: {}", e)).into());
                 }
             }
         }
         Commands::Inbox {
+            action: Some(InboxAction::Prune(args)),
+            ..
+        } => {
+            code_agent_monitor::cli::handle_inbox_prune(args)?;
+        }
+        Commands::Inbox {
+            action: None,
             team,
             member,
             unread,
             json,
         } => {
+            let team = team.unwrap_or_default();
             let bridge = TeamBridge::new();
 
             // 如果指定了成员，只读取该成员的 inbox
@@ -1314,20 +1580,28 @@ async fn main() -> Result<()> {
                                 println!("{}@{} 的消息 ({}):\n", member_name, team, filtered.len());
                                 for msg in filtered {
                                     let read_mark = if msg.read { "✓" } else { "●" };
+                                    let tags = msg.tags();
                                     println!(
-                                        "{} [{}] {}: {}",
+                                        "{} [{}] {}: {}{}",
                                         read_mark,
                                         msg.timestamp.format("%H:%M"),
                                         msg.from,
+                                        if tags.is_empty() {
+                                            String::new()
+                                        } else {
+                                            format!("{} ", tags)
+                                        },
                                         msg.text
                                     );
+                                    for attachment in &msg.attachments {
+                                        println!("    📎 {}", attachment.display());
+                                    }
                                 }
                             }
                         }
                     }
                     Err(e) => {
-                        eprintln!("读取 inbox 失败: {}", e);
-                        std::process::exit(1);
+                        return Err(CliError::Other(format!("读取 inbox 失败: {}", e)).into());
                     }
                 }
             } else {
@@ -1353,7 +1627,18 @@ async fn main() -> Result<()> {
                                         let read_mark = if msg.read { "✓" } else { "●" };
                                         let text_preview =
                                             code_agent_monitor::truncate_str(&msg.text, 50);
-                                        println!("  {} {}: {}", read_mark, msg.from, text_preview);
+                                        let tags = msg.tags();
+                                        if tags.is_empty() {
+                                            println!(
+                                                "  {} {}: {}",
+                                                read_mark, msg.from, text_preview
+                                            );
+                                        } else {
+                                            println!(
+                                                "  {} {}: {} {}",
+                                                read_mark, msg.from, tags, text_preview
+                                            );
+                                        }
                                     }
                                     if filtered.len() > 3 {
                                         println!("  ... 还有 {} 条消息", filtered.len() - 3);
@@ -1364,8 +1649,7 @@ async fn main() -> Result<()> {
                         }
                     }
                     Err(e) => {
-                        eprintln!("获取 Team 状态失败: {}", e);
-                        std::process::exit(1);
+                        return Err(CliError::Other(format!("获取 Team 状态失败: {}", e)).into());
                     }
                 }
             }
@@ -1375,25 +1659,26 @@ async fn main() -> Result<()> {
             member,
             message,
             from,
+            kind,
+            task_ref,
+            attachments,
+            priority,
         } => {
             let bridge = TeamBridge::new();
 
-            let msg = InboxMessage {
-                from,
-                text: message.clone(),
-                summary: None,
-                timestamp: chrono::Utc::now(),
-                color: None,
-                read: false,
-            };
+            let msg = InboxMessage::new(&from, &message).with_fields(MessageFields {
+                kind,
+                task_ref,
+                attachments,
+                priority,
+            });
 
             match bridge.send_to_inbox(&team, &member, msg) {
                 Ok(_) => {
                     println!("已发送消息到 {}@{}", member, team);
                 }
                 Err(e) => {
-                    eprintln!("发送消息失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("发送消息失败: {}", e)).into());
                 }
             }
         }
@@ -1410,8 +1695,7 @@ async fn main() -> Result<()> {
 
             // 验证 team 存在
             if !bridge.team_exists(&team) {
-                eprintln!("Team '{}' 不存在", team);
-                std::process::exit(1);
+                return Err(CliError::TeamNotFound(format!("Team '{}' 不存在", team)).into());
             }
 
             println!("开始监控 Team '{}' (间隔: {}秒)", team, interval);
@@ -1480,24 +1764,69 @@ async fn main() -> Result<()> {
                         println!("  成员名称: {}", result.member_name);
                         println!("  agent_id: {}", result.agent_id);
                         println!("  tmux_session: {}", result.tmux_session);
-                        println!(
-                            "\n查看输出: /opt/homebrew/bin/tmux attach -t {}",
-                            result.tmux_session
-                        );
+                        println!("\n查看输出: {}", attach_hint(&result.tmux_session));
                     }
                 }
                 Err(e) => {
-                    eprintln!("启动 Agent 失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("启动 Agent 失败: {}", e)).into());
                 }
             }
         }
-        Commands::TeamProgress { team, json } => {
+        Commands::TeamProgress {
+            team,
+            history,
+            json,
+        } => {
+            use code_agent_monitor::team::progress_history::sparkline;
+            use code_agent_monitor::team::{Burndown, ProgressHistory};
+
             let orchestrator = TeamOrchestrator::new();
 
             match orchestrator.get_team_progress(&team) {
                 Ok(progress) => {
-                    if json {
+                    let progress_history = ProgressHistory::for_team(&team);
+                    if let Err(e) = progress_history.record(&progress, chrono::Utc::now()) {
+                        warn!(team = %team, error = %e, "Failed to record team progress");
+                    }
+
+                    if history {
+                        let samples = progress_history.load();
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&samples)?);
+                            return Ok(());
+                        }
+                        println!("Team: {} ({} 条快照)", team, samples.len());
+                        println!("  时间          待处理 已完成   活跃   等待");
+                        for sample in samples.iter().rev().take(20).rev() {
+                            println!(
+                                "  {:<12} {:>6} {:>6} {:>6} {:>6}",
+                                sample
+                                    .timestamp
+                                    .with_timezone(&chrono::Local)
+                                    .format("%m-%d %H:%M")
+                                    .to_string(),
+                                sample.pending_tasks,
+                                sample.completed_tasks,
+                                sample.active_members,
+                                sample.waiting
+                            );
+                        }
+                        let pending: Vec<usize> = samples.iter().map(|s| s.pending_tasks).collect();
+                        println!("  待处理趋势: {}", sparkline(&pending, 60));
+                        if let Some(burndown) = Burndown::from_samples(&samples) {
+                            let rate = burndown
+                                .per_hour()
+                                .map(|rate| format!("，{:+.1}/小时", rate))
+                                .unwrap_or_default();
+                            println!(
+                                "  待处理: {} → {}（{:+}{}）",
+                                burndown.first_pending,
+                                burndown.last_pending,
+                                burndown.delta(),
+                                rate
+                            );
+                        }
+                    } else if json {
                         println!("{}", serde_json::to_string_pretty(&progress)?);
                     } else {
                         println!("Team: {}", progress.team_name);
@@ -1515,10 +1844,53 @@ async fn main() -> Result<()> {
                     }
                 }
                 Err(e) => {
-                    eprintln!("获取 Team 进度失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("获取 Team 进度失败: {}", e)).into());
+                }
+            }
+        }
+        Commands::TeamMerge { team, json } => {
+            use code_agent_monitor::team::worktree::conflict_event;
+            use code_agent_monitor::team::MergeOutcome;
+
+            let orchestrator = TeamOrchestrator::new();
+            let (project_path, report) = orchestrator
+                .merge_team(&team)
+                .map_err(|e| CliError::Other(format!("合并 Team 分支失败: {}", e)))?;
+
+            let notifier = load_notifier();
+            for merge in &report {
+                if let Some(event) = conflict_event(&team, &project_path, merge) {
+                    if let Err(e) = notifier.send_notification_event(&event) {
+                        warn!(team = %team, member = %merge.member, error = %e, "Failed to send merge conflict notification");
+                    }
+                }
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("Team: {} → {}", team, project_path);
+                for merge in &report {
+                    let outcome = match &merge.outcome {
+                        MergeOutcome::Merged { commits } => format!("✅ 已合并 {} 个提交", commits),
+                        MergeOutcome::UpToDate => "➖ 没有新提交".to_string(),
+                        MergeOutcome::Conflict { files } => {
+                            format!("⚠️ 冲突，已中止: {}", files.join(", "))
+                        }
+                        MergeOutcome::Failed { error } => format!("❌ {}", error),
+                    };
+                    println!("  {} ({}): {}", merge.member, merge.branch, outcome);
+                    if merge.uncommitted {
+                        println!("    worktree 有未提交的修改，未包含在合并中");
+                    }
                 }
             }
+            if report
+                .iter()
+                .any(|merge| matches!(merge.outcome, MergeOutcome::Conflict { .. }))
+            {
+                return Err(CliError::Other("部分成员分支存在冲突".to_string()).into());
+            }
         }
         Commands::TeamShutdown { team } => {
             let orchestrator = TeamOrchestrator::new();
@@ -1528,8 +1900,7 @@ async fn main() -> Result<()> {
                     println!("已关闭 Team: {}", team);
                 }
                 Err(e) => {
-                    eprintln!("关闭 Team 失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("关闭 Team 失败: {}", e)).into());
                 }
             }
         }
@@ -1547,6 +1918,9 @@ async fn main() -> Result<()> {
                             println!("待处理的确认请求 ({}):\n", pending.len());
                             for (i, conf) in pending.iter().enumerate() {
                                 println!("  {}. [{}] {}", i + 1, conf.agent_id, conf.context);
+                                for message in &conf.recent_messages {
+                                    println!("     › {}", message);
+                                }
                                 println!(
                                     "     ID: {} | 创建时间: {}",
                                     conf.id,
@@ -1557,19 +1931,47 @@ async fn main() -> Result<()> {
                     }
                 }
                 Err(e) => {
-                    eprintln!("获取待处理确认失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::Other(format!("获取待处理确认失败: {}", e)).into());
                 }
             }
         }
         Commands::Reply {
             reply,
+            file,
             target,
             all,
             agent,
             risk,
+            from,
+            reply_to,
         } => {
+            let mut reply = match file {
+                Some(path) => {
+                    let text = code_agent_monitor::cli::read_prompt_file(&path)?;
+                    eprintln!(
+                        "读取 {}（{}），正在发送...",
+                        path.display(),
+                        code_agent_monitor::cli::describe_prompt(&text)
+                    );
+                    text
+                }
+                None => reply.unwrap_or_default(),
+            };
             let state_manager = ConversationStateManager::new();
+            let target = match reply_to {
+                Some(message_id) => Some(
+                    code_agent_monitor::notification::NotificationStore::agent_for_message(
+                        &message_id,
+                    )
+                    .ok_or_else(|| {
+                        CliError::InvalidArgument(format!(
+                            "未找到消息 {} 对应的 agent 线程",
+                            message_id
+                        ))
+                    })?,
+                ),
+                None => target,
+            };
 
             // Determine batch filter
             let batch_filter = if all {
@@ -1582,20 +1984,54 @@ async fn main() -> Result<()> {
                     "medium" => RiskLevel::Medium,
                     "high" => RiskLevel::High,
                     _ => {
-                        eprintln!("无效的风险等级: {}，可选: low, medium, high", risk_str);
-                        std::process::exit(1);
+                        return Err(CliError::InvalidArgument(format!(
+                            "无效的风险等级: {}，可选: low, medium, high",
+                            risk_str
+                        ))
+                        .into());
                     }
                 };
                 Some(BatchFilter::Risk(risk_level))
+            } else if target.is_none() {
+                // 合并权限通知的回复：`y all` / `y 1,3`
+                match parse_batch_reply(&reply) {
+                    Some((batch_reply, selection)) => {
+                        let filter = match PermissionBatcher::new().latest() {
+                            Some(batch) => BatchFilter::Confirmations(
+                                batch
+                                    .select(&selection)
+                                    .map_err(CliError::InvalidArgument)?
+                                    .into_iter()
+                                    .map(|member| member.confirmation_id.clone())
+                                    .collect(),
+                            ),
+                            None if selection == BatchSelection::All => BatchFilter::All,
+                            None => {
+                                return Err(CliError::InvalidArgument(
+                                    "没有可按序号回复的合并权限请求".to_string(),
+                                )
+                                .into());
+                            }
+                        };
+                        reply = batch_reply;
+                        Some(filter)
+                    }
+                    None => None,
+                }
             } else {
                 None
             };
 
             if let Some(filter) = batch_filter {
                 // Batch reply mode
-                match state_manager.handle_reply_batch(&reply, filter) {
+                match state_manager.handle_reply_batch_from(
+                    &reply,
+                    filter,
+                    ReplySender::from_cli(from.as_deref()),
+                ) {
                     Ok(results) => {
                         if results.is_empty() {
+                            // 没有待处理确认不算失败，OpenClaw 插件依赖退出码 0
                             println!("没有待处理的确认请求");
                         } else {
                             let success_count = results.iter().filter(|r| r.success).count();
@@ -1620,17 +2056,58 @@ async fn main() -> Result<()> {
                         }
                     }
                     Err(e) => {
-                        eprintln!("批量回复失败: {}", e);
-                        std::process::exit(1);
+                        return Err(CliError::Other(format!("批量回复失败: {}", e)).into());
                     }
                 }
+            } else if let Some(agent_id) = target.as_deref().filter(|target| {
+                reply_macro::macro_name(&reply).is_none()
+                    && !state_manager.has_pending_for(target).unwrap_or(true)
+                    && AgentManager::new()
+                        .get_agent(target)
+                        .ok()
+                        .flatten()
+                        .is_some()
+            }) {
+                // 没有待处理确认：作为新指令发送，agent 处理中时排队
+                match send_or_queue(&AgentManager::new(), &PromptQueue::new(), agent_id, &reply)? {
+                    Delivery::Sent => println!("已发送指令到 {}", agent_id),
+                    Delivery::Queued(prompt) => println!(
+                        "{} 正在处理，指令已加入队列 {}（空闲后发送，cam queue list 查看）",
+                        agent_id, prompt.id
+                    ),
+                }
             } else {
                 // Single reply mode (existing logic)
-                match state_manager.handle_reply(&reply, target.as_deref()) {
+                match state_manager.handle_reply_from(
+                    &reply,
+                    target.as_deref(),
+                    ReplySender::from_cli(from.as_deref()),
+                ) {
                     Ok(result) => match result {
-                        ReplyResult::Sent { agent_id, reply } => {
-                            println!("已发送回复 '{}' 到 {}", reply, agent_id);
-                        }
+                        ReplyResult::Sent {
+                            agent_id,
+                            reply,
+                            verification,
+                        } => match verification {
+                            ReplyVerification::Verified { attempts, method } if attempts > 1 => {
+                                println!(
+                                    "已发送回复 '{}' 到 {}（已确认生效，第 {} 次尝试: {}）",
+                                    reply, agent_id, attempts, method
+                                );
+                            }
+                            ReplyVerification::Verified { .. } => {
+                                println!("已发送回复 '{}' 到 {}（已确认生效）", reply, agent_id);
+                            }
+                            ReplyVerification::Unverified { attempts } => {
+                                println!(
+                                    "⚠️ 已发送回复 '{}' 到 {}，但 {} 次尝试后提示仍在，请检查终端（确认保留为待处理）",
+                                    reply, agent_id, attempts
+                                );
+                            }
+                            ReplyVerification::Skipped => {
+                                println!("已发送回复 '{}' 到 {}", reply, agent_id);
+                            }
+                        },
                         ReplyResult::NeedSelection { options } => {
                             println!("有多个待处理的确认，请指定目标：\n");
                             for (i, opt) in options.iter().enumerate() {
@@ -1641,15 +2118,48 @@ async fn main() -> Result<()> {
                         ReplyResult::NoPending => {
                             println!("没有待处理的确认请求");
                         }
+                        ReplyResult::Denied { agent_id, reason } => {
+                            return Err(CliError::PermissionDenied(format!(
+                                "拒绝回复 {}: {}",
+                                agent_id, reason
+                            ))
+                            .into());
+                        }
                         ReplyResult::InvalidSelection(msg) => {
-                            eprintln!("无效的选择: {}", msg);
-                            std::process::exit(1);
+                            let message = format!("无效的选择: {}", msg);
+                            return Err(match target {
+                                Some(_) if reply_macro::macro_name(&reply).is_none() => {
+                                    CliError::AgentNotFound(message)
+                                }
+                                _ => CliError::InvalidArgument(message),
+                            }
+                            .into());
                         }
                     },
                     Err(e) => {
-                        eprintln!("发送回复失败: {}", e);
-                        std::process::exit(1);
+                        return Err(CliError::Other(format!("发送回复失败: {}", e)).into());
+                    }
+                }
+            }
+        }
+        Commands::Ack { target } => {
+            match ConversationStateManager::new().acknowledge(target.as_deref())? {
+                AckResult::Acknowledged { agent_id } => {
+                    println!(
+                        "已确认 {} 的通知，回复或 agent 恢复运行前不再提醒",
+                        agent_id
+                    );
+                }
+                AckResult::NeedSelection { options } => {
+                    println!("有多个待处理的确认，请指定目标：\n");
+                    for (i, opt) in options.iter().enumerate() {
+                        println!("  {}. [{}] {}", i + 1, opt.agent_id, opt.context);
                     }
+                    println!("\n使用 cam ack <agent_id> 指定目标");
+                }
+                AckResult::NoPending => return Err(CliError::NoPending.into()),
+                AckResult::NotFound(target) => {
+                    return Err(CliError::AgentNotFound(format!("未找到目标: {}", target)).into());
                 }
             }
         }
@@ -1657,10 +2167,13 @@ async fn main() -> Result<()> {
             refresh_interval,
             no_notifications: _,
         } => {
-            use code_agent_monitor::tui::{init_terminal, restore_terminal, run, App};
+            use code_agent_monitor::tui::{init_terminal, restore_terminal, run, App, TuiConfig};
 
+            // 进入备用屏幕前校验主题和按键配置，错误才能正常显示
+            let config = TuiConfig::load()
+                .map_err(|e| CliError::InvalidArgument(format!("TUI 配置无效: {}", e)))?;
             let mut terminal = init_terminal()?;
-            let mut app = App::new();
+            let mut app = App::with_config(config);
 
             let result = run(&mut terminal, &mut app, refresh_interval);
 
@@ -1672,8 +2185,9 @@ async fn main() -> Result<()> {
             let service = match LaunchdService::new() {
                 Ok(s) => s,
                 Err(e) => {
-                    eprintln!("❌ 初始化服务失败: {}", e);
-                    std::process::exit(1);
+                    return Err(
+                        CliError::ServiceFailure(format!("❌ 初始化服务失败: {}", e)).into(),
+                    );
                 }
             };
 
@@ -1691,8 +2205,9 @@ async fn main() -> Result<()> {
                             println!("   查看日志: cam service logs");
                         }
                         Err(e) => {
-                            eprintln!("❌ 安装失败: {}", e);
-                            std::process::exit(1);
+                            return Err(
+                                CliError::ServiceFailure(format!("❌ 安装失败: {}", e)).into()
+                            );
                         }
                     }
                 }
@@ -1701,8 +2216,7 @@ async fn main() -> Result<()> {
                         println!("✅ CAM watcher 服务已卸载");
                     }
                     Err(e) => {
-                        eprintln!("❌ 卸载失败: {}", e);
-                        std::process::exit(1);
+                        return Err(CliError::ServiceFailure(format!("❌ 卸载失败: {}", e)).into());
                     }
                 },
                 ServiceAction::Restart => match service.restart() {
@@ -1710,8 +2224,7 @@ async fn main() -> Result<()> {
                         println!("✅ CAM watcher 服务已重启");
                     }
                     Err(e) => {
-                        eprintln!("❌ 重启失败: {}", e);
-                        std::process::exit(1);
+                        return Err(CliError::ServiceFailure(format!("❌ 重启失败: {}", e)).into());
                     }
                 },
                 ServiceAction::Status => match service.status() {
@@ -1730,11 +2243,40 @@ async fn main() -> Result<()> {
                         }
                     }
                     Err(e) => {
-                        eprintln!("❌ 获取状态失败: {}", e);
-                        std::process::exit(1);
+                        return Err(
+                            CliError::ServiceFailure(format!("❌ 获取状态失败: {}", e)).into()
+                        );
                     }
                 },
-                ServiceAction::Logs { lines, follow } => {
+                ServiceAction::Logs {
+                    lines,
+                    follow,
+                    agent: Some(agent_id),
+                } => {
+                    let dir = agent_log::default_log_dir();
+                    let path = agent_log::log_path(&dir, &agent_id);
+                    if follow {
+                        println!("📋 跟踪 {} 的日志 (Ctrl+C 退出)...\n", agent_id);
+                        let _ = std::process::Command::new("tail")
+                            .args(["-F", "-n"])
+                            .arg(lines.to_string())
+                            .arg(&path)
+                            .status();
+                    } else {
+                        let tail = agent_log::read_tail(&dir, &agent_id, lines);
+                        if tail.is_empty() {
+                            println!("(没有 {} 的日志: {})", agent_id, path.display());
+                        }
+                        for line in tail {
+                            println!("{}", line);
+                        }
+                    }
+                }
+                ServiceAction::Logs {
+                    lines,
+                    follow,
+                    agent: None,
+                } => {
                     let (stdout_log, stderr_log) = service.log_paths();
 
                     if follow {
@@ -1783,8 +2325,9 @@ async fn main() -> Result<()> {
             let service = match LaunchdService::new() {
                 Ok(s) => s,
                 Err(e) => {
-                    eprintln!("❌ 初始化服务失败: {}", e);
-                    std::process::exit(1);
+                    return Err(
+                        CliError::ServiceFailure(format!("❌ 初始化服务失败: {}", e)).into(),
+                    );
                 }
             };
             if force {
@@ -1797,8 +2340,7 @@ async fn main() -> Result<()> {
                     println!("   查看状态: cam service status");
                 }
                 Err(e) => {
-                    eprintln!("❌ 安装失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::ServiceFailure(format!("❌ 安装失败: {}", e)).into());
                 }
             }
         }
@@ -1810,17 +2352,79 @@ async fn main() -> Result<()> {
             .await
             .expect("spawn_blocking failed");
 
-            if let Err(e) = result {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            result?;
+        }
+        Commands::Completions(args) => {
+            code_agent_monitor::cli::handle_completions(args, &mut Cli::command())?;
+        }
+        Commands::Migrate(args) => {
+            code_agent_monitor::cli::handle_migrate(args)?;
+        }
+        Commands::Record(args) => {
+            code_agent_monitor::cli::handle_record(args).await?;
         }
+        Commands::Bench(args) => {
+            // AI 提取使用阻塞 HTTP 客户端，不能直接在异步上下文中运行
+            let result =
+                tokio::task::spawn_blocking(move || code_agent_monitor::cli::handle_bench(args))
+                    .await
+                    .expect("spawn_blocking failed");
+
+            result?;
+        }
+        Commands::Snapshot(args) => {
+            code_agent_monitor::cli::handle_snapshot(args)?;
+        }
+        Commands::Replay(args) => {
+            // AI 检测使用阻塞 HTTP 客户端，不能直接在异步上下文中运行
+            let result =
+                tokio::task::spawn_blocking(move || code_agent_monitor::cli::handle_replay(args))
+                    .await
+                    .expect("spawn_blocking failed");
+
+            result?;
+        }
+        Commands::Simulate(args) => {
+            code_agent_monitor::cli::handle_simulate(args)?;
+        }
+        Commands::Status(args) => {
+            code_agent_monitor::cli::handle_status(args)?;
+        }
+        Commands::Purge(args) => {
+            code_agent_monitor::cli::handle_purge(args)?;
+        }
+        Commands::Dashboard(args) => {
+            code_agent_monitor::cli::handle_dashboard(args)?;
+        }
+        Commands::Attach(args) => {
+            code_agent_monitor::cli::handle_attach(args)?;
+        }
+        Commands::Pause(args) => {
+            code_agent_monitor::cli::handle_pause(args)?;
+        }
+        Commands::Queue(args) => {
+            code_agent_monitor::cli::handle_queue(args)?;
+        }
+        Commands::ResumeAll(args) => {
+            code_agent_monitor::cli::handle_resume_all(args)?;
+        }
+        Commands::Stats(args) => {
+            code_agent_monitor::cli::handle_stats(args)?;
+        }
+        Commands::Embeddings(args) => {
+            code_agent_monitor::cli::handle_embeddings(args)?;
+        }
+        Commands::Spool(args) => {
+            code_agent_monitor::cli::handle_spool(args)?;
+        }
+        Commands::Complete(_) | Commands::PtyHost(_) => unreachable!("handled before dispatch"),
         Commands::Uninstall => {
             let service = match LaunchdService::new() {
                 Ok(s) => s,
                 Err(e) => {
-                    eprintln!("❌ 初始化服务失败: {}", e);
-                    std::process::exit(1);
+                    return Err(
+                        CliError::ServiceFailure(format!("❌ 初始化服务失败: {}", e)).into(),
+                    );
                 }
             };
             match service.uninstall() {
@@ -1828,8 +2432,7 @@ async fn main() -> Result<()> {
                     println!("✅ CAM watcher 服务已卸载");
                 }
                 Err(e) => {
-                    eprintln!("❌ 卸载失败: {}", e);
-                    std::process::exit(1);
+                    return Err(CliError::ServiceFailure(format!("❌ 卸载失败: {}", e)).into());
                 }
             }
         }
@@ -1,5 +1,8 @@
 //! MCP Server - Model Context Protocol implementation
 
+pub mod pagination;
+pub mod prompts;
+pub mod resources;
 pub mod server;
 pub mod tools;
 pub mod types;
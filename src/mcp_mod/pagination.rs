@@ -0,0 +1,313 @@
+//! MCP 工具响应整形 - 分页、字段选择与大小上限
+//!
+//! 所有工具都接受以下通用参数（见 `with_response_options`）：
+//! - `cursor` / `limit`：列表结果分页。列表是顶层数组，或对象中唯一的数组字段
+//!   （如 `agent_list` 的 `agents`）。分页后附带 `total` 与 `next_cursor`
+//! - `fields`：只保留指定字段（作用于列表项，没有列表时作用于顶层对象）
+//! - `max_chars`：响应文本上限；超出时先减少本页条数，仍超出则截断文本
+//!
+//! 顶层数组只有在实际分页时才包装为 `{"items", "total", "next_cursor"}`，
+//! 结果不超过一页时保持原有结构。
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+/// 默认每页条数
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+/// 每页条数上限
+pub const MAX_PAGE_SIZE: usize = 200;
+/// 默认响应文本上限（字符）
+pub const DEFAULT_MAX_CHARS: usize = 20_000;
+/// 响应文本上限的最小值（避免截断后只剩提示）
+const MIN_MAX_CHARS: usize = 500;
+
+/// 响应整形参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseOptions {
+    pub offset: usize,
+    pub limit: usize,
+    pub fields: Vec<String>,
+    pub max_chars: usize,
+    /// 调用方是否显式请求了分页（显式请求时顶层数组总是包装）
+    pub explicit_page: bool,
+}
+
+impl Default for ResponseOptions {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+            fields: Vec::new(),
+            max_chars: DEFAULT_MAX_CHARS,
+            explicit_page: false,
+        }
+    }
+}
+
+impl ResponseOptions {
+    /// 从工具参数读取，`cursor` 无效时报错
+    pub fn from_arguments(arguments: &Value) -> Result<Self> {
+        let cursor = arguments.get("cursor").and_then(|v| v.as_str());
+        let offset = match cursor {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .map_err(|_| anyhow!("无效的 cursor: {}", cursor))?,
+            None => 0,
+        };
+        let limit = arguments.get("limit").and_then(|v| v.as_u64());
+        let fields = match arguments.get("fields") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect(),
+            Some(Value::String(list)) => list
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            offset,
+            limit: limit
+                .map(|l| (l as usize).clamp(1, MAX_PAGE_SIZE))
+                .unwrap_or(DEFAULT_PAGE_SIZE),
+            fields,
+            max_chars: arguments
+                .get("max_chars")
+                .and_then(|v| v.as_u64())
+                .map(|c| (c as usize).max(MIN_MAX_CHARS))
+                .unwrap_or(DEFAULT_MAX_CHARS),
+            explicit_page: cursor.is_some() || limit.is_some(),
+        })
+    }
+}
+
+/// 在工具的 inputSchema 中加入通用的分页、字段选择与大小上限参数
+pub fn with_response_options(mut schema: Value) -> Value {
+    let options = serde_json::json!({
+        "cursor": {
+            "type": "string",
+            "description": "分页游标，取自上一页响应的 next_cursor"
+        },
+        "limit": {
+            "type": "integer",
+            "description": format!(
+                "列表结果每页条数，默认 {}，最大 {}",
+                DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE
+            )
+        },
+        "fields": {
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "只返回指定字段（作用于列表项或顶层对象）"
+        },
+        "max_chars": {
+            "type": "integer",
+            "description": format!("响应文本上限（字符），默认 {}", DEFAULT_MAX_CHARS)
+        }
+    });
+    if let (Some(properties), Value::Object(options)) = (
+        schema.get_mut("properties").and_then(|p| p.as_object_mut()),
+        options,
+    ) {
+        properties.extend(options);
+    }
+    schema
+}
+
+/// 整形 `tools/call` 结果中的文本内容
+pub fn shape_tool_result(mut result: Value, options: &ResponseOptions) -> Value {
+    if let Some(content) = result.get_mut("content").and_then(|c| c.as_array_mut()) {
+        for item in content {
+            if item["type"] != "text" {
+                continue;
+            }
+            if let Some(text) = item["text"].as_str() {
+                item["text"] = Value::String(shape_text(text, options));
+            }
+        }
+    }
+    result
+}
+
+/// 整形一段文本：JSON 做分页与字段选择，其他文本只做大小限制
+pub fn shape_text(text: &str, options: &ResponseOptions) -> String {
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        return truncate_text(text, options.max_chars);
+    };
+
+    let Some((key, items)) = take_list(&value) else {
+        let value = select_fields(value, &options.fields);
+        return truncate_text(&to_pretty(&value), options.max_chars);
+    };
+
+    let total = items.len();
+    let items: Vec<Value> = items
+        .into_iter()
+        .skip(options.offset)
+        .map(|item| select_fields(item, &options.fields))
+        .collect();
+    let render = |count: usize, wrap: bool| {
+        let page: Vec<Value> = items.iter().take(count).cloned().collect();
+        let next = options.offset + page.len();
+        let next_cursor = (next < total).then(|| next.to_string());
+        let shaped = match (&key, &value) {
+            (Some(key), Value::Object(object)) => {
+                let mut object = object.clone();
+                object.insert(key.clone(), Value::Array(page));
+                if wrap {
+                    object.insert("total".to_string(), total.into());
+                    object.insert("next_cursor".to_string(), next_cursor.into());
+                }
+                Value::Object(object)
+            }
+            _ if wrap => serde_json::json!({
+                "items": page,
+                "total": total,
+                "next_cursor": next_cursor,
+            }),
+            _ => Value::Array(page),
+        };
+        to_pretty(&shaped)
+    };
+
+    let count = items.len().min(options.limit);
+    let wrap = options.explicit_page || count < items.len() || options.offset > 0;
+    let text = render(count, wrap);
+    if text.chars().count() <= options.max_chars || count <= 1 {
+        return truncate_text(&text, options.max_chars);
+    }
+
+    // 超出上限：二分查找能放下的最大条数
+    let (mut low, mut high) = (1, count - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if render(mid, true).chars().count() <= options.max_chars {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    truncate_text(&render(low, true), options.max_chars)
+}
+
+/// 找出可分页的列表：顶层数组，或对象中唯一的数组字段
+fn take_list(value: &Value) -> Option<(Option<String>, Vec<Value>)> {
+    match value {
+        Value::Array(items) => Some((None, items.clone())),
+        Value::Object(object) => {
+            let mut arrays = object.iter().filter(|(_, v)| v.is_array());
+            let (key, items) = arrays.next()?;
+            if arrays.next().is_some() {
+                return None;
+            }
+            Some((
+                Some(key.clone()),
+                items.as_array().cloned().unwrap_or_default(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// 只保留对象的指定字段（未指定字段或不是对象时原样返回）
+fn select_fields(value: Value, fields: &[String]) -> Value {
+    match value {
+        Value::Object(object) if !fields.is_empty() => Value::Object(
+            object
+                .into_iter()
+                .filter(|(k, _)| fields.iter().any(|f| f == k))
+                .collect::<Map<String, Value>>(),
+        ),
+        other => other,
+    }
+}
+
+fn to_pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// 超出上限时截断并附加提示
+fn truncate_text(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars).collect();
+    format!(
+        "{}\n...(响应已截断，共 {} 字符；可使用 fields、limit 或 max_chars 调整)",
+        kept, total
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn options(arguments: Value) -> ResponseOptions {
+        ResponseOptions::from_arguments(&arguments).unwrap()
+    }
+
+    #[test]
+    fn test_options_from_arguments() {
+        assert_eq!(options(json!({})), ResponseOptions::default());
+        let parsed = options(json!({"cursor": "40", "limit": 1000, "fields": "id, name"}));
+        assert_eq!(parsed.offset, 40);
+        assert_eq!(parsed.limit, MAX_PAGE_SIZE);
+        assert_eq!(parsed.fields, ["id", "name"]);
+        assert!(parsed.explicit_page);
+        assert!(ResponseOptions::from_arguments(&json!({"cursor": "abc"})).is_err());
+    }
+
+    #[test]
+    fn test_paginate_object_list_with_fields() {
+        let text = json!({
+            "agents": (0..5).map(|i| json!({"agent_id": format!("cam-{}", i), "project_path": "/tmp"})).collect::<Vec<_>>()
+        })
+        .to_string();
+        let shaped: Value = serde_json::from_str(&shape_text(
+            &text,
+            &options(json!({"limit": 2, "cursor": "2", "fields": ["agent_id"]})),
+        ))
+        .unwrap();
+        assert_eq!(
+            shaped["agents"],
+            json!([{"agent_id": "cam-2"}, {"agent_id": "cam-3"}])
+        );
+        assert_eq!(shaped["total"], 5);
+        assert_eq!(shaped["next_cursor"], "4");
+    }
+
+    #[test]
+    fn test_top_level_array_wrapped_only_when_paged() {
+        let small = json!([1, 2, 3]).to_string();
+        let shaped: Value = serde_json::from_str(&shape_text(&small, &options(json!({})))).unwrap();
+        assert_eq!(shaped, json!([1, 2, 3]));
+
+        let large = json!((0..30).collect::<Vec<_>>()).to_string();
+        let shaped: Value = serde_json::from_str(&shape_text(&large, &options(json!({})))).unwrap();
+        assert_eq!(shaped["items"].as_array().unwrap().len(), DEFAULT_PAGE_SIZE);
+        assert_eq!(shaped["total"], 30);
+        assert_eq!(shaped["next_cursor"], "20");
+    }
+
+    #[test]
+    fn test_size_cap_shrinks_page_then_truncates() {
+        let text = json!((0..20)
+            .map(|i| "x".repeat(100) + &i.to_string())
+            .collect::<Vec<_>>())
+        .to_string();
+        let shaped = shape_text(&text, &options(json!({"max_chars": 600})));
+        assert!(shaped.chars().count() <= 600);
+        let shaped: Value = serde_json::from_str(&shaped).unwrap();
+        assert!(shaped["items"].as_array().unwrap().len() < 20);
+        assert!(shaped["next_cursor"].is_string());
+
+        let plain = "y".repeat(1000);
+        let shaped = shape_text(&plain, &options(json!({"max_chars": 600})));
+        assert!(shaped.contains("响应已截断，共 1000 字符"));
+    }
+}
@@ -0,0 +1,241 @@
+//! MCP prompts - 预置的编排工作流提示词
+//!
+//! `prompts/get` 时用 CAM 的实时数据填充：
+//! - `triage_waiting_agents`：等待输入的 agent 与待处理确认，请 LLM 逐条给出处理建议
+//! - `summarize_team_progress`：team 成员、任务与未读消息，请 LLM 撰写进度报告
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::agent::AgentStatus;
+use crate::session::state::PendingConfirmation;
+use crate::team::bridge::TeamStatus;
+use crate::team::{Task, TaskStatus};
+
+/// 等待输入的 agent 附带的终端行数
+pub const WAITING_TERMINAL_LINES: u32 = 15;
+
+/// 等待输入的 agent
+#[derive(Debug, Clone)]
+pub struct WaitingAgent {
+    pub agent_id: String,
+    pub project_path: String,
+    pub status: AgentStatus,
+    /// 最近的终端输出
+    pub terminal: String,
+}
+
+/// 预置 prompt 名称
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    TriageWaitingAgents,
+    SummarizeTeamProgress,
+}
+
+impl PromptKind {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "triage_waiting_agents" => Ok(Self::TriageWaitingAgents),
+            "summarize_team_progress" => Ok(Self::SummarizeTeamProgress),
+            _ => Err(anyhow!("Prompt not found: {}", name)),
+        }
+    }
+}
+
+/// `prompts/list` 的 prompt 定义
+pub fn prompt_definitions() -> Value {
+    serde_json::json!({
+        "prompts": [
+            {
+                "name": "triage_waiting_agents",
+                "description": "列出等待输入的 agent 和待处理确认，逐条给出回复建议",
+                "arguments": []
+            },
+            {
+                "name": "summarize_team_progress",
+                "description": "汇总 team 成员、任务和消息，撰写进度报告",
+                "arguments": [
+                    {
+                        "name": "team_name",
+                        "description": "Team 名称",
+                        "required": true
+                    }
+                ]
+            }
+        ]
+    })
+}
+
+/// `prompts/get` 的响应（单条 user 消息）
+pub fn get_result(description: &str, text: String) -> Value {
+    serde_json::json!({
+        "description": description,
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": text }
+        }]
+    })
+}
+
+/// 生成 `triage_waiting_agents` 的提示词
+pub fn triage_waiting_agents(agents: &[WaitingAgent], pending: &[PendingConfirmation]) -> String {
+    let mut text = String::from(
+        "以下是 Code Agent Monitor 中等待处理的 agent 和确认请求。\
+         请逐条判断：可以安全批准的给出回复（如 `cam reply y --target <确认ID>`），\
+         需要人工决定的说明原因，并按紧急程度排序。\n",
+    );
+
+    text.push_str(&format!("\n## 待处理确认（{}）\n", pending.len()));
+    if pending.is_empty() {
+        text.push_str("无\n");
+    }
+    for confirmation in pending {
+        let risk = confirmation
+            .risk_level
+            .map(|r| format!("，{}", r.description()))
+            .unwrap_or_default();
+        text.push_str(&format!(
+            "- [{}] {}（{}{}）\n  {}\n",
+            confirmation.id,
+            confirmation.agent_id,
+            confirmation.created_at.format("%H:%M:%S"),
+            risk,
+            confirmation.context.trim()
+        ));
+    }
+
+    text.push_str(&format!("\n## 等待输入的 agent（{}）\n", agents.len()));
+    if agents.is_empty() {
+        text.push_str("无\n");
+    }
+    for agent in agents {
+        text.push_str(&format!(
+            "- {} {} {:?}\n```\n{}\n```\n",
+            agent.status.icon(),
+            agent.agent_id,
+            agent.project_path,
+            agent.terminal.trim_end()
+        ));
+    }
+    text
+}
+
+/// 生成 `summarize_team_progress` 的提示词
+pub fn summarize_team_progress(status: &TeamStatus, tasks: &[Task]) -> String {
+    let mut text = format!(
+        "请根据以下数据为 team `{}` 撰写一份简洁的进度报告：已完成的工作、进行中的任务、\
+         阻塞点和需要人工介入的事项。\n",
+        status.team_name
+    );
+    if let Some(description) = &status.description {
+        text.push_str(&format!("\n描述: {}\n", description));
+    }
+
+    text.push_str(&format!("\n## 成员（{}）\n", status.members.len()));
+    for member in &status.members {
+        text.push_str(&format!(
+            "- {}（{}，未读 {}）\n",
+            member.name,
+            if member.is_active { "活跃" } else { "空闲" },
+            member.unread_count
+        ));
+    }
+
+    let visible: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.status != TaskStatus::Deleted)
+        .collect();
+    text.push_str(&format!(
+        "\n## 任务（待处理 {}，已完成 {}）\n",
+        status.pending_tasks, status.completed_tasks
+    ));
+    if visible.is_empty() {
+        text.push_str("无\n");
+    }
+    for task in visible {
+        let owner = task
+            .owner
+            .as_deref()
+            .map(|o| format!(" @{}", o))
+            .unwrap_or_default();
+        let blocked = if task.blocked_by.is_empty() {
+            String::new()
+        } else {
+            format!("，被 {} 阻塞", task.blocked_by.join(", "))
+        };
+        text.push_str(&format!(
+            "- #{} [{}] {}{}{}\n",
+            task.id, task.status, task.subject, owner, blocked
+        ));
+    }
+
+    text.push_str(&format!("\n未读消息: {}\n", status.unread_messages));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::team::bridge::TeamMemberStatus;
+
+    #[test]
+    fn test_prompt_kind_parse() {
+        assert_eq!(
+            PromptKind::parse("triage_waiting_agents").unwrap(),
+            PromptKind::TriageWaitingAgents
+        );
+        assert!(PromptKind::parse("unknown").is_err());
+        let definitions = prompt_definitions();
+        let names: Vec<&str> = definitions["prompts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["triage_waiting_agents", "summarize_team_progress"]);
+    }
+
+    #[test]
+    fn test_triage_waiting_agents_text() {
+        let agents = vec![WaitingAgent {
+            agent_id: "cam-1".to_string(),
+            project_path: "/tmp/app".to_string(),
+            status: AgentStatus::WaitingForInput,
+            terminal: "Continue? [y/n]\n".to_string(),
+        }];
+        let text = triage_waiting_agents(&agents, &[]);
+        assert!(text.contains("## 待处理确认（0）\n无"));
+        assert!(text.contains("cam-1"));
+        assert!(text.contains("Continue? [y/n]"));
+    }
+
+    #[test]
+    fn test_summarize_team_progress_text() {
+        let status = TeamStatus {
+            team_name: "dev".to_string(),
+            description: None,
+            project_path: None,
+            members: vec![TeamMemberStatus {
+                name: "lead".to_string(),
+                agent_id: "lead@dev".to_string(),
+                is_active: true,
+                unread_count: 2,
+            }],
+            pending_tasks: 1,
+            completed_tasks: 0,
+            unread_messages: 2,
+        };
+        let task: Task = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "subject": "Add login",
+            "description": "",
+            "status": "in_progress",
+            "owner": "lead",
+            "blockedBy": ["2"]
+        }))
+        .unwrap();
+        let text = summarize_team_progress(&status, &[task]);
+        assert!(text.contains("- lead（活跃，未读 2）"));
+        assert!(text.contains("- #1 [in_progress] Add login @lead，被 2 阻塞"));
+    }
+}
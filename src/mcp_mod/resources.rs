@@ -0,0 +1,166 @@
+//! MCP 资源 - 以 URI 暴露 agent 快照、对话记录和 team 状态
+//!
+//! - `agent://<id>/snapshot`：agent 状态与最近终端输出
+//! - `agent://<id>/transcript`：JSONL 对话记录的最近事件
+//! - `team://<name>/status`：team 成员与任务状态
+//!
+//! 客户端通过 `resources/subscribe` 订阅后，内容变化时收到 `notifications/resources/updated`。
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// 订阅轮询间隔（秒）
+pub const SUBSCRIPTION_POLL_SECS: u64 = 2;
+
+/// 对话记录资源包含的最近事件数
+pub const TRANSCRIPT_EVENTS: usize = 200;
+
+/// 快照资源包含的终端行数
+pub const SNAPSHOT_LINES: u32 = 50;
+
+/// 资源 URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceUri {
+    AgentSnapshot(String),
+    AgentTranscript(String),
+    TeamStatus(String),
+}
+
+impl ResourceUri {
+    /// 解析资源 URI
+    pub fn parse(uri: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid resource uri: {}", uri);
+        let (scheme, rest) = uri.split_once("://").ok_or_else(invalid)?;
+        let (name, kind) = rest.rsplit_once('/').ok_or_else(invalid)?;
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        match (scheme, kind) {
+            ("agent", "snapshot") => Ok(Self::AgentSnapshot(name.to_string())),
+            ("agent", "transcript") => Ok(Self::AgentTranscript(name.to_string())),
+            ("team", "status") => Ok(Self::TeamStatus(name.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// 规范 URI 字符串
+    pub fn uri(&self) -> String {
+        match self {
+            Self::AgentSnapshot(id) => format!("agent://{}/snapshot", id),
+            Self::AgentTranscript(id) => format!("agent://{}/transcript", id),
+            Self::TeamStatus(name) => format!("team://{}/status", name),
+        }
+    }
+
+    /// `resources/list` 中的资源描述
+    pub fn descriptor(&self) -> Value {
+        let (name, description) = match self {
+            Self::AgentSnapshot(id) => (
+                format!("{} snapshot", id),
+                "agent 状态与最近终端输出".to_string(),
+            ),
+            Self::AgentTranscript(id) => (
+                format!("{} transcript", id),
+                format!("最近 {} 条对话事件", TRANSCRIPT_EVENTS),
+            ),
+            Self::TeamStatus(name) => (
+                format!("{} status", name),
+                "team 成员与任务状态".to_string(),
+            ),
+        };
+        serde_json::json!({
+            "uri": self.uri(),
+            "name": name,
+            "description": description,
+            "mimeType": "application/json"
+        })
+    }
+}
+
+/// `resources/templates/list` 的资源模板
+pub fn resource_templates() -> Value {
+    serde_json::json!({
+        "resourceTemplates": [
+            {
+                "uriTemplate": "agent://{agent_id}/snapshot",
+                "name": "Agent snapshot",
+                "description": "agent 状态、最近工具调用与终端输出",
+                "mimeType": "application/json"
+            },
+            {
+                "uriTemplate": "agent://{agent_id}/transcript",
+                "name": "Agent transcript",
+                "description": format!("agent 对话记录的最近 {} 条事件", TRANSCRIPT_EVENTS),
+                "mimeType": "application/json"
+            },
+            {
+                "uriTemplate": "team://{team_name}/status",
+                "name": "Team status",
+                "description": "team 成员状态、待处理请求与任务",
+                "mimeType": "application/json"
+            }
+        ]
+    })
+}
+
+/// `resources/read` 的响应
+pub fn read_result(uri: &str, content: &Value) -> Result<Value> {
+    Ok(serde_json::json!({
+        "contents": [{
+            "uri": uri,
+            "mimeType": "application/json",
+            "text": serde_json::to_string_pretty(content)?
+        }]
+    }))
+}
+
+/// 资源更新通知
+pub fn updated_notification(uri: &str) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/resources/updated",
+        "params": { "uri": uri }
+    })
+}
+
+/// 内容指纹（用于判断订阅的资源是否变化）
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_uri() {
+        assert_eq!(
+            ResourceUri::parse("agent://cam-123/snapshot").unwrap(),
+            ResourceUri::AgentSnapshot("cam-123".to_string())
+        );
+        assert_eq!(
+            ResourceUri::parse("agent://cam-123/transcript").unwrap(),
+            ResourceUri::AgentTranscript("cam-123".to_string())
+        );
+        let team = ResourceUri::parse("team://my-team/status").unwrap();
+        assert_eq!(team, ResourceUri::TeamStatus("my-team".to_string()));
+        assert_eq!(team.uri(), "team://my-team/status");
+
+        assert!(ResourceUri::parse("agent:///snapshot").is_err());
+        assert!(ResourceUri::parse("agent://cam-123/status").is_err());
+        assert!(ResourceUri::parse("file:///tmp/x").is_err());
+    }
+
+    #[test]
+    fn test_updated_notification() {
+        let notification = updated_notification("agent://cam-1/snapshot");
+        assert_eq!(notification["method"], "notifications/resources/updated");
+        assert_eq!(notification["params"]["uri"], "agent://cam-1/snapshot");
+        assert!(notification.get("id").is_none());
+    }
+}
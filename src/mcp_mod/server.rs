@@ -1,16 +1,27 @@
 //! MCP Server 模块 - 提供 MCP 协议接口
 
+use crate::agent::image::send_image;
+use crate::agent::inbox::receive_attachment;
+use crate::agent::prompt_queue::{send_or_queue, Delivery, PromptQueue};
 use crate::infra::input::InputWaitDetector;
 use crate::infra::jsonl::{format_tool_use, JsonlEvent, JsonlParser};
+use crate::mcp::pagination::{self, ResponseOptions};
+use crate::mcp::prompts::{self, PromptKind, WaitingAgent};
+use crate::mcp::resources::{self, ResourceUri};
 use crate::notification::load_webhook_config_from_file;
 use crate::notification::openclaw::OpenclawNotifier;
-use crate::session::state::{ConversationStateManager, ReplyResult};
+use crate::notification::{remember_reply, NotificationStore};
+use crate::session::state::{AckResult, ConversationStateManager, ReplyResult};
+use crate::session::ReplySender;
 use crate::team;
 use crate::team::task_list;
-use crate::team::{InboxMessage, InboxWatcher, TeamBridge, TeamOrchestrator};
+use crate::team::{InboxMessage, InboxWatcher, MessageFields, TeamBridge, TeamOrchestrator};
 use crate::{AgentManager, ProcessScanner, SessionManager, StartAgentRequest};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
 // Re-export types from types module for backwards compatibility
@@ -28,12 +39,15 @@ struct McpTool {
 /// MCP Server
 pub struct McpServer {
     pub agent_manager: AgentManager,
+    /// 已订阅的资源 URI -> 上次通知时的内容指纹
+    subscriptions: Mutex<HashMap<String, Option<u64>>>,
 }
 
 impl McpServer {
     pub fn new(_port: u16) -> Self {
         Self {
             agent_manager: AgentManager::new(),
+            subscriptions: Mutex::new(HashMap::new()),
         }
     }
 
@@ -41,23 +55,36 @@ impl McpServer {
     pub fn new_for_test() -> Self {
         Self {
             agent_manager: AgentManager::new_for_test(),
+            subscriptions: Mutex::new(HashMap::new()),
         }
     }
 
     /// 运行 MCP Server (stdio 模式)
+    ///
+    /// 除了逐行处理请求，还定期检查已订阅的资源，内容变化时推送更新通知。
     pub async fn run(&self) -> Result<()> {
         let stdin = tokio::io::stdin();
         let mut stdout = tokio::io::stdout();
-        let mut reader = BufReader::new(stdin);
-        let mut line = String::new();
+        let mut lines = BufReader::new(stdin).lines();
+        let mut poll = tokio::time::interval(std::time::Duration::from_secs(
+            resources::SUBSCRIPTION_POLL_SECS,
+        ));
 
         loop {
-            line.clear();
-            let bytes_read = reader.read_line(&mut line).await?;
-
-            if bytes_read == 0 {
-                break; // EOF
-            }
+            let line = tokio::select! {
+                line = lines.next_line() => match line? {
+                    Some(line) => line,
+                    None => break, // EOF
+                },
+                _ = poll.tick() => {
+                    for notification in self.poll_subscriptions() {
+                        stdout.write_all(notification.to_string().as_bytes()).await?;
+                        stdout.write_all(b"\n").await?;
+                    }
+                    stdout.flush().await?;
+                    continue;
+                }
+            };
 
             let line = line.trim();
             if line.is_empty() {
@@ -87,9 +114,20 @@ impl McpServer {
             "initialize" => self.handle_initialize(),
             "tools/list" => self.handle_tools_list(),
             "tools/call" => self.handle_tools_call(request.params),
+            // MCP resources
+            "resources/list" => self.handle_resources_list(),
+            "resources/templates/list" => Ok(resources::resource_templates()),
+            "resources/read" => self.handle_resources_read(request.params),
+            "resources/subscribe" => self.handle_resources_subscribe(request.params),
+            "resources/unsubscribe" => self.handle_resources_unsubscribe(request.params),
+            // MCP prompts
+            "prompts/list" => Ok(prompts::prompt_definitions()),
+            "prompts/get" => self.handle_prompts_get(request.params),
             // 新增的 agent/* 方法
             "agent/start" => self.handle_agent_start(request.params),
             "agent/send" => self.handle_agent_send(request.params),
+            "agent/send_image" => self.handle_agent_send_image(request.params),
+            "agent/receive_attachment" => self.handle_agent_receive_attachment(request.params),
             "agent/list" => self.handle_agent_list(),
             "agent/logs" => self.handle_agent_logs(request.params),
             "agent/stop" => self.handle_agent_stop(request.params),
@@ -144,13 +182,18 @@ impl McpServer {
             initial_prompt: params["initial_prompt"].as_str().map(|s| s.to_string()),
             agent_id: params["agent_id"].as_str().map(|s| s.to_string()),
             tmux_session: params["tmux_session"].as_str().map(|s| s.to_string()),
+            container: params["container"].as_str().map(|s| s.to_string()),
         };
 
+        let report = self.agent_manager.preflight(&request)?;
+        report.ensure_ok()?;
         let response = self.agent_manager.start_agent(request)?;
 
         Ok(serde_json::json!({
             "agent_id": response.agent_id,
-            "tmux_session": response.tmux_session
+            "tmux_session": response.tmux_session,
+            "prompt_delivered": response.prompt_delivered,
+            "warnings": report.warnings().collect::<Vec<_>>()
         }))
     }
 
@@ -166,11 +209,78 @@ impl McpServer {
             .as_str()
             .or_else(|| params["message"].as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing input or message"))?;
+        // 回答记忆：`r` 展开为上次的回答
+        let input = remember_reply(agent_id, input);
+
+        // agent 处理中时排队，空闲后由 watcher 发送
+        let queue = PromptQueue::in_dir(self.agent_manager.data_dir());
+        match send_or_queue(&self.agent_manager, &queue, agent_id, &input)? {
+            Delivery::Sent => Ok(serde_json::json!({
+                "success": true
+            })),
+            Delivery::Queued(prompt) => Ok(serde_json::json!({
+                "success": true,
+                "queued": true,
+                "queue_id": prompt.id
+            })),
+        }
+    }
 
-        self.agent_manager.send_input(agent_id, input)?;
+    /// 处理 agent/send_image
+    fn handle_agent_send_image(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let params = params.ok_or_else(|| anyhow::anyhow!("Missing params"))?;
 
+        let agent_id = params["agent_id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing agent_id"))?;
+        let path = params["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing path"))?;
+        let sent = send_image(
+            &self.agent_manager,
+            agent_id,
+            Path::new(path),
+            params["message"].as_str(),
+        )?;
         Ok(serde_json::json!({
-            "success": true
+            "success": true,
+            "path": sent.path
+        }))
+    }
+
+    /// 处理 agent/receive_attachment
+    fn handle_agent_receive_attachment(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let params = params.ok_or_else(|| anyhow::anyhow!("Missing params"))?;
+
+        let source = params["source"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing source"))?;
+        let agent_id = match params["reply_to"].as_str() {
+            Some(id) => NotificationStore::agent_for_message(id)
+                .ok_or_else(|| anyhow::anyhow!("未找到消息 {} 对应的 agent 线程", id))?,
+            None => params["agent_id"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Missing agent_id or reply_to"))?
+                .to_string(),
+        };
+        let received = receive_attachment(
+            &self.agent_manager,
+            &agent_id,
+            source,
+            params["name"].as_str(),
+            params["caption"].as_str(),
+        )?;
+        Ok(serde_json::json!({
+            "success": true,
+            "agent_id": agent_id,
+            "path": received.path,
+            "queued": received.queue_id.is_some()
         }))
     }
 
@@ -252,30 +362,7 @@ impl McpServer {
         let wait_result = input_detector.detect_immediate(&terminal_output);
 
         // 解析 JSONL 获取最近的工具调用和错误
-        let (recent_tools, recent_errors) = if let Some(ref jsonl_path) = agent.jsonl_path {
-            let mut parser = JsonlParser::new(jsonl_path);
-            let tools = parser.get_recent_tool_calls(5).unwrap_or_default();
-            let errors = parser.get_recent_errors(3).unwrap_or_default();
-            (tools, errors)
-        } else {
-            (Vec::new(), Vec::new())
-        };
-
-        // 格式化工具调用
-        let tools_formatted: Vec<String> =
-            recent_tools.iter().filter_map(format_tool_use).collect();
-
-        // 格式化错误
-        let errors_formatted: Vec<String> = recent_errors
-            .iter()
-            .filter_map(|e| {
-                if let JsonlEvent::Error { message, .. } = e {
-                    Some(message.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
+        let (tools_formatted, errors_formatted) = recent_activity(agent.jsonl_path.as_deref());
 
         // 确定状态
         let status = if wait_result.is_waiting {
@@ -441,15 +528,12 @@ impl McpServer {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing text"))?;
         let summary = params["summary"].as_str().map(String::from);
+        let fields: MessageFields = serde_json::from_value(params.clone())?;
 
         let bridge = TeamBridge::new();
         let message = InboxMessage {
-            from: from.to_string(),
-            text: text.to_string(),
             summary,
-            timestamp: chrono::Utc::now(),
-            color: None,
-            read: false,
+            ..InboxMessage::new(from, text).with_fields(fields)
         };
         bridge.send_to_inbox(team, member, message)?;
 
@@ -500,12 +584,200 @@ impl McpServer {
         }))
     }
 
+    /// 处理 resources/list - 列出当前 agent 与 team 的资源
+    fn handle_resources_list(&self) -> Result<serde_json::Value> {
+        let mut uris = Vec::new();
+        for agent in self.agent_manager.list_agents()? {
+            uris.push(ResourceUri::AgentSnapshot(agent.agent_id.clone()));
+            uris.push(ResourceUri::AgentTranscript(agent.agent_id));
+        }
+        for team in team::discovery::discover_teams() {
+            uris.push(ResourceUri::TeamStatus(team.team_name));
+        }
+
+        Ok(serde_json::json!({
+            "resources": uris.iter().map(ResourceUri::descriptor).collect::<Vec<_>>()
+        }))
+    }
+
+    /// 处理 resources/read
+    fn handle_resources_read(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let uri = resource_uri_param(params)?;
+        let content = self.read_resource(&ResourceUri::parse(&uri)?)?;
+        resources::read_result(&uri, &content)
+    }
+
+    /// 处理 resources/subscribe - 记录当前内容指纹，之后变化时推送通知
+    fn handle_resources_subscribe(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let uri = resource_uri_param(params)?;
+        let resource = ResourceUri::parse(&uri)?;
+        let hash = self
+            .read_resource(&resource)
+            .ok()
+            .map(|content| resources::content_hash(&content.to_string()));
+        self.subscriptions
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Subscription lock poisoned"))?
+            .insert(uri, hash);
+        Ok(serde_json::json!({}))
+    }
+
+    /// 处理 resources/unsubscribe
+    fn handle_resources_unsubscribe(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let uri = resource_uri_param(params)?;
+        self.subscriptions
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Subscription lock poisoned"))?
+            .remove(&uri);
+        Ok(serde_json::json!({}))
+    }
+
+    /// 读取资源内容
+    fn read_resource(&self, resource: &ResourceUri) -> Result<serde_json::Value> {
+        match resource {
+            ResourceUri::AgentSnapshot(agent_id) => {
+                // 使用 watcher 维护的状态，不做实时等待检测，适合订阅轮询
+                let agent = self
+                    .agent_manager
+                    .get_agent(agent_id)?
+                    .ok_or_else(|| anyhow::anyhow!("Agent not found: {}", agent_id))?;
+                let (recent_tools, recent_errors) = recent_activity(agent.jsonl_path.as_deref());
+                let terminal = self
+                    .agent_manager
+                    .get_logs(agent_id, resources::SNAPSHOT_LINES)
+                    .unwrap_or_default();
+                Ok(serde_json::json!({
+                    "agent_id": agent.agent_id,
+                    "agent_type": agent.agent_type.to_string(),
+                    "project_path": agent.project_path,
+                    "tmux_session": agent.tmux_session,
+                    "status": agent.status,
+                    "recent_tools": recent_tools,
+                    "recent_errors": recent_errors,
+                    "terminal": terminal,
+                    "started_at": agent.started_at
+                }))
+            }
+            ResourceUri::AgentTranscript(agent_id) => {
+                let agent = self
+                    .agent_manager
+                    .get_agent(agent_id)?
+                    .ok_or_else(|| anyhow::anyhow!("Agent not found: {}", agent_id))?;
+                let jsonl_path = match agent.jsonl_path {
+                    Some(path) => Some(std::path::PathBuf::from(path)),
+                    None => match agent.session_id.as_deref() {
+                        Some(session_id) => SessionManager::new().find_session_file(session_id)?,
+                        None => None,
+                    },
+                };
+                let events = match jsonl_path {
+                    Some(path) if path.exists() => JsonlParser::new(path).read_all_events()?,
+                    _ => Vec::new(),
+                };
+                let recent = &events[events.len().saturating_sub(resources::TRANSCRIPT_EVENTS)..];
+                Ok(serde_json::json!({
+                    "agent_id": agent.agent_id,
+                    "session_id": agent.session_id,
+                    "events": recent
+                }))
+            }
+            ResourceUri::TeamStatus(name) => Ok(serde_json::to_value(
+                TeamBridge::new().get_team_status(name)?,
+            )?),
+        }
+    }
+
+    /// 检查已订阅的资源，返回内容变化的更新通知
+    fn poll_subscriptions(&self) -> Vec<serde_json::Value> {
+        let uris: Vec<String> = match self.subscriptions.lock() {
+            Ok(subscriptions) => subscriptions.keys().cloned().collect(),
+            Err(_) => return Vec::new(),
+        };
+
+        let mut notifications = Vec::new();
+        for uri in uris {
+            let hash = ResourceUri::parse(&uri)
+                .and_then(|resource| self.read_resource(&resource))
+                .ok()
+                .map(|content| resources::content_hash(&content.to_string()));
+            let Ok(mut subscriptions) = self.subscriptions.lock() else {
+                break;
+            };
+            // 读取期间可能已取消订阅
+            if let Some(previous) = subscriptions.get_mut(&uri) {
+                if *previous != hash {
+                    *previous = hash;
+                    notifications.push(resources::updated_notification(&uri));
+                }
+            }
+        }
+        notifications
+    }
+
+    /// 处理 prompts/get - 用实时数据填充预置 prompt
+    fn handle_prompts_get(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let params = params.ok_or_else(|| anyhow::anyhow!("Missing params"))?;
+        let name = params["name"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing name"))?;
+
+        match PromptKind::parse(name)? {
+            PromptKind::TriageWaitingAgents => {
+                let agents: Vec<WaitingAgent> = self
+                    .agent_manager
+                    .list_agents()?
+                    .into_iter()
+                    .filter(|agent| agent.status.is_waiting())
+                    .map(|agent| WaitingAgent {
+                        terminal: self
+                            .agent_manager
+                            .get_logs(&agent.agent_id, prompts::WAITING_TERMINAL_LINES)
+                            .unwrap_or_default(),
+                        agent_id: agent.agent_id,
+                        project_path: agent.project_path,
+                        status: agent.status,
+                    })
+                    .collect();
+                let pending = ConversationStateManager::new().get_pending_confirmations()?;
+                Ok(prompts::get_result(
+                    "等待处理的 agent 与确认请求",
+                    prompts::triage_waiting_agents(&agents, &pending),
+                ))
+            }
+            PromptKind::SummarizeTeamProgress => {
+                let team_name = params["arguments"]["team_name"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing argument: team_name"))?;
+                let status = TeamBridge::new().get_team_status(team_name)?;
+                let tasks = task_list::list_tasks(team_name);
+                Ok(prompts::get_result(
+                    &format!("Team {} 进度报告", team_name),
+                    prompts::summarize_team_progress(&status, &tasks),
+                ))
+            }
+        }
+    }
+
     /// 处理 initialize
     fn handle_initialize(&self) -> Result<serde_json::Value> {
         Ok(serde_json::json!({
             "protocolVersion": "2024-11-05",
             "capabilities": {
-                "tools": {}
+                "tools": {},
+                "resources": {
+                    "subscribe": true,
+                    "listChanged": false
+                },
+                "prompts": {}
             },
             "serverInfo": {
                 "name": "code-agent-monitor",
@@ -554,10 +826,6 @@ impl McpServer {
                         "days": {
                             "type": "integer",
                             "description": "只返回最近 N 天的会话"
-                        },
-                        "limit": {
-                            "type": "integer",
-                            "description": "限制返回数量，默认 20"
                         }
                     },
                     "required": []
@@ -650,6 +918,10 @@ impl McpServer {
                         "initial_prompt": {
                             "type": "string",
                             "description": "可选，启动后立即发送的消息"
+                        },
+                        "container": {
+                            "type": "string",
+                            "description": "可选，在该 Docker 镜像的容器中运行（挂载项目目录）"
                         }
                     },
                     "required": ["project_path"]
@@ -657,7 +929,7 @@ impl McpServer {
             },
             McpTool {
                 name: "agent_send".to_string(),
-                description: "向指定 Agent 发送输入".to_string(),
+                description: "向指定 Agent 发送输入（Agent 正在处理时加入队列，空闲后发送）".to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -673,6 +945,58 @@ impl McpServer {
                     "required": ["agent_id", "input"]
                 }),
             },
+            McpTool {
+                name: "agent_send_image".to_string(),
+                description: "把本机图片（如聊天中收到的截图）转发给 Agent".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "agent_id": {
+                            "type": "string",
+                            "description": "CAM 分配的 Agent ID"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "图片路径（png、jpg、gif、webp，最大 10 MB）"
+                        },
+                        "message": {
+                            "type": "string",
+                            "description": "可选，随图片一起输入的说明"
+                        }
+                    },
+                    "required": ["agent_id", "path"]
+                }),
+            },
+            McpTool {
+                name: "agent_receive_attachment".to_string(),
+                description: "用户回复通知时附带的图片或文件：保存到 Agent 工作区的 .cam/inbox/ 并告诉 Agent 文件路径".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {
+                            "type": "string",
+                            "description": "附件的下载地址（http/https）或本机路径，最大 20 MB"
+                        },
+                        "reply_to": {
+                            "type": "string",
+                            "description": "被回复的通知消息 ID，按通知线程找到 Agent"
+                        },
+                        "agent_id": {
+                            "type": "string",
+                            "description": "CAM 分配的 Agent ID（未提供 reply_to 时必填）"
+                        },
+                        "name": {
+                            "type": "string",
+                            "description": "可选，渠道给出的文件名"
+                        },
+                        "caption": {
+                            "type": "string",
+                            "description": "可选，随附件发来的文字"
+                        }
+                    },
+                    "required": ["source"]
+                }),
+            },
             McpTool {
                 name: "agent_list".to_string(),
                 description: "列出所有运行中的 Agent".to_string(),
@@ -913,6 +1237,25 @@ impl McpServer {
                         "from": {
                             "type": "string",
                             "description": "发送者名称（可选，默认 'user'）"
+                        },
+                        "type": {
+                            "type": "string",
+                            "enum": ["text", "task", "question", "answer", "status", "result", "review"],
+                            "description": "消息类型（可选，默认 text）"
+                        },
+                        "task_ref": {
+                            "type": "string",
+                            "description": "关联的任务 ID（可选）"
+                        },
+                        "attachments": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "附件文件路径（可选）"
+                        },
+                        "priority": {
+                            "type": "string",
+                            "enum": ["low", "normal", "high"],
+                            "description": "优先级（可选，默认 normal）"
                         }
                     },
                     "required": ["team", "member", "message"]
@@ -1010,11 +1353,34 @@ impl McpServer {
                         "target": {
                             "type": "string",
                             "description": "目标 agent_id 或 confirmation_id（可选，单个待处理时自动选择）"
+                        },
+                        "sender": {
+                            "type": "string",
+                            "description": "回复者 ID（共享渠道中用于 reply_auth 授权，如 tg:123456）"
+                        },
+                        "reply_to": {
+                            "type": "string",
+                            "description": "用户在渠道中回复的消息 ID（线程内回复时代替 target）"
                         }
                     },
                     "required": ["reply"]
                 }),
             },
+            McpTool {
+                name: "ack_pending".to_string(),
+                description: "确认已看到通知，稍后处理；回复或 agent 恢复运行前不再提醒"
+                    .to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "target": {
+                            "type": "string",
+                            "description": "目标 agent_id 或 confirmation_id（可选，单个待处理时自动选择）"
+                        }
+                    },
+                    "required": []
+                }),
+            },
             // Remote Lead Mode tools
             McpTool {
                 name: "team_orchestrate".to_string(),
@@ -1058,7 +1424,9 @@ impl McpServer {
             },
             McpTool {
                 name: "handle_user_reply".to_string(),
-                description: "处理用户自然语言回复（自动解析意图并执行）".to_string(),
+                description:
+                    "处理用户自然语言回复（自动解析意图并执行），支持聊天命令 snooze/status/list/logs/kill"
+                        .to_string(),
                 input_schema: serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -1069,9 +1437,13 @@ impl McpServer {
                         "context": {
                             "type": "string",
                             "description": "上下文信息（如当前 team 名称）"
+                        },
+                        "sender": {
+                            "type": "string",
+                            "description": "回复者标识（如 tg:123456），用于 reply_auth 授权"
                         }
                     },
-                    "required": ["reply"]
+                    "required": ["reply", "sender"]
                 }),
             },
             McpTool {
@@ -1086,6 +1458,14 @@ impl McpServer {
             },
         ];
 
+        let tools: Vec<McpTool> = tools
+            .into_iter()
+            .map(|tool| McpTool {
+                input_schema: pagination::with_response_options(tool.input_schema),
+                ..tool
+            })
+            .collect();
+
         Ok(serde_json::json!({ "tools": tools }))
     }
 
@@ -1100,6 +1480,13 @@ impl McpServer {
             .cloned()
             .unwrap_or(serde_json::json!({}));
 
+        let options = ResponseOptions::from_arguments(&arguments)?;
+        let result = self.call_tool(name, arguments)?;
+        Ok(pagination::shape_tool_result(result, &options))
+    }
+
+    /// 执行工具调用（结果由 `handle_tools_call` 统一分页和限制大小）
+    fn call_tool(&self, name: &str, arguments: serde_json::Value) -> Result<serde_json::Value> {
         match name {
             "list_agents" => {
                 let scanner = ProcessScanner::new();
@@ -1129,7 +1516,9 @@ impl McpServer {
                 let filter = crate::session::SessionFilter {
                     project_path: arguments["project_path"].as_str().map(|s| s.to_string()),
                     days: arguments["days"].as_i64(),
-                    limit: Some(arguments["limit"].as_u64().unwrap_or(20) as usize),
+                    // 数量由通用分页参数控制
+                    limit: None,
+                    ..Default::default()
                 };
                 let sessions = manager.list_sessions_filtered(Some(filter))?;
                 Ok(serde_json::json!({
@@ -1177,6 +1566,7 @@ impl McpServer {
                     initial_prompt: None,
                     agent_id: None,
                     tmux_session: None,
+                    container: None,
                 })?;
 
                 Ok(serde_json::json!({
@@ -1234,6 +1624,24 @@ impl McpServer {
                     }]
                 }))
             }
+            "agent_send_image" => {
+                let result = self.handle_agent_send_image(Some(arguments))?;
+                Ok(serde_json::json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&result)?
+                    }]
+                }))
+            }
+            "agent_receive_attachment" => {
+                let result = self.handle_agent_receive_attachment(Some(arguments))?;
+                Ok(serde_json::json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&result)?
+                    }]
+                }))
+            }
             "agent_list" => {
                 let result = self.handle_agent_list()?;
                 Ok(serde_json::json!({
@@ -1492,15 +1900,10 @@ impl McpServer {
                     .and_then(|v| v.as_str())
                     .unwrap_or("user");
 
+                let fields: MessageFields = serde_json::from_value(arguments.clone())?;
+
                 let bridge = TeamBridge::new();
-                let message = InboxMessage {
-                    from: from.to_string(),
-                    text: message_text.to_string(),
-                    summary: None,
-                    timestamp: chrono::Utc::now(),
-                    color: None,
-                    read: false,
-                };
+                let message = InboxMessage::new(from, message_text).with_fields(fields);
                 bridge.send_to_inbox(team, member, message)?;
 
                 Ok(serde_json::json!({
@@ -1629,17 +2032,34 @@ impl McpServer {
                     .get("reply")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| anyhow::anyhow!("缺少 reply 参数"))?;
-                let target = arguments.get("target").and_then(|v| v.as_str());
+                let thread_target = arguments
+                    .get("reply_to")
+                    .and_then(|v| v.as_str())
+                    .map(|id| {
+                        NotificationStore::agent_for_message(id)
+                            .ok_or_else(|| anyhow::anyhow!("未找到消息 {} 对应的 agent 线程", id))
+                    })
+                    .transpose()?;
+                let target = thread_target
+                    .as_deref()
+                    .or_else(|| arguments.get("target").and_then(|v| v.as_str()));
+                let sender = arguments.get("sender").and_then(|v| v.as_str());
 
                 let state_manager = ConversationStateManager::new();
-                let result = state_manager.handle_reply(reply, target)?;
+                let result =
+                    state_manager.handle_reply_from(reply, target, ReplySender::Remote(sender))?;
 
                 let response = match result {
-                    ReplyResult::Sent { agent_id, reply } => {
+                    ReplyResult::Sent {
+                        agent_id,
+                        reply,
+                        verification,
+                    } => {
                         serde_json::json!({
                             "status": "sent",
                             "agent_id": agent_id,
-                            "reply": reply
+                            "reply": reply,
+                            "verification": verification
                         })
                     }
                     ReplyResult::NeedSelection { options } => {
@@ -1664,6 +2084,45 @@ impl McpServer {
                             "message": msg
                         })
                     }
+                    ReplyResult::Denied { agent_id, reason } => {
+                        serde_json::json!({
+                            "status": "denied",
+                            "agent_id": agent_id,
+                            "message": reason
+                        })
+                    }
+                };
+
+                Ok(serde_json::json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&response)?
+                    }]
+                }))
+            }
+            "ack_pending" => {
+                let target = arguments.get("target").and_then(|v| v.as_str());
+                let response = match ConversationStateManager::new().acknowledge(target)? {
+                    AckResult::Acknowledged { agent_id } => serde_json::json!({
+                        "status": "acknowledged",
+                        "agent_id": agent_id
+                    }),
+                    AckResult::NeedSelection { options } => serde_json::json!({
+                        "status": "need_selection",
+                        "options": options.iter().map(|o| serde_json::json!({
+                            "id": o.id,
+                            "agent_id": o.agent_id,
+                            "context": o.context
+                        })).collect::<Vec<_>>()
+                    }),
+                    AckResult::NoPending => serde_json::json!({
+                        "status": "no_pending",
+                        "message": "没有待处理的确认请求"
+                    }),
+                    AckResult::NotFound(target) => serde_json::json!({
+                        "status": "not_found",
+                        "message": format!("未找到目标: {}", target)
+                    }),
                 };
 
                 Ok(serde_json::json!({
@@ -1724,9 +2183,11 @@ impl McpServer {
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| anyhow::anyhow!("缺少 reply 参数"))?;
                 let context = arguments.get("context").and_then(|v| v.as_str());
+                // 缺少 sender 时按未标识的远程回复者处理（配置了 reply_auth 列表的等级会被拒绝）
+                let sender = arguments.get("sender").and_then(|v| v.as_str());
 
                 let orchestrator = TeamOrchestrator::new();
-                let result = orchestrator.handle_user_reply(reply, context)?;
+                let result = orchestrator.handle_user_reply(reply, context, sender)?;
 
                 Ok(serde_json::json!({
                     "content": [{
@@ -1754,6 +2215,37 @@ impl McpServer {
     }
 }
 
+/// 从 JSONL 读取最近的工具调用（5 条）和错误（3 条）
+fn recent_activity(jsonl_path: Option<&str>) -> (Vec<String>, Vec<String>) {
+    let Some(jsonl_path) = jsonl_path else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut parser = JsonlParser::new(jsonl_path);
+    let tools = parser
+        .get_recent_tool_calls(5)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(format_tool_use)
+        .collect();
+    let errors = parser
+        .get_recent_errors(3)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|e| match e {
+            JsonlEvent::Error { message, .. } => Some(message),
+            _ => None,
+        })
+        .collect();
+    (tools, errors)
+}
+
+/// 读取资源请求中的 `uri` 参数
+fn resource_uri_param(params: Option<serde_json::Value>) -> Result<String> {
+    params
+        .and_then(|p| p["uri"].as_str().map(|s| s.to_string()))
+        .ok_or_else(|| anyhow::anyhow!("Missing uri"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1838,9 +2330,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_mcp_agent_list() {
-        // Given: 两个运行中的 agent
+        // Given: 两个运行中的 agent（启动前检查要求工作目录存在）
         let server = McpServer::new_for_test();
         cleanup_test_agents(&server);
+        let (dir_a, dir_b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
 
         let r1 = server
             .handle_request(McpRequest {
@@ -1848,7 +2341,7 @@ mod tests {
                 id: Some(serde_json::json!(1)),
                 method: "agent/start".to_string(),
                 params: Some(serde_json::json!({
-                    "project_path": "/tmp/a",
+                    "project_path": dir_a.path(),
                     "agent_type": "mock"
                 })),
             })
@@ -1863,7 +2356,7 @@ mod tests {
                 id: Some(serde_json::json!(2)),
                 method: "agent/start".to_string(),
                 params: Some(serde_json::json!({
-                    "project_path": "/tmp/b",
+                    "project_path": dir_b.path(),
                     "agent_type": "mock"
                 })),
             })
@@ -1891,6 +2384,127 @@ mod tests {
         server.agent_manager.stop_agent(&id2).unwrap();
     }
 
+    #[tokio::test]
+    async fn test_mcp_resources() {
+        // Given: 一个运行中的 agent
+        let server = McpServer::new_for_test();
+        cleanup_test_agents(&server);
+        let request = |id: u64, method: &str, params: serde_json::Value| McpRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(id)),
+            method: method.to_string(),
+            params: Some(params),
+        };
+        let started = server
+            .handle_request(request(
+                1,
+                "agent/start",
+                serde_json::json!({"project_path": "/tmp", "agent_type": "mock"}),
+            ))
+            .await;
+        let agent_id = started.result.unwrap()["agent_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let snapshot_uri = format!("agent://{}/snapshot", agent_id);
+
+        // When/Then: resources/list 包含该 agent 的快照与对话记录
+        let listed = server
+            .handle_request(request(2, "resources/list", serde_json::json!({})))
+            .await;
+        let uris: Vec<String> = listed.result.unwrap()["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["uri"].as_str().unwrap().to_string())
+            .collect();
+        assert!(uris.contains(&snapshot_uri));
+        assert!(uris.contains(&format!("agent://{}/transcript", agent_id)));
+
+        // resources/read 返回 JSON 内容
+        let read = server
+            .handle_request(request(
+                3,
+                "resources/read",
+                serde_json::json!({"uri": snapshot_uri}),
+            ))
+            .await;
+        let contents = &read.result.unwrap()["contents"][0];
+        assert_eq!(contents["uri"], snapshot_uri.as_str());
+        let snapshot: serde_json::Value =
+            serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+        assert_eq!(snapshot["agent_id"], agent_id.as_str());
+
+        // 订阅后取消，不再产生通知
+        let subscribed = server
+            .handle_request(request(
+                4,
+                "resources/subscribe",
+                serde_json::json!({"uri": snapshot_uri}),
+            ))
+            .await;
+        assert!(subscribed.error.is_none());
+        server
+            .handle_request(request(
+                5,
+                "resources/unsubscribe",
+                serde_json::json!({"uri": snapshot_uri}),
+            ))
+            .await;
+        assert!(server.poll_subscriptions().is_empty());
+
+        // 无效 URI 返回错误
+        let invalid = server
+            .handle_request(request(
+                6,
+                "resources/read",
+                serde_json::json!({"uri": "file:///etc/passwd"}),
+            ))
+            .await;
+        assert!(invalid.error.is_some());
+
+        server.agent_manager.stop_agent(&agent_id).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mcp_prompts() {
+        let server = McpServer::new_for_test();
+        let request = |id: u64, method: &str, params: serde_json::Value| McpRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(id)),
+            method: method.to_string(),
+            params: Some(params),
+        };
+
+        let listed = server
+            .handle_request(request(1, "prompts/list", serde_json::json!({})))
+            .await;
+        assert_eq!(
+            listed.result.unwrap()["prompts"].as_array().unwrap().len(),
+            2
+        );
+
+        // 缺少必需参数
+        let missing = server
+            .handle_request(request(
+                2,
+                "prompts/get",
+                serde_json::json!({"name": "summarize_team_progress"}),
+            ))
+            .await;
+        assert!(missing.error.unwrap().message.contains("team_name"));
+
+        // 未知 prompt
+        let unknown = server
+            .handle_request(request(
+                3,
+                "prompts/get",
+                serde_json::json!({"name": "unknown"}),
+            ))
+            .await;
+        assert!(unknown.error.is_some());
+    }
+
     #[tokio::test]
     async fn test_mcp_invalid_method_returns_error() {
         // Given: MCP Server
@@ -5,9 +5,11 @@
 use anyhow::Result;
 use serde_json::Value;
 
+use crate::agent::prompt_queue::{send_or_queue, Delivery, PromptQueue};
 use crate::agent::{AgentManager, StartAgentRequest};
 use crate::infra::input::InputWaitDetector;
 use crate::infra::jsonl::{format_tool_use, JsonlEvent, JsonlParser};
+use crate::notification::remember_reply;
 
 /// Handle agent/start request
 pub fn handle_agent_start(agent_manager: &AgentManager, params: Option<Value>) -> Result<Value> {
@@ -23,13 +25,18 @@ pub fn handle_agent_start(agent_manager: &AgentManager, params: Option<Value>) -
         initial_prompt: params["initial_prompt"].as_str().map(|s| s.to_string()),
         agent_id: params["agent_id"].as_str().map(|s| s.to_string()),
         tmux_session: params["tmux_session"].as_str().map(|s| s.to_string()),
+        container: params["container"].as_str().map(|s| s.to_string()),
     };
 
+    let report = agent_manager.preflight(&request)?;
+    report.ensure_ok()?;
     let response = agent_manager.start_agent(request)?;
 
     Ok(serde_json::json!({
         "agent_id": response.agent_id,
-        "tmux_session": response.tmux_session
+        "tmux_session": response.tmux_session,
+        "prompt_delivered": response.prompt_delivered,
+        "warnings": report.warnings().collect::<Vec<_>>()
     }))
 }
 
@@ -45,15 +52,26 @@ pub fn handle_agent_send(agent_manager: &AgentManager, params: Option<Value>) ->
         .as_str()
         .or_else(|| params["message"].as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing input or message"))?;
-
-    agent_manager.send_input(agent_id, input)?;
-
-    Ok(serde_json::json!({
-        "success": true,
-        "agent_id": agent_id,
-        "input_sent": input,
-        "message": format!("Input '{}' sent to agent {}. The agent is now processing.", input, agent_id)
-    }))
+    // Expand the "r" shortcut to the remembered answer
+    let input = remember_reply(agent_id, input);
+
+    // Queue the input while the agent is processing; the watcher sends it once idle
+    let queue = PromptQueue::in_dir(agent_manager.data_dir());
+    match send_or_queue(agent_manager, &queue, agent_id, &input)? {
+        Delivery::Sent => Ok(serde_json::json!({
+            "success": true,
+            "agent_id": agent_id,
+            "input_sent": input,
+            "message": format!("Input '{}' sent to agent {}. The agent is now processing.", input, agent_id)
+        })),
+        Delivery::Queued(prompt) => Ok(serde_json::json!({
+            "success": true,
+            "agent_id": agent_id,
+            "queued": true,
+            "queue_id": prompt.id,
+            "message": format!("Agent {} is processing. Input '{}' queued as {} and will be sent when the agent is idle.", agent_id, input, prompt.id)
+        })),
+    }
 }
 
 /// Handle agent/list request
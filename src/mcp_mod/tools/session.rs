@@ -16,6 +16,7 @@ pub fn handle_list_sessions(params: Option<Value>) -> Result<Value> {
         project_path: p["project_path"].as_str().map(|s| s.to_string()),
         days: p["days"].as_i64(),
         limit: Some(p["limit"].as_u64().unwrap_or(20) as usize),
+        ..Default::default()
     });
 
     let sessions = manager.list_sessions_filtered(filter)?;
@@ -75,6 +76,7 @@ pub fn handle_resume_session(agent_manager: &AgentManager, params: Option<Value>
         initial_prompt: None,
         agent_id: None,
         tmux_session: None,
+        container: None,
     })?;
 
     Ok(serde_json::json!({
@@ -5,7 +5,9 @@
 use anyhow::Result;
 use serde_json::Value;
 
+use crate::notification::NotificationStore;
 use crate::session::state::{ConversationStateManager, ReplyResult};
+use crate::session::ReplySender;
 use crate::team::task_list::{self, TaskStatus};
 
 /// Handle task_list request
@@ -119,17 +121,33 @@ pub fn handle_reply_pending(params: Option<Value>) -> Result<Value> {
         .get("reply")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing reply parameter"))?;
-    let target = params.get("target").and_then(|v| v.as_str());
+    let thread_target = params
+        .get("reply_to")
+        .and_then(|v| v.as_str())
+        .map(|id| {
+            NotificationStore::agent_for_message(id)
+                .ok_or_else(|| anyhow::anyhow!("No agent thread found for message {}", id))
+        })
+        .transpose()?;
+    let target = thread_target
+        .as_deref()
+        .or_else(|| params.get("target").and_then(|v| v.as_str()));
+    let sender = params.get("sender").and_then(|v| v.as_str());
 
     let state_manager = ConversationStateManager::new();
-    let result = state_manager.handle_reply(reply, target)?;
+    let result = state_manager.handle_reply_from(reply, target, ReplySender::Remote(sender))?;
 
     let response = match result {
-        ReplyResult::Sent { agent_id, reply } => {
+        ReplyResult::Sent {
+            agent_id,
+            reply,
+            verification,
+        } => {
             serde_json::json!({
                 "status": "sent",
                 "agent_id": agent_id,
-                "reply": reply
+                "reply": reply,
+                "verification": verification
             })
         }
         ReplyResult::NeedSelection { options } => {
@@ -154,6 +172,13 @@ pub fn handle_reply_pending(params: Option<Value>) -> Result<Value> {
                 "message": msg
             })
         }
+        ReplyResult::Denied { agent_id, reason } => {
+            serde_json::json!({
+                "status": "denied",
+                "agent_id": agent_id,
+                "message": reason
+            })
+        }
     };
 
     Ok(serde_json::json!({
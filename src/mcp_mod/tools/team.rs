@@ -7,7 +7,9 @@ use serde_json::Value;
 
 use crate::notification::load_webhook_config_from_file;
 use crate::notification::openclaw::OpenclawNotifier;
-use crate::team::{discovery, InboxMessage, InboxWatcher, TeamBridge, TeamOrchestrator};
+use crate::team::{
+    discovery, InboxMessage, InboxWatcher, MessageFields, TeamBridge, TeamOrchestrator,
+};
 
 /// Handle team/list request
 pub fn handle_team_list() -> Result<Value> {
@@ -151,15 +153,12 @@ pub fn handle_inbox_send(params: Option<Value>) -> Result<Value> {
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("Missing text"))?;
     let summary = params["summary"].as_str().map(String::from);
+    let fields: MessageFields = serde_json::from_value(params.clone())?;
 
     let bridge = TeamBridge::new();
     let message = InboxMessage {
-        from: from.to_string(),
-        text: text.to_string(),
         summary,
-        timestamp: chrono::Utc::now(),
-        color: None,
-        read: false,
+        ..InboxMessage::new(from, text).with_fields(fields)
     };
     bridge.send_to_inbox(team, member, message)?;
 
@@ -337,9 +336,11 @@ pub fn handle_user_reply(params: Option<Value>) -> Result<Value> {
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing reply parameter"))?;
     let context = params.get("context").and_then(|v| v.as_str());
+    // 缺少 sender 时按未标识的远程回复者处理（配置了 reply_auth 列表的等级会被拒绝）
+    let sender = params.get("sender").and_then(|v| v.as_str());
 
     let orchestrator = TeamOrchestrator::new();
-    let result = orchestrator.handle_user_reply(reply, context)?;
+    let result = orchestrator.handle_user_reply(reply, context, sender)?;
 
     Ok(serde_json::json!({
         "content": [{
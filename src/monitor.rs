@@ -0,0 +1,330 @@
+//! Monitor - 面向库使用者的高层封装
+//!
+//! 隐藏数据文件路径、tmux 和轮询细节，便于在其他 Rust 项目中嵌入 CAM：
+//! 通过 `MonitorBuilder` 配置后即可启动 agent、订阅监控事件、回复待处理确认。
+//!
+//! # 使用示例
+//! ```ignore
+//! use code_agent_monitor::{Monitor, StartAgentRequest, WatchEvent};
+//! use tokio_stream::StreamExt;
+//!
+//! let monitor = Monitor::builder()
+//!     .poll_interval(std::time::Duration::from_secs(2))
+//!     .build();
+//!
+//! let agent = monitor
+//!     .start_agent(StartAgentRequest {
+//!         project_path: "/path/to/project".into(),
+//!         agent_type: None,
+//!         resume_session: None,
+//!         initial_prompt: Some("修复测试".into()),
+//!         agent_id: None,
+//!         tmux_session: None,
+//!     })
+//!     .await?;
+//!
+//! let mut events = monitor.subscribe_events();
+//! while let Some(event) = events.next().await {
+//!     if let WatchEvent::WaitingForInput { .. } = event {
+//!         monitor.reply("y", Some(&agent.agent_id)).await?;
+//!     }
+//! }
+//! ```
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tracing::{error, warn};
+
+use crate::agent::{
+    AgentManager, AgentRecord, AgentWatcher, StartAgentRequest, StartAgentResponse, WatchEvent,
+};
+use crate::session::{ConversationStateManager, PendingConfirmation, ReplyResult};
+
+/// Monitor 构建器
+pub struct MonitorBuilder {
+    data_dir: Option<PathBuf>,
+    poll_interval: Duration,
+    event_capacity: usize,
+}
+
+impl MonitorBuilder {
+    pub fn new() -> Self {
+        Self {
+            data_dir: None,
+            poll_interval: Duration::from_secs(2),
+            event_capacity: 256,
+        }
+    }
+
+    /// 设置数据目录（默认 `~/.config/code-agent-monitor`）
+    pub fn data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(dir.into());
+        self
+    }
+
+    /// 设置事件轮询间隔（默认 2 秒）
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 设置事件缓冲区大小，订阅者消费过慢时最旧的事件会被丢弃（默认 256）
+    pub fn event_capacity(mut self, capacity: usize) -> Self {
+        self.event_capacity = capacity.max(1);
+        self
+    }
+
+    /// 构建 Monitor
+    pub fn build(self) -> Monitor {
+        let data_dir = self.data_dir.unwrap_or_else(crate::infra::paths::state_dir);
+        let (events, _) = broadcast::channel(self.event_capacity);
+
+        Monitor {
+            agent_manager: Arc::new(AgentManager::with_data_dir(data_dir.clone())),
+            conversation: Arc::new(ConversationStateManager::with_agent_manager(
+                data_dir.join("conversation_state.json"),
+                AgentManager::with_data_dir(data_dir.clone()),
+            )),
+            data_dir,
+            events,
+            poll_interval: self.poll_interval,
+            poller: Mutex::new(None),
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl Default for MonitorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 可嵌入的监控句柄
+///
+/// 所有阻塞操作（文件锁、tmux 调用）都在 `spawn_blocking` 中执行，需要在 tokio 运行时内使用。
+/// 首次调用 `subscribe_events` 时启动后台轮询线程，Monitor 被 drop 时停止。
+pub struct Monitor {
+    data_dir: PathBuf,
+    agent_manager: Arc<AgentManager>,
+    conversation: Arc<ConversationStateManager>,
+    events: broadcast::Sender<WatchEvent>,
+    poll_interval: Duration,
+    poller: Mutex<Option<std::thread::JoinHandle<()>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Monitor {
+    /// 使用默认配置创建 Monitor
+    pub fn new() -> Self {
+        MonitorBuilder::new().build()
+    }
+
+    /// 创建构建器
+    pub fn builder() -> MonitorBuilder {
+        MonitorBuilder::new()
+    }
+
+    /// 数据目录
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// 启动 Agent
+    pub async fn start_agent(&self, request: StartAgentRequest) -> Result<StartAgentResponse> {
+        let manager = Arc::clone(&self.agent_manager);
+        run_blocking(move || manager.start_agent(request)).await
+    }
+
+    /// 停止 Agent
+    pub async fn stop_agent(&self, agent_id: &str) -> Result<()> {
+        let manager = Arc::clone(&self.agent_manager);
+        let agent_id = agent_id.to_string();
+        run_blocking(move || manager.stop_agent(&agent_id)).await
+    }
+
+    /// 列出所有 Agent
+    pub async fn list_agents(&self) -> Result<Vec<AgentRecord>> {
+        let manager = Arc::clone(&self.agent_manager);
+        run_blocking(move || manager.list_agents()).await
+    }
+
+    /// 获取所有待处理的确认
+    pub async fn pending_confirmations(&self) -> Result<Vec<PendingConfirmation>> {
+        let conversation = Arc::clone(&self.conversation);
+        run_blocking(move || conversation.get_pending_confirmations()).await
+    }
+
+    /// 回复待处理确认（支持 y/n/1/2/3 快捷回复），target 为 agent_id 或确认 ID
+    pub async fn reply(&self, reply: &str, target: Option<&str>) -> Result<ReplyResult> {
+        let conversation = Arc::clone(&self.conversation);
+        let reply = reply.to_string();
+        let target = target.map(|s| s.to_string());
+        run_blocking(move || conversation.handle_reply(&reply, target.as_deref())).await
+    }
+
+    /// 订阅监控事件
+    ///
+    /// 每个订阅者收到订阅之后产生的所有事件；消费过慢导致溢出的事件会被跳过。
+    pub fn subscribe_events(&self) -> impl Stream<Item = WatchEvent> + Send + 'static {
+        let receiver = self.events.subscribe();
+        self.ensure_poller();
+
+        BroadcastStream::new(receiver).filter_map(|item| match item {
+            Ok(event) => Some(event),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                warn!(skipped = skipped, "Event subscriber lagged, events dropped");
+                None
+            }
+        })
+    }
+
+    /// 启动后台轮询线程（已启动时不重复启动）
+    ///
+    /// 轮询使用独立线程而非 tokio 任务：AgentWatcher 内部的 AI 客户端是阻塞式的，
+    /// 不能在异步上下文中创建或销毁。
+    fn ensure_poller(&self) {
+        let mut poller = self.poller.lock().unwrap_or_else(|e| e.into_inner());
+        if poller.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return;
+        }
+
+        let data_dir = self.data_dir.clone();
+        let sender = self.events.clone();
+        let interval = self.poll_interval;
+        let stopped = Arc::clone(&self.stopped);
+
+        let spawned = std::thread::Builder::new()
+            .name("cam-monitor-poller".to_string())
+            .spawn(move || {
+                let mut watcher =
+                    AgentWatcher::with_agent_manager(AgentManager::with_data_dir(data_dir));
+                while !stopped.load(Ordering::SeqCst) {
+                    // 没有订阅者时跳过轮询
+                    if sender.receiver_count() > 0 {
+                        match watcher.poll_once() {
+                            Ok(events) => {
+                                for event in events {
+                                    let _ = sender.send(event);
+                                }
+                            }
+                            Err(e) => warn!(error = %e, "Poll failed"),
+                        }
+                    }
+                    std::thread::sleep(interval);
+                }
+            });
+
+        match spawned {
+            Ok(handle) => *poller = Some(handle),
+            Err(e) => error!(error = %e, "Failed to spawn poller thread"),
+        }
+    }
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        // 通知轮询线程在下一轮退出（不等待，避免阻塞调用方）
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+/// 在阻塞线程池中执行同步操作
+async fn run_blocking<F, T>(operation: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(operation)
+        .await
+        .map_err(|e| anyhow!("blocking task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn test_monitor(dir: &Path) -> Monitor {
+        Monitor::builder()
+            .data_dir(dir)
+            .poll_interval(Duration::from_millis(50))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_list_agents_uses_data_dir() {
+        let dir = tempdir().unwrap();
+        let monitor = test_monitor(dir.path());
+        assert_eq!(monitor.data_dir(), dir.path());
+
+        // tmux session 不存在的记录会被 list_agents 清理并写回数据目录
+        let agents = serde_json::json!({
+            "agents": [{
+                "agent_id": "cam-monitor-test",
+                "agent_type": "claude",
+                "project_path": "/tmp/project",
+                "tmux_session": "cam-monitor-test-nonexistent-session",
+                "started_at": "2026-01-01T00:00:00Z",
+                "status": "processing"
+            }]
+        });
+        let agents_file = dir.path().join("agents.json");
+        std::fs::write(&agents_file, agents.to_string()).unwrap();
+
+        assert!(monitor.list_agents().await.unwrap().is_empty());
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&agents_file).unwrap()).unwrap();
+        assert_eq!(saved["agents"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_reply_without_pending() {
+        let dir = tempdir().unwrap();
+        let monitor = test_monitor(dir.path());
+        assert!(monitor.pending_confirmations().await.unwrap().is_empty());
+        assert!(matches!(
+            monitor.reply("y", None).await.unwrap(),
+            ReplyResult::NoPending
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_events_broadcasts_to_all_subscribers() {
+        let dir = tempdir().unwrap();
+        let monitor = test_monitor(dir.path());
+
+        let mut first = Box::pin(monitor.subscribe_events());
+        let mut second = Box::pin(monitor.subscribe_events());
+        assert_eq!(monitor.events.receiver_count(), 2);
+        assert!(monitor.poller.lock().unwrap().is_some());
+
+        monitor
+            .events
+            .send(WatchEvent::AgentResumed {
+                agent_id: "cam-1".to_string(),
+            })
+            .unwrap();
+
+        for stream in [&mut first, &mut second] {
+            let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("timed out waiting for event")
+                .expect("stream ended");
+            assert!(matches!(event, WatchEvent::AgentResumed { agent_id } if agent_id == "cam-1"));
+        }
+    }
+}
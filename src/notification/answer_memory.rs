@@ -0,0 +1,601 @@
+//! 回答记忆 - 记住同一项目中重复出现的问题的回答
+//!
+//! config.json 中 `"answer_memory": {"enabled": true}` 开启（默认关闭）。
+//!
+//! - 发送带问题指纹（AI 提取器输出的 fingerprint）的通知时，记录 agent 当前的问题；
+//!   同一 (项目, 指纹) 之前回答过时，通知中附带 "上次回答: 2 (Tailwind CSS)"。
+//! - 回复该 agent 时记录回答；回复 `r` 会替换为上次的回答。
+//! - `auto_answer` 开启时，上次回答次数达到 `auto_answer_min_count` 的问题会在
+//!   `auto_answer_delay_secs` 秒后由 watcher 自动发送上次的回答（期间回复其他内容即取消）。
+//!   权限确认（`blocking_permission`）和 HIGH 风险的问题从不自动回答，只附带上次的回答。
+//!   自动回答以 `cam:auto-answer` 身份发送，受 `reply_auth` 约束；每次结果写入
+//!   `~/.config/code-agent-monitor/auto_answers.jsonl`。
+//!
+//! 状态保存在 `~/.config/code-agent-monitor/answer_memory.json`。
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::notification::waiting_kind::WaitingKind;
+
+/// 重复上次回答的快捷回复
+const REPEAT_REPLIES: [&str; 4] = ["r", "repeat", "重复", "同上"];
+/// 最多记住的问题数
+const MAX_ANSWERS: usize = 500;
+/// 自动回答到期后超过此时间（秒）仍未发送（如 watcher 未运行）则放弃
+const AUTO_ANSWER_EXPIRY_SECS: i64 = 600;
+/// 自动回答使用的回复者身份（用于 `reply_auth`）
+pub const AUTO_ANSWER_SENDER: &str = "cam:auto-answer";
+
+/// 回答记忆配置（config.json 的 `answer_memory`）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnswerMemoryConfig {
+    pub enabled: bool,
+    /// 相同问题再次出现时自动发送上次的回答
+    pub auto_answer: bool,
+    /// 自动发送前等待的秒数（期间可回复其他内容取消）
+    pub auto_answer_delay_secs: u64,
+    /// 上次回答至少连续出现的次数
+    pub auto_answer_min_count: u32,
+}
+
+impl Default for AnswerMemoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto_answer: false,
+            auto_answer_delay_secs: 60,
+            auto_answer_min_count: 1,
+        }
+    }
+}
+
+impl AnswerMemoryConfig {
+    /// 读取 config.json，缺失或无效时使用默认值（关闭）
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("answer_memory").unwrap_or_default()
+    }
+}
+
+/// agent 正在等待回答的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AskedQuestion {
+    pub project: String,
+    pub fingerprint: String,
+    pub question: String,
+    pub asked_at: DateTime<Utc>,
+    /// 计划自动发送上次回答的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_answer_at: Option<DateTime<Utc>>,
+}
+
+/// 记住的回答
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RememberedAnswer {
+    /// 实际发送的回复（如 "2"）
+    pub answer: String,
+    /// 回复对应的选项文字（如 "Tailwind CSS"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub answered_at: DateTime<Utc>,
+    /// 连续给出相同回答的次数
+    pub count: u32,
+}
+
+impl RememberedAnswer {
+    /// 显示文本，如 `2 (Tailwind CSS)`
+    pub fn display(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", self.answer, label),
+            None => self.answer.clone(),
+        }
+    }
+}
+
+/// 回答记忆
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnswerMemory {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    config: AnswerMemoryConfig,
+    /// agent_id -> 等待回答的问题
+    #[serde(default)]
+    asked: BTreeMap<String, AskedQuestion>,
+    /// "项目\n指纹" -> 上次回答
+    #[serde(default)]
+    answers: BTreeMap<String, RememberedAnswer>,
+}
+
+fn answer_key(project: &str, fingerprint: &str) -> String {
+    format!("{}\n{}", project, fingerprint)
+}
+
+/// 从问题文本中找出回复对应的选项文字（`2. Tailwind CSS`、`2) ...`、`❯ 2. ...`）
+pub fn option_label(question: &str, answer: &str) -> Option<String> {
+    let answer = answer.trim();
+    if answer.is_empty() || !answer.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    question.lines().find_map(|line| {
+        let line = line
+            .trim()
+            .trim_start_matches(|c: char| !c.is_alphanumeric());
+        let rest = line.strip_prefix(answer)?;
+        let rest = rest.strip_prefix(['.', ')', ':', '、'])?;
+        let label = rest.trim();
+        (!label.is_empty()).then(|| label.to_string())
+    })
+}
+
+/// 问题是否可以自动回答：权限确认（`blocking_permission`）和 HIGH 风险的问题不行
+pub fn auto_answer_allowed(waiting_kind: Option<WaitingKind>, risk_level: &str) -> bool {
+    waiting_kind != Some(WaitingKind::BlockingPermission)
+        && !risk_level.eq_ignore_ascii_case("HIGH")
+}
+
+impl AnswerMemory {
+    /// 开启时读取记忆，未开启返回 None
+    pub fn load() -> Option<Self> {
+        let config = AnswerMemoryConfig::load();
+        if !config.enabled {
+            return None;
+        }
+        Some(Self::load_from(&default_memory_path()).with_config(config))
+    }
+
+    /// 设置配置（`load_from` 使用默认配置）
+    pub fn with_config(mut self, config: AnswerMemoryConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 从指定文件读取（不检查配置）
+    pub fn load_from(path: &Path) -> Self {
+        let mut memory: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        memory.path = path.to_path_buf();
+        memory
+    }
+
+    fn save(&mut self) -> Result<()> {
+        if self.answers.len() > MAX_ANSWERS {
+            let mut by_age: Vec<(String, DateTime<Utc>)> = self
+                .answers
+                .iter()
+                .map(|(key, answer)| (key.clone(), answer.answered_at))
+                .collect();
+            by_age.sort_by_key(|(_, answered_at)| *answered_at);
+            for (key, _) in by_age.into_iter().take(self.answers.len() - MAX_ANSWERS) {
+                self.answers.remove(&key);
+            }
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 同一 (项目, 指纹) 上次的回答
+    pub fn previous_answer(&self, project: &str, fingerprint: &str) -> Option<&RememberedAnswer> {
+        self.answers.get(&answer_key(project, fingerprint))
+    }
+
+    /// 记录 agent 当前等待回答的问题
+    pub fn record_question(
+        &mut self,
+        agent_id: &str,
+        project: &str,
+        fingerprint: &str,
+        question: &str,
+    ) -> Result<()> {
+        self.asked.insert(
+            agent_id.to_string(),
+            AskedQuestion {
+                project: project.to_string(),
+                fingerprint: fingerprint.to_string(),
+                question: question.to_string(),
+                asked_at: Utc::now(),
+                auto_answer_at: None,
+            },
+        );
+        self.save()
+    }
+
+    /// `auto_answer` 开启且上次回答足够稳定时，安排自动回答 agent 当前的问题，返回延迟秒数
+    ///
+    /// 权限确认和 HIGH 风险的问题必须由人回答，不安排（见 [`auto_answer_allowed`]）。
+    pub fn schedule_auto_answer(
+        &mut self,
+        agent_id: &str,
+        waiting_kind: Option<WaitingKind>,
+        risk_level: &str,
+    ) -> Result<Option<u64>> {
+        if !self.config.auto_answer || !auto_answer_allowed(waiting_kind, risk_level) {
+            return Ok(None);
+        }
+        let Some(asked) = self.asked.get(agent_id) else {
+            return Ok(None);
+        };
+        let stable = self
+            .previous_answer(&asked.project, &asked.fingerprint)
+            .is_some_and(|answer| answer.count >= self.config.auto_answer_min_count);
+        if !stable {
+            return Ok(None);
+        }
+        let delay = self.config.auto_answer_delay_secs;
+        if let Some(asked) = self.asked.get_mut(agent_id) {
+            asked.auto_answer_at = Some(asked.asked_at + chrono::Duration::seconds(delay as i64));
+        }
+        self.save()?;
+        Ok(Some(delay))
+    }
+
+    /// 取出到期的自动回答（取出后不再重复发送）
+    pub fn take_due_answers(&mut self, now: DateTime<Utc>) -> Result<Vec<DueAnswer>> {
+        let mut due = Vec::new();
+        for (agent_id, asked) in self.asked.iter_mut() {
+            let Some(at) = asked.auto_answer_at.filter(|at| *at <= now) else {
+                continue;
+            };
+            asked.auto_answer_at = None;
+            let key = answer_key(&asked.project, &asked.fingerprint);
+            if let Some(answer) = self.answers.get(&key) {
+                due.push(DueAnswer {
+                    agent_id: agent_id.clone(),
+                    question: asked.clone(),
+                    answer: answer.clone(),
+                    expired: (now - at).num_seconds() > AUTO_ANSWER_EXPIRY_SECS,
+                });
+            }
+        }
+        if !due.is_empty() {
+            self.save()?;
+        }
+        Ok(due)
+    }
+
+    /// 回复为 `r` 等快捷回复且 agent 当前问题有上次回答时，返回该回答
+    pub fn resolve_reply(&self, agent_id: &str, reply: &str) -> Option<String> {
+        let reply = reply.trim().to_lowercase();
+        if !REPEAT_REPLIES.contains(&reply.as_str()) {
+            return None;
+        }
+        let asked = self.asked.get(agent_id)?;
+        self.previous_answer(&asked.project, &asked.fingerprint)
+            .map(|answer| answer.answer.clone())
+    }
+
+    /// 记录对 agent 当前问题的回答（没有记录的问题时忽略）
+    pub fn record_reply(&mut self, agent_id: &str, reply: &str) -> Result<()> {
+        let Some(asked) = self.asked.remove(agent_id) else {
+            return Ok(());
+        };
+        let reply = reply.trim();
+        let key = answer_key(&asked.project, &asked.fingerprint);
+        let count = match self.answers.get(&key) {
+            Some(previous) if previous.answer == reply => previous.count + 1,
+            _ => 1,
+        };
+        self.answers.insert(
+            key,
+            RememberedAnswer {
+                answer: reply.to_string(),
+                label: option_label(&asked.question, reply),
+                answered_at: Utc::now(),
+                count,
+            },
+        );
+        self.save()
+    }
+}
+
+fn default_memory_path() -> PathBuf {
+    crate::infra::paths::state_dir().join("answer_memory.json")
+}
+
+/// 到期的自动回答
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueAnswer {
+    pub agent_id: String,
+    pub question: AskedQuestion,
+    pub answer: RememberedAnswer,
+    /// 到期太久（watcher 未运行），不应再发送
+    pub expired: bool,
+}
+
+/// 自动回答的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoAnswerOutcome {
+    Sent,
+    /// agent 已不在等待该问题
+    Skipped,
+    /// 被 `reply_auth` 拒绝
+    Denied,
+    Expired,
+    Failed,
+}
+
+/// 自动回答审计记录（`auto_answers.jsonl` 的一行）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoAnswerRecord {
+    pub ts: DateTime<Utc>,
+    pub agent_id: String,
+    pub project: String,
+    pub fingerprint: String,
+    pub question: String,
+    pub answer: String,
+    pub outcome: AutoAnswerOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// 自动回答：到期调度与审计日志，由 watcher 每轮调用
+#[derive(Debug, Clone)]
+pub struct AutoAnswerer {
+    memory_path: PathBuf,
+    audit_log: PathBuf,
+}
+
+impl AutoAnswerer {
+    /// 回答记忆和 `auto_answer` 都开启时创建
+    pub fn load() -> Option<Self> {
+        let config = AnswerMemoryConfig::load();
+        (config.enabled && config.auto_answer).then(|| {
+            Self::at(
+                &default_memory_path(),
+                &crate::infra::paths::state_dir().join("auto_answers.jsonl"),
+            )
+        })
+    }
+
+    pub fn at(memory_path: &Path, audit_log: &Path) -> Self {
+        Self {
+            memory_path: memory_path.to_path_buf(),
+            audit_log: audit_log.to_path_buf(),
+        }
+    }
+
+    /// 取出到期的自动回答
+    pub fn take_due(&self, now: DateTime<Utc>) -> Vec<DueAnswer> {
+        AnswerMemory::load_from(&self.memory_path)
+            .take_due_answers(now)
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to update answer memory");
+                Vec::new()
+            })
+    }
+
+    /// 写入审计日志
+    pub fn record(
+        &self,
+        due: &DueAnswer,
+        outcome: AutoAnswerOutcome,
+        detail: Option<String>,
+    ) -> Result<()> {
+        let record = AutoAnswerRecord {
+            ts: Utc::now(),
+            agent_id: due.agent_id.clone(),
+            project: due.question.project.clone(),
+            fingerprint: due.question.fingerprint.clone(),
+            question: due.question.question.clone(),
+            answer: due.answer.answer.clone(),
+            outcome,
+            detail,
+        };
+        if let Some(parent) = self.audit_log.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.audit_log)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(())
+    }
+}
+
+/// 用户回复 agent 前调用：展开 `r` 快捷回复并记录回答（未开启时原样返回）
+pub fn remember_reply(agent_id: &str, reply: &str) -> String {
+    let Some(mut memory) = AnswerMemory::load() else {
+        return reply.to_string();
+    };
+    let reply = memory
+        .resolve_reply(agent_id, reply)
+        .unwrap_or_else(|| reply.to_string());
+    if let Err(e) = memory.record_reply(agent_id, &reply) {
+        warn!(agent_id = %agent_id, error = %e, "Failed to record answer memory");
+    }
+    reply
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const QUESTION: &str = "Which CSS framework?\n1. Plain CSS\n❯ 2. Tailwind CSS\n3) Bootstrap";
+
+    #[test]
+    fn test_option_label() {
+        assert_eq!(option_label(QUESTION, "2").as_deref(), Some("Tailwind CSS"));
+        assert_eq!(option_label(QUESTION, "3").as_deref(), Some("Bootstrap"));
+        assert!(option_label(QUESTION, "4").is_none());
+        assert!(option_label(QUESTION, "y").is_none());
+    }
+
+    #[test]
+    fn test_remember_and_repeat_answer() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("answer_memory.json");
+        let mut memory = AnswerMemory::load_from(&path);
+        memory
+            .record_question("cam-1", "/proj", "css-framework", QUESTION)
+            .unwrap();
+        assert!(memory.resolve_reply("cam-1", "r").is_none());
+        memory.record_reply("cam-1", "2").unwrap();
+
+        // 同一项目再次出现相同问题
+        let mut memory = AnswerMemory::load_from(&path);
+        let previous = memory.previous_answer("/proj", "css-framework").unwrap();
+        assert_eq!(previous.display(), "2 (Tailwind CSS)");
+        assert!(memory.previous_answer("/other", "css-framework").is_none());
+
+        memory
+            .record_question("cam-2", "/proj", "css-framework", QUESTION)
+            .unwrap();
+        assert_eq!(memory.resolve_reply("cam-2", "R").as_deref(), Some("2"));
+        assert!(memory.resolve_reply("cam-2", "3").is_none());
+        memory.record_reply("cam-2", "2").unwrap();
+        assert_eq!(
+            memory
+                .previous_answer("/proj", "css-framework")
+                .unwrap()
+                .count,
+            2
+        );
+
+        // 没有等待回答的问题时不记录
+        memory.record_reply("cam-3", "1").unwrap();
+        assert_eq!(memory.answers.len(), 1);
+    }
+
+    #[test]
+    fn test_auto_answer_schedule() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("answer_memory.json");
+        let config = AnswerMemoryConfig {
+            enabled: true,
+            auto_answer: true,
+            auto_answer_min_count: 2,
+            ..Default::default()
+        };
+        let mut memory = AnswerMemory::load_from(&path).with_config(config);
+        memory
+            .record_question("cam-1", "/proj", "css", QUESTION)
+            .unwrap();
+        memory.record_reply("cam-1", "2").unwrap();
+
+        // 只回答过一次，未达到 min_count
+        memory
+            .record_question("cam-2", "/proj", "css", QUESTION)
+            .unwrap();
+        assert_eq!(
+            memory
+                .schedule_auto_answer("cam-2", None, "MEDIUM")
+                .unwrap(),
+            None
+        );
+        memory.record_reply("cam-2", "2").unwrap();
+
+        memory
+            .record_question("cam-3", "/proj", "css", QUESTION)
+            .unwrap();
+        assert_eq!(
+            memory
+                .schedule_auto_answer("cam-3", None, "MEDIUM")
+                .unwrap(),
+            Some(60)
+        );
+        assert!(memory
+            .schedule_auto_answer("cam-4", None, "MEDIUM")
+            .unwrap()
+            .is_none());
+
+        let asked_at = memory.asked["cam-3"].asked_at;
+        let auto = AutoAnswerer::at(&path, &tmp.path().join("auto_answers.jsonl"));
+        assert!(auto
+            .take_due(asked_at + chrono::Duration::seconds(30))
+            .is_empty());
+        let due = auto.take_due(asked_at + chrono::Duration::seconds(61));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].agent_id, "cam-3");
+        assert_eq!(due[0].answer.display(), "2 (Tailwind CSS)");
+        assert!(!due[0].expired);
+        // 取出后不再重复
+        assert!(auto
+            .take_due(asked_at + chrono::Duration::seconds(120))
+            .is_empty());
+
+        auto.record(&due[0], AutoAnswerOutcome::Sent, None).unwrap();
+        let log = fs::read_to_string(tmp.path().join("auto_answers.jsonl")).unwrap();
+        let record: AutoAnswerRecord = serde_json::from_str(log.trim()).unwrap();
+        assert_eq!(record.outcome, AutoAnswerOutcome::Sent);
+        assert_eq!(record.answer, "2");
+
+        // 用户先回复则取消
+        let mut memory = AnswerMemory::load_from(&path).with_config(AnswerMemoryConfig {
+            enabled: true,
+            auto_answer: true,
+            ..Default::default()
+        });
+        memory
+            .record_question("cam-5", "/proj", "css", QUESTION)
+            .unwrap();
+        memory
+            .schedule_auto_answer("cam-5", None, "MEDIUM")
+            .unwrap();
+        memory.record_reply("cam-5", "3").unwrap();
+        assert!(auto
+            .take_due(Utc::now() + chrono::Duration::seconds(61))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_permission_prompt_never_auto_answered() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("answer_memory.json");
+        let mut memory = AnswerMemory::load_from(&path).with_config(AnswerMemoryConfig {
+            enabled: true,
+            auto_answer: true,
+            ..Default::default()
+        });
+        let permission = "Do you want to proceed?\n1. Yes\n2. No";
+        memory
+            .record_question("cam-1", "/proj", "bash-rm", permission)
+            .unwrap();
+        memory.record_reply("cam-1", "1").unwrap();
+        memory
+            .record_question("cam-2", "/proj", "bash-rm", permission)
+            .unwrap();
+
+        // 回答过的权限确认再次出现：附带上次回答，但不安排自动回答
+        assert!(memory.previous_answer("/proj", "bash-rm").is_some());
+        assert_eq!(
+            memory
+                .schedule_auto_answer("cam-2", Some(WaitingKind::BlockingPermission), "MEDIUM")
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            memory
+                .schedule_auto_answer("cam-2", Some(WaitingKind::NeedsDecision), "HIGH")
+                .unwrap(),
+            None
+        );
+        let auto = AutoAnswerer::at(&path, &tmp.path().join("auto_answers.jsonl"));
+        assert!(auto
+            .take_due(Utc::now() + chrono::Duration::seconds(3600))
+            .is_empty());
+
+        assert_eq!(
+            memory
+                .schedule_auto_answer("cam-2", Some(WaitingKind::LongFormInput), "MEDIUM")
+                .unwrap(),
+            Some(60)
+        );
+    }
+}
@@ -1,6 +1,6 @@
 //! 通知系统构建器 - 自动检测并配置渠道
 
-use super::channel::NotificationMessage;
+use super::channel::{NotificationMessage, Notifier};
 use super::channels::dashboard::{DashboardChannel, DashboardConfig};
 use super::channels::local_file::LocalFileChannel;
 use super::dispatcher::NotificationDispatcher;
@@ -14,6 +14,7 @@ pub struct NotificationBuilder {
     openclaw_cmd: String,
     dry_run: bool,
     enable_dashboard: bool,
+    notifiers: Vec<Arc<dyn Notifier>>,
 }
 
 impl NotificationBuilder {
@@ -22,6 +23,7 @@ impl NotificationBuilder {
             openclaw_cmd: Self::find_openclaw_path(),
             dry_run: false,
             enable_dashboard: true,
+            notifiers: Vec::new(),
         }
     }
 
@@ -43,6 +45,12 @@ impl NotificationBuilder {
         self
     }
 
+    /// 注册自定义事件通知器（可多次调用）
+    pub fn notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
     /// 构建 NotificationDispatcher
     pub fn build(self) -> Result<NotificationDispatcher> {
         let mut dispatcher = NotificationDispatcher::new().with_dry_run(self.dry_run);
@@ -61,6 +69,10 @@ impl NotificationBuilder {
         info!(channel = "local_file", "Enabling LocalFile channel");
         dispatcher.register_channel(Arc::new(LocalFileChannel::new()));
 
+        for notifier in self.notifiers {
+            dispatcher.register_notifier(notifier);
+        }
+
         Ok(dispatcher)
     }
 
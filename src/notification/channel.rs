@@ -1,5 +1,6 @@
 //! 通知渠道 trait 定义
 
+use super::event::NotificationEvent;
 use super::urgency::Urgency;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -87,6 +88,19 @@ pub trait NotificationChannel: Send + Sync {
     fn send_async(&self, message: &NotificationMessage) -> Result<()>;
 }
 
+/// 通知器 trait - 接收统一的 `NotificationEvent`
+///
+/// 嵌入 CAM 的应用实现此 trait，通过 `NotificationBuilder::notifier` 或
+/// `WatchEngine::with_notifier` 注册后即可把事件转发到自己的系统（如公司内部聊天）。
+/// 事件未经 urgency 过滤与去重，由实现自行决定是否发送。
+pub trait Notifier: Send + Sync {
+    /// 名称（用于日志）
+    fn name(&self) -> &str;
+
+    /// 同步发送事件
+    fn send_notification_event(&self, event: &NotificationEvent) -> Result<SendResult>;
+}
+
 /// 检查 urgency 是否满足最低要求
 pub fn urgency_meets_threshold(message_urgency: Urgency, min_urgency: Urgency) -> bool {
     match (message_urgency, min_urgency) {
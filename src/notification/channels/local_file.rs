@@ -4,6 +4,7 @@ use anyhow::Result;
 use chrono::Utc;
 use tracing::{debug, warn};
 
+use crate::infra::text::{display_width, take_width};
 use crate::notification::channel::{NotificationChannel, NotificationMessage, SendResult};
 use crate::notification::store::{NotificationRecord, NotificationStore};
 
@@ -87,6 +88,7 @@ fn build_record(message: &NotificationMessage) -> NotificationRecord {
 
     NotificationRecord {
         ts: Utc::now(),
+        id: None,
         agent_id: message
             .agent_id
             .clone()
@@ -98,15 +100,17 @@ fn build_record(message: &NotificationMessage) -> NotificationRecord {
         event_detail,
         terminal_snapshot,
         risk_level,
+        message_id: None,
+        reply_to: None,
     }
 }
 
-/// 截断摘要到指定长度
+/// 截断摘要到指定显示宽度
 fn truncate_summary(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
+    if display_width(s) <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+        format!("{}...", take_width(s, max_len.saturating_sub(3)))
     }
 }
 
@@ -126,6 +130,7 @@ mod tests {
     fn test_truncate_summary() {
         assert_eq!(truncate_summary("short", 10), "short");
         assert_eq!(truncate_summary("this is a long message", 10), "this is...");
+        assert_eq!(truncate_summary("等待用户确认数据库迁移", 10), "等待用...");
     }
 
     #[test]
@@ -9,9 +9,29 @@
 //! (like "Brewing", "Thinking", spinners, etc.) because CAM must be compatible
 //! with multiple AI coding tools (Claude Code, Codex, OpenCode, etc.).
 //! Noise filtering is handled by the AI extraction layer in src/anthropic.rs.
+//!
+//! Waiting-for-input notifications use [`question_dedup_key`] on every path:
+//! the hook path (`cam notify`, keyed in `OpenclawNotifier`) and the watcher path
+//! (`AgentWatcher`) capture the screen at different moments and with different
+//! line counts, so the key only covers the question region at the bottom of the
+//! screen, with generic volatile tokens (elapsed times, percentages, token counts)
+//! masked. Hashes are FNV-1a so keys stay identical across cam builds (the hook
+//! binary and the daemon may be different versions during an upgrade).
+
+use std::sync::LazyLock;
+
+use regex::Regex;
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+/// Number of trailing content lines that make up the question fingerprint
+pub const QUESTION_LINES: usize = 15;
+
+/// Elapsed times (`12s`, `1m 5s`, `3.2s`), percentages and token counts
+static VOLATILE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:\d+h\s*)?(?:\d+m\s*)?\d+(?:\.\d+)?(?:ms|s)\b|\d+(?:\.\d+)?%|\d+(?:\.\d+)?k?\s*tokens\b",
+    )
+    .expect("Invalid volatile regex")
+});
 
 /// Generate a deterministic dedup key from terminal snapshot
 ///
@@ -25,6 +45,33 @@ pub fn generate_dedup_key(terminal_snapshot: &str) -> String {
     format!("{:016x}", hash)
 }
 
+/// Fingerprint of the question at the bottom of a terminal snapshot
+///
+/// Normalizes the snapshot, drops pure border/separator lines, masks volatile
+/// tokens and keeps the last [`QUESTION_LINES`] content lines. Still tool-agnostic:
+/// nothing here knows about a specific AI tool's UI.
+pub fn question_fingerprint(terminal_snapshot: &str) -> String {
+    let normalized = normalize_terminal_content(terminal_snapshot);
+    let lines: Vec<String> = normalized
+        .lines()
+        .map(|line| VOLATILE_RE.replace_all(line.trim(), "#").into_owned())
+        .filter(|line| line.chars().any(char::is_alphanumeric))
+        .collect();
+    lines[lines.len().saturating_sub(QUESTION_LINES)..].join("\n")
+}
+
+/// Dedup key for a waiting-for-input notification: `<agent_id>:<question hash>`
+///
+/// Both `cam notify` (hook) and `AgentWatcher` use this, so the same prompt
+/// produces the same key no matter which path detected it first.
+pub fn question_dedup_key(agent_id: &str, terminal_snapshot: &str) -> String {
+    format!(
+        "{}:{:016x}",
+        agent_id,
+        hash_content(&question_fingerprint(terminal_snapshot))
+    )
+}
+
 /// Normalize terminal content by removing tool-agnostic noise
 ///
 /// Steps:
@@ -138,11 +185,11 @@ pub fn strip_timestamps(s: &str) -> String {
     result
 }
 
-/// Hash content using DefaultHasher
+/// Hash content using 64-bit FNV-1a (stable across builds and platforms)
 pub fn hash_content(content: &str) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    content.hash(&mut hasher);
-    hasher.finish()
+    content.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 #[cfg(test)]
@@ -281,6 +328,56 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_hash_content_stable() {
+        // FNV-1a reference values
+        assert_eq!(hash_content(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash_content("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    // ==================== question_dedup_key tests ====================
+
+    const QUESTION: &str =
+        "Do you want to make this edit to main.rs?\n❯ 1. Yes\n  2. Yes, allow all edits\n  3. No";
+
+    #[test]
+    fn test_question_key_ignores_scrollback_and_volatile_tokens() {
+        // Hook 路径取 50 行日志，watcher 只有截断后的屏幕，状态栏计时不同
+        let scrollback: String = (0..40).map(|i| format!("output line {}\n", i)).collect();
+        let hook_snapshot = format!(
+            "{}\x1b[2m✻ Worked for 12s · 3.2k tokens\x1b[0m\n╭──────╮\n{}\n╰──────╯\n",
+            scrollback, QUESTION
+        );
+        let recent: String = (20..40).map(|i| format!("output line {}\n", i)).collect();
+        let watcher_context = format!(
+            "{}✻ Worked for 1m 4s · 3.4k tokens\n\n{}\n──────\n",
+            recent, QUESTION
+        );
+        assert_eq!(
+            question_dedup_key("cam-1", &hook_snapshot),
+            question_dedup_key("cam-1", &watcher_context)
+        );
+        assert!(question_dedup_key("cam-1", QUESTION).starts_with("cam-1:"));
+    }
+
+    #[test]
+    fn test_question_key_distinguishes_questions_and_agents() {
+        let other = QUESTION.replace("main.rs", "lib.rs");
+        assert_ne!(
+            question_dedup_key("cam-1", QUESTION),
+            question_dedup_key("cam-1", &other)
+        );
+        assert_ne!(
+            question_dedup_key("cam-1", QUESTION),
+            question_dedup_key("cam-2", QUESTION)
+        );
+        // 选项编号不属于易变内容
+        assert_ne!(
+            question_fingerprint("Delete 3 files?"),
+            question_fingerprint("Delete 4 files?")
+        );
+    }
+
     // ==================== Integration tests ====================
 
     #[test]
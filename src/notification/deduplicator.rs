@@ -11,6 +11,7 @@
 //! 去重状态持久化到 `~/.config/code-agent-monitor/dedup_state.json`，
 //! 使用 fs2 文件锁确保跨进程并发安全。
 
+use crate::infra::schema::DEDUP_STATE_SCHEMA;
 use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -106,7 +107,7 @@ impl NotificationDeduplicator {
 
     /// 获取状态文件路径
     fn state_file_path() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".config/code-agent-monitor/dedup_state.json"))
+        Some(crate::infra::paths::state_dir().join("dedup_state.json"))
     }
 
     /// 获取实例的状态文件路径（支持自定义路径用于测试）
@@ -141,7 +142,7 @@ impl NotificationDeduplicator {
 
                 let mut content = String::new();
                 if file.read_to_string(&mut content).is_ok() {
-                    if let Ok(state) = serde_json::from_str::<DedupState>(&content) {
+                    if let Ok(state) = DEDUP_STATE_SCHEMA.load_versioned::<DedupState>(&content) {
                         self.locks = state.locks;
                         debug!(records = self.locks.len(), "Loaded dedup state from disk");
                     }
@@ -185,7 +186,10 @@ impl NotificationDeduplicator {
                     return;
                 }
 
-                if let Ok(content) = serde_json::to_string(&state) {
+                if let Ok(content) = DEDUP_STATE_SCHEMA
+                    .to_versioned_value(&state)
+                    .and_then(|value| Ok(serde_json::to_string(&value)?))
+                {
                     let _ = file.write_all(content.as_bytes());
                 }
 
@@ -227,10 +231,14 @@ impl NotificationDeduplicator {
     /// IMPORTANT: Reloads state from disk before checking to enable cross-process deduplication.
     /// Multiple cam processes (watcher, hook) share state via the persisted file.
     pub fn should_send(&mut self, agent_id: &str, content: &str) -> NotifyAction {
+        self.should_send_at(agent_id, content, Self::current_timestamp())
+    }
+
+    /// 以指定时间（Unix 秒）检查是否应该发送通知，用于离线重放
+    pub fn should_send_at(&mut self, agent_id: &str, content: &str, now: u64) -> NotifyAction {
         // Reload state from disk to see updates from other processes
         self.load_state();
 
-        let now = Self::current_timestamp();
         let fingerprint = Self::content_fingerprint(content);
 
         // 先检查当前 agent 是否超过最大时限（在清理之前）
@@ -1,16 +1,39 @@
 //! 通知分发器 - 管理多个渠道并路由消息
 
-use super::channel::{NotificationChannel, NotificationMessage, SendResult};
+use super::channel::{NotificationChannel, NotificationMessage, Notifier, SendResult};
+use super::event::NotificationEvent;
 use anyhow::Result;
+use std::sync::mpsc;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, warn};
 
+/// 故障转移链中单个渠道的默认确认超时
+const DEFAULT_FAILOVER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 故障转移投递结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverDelivery {
+    /// 成功送达的渠道
+    pub channel: String,
+    /// 在链中的位置（0 为首选渠道）
+    pub hop: usize,
+    /// 之前失败或超时的渠道及原因
+    pub failed: Vec<(String, String)>,
+}
+
 /// 通知分发器 - 管理多个渠道并路由消息
 pub struct NotificationDispatcher {
     /// 所有注册的渠道
     channels: Vec<Arc<dyn NotificationChannel>>,
+    /// 注册的事件通知器（自定义集成）
+    notifiers: Vec<Arc<dyn Notifier>>,
     /// 是否为 dry-run 模式
     dry_run: bool,
+    /// 故障转移链（按顺序尝试的渠道名）
+    failover: Vec<String>,
+    /// 每个渠道的确认超时
+    failover_timeout: Duration,
 }
 
 impl NotificationDispatcher {
@@ -18,7 +41,10 @@ impl NotificationDispatcher {
     pub fn new() -> Self {
         Self {
             channels: Vec::new(),
+            notifiers: Vec::new(),
             dry_run: false,
+            failover: Vec::new(),
+            failover_timeout: DEFAULT_FAILOVER_TIMEOUT,
         }
     }
 
@@ -28,12 +54,52 @@ impl NotificationDispatcher {
         self
     }
 
+    /// 设置故障转移链：按顺序尝试这些渠道，一个渠道在 `timeout` 内没有确认送达就换下一个
+    pub fn with_failover_chain(mut self, channels: Vec<String>, timeout: Duration) -> Self {
+        self.failover = channels;
+        self.failover_timeout = timeout;
+        self
+    }
+
     /// 注册渠道
     pub fn register_channel(&mut self, channel: Arc<dyn NotificationChannel>) {
         info!(channel = channel.name(), "Registering notification channel");
         self.channels.push(channel);
     }
 
+    /// 注册事件通知器
+    pub fn register_notifier(&mut self, notifier: Arc<dyn Notifier>) {
+        info!(notifier = notifier.name(), "Registering notifier");
+        self.notifiers.push(notifier);
+    }
+
+    /// 同步发送事件到所有通知器
+    pub fn send_event(&self, event: &NotificationEvent) -> Result<Vec<(String, SendResult)>> {
+        let mut results = Vec::new();
+
+        for notifier in &self.notifiers {
+            let name = notifier.name().to_string();
+
+            if self.dry_run {
+                eprintln!("[DRY-RUN] Would send event to notifier: {}", name);
+                results.push((name, SendResult::Skipped("dry-run".to_string())));
+                continue;
+            }
+
+            let result = match notifier.send_notification_event(event) {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!(notifier = %name, error = %e, "Notifier send failed");
+                    SendResult::Failed(e.to_string())
+                }
+            };
+
+            results.push((name, result));
+        }
+
+        Ok(results)
+    }
+
     /// 同步发送消息到所有渠道
     pub fn send_sync(&self, message: &NotificationMessage) -> Result<Vec<(String, SendResult)>> {
         let mut results = Vec::new();
@@ -77,6 +143,81 @@ impl NotificationDispatcher {
         Ok(())
     }
 
+    /// 按故障转移链投递，返回第一个确认送达的渠道
+    ///
+    /// 每个渠道在独立线程中同步发送，等待结果不超过配置的超时；
+    /// 超时的发送线程不会被取消，但其结果会被忽略。
+    /// 未配置故障转移链时依次尝试所有已注册渠道。
+    pub fn send_failover(&self, message: &NotificationMessage) -> Result<FailoverDelivery> {
+        let chain: Vec<&Arc<dyn NotificationChannel>> = if self.failover.is_empty() {
+            self.channels.iter().collect()
+        } else {
+            self.failover
+                .iter()
+                .filter_map(|name| {
+                    let channel = self.channels.iter().find(|c| c.name() == name);
+                    if channel.is_none() {
+                        warn!(channel = %name, "Failover channel not registered");
+                    }
+                    channel
+                })
+                .collect()
+        };
+
+        let mut failed = Vec::new();
+        for (hop, channel) in chain.into_iter().enumerate() {
+            let name = channel.name().to_string();
+
+            if self.dry_run {
+                eprintln!("[DRY-RUN] Would send to failover channel: {}", name);
+                return Ok(FailoverDelivery {
+                    channel: name,
+                    hop,
+                    failed,
+                });
+            }
+            if !channel.should_send(message) {
+                failed.push((name, "filtered".to_string()));
+                continue;
+            }
+
+            let (tx, rx) = mpsc::channel();
+            let sender = Arc::clone(channel);
+            let msg = message.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(sender.send(&msg));
+            });
+
+            let reason = match rx.recv_timeout(self.failover_timeout) {
+                Ok(Ok(SendResult::Sent)) => {
+                    if hop > 0 {
+                        info!(channel = %name, hop, "Delivered via failover channel");
+                    }
+                    return Ok(FailoverDelivery {
+                        channel: name,
+                        hop,
+                        failed,
+                    });
+                }
+                Ok(Ok(SendResult::Skipped(reason))) => reason,
+                Ok(Ok(SendResult::Failed(reason))) => reason,
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => format!("timed out after {:?}", self.failover_timeout),
+            };
+            warn!(channel = %name, hop, reason = %reason, "Failover channel did not deliver");
+            failed.push((name, reason));
+        }
+
+        anyhow::bail!(
+            "all failover channels failed: {}",
+            failed
+                .iter()
+                .map(|(name, reason)| format!("{} ({})", name, reason))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
     /// 获取已注册的渠道数量
     pub fn channel_count(&self) -> usize {
         self.channels.len()
@@ -86,6 +227,30 @@ impl NotificationDispatcher {
     pub fn channel_names(&self) -> Vec<&str> {
         self.channels.iter().map(|c| c.name()).collect()
     }
+
+    /// 获取已注册的通知器名称
+    pub fn notifier_names(&self) -> Vec<&str> {
+        self.notifiers.iter().map(|n| n.name()).collect()
+    }
+}
+
+/// 分发器本身也是通知器：任一通知器发送成功即为成功
+impl Notifier for NotificationDispatcher {
+    fn name(&self) -> &str {
+        "dispatcher"
+    }
+
+    fn send_notification_event(&self, event: &NotificationEvent) -> Result<SendResult> {
+        let results = self.send_event(event)?;
+        if results.iter().any(|(_, r)| *r == SendResult::Sent) {
+            return Ok(SendResult::Sent);
+        }
+        Ok(results
+            .into_iter()
+            .map(|(_, r)| r)
+            .find(|r| matches!(r, SendResult::Failed(_)))
+            .unwrap_or_else(|| SendResult::Skipped("no notifier sent".to_string())))
+    }
 }
 
 impl Default for NotificationDispatcher {
@@ -177,6 +342,117 @@ mod tests {
         assert_eq!(channel.get_send_count(), 0); // 不应该实际发送
     }
 
+    /// 测试用的自定义通知器，记录收到的 agent_id
+    struct RecordingNotifier {
+        received: std::sync::Mutex<Vec<String>>,
+        fail: bool,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            if self.fail {
+                "failing"
+            } else {
+                "chat"
+            }
+        }
+
+        fn send_notification_event(&self, event: &NotificationEvent) -> Result<SendResult> {
+            if self.fail {
+                anyhow::bail!("chat unavailable");
+            }
+            self.received.lock().unwrap().push(event.agent_id.clone());
+            Ok(SendResult::Sent)
+        }
+    }
+
+    #[test]
+    fn test_dispatcher_custom_notifier() {
+        let chat = Arc::new(RecordingNotifier {
+            received: Default::default(),
+            fail: false,
+        });
+        let failing = Arc::new(RecordingNotifier {
+            received: Default::default(),
+            fail: true,
+        });
+        let dispatcher = crate::notification::NotificationBuilder::new()
+            .enable_dashboard(false)
+            .notifier(failing)
+            .notifier(chat.clone())
+            .build()
+            .unwrap();
+        assert_eq!(dispatcher.notifier_names(), vec!["failing", "chat"]);
+
+        let event = NotificationEvent::agent_exited("cam-1");
+        let results = dispatcher.send_event(&event).unwrap();
+        assert!(matches!(results[0].1, SendResult::Failed(_)));
+        assert_eq!(results[1].1, SendResult::Sent);
+        assert_eq!(*chat.received.lock().unwrap(), ["cam-1"]);
+
+        // 作为 trait object 使用时任一成功即为成功
+        let notifier: &dyn Notifier = &dispatcher;
+        assert_eq!(
+            notifier.send_notification_event(&event).unwrap(),
+            SendResult::Sent
+        );
+    }
+
+    /// 发送前先等待的 mock 渠道
+    struct SlowChannel(Duration);
+
+    impl NotificationChannel for SlowChannel {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn should_send(&self, _message: &NotificationMessage) -> bool {
+            true
+        }
+
+        fn send(&self, _message: &NotificationMessage) -> Result<SendResult> {
+            std::thread::sleep(self.0);
+            Ok(SendResult::Sent)
+        }
+
+        fn send_async(&self, _message: &NotificationMessage) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dispatcher_failover_chain() {
+        let backup = Arc::new(MockChannel::new("ntfy"));
+        let mut dispatcher = NotificationDispatcher::new().with_failover_chain(
+            vec![
+                "telegram".to_string(),
+                "slow".to_string(),
+                "missing".to_string(),
+                "ntfy".to_string(),
+            ],
+            Duration::from_millis(50),
+        );
+        dispatcher.register_channel(backup.clone());
+        dispatcher.register_channel(Arc::new(SlowChannel(Duration::from_secs(1))));
+        dispatcher.register_channel(Arc::new(FailingMockChannel::new("telegram", 5)));
+
+        let message = NotificationMessage::new("test", Urgency::High);
+        let delivery = dispatcher.send_failover(&message).unwrap();
+        assert_eq!(delivery.channel, "ntfy");
+        assert_eq!(delivery.hop, 2);
+        assert_eq!(delivery.failed[0].0, "telegram");
+        assert_eq!(
+            delivery.failed[1],
+            ("slow".to_string(), "timed out after 50ms".to_string())
+        );
+        assert_eq!(backup.get_send_count(), 1);
+
+        // 全部失败时返回错误
+        let dispatcher = NotificationDispatcher::new()
+            .with_failover_chain(vec!["telegram".to_string()], Duration::from_millis(50));
+        assert!(dispatcher.send_failover(&message).is_err());
+    }
+
     // =========================================================================
     // TDD Tests for Retry Logic with Exponential Backoff
     // =========================================================================
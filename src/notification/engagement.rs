@@ -0,0 +1,368 @@
+//! 通知响应统计 - 关联回复/确认与触发它们的通知，按渠道统计响应时间和未响应率
+//!
+//! 每次回复或 `cam ack` 成功时追加一条 [`ResponseRecord`] 到
+//! `~/.config/code-agent-monitor/responses.jsonl`。统计时把需要回复的通知（延迟样本中的
+//! `WaitingForInput` 和 `permission_request`，见 [`crate::notification::latency`]）与同一 agent
+//! 之后的第一次响应关联：响应必须早于该 agent 的下一条通知，否则这条通知记为未响应。
+//! 最后一条通知在确认过期（1 小时）前没有响应时记为等待中，不计入未响应率。
+//!
+//! `cam stats --notifications` 按渠道显示，可用 `--between 22-8` 只看某个时段发出的通知。
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::notification::latency::{LatencySample, LatencyStats};
+
+/// 保留的最多响应记录数
+pub const MAX_RECORDS: usize = 5000;
+
+/// 需要用户回复的通知事件
+pub const REPLYABLE_EVENTS: &[&str] = &["WaitingForInput", "permission_request"];
+
+/// 没有响应的通知在多久后记为未响应（与待处理确认的过期时间一致）
+const RESPONSE_WINDOW_MINUTES: i64 = 60;
+
+/// 响应类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseKind {
+    /// 回复了确认
+    Reply,
+    /// `cam ack` 确认已看到
+    Ack,
+}
+
+/// 一次响应
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseRecord {
+    pub ts: DateTime<Utc>,
+    pub agent_id: String,
+    pub kind: ResponseKind,
+    /// 回复者（如 `tg:123456`），本机终端为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+}
+
+impl ResponseRecord {
+    pub fn new(agent_id: &str, kind: ResponseKind, sender: Option<&str>) -> Self {
+        Self {
+            ts: Utc::now(),
+            agent_id: agent_id.to_string(),
+            kind,
+            sender: sender.map(str::to_string),
+        }
+    }
+}
+
+/// 响应记录文件
+#[derive(Debug, Clone)]
+pub struct ResponseLog {
+    path: PathBuf,
+}
+
+impl ResponseLog {
+    /// 默认路径
+    pub fn new() -> Self {
+        Self::in_dir(&crate::infra::paths::state_dir())
+    }
+
+    /// 状态目录中的记录文件
+    pub fn in_dir(dir: &Path) -> Self {
+        Self::at(&dir.join("responses.jsonl"))
+    }
+
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// 读取全部记录（按时间顺序），跳过无法解析的行
+    pub fn load(&self) -> Vec<ResponseRecord> {
+        fs::read_to_string(&self.path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 追加一条记录，超过 [`MAX_RECORDS`] 时丢弃最旧的
+    pub fn append(&self, record: &ResponseRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut records = self.load();
+        if records.len() >= MAX_RECORDS {
+            records.push(record.clone());
+            let content: Vec<String> = records[records.len() - MAX_RECORDS..]
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<serde_json::Result<_>>()?;
+            fs::write(&self.path, content.join("\n") + "\n")?;
+        } else {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            writeln!(file, "{}", serde_json::to_string(record)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for ResponseLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单个渠道的响应统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChannelEngagement {
+    /// 需要回复的通知数
+    pub notified: usize,
+    /// 收到回复的通知数
+    pub replied: usize,
+    /// 只被 ack 的通知数
+    pub acked: usize,
+    /// 未响应的通知数
+    pub unanswered: usize,
+    /// 仍在等待响应的通知数（不计入未响应率）
+    pub open: usize,
+    /// 从通知送达到第一次响应的耗时
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time: Option<LatencyStats>,
+}
+
+impl ChannelEngagement {
+    /// 未响应率（0-1），没有已结束的通知时为 None
+    pub fn unanswered_rate(&self) -> Option<f64> {
+        let closed = self.notified - self.open;
+        (closed > 0).then(|| self.unanswered as f64 / closed as f64)
+    }
+}
+
+/// 本地时段（小时，左闭右开，可跨午夜，如 22-8）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HourRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl HourRange {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+impl std::str::FromStr for HourRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("时段格式应为 START-END（如 22-8）: {}", s))?;
+        let parse = |hour: &str| {
+            hour.trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|h| *h <= 24)
+                .map(|h| h % 24)
+                .ok_or_else(|| format!("无效的小时: {}", hour))
+        };
+        let range = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start == range.end {
+            return Err(format!("时段不能为空: {}", s));
+        }
+        Ok(range)
+    }
+}
+
+/// 按渠道关联通知和响应
+///
+/// `hours` 只统计在该本地时段送达的通知。
+pub fn compute_engagement(
+    samples: &[LatencySample],
+    responses: &[ResponseRecord],
+    hours: Option<HourRange>,
+    now: DateTime<Utc>,
+) -> BTreeMap<String, ChannelEngagement> {
+    let mut notifications: Vec<&LatencySample> = samples
+        .iter()
+        .filter(|s| REPLYABLE_EVENTS.contains(&s.event.as_str()))
+        .collect();
+    notifications.sort_by_key(|s| s.ts);
+
+    let mut by_channel: BTreeMap<String, (ChannelEngagement, Vec<u64>)> = BTreeMap::new();
+    for (index, sample) in notifications.iter().enumerate() {
+        if hours.is_some_and(|range| !range.contains(sample.ts.with_timezone(&Local).hour())) {
+            continue;
+        }
+        // 响应窗口：到该 agent 的下一条通知为止
+        let next = notifications[index + 1..]
+            .iter()
+            .find(|s| s.agent_id == sample.agent_id)
+            .map(|s| s.ts);
+        let in_window = |ts: DateTime<Utc>| ts >= sample.ts && next.is_none_or(|next| ts < next);
+        let first_reply = responses
+            .iter()
+            .filter(|r| r.agent_id == sample.agent_id && r.kind == ResponseKind::Reply)
+            .find(|r| in_window(r.ts));
+        let first_response = responses
+            .iter()
+            .filter(|r| r.agent_id == sample.agent_id)
+            .find(|r| in_window(r.ts));
+
+        let (stats, response_times) = by_channel.entry(sample.channel.clone()).or_default();
+        stats.notified += 1;
+        match first_response {
+            Some(response) => {
+                if first_reply.is_some() {
+                    stats.replied += 1;
+                } else {
+                    stats.acked += 1;
+                }
+                response_times.push((response.ts - sample.ts).num_milliseconds().max(0) as u64);
+            }
+            None if next.is_none()
+                && now - sample.ts < Duration::minutes(RESPONSE_WINDOW_MINUTES) =>
+            {
+                stats.open += 1
+            }
+            None => stats.unanswered += 1,
+        }
+    }
+
+    by_channel
+        .into_iter()
+        .map(|(channel, (mut stats, response_times))| {
+            stats.response_time = LatencyStats::compute(response_times);
+            (channel, stats)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::minutes(minutes)
+    }
+
+    fn notified(minutes: i64, agent_id: &str, channel: &str, event: &str) -> LatencySample {
+        LatencySample {
+            ts: at(minutes),
+            agent_id: agent_id.to_string(),
+            event: event.to_string(),
+            channel: channel.to_string(),
+            latency_ms: 500,
+        }
+    }
+
+    fn response(minutes: i64, agent_id: &str, kind: ResponseKind) -> ResponseRecord {
+        ResponseRecord {
+            ts: at(minutes),
+            agent_id: agent_id.to_string(),
+            kind,
+            sender: None,
+        }
+    }
+
+    #[test]
+    fn test_compute_engagement_per_channel() {
+        let samples = vec![
+            notified(0, "cam-1", "telegram", "WaitingForInput"),
+            // 不需要回复的事件不统计
+            notified(1, "cam-1", "telegram", "AgentExited"),
+            // cam-1 第一条通知没有回复就被下一条取代
+            notified(10, "cam-1", "telegram", "permission_request"),
+            notified(12, "cam-2", "whatsapp", "WaitingForInput"),
+            notified(30, "cam-2", "whatsapp", "WaitingForInput"),
+            notified(100, "cam-3", "whatsapp", "WaitingForInput"),
+            notified(200, "cam-1", "telegram", "WaitingForInput"),
+        ];
+        let responses = vec![
+            response(15, "cam-1", ResponseKind::Reply),
+            // 先 ack 后回复，响应时间按 ack 计算，仍算作回复
+            response(13, "cam-2", ResponseKind::Ack),
+            response(14, "cam-2", ResponseKind::Reply),
+            response(31, "cam-2", ResponseKind::Ack),
+        ];
+
+        let stats = compute_engagement(&samples, &responses, None, at(220));
+        let telegram = &stats["telegram"];
+        assert_eq!(
+            (
+                telegram.notified,
+                telegram.replied,
+                telegram.unanswered,
+                telegram.open
+            ),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(telegram.response_time.as_ref().unwrap().p50_ms, 300_000);
+        assert_eq!(telegram.unanswered_rate(), Some(0.5));
+
+        let whatsapp = &stats["whatsapp"];
+        assert_eq!(
+            (
+                whatsapp.notified,
+                whatsapp.replied,
+                whatsapp.acked,
+                whatsapp.unanswered
+            ),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(whatsapp.response_time.as_ref().unwrap().max_ms, 60_000);
+    }
+
+    #[test]
+    fn test_hour_range() {
+        let night: HourRange = "22-8".parse().unwrap();
+        assert!(night.contains(23) && night.contains(0) && night.contains(7));
+        assert!(!night.contains(8) && !night.contains(12));
+        let day: HourRange = "9-18".parse().unwrap();
+        assert!(day.contains(9) && !day.contains(18));
+        assert!("9".parse::<HourRange>().is_err());
+        assert!("8-8".parse::<HourRange>().is_err());
+        assert!("25-3".parse::<HourRange>().is_err());
+    }
+
+    #[test]
+    fn test_response_log_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let log = ResponseLog::in_dir(temp.path());
+        log.append(&ResponseRecord::new(
+            "cam-1",
+            ResponseKind::Reply,
+            Some("tg:1"),
+        ))
+        .unwrap();
+        log.append(&ResponseRecord::new("cam-1", ResponseKind::Ack, None))
+            .unwrap();
+        let records = log.load();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].sender.as_deref(), Some("tg:1"));
+        assert_eq!(records[1].kind, ResponseKind::Ack);
+    }
+}
@@ -0,0 +1,384 @@
+//! 重复错误自动提交 issue
+//!
+//! 同一 agent 的同类错误（[`ErrorCategory`]，不含 Other）在 `window_mins` 分钟内出现超过
+//! `threshold` 次时，用 `gh issue create` 提交 GitHub issue（或追加到本地 errors.md），
+//! 附带错误摘录、终端快照和会话信息，并只发送一条带 issue 链接的通知；
+//! 之后同一窗口内的同类错误不再单独通知。
+//!
+//! config.json 中 `"error_filing": {"enabled": true}` 开启（默认关闭），
+//! `"target": "local"` 只写本地文件。状态保存在 `~/.config/code-agent-monitor/error_filing.json`。
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::infra::text::{take_width, take_width_end};
+use crate::notification::summarizer::ErrorCategory;
+
+/// issue 中保留的终端快照行数
+const SNAPSHOT_LINES: usize = 40;
+/// issue 标题中错误摘录的最大宽度
+const TITLE_WIDTH: usize = 80;
+/// issue 正文中错误摘录的最大宽度
+const EXCERPT_WIDTH: usize = 2000;
+
+/// 提交目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilingTarget {
+    /// `gh issue create`，失败时写本地文件
+    #[default]
+    Github,
+    /// 追加到 `~/.config/code-agent-monitor/errors.md`
+    Local,
+}
+
+/// 配置（config.json 的 `error_filing`）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ErrorFilingConfig {
+    pub enabled: bool,
+    /// 窗口内超过此次数时提交
+    pub threshold: usize,
+    pub window_mins: i64,
+    pub target: FilingTarget,
+    /// `owner/repo`，未设置时由 gh 按项目目录的 git remote 推断
+    pub repo: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl Default for ErrorFilingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 3,
+            window_mins: 60,
+            target: FilingTarget::Github,
+            repo: None,
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl ErrorFilingConfig {
+    /// 读取 config.json，缺失或无效时使用默认值（关闭）
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("error_filing").unwrap_or_default()
+    }
+
+    fn window(&self) -> Duration {
+        Duration::minutes(self.window_mins.max(1))
+    }
+}
+
+/// 一次错误的处理方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilingDecision {
+    /// 未达到阈值，照常通知
+    Notify,
+    /// 刚超过阈值，提交 issue（窗口内出现的次数）
+    File { count: usize },
+    /// 窗口内已提交过，不再通知
+    Filed { url: String },
+}
+
+/// 已提交的 issue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FiledIssue {
+    pub url: String,
+    pub filed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ErrorEntry {
+    occurrences: Vec<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filed: Option<FiledIssue>,
+}
+
+/// 各 (agent, 错误类别) 的出现记录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorFilingState {
+    #[serde(default)]
+    entries: BTreeMap<String, ErrorEntry>,
+}
+
+fn entry_key(agent_id: &str, category: ErrorCategory) -> String {
+    format!("{}/{}", agent_id, category.as_str())
+}
+
+impl ErrorFilingState {
+    /// 默认路径
+    pub fn path() -> PathBuf {
+        crate::infra::paths::state_dir().join("error_filing.json")
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 记录一次错误并决定如何处理
+    pub fn record(
+        &mut self,
+        agent_id: &str,
+        category: ErrorCategory,
+        config: &ErrorFilingConfig,
+        now: DateTime<Utc>,
+    ) -> FilingDecision {
+        let cutoff = now - config.window();
+        // 清理窗口外且没有有效 issue 的记录
+        self.entries.retain(|_, entry| {
+            entry.occurrences.retain(|at| *at > cutoff);
+            if entry.filed.as_ref().is_some_and(|f| f.filed_at <= cutoff) {
+                entry.filed = None;
+            }
+            !entry.occurrences.is_empty() || entry.filed.is_some()
+        });
+
+        let entry = self
+            .entries
+            .entry(entry_key(agent_id, category))
+            .or_default();
+        entry.occurrences.push(now);
+        if let Some(ref filed) = entry.filed {
+            return FilingDecision::Filed {
+                url: filed.url.clone(),
+            };
+        }
+        if entry.occurrences.len() > config.threshold {
+            FilingDecision::File {
+                count: entry.occurrences.len(),
+            }
+        } else {
+            FilingDecision::Notify
+        }
+    }
+
+    /// 记录已提交的 issue，窗口内同类错误不再通知
+    pub fn mark_filed(
+        &mut self,
+        agent_id: &str,
+        category: ErrorCategory,
+        url: &str,
+        now: DateTime<Utc>,
+    ) {
+        self.entries
+            .entry(entry_key(agent_id, category))
+            .or_default()
+            .filed = Some(FiledIssue {
+            url: url.to_string(),
+            filed_at: now,
+        });
+    }
+}
+
+/// issue 内容
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    pub agent_id: String,
+    pub category: ErrorCategory,
+    pub message: String,
+    pub count: usize,
+    pub window_mins: i64,
+    pub project_path: Option<String>,
+    pub terminal_snapshot: Option<String>,
+    pub session_id: Option<String>,
+    pub jsonl_path: Option<String>,
+}
+
+impl ErrorReport {
+    pub fn title(&self) -> String {
+        let first_line = self.message.lines().next().unwrap_or_default().trim();
+        format!(
+            "[cam] {}: {}",
+            self.category.label(),
+            take_width(first_line, TITLE_WIDTH)
+        )
+    }
+
+    /// Markdown 正文
+    pub fn body(&self) -> String {
+        let mut out = format!(
+            "Agent `{}` 在 {} 分钟内出现 {} 次{}。\n\n",
+            self.agent_id,
+            self.window_mins,
+            self.count,
+            self.category.label()
+        );
+        if let Some(ref project) = self.project_path {
+            out.push_str(&format!("- 项目: `{}`\n", project));
+        }
+        out.push_str(&format!("- 会话: `cam attach {}`\n", self.agent_id));
+        if let Some(ref session_id) = self.session_id {
+            out.push_str(&format!("- 会话 ID: `{}`\n", session_id));
+        }
+        if let Some(ref jsonl_path) = self.jsonl_path {
+            out.push_str(&format!("- Transcript: `{}`\n", jsonl_path));
+        }
+        out.push_str(&format!(
+            "\n### 错误\n\n```\n{}\n```\n",
+            take_width(self.message.trim(), EXCERPT_WIDTH)
+        ));
+        if let Some(ref snapshot) = self.terminal_snapshot {
+            let lines: Vec<&str> = snapshot.lines().collect();
+            let start = lines.len().saturating_sub(SNAPSHOT_LINES);
+            let tail = lines[start..].join("\n");
+            out.push_str(&format!(
+                "\n### 终端快照\n\n```\n{}\n```\n",
+                take_width_end(tail.trim_end(), EXCERPT_WIDTH * 2)
+            ));
+        }
+        out
+    }
+}
+
+/// 提交 issue，返回链接（本地文件为 `file://` 路径）
+pub fn file_report(config: &ErrorFilingConfig, report: &ErrorReport) -> Result<String> {
+    if config.target == FilingTarget::Github {
+        match create_github_issue(config, report) {
+            Ok(url) => return Ok(url),
+            Err(e) => warn!(error = %e, "gh issue create failed, writing errors.md instead"),
+        }
+    }
+    append_local(&crate::infra::paths::state_dir().join("errors.md"), report)
+}
+
+fn create_github_issue(config: &ErrorFilingConfig, report: &ErrorReport) -> Result<String> {
+    let mut command = Command::new("gh");
+    command.args(["issue", "create", "--title"]);
+    command.arg(report.title()).arg("--body").arg(report.body());
+    if let Some(ref repo) = config.repo {
+        command.args(["--repo", repo]);
+    }
+    for label in &config.labels {
+        command.args(["--label", label]);
+    }
+    if let Some(ref project) = report.project_path {
+        command.current_dir(project);
+    }
+    let output = command.output().context("无法运行 gh")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    // gh 在最后一行输出 issue 链接
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with("http"))
+        .map(str::to_string)
+        .context("gh 没有输出 issue 链接")
+}
+
+/// 追加到本地 errors.md
+pub fn append_local(path: &Path, report: &ErrorReport) -> Result<String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    write!(
+        file,
+        "## {} ({})\n\n{}\n",
+        report.title(),
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        report.body()
+    )?;
+    Ok(format!("file://{}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_files_once_per_window() {
+        let config = ErrorFilingConfig {
+            enabled: true,
+            threshold: 2,
+            ..Default::default()
+        };
+        let mut state = ErrorFilingState::default();
+        let start = Utc::now();
+        let at = |mins| start + Duration::minutes(mins);
+        let record = |state: &mut ErrorFilingState, category, mins| {
+            state.record("cam-1", category, &config, at(mins))
+        };
+
+        assert_eq!(
+            record(&mut state, ErrorCategory::Network, 0),
+            FilingDecision::Notify
+        );
+        assert_eq!(
+            record(&mut state, ErrorCategory::Network, 10),
+            FilingDecision::Notify
+        );
+        assert_eq!(
+            record(&mut state, ErrorCategory::RateLimit, 15),
+            FilingDecision::Notify
+        );
+        assert_eq!(
+            record(&mut state, ErrorCategory::Network, 20),
+            FilingDecision::File { count: 3 }
+        );
+        state.mark_filed("cam-1", ErrorCategory::Network, "https://gh/1", at(20));
+        assert_eq!(
+            record(&mut state, ErrorCategory::Network, 30),
+            FilingDecision::Filed {
+                url: "https://gh/1".to_string()
+            }
+        );
+
+        // 窗口过后重新计数
+        assert_eq!(
+            record(&mut state, ErrorCategory::Network, 95),
+            FilingDecision::Notify
+        );
+    }
+
+    #[test]
+    fn test_report_appended_locally() {
+        let temp = tempfile::tempdir().unwrap();
+        let report = ErrorReport {
+            agent_id: "cam-1".to_string(),
+            category: ErrorCategory::Network,
+            message: "Error: connect ECONNREFUSED 127.0.0.1:5432\n    at TCP".to_string(),
+            count: 4,
+            window_mins: 60,
+            project_path: Some("/work/api".to_string()),
+            terminal_snapshot: Some("$ npm test\nECONNREFUSED".to_string()),
+            session_id: Some("s-1".to_string()),
+            jsonl_path: None,
+        };
+        assert_eq!(
+            report.title(),
+            "[cam] 网络错误: Error: connect ECONNREFUSED 127.0.0.1:5432"
+        );
+
+        let path = temp.path().join("errors.md");
+        let url = append_local(&path, &report).unwrap();
+        assert_eq!(url, format!("file://{}", path.display()));
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("## [cam] 网络错误"));
+        assert!(content.contains("在 60 分钟内出现 4 次网络错误"));
+        assert!(content.contains("`cam attach cam-1`"));
+        assert!(content.contains("$ npm test"));
+    }
+}
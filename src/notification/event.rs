@@ -24,6 +24,9 @@ pub struct NotificationEvent {
     /// 跳过去重（强制发送）
     #[serde(default)]
     pub skip_dedup: bool,
+    /// 权限请求可回复 `s` 在沙箱中执行
+    #[serde(default)]
+    pub sandbox_option: bool,
 }
 
 /// 事件类型枚举
@@ -69,6 +72,7 @@ impl NotificationEvent {
             timestamp: Utc::now(),
             dedup_key: None,
             skip_dedup: false,
+            sandbox_option: false,
         }
     }
 
@@ -159,6 +163,7 @@ impl NotificationEventBuilder {
             timestamp: self.timestamp.unwrap_or_else(Utc::now),
             dedup_key: self.dedup_key,
             skip_dedup: false,
+            sandbox_option: false,
         })
     }
 }
@@ -268,12 +273,114 @@ impl NotificationEvent {
         self.skip_dedup = skip;
         self
     }
+
+    /// 提供沙箱执行选项（链式调用）
+    pub fn with_sandbox_option(mut self, enabled: bool) -> Self {
+        self.sandbox_option = enabled;
+        self
+    }
+
+    /// 从 hook 输入构建事件（`cam notify` 与 `cam replay` 共用）
+    ///
+    /// `payload` 为 stdin 中解析出的 JSON，`context` 为原始 stdin 内容。
+    pub fn from_hook(
+        agent_id: impl Into<String>,
+        event: &str,
+        payload: Option<&Value>,
+        context: &str,
+        terminal_snapshot: Option<&str>,
+    ) -> Self {
+        let field = |key: &str| {
+            payload
+                .and_then(|j| j.get(key))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+
+        let event_type = match event {
+            "WaitingForInput" => NotificationEventType::WaitingForInput {
+                pattern_type: "unknown".to_string(),
+                is_decision_required: false,
+            },
+            "permission_request" => NotificationEventType::PermissionRequest {
+                tool_name: field("tool_name").unwrap_or_else(|| "unknown".to_string()),
+                tool_input: payload
+                    .and_then(|j| j.get("tool_input"))
+                    .cloned()
+                    .unwrap_or(serde_json::json!({})),
+            },
+            "notification" => NotificationEventType::Notification {
+                notification_type: field("notification_type").unwrap_or_default(),
+                message: field("message").unwrap_or_default(),
+            },
+            "AgentExited" => NotificationEventType::AgentExited,
+            "Error" => NotificationEventType::Error {
+                message: context.to_string(),
+            },
+            "stop" => NotificationEventType::Stop,
+            "session_start" => NotificationEventType::SessionStart,
+            "session_end" => NotificationEventType::SessionEnd,
+            _ => NotificationEventType::Notification {
+                notification_type: event.to_string(),
+                message: String::new(),
+            },
+        };
+
+        let mut evt = Self::new(agent_id, event_type);
+        // 设置项目路径（从 cwd 获取）
+        if let Some(cwd) = field("cwd") {
+            evt = evt.with_project_path(cwd);
+        }
+        if let Some(snapshot) = terminal_snapshot {
+            evt = evt.with_terminal_snapshot(snapshot);
+        }
+        evt
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_from_hook_permission_request() {
+        let payload = serde_json::json!({
+            "cwd": "/workspace/app",
+            "tool_name": "Bash",
+            "tool_input": {"command": "ls"}
+        });
+        let event = NotificationEvent::from_hook(
+            "cam-1",
+            "permission_request",
+            Some(&payload),
+            &payload.to_string(),
+            None,
+        );
+
+        assert_eq!(event.project_path.as_deref(), Some("/workspace/app"));
+        assert_eq!(
+            event.event_type,
+            NotificationEventType::PermissionRequest {
+                tool_name: "Bash".to_string(),
+                tool_input: serde_json::json!({"command": "ls"}),
+            }
+        );
+        assert!(event.terminal_snapshot.is_none());
+    }
+
+    #[test]
+    fn test_from_hook_unknown_event_becomes_notification() {
+        let event = NotificationEvent::from_hook("cam-1", "custom", None, "", Some("screen"));
+        assert_eq!(
+            event.event_type,
+            NotificationEventType::Notification {
+                notification_type: "custom".to_string(),
+                message: String::new(),
+            }
+        );
+        assert_eq!(event.terminal_snapshot.as_deref(), Some("screen"));
+    }
+
     #[test]
     fn test_new_event() {
         let event = NotificationEvent::new(
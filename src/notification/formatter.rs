@@ -0,0 +1,795 @@
+//! 消息格式化 - 按渠道能力生成通知文本
+//!
+//! 不同渠道对标记语法的支持差异很大：Telegram MarkdownV2 需要转义大量符号，
+//! Slack 使用 mrkdwn（`*粗体*`、转义 `&<>`），WhatsApp 不支持代码块语言标记，
+//! 短信类渠道只能显示纯文本。`ChannelProfile` 描述渠道能力，
+//! `MessageFormatter` 把结构化的消息块渲染为对应格式并控制长度。
+//! 用户模板（见 `templates`）只替换正文，标题、限流摘要和原始 JSON 仍由这里生成。
+
+use crate::infra::text::{take_chars, take_utf16};
+use crate::notification::summarizer::ErrorCategory;
+use crate::notification::system_event::{reply_options, EventData, SystemEventPayload};
+use crate::notification::templates::NotificationTemplates;
+
+/// 标记语法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkupFlavor {
+    /// 纯文本（短信等）
+    Plain,
+    /// CommonMark（Discord 等）
+    Markdown,
+    /// Telegram MarkdownV2
+    TelegramMarkdownV2,
+    /// Slack mrkdwn
+    SlackMrkdwn,
+    /// WhatsApp 格式（`*粗体*`、无语言标记的代码块）
+    WhatsApp,
+    /// HTML
+    Html,
+}
+
+impl MarkupFlavor {
+    /// 从配置字符串解析（plain/markdown/markdownv2/mrkdwn/whatsapp/html）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "plain" | "text" => Some(Self::Plain),
+            "markdown" | "md" => Some(Self::Markdown),
+            "markdownv2" | "telegram" => Some(Self::TelegramMarkdownV2),
+            "mrkdwn" | "slack" => Some(Self::SlackMrkdwn),
+            "whatsapp" => Some(Self::WhatsApp),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// 渠道能力描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelProfile {
+    /// 标记语法
+    pub markup: MarkupFlavor,
+    /// 单条消息最大长度（按 UTF-16 单元计，emoji 占 2）
+    pub max_len: Option<usize>,
+    /// 是否显示 emoji
+    pub emoji: bool,
+}
+
+impl Default for ChannelProfile {
+    /// 未知渠道：CommonMark，不限长度
+    fn default() -> Self {
+        Self {
+            markup: MarkupFlavor::Markdown,
+            max_len: None,
+            emoji: true,
+        }
+    }
+}
+
+impl ChannelProfile {
+    /// 内置渠道能力（按 OpenClaw 渠道名）
+    pub fn for_channel(channel: Option<&str>) -> Self {
+        let Some(channel) = channel else {
+            return Self::default();
+        };
+        let (markup, max_len, emoji) = match channel.to_ascii_lowercase().as_str() {
+            "telegram" => (MarkupFlavor::TelegramMarkdownV2, Some(4096), true),
+            "slack" => (MarkupFlavor::SlackMrkdwn, Some(4000), true),
+            "discord" => (MarkupFlavor::Markdown, Some(2000), true),
+            "whatsapp" => (MarkupFlavor::WhatsApp, Some(4096), true),
+            "sms" => (MarkupFlavor::Plain, Some(1600), false),
+            "signal" | "imessage" => (MarkupFlavor::Plain, None, true),
+            "email" => (MarkupFlavor::Html, None, true),
+            _ => return Self::default(),
+        };
+        Self {
+            markup,
+            max_len,
+            emoji,
+        }
+    }
+
+    /// 覆盖标记语法
+    pub fn with_markup(mut self, markup: MarkupFlavor) -> Self {
+        self.markup = markup;
+        self
+    }
+}
+
+/// 消息块
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// 标题行：emoji + 粗体标签 + 文本
+    Title {
+        emoji: &'static str,
+        label: String,
+        text: String,
+    },
+    /// 普通文本（可多行）
+    Text(String),
+    /// 代码块（终端输出、JSON），可带一行说明
+    Code {
+        caption: Option<String>,
+        lang: Option<&'static str>,
+        content: String,
+    },
+}
+
+/// 超长截断标记
+const TRUNCATED: &str = "... (truncated)";
+
+/// 渠道计算的消息长度（UTF-16 单元）
+fn message_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// 按渠道能力渲染消息
+#[derive(Debug, Clone)]
+pub struct MessageFormatter {
+    profile: ChannelProfile,
+    /// 回复类事件附带原始 JSON（供 OpenClaw hooks/skills 使用）
+    include_raw_json: bool,
+    /// 用户模板（覆盖正文）
+    templates: NotificationTemplates,
+    /// 快照隐私模式：消息中不出现终端快照
+    local_snapshots: bool,
+}
+
+impl MessageFormatter {
+    pub fn new(profile: ChannelProfile) -> Self {
+        Self {
+            profile,
+            include_raw_json: false,
+            templates: NotificationTemplates::default(),
+            local_snapshots: false,
+        }
+    }
+
+    /// 使用内置渠道能力
+    pub fn for_channel(channel: Option<&str>) -> Self {
+        Self::new(ChannelProfile::for_channel(channel))
+    }
+
+    /// 回复类事件（权限请求、等待输入）附带原始 JSON
+    pub fn with_raw_json(mut self, include: bool) -> Self {
+        self.include_raw_json = include;
+        self
+    }
+
+    /// 使用用户模板覆盖正文
+    pub fn with_templates(mut self, templates: NotificationTemplates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// 快照隐私模式（`privacy.local_snapshots`）：不渲染终端快照
+    pub fn with_local_snapshots(mut self, local: bool) -> Self {
+        self.local_snapshots = local;
+        self
+    }
+
+    /// 当前渠道能力
+    pub fn profile(&self) -> &ChannelProfile {
+        &self.profile
+    }
+
+    fn emoji<'a>(&self, emoji: &'a str) -> &'a str {
+        if self.profile.emoji {
+            emoji
+        } else {
+            ""
+        }
+    }
+
+    /// 格式化 system event
+    pub fn format_system_event(&self, payload: &SystemEventPayload) -> String {
+        self.render(&self.system_event_blocks(payload))
+    }
+
+    /// system event 的消息块
+    pub fn system_event_blocks(&self, payload: &SystemEventPayload) -> Vec<Block> {
+        let emoji = match payload.urgency.as_str() {
+            "HIGH" => "⚠️",
+            "MEDIUM" => "💬",
+            _ => "ℹ️",
+        };
+        let mut blocks = vec![Block::Title {
+            emoji,
+            label: "CAM".to_string(),
+            text: payload.agent_id.clone(),
+        }];
+
+        match self.templates.render(payload) {
+            Some(text) => blocks.push(Block::Text(text)),
+            None => self.push_builtin_body(payload, &mut blocks),
+        }
+
+        if let Some(ref url) = payload.context.issue_url {
+            blocks.push(Block::Text(format!(
+                "同类错误反复出现，已提交 issue: {}（之后不再逐条通知）",
+                url
+            )));
+        }
+
+        if let Some(ref id) = payload.context.snapshot_id {
+            blocks.push(Block::Text(format!("终端快照留在本机: cam show {}", id)));
+        }
+
+        if let Some(progress) = payload.context.todos {
+            blocks.push(Block::Text(format!("进度: {}", progress)));
+        }
+
+        if !payload.context.recent_messages.is_empty() {
+            blocks.push(Block::Text(format!(
+                "此前的消息:\n{}",
+                payload
+                    .context
+                    .recent_messages
+                    .iter()
+                    .map(|message| format!("› {}", message))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )));
+        }
+
+        if !payload.context.digest.is_empty() {
+            blocks.push(Block::Text(format!(
+                "限流期间另有 {} 条通知:\n{}",
+                payload.context.digest.len(),
+                payload.context.digest.join("\n")
+            )));
+        }
+
+        if let Some(ref previous) = payload.context.previous_answer {
+            let hint = match payload.context.auto_answer_secs {
+                Some(secs) => format!("{} 秒后自动发送，回复其他内容取消", secs),
+                None => "回复 r 重复上次回答".to_string(),
+            };
+            blocks.push(Block::Text(format!(
+                "上次回答: {} · {}",
+                previous.display(),
+                hint
+            )));
+        }
+
+        let reply_required = matches!(
+            payload.event_type.as_str(),
+            "permission_request" | "waiting_for_input"
+        );
+        if self.include_raw_json && reply_required {
+            let raw = if self.local_snapshots {
+                let mut redacted = payload.clone();
+                redacted.context.terminal_snapshot = None;
+                serde_json::to_string_pretty(&redacted)
+            } else {
+                serde_json::to_string_pretty(payload)
+            }
+            .unwrap_or_default();
+            let max_chars = 3500usize;
+            let mut content = take_chars(&raw, max_chars).to_string();
+            if raw.chars().count() > max_chars {
+                content.push('\n');
+                content.push_str(TRUNCATED);
+            }
+            blocks.push(Block::Code {
+                caption: Some("raw_event_json:".to_string()),
+                lang: Some("json"),
+                content,
+            });
+        }
+
+        blocks
+    }
+
+    /// 内置正文：事件描述、文件影响、风险等级和回复提示
+    fn push_builtin_body(&self, payload: &SystemEventPayload, blocks: &mut Vec<Block>) {
+        // 终端最后 30 行（隐私模式下不附带）
+        let snapshot = payload
+            .context
+            .terminal_snapshot
+            .as_ref()
+            .filter(|_| !self.local_snapshots);
+        let snapshot_tail = snapshot.map(|snapshot| {
+            let lines: Vec<&str> = snapshot.lines().collect();
+            let start = lines.len().saturating_sub(30);
+            lines[start..].join("\n")
+        });
+
+        let code = |content: String| Block::Code {
+            caption: None,
+            lang: None,
+            content,
+        };
+
+        match payload.event_type.as_str() {
+            "permission_request" | "waiting_for_input"
+                if payload.context.extracted_message.is_some() =>
+            {
+                // 优先使用 AI 提取的消息
+                let extracted = payload
+                    .context
+                    .extracted_message
+                    .clone()
+                    .unwrap_or_default();
+                blocks.push(Block::Text(extracted));
+            }
+            "permission_request" => {
+                if let EventData::PermissionRequest {
+                    tool_name,
+                    tool_input,
+                } = &payload.event_data
+                {
+                    let cmd = tool_input
+                        .get("command")
+                        .or_else(|| tool_input.get("file_path"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown");
+                    blocks.push(Block::Text(format!("执行: {} {}", tool_name, cmd)));
+                    blocks.extend(snapshot_tail.map(code));
+                } else {
+                    blocks.push(Block::Text("请求权限".to_string()));
+                }
+            }
+            "waiting_for_input" => {
+                let title = match payload.context.waiting_kind {
+                    Some(kind) => format!("等待输入（{}）", kind.label()),
+                    None => "等待输入".to_string(),
+                };
+                blocks.push(Block::Text(title));
+                blocks.extend(snapshot_tail.map(code));
+            }
+            "notification" => {
+                if let EventData::Notification {
+                    message,
+                    notification_type,
+                } = &payload.event_data
+                {
+                    blocks.push(Block::Text(format!("{}: {}", notification_type, message)));
+                } else {
+                    blocks.push(Block::Text("通知".to_string()));
+                }
+            }
+            "error" => {
+                if let EventData::Error {
+                    message,
+                    category,
+                    suggestion,
+                } = &payload.event_data
+                {
+                    let label = match category {
+                        Some(c) if *c != ErrorCategory::Other => c.label(),
+                        _ => "错误",
+                    };
+                    blocks.push(Block::Text(format!("{}: {}", label, message)));
+                    if let Some(suggestion) = suggestion {
+                        blocks.push(Block::Text(format!("建议: {}", suggestion)));
+                    }
+                } else {
+                    blocks.push(Block::Text("发生错误".to_string()));
+                }
+            }
+            "agent_exited" => blocks.push(Block::Text("Agent 已退出".to_string())),
+            other => blocks.push(Block::Text(other.to_string())),
+        }
+
+        if let Some(ref impact) = payload.context.file_impact {
+            blocks.push(Block::Text(format!("影响: {}", impact)));
+        }
+
+        let risk = payload.context.risk_level.as_str();
+        let risk_emoji = match risk {
+            "HIGH" => "🔴",
+            "MEDIUM" => "🟡",
+            "LOW" => "🟢",
+            _ => "⚪",
+        };
+        let mut risk_line = if self.profile.emoji {
+            format!("风险: {} {}", risk_emoji, risk)
+        } else {
+            format!("风险: {}", risk)
+        };
+        if !payload.context.risk_findings.is_empty() {
+            risk_line.push_str(&format!("（{}）", payload.context.risk_findings.join("；")));
+        }
+        blocks.push(Block::Text(risk_line));
+
+        let action_hint = match (payload.event_type.as_str(), reply_options(payload)) {
+            (_, "") => "无需回复".to_string(),
+            ("permission_request", options) => format!("回复 {}", options),
+            (_, options) => options.to_string(),
+        };
+        blocks.push(Block::Text(action_hint));
+    }
+
+    /// 渲染消息块，超过渠道长度上限时优先截断代码块
+    ///
+    /// 长度按 UTF-16 单元计算（Telegram 的计数方式，emoji 占 2），截断不拆分字素簇。
+    pub fn render(&self, blocks: &[Block]) -> String {
+        let rendered = self.render_unbounded(blocks);
+        let Some(max_len) = self.profile.max_len else {
+            return rendered;
+        };
+        let len = message_len(&rendered);
+        if len <= max_len {
+            return rendered;
+        }
+
+        // 按溢出量缩短最长的代码块（保留开头），再不够则缩短最后一个文本块
+        let mut blocks = blocks.to_vec();
+        let mut overflow = len - max_len;
+        for _ in 0..blocks.len() {
+            let target = blocks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, b)| match b {
+                    Block::Code { content, .. } if !content.is_empty() => {
+                        Some((i, message_len(content)))
+                    }
+                    _ => None,
+                })
+                .max_by_key(|(_, len)| *len)
+                .or_else(|| {
+                    blocks.iter().enumerate().rev().find_map(|(i, b)| match b {
+                        Block::Text(text) if !text.is_empty() => Some((i, message_len(text))),
+                        _ => None,
+                    })
+                });
+            let Some((index, current)) = target else {
+                break;
+            };
+            // 转义可能使一个字符渲染为多个，多留余量
+            let keep = current.saturating_sub(overflow + TRUNCATED.len() * 2 + 8);
+            match &mut blocks[index] {
+                Block::Code { content, .. } | Block::Text(content) => {
+                    let mut shortened = take_utf16(content, keep).to_string();
+                    if keep > 0 {
+                        shortened.push('\n');
+                    }
+                    shortened.push_str(TRUNCATED);
+                    *content = shortened;
+                }
+                Block::Title { .. } => {}
+            }
+
+            let rendered = self.render_unbounded(&blocks);
+            let len = message_len(&rendered);
+            if len <= max_len {
+                return rendered;
+            }
+            overflow = len - max_len;
+        }
+
+        // 仍然超长（标题过长等）：纯文本安全截断
+        let rendered = self.render_unbounded(&blocks);
+        take_utf16(&rendered, max_len).to_string()
+    }
+
+    fn render_unbounded(&self, blocks: &[Block]) -> String {
+        blocks
+            .iter()
+            .map(|block| self.render_block(block))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn render_block(&self, block: &Block) -> String {
+        match block {
+            Block::Title { emoji, label, text } => {
+                let emoji = self.emoji(emoji);
+                let title = format!("{} {}", self.bold(label), self.escape(text));
+                if emoji.is_empty() {
+                    title
+                } else {
+                    format!("{} {}", emoji, title)
+                }
+            }
+            Block::Text(text) => self.escape(text),
+            Block::Code {
+                caption,
+                lang,
+                content,
+            } => {
+                let code = self.code_block(*lang, content);
+                match caption {
+                    Some(caption) => format!("---\n{}\n{}", self.escape(caption), code),
+                    None => code,
+                }
+            }
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        let text = self.escape(text);
+        match self.profile.markup {
+            MarkupFlavor::Plain => text,
+            MarkupFlavor::Markdown => format!("**{}**", text),
+            MarkupFlavor::TelegramMarkdownV2
+            | MarkupFlavor::SlackMrkdwn
+            | MarkupFlavor::WhatsApp => format!("*{}*", text),
+            MarkupFlavor::Html => format!("<b>{}</b>", text),
+        }
+    }
+
+    fn code_block(&self, lang: Option<&str>, content: &str) -> String {
+        match self.profile.markup {
+            MarkupFlavor::Plain => content.to_string(),
+            MarkupFlavor::Markdown => {
+                format!("```{}\n{}\n```", lang.unwrap_or(""), content)
+            }
+            MarkupFlavor::TelegramMarkdownV2 => {
+                // 代码块内只需转义 ` 和 \
+                let escaped = content.replace('\\', "\\\\").replace('`', "\\`");
+                format!("```{}\n{}\n```", lang.unwrap_or(""), escaped)
+            }
+            MarkupFlavor::SlackMrkdwn => format!("```\n{}\n```", escape_slack(content)),
+            MarkupFlavor::WhatsApp => format!("```\n{}\n```", content),
+            MarkupFlavor::Html => match lang {
+                Some(lang) => format!(
+                    "<pre><code class=\"language-{}\">{}</code></pre>",
+                    lang,
+                    escape_html(content)
+                ),
+                None => format!("<pre>{}</pre>", escape_html(content)),
+            },
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        match self.profile.markup {
+            MarkupFlavor::Plain | MarkupFlavor::Markdown | MarkupFlavor::WhatsApp => {
+                text.to_string()
+            }
+            MarkupFlavor::TelegramMarkdownV2 => escape_markdown_v2(text),
+            MarkupFlavor::SlackMrkdwn => escape_slack(text),
+            MarkupFlavor::Html => escape_html(text),
+        }
+    }
+}
+
+/// Telegram MarkdownV2 转义（代码块外）
+pub fn escape_markdown_v2(text: &str) -> String {
+    const SPECIAL: &[char] = &[
+        '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+        '\\',
+    ];
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Slack mrkdwn 转义
+pub fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// HTML 转义
+pub fn escape_html(text: &str) -> String {
+    escape_slack(text).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::event::NotificationEvent;
+    use crate::notification::urgency::Urgency;
+
+    fn permission_payload() -> SystemEventPayload {
+        let event = NotificationEvent::permission_request(
+            "cam-1",
+            "Bash",
+            serde_json::json!({"command": "rm -rf target/*.o"}),
+        )
+        .with_terminal_snapshot("$ cargo build\nerror[E0433]: `foo` <not found>");
+        SystemEventPayload::from_event(&event, Urgency::High)
+    }
+
+    #[test]
+    fn test_previous_answer_hint() {
+        let mut payload = permission_payload();
+        assert!(!MessageFormatter::new(ChannelProfile::default())
+            .format_system_event(&payload)
+            .contains("上次回答"));
+
+        payload.set_previous_answer(Some(crate::notification::RememberedAnswer {
+            answer: "2".to_string(),
+            label: Some("Tailwind CSS".to_string()),
+            answered_at: chrono::Utc::now(),
+            count: 1,
+        }));
+        let msg = MessageFormatter::new(ChannelProfile::default()).format_system_event(&payload);
+        assert!(msg.contains("上次回答: 2 (Tailwind CSS) · 回复 r 重复上次回答"));
+
+        payload.set_auto_answer(Some(60));
+        let msg = MessageFormatter::new(ChannelProfile::default()).format_system_event(&payload);
+        assert!(msg.contains("上次回答: 2 (Tailwind CSS) · 60 秒后自动发送，回复其他内容取消"));
+    }
+
+    #[test]
+    fn test_local_snapshots() {
+        let payload = permission_payload();
+        let formatter = MessageFormatter::new(ChannelProfile::default()).with_raw_json(true);
+        assert!(formatter.format_system_event(&payload).contains("E0433"));
+
+        let msg = formatter
+            .clone()
+            .with_local_snapshots(true)
+            .format_system_event(&payload);
+        assert!(!msg.contains("E0433"));
+        assert!(msg.contains("执行: Bash rm -rf target/*.o"));
+
+        let mut payload = payload;
+        payload.keep_snapshot_local("0a1b2c3d");
+        assert!(payload.context.terminal_snapshot.is_none());
+        let msg = formatter.format_system_event(&payload);
+        assert!(!msg.contains("E0433"));
+        assert!(msg.contains("终端快照留在本机: cam show 0a1b2c3d"));
+    }
+
+    #[test]
+    fn test_context_extras() {
+        let mut payload = permission_payload();
+        payload.set_recent_messages(vec![
+            "方案一：重写登录".to_string(),
+            "方案二：只修复 token 校验".to_string(),
+        ]);
+        let msg = MessageFormatter::new(ChannelProfile::default()).format_system_event(&payload);
+        assert!(msg.contains("此前的消息:\n› 方案一：重写登录\n› 方案二：只修复 token 校验"));
+
+        payload.set_todos(Some(crate::agent::todos::TodoProgress { done: 4, total: 6 }));
+        let msg = MessageFormatter::new(ChannelProfile::default()).format_system_event(&payload);
+        assert!(msg.contains("进度: 4/6 todos done"));
+
+        payload.set_issue_url(Some("https://github.com/o/r/issues/7".to_string()));
+        let msg = MessageFormatter::new(ChannelProfile::default()).format_system_event(&payload);
+        assert!(msg.contains("已提交 issue: https://github.com/o/r/issues/7"));
+    }
+
+    #[test]
+    fn test_channel_profiles() {
+        assert_eq!(
+            ChannelProfile::for_channel(Some("Telegram")).markup,
+            MarkupFlavor::TelegramMarkdownV2
+        );
+        assert_eq!(
+            ChannelProfile::for_channel(Some("slack")).markup,
+            MarkupFlavor::SlackMrkdwn
+        );
+        let sms = ChannelProfile::for_channel(Some("sms"));
+        assert_eq!(sms.markup, MarkupFlavor::Plain);
+        assert!(!sms.emoji);
+        assert_eq!(ChannelProfile::for_channel(None), ChannelProfile::default());
+        assert_eq!(
+            MarkupFlavor::parse("MarkdownV2"),
+            Some(MarkupFlavor::TelegramMarkdownV2)
+        );
+        assert_eq!(MarkupFlavor::parse("nope"), None);
+    }
+
+    #[test]
+    fn test_telegram_markdown_v2_escaping() {
+        let msg = MessageFormatter::for_channel(Some("telegram"))
+            .format_system_event(&permission_payload());
+
+        assert!(msg.starts_with("⚠️ *CAM* cam\\-1"));
+        assert!(msg.contains("执行: Bash rm \\-rf target/\\*\\.o"));
+        // 代码块内只转义反引号
+        assert!(msg.contains("```\n$ cargo build\nerror[E0433]: \\`foo\\` <not found>\n```"));
+        assert!(msg.contains("回复 y 允许 / n 拒绝"));
+    }
+
+    #[test]
+    fn test_slack_and_whatsapp() {
+        let slack =
+            MessageFormatter::for_channel(Some("slack")).format_system_event(&permission_payload());
+        assert!(slack.starts_with("⚠️ *CAM* cam-1"));
+        assert!(slack.contains("`foo` &lt;not found&gt;"));
+
+        let whatsapp = MessageFormatter::for_channel(Some("whatsapp"))
+            .with_raw_json(true)
+            .format_system_event(&permission_payload());
+        assert!(whatsapp.contains("raw_event_json:\n```\n{"));
+        assert!(!whatsapp.contains("```json"));
+    }
+
+    #[test]
+    fn test_plain_without_emoji_or_markup() {
+        let msg =
+            MessageFormatter::for_channel(Some("sms")).format_system_event(&permission_payload());
+        assert!(msg.starts_with("CAM cam-1\n\n执行: Bash rm -rf target/*.o"));
+        assert!(msg.contains("风险: "));
+        assert!(!msg.contains("```"));
+        assert!(!msg.contains("⚠️"));
+    }
+
+    #[test]
+    fn test_error_category_and_suggestion() {
+        let event = NotificationEvent::error("cam-1", "OAuth token has expired");
+        let msg = MessageFormatter::for_channel(Some("sms"))
+            .format_system_event(&SystemEventPayload::from_event(&event, Urgency::High));
+        assert!(msg.contains("认证失败: OAuth token has expired"));
+        assert!(msg.contains("建议: API key 或登录已失效"));
+
+        let event = NotificationEvent::error("cam-1", "Test error");
+        let msg = MessageFormatter::for_channel(Some("sms"))
+            .format_system_event(&SystemEventPayload::from_event(&event, Urgency::High));
+        assert!(msg.contains("错误: Test error"));
+        assert!(!msg.contains("建议"));
+    }
+
+    #[test]
+    fn test_html() {
+        let msg =
+            MessageFormatter::for_channel(Some("email")).format_system_event(&permission_payload());
+        assert!(msg.starts_with("⚠️ <b>CAM</b> cam-1"));
+        assert!(msg.contains("<pre>$ cargo build\nerror[E0433]: `foo` &lt;not found&gt;</pre>"));
+    }
+
+    #[test]
+    fn test_template_replaces_body() {
+        use crate::notification::templates::TemplateKind;
+
+        let templates = NotificationTemplates::default().with_template(
+            TemplateKind::PermissionRequest,
+            "Approve {{question}}? ({{risk}})",
+        );
+        let msg = MessageFormatter::for_channel(Some("telegram"))
+            .with_templates(templates)
+            .with_raw_json(true)
+            .format_system_event(&permission_payload());
+        assert!(
+            msg.starts_with("⚠️ *CAM* cam\\-1\n\nApprove Bash rm \\-rf target/\\*\\.o? \\(MEDIUM\\)")
+        );
+        assert!(!msg.contains("回复 y 允许"));
+        assert!(!msg.contains("```\n$ cargo build"));
+        assert!(msg.contains("raw\\_event\\_json:"));
+    }
+
+    #[test]
+    fn test_render_truncates_code_first() {
+        let formatter = MessageFormatter::new(ChannelProfile {
+            markup: MarkupFlavor::TelegramMarkdownV2,
+            max_len: Some(200),
+            emoji: true,
+        });
+        let blocks = vec![
+            Block::Title {
+                emoji: "💬",
+                label: "CAM".to_string(),
+                text: "cam-1".to_string(),
+            },
+            Block::Code {
+                caption: None,
+                lang: None,
+                content: "x.".repeat(500),
+            },
+            Block::Text("回复你的选择或输入内容".to_string()),
+        ];
+        let msg = formatter.render(&blocks);
+        assert!(msg.chars().count() <= 200, "{}", msg.chars().count());
+        assert!(msg.contains(TRUNCATED));
+        assert!(msg.ends_with("回复你的选择或输入内容"));
+        assert!(msg.contains("\n```\n\n"));
+    }
+
+    #[test]
+    fn test_render_counts_emoji_as_utf16() {
+        let formatter = MessageFormatter::new(ChannelProfile {
+            markup: MarkupFlavor::Plain,
+            max_len: Some(120),
+            emoji: true,
+        });
+        let blocks = vec![Block::Text("🚀👍🏽 部署完成 ".repeat(40))];
+        let msg = formatter.render(&blocks);
+        assert!(message_len(&msg) <= 120, "{}", message_len(&msg));
+        // 不留下半个肤色修饰 emoji
+        assert_eq!(msg.matches('👍').count(), msg.matches('\u{1F3FD}').count());
+        assert!(msg.ends_with(TRUNCATED));
+    }
+}
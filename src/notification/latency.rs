@@ -0,0 +1,220 @@
+//! 通知投递延迟 - 记录从事件检测到渠道确认送达的耗时，并在持续超过 SLO 时告警
+//!
+//! 每条成功投递的通知按 `timestamp`（hook 触发或 watcher 检测时创建事件的时间）到渠道返回
+//! 成功的间隔记录一条样本，追加到 `~/.config/code-agent-monitor/latency.jsonl`，
+//! 超过 [`MAX_SAMPLES`] 条时丢弃最旧的。`cam stats` 按渠道显示 p50/p90/p99。
+//!
+//! config.json 的 `latency_slo`（如 `{"max_secs": 10, "window": 5}`）开启告警：最近 `window`
+//! 条投递全部超过 `max_secs` 时发送一条 HIGH 通知，恢复后再次持续超标才会重新告警。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 保留的最多样本数
+pub const MAX_SAMPLES: usize = 5000;
+
+/// 一次投递的延迟
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySample {
+    /// 送达时间
+    pub ts: DateTime<Utc>,
+    pub agent_id: String,
+    pub event: String,
+    /// 投递渠道（webhook 的 channel，`openclaw` 表示 system event）
+    pub channel: String,
+    /// 检测到送达的耗时（毫秒）
+    pub latency_ms: u64,
+}
+
+/// 延迟样本文件
+#[derive(Debug, Clone)]
+pub struct LatencyLog {
+    path: PathBuf,
+}
+
+impl LatencyLog {
+    /// 默认路径
+    pub fn new() -> Self {
+        Self::at(&crate::infra::paths::state_dir().join("latency.jsonl"))
+    }
+
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// 读取全部样本（按时间顺序），跳过无法解析的行
+    pub fn load(&self) -> Vec<LatencySample> {
+        fs::read_to_string(&self.path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 追加一条样本
+    pub fn append(&self, sample: &LatencySample) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut samples = self.load();
+        if samples.len() >= MAX_SAMPLES {
+            samples.push(sample.clone());
+            let content: Vec<String> = samples[samples.len() - MAX_SAMPLES..]
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<serde_json::Result<_>>()?;
+            fs::write(&self.path, content.join("\n") + "\n")?;
+        } else {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            writeln!(file, "{}", serde_json::to_string(sample)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for LatencyLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 延迟百分位（毫秒）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+impl LatencyStats {
+    /// 没有样本时返回 None
+    pub fn compute(latencies: impl IntoIterator<Item = u64>) -> Option<Self> {
+        let mut sorted: Vec<u64> = latencies.into_iter().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+        // 最近秩法：第 ceil(p * n) 个值
+        let percentile = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        Some(Self {
+            count: sorted.len(),
+            p50_ms: percentile(50),
+            p90_ms: percentile(90),
+            p99_ms: percentile(99),
+            max_ms: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// 延迟 SLO（config.json 的 `latency_slo`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySlo {
+    /// 单条通知允许的最大延迟（秒）
+    #[serde(default = "default_max_secs")]
+    pub max_secs: u64,
+    /// 连续超标多少条视为违反
+    #[serde(default = "default_window")]
+    pub window: usize,
+}
+
+fn default_max_secs() -> u64 {
+    10
+}
+
+fn default_window() -> usize {
+    5
+}
+
+impl LatencySlo {
+    /// 读取配置，未配置时为 None（不告警）
+    pub fn load() -> Option<Self> {
+        crate::infra::paths::config_section("latency_slo")
+    }
+
+    /// 最近 `window` 条样本是否全部超标
+    pub fn breached(&self, samples: &[LatencySample]) -> bool {
+        let window = self.window.max(1);
+        samples.len() >= window
+            && samples[samples.len() - window..]
+                .iter()
+                .all(|s| s.latency_ms > self.max_secs * 1000)
+    }
+
+    /// 加入最新一条后刚开始违反（之前未违反）时返回 true，用于只告警一次
+    pub fn newly_breached(&self, samples: &[LatencySample]) -> bool {
+        !samples.is_empty()
+            && self.breached(samples)
+            && !self.breached(&samples[..samples.len() - 1])
+    }
+}
+
+/// 毫秒格式化为秒（保留一位小数）
+pub fn format_latency(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latency_ms: u64) -> LatencySample {
+        LatencySample {
+            ts: Utc::now(),
+            agent_id: "cam-1".to_string(),
+            event: "WaitingForInput".to_string(),
+            channel: "telegram".to_string(),
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn test_latency_stats() {
+        let stats = LatencyStats::compute((1..=100).map(|n| n * 100)).unwrap();
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.p50_ms, 5000);
+        assert_eq!(stats.p90_ms, 9000);
+        assert_eq!(stats.p99_ms, 9900);
+        assert_eq!(stats.max_ms, 10000);
+
+        let single = LatencyStats::compute([1200]).unwrap();
+        assert_eq!(single.p50_ms, 1200);
+        assert_eq!(single.p99_ms, 1200);
+        assert!(LatencyStats::compute([]).is_none());
+        assert_eq!(format_latency(12345), "12.3s");
+    }
+
+    #[test]
+    fn test_slo_breach_and_log() {
+        let temp = tempfile::tempdir().unwrap();
+        let log = LatencyLog::at(&temp.path().join("latency.jsonl"));
+        let slo: LatencySlo = serde_json::from_str(r#"{"window": 3}"#).unwrap();
+        assert_eq!(slo.max_secs, 10);
+
+        let mut alerts = 0;
+        for ms in [500, 12_000, 15_000, 11_000, 20_000, 800, 13_000] {
+            log.append(&sample(ms)).unwrap();
+            if slo.newly_breached(&log.load()) {
+                alerts += 1;
+            }
+        }
+        // 第 4 条时连续 3 条超标告警一次，恢复后未再连续超标
+        assert_eq!(alerts, 1);
+        assert_eq!(log.load().len(), 7);
+        assert!(!slo.breached(&log.load()));
+    }
+}
@@ -1,7 +1,7 @@
 //! 通知抽象层 - 统一管理所有通知渠道
 //!
 //! # 设计目标
-//! 1. 统一接口：所有渠道实现 `NotificationChannel` trait
+//! 1. 统一接口：所有渠道实现 `NotificationChannel` trait，事件接收方实现 `Notifier` trait
 //! 2. 渠道解耦：每个渠道独立实现，互不影响
 //! 3. 灵活路由：`NotificationDispatcher` 根据配置决定发送到哪些渠道
 //! 4. 异步发送：所有渠道支持异步发送，不阻塞调用方
@@ -18,44 +18,84 @@
 //! dispatcher.send_sync(&message)?;
 //! ```
 
+pub mod answer_memory;
 pub mod builder;
 pub mod channel;
 pub mod channels;
 pub mod dedup_key;
 pub mod deduplicator;
 pub mod dispatcher;
+pub mod engagement;
+pub mod error_filing;
 pub mod event;
+pub mod formatter;
+pub mod latency;
 pub mod openclaw;
+pub mod outbound;
 pub mod payload;
+pub mod permission_batch;
+pub mod rate_limit;
+pub mod shell_risk;
+pub mod signing;
+pub mod snooze;
 pub mod store;
 pub mod summarizer;
 pub mod system_event;
+pub mod templates;
 pub mod terminal_cleaner;
 pub mod throttle;
+pub mod tool_policy;
+pub mod transcript_context;
 pub mod urgency;
+pub mod waiting_kind;
 pub mod watcher;
 pub mod webhook;
 
 #[cfg(test)]
 mod system_event_test;
 
+pub use answer_memory::{
+    remember_reply, AnswerMemory, AnswerMemoryConfig, AutoAnswerOutcome, AutoAnswerer, DueAnswer,
+    RememberedAnswer, AUTO_ANSWER_SENDER,
+};
 pub use builder::NotificationBuilder;
-pub use channel::{MessageMetadata, NotificationChannel, NotificationMessage, SendResult};
-pub use dedup_key::{generate_dedup_key, normalize_terminal_content};
+pub use channel::{
+    MessageMetadata, NotificationChannel, NotificationMessage, Notifier, SendResult,
+};
+pub use dedup_key::{
+    generate_dedup_key, normalize_terminal_content, question_dedup_key, question_fingerprint,
+};
 pub use deduplicator::{NotificationDeduplicator, NotifyAction};
 pub use dispatcher::NotificationDispatcher;
 pub use event::{NotificationEvent, NotificationEventBuilder, NotificationEventType};
+pub use formatter::{ChannelProfile, MarkupFlavor, MessageFormatter};
 pub use openclaw::OpenclawNotifier;
+pub use outbound::{Outbound, OutboundQueue};
 pub use payload::PayloadBuilder;
-pub use store::{NotificationRecord, NotificationStore};
+pub use permission_batch::{
+    parse_batch_reply, BatchSelection, PermissionBatch, PermissionBatcher,
+};
+pub use rate_limit::{
+    DigestEntry, RateDecision, RateLimitConfig, RateLimiter, ThrottleStatus, TokenBucket,
+};
+pub use signing::{SignatureError, SignatureHeaders, WebhookVerifier};
+pub use snooze::SnoozeState;
+pub use store::{
+    channel_supports_threads, NotificationRecord, NotificationStore, NotificationThread,
+};
 pub use summarizer::{
-    CompletionSummary, ErrorSummary, NotificationSummarizer, PermissionSummary, RiskLevel,
+    CompletionSummary, ErrorCategory, ErrorSummary, NotificationSummarizer, PermissionSummary,
+    RiskLevel,
 };
 pub use system_event::SystemEventPayload;
+pub use templates::{NotificationTemplates, TemplateKind};
 pub use terminal_cleaner::is_processing;
 pub use throttle::{MergedNotification, NotifyThrottle, ThrottledEvent};
+pub use transcript_context::TranscriptContextConfig;
 pub use urgency::{get_urgency, Urgency};
-pub use watcher::{Notifier, NotifyEvent, Watcher};
+pub use waiting_kind::{classify_waiting, WaitingKind};
+#[allow(deprecated)]
+pub use watcher::{NotifyEvent, Watcher};
 pub use webhook::{
     load_webhook_config_from_file, WebhookClient, WebhookConfig, WebhookPayload, WebhookResponse,
 };
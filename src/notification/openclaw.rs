@@ -11,27 +11,55 @@
 //! - `notification::system_event` - System Event 结构化数据
 
 use crate::agent::extractor::extract_message_from_snapshot;
-use crate::infra::terminal::truncate_for_status;
-use crate::notification::channel::SendResult;
-use crate::notification::dedup_key::generate_dedup_key;
+use crate::agent::todos::{agent_todos, TodoProgress};
+use crate::agent::AgentManager;
+use crate::ai::classify_error_with_ai;
+use crate::infra::event_bus::EventBus;
+use crate::infra::privacy::PrivacyConfig;
+use crate::infra::text::truncate_width;
+use crate::notification::answer_memory::AnswerMemory;
+use crate::notification::channel::{Notifier, SendResult};
+use crate::notification::dedup_key::{generate_dedup_key, question_dedup_key};
 use crate::notification::deduplicator::NotificationDeduplicator;
+use crate::notification::error_filing::{
+    file_report, ErrorFilingConfig, ErrorFilingState, ErrorReport, FilingDecision,
+};
 use crate::notification::event::{NotificationEvent, NotificationEventType};
+use crate::notification::formatter::{ChannelProfile, MarkupFlavor, MessageFormatter};
+use crate::notification::latency::{
+    format_latency, LatencyLog, LatencySample, LatencySlo, LatencyStats,
+};
 use crate::notification::payload::PayloadBuilder;
-use crate::notification::store::{NotificationRecord, NotificationStore};
+use crate::notification::rate_limit::{RateDecision, RateLimitConfig, RateLimiter};
+use crate::notification::snooze::SnoozeState;
+use crate::notification::store::{
+    channel_supports_threads, new_notification_id, NotificationRecord, NotificationStore,
+};
+use crate::notification::summarizer::{ErrorCategory, NotificationSummarizer};
+use crate::notification::templates::NotificationTemplates;
+use crate::notification::transcript_context::TranscriptContextConfig;
 use crate::notification::urgency::{get_urgency, Urgency};
-use crate::notification::webhook::{WebhookClient, WebhookConfig};
+use crate::notification::waiting_kind::{classify_waiting, snapshot_tail};
+use crate::notification::webhook::{WebhookClient, WebhookConfig, WebhookRoute};
+use crate::session::ConversationStateManager;
+use crate::team::TeamBridge;
 use anyhow::Result;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::process::Command;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, error, info, warn};
 
+/// 通知摘要（限流摘要、本地记录、TUI 列表）的最大显示宽度
+const SUMMARY_MAX_WIDTH: usize = 80;
+
+/// 等待输入分类时查看的终端末尾行数
+const WAITING_TAIL_LINES: usize = 15;
+
 /// 记录到 hook.log
 fn log_to_hook_file(message: &str) {
-    let log_path = dirs::home_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join(".config/code-agent-monitor/hook.log");
+    let log_path = crate::infra::paths::state_dir().join("hook.log");
 
     let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
     if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&log_path) {
@@ -39,6 +67,66 @@ fn log_to_hook_file(message: &str) {
     }
 }
 
+/// 重复错误的 issue 内容（附带 agent 的会话信息）
+fn error_report(
+    event: &NotificationEvent,
+    category: ErrorCategory,
+    message: &str,
+    count: usize,
+    config: &ErrorFilingConfig,
+    local_snapshots: bool,
+) -> ErrorReport {
+    let agent = AgentManager::new()
+        .get_agent(&event.agent_id)
+        .ok()
+        .flatten();
+    ErrorReport {
+        agent_id: event.agent_id.clone(),
+        category,
+        message: message.to_string(),
+        count,
+        window_mins: config.window_mins,
+        project_path: event
+            .project_path
+            .clone()
+            .or_else(|| agent.as_ref().map(|a| a.project_path.clone())),
+        terminal_snapshot: event.terminal_snapshot.clone().filter(|_| !local_snapshots),
+        session_id: agent.as_ref().and_then(|a| a.session_id.clone()),
+        jsonl_path: agent.and_then(|a| a.jsonl_path),
+    }
+}
+
+/// 任务完成或停下等待时发送的通知（附带 TODO 进度）
+fn is_completion(event_type: &NotificationEventType) -> bool {
+    match event_type {
+        NotificationEventType::WaitingForInput { .. }
+        | NotificationEventType::AgentExited
+        | NotificationEventType::Stop
+        | NotificationEventType::SessionEnd => true,
+        NotificationEventType::Notification {
+            notification_type, ..
+        } => notification_type == "idle_prompt",
+        _ => false,
+    }
+}
+
+/// 事件的去重键：优先使用 watcher 传入的键；有终端快照时使用与 watcher 相同的问题指纹键，
+/// hook 与 watcher 检测到同一问题时只通知一次
+fn event_dedup_key(event: &NotificationEvent) -> String {
+    if let Some(ref key) = event.dedup_key {
+        key.clone()
+    } else if let Some(snapshot) = event.terminal_snapshot.as_deref() {
+        question_dedup_key(&event.agent_id, snapshot)
+    } else {
+        let fallback_content = format!(
+            "{}:{}",
+            event_type_to_string(&event.event_type),
+            event.agent_id
+        );
+        generate_dedup_key(&fallback_content)
+    }
+}
+
 /// Convert NotificationEventType to a string for dedup key generation
 /// Used when terminal_snapshot is not available
 fn event_type_to_string(event_type: &NotificationEventType) -> String {
@@ -82,15 +170,28 @@ pub struct OpenclawNotifier {
     /// Optional defaults for webhook delivery routing
     webhook_default_channel: Option<String>,
     webhook_default_to: Option<String>,
+    /// 消息格式（按 webhook 渠道能力选择）
+    message_profile: ChannelProfile,
+    /// 用户通知模板（`~/.config/code-agent-monitor/templates/`）
+    templates: NotificationTemplates,
     /// Payload 构建器
     payload_builder: PayloadBuilder,
+    /// 快照隐私模式（`privacy.local_snapshots`）：终端快照不离开本机
+    local_snapshots: bool,
     /// 通知去重器
     deduplicator: Mutex<NotificationDeduplicator>,
+    /// 通知速率限制器（令牌桶 + 摘要队列）
+    rate_limiter: Mutex<RateLimiter>,
+    /// 重放时钟（Unix 秒）；设置后去重按该时间判断且不读写磁盘状态
+    replay_clock: Option<Arc<AtomicU64>>,
+    /// 读取 Team 配置中按成员的投递目标（`camRoutes`）
+    team_bridge: TeamBridge,
 }
 
 impl OpenclawNotifier {
     /// 创建新的通知器
     pub fn new() -> Self {
+        let local_snapshots = PrivacyConfig::load().local_snapshots;
         Self {
             openclaw_cmd: Self::find_openclaw_path(),
             dry_run: false,
@@ -98,8 +199,14 @@ impl OpenclawNotifier {
             webhook_client: None,
             webhook_default_channel: None,
             webhook_default_to: None,
-            payload_builder: PayloadBuilder::new(),
+            message_profile: ChannelProfile::default(),
+            templates: NotificationTemplates::default(),
+            payload_builder: PayloadBuilder::new().with_local_snapshots(local_snapshots),
+            local_snapshots,
             deduplicator: Mutex::new(NotificationDeduplicator::new()),
+            rate_limiter: Mutex::new(RateLimiter::new()),
+            replay_clock: None,
+            team_bridge: TeamBridge::new(),
         }
     }
 
@@ -107,7 +214,12 @@ impl OpenclawNotifier {
     pub fn with_webhook(config: WebhookConfig) -> Result<Self, String> {
         let webhook_default_channel = config.default_channel.clone();
         let webhook_default_to = config.default_to.clone();
+        let mut message_profile = ChannelProfile::for_channel(webhook_default_channel.as_deref());
+        if let Some(markup) = config.format.as_deref().and_then(MarkupFlavor::parse) {
+            message_profile = message_profile.with_markup(markup);
+        }
         let webhook_client = WebhookClient::new(config)?;
+        let local_snapshots = PrivacyConfig::load().local_snapshots;
         Ok(Self {
             openclaw_cmd: Self::find_openclaw_path(),
             dry_run: false,
@@ -115,8 +227,14 @@ impl OpenclawNotifier {
             webhook_client: Some(webhook_client),
             webhook_default_channel,
             webhook_default_to,
-            payload_builder: PayloadBuilder::new(),
+            message_profile,
+            templates: NotificationTemplates::load(),
+            payload_builder: PayloadBuilder::new().with_local_snapshots(local_snapshots),
+            local_snapshots,
             deduplicator: Mutex::new(NotificationDeduplicator::new()),
+            rate_limiter: Mutex::new(RateLimiter::new()),
+            replay_clock: None,
+            team_bridge: TeamBridge::new(),
         })
     }
 
@@ -126,6 +244,21 @@ impl OpenclawNotifier {
         self
     }
 
+    /// 使用重放时钟（用于 `cam replay`）：去重按录制时间判断，不影响真实去重状态
+    pub fn with_replay_clock(mut self, clock: Arc<AtomicU64>) -> Self {
+        self.deduplicator = Mutex::new(NotificationDeduplicator::new_without_persistence());
+        self.rate_limiter =
+            Mutex::new(RateLimiter::new_without_persistence(RateLimitConfig::load()));
+        self.replay_clock = Some(clock);
+        self
+    }
+
+    /// 使用指定目录的 Team 配置解析成员投递目标（用于测试）
+    pub fn with_team_bridge(mut self, team_bridge: TeamBridge) -> Self {
+        self.team_bridge = team_bridge;
+        self
+    }
+
     /// 设置是否禁用 AI 提取
     pub fn with_no_ai(mut self, no_ai: bool) -> Self {
         self.no_ai = no_ai;
@@ -301,6 +434,29 @@ impl OpenclawNotifier {
             NotificationEventType::SessionEnd => "session_end",
         };
 
+        // 错误分类：关键字优先，无法识别时用 AI 兜底
+        let error_category = match &event.event_type {
+            NotificationEventType::Error { message } => Some(self.classify_error(message)),
+            _ => None,
+        };
+
+        // 等待输入分类：先按模式和终端文本判断，AI 提取后再细化（无快照时不分类，按 HIGH 处理）
+        let mut waiting_kind = match (&event.event_type, event.terminal_snapshot.as_deref()) {
+            (
+                NotificationEventType::WaitingForInput {
+                    pattern_type,
+                    is_decision_required,
+                },
+                Some(snapshot),
+            ) => Some(classify_waiting(
+                pattern_type,
+                *is_decision_required,
+                None,
+                &snapshot_tail(snapshot, WAITING_TAIL_LINES),
+            )),
+            _ => None,
+        };
+
         let context_for_urgency = match &event.event_type {
             NotificationEventType::Notification {
                 notification_type,
@@ -310,10 +466,18 @@ impl OpenclawNotifier {
                 "message": message
             })
             .to_string(),
+            NotificationEventType::Error { .. } => serde_json::json!({
+                "error_category": error_category.map(|c| c.as_str())
+            })
+            .to_string(),
+            NotificationEventType::WaitingForInput { .. } => serde_json::json!({
+                "waiting_kind": waiting_kind.map(|k| k.as_str())
+            })
+            .to_string(),
             _ => String::new(),
         };
 
-        let urgency = get_urgency(event_type_str, &context_for_urgency);
+        let mut urgency = get_urgency(event_type_str, &context_for_urgency);
 
         // LOW urgency 静默处理
         if matches!(urgency, Urgency::Low) {
@@ -324,29 +488,182 @@ impl OpenclawNotifier {
             )));
         }
 
+        // 聊天中 `snooze` 暂停期间只发送 HIGH 通知（重放时不读取真实状态）
+        if !matches!(urgency, Urgency::High) && self.replay_clock.is_none() {
+            if let Some(until) = SnoozeState::load().snoozed_until(agent_id, chrono::Utc::now()) {
+                debug!(agent_id = %agent_id, until = %until, "Notification skipped (snoozed)");
+                return Ok(SendResult::Skipped("snoozed".to_string()));
+            }
+        }
+
+        // 同类错误反复出现：提交 issue 并只通知一次（重放和 dry-run 不提交）
+        let mut filed_issue = None;
+        if let (Some(category), NotificationEventType::Error { message }) =
+            (error_category, &event.event_type)
+        {
+            let config = ErrorFilingConfig::load();
+            if config.enabled
+                && category != ErrorCategory::Other
+                && self.replay_clock.is_none()
+                && !self.dry_run
+            {
+                let path = ErrorFilingState::path();
+                let mut state = ErrorFilingState::load_from(&path);
+                match state.record(agent_id, category, &config, chrono::Utc::now()) {
+                    FilingDecision::Notify => {}
+                    FilingDecision::Filed { url } => {
+                        if let Err(e) = state.save_to(&path) {
+                            warn!(error = %e, "Failed to save error filing state");
+                        }
+                        debug!(agent_id = %agent_id, url = %url, "Repeated error already filed");
+                        return Ok(SendResult::Skipped(format!("error filed: {}", url)));
+                    }
+                    FilingDecision::File { count } => {
+                        let report = error_report(
+                            event,
+                            category,
+                            message,
+                            count,
+                            &config,
+                            self.local_snapshots,
+                        );
+                        match file_report(&config, &report) {
+                            Ok(url) => {
+                                log_to_hook_file(&format!(
+                                    "🐛 Filed repeated error: agent={} category={} {}",
+                                    agent_id,
+                                    category.as_str(),
+                                    url
+                                ));
+                                state.mark_filed(agent_id, category, &url, chrono::Utc::now());
+                                filed_issue = Some(url);
+                            }
+                            Err(e) => {
+                                warn!(agent_id = %agent_id, error = %e, "Failed to file repeated error")
+                            }
+                        }
+                    }
+                }
+                if let Err(e) = state.save_to(&path) {
+                    warn!(error = %e, "Failed to save error filing state");
+                }
+            }
+        }
+
         // 去重检查
-        let dedup_key = if let Some(ref key) = event.dedup_key {
-            key.clone()
-        } else if let Some(snapshot) = event.terminal_snapshot.as_deref() {
-            let truncated = truncate_for_status(snapshot);
-            generate_dedup_key(&truncated)
-        } else {
-            let fallback_content =
-                format!("{}:{}", event_type_to_string(&event.event_type), agent_id);
-            generate_dedup_key(&fallback_content)
-        };
+        let dedup_key = event_dedup_key(event);
 
-        if !event.skip_dedup {
+        if !event.skip_dedup && filed_issue.is_none() {
             let mut dedup = self.deduplicator.lock().unwrap();
-            let action = dedup.should_send(agent_id, &dedup_key);
-            if let crate::notification::NotifyAction::Suppressed(reason) = action {
-                debug!(agent_id = %agent_id, reason = %reason, "Notification deduplicated");
-                return Ok(SendResult::Skipped("duplicate".to_string()));
+            let action = match self.replay_clock {
+                Some(ref clock) => {
+                    dedup.should_send_at(agent_id, &dedup_key, clock.load(Ordering::SeqCst))
+                }
+                None => dedup.should_send(agent_id, &dedup_key),
+            };
+            match action {
+                crate::notification::NotifyAction::Suppressed(reason) => {
+                    debug!(agent_id = %agent_id, reason = %reason, "Notification deduplicated");
+                    return Ok(SendResult::Skipped("duplicate".to_string()));
+                }
+                // 用户已通过 `cam ack` 确认收到，不再提醒（重放时不读取真实状态）
+                crate::notification::NotifyAction::SendReminder
+                    if self.replay_clock.is_none()
+                        && ConversationStateManager::new().is_acknowledged(agent_id) =>
+                {
+                    debug!(agent_id = %agent_id, "Reminder suppressed (acknowledged)");
+                    return Ok(SendResult::Skipped("acknowledged".to_string()));
+                }
+                _ => {}
             }
         }
 
+        // 通知摘要（用于限流摘要和本地记录）
+        let summary = match &event.event_type {
+            NotificationEventType::PermissionRequest { tool_name, .. } => {
+                format!("Permission: {}", tool_name)
+            }
+            NotificationEventType::WaitingForInput { pattern_type, .. } => {
+                format!("Waiting: {}", pattern_type)
+            }
+            NotificationEventType::Notification {
+                notification_type,
+                message,
+            } => {
+                if message.is_empty() {
+                    notification_type.clone()
+                } else {
+                    truncate_width(message, SUMMARY_MAX_WIDTH)
+                }
+            }
+            NotificationEventType::Error { message } => {
+                format!("Error: {}", truncate_width(message, SUMMARY_MAX_WIDTH - 20))
+            }
+            NotificationEventType::AgentExited => "Agent exited".to_string(),
+            NotificationEventType::Stop => "Stopped".to_string(),
+            NotificationEventType::SessionStart => "Session started".to_string(),
+            NotificationEventType::SessionEnd => "Session ended".to_string(),
+        };
+
+        // 速率限制：超出令牌桶的通知进入摘要队列，随下一条放行的通知发送
+        // dry-run 不消耗令牌；重放时按录制时间在内存中计算
+        // 需要用户回复的通知不限流：摘要行不含问题和选项，agent 会一直等待
+        let needs_reply = matches!(urgency, Urgency::High)
+            || matches!(
+                event.event_type,
+                NotificationEventType::PermissionRequest { .. }
+                    | NotificationEventType::WaitingForInput { .. }
+            );
+        let digest = match self.replay_clock {
+            _ if needs_reply && self.dry_run => RateDecision::Allow { digest: Vec::new() },
+            _ if needs_reply => RateDecision::Allow {
+                digest: self
+                    .rate_limiter
+                    .lock()
+                    .unwrap()
+                    .pass_through(&self.rate_limit_channel()),
+            },
+            Some(ref clock) => self.rate_limiter.lock().unwrap().check_at(
+                agent_id,
+                event_type_str,
+                &self.rate_limit_channel(),
+                &summary,
+                clock.load(Ordering::SeqCst),
+            ),
+            None if self.dry_run => RateDecision::Allow { digest: Vec::new() },
+            None => self.rate_limiter.lock().unwrap().check(
+                agent_id,
+                event_type_str,
+                &self.rate_limit_channel(),
+                &summary,
+            ),
+        };
+        let digest = match digest {
+            RateDecision::Allow { digest } => digest,
+            RateDecision::Spilled(reason) => {
+                debug!(agent_id = %agent_id, reason = %reason, "Notification rate limited");
+                log_to_hook_file(&format!(
+                    "⏳ Rate limited: agent={} event={} ({})",
+                    agent_id, event_type_str, reason
+                ));
+                return Ok(SendResult::Skipped(format!("rate limited: {}", reason)));
+            }
+        };
+
         // 构建并发送 system event
         let mut payload = SystemEventPayload::from_event(event, urgency);
+        if let Some(category) = error_category {
+            payload.set_error_category(category);
+        }
+        if filed_issue.is_some() {
+            payload.set_issue_url(filed_issue);
+        }
+        if !digest.is_empty() {
+            payload.set_digest(digest.iter().map(|entry| entry.line()).collect());
+        }
+        if let Some(kind) = waiting_kind {
+            payload.set_waiting_kind(kind);
+        }
 
         // 对于需要用户输入的事件，使用 ReAct 提取器提取格式化消息
         // 只在确定要发送时才调用，避免浪费 API 调用
@@ -358,10 +675,10 @@ impl OpenclawNotifier {
                         | NotificationEventType::PermissionRequest { .. }
                 ) {
                     match extract_message_from_snapshot(snapshot) {
-                        Some((message, fingerprint, is_decision_required)) => {
+                        Some(extracted) => {
                             // 检查是否是错误消息，如果是则升级为 Error 事件
-                            if message.starts_with("ERROR: ") {
-                                let error_msg = message.strip_prefix("ERROR: ").unwrap_or(&message).to_string();
+                            if let Some(error_msg) = extracted.content.strip_prefix("ERROR: ") {
+                                let error_msg = error_msg.to_string();
                                 info!(
                                     agent_id = %agent_id,
                                     error = %error_msg,
@@ -374,22 +691,40 @@ impl OpenclawNotifier {
                                 .with_terminal_snapshot(snapshot.clone())
                                 .with_skip_dedup(event.skip_dedup);
                                 if let Some(ref project) = event.project_path {
-                                    return self.send_system_event_only(&error_event.with_project_path(project));
+                                    return self.send_system_event_only(
+                                        &error_event.with_project_path(project),
+                                    );
                                 }
                                 return self.send_system_event_only(&error_event);
                             }
                             debug!(
                                 agent_id = %agent_id,
-                                fingerprint = %fingerprint,
-                                is_decision_required = %is_decision_required,
+                                fingerprint = %extracted.fingerprint,
+                                is_decision_required = %extracted.is_decision_required,
                                 "ReAct extracted formatted message"
                             );
-                            payload.set_extracted_message(message, fingerprint);
+                            // Refine the waiting classification with the extractor's message type
+                            if let NotificationEventType::WaitingForInput {
+                                pattern_type,
+                                is_decision_required,
+                            } = &event.event_type
+                            {
+                                let kind = classify_waiting(
+                                    pattern_type,
+                                    *is_decision_required || extracted.is_decision_required,
+                                    Some(&extracted.message_type),
+                                    &extracted.content,
+                                );
+                                payload.set_waiting_kind(kind);
+                                waiting_kind = Some(kind);
+                                urgency = kind.urgency();
+                            }
                             // Upgrade decision_required if AI extractor detected it
                             // (initial event from InputWaitDetector may have false)
-                            if is_decision_required {
+                            if extracted.is_decision_required {
                                 payload.set_decision_required(true);
                             }
+                            payload.set_extracted_message(extracted.content, extracted.fingerprint);
                         }
                         None => {
                             debug!(
@@ -402,6 +737,29 @@ impl OpenclawNotifier {
             }
         }
 
+        // 回答记忆：附带相同问题上次的回答，并记录 agent 当前的问题（重放时不读写真实状态）
+        if self.replay_clock.is_none() {
+            self.apply_answer_memory(agent_id, event.project_path.as_deref(), &mut payload);
+        }
+
+        // 附带 transcript 中最近的助手消息，问题引用的内容可能已滚出屏幕（重放时不读取真实状态）
+        if self.replay_clock.is_none() && event.needs_reply() {
+            payload.set_recent_messages(TranscriptContextConfig::load().recent_for_agent(agent_id));
+        }
+        // 完成类通知附带 Claude Code 的 TODO 进度（"4/6 todos done"）
+        if self.replay_clock.is_none() && is_completion(&event.event_type) {
+            if let Ok(Some(agent)) = AgentManager::new().get_agent(agent_id) {
+                payload.set_todos(TodoProgress::of(&agent_todos(&agent)));
+            }
+        }
+
+        // 快照隐私模式：快照只写入本地记录，消息中提示 `cam show <id>`
+        let sent_at = chrono::Utc::now();
+        let notification_id = new_notification_id(agent_id, sent_at);
+        if self.local_snapshots {
+            payload.keep_snapshot_local(&notification_id);
+        }
+
         if self.dry_run {
             eprintln!("[DRY-RUN] Would send system event:");
             eprintln!(
@@ -413,7 +771,24 @@ impl OpenclawNotifier {
 
         // If a webhook is configured, prefer it (single-channel delivery).
         // This is especially important for reply-required events so OpenClaw hooks/skills can run.
-        self.send_via_gateway_async(&payload.to_json())?;
+        let delivery = self.send_via_gateway_async(&payload.to_json())?;
+
+        // 记录投递延迟（重放时事件时间是录制时间，不记录）
+        if self.replay_clock.is_none() {
+            self.record_latency(event, event_type_str, delivery.channel.as_deref());
+            EventBus::new().publish(
+                "notification",
+                Some(agent_id),
+                serde_json::json!({
+                    "event": event_type_str,
+                    "urgency": urgency.as_str(),
+                    "channel": delivery.channel.as_deref().unwrap_or("openclaw"),
+                    "message_id": delivery.message_id,
+                    "hop": delivery.hop,
+                    "failed_channels": delivery.failed,
+                }),
+            );
+        }
 
         // 记录详细的发送内容到 hook.log
         log_to_hook_file(&format!(
@@ -433,31 +808,6 @@ impl OpenclawNotifier {
         }
 
         // 记录到本地文件（供 TUI 显示）
-        let summary = match &event.event_type {
-            NotificationEventType::PermissionRequest { tool_name, .. } => {
-                format!("Permission: {}", tool_name)
-            }
-            NotificationEventType::WaitingForInput { pattern_type, .. } => {
-                format!("Waiting: {}", pattern_type)
-            }
-            NotificationEventType::Notification {
-                notification_type,
-                message,
-            } => {
-                if message.is_empty() {
-                    notification_type.clone()
-                } else {
-                    message.chars().take(80).collect()
-                }
-            }
-            NotificationEventType::Error { message } => {
-                format!("Error: {}", message.chars().take(60).collect::<String>())
-            }
-            NotificationEventType::AgentExited => "Agent exited".to_string(),
-            NotificationEventType::Stop => "Stopped".to_string(),
-            NotificationEventType::SessionStart => "Session started".to_string(),
-            NotificationEventType::SessionEnd => "Session ended".to_string(),
-        };
 
         // Build event_detail JSON from event type
         let event_detail = match &event.event_type {
@@ -474,6 +824,7 @@ impl OpenclawNotifier {
             } => Some(serde_json::json!({
                 "pattern_type": pattern_type,
                 "is_decision_required": is_decision_required,
+                "waiting_kind": waiting_kind.map(|k| k.as_str()),
             })),
             NotificationEventType::Notification {
                 notification_type,
@@ -496,7 +847,8 @@ impl OpenclawNotifier {
             .and_then(|_| Some("AI_EXTRACTED".to_string()));
 
         let record = NotificationRecord {
-            ts: chrono::Utc::now(),
+            ts: sent_at,
+            id: Some(notification_id),
             agent_id: agent_id.clone(),
             urgency,
             event: event_type_str.to_string(),
@@ -505,6 +857,8 @@ impl OpenclawNotifier {
             event_detail,
             terminal_snapshot: event.terminal_snapshot.clone(),
             risk_level,
+            message_id: delivery.message_id,
+            reply_to: delivery.reply_to,
         };
         if let Err(e) = NotificationStore::append(&record) {
             warn!(error = %e, "Failed to write notification to local file");
@@ -520,10 +874,153 @@ impl OpenclawNotifier {
         Ok(SendResult::Sent)
     }
 
+    /// 记录一次投递延迟，刚开始持续超过 `latency_slo` 时发送告警
+    fn record_latency(&self, event: &NotificationEvent, event_type: &str, channel: Option<&str>) {
+        let log = LatencyLog::new();
+        let sample = LatencySample {
+            ts: chrono::Utc::now(),
+            agent_id: event.agent_id.clone(),
+            event: event_type.to_string(),
+            channel: channel.unwrap_or("openclaw").to_string(),
+            latency_ms: (chrono::Utc::now() - event.timestamp)
+                .num_milliseconds()
+                .max(0) as u64,
+        };
+        if let Err(e) = log.append(&sample) {
+            warn!(error = %e, "Failed to record notification latency");
+            return;
+        }
+
+        let Some(slo) = LatencySlo::load() else {
+            return;
+        };
+        let samples = log.load();
+        if !slo.newly_breached(&samples) {
+            return;
+        }
+        let recent = &samples[samples.len() - slo.window.max(1)..];
+        let Some(stats) = LatencyStats::compute(recent.iter().map(|s| s.latency_ms)) else {
+            return;
+        };
+        warn!(
+            p50_ms = stats.p50_ms,
+            max_ms = stats.max_ms,
+            slo_secs = slo.max_secs,
+            "Notification latency exceeds SLO"
+        );
+        let message = format!(
+            "通知投递延迟持续超标：最近 {} 条中位数 {}，最慢 {}（SLO {}s）",
+            stats.count,
+            format_latency(stats.p50_ms),
+            format_latency(stats.max_ms),
+            slo.max_secs
+        );
+        let alert = NotificationEvent::notification(&event.agent_id, "latency_slo", message)
+            .with_skip_dedup(true);
+        if let Err(e) = self.send_system_event_only(&alert) {
+            warn!(error = %e, "Failed to send latency alert");
+        }
+    }
+
+    /// 按问题指纹查找上次的回答，并记录 agent 当前等待回答的问题
+    fn apply_answer_memory(
+        &self,
+        agent_id: &str,
+        project: Option<&str>,
+        payload: &mut crate::notification::system_event::SystemEventPayload,
+    ) {
+        let Some(fingerprint) = payload
+            .context
+            .question_fingerprint
+            .clone()
+            .filter(|fingerprint| !fingerprint.is_empty())
+        else {
+            return;
+        };
+        let Some(mut memory) = AnswerMemory::load() else {
+            return;
+        };
+        let project = project.unwrap_or("");
+        payload.set_previous_answer(memory.previous_answer(project, &fingerprint).cloned());
+        if self.dry_run {
+            return;
+        }
+        let question = payload
+            .context
+            .extracted_message
+            .clone()
+            .unwrap_or_default();
+        if let Err(e) = memory.record_question(agent_id, project, &fingerprint, &question) {
+            warn!(agent_id = %agent_id, error = %e, "Failed to record answer memory");
+            return;
+        }
+        match memory.schedule_auto_answer(
+            agent_id,
+            payload.context.waiting_kind,
+            &payload.context.risk_level,
+        ) {
+            Ok(delay) => payload.set_auto_answer(delay),
+            Err(e) => warn!(agent_id = %agent_id, error = %e, "Failed to schedule auto answer"),
+        }
+    }
+
+    /// 错误分类（关键字无法识别且未设置 no_ai 时用 AI 兜底）
+    fn classify_error(&self, message: &str) -> ErrorCategory {
+        let category = NotificationSummarizer::new().classify_error(message);
+        if category != ErrorCategory::Other || self.no_ai {
+            return category;
+        }
+        classify_error_with_ai(message).unwrap_or(category)
+    }
+
+    /// 限流使用的渠道名称
+    fn rate_limit_channel(&self) -> String {
+        match (&self.webhook_client, &self.webhook_default_channel) {
+            (Some(_), Some(channel)) => channel.clone(),
+            (Some(_), None) => "webhook".to_string(),
+            (None, _) => "openclaw".to_string(),
+        }
+    }
+
+    /// 发送积压过久的限流摘要（由 watcher 每轮调用）
+    ///
+    /// 摘要队列为空、未到 `digest_flush_secs` 或渠道令牌不足时不发送。
+    pub fn flush_digest(&self) -> Result<SendResult> {
+        use crate::notification::system_event::SystemEventPayload;
+
+        if self.dry_run || self.replay_clock.is_some() {
+            return Ok(SendResult::Skipped("dry run".to_string()));
+        }
+        let channel = self.rate_limit_channel();
+        let Some(digest) = self.rate_limiter.lock().unwrap().take_due_digest(&channel) else {
+            return Ok(SendResult::Skipped("no due digest".to_string()));
+        };
+        let Some(first) = digest.first() else {
+            return Ok(SendResult::Skipped("no due digest".to_string()));
+        };
+
+        let event = NotificationEvent::notification(
+            &first.agent_id,
+            "digest",
+            format!("限流期间积压 {} 条通知", digest.len()),
+        );
+        let mut payload = SystemEventPayload::from_event(&event, Urgency::Medium);
+        payload.set_digest(digest.iter().map(|entry| entry.line()).collect());
+        self.send_via_gateway_async(&payload.to_json())?;
+
+        log_to_hook_file(&format!(
+            "📤 Digest sent: channel={} entries={}",
+            channel,
+            digest.len()
+        ));
+        info!(channel = %channel, entries = digest.len(), "Rate limit digest sent");
+        Ok(SendResult::Sent)
+    }
+
     /// 发送 system event 到 Gateway 并等待 Agent 处理
     ///
     /// 使用 --expect-final 等待 Agent 完成处理，确保通知被发送到用户
-    fn send_via_gateway_async(&self, payload: &serde_json::Value) -> Result<()> {
+    fn send_via_gateway_async(&self, payload: &serde_json::Value) -> Result<Delivery> {
         // 如果配置了 webhook client，优先使用 webhook
         if let Some(ref _client) = self.webhook_client {
             return self.send_via_webhook(payload);
@@ -535,7 +1032,7 @@ impl OpenclawNotifier {
                 "[DRY-RUN] Payload: {}",
                 serde_json::to_string_pretty(payload).unwrap_or_default()
             );
-            return Ok(());
+            return Ok(Delivery::default());
         }
 
         let payload_text = payload.to_string();
@@ -559,7 +1056,7 @@ impl OpenclawNotifier {
         match output {
             Ok(out) => {
                 if out.status.success() {
-                    Ok(())
+                    Ok(Delivery::default())
                 } else {
                     let stderr = String::from_utf8_lossy(&out.stderr);
                     error!(status = ?out.status, stderr = %stderr, "System event command failed");
@@ -573,84 +1070,200 @@ impl OpenclawNotifier {
         }
     }
 
-    /// 通过 Webhook 发送通知 (推荐方案)
-    fn send_via_webhook(&self, payload: &serde_json::Value) -> anyhow::Result<()> {
-        if let Some(ref client) = self.webhook_client {
-            // 从 payload 中提取消息内容，优先使用格式化消息
-            // NOTE: SystemEventPayload 使用 camelCase (eventType)，旧版 PayloadBuilder 使用 snake_case (event_type)
-            let message =
-                if payload.get("eventType").is_some() || payload.get("event_type").is_some() {
-                    // 这是 SystemEventPayload 格式，使用格式化消息
-                    use crate::notification::system_event::SystemEventPayload;
-                    if let Ok(sep) = serde_json::from_value::<SystemEventPayload>(payload.clone()) {
-                        let mut msg = sep.to_telegram_message();
-
-                        // For reply-required events, include raw JSON so hooks/skills (and humans) have full context.
-                        if matches!(
-                            sep.event_type.as_str(),
-                            "permission_request" | "waiting_for_input"
-                        ) {
-                            let raw = serde_json::to_string_pretty(payload).unwrap_or_default();
-                            let max_chars = 3500usize;
-                            let raw_trunc: String = raw.chars().take(max_chars).collect();
-                            msg.push_str("\n\n---\nraw_event_json:\n```json\n");
-                            msg.push_str(&raw_trunc);
-                            if raw.len() > max_chars {
-                                msg.push_str("\n... (truncated)");
-                            }
-                            msg.push_str("\n```\n");
-                        }
+    /// 启用线程且渠道支持回复时返回渠道名
+    fn thread_channel<'a>(client: &WebhookClient, channel: Option<&'a str>) -> Option<&'a str> {
+        let channel = channel?;
+        (client.threads_enabled() && channel_supports_threads(channel)).then_some(channel)
+    }
 
-                        msg
-                    } else {
-                        payload
-                            .get("message")
-                            .and_then(|m| m.as_str())
-                            .unwrap_or("Agent notification")
-                            .to_string()
-                    }
-                } else {
-                    payload
-                        .get("message")
-                        .and_then(|m| m.as_str())
-                        .unwrap_or("Agent notification")
-                        .to_string()
-                };
-
-            // 支持 camelCase (agentId) 和 snake_case (agent_id)
-            let agent_id = payload
-                .get("agentId")
-                .or_else(|| payload.get("agent_id"))
+    /// 投递渠道和目标：agent 是 Team 成员且配置了 `camRoutes` 时使用成员的目标，
+    /// 否则按等待输入分类匹配 `webhook.routes`，都未配置时使用默认值
+    fn delivery_target(
+        &self,
+        client: &WebhookClient,
+        payload: &serde_json::Value,
+    ) -> (Option<String>, Option<String>) {
+        let member_route = payload
+            .get("agent_id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| self.team_bridge.member_route(id));
+        let route: Option<&WebhookRoute> = member_route.as_ref().or_else(|| {
+            payload
+                .pointer("/context/waitingKind")
+                .and_then(|kind| kind.as_str())
+                .and_then(|kind| client.route(kind))
+        });
+        match route {
+            Some(route) if route.channel.is_some() => (route.channel.clone(), route.to.clone()),
+            Some(route) => (
+                self.webhook_default_channel.clone(),
+                route.to.clone().or_else(|| self.webhook_default_to.clone()),
+            ),
+            None => (
+                self.webhook_default_channel.clone(),
+                self.webhook_default_to.clone(),
+            ),
+        }
+    }
+
+    /// 按渠道格式化 webhook 消息，优先使用格式化的 SystemEventPayload
+    fn webhook_message(&self, payload: &serde_json::Value, channel: Option<&str>) -> String {
+        // 路由到其他渠道时按该渠道的能力选择消息格式
+        let profile = if channel == self.webhook_default_channel.as_deref() {
+            self.message_profile
+        } else {
+            ChannelProfile::for_channel(channel)
+        };
+        let fallback = || {
+            payload
+                .get("message")
                 .and_then(|m| m.as_str())
-                .map(String::from);
+                .unwrap_or("Agent notification")
+                .to_string()
+        };
+
+        // NOTE: SystemEventPayload 使用 camelCase (eventType)，旧版 PayloadBuilder 使用 snake_case (event_type)
+        if payload.get("eventType").is_none() && payload.get("event_type").is_none() {
+            return fallback();
+        }
+        use crate::notification::system_event::SystemEventPayload;
+        match serde_json::from_value::<SystemEventPayload>(payload.clone()) {
+            // For reply-required events, include raw JSON so hooks/skills (and humans) have full context.
+            Ok(sep) => MessageFormatter::new(profile)
+                .with_templates(self.templates.clone())
+                .with_local_snapshots(self.local_snapshots)
+                .with_raw_json(true)
+                .format_system_event(&sep),
+            Err(_) => fallback(),
+        }
+    }
 
-            let agent_id_for_log = agent_id.clone();
+    /// 投递链：首选渠道，之后是 `webhook.failover` 中的备用渠道（去重）
+    fn delivery_hops(
+        &self,
+        client: &WebhookClient,
+        payload: &serde_json::Value,
+    ) -> Vec<(Option<String>, Option<String>)> {
+        let mut hops = vec![self.delivery_target(client, payload)];
+        for route in client
+            .failover()
+            .map(|c| c.channels.as_slice())
+            .unwrap_or(&[])
+        {
+            let hop = (route.channel.clone(), route.to.clone());
+            if !hops.contains(&hop) {
+                hops.push(hop);
+            }
+        }
+        hops
+    }
 
+    /// 通过 Webhook 发送通知 (推荐方案)
+    ///
+    /// 配置了 `webhook.failover` 时，某一跳在超时内没有确认送达就改用下一个渠道，
+    /// 返回的 [`Delivery`] 记录成功的是第几跳。
+    fn send_via_webhook(&self, payload: &serde_json::Value) -> anyhow::Result<Delivery> {
+        let Some(ref client) = self.webhook_client else {
+            anyhow::bail!("Webhook client not configured")
+        };
+
+        // 支持 camelCase (agentId) 和 snake_case (agent_id)
+        let agent_id = payload
+            .get("agentId")
+            .or_else(|| payload.get("agent_id"))
+            .and_then(|m| m.as_str())
+            .map(String::from);
+
+        let hops = self.delivery_hops(client, payload);
+        let hop_timeout = client.failover().map(|c| c.timeout_secs);
+        let mut failed = Vec::new();
+        for (hop, (channel, to)) in hops.iter().enumerate() {
+            let message = self.webhook_message(payload, channel.as_deref());
+
+            // 同一 agent 的后续通知回复到线程首条消息
+            let thread_channel = Self::thread_channel(client, channel.as_deref());
+            let reply_to = thread_channel
+                .zip(agent_id.as_deref())
+                .and_then(|(channel, agent_id)| NotificationStore::thread_root(channel, agent_id));
+
+            // 后面还有备用渠道时按 failover 超时等待确认，否则使用 webhook 超时
+            let timeout = match hop_timeout {
+                Some(secs) if hop + 1 < hops.len() => secs,
+                _ => client.timeout_secs(),
+            };
             // 使用阻塞版本发送（避免在 async runtime 中创建新 runtime）
-            let result = client.send_notification_blocking(
+            let result = client.send_reply_blocking_within(
                 message,
-                agent_id,
-                self.webhook_default_channel.clone(),
-                self.webhook_default_to.clone(),
+                agent_id.clone(),
+                channel.clone(),
+                to.clone(),
+                reply_to.clone(),
+                std::time::Duration::from_secs(timeout),
             );
 
+            let channel_name = channel.clone().unwrap_or_else(|| "webhook".to_string());
             match result {
-                Ok(resp) => {
-                    if resp.ok {
-                        info!(agent_id = ?agent_id_for_log, "Webhook notification sent successfully");
-                        Ok(())
-                    } else {
-                        anyhow::bail!("Webhook failed: {:?}", resp.error)
+                Ok(resp) if resp.ok => {
+                    info!(agent_id = ?agent_id, channel = %channel_name, hop, "Webhook notification sent successfully");
+                    if hop > 0 {
+                        log_to_hook_file(&format!(
+                            "🔁 Failover: delivered via {} (hop {}) after {} failed",
+                            channel_name,
+                            hop,
+                            failed.join(", ")
+                        ));
                     }
+                    if let (Some(channel), Some(agent_id), Some(message_id)) =
+                        (thread_channel, &agent_id, &resp.message_id)
+                    {
+                        if let Err(e) =
+                            NotificationStore::record_thread_message(channel, agent_id, message_id)
+                        {
+                            warn!(error = %e, "Failed to record notification thread");
+                        }
+                    }
+                    return Ok(Delivery {
+                        message_id: resp.message_id,
+                        reply_to,
+                        channel: Some(channel_name),
+                        hop,
+                        failed,
+                    });
+                }
+                Ok(resp) => {
+                    error!(channel = %channel_name, hop, error = ?resp.error, "Webhook notification rejected");
+                    failed.push(channel_name);
                 }
                 Err(e) => {
-                    error!(error = %e, "Failed to send webhook notification");
-                    anyhow::bail!("Webhook error: {}", e)
+                    error!(channel = %channel_name, hop, error = %e, "Failed to send webhook notification");
+                    failed.push(channel_name);
                 }
             }
-        } else {
-            anyhow::bail!("Webhook client not configured")
         }
+        anyhow::bail!("Webhook error: all channels failed ({})", failed.join(", "))
+    }
+}
+
+/// 一次投递的渠道消息信息
+#[derive(Debug, Default)]
+struct Delivery {
+    message_id: Option<String>,
+    reply_to: Option<String>,
+    /// 投递渠道（用于延迟统计）
+    channel: Option<String>,
+    /// 成功的是投递链中的第几跳（0 为首选渠道）
+    hop: usize,
+    /// 之前失败的渠道
+    failed: Vec<String>,
+}
+
+impl Notifier for OpenclawNotifier {
+    fn name(&self) -> &str {
+        "openclaw"
+    }
+
+    fn send_notification_event(&self, event: &NotificationEvent) -> Result<SendResult> {
+        OpenclawNotifier::send_notification_event(self, event)
     }
 }
 
@@ -970,6 +1583,53 @@ $ cargo build
         );
     }
 
+    #[test]
+    fn test_hook_and_watcher_dedup_keys_match() {
+        use crate::agent::engine::to_notification;
+        use crate::agent::WatchEvent;
+        use crate::infra::terminal::truncate_for_status;
+
+        // 同一时刻：hook 取 50 行日志，watcher 截取屏幕后保留 30 行
+        let mut screen: Vec<String> = (0..45).map(|i| format!("⏺ Read file {}.rs", i)).collect();
+        screen.push("✻ Churned for 48s · ↓ 2.1k tokens".to_string());
+        screen.push("Do you want to proceed?".to_string());
+        screen.push("❯ 1. Yes".to_string());
+        screen.push("  2. No, and tell Claude what to do differently".to_string());
+        screen.push("─".repeat(40));
+        let hook_screen = screen.join("\n");
+        // watcher 稍晚一轮截屏，状态栏计时已变化
+        let watcher_screen = hook_screen.replace("48s · ↓ 2.1k", "1m 2s · ↓ 2.3k");
+
+        let payload = serde_json::json!({"notification_type": "idle_prompt", "message": ""});
+        let hook_event = NotificationEvent::from_hook(
+            "cam-1",
+            "notification",
+            Some(&payload),
+            "",
+            Some(&hook_screen),
+        );
+
+        let context = truncate_for_status(&watcher_screen);
+        let watch_event = WatchEvent::WaitingForInput {
+            agent_id: "cam-1".to_string(),
+            pattern_type: "Confirmation".to_string(),
+            dedup_key: question_dedup_key("cam-1", &context),
+            context,
+            is_decision_required: false,
+        };
+        let watcher_event = to_notification(&watch_event, |_| String::new(), false).unwrap();
+
+        assert_eq!(
+            event_dedup_key(&hook_event),
+            event_dedup_key(&watcher_event)
+        );
+
+        // 问题变化后键不同
+        let next = hook_screen.replace("proceed?", "overwrite config.json?");
+        let next_event = hook_event.clone().with_terminal_snapshot(next);
+        assert_ne!(event_dedup_key(&hook_event), event_dedup_key(&next_event));
+    }
+
     // ==================== Passed dedup_key preference tests ====================
 
     #[test]
@@ -991,4 +1651,124 @@ $ cargo build
             Some("watcher-generated-key-123".to_string())
         );
     }
+
+    #[test]
+    fn test_delivery_target_routes_by_team_member() {
+        let temp = tempfile::tempdir().unwrap();
+        let team_dir = temp.path().join("teams").join("app");
+        std::fs::create_dir_all(&team_dir).unwrap();
+        std::fs::write(
+            team_dir.join("config.json"),
+            serde_json::json!({
+                "members": [
+                    {"name": "backend", "agentId": "backend@app", "agentType": "general-purpose",
+                     "tmuxPaneId": "cam-backend"}
+                ],
+                "camRoutes": {
+                    "frontend": {"channel": "telegram", "to": "alice"},
+                    "backend": {"to": "bob"}
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let config = WebhookConfig {
+            hook_token: "token".to_string(),
+            default_channel: Some("slack".to_string()),
+            default_to: Some("#agents".to_string()),
+            routes: [(
+                "needs_decision".to_string(),
+                WebhookRoute {
+                    channel: None,
+                    to: Some("#decisions".to_string()),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let notifier = OpenclawNotifier::with_webhook(config)
+            .unwrap()
+            .with_team_bridge(TeamBridge::new_with_base_dir(temp.path().to_path_buf()));
+        let client = notifier.webhook_client.as_ref().unwrap();
+        let target = |agent_id: &str| {
+            notifier.delivery_target(
+                client,
+                &serde_json::json!({
+                    "agent_id": agent_id,
+                    "context": { "waitingKind": "needs_decision" }
+                }),
+            )
+        };
+
+        // 成员目标优先于等待分类
+        assert_eq!(
+            target("frontend@app"),
+            (Some("telegram".to_string()), Some("alice".to_string()))
+        );
+        // 通过 tmux session 找到成员，只覆盖接收人
+        assert_eq!(
+            target("cam-backend"),
+            (Some("slack".to_string()), Some("bob".to_string()))
+        );
+        // 非成员 agent 和未配置的成员沿用原有路由
+        assert_eq!(
+            target("cam-other"),
+            (Some("slack".to_string()), Some("#decisions".to_string()))
+        );
+        assert_eq!(
+            target("qa@app"),
+            (Some("slack".to_string()), Some("#decisions".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_delivery_target_routes_by_waiting_kind() {
+        let config = WebhookConfig {
+            hook_token: "token".to_string(),
+            default_channel: Some("telegram".to_string()),
+            default_to: Some("123".to_string()),
+            routes: [
+                (
+                    "informational_pause".to_string(),
+                    WebhookRoute {
+                        channel: Some("slack".to_string()),
+                        to: Some("#agents".to_string()),
+                    },
+                ),
+                (
+                    "blocking_permission".to_string(),
+                    WebhookRoute {
+                        channel: None,
+                        to: Some("456".to_string()),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let notifier = OpenclawNotifier::with_webhook(config).unwrap();
+        let client = notifier.webhook_client.as_ref().unwrap();
+        let target = |kind: &str| {
+            notifier.delivery_target(
+                client,
+                &serde_json::json!({ "context": { "waitingKind": kind } }),
+            )
+        };
+
+        assert_eq!(
+            target("informational_pause"),
+            (Some("slack".to_string()), Some("#agents".to_string()))
+        );
+        assert_eq!(
+            target("blocking_permission"),
+            (Some("telegram".to_string()), Some("456".to_string()))
+        );
+        assert_eq!(
+            target("needs_decision"),
+            (Some("telegram".to_string()), Some("123".to_string()))
+        );
+    }
 }
@@ -0,0 +1,320 @@
+//! 出站队列 - watcher daemon 中按 agent 保序发送通知，并合并已过时的消息
+//!
+//! 轮询线程只负责入队，单个发送线程按入队顺序逐条发送，同一 agent 的消息（如先问题、后
+//! 选项）不会乱序送达，慢渠道（`--expect-final` 最长 60 秒）也不会阻塞轮询。
+//!
+//! 尚未发送的消息被同一 agent 的新消息取代时直接丢弃：
+//! - 新的等待输入取代旧的等待输入（屏幕上的问题已经变了）
+//! - 已在终端中处理取消尚未发出的等待输入，两条都不发送
+//! - 工具批次、卡住、空闲、上下文提醒只保留最新一条
+//! - Agent 退出取代以上所有状态类消息
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use anyhow::Result;
+use tracing::{debug, warn};
+
+use crate::notification::channel::{Notifier, SendResult};
+use crate::notification::event::{NotificationEvent, NotificationEventType};
+use crate::notification::openclaw::OpenclawNotifier;
+
+/// 最多积压的消息数，超过时丢弃最旧的
+pub const MAX_PENDING: usize = 256;
+
+/// 待发送的消息
+#[derive(Debug, Clone)]
+pub enum Outbound {
+    Event(NotificationEvent),
+    ToolUse {
+        agent_id: String,
+        tool_name: String,
+        context: String,
+    },
+}
+
+/// 新消息对积压中同一 agent 旧消息的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Coalesce {
+    Keep,
+    /// 丢弃旧消息
+    Replace,
+    /// 丢弃旧消息，新消息也不再发送
+    Cancel,
+}
+
+impl Outbound {
+    pub fn agent_id(&self) -> &str {
+        match self {
+            Outbound::Event(event) => &event.agent_id,
+            Outbound::ToolUse { agent_id, .. } => agent_id,
+        }
+    }
+
+    /// 状态类消息的合并标签，None 表示总是发送
+    fn tag(&self) -> Option<&str> {
+        let Outbound::Event(event) = self else {
+            return None;
+        };
+        match &event.event_type {
+            NotificationEventType::WaitingForInput { .. } => Some("waiting"),
+            NotificationEventType::Notification {
+                notification_type, ..
+            } => match notification_type.as_str() {
+                "resolved_in_terminal"
+                | "tool_batch"
+                | "agent_stuck"
+                | "agent_idle"
+                | "context_high" => Some(notification_type),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn coalesce(&self, pending: &Outbound) -> Coalesce {
+        if self.agent_id() != pending.agent_id() {
+            return Coalesce::Keep;
+        }
+        let Some(old) = pending.tag() else {
+            return Coalesce::Keep;
+        };
+        let exited = matches!(
+            self,
+            Outbound::Event(NotificationEvent {
+                event_type: NotificationEventType::AgentExited,
+                ..
+            })
+        );
+        match self.tag() {
+            _ if exited => Coalesce::Replace,
+            Some("resolved_in_terminal") if old == "waiting" => Coalesce::Cancel,
+            Some(new) if new == old => Coalesce::Replace,
+            _ => Coalesce::Keep,
+        }
+    }
+
+    /// 通过通知器发送
+    pub fn send(&self, notifier: &OpenclawNotifier) -> Result<SendResult> {
+        match self {
+            Outbound::Event(event) => notifier.send_notification_event(event),
+            Outbound::ToolUse {
+                agent_id,
+                tool_name,
+                context,
+            } => notifier.send_event(agent_id, "ToolUse", tool_name, context),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    pending: VecDeque<Outbound>,
+    closed: bool,
+}
+
+type Shared = Arc<(Mutex<QueueState>, Condvar)>;
+
+/// 单发送线程的出站队列，drop 时发送完积压的消息再退出
+pub struct OutboundQueue {
+    shared: Shared,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl OutboundQueue {
+    /// 启动发送线程，`send` 按入队顺序逐条调用
+    pub fn start(send: impl Fn(&Outbound) + Send + 'static) -> Self {
+        let shared: Shared = Arc::new((Mutex::new(QueueState::default()), Condvar::new()));
+        let worker_shared = Arc::clone(&shared);
+        let worker = std::thread::spawn(move || {
+            let (state, ready) = &*worker_shared;
+            loop {
+                let message = {
+                    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                    loop {
+                        if let Some(message) = state.pending.pop_front() {
+                            break message;
+                        }
+                        if state.closed {
+                            return;
+                        }
+                        state = ready.wait(state).unwrap_or_else(|e| e.into_inner());
+                    }
+                };
+                send(&message);
+            }
+        });
+        Self {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// 使用通知器发送，失败只记录日志；`custom` 中的通知器另外接收所有通知事件（不含工具调用）
+    pub fn for_notifiers(
+        notifier: Option<Arc<OpenclawNotifier>>,
+        custom: Vec<Arc<dyn Notifier>>,
+    ) -> Self {
+        Self::start(move |message| {
+            let agent_id = message.agent_id();
+            if let Some(ref notifier) = notifier {
+                match message.send(notifier) {
+                    Ok(result) => {
+                        debug!(agent_id = %agent_id, result = ?result, "Queued notification sent")
+                    }
+                    Err(e) => warn!(agent_id = %agent_id, error = %e, "Queued notification failed"),
+                }
+            }
+            if let Outbound::Event(event) = message {
+                for notifier in &custom {
+                    if let Err(e) = notifier.send_notification_event(event) {
+                        warn!(agent_id = %agent_id, notifier = notifier.name(), error = %e, "Custom notifier failed");
+                    }
+                }
+            }
+        })
+    }
+
+    /// 入队，先合并同一 agent 尚未发送的过时消息
+    pub fn push(&self, message: Outbound) {
+        let (state, ready) = &*self.shared;
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut cancelled = false;
+        state
+            .pending
+            .retain(|pending| match message.coalesce(pending) {
+                Coalesce::Keep => true,
+                Coalesce::Replace => false,
+                Coalesce::Cancel => {
+                    cancelled = true;
+                    false
+                }
+            });
+        if cancelled {
+            debug!(agent_id = %message.agent_id(), "Pending notification cancelled");
+            return;
+        }
+        if state.pending.len() >= MAX_PENDING {
+            if let Some(dropped) = state.pending.pop_front() {
+                warn!(agent_id = %dropped.agent_id(), "Outbound queue full, dropping oldest notification");
+            }
+        }
+        state.pending.push_back(message);
+        ready.notify_one();
+    }
+
+    /// 尚未发送的消息数
+    pub fn pending(&self) -> usize {
+        let (state, _) = &*self.shared;
+        state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pending
+            .len()
+    }
+}
+
+impl Drop for OutboundQueue {
+    fn drop(&mut self) {
+        let (state, ready) = &*self.shared;
+        state.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+        ready.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn note(agent_id: &str, notification_type: &str, message: &str) -> Outbound {
+        Outbound::Event(NotificationEvent::notification(
+            agent_id,
+            notification_type,
+            message,
+        ))
+    }
+
+    fn waiting(agent_id: &str, pattern_type: &str) -> Outbound {
+        Outbound::Event(NotificationEvent::waiting_for_input(agent_id, pattern_type))
+    }
+
+    fn label(message: &Outbound) -> String {
+        match message {
+            Outbound::Event(event) => match &event.event_type {
+                NotificationEventType::WaitingForInput { pattern_type, .. } => {
+                    format!("{}:waiting:{}", event.agent_id, pattern_type)
+                }
+                NotificationEventType::Notification { message, .. } => {
+                    format!("{}:{}", event.agent_id, message)
+                }
+                other => format!("{}:{:?}", event.agent_id, other),
+            },
+            Outbound::ToolUse {
+                agent_id,
+                tool_name,
+                ..
+            } => format!("{}:tool:{}", agent_id, tool_name),
+        }
+    }
+
+    /// 第一条消息发送时阻塞，直到测试放行，便于在积压中验证合并
+    fn blocked_queue() -> (OutboundQueue, mpsc::Sender<()>, mpsc::Receiver<String>) {
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let (sent_tx, sent_rx) = mpsc::channel();
+        let release_rx = Mutex::new(release_rx);
+        let queue = OutboundQueue::start(move |message| {
+            sent_tx.send(label(message)).unwrap();
+            let _ = release_rx.lock().unwrap().recv();
+        });
+        (queue, release_tx, sent_rx)
+    }
+
+    #[test]
+    fn test_queue_preserves_order() {
+        let (queue, release, sent) = blocked_queue();
+        for i in 0..5 {
+            queue.push(note("cam-1", "rule_match", &format!("m{}", i)));
+            release.send(()).unwrap();
+        }
+        drop(queue);
+        let sent: Vec<String> = sent.try_iter().collect();
+        assert_eq!(
+            sent,
+            ["cam-1:m0", "cam-1:m1", "cam-1:m2", "cam-1:m3", "cam-1:m4"]
+        );
+    }
+
+    #[test]
+    fn test_queue_coalesces_superseded() {
+        let (queue, release, sent) = blocked_queue();
+        queue.push(note("cam-0", "rule_match", "first"));
+        // 等待发送线程取走第一条后再积压
+        assert_eq!(
+            sent.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "cam-0:first"
+        );
+
+        queue.push(waiting("cam-1", "Confirmation"));
+        queue.push(note("cam-2", "tool_batch", "batch 1"));
+        queue.push(waiting("cam-1", "ChoiceSelection"));
+        queue.push(note("cam-2", "tool_batch", "batch 2"));
+        queue.push(waiting("cam-3", "Confirmation"));
+        queue.push(note("cam-3", "resolved_in_terminal", "done"));
+        queue.push(note("cam-2", "agent_stuck", "stuck"));
+        queue.push(Outbound::Event(NotificationEvent::agent_exited("cam-2")));
+        assert_eq!(queue.pending(), 2);
+
+        for _ in 0..3 {
+            release.send(()).unwrap();
+        }
+        drop(queue);
+        let sent: Vec<String> = sent.try_iter().collect();
+        assert_eq!(sent, ["cam-1:waiting:ChoiceSelection", "cam-2:AgentExited"]);
+    }
+}
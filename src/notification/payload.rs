@@ -41,12 +41,17 @@ mod msg {
 pub struct PayloadBuilder {
     /// 是否禁用 AI 提取（用于测试）
     no_ai: bool,
+    /// 快照隐私模式：payload 不附带终端快照
+    local_snapshots: bool,
 }
 
 impl PayloadBuilder {
     /// 创建新的 PayloadBuilder
     pub fn new() -> Self {
-        Self { no_ai: false }
+        Self {
+            no_ai: false,
+            local_snapshots: false,
+        }
     }
 
     /// 设置是否禁用 AI 提取
@@ -55,6 +60,12 @@ impl PayloadBuilder {
         self
     }
 
+    /// 快照隐私模式（`privacy.local_snapshots`）：不附带终端快照
+    pub fn with_local_snapshots(mut self, local: bool) -> Self {
+        self.local_snapshots = local;
+        self
+    }
+
     /// 创建结构化 payload
     ///
     /// # Arguments
@@ -93,8 +104,8 @@ impl PayloadBuilder {
         // 生成简短摘要
         let summary = self.generate_summary(event_type, &json, pattern_or_path);
 
-        // 对于权限请求，添加风险评估
-        let risk_level = if event_type == "permission_request" {
+        // 对于权限请求，添加风险评估（Write/Edit 附带文件影响）
+        let (risk_level, impact) = if event_type == "permission_request" {
             let tool_name = json
                 .as_ref()
                 .and_then(|j| j.get("tool_name"))
@@ -107,10 +118,17 @@ impl PayloadBuilder {
                 .unwrap_or(serde_json::json!({}));
 
             let summarizer = NotificationSummarizer::new();
-            let perm_summary = summarizer.summarize_permission(tool_name, &tool_input);
-            Some(format!("{:?}", perm_summary.risk_level).to_uppercase())
+            let cwd = json
+                .as_ref()
+                .and_then(|j| j.get("cwd"))
+                .and_then(|v| v.as_str());
+            let perm_summary = summarizer.summarize_permission_in(tool_name, &tool_input, cwd);
+            (
+                Some(format!("{:?}", perm_summary.risk_level).to_uppercase()),
+                perm_summary.impact,
+            )
         } else {
-            None
+            (None, None)
         };
 
         let mut payload = serde_json::json!({
@@ -129,9 +147,12 @@ impl PayloadBuilder {
         if let Some(risk) = risk_level {
             payload["risk_level"] = serde_json::Value::String(risk);
         }
+        if let Some(impact) = impact {
+            payload["impact"] = serde_json::Value::String(impact);
+        }
 
-        // 添加终端快照（如果有）
-        if let Some(snapshot) = terminal_snapshot {
+        // 添加终端快照（如果有，隐私模式下不添加）
+        if let Some(snapshot) = terminal_snapshot.filter(|_| !self.local_snapshots) {
             // 截取最后 15 行
             let lines: Vec<&str> = snapshot.lines().collect();
             let truncated = if lines.len() > 15 {
@@ -389,6 +410,16 @@ $ cargo build
             .as_str()
             .unwrap()
             .contains("cargo build"));
+
+        let payload = builder.with_local_snapshots(true).create_payload(
+            "cam-123",
+            "AgentExited",
+            "",
+            context,
+            Urgency::Medium,
+        );
+        assert!(payload.get("terminal_snapshot").is_none());
+        assert_eq!(payload["project"], "/workspace");
     }
 
     #[test]
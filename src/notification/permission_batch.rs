@@ -0,0 +1,415 @@
+//! 跨 agent 权限请求合并 - 多个 agent 同时请求相同的工具调用时只发送一条通知
+//!
+//! PreToolUse 策略要求远程确认时（见 `cli::tool_hook`），各 hook 进程按规范化的工具 + 输入
+//! 加入同一批次。第一个加入的进程等待 `window_secs` 后关闭批次并发送通知：只有自己时发送
+//! 普通权限请求，有多个 agent 时发送一条列出所有 agent 的合并通知，其余进程只等待回复。
+//!
+//! 合并通知的回复由 `cam reply` 的批量回复路径处理：`y all` 全部允许，`y 1,3` 只允许第 1、3
+//! 个 agent，`n all` 全部拒绝。
+//!
+//! config.json 的 `permission_batch`（如 `{"window_secs": 3}`）设置等待时间，0 关闭合并。
+
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::notification::dedup_key::hash_content;
+
+/// 默认合并等待时间（秒）
+pub const DEFAULT_WINDOW_SECS: u64 = 2;
+
+/// 超过该时间的批次被清理，不再用于解析回复
+const BATCH_TTL_HOURS: i64 = 1;
+
+/// 规范化时忽略的输入字段（各 agent 自行填写的说明）
+const IGNORED_INPUT_KEYS: &[&str] = &["description", "timeout"];
+
+/// 合并配置（config.json 的 `permission_batch`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionBatchConfig {
+    /// 第一个请求等待其他 agent 加入的时间（秒），0 表示不合并
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_window_secs() -> u64 {
+    DEFAULT_WINDOW_SECS
+}
+
+impl Default for PermissionBatchConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: DEFAULT_WINDOW_SECS,
+        }
+    }
+}
+
+impl PermissionBatchConfig {
+    /// 读取配置，未配置时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("permission_batch").unwrap_or_default()
+    }
+}
+
+/// 批次中的一个请求
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchMember {
+    pub agent_id: String,
+    /// 对应的待处理确认 ID
+    pub confirmation_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+/// 相同工具调用的一批权限请求
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionBatch {
+    pub id: String,
+    /// 规范化的工具 + 输入（见 [`batch_key`]）
+    pub key: String,
+    /// 工具调用描述（如 `Bash git push`）
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+    /// 已发送通知，不再接受新成员
+    #[serde(default)]
+    pub closed: bool,
+    pub members: Vec<BatchMember>,
+}
+
+/// 合并通知回复中选择的 agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchSelection {
+    All,
+    /// 从 1 开始的序号
+    Indices(Vec<usize>),
+}
+
+impl PermissionBatch {
+    /// 按回复选择成员，序号超出范围时返回错误
+    pub fn select(&self, selection: &BatchSelection) -> Result<Vec<&BatchMember>, String> {
+        match selection {
+            BatchSelection::All => Ok(self.members.iter().collect()),
+            BatchSelection::Indices(indices) => indices
+                .iter()
+                .map(|i| {
+                    self.members
+                        .get(i.wrapping_sub(1))
+                        .ok_or_else(|| format!("序号 {} 超出范围（1-{}）", i, self.members.len()))
+                })
+                .collect(),
+        }
+    }
+
+    /// 合并通知内容
+    pub fn message(&self) -> String {
+        let mut lines = vec![format!(
+            "{} 个 agent 请求相同的操作：{}",
+            self.members.len(),
+            self.summary
+        )];
+        for (i, member) in self.members.iter().enumerate() {
+            lines.push(match member.project {
+                Some(ref project) => format!("{}. {}（{}）", i + 1, member.agent_id, project),
+                None => format!("{}. {}", i + 1, member.agent_id),
+            });
+        }
+        lines.push("回复 `y all` 全部允许，`y 1,3` 允许指定 agent，`n all` 全部拒绝".to_string());
+        lines.join("\n")
+    }
+}
+
+/// 加入批次的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchJoin {
+    /// 新建批次，等待窗口结束后由自己发送通知
+    Leader(String),
+    /// 加入了其他 agent 的批次，不发送通知
+    Follower(String),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BatchState {
+    #[serde(default)]
+    batches: Vec<PermissionBatch>,
+}
+
+/// 批次状态文件（多个 hook 进程通过文件锁共享）
+#[derive(Debug, Clone)]
+pub struct PermissionBatcher {
+    path: PathBuf,
+    window_secs: u64,
+}
+
+impl PermissionBatcher {
+    /// 默认路径，等待时间读取 config.json
+    pub fn new() -> Self {
+        Self::at(
+            &crate::infra::paths::state_dir().join("permission_batches.json"),
+            PermissionBatchConfig::load().window_secs,
+        )
+    }
+
+    pub fn at(path: &Path, window_secs: u64) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            window_secs,
+        }
+    }
+
+    /// 是否合并（等待时间为 0 时关闭）
+    pub fn enabled(&self) -> bool {
+        self.window_secs > 0
+    }
+
+    /// 第一个请求等待其他 agent 加入的时间
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+
+    /// 在文件锁内读取、修改并保存状态
+    fn update<T>(&self, operation: impl FnOnce(&mut BatchState) -> T) -> Result<T> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("lock"))?;
+        lock_file.lock_exclusive()?;
+
+        let mut state: BatchState = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let expired = Utc::now() - chrono::Duration::hours(BATCH_TTL_HOURS);
+        state.batches.retain(|batch| batch.created_at > expired);
+        let result = operation(&mut state);
+        let saved = serde_json::to_string_pretty(&state)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(fs::write(&self.path, content)?));
+
+        let _ = lock_file.unlock();
+        saved.map(|_| result)
+    }
+
+    /// 加入窗口内尚未发送的相同请求批次，没有时新建
+    pub fn join(&self, key: &str, summary: &str, member: BatchMember) -> Result<BatchJoin> {
+        let window = chrono::Duration::seconds(self.window_secs as i64);
+        self.update(|state| {
+            let now = Utc::now();
+            if let Some(batch) = state
+                .batches
+                .iter_mut()
+                .find(|b| !b.closed && b.key == key && b.created_at + window >= now)
+            {
+                batch.members.push(member);
+                return BatchJoin::Follower(batch.id.clone());
+            }
+            let id = format!("pb-{}-{}", now.timestamp_millis(), member.agent_id);
+            state.batches.push(PermissionBatch {
+                id: id.clone(),
+                key: key.to_string(),
+                summary: summary.to_string(),
+                created_at: now,
+                closed: false,
+                members: vec![member],
+            });
+            BatchJoin::Leader(id)
+        })
+    }
+
+    /// 关闭批次（之后的相同请求新建批次），返回关闭时的成员
+    pub fn close(&self, batch_id: &str) -> Result<Option<PermissionBatch>> {
+        self.update(|state| {
+            let batch = state.batches.iter_mut().find(|b| b.id == batch_id)?;
+            batch.closed = true;
+            Some(batch.clone())
+        })
+    }
+
+    /// 最近一次已发送的合并通知（多个 agent），用于解析 `y 1,3`
+    pub fn latest(&self) -> Option<PermissionBatch> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let state: BatchState = serde_json::from_str(&content).ok()?;
+        let expired = Utc::now() - chrono::Duration::hours(BATCH_TTL_HOURS);
+        state
+            .batches
+            .into_iter()
+            .filter(|b| b.closed && b.members.len() > 1 && b.created_at > expired)
+            .max_by_key(|b| b.created_at)
+    }
+}
+
+impl Default for PermissionBatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 规范化的工具 + 输入：对象按键排序，字符串合并空白，忽略说明类字段
+pub fn batch_key(tool: &str, input: &Value) -> String {
+    format!(
+        "{}:{:016x}",
+        tool,
+        hash_content(&canonical_input(input, true))
+    )
+}
+
+fn canonical_input(value: &Value, top_level: bool) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map
+                .iter()
+                .filter(|(key, _)| !top_level || !IGNORED_INPUT_KEYS.contains(&key.as_str()))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| format!("{}={}", key, canonical_input(value, false)))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| canonical_input(item, false))
+                .collect();
+            format!("[{}]", items.join(","))
+        }
+        Value::String(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        other => other.to_string(),
+    }
+}
+
+/// 解析合并通知的回复（`y all`、`n 全部`、`y 1,3`），返回回复内容和选择
+pub fn parse_batch_reply(text: &str) -> Option<(String, BatchSelection)> {
+    let mut parts = text.split_whitespace();
+    let (reply, selector) = (parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let selection = match selector.to_lowercase().as_str() {
+        "all" | "全部" => BatchSelection::All,
+        selector => BatchSelection::Indices(
+            selector
+                .split([',', '，'])
+                .map(|n| n.trim().parse::<usize>().ok().filter(|n| *n > 0))
+                .collect::<Option<Vec<_>>>()?,
+        ),
+    };
+    Some((reply.to_string(), selection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn member(agent_id: &str) -> BatchMember {
+        BatchMember {
+            agent_id: agent_id.to_string(),
+            confirmation_id: format!("conf-{}", agent_id),
+            project: None,
+        }
+    }
+
+    #[test]
+    fn test_batch_key_normalization() {
+        let a = batch_key(
+            "Bash",
+            &json!({"command": "git  push origin main", "description": "Push changes"}),
+        );
+        let b = batch_key(
+            "Bash",
+            &json!({"description": "Push to remote", "command": "git push origin main "}),
+        );
+        assert_eq!(a, b);
+        assert_ne!(
+            a,
+            batch_key("Bash", &json!({"command": "git push --force"}))
+        );
+        assert_ne!(
+            a,
+            batch_key("Write", &json!({"command": "git push origin main"}))
+        );
+    }
+
+    #[test]
+    fn test_batcher_join_and_close() {
+        let temp = tempfile::tempdir().unwrap();
+        let batcher = PermissionBatcher::at(&temp.path().join("batches.json"), 60);
+        let key = batch_key("Bash", &json!({"command": "git push"}));
+
+        let BatchJoin::Leader(id) = batcher
+            .join(&key, "Bash git push", member("cam-1"))
+            .unwrap()
+        else {
+            panic!("first request should lead");
+        };
+        assert_eq!(
+            batcher
+                .join(&key, "Bash git push", member("cam-2"))
+                .unwrap(),
+            BatchJoin::Follower(id.clone())
+        );
+        // 不同的请求单独成批
+        let other = batch_key("Bash", &json!({"command": "rm -rf build"}));
+        assert!(matches!(
+            batcher.join(&other, "Bash rm", member("cam-3")).unwrap(),
+            BatchJoin::Leader(_)
+        ));
+        assert!(batcher.latest().is_none());
+
+        let batch = batcher.close(&id).unwrap().unwrap();
+        assert_eq!(batch.members.len(), 2);
+        assert!(batch.message().contains("2. cam-2"));
+        assert_eq!(batcher.latest().unwrap().id, id);
+
+        // 关闭后的相同请求新建批次
+        assert!(matches!(
+            batcher
+                .join(&key, "Bash git push", member("cam-4"))
+                .unwrap(),
+            BatchJoin::Leader(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_batch_reply_and_select() {
+        assert_eq!(
+            parse_batch_reply("y all"),
+            Some(("y".to_string(), BatchSelection::All))
+        );
+        assert_eq!(
+            parse_batch_reply("n 全部"),
+            Some(("n".to_string(), BatchSelection::All))
+        );
+        assert_eq!(
+            parse_batch_reply("y 1,3"),
+            Some(("y".to_string(), BatchSelection::Indices(vec![1, 3])))
+        );
+        assert_eq!(parse_batch_reply("y"), None);
+        assert_eq!(parse_batch_reply("please continue"), None);
+        assert_eq!(parse_batch_reply("y 0"), None);
+
+        let batch = PermissionBatch {
+            id: "pb-1".to_string(),
+            key: "Bash:0".to_string(),
+            summary: "Bash git push".to_string(),
+            created_at: Utc::now(),
+            closed: true,
+            members: vec![member("cam-1"), member("cam-2"), member("cam-3")],
+        };
+        let selected = batch.select(&BatchSelection::Indices(vec![1, 3])).unwrap();
+        assert_eq!(selected[1].agent_id, "cam-3");
+        assert_eq!(batch.select(&BatchSelection::All).unwrap().len(), 3);
+        assert!(batch.select(&BatchSelection::Indices(vec![4])).is_err());
+    }
+}
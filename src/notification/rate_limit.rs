@@ -0,0 +1,655 @@
+//! 通知速率限制 - 按 (agent, 事件类型) 和渠道的令牌桶
+//!
+//! ## 策略
+//! 1. 每个 (agent, event_type) 一个令牌桶，防止单个 agent 抖动时刷屏
+//! 2. 每个渠道一个令牌桶，限制所有 agent 合计的发送速率
+//! 3. 两个桶都有令牌才发送；否则事件进入摘要队列（不丢弃）
+//!    需要用户回复的通知（权限请求、等待输入、HIGH urgency）不限流，
+//!    摘要只有一行，会丢掉问题和选项，agent 会一直停在等待回复
+//! 4. 下一条放行的通知携带该渠道积压的摘要；积压超过 `digest_flush_secs`
+//!    时由 watcher 单独发送一条摘要通知
+//!
+//! ## 配置
+//! `~/.config/code-agent-monitor/config.json` 的 `rate_limit` 字段：
+//! ```json
+//! {
+//!   "rate_limit": {
+//!     "enabled": true,
+//!     "agent": { "capacity": 3, "refill_secs": 60 },
+//!     "channel": { "capacity": 10, "refill_secs": 12 },
+//!     "digest_flush_secs": 300
+//!   }
+//! }
+//! ```
+//!
+//! ## 持久化
+//! 令牌桶和摘要队列持久化到 `~/.config/code-agent-monitor/throttle_state.json`，
+//! 与去重器一样使用 fs2 文件锁，watcher 和 hook 进程共享限流状态。
+
+use crate::infra::schema::THROTTLE_STATE_SCHEMA;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use tracing::debug;
+
+/// 摘要队列上限，超出时丢弃最旧的条目
+const MAX_DIGEST_ENTRIES: usize = 100;
+
+/// 长时间未使用的令牌桶（已回满）在此时间后清理（秒）
+const IDLE_BUCKET_SECS: u64 = 86400;
+
+/// 单个令牌桶的参数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BucketConfig {
+    /// 桶容量（允许的突发条数）
+    pub capacity: f64,
+    /// 每恢复一个令牌所需秒数
+    pub refill_secs: f64,
+}
+
+impl BucketConfig {
+    fn refill_per_sec(&self) -> f64 {
+        if self.refill_secs <= 0.0 {
+            f64::INFINITY
+        } else {
+            1.0 / self.refill_secs
+        }
+    }
+}
+
+/// 速率限制配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 每个 (agent, event_type) 的令牌桶
+    pub agent: BucketConfig,
+    /// 每个渠道的令牌桶
+    pub channel: BucketConfig,
+    /// 摘要积压超过此时间（秒）后单独发送
+    pub digest_flush_secs: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            agent: BucketConfig {
+                capacity: 3.0,
+                refill_secs: 60.0,
+            },
+            channel: BucketConfig {
+                capacity: 10.0,
+                refill_secs: 12.0,
+            },
+            digest_flush_secs: 300,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// 从 config.json 的 `rate_limit` 字段加载，缺失或格式错误时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("rate_limit").unwrap_or_default()
+    }
+}
+
+/// 令牌桶
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucket {
+    /// 当前令牌数
+    pub tokens: f64,
+    /// 上次更新时间（Unix 时间戳秒）
+    pub updated_at: u64,
+}
+
+impl TokenBucket {
+    fn full(config: &BucketConfig, now: u64) -> Self {
+        Self {
+            tokens: config.capacity,
+            updated_at: now,
+        }
+    }
+
+    /// 按经过的时间恢复令牌
+    fn refill(&mut self, config: &BucketConfig, now: u64) {
+        let elapsed = now.saturating_sub(self.updated_at) as f64;
+        self.tokens = (self.tokens + elapsed * config.refill_per_sec()).min(config.capacity);
+        self.updated_at = self.updated_at.max(now);
+    }
+}
+
+/// 因限流进入摘要队列的事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestEntry {
+    pub agent_id: String,
+    pub event_type: String,
+    pub channel: String,
+    /// 一行摘要
+    pub summary: String,
+    /// 进入队列时间（Unix 时间戳秒）
+    pub at: u64,
+}
+
+impl DigestEntry {
+    /// 摘要中显示的一行文本
+    pub fn line(&self) -> String {
+        format!("[{}] {}: {}", self.agent_id, self.event_type, self.summary)
+    }
+}
+
+/// 限流判断结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateDecision {
+    /// 放行，附带该渠道积压的摘要（需随本条通知一起发送）
+    Allow { digest: Vec<DigestEntry> },
+    /// 已进入摘要队列
+    Spilled(String),
+}
+
+/// 令牌桶状态（供 `cam status` 显示）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BucketStatus {
+    /// "agent" 或 "channel"
+    pub scope: &'static str,
+    pub key: String,
+    pub tokens: f64,
+    pub capacity: f64,
+}
+
+/// 限流器整体状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThrottleStatus {
+    pub enabled: bool,
+    /// 仅包含未满的令牌桶
+    pub buckets: Vec<BucketStatus>,
+    pub digest: Vec<DigestEntry>,
+}
+
+/// 持久化状态
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ThrottleState {
+    /// "agent_id:event_type" -> TokenBucket
+    #[serde(default)]
+    agent_buckets: HashMap<String, TokenBucket>,
+    /// channel -> TokenBucket
+    #[serde(default)]
+    channel_buckets: HashMap<String, TokenBucket>,
+    #[serde(default)]
+    digest: Vec<DigestEntry>,
+}
+
+/// 通知速率限制器
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    state: ThrottleState,
+    /// 是否启用持久化
+    persist: bool,
+    /// 自定义状态文件路径
+    custom_state_path: Option<PathBuf>,
+}
+
+impl RateLimiter {
+    /// 使用 config.json 中的配置创建限流器，状态持久化到默认路径
+    pub fn new() -> Self {
+        Self {
+            config: RateLimitConfig::load(),
+            state: ThrottleState::default(),
+            persist: true,
+            custom_state_path: None,
+        }
+    }
+
+    /// 创建不持久化的限流器（用于离线重放和测试）
+    pub fn new_without_persistence(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            state: ThrottleState::default(),
+            persist: false,
+            custom_state_path: None,
+        }
+    }
+
+    /// 创建使用自定义状态文件路径的限流器
+    pub fn with_state_path(config: RateLimitConfig, path: PathBuf) -> Self {
+        Self {
+            config,
+            state: ThrottleState::default(),
+            persist: true,
+            custom_state_path: Some(path),
+        }
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    fn state_path(&self) -> Option<PathBuf> {
+        if let Some(ref path) = self.custom_state_path {
+            return Some(path.clone());
+        }
+        Some(crate::infra::paths::state_dir().join("throttle_state.json"))
+    }
+
+    /// 从磁盘加载状态（带共享锁）
+    fn load_state(&mut self) {
+        if !self.persist {
+            return;
+        }
+        let Some(path) = self.state_path() else {
+            return;
+        };
+        let Ok(mut file) = std::fs::File::open(&path) else {
+            return;
+        };
+        if file.lock_shared().is_err() {
+            debug!("Failed to acquire shared lock for reading throttle state");
+            return;
+        }
+        let mut content = String::new();
+        if file.read_to_string(&mut content).is_ok() {
+            if let Ok(state) = THROTTLE_STATE_SCHEMA.load_versioned::<ThrottleState>(&content) {
+                self.state = state;
+            }
+        }
+        let _ = file.unlock();
+    }
+
+    /// 保存状态到磁盘（带排他锁）
+    fn save_state(&self) {
+        if !self.persist {
+            return;
+        }
+        let Some(path) = self.state_path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                if file.lock_exclusive().is_err() {
+                    debug!("Failed to acquire exclusive lock for writing throttle state");
+                    return;
+                }
+                if let Ok(content) = THROTTLE_STATE_SCHEMA
+                    .to_versioned_value(&self.state)
+                    .and_then(|value| Ok(serde_json::to_string(&value)?))
+                {
+                    let _ = file.write_all(content.as_bytes());
+                }
+                let _ = file.unlock();
+            }
+            Err(e) => {
+                debug!(error = %e, "Failed to save throttle state");
+            }
+        }
+    }
+
+    fn current_timestamp() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// 清理已回满且长时间未使用的令牌桶
+    fn cleanup_idle(&mut self, now: u64) {
+        let idle = |bucket: &TokenBucket| now.saturating_sub(bucket.updated_at) >= IDLE_BUCKET_SECS;
+        self.state.agent_buckets.retain(|_, b| !idle(b));
+        self.state.channel_buckets.retain(|_, b| !idle(b));
+    }
+
+    /// 检查一条通知是否可以发送
+    pub fn check(
+        &mut self,
+        agent_id: &str,
+        event_type: &str,
+        channel: &str,
+        summary: &str,
+    ) -> RateDecision {
+        self.check_at(
+            agent_id,
+            event_type,
+            channel,
+            summary,
+            Self::current_timestamp(),
+        )
+    }
+
+    /// 以指定时间（Unix 秒）检查，用于离线重放
+    pub fn check_at(
+        &mut self,
+        agent_id: &str,
+        event_type: &str,
+        channel: &str,
+        summary: &str,
+        now: u64,
+    ) -> RateDecision {
+        if !self.config.enabled {
+            return RateDecision::Allow { digest: Vec::new() };
+        }
+
+        self.load_state();
+        self.cleanup_idle(now);
+
+        let agent_config = self.config.agent;
+        let channel_config = self.config.channel;
+        let agent_key = format!("{}:{}", agent_id, event_type);
+
+        let agent_bucket = self
+            .state
+            .agent_buckets
+            .entry(agent_key.clone())
+            .or_insert_with(|| TokenBucket::full(&agent_config, now));
+        agent_bucket.refill(&agent_config, now);
+        let agent_ok = agent_bucket.tokens >= 1.0;
+
+        let channel_bucket = self
+            .state
+            .channel_buckets
+            .entry(channel.to_string())
+            .or_insert_with(|| TokenBucket::full(&channel_config, now));
+        channel_bucket.refill(&channel_config, now);
+        let channel_ok = channel_bucket.tokens >= 1.0;
+
+        let decision = if agent_ok && channel_ok {
+            channel_bucket.tokens -= 1.0;
+            if let Some(bucket) = self.state.agent_buckets.get_mut(&agent_key) {
+                bucket.tokens -= 1.0;
+            }
+            RateDecision::Allow {
+                digest: self.drain_digest(channel),
+            }
+        } else {
+            let reason = if agent_ok {
+                format!("channel {} rate limited", channel)
+            } else {
+                format!("{} rate limited", agent_key)
+            };
+            debug!(agent_id = %agent_id, event_type = %event_type, reason = %reason, "Notification spilled into digest");
+            self.state.digest.push(DigestEntry {
+                agent_id: agent_id.to_string(),
+                event_type: event_type.to_string(),
+                channel: channel.to_string(),
+                summary: summary.to_string(),
+                at: now,
+            });
+            let overflow = self.state.digest.len().saturating_sub(MAX_DIGEST_ENTRIES);
+            self.state.digest.drain(..overflow);
+            RateDecision::Spilled(reason)
+        };
+
+        self.save_state();
+        decision
+    }
+
+    /// 不限流直接放行（需要用户回复的通知），不消耗令牌，带上该渠道积压的摘要
+    pub fn pass_through(&mut self, channel: &str) -> Vec<DigestEntry> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+        self.load_state();
+        let digest = self.drain_digest(channel);
+        if !digest.is_empty() {
+            self.save_state();
+        }
+        digest
+    }
+
+    fn drain_digest(&mut self, channel: &str) -> Vec<DigestEntry> {
+        let (drained, kept) = std::mem::take(&mut self.state.digest)
+            .into_iter()
+            .partition(|entry| entry.channel == channel);
+        self.state.digest = kept;
+        drained
+    }
+
+    /// 取出积压过久的摘要（消耗一个渠道令牌），无需发送时返回 None
+    pub fn take_due_digest(&mut self, channel: &str) -> Option<Vec<DigestEntry>> {
+        self.take_due_digest_at(channel, Self::current_timestamp())
+    }
+
+    /// 以指定时间（Unix 秒）取出积压过久的摘要
+    pub fn take_due_digest_at(&mut self, channel: &str, now: u64) -> Option<Vec<DigestEntry>> {
+        self.load_state();
+        let oldest = self
+            .state
+            .digest
+            .iter()
+            .filter(|entry| entry.channel == channel)
+            .map(|entry| entry.at)
+            .min()?;
+        if now.saturating_sub(oldest) < self.config.digest_flush_secs {
+            return None;
+        }
+
+        let channel_config = self.config.channel;
+        let bucket = self
+            .state
+            .channel_buckets
+            .entry(channel.to_string())
+            .or_insert_with(|| TokenBucket::full(&channel_config, now));
+        bucket.refill(&channel_config, now);
+        if bucket.tokens < 1.0 {
+            self.save_state();
+            return None;
+        }
+        bucket.tokens -= 1.0;
+
+        let digest = self.drain_digest(channel);
+        self.save_state();
+        Some(digest)
+    }
+
+    /// 当前限流状态（不修改持久化数据）
+    pub fn status(&mut self) -> ThrottleStatus {
+        self.status_at(Self::current_timestamp())
+    }
+
+    /// 以指定时间（Unix 秒）计算限流状态
+    pub fn status_at(&mut self, now: u64) -> ThrottleStatus {
+        self.load_state();
+
+        let mut buckets = Vec::new();
+        let mut collect =
+            |scope: &'static str, map: &HashMap<String, TokenBucket>, config: &BucketConfig| {
+                let mut entries: Vec<_> = map
+                    .iter()
+                    .filter_map(|(key, bucket)| {
+                        let mut bucket = bucket.clone();
+                        bucket.refill(config, now);
+                        (bucket.tokens < config.capacity).then(|| BucketStatus {
+                            scope,
+                            key: key.clone(),
+                            tokens: bucket.tokens,
+                            capacity: config.capacity,
+                        })
+                    })
+                    .collect();
+                entries.sort_by(|a, b| a.key.cmp(&b.key));
+                buckets.extend(entries);
+            };
+        collect("agent", &self.state.agent_buckets, &self.config.agent);
+        collect("channel", &self.state.channel_buckets, &self.config.channel);
+
+        ThrottleStatus {
+            enabled: self.config.enabled,
+            buckets,
+            digest: self.state.digest.clone(),
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            agent: BucketConfig {
+                capacity: 2.0,
+                refill_secs: 10.0,
+            },
+            channel: BucketConfig {
+                capacity: 3.0,
+                refill_secs: 5.0,
+            },
+            digest_flush_secs: 60,
+        }
+    }
+
+    fn allowed(decision: &RateDecision) -> bool {
+        matches!(decision, RateDecision::Allow { .. })
+    }
+
+    #[test]
+    fn test_agent_bucket_spills_and_refills() {
+        let mut limiter = RateLimiter::new_without_persistence(config());
+
+        // 容量内的突发放行
+        assert!(allowed(
+            &limiter.check_at("cam-1", "Error", "openclaw", "a", 100)
+        ));
+        assert!(allowed(
+            &limiter.check_at("cam-1", "Error", "openclaw", "b", 100)
+        ));
+        // 第三条进入摘要
+        let spilled = limiter.check_at("cam-1", "Error", "openclaw", "c", 101);
+        assert!(matches!(spilled, RateDecision::Spilled(ref r) if r.contains("cam-1:Error")));
+        // 10 秒后恢复一个令牌
+        assert!(!allowed(
+            &limiter.check_at("cam-1", "Error", "openclaw", "c2", 105)
+        ));
+        match limiter.check_at("cam-1", "Error", "openclaw", "e", 111) {
+            RateDecision::Allow { digest } => assert_eq!(digest.len(), 2),
+            other => panic!("unexpected decision: {:?}", other),
+        }
+
+        // 不同事件类型使用独立的桶，并带出积压的摘要
+        limiter.check_at("cam-1", "Error", "openclaw", "f", 111);
+        match limiter.check_at("cam-1", "AgentExited", "openclaw", "g", 111) {
+            RateDecision::Allow { digest } => {
+                assert_eq!(digest.len(), 1);
+                assert_eq!(digest[0].summary, "f");
+                assert_eq!(digest[0].line(), "[cam-1] Error: f");
+            }
+            other => panic!("unexpected decision: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_channel_bucket_limits_across_agents() {
+        let mut limiter = RateLimiter::new_without_persistence(config());
+        for agent in ["cam-1", "cam-2", "cam-3"] {
+            assert!(allowed(
+                &limiter.check_at(agent, "Error", "telegram", "x", 100)
+            ));
+        }
+        let spilled = limiter.check_at("cam-4", "Error", "telegram", "y", 100);
+        assert_eq!(
+            spilled,
+            RateDecision::Spilled("channel telegram rate limited".to_string())
+        );
+        // 其他渠道不受影响，也不会带出 telegram 的摘要
+        assert_eq!(
+            limiter.check_at("cam-4", "Error", "slack", "z", 100),
+            RateDecision::Allow { digest: Vec::new() }
+        );
+
+        let status = limiter.status_at(100);
+        assert_eq!(status.digest.len(), 1);
+        assert!(status
+            .buckets
+            .iter()
+            .any(|b| b.scope == "channel" && b.key == "telegram" && b.tokens < 1.0));
+    }
+
+    #[test]
+    fn test_take_due_digest() {
+        let mut limiter = RateLimiter::new_without_persistence(config());
+        limiter.check_at("cam-1", "Error", "openclaw", "a", 100);
+        limiter.check_at("cam-1", "Error", "openclaw", "b", 100);
+        limiter.check_at("cam-1", "Error", "openclaw", "c", 100);
+
+        // 未到 flush 时间
+        assert!(limiter.take_due_digest_at("openclaw", 130).is_none());
+        let digest = limiter.take_due_digest_at("openclaw", 160).unwrap();
+        assert_eq!(digest.len(), 1);
+        assert!(limiter.take_due_digest_at("openclaw", 500).is_none());
+    }
+
+    #[test]
+    fn test_pass_through_never_spills() {
+        let mut limiter = RateLimiter::new_without_persistence(config());
+        for summary in ["a", "b"] {
+            limiter.check_at("cam-1", "Error", "openclaw", summary, 100);
+        }
+        assert!(!allowed(
+            &limiter.check_at("cam-1", "Error", "openclaw", "c", 100)
+        ));
+
+        // 需要回复的通知照常发送，并带走积压的摘要
+        let digest = limiter.pass_through("openclaw");
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].summary, "c");
+        assert!(limiter.pass_through("openclaw").is_empty());
+    }
+
+    #[test]
+    fn test_disabled_always_allows() {
+        let mut limiter = RateLimiter::new_without_persistence(RateLimitConfig {
+            enabled: false,
+            ..config()
+        });
+        for _ in 0..10 {
+            assert!(allowed(
+                &limiter.check_at("cam-1", "Error", "openclaw", "a", 100)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_state_shared_across_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("throttle_state.json");
+
+        let mut first = RateLimiter::with_state_path(config(), path.clone());
+        first.check_at("cam-1", "Error", "openclaw", "a", 100);
+        first.check_at("cam-1", "Error", "openclaw", "b", 100);
+
+        // 另一个进程看到已消耗的令牌
+        let mut second = RateLimiter::with_state_path(config(), path);
+        assert!(!allowed(
+            &second.check_at("cam-1", "Error", "openclaw", "c", 100)
+        ));
+        assert_eq!(second.status_at(100).digest.len(), 1);
+    }
+
+    #[test]
+    fn test_config_partial_override() {
+        let parsed: RateLimitConfig = serde_json::from_value(
+            serde_json::json!({"agent": {"capacity": 1, "refill_secs": 30}}),
+        )
+        .unwrap();
+        assert_eq!(parsed.agent.capacity, 1.0);
+        assert_eq!(parsed.channel, RateLimitConfig::default().channel);
+        assert!(parsed.enabled);
+    }
+}
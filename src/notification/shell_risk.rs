@@ -0,0 +1,1208 @@
+//! Bash 命令静态风险分析 - 按命令结构逐条判断，而不是在整行上匹配关键字
+//!
+//! 解析器只覆盖判断风险需要的 shell 子集：引号与转义、管道与连接符（`|`、`&&`、`||`、`;`、`&`）、
+//! 重定向、here-doc、`$(...)`/反引号命令替换和变量展开。命令替换、`sh -c '...'`、`eval`、
+//! `find -exec` 和 `xargs` 执行的命令递归分析；`sudo`、`env`、`nohup` 等前缀剥离后按实际命令判断。
+//! `rm` 的目标按工作目录（跟随 `cd`）解析，区分项目内、临时目录、项目外和根目录/home/系统目录。
+//!
+//! 每条发现（[`RiskFinding`]）带风险等级和中文说明，整体风险取最高一条；没有发现时，
+//! 所有命令都是只读命令为 Low，否则为 Medium。
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::summarizer::{expand_home, is_temp_path, normalize_path, RiskLevel};
+
+/// 命令替换、`sh -c` 等嵌套的最大深度
+const MAX_DEPTH: usize = 4;
+
+/// 通知中最多列出的发现条数
+const SUMMARY_FINDINGS: usize = 3;
+
+/// 只读命令（没有其他发现时为 Low）
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "ls", "cat", "echo", "pwd", "cd", "head", "tail", "grep", "rg", "find", "which", "whoami",
+    "date", "env", "printenv", "wc", "sort", "uniq", "diff", "tree", "file", "stat", "less",
+    "printf", "true", "false", "test", "[", "basename", "dirname", "realpath", "du", "df", "ps",
+    "uname", "id", "jq", "sleep", "type",
+];
+
+/// 只读的 git 子命令
+const READ_ONLY_GIT: &[&str] = &[
+    "status",
+    "log",
+    "diff",
+    "show",
+    "blame",
+    "rev-parse",
+    "ls-files",
+    "describe",
+    "shortlog",
+    "grep",
+    "reflog",
+];
+
+/// 读取也需要确认的敏感路径
+const SENSITIVE_PATH_PATTERNS: &[&str] = &[
+    "/etc/",
+    "~/.ssh/",
+    "/.ssh/",
+    "~/.aws/",
+    "/.aws/",
+    "~/.config/",
+    ".env",
+    "credentials",
+    "secret",
+    "token",
+    "password",
+    "id_rsa",
+    "id_ed25519",
+];
+
+/// 删除或覆盖会破坏系统的目录
+const SYSTEM_DIRS: &[&str] = &[
+    "/etc",
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/boot",
+    "/var",
+    "/opt",
+    "/dev",
+    "/proc",
+    "/sys",
+    "/System",
+    "/Library",
+    "/Applications",
+];
+
+/// 可以从标准输入读取并执行代码的解释器
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "fish"];
+const INTERPRETERS: &[&str] = &["python", "python3", "perl", "ruby", "node"];
+
+/// here-doc 的开始（`<<EOF`、`<<-'EOF'`，不含 here-string `<<<`）
+static HEREDOC_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:^|[^<])<<-?\s*(['"]?[A-Za-z_][A-Za-z0-9_]*['"]?)"#)
+        .expect("Invalid heredoc regex")
+});
+
+static FORK_BOMB_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r":\(\)\s*\{").expect("Invalid fork bomb regex"));
+
+/// 一条风险发现
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RiskFinding {
+    pub risk: RiskLevel,
+    /// 规则名（如 `sudo`、`pipe_to_shell`、`rm_outside_project`）
+    pub rule: &'static str,
+    /// 中文说明
+    pub detail: String,
+}
+
+/// 风险分析结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RiskAnalysis {
+    pub risk: RiskLevel,
+    /// 按风险从高到低排序
+    pub findings: Vec<RiskFinding>,
+}
+
+impl RiskAnalysis {
+    /// 没有具体发现的结果
+    pub fn plain(risk: RiskLevel) -> Self {
+        Self {
+            risk,
+            findings: Vec::new(),
+        }
+    }
+
+    /// 风险最高的几条说明，没有发现时为空
+    pub fn details(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .take(SUMMARY_FINDINGS)
+            .map(|f| f.detail.clone())
+            .collect()
+    }
+
+    /// 说明用 `；` 连接，没有发现时为 None
+    pub fn summary(&self) -> Option<String> {
+        let details = self.details();
+        (!details.is_empty()).then(|| details.join("；"))
+    }
+}
+
+/// 一个 shell 词
+#[derive(Debug, Clone, Default)]
+struct Word {
+    /// 去掉引号后的文本，变量保留为 `$NAME`（`$HOME` 换成 `~`），命令替换保留原文
+    text: String,
+    /// 含变量展开或命令替换，实际值无法预知
+    dynamic: bool,
+    /// 命令替换的内容
+    substitutions: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Word(Word),
+    /// `|`、`||`、`&&`、`;`、`&`
+    Op(&'static str),
+    /// 输出重定向（`>`、`>>`、`&>`），下一个词是目标
+    WriteTo,
+    /// 输入重定向（`<`、`<<<`），下一个词是来源
+    ReadFrom,
+}
+
+/// 词法分析
+fn tokenize(command: &str) -> Vec<Token> {
+    let chars: Vec<char> = command.chars().collect();
+    let mut tokens = Vec::new();
+    let mut word: Option<Word> = None;
+    let flush = |word: &mut Option<Word>, tokens: &mut Vec<Token>| {
+        if let Some(word) = word.take() {
+            tokens.push(Token::Word(word));
+        }
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' => {
+                flush(&mut word, &mut tokens);
+                i += 1;
+            }
+            '\n' | ';' => {
+                flush(&mut word, &mut tokens);
+                tokens.push(Token::Op(";"));
+                i += 1;
+            }
+            // 子 shell 和命令组按普通分隔处理
+            '(' | ')' if word.is_none() => {
+                tokens.push(Token::Op(";"));
+                i += 1;
+            }
+            '#' if word.is_none() => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '|' | '&' => {
+                flush(&mut word, &mut tokens);
+                match (c, chars.get(i + 1)) {
+                    ('|', Some('|')) => tokens.push(Token::Op("||")),
+                    ('&', Some('&')) => tokens.push(Token::Op("&&")),
+                    ('|', Some('&')) => tokens.push(Token::Op("|")),
+                    ('&', Some('>')) => {
+                        i += usize::from(chars.get(i + 2) == Some(&'>'));
+                        tokens.push(Token::WriteTo);
+                    }
+                    ('|', _) => {
+                        tokens.push(Token::Op("|"));
+                        i += 1;
+                        continue;
+                    }
+                    _ => {
+                        tokens.push(Token::Op("&"));
+                        i += 1;
+                        continue;
+                    }
+                }
+                i += 2;
+            }
+            '>' | '<' => {
+                // 紧挨着的数字是文件描述符（`2>`），不是参数
+                if word
+                    .as_ref()
+                    .is_some_and(|w| !w.dynamic && w.text.chars().all(|c| c.is_ascii_digit()))
+                {
+                    word = None;
+                }
+                flush(&mut word, &mut tokens);
+                i += 1;
+                if c == '>' {
+                    if matches!(chars.get(i), Some('>') | Some('|')) {
+                        i += 1;
+                    }
+                    // 复制文件描述符（`2>&1`）
+                    if chars.get(i) == Some(&'&') {
+                        i += 1;
+                        while chars
+                            .get(i)
+                            .is_some_and(|c| c.is_ascii_digit() || *c == '-')
+                        {
+                            i += 1;
+                        }
+                        continue;
+                    }
+                    tokens.push(Token::WriteTo);
+                } else {
+                    // here-doc 的分隔符和 here-string 都当作输入来源
+                    while matches!(chars.get(i), Some('<') | Some('-')) {
+                        i += 1;
+                    }
+                    tokens.push(Token::ReadFrom);
+                }
+            }
+            '\'' => {
+                let w = word.get_or_insert_with(Word::default);
+                i += 1;
+                while i < chars.len() && chars[i] != '\'' {
+                    w.text.push(chars[i]);
+                    i += 1;
+                }
+                i += 1;
+            }
+            '"' => {
+                let w = word.get_or_insert_with(Word::default);
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    match chars[i] {
+                        '\\' if matches!(chars.get(i + 1), Some('"' | '\\' | '$' | '`')) => {
+                            w.text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        '$' | '`' => i = read_expansion(&chars, i, w),
+                        other => {
+                            w.text.push(other);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            '\\' => {
+                let w = word.get_or_insert_with(Word::default);
+                if let Some(&next) = chars.get(i + 1).filter(|c| **c != '\n') {
+                    w.text.push(next);
+                }
+                i += 2;
+            }
+            '$' | '`' => i = read_expansion(&chars, i, word.get_or_insert_with(Word::default)),
+            other => {
+                word.get_or_insert_with(Word::default).text.push(other);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut word, &mut tokens);
+    tokens
+}
+
+/// 去掉 here-doc 的正文（`<<EOF` 之后到结束行），正文是数据而不是命令
+fn strip_heredocs(command: &str) -> String {
+    let mut lines = command.lines();
+    let mut kept = Vec::new();
+    while let Some(line) = lines.next() {
+        kept.push(line);
+        for delimiter in HEREDOC_RE
+            .captures_iter(line)
+            .map(|c| c[1].trim_matches(['\'', '"', '\\']).to_string())
+        {
+            for body in lines.by_ref() {
+                if body.trim() == delimiter {
+                    break;
+                }
+            }
+        }
+    }
+    kept.join("\n")
+}
+
+/// 解析 `$` 或反引号开头的展开，返回之后的位置
+fn read_expansion(chars: &[char], start: usize, word: &mut Word) -> usize {
+    let source =
+        |from: usize, to: usize| chars[from..to.min(chars.len())].iter().collect::<String>();
+    if chars[start] == '`' {
+        let end = (start + 1..chars.len())
+            .find(|&j| chars[j] == '`' && chars[j - 1] != '\\')
+            .unwrap_or(chars.len());
+        word.substitutions.push(source(start + 1, end));
+        word.text.push_str(&source(start, end + 1));
+        word.dynamic = true;
+        return end + 1;
+    }
+    match chars.get(start + 1) {
+        Some('(') => {
+            let mut depth = 0;
+            let mut j = start + 1;
+            while j < chars.len() {
+                match chars[j] {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    '\'' => {
+                        j += 1;
+                        while j < chars.len() && chars[j] != '\'' {
+                            j += 1;
+                        }
+                    }
+                    _ => {}
+                }
+                j += 1;
+            }
+            // `$((...))` 是算术展开，不执行命令
+            if chars.get(start + 2) != Some(&'(') {
+                word.substitutions.push(source(start + 2, j));
+            }
+            word.text.push_str(&source(start, j + 1));
+            word.dynamic = true;
+            j + 1
+        }
+        Some('{') => {
+            let end = (start + 2..chars.len())
+                .find(|&j| chars[j] == '}')
+                .unwrap_or(chars.len());
+            let inner = source(start + 2, end);
+            let name: String = inner
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            push_variable(word, &name, name.len() == inner.len());
+            end + 1
+        }
+        Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+            let end = (start + 1..chars.len())
+                .find(|&j| !(chars[j].is_ascii_alphanumeric() || chars[j] == '_'))
+                .unwrap_or(chars.len());
+            push_variable(word, &source(start + 1, end), true);
+            end
+        }
+        Some(c) if c.is_ascii_digit() || "@*#?$!-".contains(*c) => {
+            word.text.push('$');
+            word.text.push(*c);
+            word.dynamic = true;
+            start + 2
+        }
+        _ => {
+            word.text.push('$');
+            start + 1
+        }
+    }
+}
+
+/// `$HOME` 的值可以确定，换成 `~`；其他变量保留名称并标记为动态
+fn push_variable(word: &mut Word, name: &str, plain: bool) {
+    if name == "HOME" && plain {
+        word.text.push('~');
+    } else {
+        word.text.push('$');
+        word.text.push_str(name);
+        word.dynamic = true;
+    }
+}
+
+/// 一条简单命令
+#[derive(Debug, Default)]
+struct SimpleCommand {
+    words: Vec<Word>,
+    /// 输出重定向目标
+    writes: Vec<Word>,
+    /// 输入重定向来源
+    reads: Vec<Word>,
+    /// 标准输入来自管道
+    piped: bool,
+}
+
+/// 按连接符拆成管道，每个管道由若干简单命令组成
+fn parse(tokens: Vec<Token>) -> Vec<Vec<SimpleCommand>> {
+    let mut pipelines = Vec::new();
+    let mut pipeline = Vec::new();
+    let mut current = SimpleCommand::default();
+    let mut pending: Option<bool> = None;
+    for token in tokens {
+        match token {
+            Token::Word(word) => match pending.take() {
+                Some(true) => current.writes.push(word),
+                Some(false) => current.reads.push(word),
+                None => current.words.push(word),
+            },
+            Token::WriteTo => pending = Some(true),
+            Token::ReadFrom => pending = Some(false),
+            Token::Op("|") => {
+                pipeline.push(std::mem::take(&mut current));
+                current.piped = true;
+            }
+            Token::Op(_) => {
+                pipeline.push(std::mem::take(&mut current));
+                pipelines.push(std::mem::take(&mut pipeline));
+            }
+        }
+    }
+    pipeline.push(current);
+    pipelines.push(pipeline);
+    pipelines
+        .into_iter()
+        .map(|pipeline: Vec<SimpleCommand>| {
+            pipeline
+                .into_iter()
+                .filter(|c| !c.words.is_empty() || !c.writes.is_empty() || !c.reads.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|pipeline| !pipeline.is_empty())
+        .collect()
+}
+
+/// 命令名（去掉路径）
+fn command_name(text: &str) -> &str {
+    text.rsplit('/').next().unwrap_or(text)
+}
+
+fn is_assignment(text: &str) -> bool {
+    text.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// 跳过选项，`with_value` 中的选项带一个参数
+fn skip_options(words: &[Word], mut i: usize, with_value: &[&str]) -> usize {
+    while let Some(word) = words.get(i).filter(|w| w.text.starts_with('-')) {
+        if word.text == "--" {
+            return i + 1;
+        }
+        i += if with_value.contains(&word.text.as_str()) {
+            2
+        } else {
+            1
+        };
+    }
+    i
+}
+
+fn is_sensitive(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    SENSITIVE_PATH_PATTERNS.iter().any(|p| lower.contains(p))
+}
+
+fn is_system_path(path: &Path) -> bool {
+    SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+/// `rm` 目标的位置
+enum Target {
+    /// 含变量或命令替换
+    Dynamic,
+    /// 根目录、home、系统目录、项目目录本身或其上级
+    Critical(&'static str),
+    /// 项目目录之内
+    Project,
+    /// 临时目录
+    Temp,
+    /// 项目目录之外
+    Outside,
+    /// 相对路径且不知道工作目录
+    Unknown,
+}
+
+struct Analyzer {
+    /// 项目目录
+    project: Option<PathBuf>,
+    /// 当前目录（随 `cd` 变化，无法确定时为 None）
+    cwd: Option<PathBuf>,
+    home: Option<PathBuf>,
+    findings: Vec<RiskFinding>,
+    commands: usize,
+    read_only: bool,
+}
+
+impl Analyzer {
+    fn add(&mut self, risk: RiskLevel, rule: &'static str, detail: String) {
+        if !self
+            .findings
+            .iter()
+            .any(|f| f.rule == rule && f.detail == detail)
+        {
+            self.findings.push(RiskFinding { risk, rule, detail });
+        }
+    }
+
+    fn analyze(&mut self, command: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            self.add(
+                RiskLevel::High,
+                "nested",
+                "命令嵌套过深，无法分析".to_string(),
+            );
+            return;
+        }
+        if FORK_BOMB_RE.is_match(command) {
+            self.add(RiskLevel::High, "fork_bomb", "fork 炸弹".to_string());
+        }
+        for pipeline in parse(tokenize(&strip_heredocs(command))) {
+            let downloads = pipeline.iter().any(|cmd| {
+                let words = self.strip_prefixes(&cmd.words, false);
+                words
+                    .first()
+                    .is_some_and(|w| matches!(command_name(&w.text), "curl" | "wget"))
+            });
+            for cmd in &pipeline {
+                self.command(cmd, downloads, depth);
+            }
+        }
+    }
+
+    /// 去掉 `VAR=x`、`sudo`、`env`、`nohup` 等前缀，返回实际命令开始的位置
+    fn strip_prefixes<'a>(&mut self, words: &'a [Word], record: bool) -> &'a [Word] {
+        let mut i = 0;
+        while let Some(word) = words.get(i) {
+            if is_assignment(&word.text) {
+                i += 1;
+                continue;
+            }
+            let name = command_name(&word.text);
+            i = match name {
+                "sudo" | "doas" => {
+                    if record {
+                        self.add(
+                            RiskLevel::High,
+                            "sudo",
+                            format!("{} 以管理员权限执行", name),
+                        );
+                    }
+                    skip_options(
+                        words,
+                        i + 1,
+                        &["-u", "-g", "-C", "-D", "-h", "-p", "-U", "-r", "-t"],
+                    )
+                }
+                "env" if words.len() > i + 1 => skip_options(words, i + 1, &["-u", "-C", "-S"]),
+                "nohup" | "time" | "command" | "builtin" | "exec" | "stdbuf" => {
+                    skip_options(words, i + 1, &[])
+                }
+                "nice" | "ionice" => skip_options(words, i + 1, &["-n", "-c"]),
+                "timeout" => skip_options(words, i + 1, &["-s", "-k"]) + 1,
+                _ => break,
+            };
+        }
+        &words[i.min(words.len())..]
+    }
+
+    fn command(&mut self, cmd: &SimpleCommand, downloads: bool, depth: usize) {
+        for word in cmd.words.iter().chain(&cmd.writes).chain(&cmd.reads) {
+            for substitution in &word.substitutions {
+                self.add(
+                    RiskLevel::Medium,
+                    "substitution",
+                    format!("命令替换 {}", truncate(&word.text)),
+                );
+                self.analyze(substitution, depth + 1);
+            }
+        }
+        for word in cmd.reads.iter().chain(&cmd.writes) {
+            if is_sensitive(&word.text) {
+                self.add(
+                    RiskLevel::High,
+                    "sensitive_path",
+                    format!("涉及敏感路径 {}", word.text),
+                );
+            }
+        }
+        for target in &cmd.writes {
+            self.redirect(target);
+        }
+
+        let words = self.strip_prefixes(&cmd.words, true);
+        let Some(first) = words.first() else {
+            return;
+        };
+        self.commands += 1;
+        let name = command_name(&first.text);
+        let args = &words[1..];
+        if first.dynamic {
+            self.add(
+                RiskLevel::High,
+                "dynamic_command",
+                format!("执行动态命令 {}", truncate(&first.text)),
+            );
+        }
+        for arg in args {
+            if is_sensitive(&arg.text) {
+                self.add(
+                    RiskLevel::High,
+                    "sensitive_path",
+                    format!("涉及敏感路径 {}", arg.text),
+                );
+            } else if arg.dynamic && arg.substitutions.is_empty() {
+                self.add(
+                    RiskLevel::Medium,
+                    "variable",
+                    format!("参数含变量 {}，实际值无法预知", arg.text),
+                );
+            }
+        }
+
+        let read_only = match name {
+            _ if first.dynamic => false,
+            "cd" => {
+                self.cwd = match args.first() {
+                    None => self.home.clone(),
+                    Some(dir) if dir.dynamic || dir.text == "-" => None,
+                    Some(dir) => self.resolve(&dir.text),
+                };
+                true
+            }
+            "find" => self.find(args, depth),
+            "git" => self.git(args),
+            _ if SHELLS.contains(&name) => {
+                self.shell(name, args, cmd.piped, downloads, depth);
+                false
+            }
+            _ if INTERPRETERS.contains(&name) => {
+                if cmd.piped && args.iter().all(|a| a.text.starts_with('-')) {
+                    self.pipe_to_shell(name, downloads);
+                }
+                false
+            }
+            "eval" => {
+                self.add(
+                    RiskLevel::High,
+                    "eval",
+                    "eval 执行动态拼接的命令".to_string(),
+                );
+                let joined: Vec<&str> = args.iter().map(|a| a.text.as_str()).collect();
+                self.analyze(&joined.join(" "), depth + 1);
+                false
+            }
+            "source" | "." => {
+                let script = args.first().map_or("", |a| a.text.as_str());
+                self.add(
+                    RiskLevel::Medium,
+                    "source",
+                    format!("加载并执行脚本 {}", script),
+                );
+                false
+            }
+            "xargs" => {
+                let start =
+                    skip_options(args, 0, &["-I", "-n", "-P", "-L", "-d", "-s", "-E", "-a"]);
+                let joined: Vec<&str> = args[start..].iter().map(|a| a.text.as_str()).collect();
+                let joined = if joined.is_empty() {
+                    "echo".to_string()
+                } else {
+                    joined.join(" ")
+                };
+                self.add(
+                    RiskLevel::Medium,
+                    "xargs",
+                    format!("xargs 按输入执行 {}", truncate(&joined)),
+                );
+                self.analyze(&joined, depth + 1);
+                false
+            }
+            "rm" | "rmdir" | "unlink" | "shred" => {
+                self.remove(name, args);
+                false
+            }
+            "chmod" => {
+                self.chmod(args);
+                false
+            }
+            "chown" | "chgrp" => {
+                self.add(RiskLevel::High, "chown", format!("{} 修改文件所有者", name));
+                false
+            }
+            "dd" => {
+                if let Some(of) = args.iter().find(|a| a.text.starts_with("of=")) {
+                    self.add(
+                        RiskLevel::High,
+                        "dd",
+                        format!("dd 直接写入 {}", &of.text[3..]),
+                    );
+                }
+                false
+            }
+            "fdisk" | "parted" | "diskutil" | "wipefs" => {
+                self.add(RiskLevel::High, "disk", format!("{} 修改磁盘分区", name));
+                false
+            }
+            _ if name.starts_with("mkfs") => {
+                self.add(RiskLevel::High, "disk", format!("{} 格式化磁盘", name));
+                false
+            }
+            "shutdown" | "reboot" | "halt" | "poweroff" => {
+                self.add(RiskLevel::High, "shutdown", format!("{} 关机或重启", name));
+                false
+            }
+            "sed" => !args.iter().any(|a| a.text.starts_with("-i")),
+            _ => READ_ONLY_COMMANDS.contains(&name),
+        };
+        self.read_only &= read_only;
+    }
+
+    fn pipe_to_shell(&mut self, name: &str, downloads: bool) {
+        let detail = if downloads {
+            format!("下载的内容直接交给 {} 执行", name)
+        } else {
+            format!("管道输入交给 {} 执行", name)
+        };
+        self.add(RiskLevel::High, "pipe_to_shell", detail);
+    }
+
+    fn shell(&mut self, name: &str, args: &[Word], piped: bool, downloads: bool, depth: usize) {
+        let inline = args.iter().position(|a| {
+            a.text == "-c"
+                || (a.text.starts_with('-') && !a.text.starts_with("--") && a.text.contains('c'))
+        });
+        if let Some(pos) = inline {
+            if let Some(script) = args.get(pos + 1) {
+                self.analyze(&script.text, depth + 1);
+            }
+            return;
+        }
+        match args
+            .iter()
+            .find(|a| !a.text.starts_with('-') || a.text == "-")
+        {
+            Some(script) if script.text != "-" => self.add(
+                RiskLevel::Medium,
+                "script",
+                format!("执行脚本 {}", script.text),
+            ),
+            _ if piped => self.pipe_to_shell(name, downloads),
+            _ => {}
+        }
+    }
+
+    /// `find` 的 `-delete`/`-exec` 不是只读操作，返回是否只读
+    fn find(&mut self, args: &[Word], depth: usize) -> bool {
+        let mut read_only = true;
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].text.as_str() {
+                "-delete" => {
+                    self.add(
+                        RiskLevel::Medium,
+                        "find_delete",
+                        "find -delete 删除匹配的文件".to_string(),
+                    );
+                    read_only = false;
+                }
+                "-exec" | "-execdir" | "-ok" | "-okdir" => {
+                    let end = (i + 1..args.len())
+                        .find(|&j| matches!(args[j].text.as_str(), ";" | "+"))
+                        .unwrap_or(args.len());
+                    let exec: Vec<&str> =
+                        args[i + 1..end].iter().map(|a| a.text.as_str()).collect();
+                    let exec = exec.join(" ");
+                    self.add(
+                        RiskLevel::Medium,
+                        "find_exec",
+                        format!("find 对匹配的文件执行 {}", truncate(&exec)),
+                    );
+                    self.analyze(&exec, depth + 1);
+                    read_only = false;
+                    i = end;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        read_only
+    }
+
+    /// 返回是否只读
+    fn git(&mut self, args: &[Word]) -> bool {
+        let start = skip_options(args, 0, &["-C", "-c", "--git-dir", "--work-tree"]);
+        let Some(sub) = args.get(start).map(|w| w.text.as_str()) else {
+            return true;
+        };
+        let rest = &args[start + 1..];
+        let has = |flags: &[&str]| rest.iter().any(|a| flags.contains(&a.text.as_str()));
+        match sub {
+            "push"
+                if has(&[
+                    "--force",
+                    "-f",
+                    "--force-with-lease",
+                    "--mirror",
+                    "--delete",
+                    "-d",
+                ]) || rest.iter().any(|a| a.text.starts_with('+')) =>
+            {
+                self.add(
+                    RiskLevel::High,
+                    "git_force_push",
+                    "git push 强制推送或删除远程分支".to_string(),
+                );
+            }
+            "reset" if has(&["--hard"]) => self.add(
+                RiskLevel::High,
+                "git_reset_hard",
+                "git reset --hard 丢弃未提交的修改".to_string(),
+            ),
+            "clean"
+                if rest.iter().any(|a| {
+                    a.text.starts_with('-') && !a.text.starts_with("--") && a.text.contains('f')
+                }) || has(&["--force"]) =>
+            {
+                self.add(
+                    RiskLevel::High,
+                    "git_clean",
+                    "git clean 删除未跟踪的文件".to_string(),
+                )
+            }
+            "branch" if has(&["-D"]) => self.add(
+                RiskLevel::Medium,
+                "git_branch_delete",
+                "git branch -D 强制删除分支".to_string(),
+            ),
+            _ => {}
+        }
+        READ_ONLY_GIT.contains(&sub)
+            || (sub == "branch" && rest.is_empty())
+            || (sub == "remote" && rest.iter().all(|a| a.text == "-v"))
+    }
+
+    fn chmod(&mut self, args: &[Word]) {
+        let Some(mode) = args.iter().find(|a| !a.text.starts_with('-')) else {
+            return;
+        };
+        let mode = mode.text.as_str();
+        let world_writable = mode.len() >= 3
+            && mode.chars().all(|c| c.is_ascii_digit())
+            && mode
+                .chars()
+                .last()
+                .and_then(|c| c.to_digit(8))
+                .is_some_and(|other| other & 2 != 0);
+        if world_writable || mode.contains("o+w") || mode.contains("a+w") || mode == "+w" {
+            self.add(
+                RiskLevel::High,
+                "chmod",
+                format!("chmod {} 允许所有用户写入", mode),
+            );
+        } else if mode.contains("+s") || (mode.len() == 4 && mode.starts_with(['4', '2', '6'])) {
+            self.add(
+                RiskLevel::High,
+                "chmod",
+                format!("chmod {} 设置 setuid/setgid", mode),
+            );
+        }
+    }
+
+    /// 相对路径按工作目录解析（不知道工作目录时为 None）
+    fn resolve(&self, text: &str) -> Option<PathBuf> {
+        let path = match (text, &self.home) {
+            ("~", Some(home)) => home.clone(),
+            _ => expand_home(text),
+        };
+        if path.is_absolute() {
+            Some(normalize_path(&path))
+        } else {
+            self.cwd.as_ref().map(|cwd| normalize_path(&cwd.join(path)))
+        }
+    }
+
+    fn classify(&self, word: &Word) -> Target {
+        if word.dynamic {
+            return Target::Dynamic;
+        }
+        // 删除 `dir/*` 相当于清空 dir
+        let text = word.text.trim_end_matches('/');
+        let text = match text.rsplit_once('/') {
+            Some((parent, last)) if last.contains(['*', '?']) => {
+                if parent.is_empty() {
+                    "/"
+                } else {
+                    parent
+                }
+            }
+            None if text.contains(['*', '?']) => ".",
+            _ if text.is_empty() => "/",
+            _ => text,
+        };
+        let Some(path) = self.resolve(text) else {
+            let relative = normalize_path(Path::new(text));
+            return if relative.as_os_str().is_empty() {
+                Target::Critical("当前目录")
+            } else if text.starts_with("..") {
+                Target::Outside
+            } else {
+                Target::Unknown
+            };
+        };
+        if path.parent().is_none() {
+            return Target::Critical("根目录");
+        }
+        if self
+            .home
+            .as_ref()
+            .is_some_and(|home| home.starts_with(&path))
+        {
+            return Target::Critical("home 目录");
+        }
+        if let Some(project) = &self.project {
+            if project.starts_with(&path) {
+                return Target::Critical("项目目录");
+            }
+            if path.starts_with(project) {
+                return Target::Project;
+            }
+        }
+        if is_temp_path(&path) {
+            Target::Temp
+        } else if is_system_path(&path) {
+            Target::Critical("系统目录")
+        } else {
+            Target::Outside
+        }
+    }
+
+    fn remove(&mut self, name: &str, args: &[Word]) {
+        let mut recursive = false;
+        let mut targets = Vec::new();
+        let mut options_done = false;
+        for arg in args {
+            match arg.text.as_str() {
+                "--" if !options_done => options_done = true,
+                "--recursive" if !options_done => recursive = true,
+                "--no-preserve-root" if !options_done => self.add(
+                    RiskLevel::High,
+                    "rm_no_preserve_root",
+                    format!("{} --no-preserve-root 允许删除根目录", name),
+                ),
+                text if !options_done
+                    && text.starts_with('-')
+                    && text.len() > 1
+                    && !arg.dynamic =>
+                {
+                    recursive |= !text.starts_with("--") && text.contains(['r', 'R']);
+                }
+                _ => targets.push(arg),
+            }
+        }
+
+        let verb = if recursive { "递归删除" } else { "删除" };
+        let elevated = if recursive {
+            RiskLevel::High
+        } else {
+            RiskLevel::Medium
+        };
+        for target in targets {
+            let text = &target.text;
+            match self.classify(target) {
+                Target::Dynamic => self.add(
+                    elevated,
+                    "rm_dynamic",
+                    format!("{}的目标含变量 {}，展开结果无法预知", verb, text),
+                ),
+                Target::Critical(what) => self.add(
+                    RiskLevel::High,
+                    "rm_critical",
+                    format!("{}{} {}", verb, what, text),
+                ),
+                Target::Outside => self.add(
+                    elevated,
+                    "rm_outside_project",
+                    format!("{}项目外路径 {}", verb, text),
+                ),
+                Target::Project | Target::Unknown if recursive => self.add(
+                    RiskLevel::Medium,
+                    "rm_recursive",
+                    format!("递归删除 {}", text),
+                ),
+                Target::Project | Target::Unknown | Target::Temp => {}
+            }
+        }
+    }
+
+    fn redirect(&mut self, target: &Word) {
+        let text = target.text.as_str();
+        if matches!(
+            text,
+            "/dev/null" | "/dev/stdout" | "/dev/stderr" | "/dev/tty"
+        ) {
+            return;
+        }
+        self.read_only = false;
+        if target.dynamic {
+            self.add(
+                RiskLevel::Medium,
+                "redirect",
+                format!("重定向写入 {}（路径含变量）", text),
+            );
+            return;
+        }
+        let path = self.resolve(text);
+        let (risk, detail) = match &path {
+            Some(path) if path.starts_with("/dev") => {
+                (RiskLevel::High, format!("直接写入设备 {}", text))
+            }
+            Some(path) if !is_temp_path(path) && is_system_path(path) => {
+                (RiskLevel::High, format!("重定向写入系统文件 {}", text))
+            }
+            Some(path)
+                if self
+                    .project
+                    .as_ref()
+                    .is_some_and(|project| !path.starts_with(project))
+                    && !is_temp_path(path) =>
+            {
+                (RiskLevel::Medium, format!("重定向写入项目外文件 {}", text))
+            }
+            _ => (RiskLevel::Medium, format!("重定向写入 {}", text)),
+        };
+        self.add(risk, "redirect", detail);
+    }
+}
+
+fn truncate(text: &str) -> String {
+    crate::infra::text::truncate_width(text, 40)
+}
+
+/// 分析一条 Bash 命令，`cwd` 为执行目录（通常是项目目录），用于解析 `rm` 和重定向的相对路径
+pub fn analyze_bash(command: &str, cwd: Option<&str>) -> RiskAnalysis {
+    let project = cwd
+        .filter(|cwd| !cwd.is_empty())
+        .map(|cwd| normalize_path(&expand_home(cwd)));
+    let mut analyzer = Analyzer {
+        cwd: project.clone(),
+        project,
+        home: dirs::home_dir(),
+        findings: Vec::new(),
+        commands: 0,
+        read_only: true,
+    };
+    analyzer.analyze(command, 0);
+
+    let mut findings = analyzer.findings;
+    findings.sort_by_key(|f| std::cmp::Reverse(f.risk));
+    let baseline = if analyzer.read_only && analyzer.commands > 0 {
+        RiskLevel::Low
+    } else {
+        RiskLevel::Medium
+    };
+    RiskAnalysis {
+        risk: findings.first().map_or(baseline, |f| f.risk.max(baseline)),
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn risk(command: &str) -> RiskLevel {
+        analyze_bash(command, Some("/work/app")).risk
+    }
+
+    fn rules(command: &str) -> Vec<&'static str> {
+        analyze_bash(command, Some("/work/app"))
+            .findings
+            .iter()
+            .map(|f| f.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_quotes_and_operators() {
+        let pipelines = parse(tokenize(
+            r#"cd "my dir" && grep -r 'a|b' src 2>&1 | head -n 5 > out.txt; echo "$X" \$Y"#,
+        ));
+        let texts: Vec<Vec<Vec<&str>>> = pipelines
+            .iter()
+            .map(|p| {
+                p.iter()
+                    .map(|c| c.words.iter().map(|w| w.text.as_str()).collect())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                vec![vec!["cd", "my dir"]],
+                vec![vec!["grep", "-r", "a|b", "src"], vec!["head", "-n", "5"]],
+                vec![vec!["echo", "$X", "$Y"]],
+            ]
+        );
+        assert!(pipelines[1][1].piped);
+        assert_eq!(pipelines[1][1].writes[0].text, "out.txt");
+        assert!(pipelines[2][0].words[1].dynamic);
+        assert!(!pipelines[2][0].words[2].dynamic);
+    }
+
+    #[test]
+    fn test_chains_of_safe_commands() {
+        assert_eq!(risk("cd src && ls -la | grep rs"), RiskLevel::Low);
+        assert_eq!(risk("git status; git diff --stat"), RiskLevel::Low);
+        assert_eq!(risk("cargo build && cargo test"), RiskLevel::Medium);
+        assert_eq!(risk("cat <<EOF\nrm -rf /\nEOF"), RiskLevel::Low);
+        assert!(analyze_bash("ls", None).findings.is_empty());
+    }
+
+    #[test]
+    fn test_pipe_to_shell_and_prefixes() {
+        assert_eq!(
+            analyze_bash("curl -fsSL https://x.sh | sudo bash", None).details(),
+            ["sudo 以管理员权限执行", "下载的内容直接交给 bash 执行"]
+        );
+        assert_eq!(rules("wget -qO- x | python3"), ["pipe_to_shell"]);
+        assert_eq!(rules("env FOO=1 nohup sudo -u root ls"), ["sudo"]);
+        assert_eq!(risk("bash scripts/build.sh"), RiskLevel::Medium);
+        assert_eq!(rules("bash -c 'ls && rm -rf /'"), ["rm_critical"]);
+        assert_eq!(
+            rules("eval \"$CMD\""),
+            ["eval", "dynamic_command", "variable"]
+        );
+        assert_eq!(rules(":(){ :|:& };:")[0], "fork_bomb");
+    }
+
+    #[test]
+    fn test_rm_targets() {
+        // 项目内递归删除为中风险，项目外/根目录/home/变量为高风险
+        assert_eq!(risk("rm -rf target/debug"), RiskLevel::Medium);
+        assert_eq!(risk("rm -rf /work/app/node_modules"), RiskLevel::Medium);
+        assert_eq!(risk("rm -rf /tmp/build"), RiskLevel::Medium);
+        assert_eq!(risk("rm notes.txt"), RiskLevel::Medium);
+        assert_eq!(rules("rm -rf /"), ["rm_critical"]);
+        assert_eq!(rules("rm -rf /*"), ["rm_critical"]);
+        assert_eq!(rules("rm -rf ~"), ["rm_critical"]);
+        assert_eq!(rules("rm -fr $HOME"), ["rm_critical"]);
+        assert_eq!(rules("rm -rf *"), ["rm_critical"]);
+        assert_eq!(rules("rm -rf ../other"), ["rm_outside_project"]);
+        assert_eq!(rules("rm -rf /usr/local/lib"), ["rm_critical"]);
+        assert_eq!(rules("rm -rf \"$BUILD_DIR/\""), ["rm_dynamic", "variable"]);
+        assert_eq!(risk("rm ../other/file"), RiskLevel::Medium);
+        // 不知道工作目录时，相对路径无法判断是否在项目内
+        assert_eq!(analyze_bash("rm -rf build", None).risk, RiskLevel::Medium);
+        assert_eq!(analyze_bash("rm -rf /srv/data", None).risk, RiskLevel::High);
+    }
+
+    #[test]
+    fn test_redirects_and_substitutions() {
+        assert_eq!(risk("cargo test 2>&1 > /dev/null"), RiskLevel::Medium);
+        assert!(rules("cargo test 2>&1 > /dev/null").is_empty());
+        assert_eq!(risk("ls > files.txt"), RiskLevel::Medium);
+        assert_eq!(risk("echo x > /etc/hosts"), RiskLevel::High);
+        assert_eq!(risk("echo 1 > /dev/sda"), RiskLevel::High);
+        assert_eq!(risk("echo `whoami`"), RiskLevel::Medium);
+        assert_eq!(risk("echo $(cat ~/.ssh/id_rsa)"), RiskLevel::High);
+        assert_eq!(risk("find . -name '*.o' -delete"), RiskLevel::Medium);
+        assert_eq!(risk("find / -exec rm -rf {} +"), RiskLevel::Medium);
+        assert_eq!(risk("ls | xargs sudo rm"), RiskLevel::High);
+    }
+
+    #[test]
+    fn test_git_and_permissions() {
+        assert_eq!(risk("git log --oneline -5"), RiskLevel::Low);
+        assert_eq!(risk("git -C sub push origin main"), RiskLevel::Medium);
+        assert_eq!(rules("git push --force origin main"), ["git_force_push"]);
+        assert_eq!(rules("git push origin +main"), ["git_force_push"]);
+        assert_eq!(rules("git reset --hard HEAD~1"), ["git_reset_hard"]);
+        assert_eq!(rules("git clean -fdx"), ["git_clean"]);
+        assert_eq!(rules("chmod 777 run.sh"), ["chmod"]);
+        assert_eq!(risk("chmod +x run.sh"), RiskLevel::Medium);
+        assert_eq!(rules("dd if=img.iso of=/dev/disk2"), ["dd"]);
+    }
+}
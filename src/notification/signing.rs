@@ -0,0 +1,273 @@
+//! Webhook 签名 - HMAC-SHA256 签名与验证，带时间戳 + nonce 防重放
+//!
+//! config.json 的 `webhook.signing_secret` 设置后，CAM 发出的每个 webhook 请求都带有：
+//!
+//! - `X-CAM-Timestamp`：Unix 秒
+//! - `X-CAM-Nonce`：每个请求唯一的随机串
+//! - `X-CAM-Signature`：`sha256=<hex>`，对 `"{timestamp}.{nonce}.{body}"` 计算的 HMAC-SHA256
+//!
+//! 接收方用 [`WebhookVerifier`]（或 `WebhookClient::verifier`）验证：签名不符、时间戳超出
+//! [`DEFAULT_TOLERANCE_SECS`] 或 nonce 已出现过的请求都被拒绝。`cam serve --webhook-port`
+//! 用同一个密钥验证收到的回复请求。
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const TIMESTAMP_HEADER: &str = "X-CAM-Timestamp";
+pub const NONCE_HEADER: &str = "X-CAM-Nonce";
+pub const SIGNATURE_HEADER: &str = "X-CAM-Signature";
+
+/// 允许的时间戳偏差（秒），超出视为重放或时钟错误
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+/// 签名后需要附加的请求头
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHeaders {
+    pub timestamp: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+impl SignatureHeaders {
+    /// (header 名, 值) 列表
+    pub fn pairs(&self) -> [(&'static str, &str); 3] {
+        [
+            (TIMESTAMP_HEADER, &self.timestamp),
+            (NONCE_HEADER, &self.nonce),
+            (SIGNATURE_HEADER, &self.signature),
+        ]
+    }
+}
+
+/// 验证失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// 缺少签名相关的请求头
+    MissingHeader(&'static str),
+    /// 时间戳无法解析或超出允许偏差
+    Expired,
+    /// 签名不符
+    Mismatch,
+    /// nonce 已使用过
+    Replayed,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::MissingHeader(name) => write!(f, "missing header {}", name),
+            SignatureError::Expired => write!(f, "timestamp outside tolerance"),
+            SignatureError::Mismatch => write!(f, "signature mismatch"),
+            SignatureError::Replayed => write!(f, "nonce already used"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+fn mac(secret: &str, timestamp: &str, nonce: &str, body: &[u8]) -> HmacSha256 {
+    // HMAC 接受任意长度的密钥
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(nonce.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// 生成 nonce（只要求唯一，不用作密钥）
+fn new_nonce() -> String {
+    let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(nanos);
+    hasher.write_u32(std::process::id());
+    format!("{:016x}{:016x}", nanos as u64, hasher.finish())
+}
+
+/// 按指定时间戳和 nonce 签名
+pub fn sign_with(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> SignatureHeaders {
+    let timestamp = timestamp.to_string();
+    let signature = to_hex(&mac(secret, &timestamp, nonce, body).finalize().into_bytes());
+    SignatureHeaders {
+        timestamp,
+        nonce: nonce.to_string(),
+        signature: format!("sha256={}", signature),
+    }
+}
+
+/// 用当前时间和新 nonce 签名请求体
+pub fn sign(secret: &str, body: &[u8]) -> SignatureHeaders {
+    sign_with(secret, chrono::Utc::now().timestamp(), &new_nonce(), body)
+}
+
+/// 签名验证器，记住容忍窗口内见过的 nonce
+#[derive(Debug)]
+pub struct WebhookVerifier {
+    secret: String,
+    tolerance_secs: i64,
+    /// nonce -> 时间戳
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl WebhookVerifier {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            tolerance_secs: DEFAULT_TOLERANCE_SECS,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 设置允许的时间戳偏差
+    pub fn with_tolerance(mut self, tolerance_secs: i64) -> Self {
+        self.tolerance_secs = tolerance_secs;
+        self
+    }
+
+    /// 按当前时间验证
+    pub fn verify(
+        &self,
+        timestamp: &str,
+        nonce: &str,
+        signature: &str,
+        body: &[u8],
+    ) -> Result<(), SignatureError> {
+        self.verify_at(
+            timestamp,
+            nonce,
+            signature,
+            body,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// 从请求头验证，`header` 按名称（不区分大小写）取值
+    pub fn verify_headers<'a>(
+        &self,
+        header: impl Fn(&str) -> Option<&'a str>,
+        body: &[u8],
+    ) -> Result<(), SignatureError> {
+        let get = |name: &'static str| header(name).ok_or(SignatureError::MissingHeader(name));
+        self.verify(
+            get(TIMESTAMP_HEADER)?,
+            get(NONCE_HEADER)?,
+            get(SIGNATURE_HEADER)?,
+            body,
+        )
+    }
+
+    /// 按指定的当前时间验证（用于测试）
+    pub fn verify_at(
+        &self,
+        timestamp: &str,
+        nonce: &str,
+        signature: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), SignatureError> {
+        let ts: i64 = timestamp.parse().map_err(|_| SignatureError::Expired)?;
+        if (now - ts).abs() > self.tolerance_secs {
+            return Err(SignatureError::Expired);
+        }
+        let expected = signature
+            .strip_prefix("sha256=")
+            .and_then(from_hex)
+            .ok_or(SignatureError::Mismatch)?;
+        mac(&self.secret, timestamp, nonce, body)
+            .verify_slice(&expected)
+            .map_err(|_| SignatureError::Mismatch)?;
+
+        // 签名有效后才记录 nonce，避免伪造请求填满缓存
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, seen_ts| (now - *seen_ts).abs() <= self.tolerance_secs);
+        if seen.insert(nonce.to_string(), ts).is_some() {
+            return Err(SignatureError::Replayed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let body = br#"{"message":"hello"}"#;
+        let headers = sign_with("s3cret", 1_700_000_000, "n1", body);
+        assert!(headers.signature.starts_with("sha256="));
+        assert_eq!(headers.signature.len(), "sha256=".len() + 64);
+
+        let verifier = WebhookVerifier::new("s3cret");
+        let verify = |h: &SignatureHeaders, body: &[u8], now: i64| {
+            verifier.verify_at(&h.timestamp, &h.nonce, &h.signature, body, now)
+        };
+        assert_eq!(verify(&headers, body, 1_700_000_010), Ok(()));
+        // 同一 nonce 再次出现
+        assert_eq!(
+            verify(&headers, body, 1_700_000_020),
+            Err(SignatureError::Replayed)
+        );
+        // 篡改内容
+        let other = sign_with("s3cret", 1_700_000_000, "n2", body);
+        assert_eq!(
+            verify(&other, b"{}", 1_700_000_000),
+            Err(SignatureError::Mismatch)
+        );
+        // 超出时间窗口
+        let stale = sign_with("s3cret", 1_700_000_000, "n3", body);
+        assert_eq!(
+            verify(&stale, body, 1_700_000_000 + DEFAULT_TOLERANCE_SECS + 1),
+            Err(SignatureError::Expired)
+        );
+        // 不同的密钥
+        let forged = sign_with("wrong", 1_700_000_000, "n4", body);
+        assert_eq!(
+            verify(&forged, body, 1_700_000_000),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_headers() {
+        let body = b"payload";
+        let signed = sign("key", body);
+        assert_ne!(sign("key", body).nonce, signed.nonce);
+
+        let headers: HashMap<String, String> = signed
+            .pairs()
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+            .collect();
+        let lookup = |name: &str| headers.get(&name.to_lowercase()).map(String::as_str);
+        let verifier = WebhookVerifier::new("key");
+        assert_eq!(verifier.verify_headers(lookup, body), Ok(()));
+        assert_eq!(
+            verifier.verify_headers(|_| None, body),
+            Err(SignatureError::MissingHeader(TIMESTAMP_HEADER))
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! 通知暂停（snooze）- 聊天中 `snooze 30m` 暂停提醒
+//!
+//! 状态保存在 `~/.config/code-agent-monitor/snooze.json`，可暂停全部 agent 或单个 agent。
+//! 暂停期间 MEDIUM 通知不发送；HIGH 通知（权限请求、错误等需要决策的事件）照常发送。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 暂停状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnoozeState {
+    /// 全部 agent 暂停到该时间
+    pub all: Option<DateTime<Utc>>,
+    /// 单个 agent 暂停到该时间
+    pub agents: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl SnoozeState {
+    /// 状态文件路径
+    pub fn path() -> PathBuf {
+        crate::infra::paths::state_dir().join("snooze.json")
+    }
+
+    /// 读取状态，缺失或无效时为空
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入状态（先清理已过期的条目）
+    pub fn save(&mut self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    fn save_to(&mut self, path: &Path) -> Result<()> {
+        let now = Utc::now();
+        self.all = self.all.filter(|until| *until > now);
+        self.agents.retain(|_, until| *until > now);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 暂停 `target`（None 为全部）到 `until`
+    pub fn snooze(&mut self, target: Option<&str>, until: DateTime<Utc>) {
+        match target {
+            Some(agent_id) => {
+                self.agents.insert(agent_id.to_string(), until);
+            }
+            None => self.all = Some(until),
+        }
+    }
+
+    /// 恢复 `target`（None 为全部，同时清除单个 agent 的暂停）
+    pub fn resume(&mut self, target: Option<&str>) {
+        match target {
+            Some(agent_id) => {
+                self.agents.remove(agent_id);
+            }
+            None => {
+                self.all = None;
+                self.agents.clear();
+            }
+        }
+    }
+
+    /// agent 在 `now` 时是否处于暂停中，返回暂停截止时间
+    pub fn snoozed_until(&self, agent_id: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        [self.all, self.agents.get(agent_id).copied()]
+            .into_iter()
+            .flatten()
+            .filter(|until| *until > now)
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snooze_all_and_single_agent() {
+        let now = Utc::now();
+        let mut state = SnoozeState::default();
+        state.snooze(Some("cam-1"), now + Duration::minutes(30));
+        assert!(state.snoozed_until("cam-1", now).is_some());
+        assert!(state.snoozed_until("cam-2", now).is_none());
+        assert!(state
+            .snoozed_until("cam-1", now + Duration::minutes(31))
+            .is_none());
+
+        state.snooze(None, now + Duration::hours(1));
+        assert_eq!(
+            state.snoozed_until("cam-2", now),
+            Some(now + Duration::hours(1))
+        );
+
+        state.resume(None);
+        assert!(state.snoozed_until("cam-1", now).is_none());
+    }
+
+    #[test]
+    fn test_save_drops_expired() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("snooze.json");
+        let now = Utc::now();
+        let mut state = SnoozeState::default();
+        state.snooze(Some("cam-old"), now - Duration::minutes(1));
+        state.snooze(Some("cam-new"), now + Duration::minutes(10));
+        state.save_to(&path).unwrap();
+
+        let loaded = SnoozeState::load_from(&path);
+        assert!(!loaded.agents.contains_key("cam-old"));
+        assert!(loaded.agents.contains_key("cam-new"));
+    }
+}
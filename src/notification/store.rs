@@ -3,11 +3,13 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use super::dedup_key::hash_content;
 use super::urgency::Urgency;
 
 /// 通知记录（JSONL 格式）
@@ -15,6 +17,9 @@ use super::urgency::Urgency;
 pub struct NotificationRecord {
     /// ISO8601 时间戳
     pub ts: DateTime<Utc>,
+    /// 通知 ID（`cam show <id>` 查看完整快照）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     /// Agent ID
     pub agent_id: String,
     /// 紧急程度
@@ -35,6 +40,42 @@ pub struct NotificationRecord {
     /// 风险等级
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub risk_level: Option<String>,
+    /// 渠道返回的消息 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// 作为回复发送时所回复的消息 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+
+/// 生成通知 ID（8 位十六进制）
+pub fn new_notification_id(agent_id: &str, ts: DateTime<Utc>) -> String {
+    let seed = format!(
+        "{}:{}",
+        agent_id,
+        ts.timestamp_nanos_opt().unwrap_or_default()
+    );
+    format!("{:08x}", hash_content(&seed) as u32)
+}
+
+/// 一个 agent 在某个渠道中的消息线程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationThread {
+    pub agent_id: String,
+    pub channel: String,
+    /// 线程首条消息，后续通知作为它的回复发送
+    pub root_message_id: String,
+    /// 线程内已发送的消息 ID（含首条，最多保留 MAX_THREAD_MESSAGES 条）
+    pub message_ids: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 支持回复/线程的渠道
+pub fn channel_supports_threads(channel: &str) -> bool {
+    matches!(
+        channel.to_ascii_lowercase().as_str(),
+        "telegram" | "slack" | "discord"
+    )
 }
 
 /// 通知存储
@@ -44,15 +85,116 @@ const MAX_NOTIFICATIONS: usize = 200;
 const KEEP_AFTER_CLEANUP: usize = 100;
 const CLEANUP_CHECK_INTERVAL: usize = 10;
 static WRITE_COUNT: AtomicUsize = AtomicUsize::new(0);
+const MAX_THREADS: usize = 100;
+const MAX_THREAD_MESSAGES: usize = 50;
+
+fn thread_key(channel: &str, agent_id: &str) -> String {
+    format!("{}/{}", channel, agent_id)
+}
+
+fn load_threads(path: &Path) -> BTreeMap<String, NotificationThread> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn thread_root_in(path: &Path, channel: &str, agent_id: &str) -> Option<String> {
+    load_threads(path)
+        .remove(&thread_key(channel, agent_id))
+        .map(|thread| thread.root_message_id)
+}
+
+fn record_thread_message_in(
+    path: &Path,
+    channel: &str,
+    agent_id: &str,
+    message_id: &str,
+) -> Result<()> {
+    let mut threads = load_threads(path);
+    let now = Utc::now();
+    let thread = threads
+        .entry(thread_key(channel, agent_id))
+        .or_insert_with(|| NotificationThread {
+            agent_id: agent_id.to_string(),
+            channel: channel.to_string(),
+            root_message_id: message_id.to_string(),
+            message_ids: Vec::new(),
+            updated_at: now,
+        });
+    if !thread.message_ids.iter().any(|id| id == message_id) {
+        thread.message_ids.push(message_id.to_string());
+    }
+    // 首条消息始终保留，便于回复它时仍能找到 agent
+    let excess = thread.message_ids.len().saturating_sub(MAX_THREAD_MESSAGES);
+    if excess > 0 {
+        thread.message_ids.drain(1..=excess);
+    }
+    thread.updated_at = now;
+
+    // 只保留最近活跃的线程
+    if threads.len() > MAX_THREADS {
+        let mut by_age: Vec<(String, DateTime<Utc>)> = threads
+            .iter()
+            .map(|(key, thread)| (key.clone(), thread.updated_at))
+            .collect();
+        by_age.sort_by_key(|(_, updated_at)| *updated_at);
+        for (key, _) in by_age.into_iter().take(threads.len() - MAX_THREADS) {
+            threads.remove(&key);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&threads)?)?;
+    Ok(())
+}
+
+fn agent_for_message_in(path: &Path, message_id: &str) -> Option<String> {
+    load_threads(path)
+        .into_values()
+        .find(|thread| {
+            thread.root_message_id == message_id
+                || thread.message_ids.iter().any(|id| id == message_id)
+        })
+        .map(|thread| thread.agent_id)
+}
+
+fn find_in(path: &Path, id: &str) -> Option<NotificationRecord> {
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<NotificationRecord>(&line).ok())
+        .filter(|record| record.id.as_deref() == Some(id))
+        .last()
+}
 
 impl NotificationStore {
     /// 获取存储文件路径
     pub fn path() -> PathBuf {
-        dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".config")
-            .join("code-agent-monitor")
-            .join("notifications.jsonl")
+        crate::infra::paths::state_dir().join("notifications.jsonl")
+    }
+
+    /// 消息线程索引路径
+    pub fn threads_path() -> PathBuf {
+        Self::path().with_file_name("threads.json")
+    }
+
+    /// agent 在该渠道中已有线程的首条消息 ID
+    pub fn thread_root(channel: &str, agent_id: &str) -> Option<String> {
+        thread_root_in(&Self::threads_path(), channel, agent_id)
+    }
+
+    /// 记录发送到渠道的消息 ID（首条消息成为线程起点）
+    pub fn record_thread_message(channel: &str, agent_id: &str, message_id: &str) -> Result<()> {
+        record_thread_message_in(&Self::threads_path(), channel, agent_id, message_id)
+    }
+
+    /// 根据用户回复的消息 ID 找到对应的 agent
+    pub fn agent_for_message(message_id: &str) -> Option<String> {
+        agent_for_message_in(&Self::threads_path(), message_id)
     }
 
     /// 追加通知记录（带文件锁）
@@ -107,6 +249,11 @@ impl NotificationStore {
         recent
     }
 
+    /// 按 ID 查找通知（只在保留的最近记录中查找）
+    pub fn find(id: &str) -> Option<NotificationRecord> {
+        find_in(&Self::path(), id)
+    }
+
     /// 定期检查并清理
     fn maybe_cleanup() {
         let count = WRITE_COUNT.fetch_add(1, Ordering::Relaxed);
@@ -174,6 +321,7 @@ mod tests {
     fn create_test_record(agent_id: &str, summary: &str) -> NotificationRecord {
         NotificationRecord {
             ts: Utc::now(),
+            id: None,
             agent_id: agent_id.to_string(),
             urgency: Urgency::High,
             event: "test".to_string(),
@@ -182,6 +330,8 @@ mod tests {
             event_detail: None,
             terminal_snapshot: None,
             risk_level: None,
+            message_id: None,
+            reply_to: None,
         }
     }
 
@@ -214,12 +364,14 @@ mod tests {
         assert!(record.event_detail.is_none());
         assert!(record.terminal_snapshot.is_none());
         assert!(record.risk_level.is_none());
+        assert!(record.message_id.is_none());
     }
 
     #[test]
     fn test_notification_record_with_new_fields() {
         let record = NotificationRecord {
             ts: Utc::now(),
+            id: Some("0a1b2c3d".to_string()),
             agent_id: "cam-123".to_string(),
             urgency: Urgency::High,
             event: "permission_request".to_string(),
@@ -230,6 +382,8 @@ mod tests {
             ),
             terminal_snapshot: Some("$ ls\nfile1 file2".to_string()),
             risk_level: Some("LOW".to_string()),
+            message_id: Some("42".to_string()),
+            reply_to: None,
         };
         let json = serde_json::to_string(&record).unwrap();
         let parsed: NotificationRecord = serde_json::from_str(&json).unwrap();
@@ -238,4 +392,71 @@ mod tests {
         assert!(parsed.event_detail.is_some());
         assert!(parsed.terminal_snapshot.is_some());
     }
+
+    #[test]
+    fn test_find_by_id() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("notifications.jsonl");
+        let ts = Utc::now();
+        let id = new_notification_id("cam-1", ts);
+        assert_eq!(id.len(), 8);
+        assert_ne!(id, new_notification_id("cam-2", ts));
+
+        let mut record = create_test_record("cam-1", "Waiting");
+        record.id = Some(id.clone());
+        record.terminal_snapshot = Some("$ cargo test".to_string());
+        let other = create_test_record("cam-2", "Error");
+        let lines = [&other, &record]
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(&path, lines).unwrap();
+
+        let found = find_in(&path, &id).unwrap();
+        assert_eq!(found.agent_id, "cam-1");
+        assert_eq!(found.terminal_snapshot.as_deref(), Some("$ cargo test"));
+        assert!(find_in(&path, "ffffffff").is_none());
+    }
+
+    #[test]
+    fn test_thread_tracking() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("threads.json");
+        assert!(thread_root_in(&path, "telegram", "cam-1").is_none());
+
+        record_thread_message_in(&path, "telegram", "cam-1", "100").unwrap();
+        record_thread_message_in(&path, "telegram", "cam-1", "105").unwrap();
+        record_thread_message_in(&path, "telegram", "cam-2", "101").unwrap();
+        record_thread_message_in(&path, "slack", "cam-1", "1700.01").unwrap();
+
+        assert_eq!(
+            thread_root_in(&path, "telegram", "cam-1").as_deref(),
+            Some("100")
+        );
+        assert_eq!(
+            thread_root_in(&path, "slack", "cam-1").as_deref(),
+            Some("1700.01")
+        );
+        assert_eq!(agent_for_message_in(&path, "105").as_deref(), Some("cam-1"));
+        assert_eq!(agent_for_message_in(&path, "101").as_deref(), Some("cam-2"));
+        assert!(agent_for_message_in(&path, "999").is_none());
+
+        assert!(channel_supports_threads("Telegram"));
+        assert!(!channel_supports_threads("whatsapp"));
+    }
+
+    #[test]
+    fn test_thread_keeps_root_when_trimmed() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("threads.json");
+        for i in 0..MAX_THREAD_MESSAGES + 5 {
+            record_thread_message_in(&path, "telegram", "cam-1", &i.to_string()).unwrap();
+        }
+        let thread = load_threads(&path).remove("telegram/cam-1").unwrap();
+        assert_eq!(thread.message_ids.len(), MAX_THREAD_MESSAGES);
+        assert_eq!(thread.message_ids[0], "0");
+        assert_eq!(agent_for_message_in(&path, "0").as_deref(), Some("cam-1"));
+        assert!(agent_for_message_in(&path, "1").is_none());
+    }
 }
@@ -5,13 +5,40 @@
 //! 风险评估规则：
 //! - Low: 读操作、/tmp 路径、安全命令 (ls, cat, echo)
 //! - Medium: 写入项目文件、npm/cargo 命令、git 操作
-//! - High: 系统文件、rm -rf、sudo、敏感路径
+//! - High: 系统文件、rm -rf、sudo、敏感路径、写到项目目录之外（`summarize_permission_in`）
+//!
+//! Bash 命令按结构分析（管道、连接符、命令替换、`rm` 目标等，见 [`crate::notification::shell_risk`]），
+//! 具体发现写入建议。
+//!
+//! Write/Edit 另附一行文件影响评估（[`FileImpact`]）：新建还是覆盖、文件大小、是否被 git 跟踪、
+//! 是否在项目目录之外。
+//!
+//! 错误分类（`classify_error`）按关键字识别限流、认证、网络、编译/测试失败和上下文超限，
+//! 无法识别时返回 Other，由调用方决定是否再用 AI 判断。
 
+use crate::infra::text::{display_width, format_bytes, take_width, take_width_end};
+use crate::notification::shell_risk::analyze_bash;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+/// HTTP 状态码（限流 429/529、认证 401），要求前面有 error/status 等字样，避免误匹配行号
+static RATE_LIMIT_STATUS_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:error|status|code|http)\D{0,3}(?:429|529)\b")
+        .expect("Invalid rate limit regex")
+});
+static AUTH_STATUS_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:error|status|code|http)\D{0,3}401\b").expect("Invalid auth regex")
+});
+/// 测试框架的失败计数（"2 failed"、"1 failing"）
+static TEST_FAILED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d+ (failed|failing)\b").expect("Invalid test regex"));
 
 /// 风险等级
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -19,6 +46,15 @@ pub enum RiskLevel {
 }
 
 impl RiskLevel {
+    /// 大写名称（`LOW`/`MEDIUM`/`HIGH`，通知 payload 中的格式）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "LOW",
+            RiskLevel::Medium => "MEDIUM",
+            RiskLevel::High => "HIGH",
+        }
+    }
+
     /// 获取风险等级对应的 emoji
     pub fn emoji(&self) -> &'static str {
         match self {
@@ -38,6 +74,85 @@ impl RiskLevel {
     }
 }
 
+/// 错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// API 限流或额度用尽
+    RateLimit,
+    /// API key / 登录失效
+    AuthFailure,
+    /// 网络不可达
+    Network,
+    /// 编译失败
+    CompileFailure,
+    /// 测试失败
+    TestFailure,
+    /// 上下文窗口超限
+    ContextWindow,
+    /// 无法识别
+    Other,
+}
+
+impl ErrorCategory {
+    /// 所有类别（Other 在最后）
+    pub const ALL: [ErrorCategory; 7] = [
+        ErrorCategory::RateLimit,
+        ErrorCategory::AuthFailure,
+        ErrorCategory::Network,
+        ErrorCategory::CompileFailure,
+        ErrorCategory::TestFailure,
+        ErrorCategory::ContextWindow,
+        ErrorCategory::Other,
+    ];
+
+    /// 序列化名称（如 `rate_limit`）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::RateLimit => "rate_limit",
+            ErrorCategory::AuthFailure => "auth_failure",
+            ErrorCategory::Network => "network",
+            ErrorCategory::CompileFailure => "compile_failure",
+            ErrorCategory::TestFailure => "test_failure",
+            ErrorCategory::ContextWindow => "context_window",
+            ErrorCategory::Other => "other",
+        }
+    }
+
+    /// 从序列化名称解析
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+
+    /// 中文名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorCategory::RateLimit => "触发限流",
+            ErrorCategory::AuthFailure => "认证失败",
+            ErrorCategory::Network => "网络错误",
+            ErrorCategory::CompileFailure => "编译失败",
+            ErrorCategory::TestFailure => "测试失败",
+            ErrorCategory::ContextWindow => "上下文超限",
+            ErrorCategory::Other => "未知错误",
+        }
+    }
+
+    /// 处理建议
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            ErrorCategory::RateLimit => "API 限流或额度用尽，稍等几分钟后让 agent 继续",
+            ErrorCategory::AuthFailure => {
+                "API key 或登录已失效，重新登录（如 /login）或更新 key 后继续"
+            }
+            ErrorCategory::Network => "检查网络或代理设置，恢复后让 agent 重试",
+            ErrorCategory::CompileFailure => "agent 通常会自行修复，持续失败时查看编译输出",
+            ErrorCategory::TestFailure => "agent 通常会自行修复，持续失败时查看失败的测试",
+            ErrorCategory::ContextWindow => "上下文窗口已满，执行 /compact 或开启新会话",
+            ErrorCategory::Other => "查看详细日志",
+        }
+    }
+}
+
 /// 权限请求摘要
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionSummary {
@@ -47,11 +162,132 @@ pub struct PermissionSummary {
     pub operation_desc: String,
     /// 建议
     pub recommendation: String,
+    /// 目标文件的影响评估（仅 Write/Edit，见 [`FileImpact`]）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impact: Option<String>,
+}
+
+/// Write/Edit 目标文件的影响评估
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileImpact {
+    /// 文件已存在（Write 会覆盖，Edit 会修改）
+    pub exists: bool,
+    /// 已存在文件的大小（字节）
+    pub size: Option<u64>,
+    /// 是否被 git 跟踪，文件不存在或不在 git 仓库中时为 None
+    pub git_tracked: Option<bool>,
+    /// 是否在项目目录之外（临时目录不算），不知道项目目录时为 None
+    pub outside_project: Option<bool>,
+}
+
+impl FileImpact {
+    /// 检查目标文件，相对路径按项目目录解析
+    pub fn inspect(path: &str, project_root: Option<&str>) -> Self {
+        let root = project_root
+            .filter(|root| !root.is_empty())
+            .map(|root| normalize_path(&expand_home(root)));
+        let path = expand_home(path);
+        let target = match &root {
+            Some(root) if path.is_relative() => normalize_path(&root.join(&path)),
+            _ => normalize_path(&path),
+        };
+        let metadata = fs::metadata(&target).ok().filter(|m| m.is_file());
+        Self {
+            exists: metadata.is_some(),
+            size: metadata.as_ref().map(|m| m.len()),
+            git_tracked: metadata.and_then(|_| git_tracked(&target)),
+            outside_project: root.map(|root| !target.starts_with(&root) && !is_temp_path(&target)),
+        }
+    }
+
+    /// 一行说明，如 `覆盖已有文件（3.2 KB，git 未跟踪）· 项目目录外`
+    pub fn describe(&self, tool: &str) -> String {
+        let mut line = match (self.exists, tool) {
+            (false, _) => "新建文件".to_string(),
+            (true, "Edit") => "修改已有文件".to_string(),
+            (true, _) => "覆盖已有文件".to_string(),
+        };
+        let details: Vec<String> = self
+            .size
+            .map(format_bytes)
+            .into_iter()
+            .chain(self.git_tracked.map(|tracked| {
+                if tracked {
+                    "git 已跟踪"
+                } else {
+                    "git 未跟踪"
+                }
+                .to_string()
+            }))
+            .collect();
+        if !details.is_empty() {
+            line.push_str(&format!("（{}）", details.join("，")));
+        }
+        if self.outside_project == Some(true) {
+            line.push_str(" · 项目目录外");
+        }
+        line
+    }
+}
+
+/// 展开开头的 `~/`
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// 按字面处理 `.` 和 `..`（不访问文件系统，目标文件可能还不存在）
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+pub(crate) fn is_temp_path(path: &Path) -> bool {
+    [
+        std::env::temp_dir(),
+        PathBuf::from("/tmp"),
+        PathBuf::from("/var/tmp"),
+        PathBuf::from("/private/tmp"),
+    ]
+    .iter()
+    .any(|dir| path.starts_with(dir))
+}
+
+/// `git ls-files --error-unmatch`：退出码 0 已跟踪、1 未跟踪，其他（不是仓库、没有 git）为 None
+fn git_tracked(path: &Path) -> Option<bool> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(path.parent()?)
+        .args(["ls-files", "--error-unmatch", "--"])
+        .arg(path.file_name()?)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()?;
+    match status.code() {
+        Some(0) => Some(true),
+        Some(1) => Some(false),
+        _ => None,
+    }
 }
 
 /// 错误摘要
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorSummary {
+    /// 错误类别
+    pub category: ErrorCategory,
     /// 错误类型
     pub error_type: String,
     /// 错误描述
@@ -72,83 +308,83 @@ pub struct CompletionSummary {
 /// 通知汇总器
 pub struct NotificationSummarizer;
 
-/// Sensitive paths that require human confirmation even for whitelisted commands
-const SENSITIVE_PATH_PATTERNS: &[&str] = &[
-    "/etc/",
-    "~/.ssh/",
-    "~/.aws/",
-    "~/.config/",
-    ".env",
-    "credentials",
-    "secret",
-    "token",
-    "password",
-    "id_rsa",
-    "id_ed25519",
-];
-
-/// Command chain/redirection patterns that require human confirmation
-const COMMAND_CHAIN_PATTERNS: &[&str] = &[
-    "&&", // command chain
-    "||", // conditional chain
-    ";",  // sequential execution
-    "|",  // pipe (can pipe to sh)
-    ">",  // output redirection
-    ">>", // append redirection
-    "<",  // input redirection
-    "$(", // command substitution
-    "`",  // backtick substitution
-    "$",  // environment variable (can't predict expanded value)
-];
-
 impl NotificationSummarizer {
     /// 创建新的通知汇总器
     pub fn new() -> Self {
         Self
     }
 
-    /// Check if command arguments contain sensitive paths
-    fn contains_sensitive_path(&self, command: &str) -> bool {
-        let command_lower = command.to_lowercase();
-        SENSITIVE_PATH_PATTERNS
-            .iter()
-            .any(|pattern| command_lower.contains(pattern))
-    }
-
-    /// Check if command contains chain/redirection operators
-    fn contains_command_chain(&self, command: &str) -> bool {
-        COMMAND_CHAIN_PATTERNS
-            .iter()
-            .any(|pattern| command.contains(pattern))
-    }
-
     /// 汇总权限请求
     pub fn summarize_permission(&self, tool: &str, input: &serde_json::Value) -> PermissionSummary {
-        match tool {
-            "Bash" => self.summarize_bash_permission(input),
+        self.summarize_permission_in(tool, input, None)
+    }
+
+    /// 汇总权限请求，`project_root` 用于解析 Bash 命令和 Write/Edit 的相对路径；
+    /// Write/Edit 附带目标文件的影响评估，写到项目目录外时提升为高风险
+    pub fn summarize_permission_in(
+        &self,
+        tool: &str,
+        input: &serde_json::Value,
+        project_root: Option<&str>,
+    ) -> PermissionSummary {
+        let mut summary = match tool {
+            "Bash" => self.summarize_bash_permission(input, project_root),
             "Write" | "Edit" => self.summarize_file_write_permission(tool, input),
             "Read" => self.summarize_file_read_permission(input),
             "WebFetch" | "WebSearch" => self.summarize_network_permission(tool, input),
             _ => self.summarize_generic_permission(tool, input),
+        };
+        if let Some(impact) = self.file_impact(tool, input, project_root) {
+            if impact.outside_project == Some(true) {
+                summary.risk_level = RiskLevel::High;
+                summary.recommendation = "写入项目目录之外，请仔细检查".to_string();
+            }
+            summary.impact = Some(impact.describe(tool));
         }
+        summary
+    }
+
+    /// Write/Edit 目标文件的影响评估，其他工具或没有 file_path 时为 None
+    pub fn file_impact(
+        &self,
+        tool: &str,
+        input: &serde_json::Value,
+        project_root: Option<&str>,
+    ) -> Option<FileImpact> {
+        if !matches!(tool, "Write" | "Edit") {
+            return None;
+        }
+        let path = input
+            .get("file_path")
+            .and_then(|v| v.as_str())
+            .filter(|path| !path.is_empty())?;
+        Some(FileImpact::inspect(path, project_root))
     }
 
     /// 汇总 Bash 命令权限请求
-    fn summarize_bash_permission(&self, input: &serde_json::Value) -> PermissionSummary {
+    fn summarize_bash_permission(
+        &self,
+        input: &serde_json::Value,
+        cwd: Option<&str>,
+    ) -> PermissionSummary {
         let command = input.get("command").and_then(|v| v.as_str()).unwrap_or("");
 
-        let risk_level = self.assess_bash_risk(command);
+        let analysis = analyze_bash(command, cwd);
+        let risk_level = analysis.risk;
         let operation_desc = self.describe_bash_command(command);
-        let recommendation = match risk_level {
-            RiskLevel::Low => "安全操作，可以允许".to_string(),
-            RiskLevel::Medium => "请确认操作目标正确".to_string(),
-            RiskLevel::High => "高风险操作，请仔细检查".to_string(),
+        let recommendation = match (risk_level, analysis.summary()) {
+            (RiskLevel::Low, _) => "安全操作，可以允许".to_string(),
+            (RiskLevel::Medium, None) => "请确认操作目标正确".to_string(),
+            (RiskLevel::High, None) => "高风险操作，请仔细检查".to_string(),
+            (RiskLevel::Medium, Some(summary)) => format!("请确认操作目标正确：{}", summary),
+            (RiskLevel::High, Some(summary)) => format!("高风险操作，请仔细检查：{}", summary),
         };
 
         PermissionSummary {
             risk_level,
             operation_desc,
             recommendation,
+            impact: None,
         }
     }
 
@@ -176,6 +412,7 @@ impl NotificationSummarizer {
             risk_level,
             operation_desc,
             recommendation,
+            impact: None,
         }
     }
 
@@ -194,6 +431,7 @@ impl NotificationSummarizer {
             risk_level,
             operation_desc,
             recommendation,
+            impact: None,
         }
     }
 
@@ -225,6 +463,7 @@ impl NotificationSummarizer {
             risk_level,
             operation_desc,
             recommendation,
+            impact: None,
         }
     }
 
@@ -246,11 +485,101 @@ impl NotificationSummarizer {
             risk_level,
             operation_desc,
             recommendation: "请确认操作内容".to_string(),
+            impact: None,
+        }
+    }
+
+    /// 按关键字分类错误（无法识别时返回 Other）
+    pub fn classify_error(&self, error: &str) -> ErrorCategory {
+        let lower = error.to_lowercase();
+        let has = |patterns: &[&str]| patterns.iter().any(|p| lower.contains(p));
+
+        if has(&[
+            "context window",
+            "context length",
+            "context_length_exceeded",
+            "prompt is too long",
+            "maximum context",
+            "exceeds the maximum number of tokens",
+        ]) {
+            ErrorCategory::ContextWindow
+        } else if has(&[
+            "rate limit",
+            "rate_limit",
+            "ratelimit",
+            "too many requests",
+            "overloaded",
+            "usage limit",
+            "quota exceeded",
+            "insufficient_quota",
+        ]) || RATE_LIMIT_STATUS_RE.is_match(&lower)
+        {
+            ErrorCategory::RateLimit
+        } else if has(&[
+            "unauthorized",
+            "authentication_error",
+            "authentication failed",
+            "invalid api key",
+            "invalid x-api-key",
+            "oauth token has expired",
+            "token has expired",
+            "please run /login",
+            "not logged in",
+        ]) || AUTH_STATUS_RE.is_match(&lower)
+        {
+            ErrorCategory::AuthFailure
+        } else if has(&[
+            "econnrefused",
+            "econnreset",
+            "enotfound",
+            "etimedout",
+            "connection refused",
+            "connection reset",
+            "could not resolve host",
+            "getaddrinfo",
+            "network error",
+            "network is unreachable",
+            "socket hang up",
+            "fetch failed",
+        ]) {
+            ErrorCategory::Network
+        } else if has(&[
+            "test result: failed",
+            "tests failed",
+            "test failed",
+            "assertionerror",
+            "assertion failed",
+        ]) || TEST_FAILED_RE.is_match(&lower)
+        {
+            ErrorCategory::TestFailure
+        } else if has(&[
+            "error[e",
+            "could not compile",
+            "compilation failed",
+            "compile error",
+            "build failed",
+            "cannot find symbol",
+            "undefined reference",
+        ]) || lower.contains("error ts")
+        {
+            ErrorCategory::CompileFailure
+        } else {
+            ErrorCategory::Other
         }
     }
 
     /// 汇总错误
     pub fn summarize_error(&self, error: &str, _context: &str) -> ErrorSummary {
+        let category = self.classify_error(error);
+        if category != ErrorCategory::Other {
+            return ErrorSummary {
+                category,
+                error_type: category.label().to_string(),
+                description: truncate_text(error, 100),
+                suggestion: category.suggestion().to_string(),
+            };
+        }
+
         let error_lower = error.to_lowercase();
 
         let (error_type, suggestion) =
@@ -271,6 +600,7 @@ impl NotificationSummarizer {
             };
 
         ErrorSummary {
+            category,
             error_type: error_type.to_string(),
             description: truncate_text(error, 100),
             suggestion: suggestion.to_string(),
@@ -289,87 +619,9 @@ impl NotificationSummarizer {
         }
     }
 
-    /// 评估 Bash 命令风险
+    /// 评估 Bash 命令风险（按命令结构分析，见 [`analyze_bash`]）
     pub fn assess_bash_risk(&self, command: &str) -> RiskLevel {
-        let command_lower = command.to_lowercase();
-
-        // Command chain detection - always HIGH risk (can hide dangerous commands)
-        if self.contains_command_chain(command) {
-            return RiskLevel::High;
-        }
-
-        // 高风险命令模式
-        let high_risk_patterns = [
-            r"rm\s+-rf",
-            r"rm\s+-r\s+/",
-            r"sudo\s+",
-            r"chmod\s+777",
-            r"chown\s+",
-            r"mkfs",
-            r"dd\s+if=",
-            r">\s*/dev/",
-            r"curl.*\|\s*sh",
-            r"wget.*\|\s*sh",
-            r"eval\s+",
-            r":\(\)\s*\{", // fork bomb
-            r"/etc/passwd",
-            r"/etc/shadow",
-            r"\.ssh/",
-        ];
-
-        for pattern in &high_risk_patterns {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(&command_lower) {
-                    return RiskLevel::High;
-                }
-            }
-        }
-
-        // 中风险命令模式
-        let medium_risk_patterns = [
-            r"npm\s+install",
-            r"npm\s+run",
-            r"yarn\s+",
-            r"cargo\s+build",
-            r"cargo\s+run",
-            r"make\s+",
-            r"git\s+push",
-            r"git\s+reset",
-            r"git\s+checkout",
-            r"pip\s+install",
-            r"brew\s+install",
-            r"apt\s+install",
-            r"rm\s+",
-            r"mv\s+",
-            r"cp\s+-r",
-        ];
-
-        for pattern in &medium_risk_patterns {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(&command_lower) {
-                    return RiskLevel::Medium;
-                }
-            }
-        }
-
-        // 低风险命令
-        let low_risk_commands = [
-            "ls", "cat", "echo", "pwd", "cd", "head", "tail", "grep", "find", "which", "whoami",
-            "date", "env", "printenv", "wc", "sort", "uniq", "diff", "tree", "file", "stat",
-        ];
-
-        let first_word = command_lower.split_whitespace().next().unwrap_or("");
-        if low_risk_commands.contains(&first_word) {
-            // Parameter safety check: even whitelisted commands need confirmation
-            // if arguments contain sensitive paths
-            if self.contains_sensitive_path(command) {
-                return RiskLevel::High;
-            }
-            return RiskLevel::Low;
-        }
-
-        // 默认中风险
-        RiskLevel::Medium
+        analyze_bash(command, None).risk
     }
 
     /// 评估文件操作风险
@@ -535,30 +787,30 @@ impl Default for NotificationSummarizer {
     }
 }
 
-/// 截断文本
+/// 截断文本（按显示宽度，不拆分 CJK 字符和 emoji）
 fn truncate_text(text: &str, max_len: usize) -> String {
-    if text.len() <= max_len {
+    if display_width(text) <= max_len {
         text.to_string()
     } else {
-        format!("{}...", &text[..max_len])
+        format!("{}...", take_width(text, max_len))
     }
 }
 
 /// 截断路径（保留文件名）
 fn truncate_path(path: &str, max_len: usize) -> String {
-    if path.len() <= max_len {
+    if display_width(path) <= max_len {
         return path.to_string();
     }
 
     // 尝试保留文件名
     if let Some(pos) = path.rfind('/') {
         let filename = &path[pos + 1..];
-        if filename.len() < max_len - 4 {
-            return format!("...{}", &path[path.len() - max_len + 3..]);
+        if display_width(filename) < max_len.saturating_sub(4) {
+            return format!("...{}", take_width_end(path, max_len - 3));
         }
     }
 
-    format!("{}...", &path[..max_len - 3])
+    format!("{}...", take_width(path, max_len.saturating_sub(3)))
 }
 
 #[cfg(test)]
@@ -723,6 +975,86 @@ mod tests {
         assert!(summary.operation_desc.contains("创建文件"));
     }
 
+    #[test]
+    fn test_file_impact() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let repo = tmp.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        fs::write(repo.join("tracked.rs"), "x".repeat(3300)).unwrap();
+        fs::write(repo.join("notes.md"), "draft").unwrap();
+        git(&["add", "tracked.rs"]);
+        let root = repo.to_str();
+
+        let tracked = FileImpact::inspect("tracked.rs", root);
+        assert_eq!(tracked.git_tracked, Some(true));
+        assert_eq!(tracked.outside_project, Some(false));
+        assert_eq!(
+            tracked.describe("Edit"),
+            "修改已有文件（3.2 KB，git 已跟踪）"
+        );
+        assert_eq!(
+            FileImpact::inspect(&repo.join("notes.md").to_string_lossy(), root).describe("Write"),
+            "覆盖已有文件（5 B，git 未跟踪）"
+        );
+        assert_eq!(
+            FileImpact::inspect("src/new.rs", root).describe("Write"),
+            "新建文件"
+        );
+
+        // 按字面解析 `..`；临时目录不算项目外
+        let project = Some("/workspace/project");
+        assert_eq!(
+            FileImpact::inspect("src/../lib.rs", project).outside_project,
+            Some(false)
+        );
+        assert_eq!(
+            FileImpact::inspect("../other/lib.rs", project).describe("Write"),
+            "新建文件 · 项目目录外"
+        );
+        assert_eq!(
+            FileImpact::inspect("/tmp/scratch.txt", project).outside_project,
+            Some(false)
+        );
+        assert_eq!(FileImpact::inspect("lib.rs", None).outside_project, None);
+    }
+
+    #[test]
+    fn test_summarize_permission_outside_project() {
+        let summarizer = NotificationSummarizer::new();
+        let project = Some("/workspace/project");
+
+        let inside = summarizer.summarize_permission_in(
+            "Write",
+            &serde_json::json!({"file_path": "/workspace/project/src/app.rs"}),
+            project,
+        );
+        assert_eq!(inside.risk_level, RiskLevel::Medium);
+        assert_eq!(inside.impact.as_deref(), Some("新建文件"));
+
+        let outside = summarizer.summarize_permission_in(
+            "Edit",
+            &serde_json::json!({"file_path": "/workspace/shared/app.rs"}),
+            project,
+        );
+        assert_eq!(outside.risk_level, RiskLevel::High);
+        assert_eq!(outside.impact.as_deref(), Some("新建文件 · 项目目录外"));
+
+        let bash = summarizer.summarize_permission_in(
+            "Bash",
+            &serde_json::json!({"command": "ls"}),
+            project,
+        );
+        assert_eq!(bash.impact, None);
+    }
+
     #[test]
     fn test_summarize_error() {
         let summarizer = NotificationSummarizer::new();
@@ -735,6 +1067,55 @@ mod tests {
 
         let summary = summarizer.summarize_error("Connection timeout", "");
         assert_eq!(summary.error_type, "超时错误");
+
+        let summary = summarizer.summarize_error("API Error: 429 Too Many Requests", "");
+        assert_eq!(summary.category, ErrorCategory::RateLimit);
+        assert_eq!(summary.error_type, "触发限流");
+    }
+
+    #[test]
+    fn test_classify_error() {
+        let summarizer = NotificationSummarizer::new();
+        let cases = [
+            (
+                "API Error: 529 {\"type\":\"overloaded_error\"}",
+                ErrorCategory::RateLimit,
+            ),
+            ("Claude usage limit reached", ErrorCategory::RateLimit),
+            (
+                "API Error: 401 authentication_error",
+                ErrorCategory::AuthFailure,
+            ),
+            (
+                "OAuth token has expired. Please run /login",
+                ErrorCategory::AuthFailure,
+            ),
+            ("connect ECONNREFUSED 127.0.0.1:443", ErrorCategory::Network),
+            (
+                "error[E0308]: mismatched types",
+                ErrorCategory::CompileFailure,
+            ),
+            (
+                "src/app.ts(3,5): error TS2322",
+                ErrorCategory::CompileFailure,
+            ),
+            (
+                "test result: FAILED. 10 passed; 2 failed",
+                ErrorCategory::TestFailure,
+            ),
+            ("Tests: 1 failed, 12 passed", ErrorCategory::TestFailure),
+            ("Prompt is too long", ErrorCategory::ContextWindow),
+            ("Failed at line 429 of parser", ErrorCategory::Other),
+            ("something odd happened", ErrorCategory::Other),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(summarizer.classify_error(error), expected, "{}", error);
+        }
+        assert_eq!(
+            ErrorCategory::parse("context_window"),
+            Some(ErrorCategory::ContextWindow)
+        );
+        assert_eq!(ErrorCategory::parse("bogus"), None);
     }
 
     #[test]
@@ -752,6 +1133,11 @@ mod tests {
     fn test_truncate_text() {
         assert_eq!(truncate_text("short", 10), "short");
         assert_eq!(truncate_text("this is a long text", 10), "this is a ...");
+        // 多字节字符不再按字节切片
+        assert_eq!(
+            truncate_text("部署到生产环境并重启服务", 10),
+            "部署到生产..."
+        );
     }
 
     #[test]
@@ -795,7 +1181,7 @@ mod tests {
     fn test_assess_bash_risk_command_chains() {
         let summarizer = NotificationSummarizer::new();
 
-        // Command chains should be HIGH risk (can hide dangerous commands)
+        // 连接符后面的危险命令同样识别
         assert_eq!(
             summarizer.assess_bash_risk("ls && rm -rf /"),
             RiskLevel::High
@@ -816,12 +1202,17 @@ mod tests {
             summarizer.assess_bash_risk("$(cat /etc/passwd)"),
             RiskLevel::High
         );
+        // 连接符和命令替换本身不再直接判为高风险，按其中的命令判断
         assert_eq!(
             summarizer.assess_bash_risk("echo `whoami`"),
-            RiskLevel::High
+            RiskLevel::Medium
+        );
+        assert_eq!(
+            summarizer.assess_bash_risk("cd src && ls"),
+            RiskLevel::Low
         );
 
-        // Environment variable expansion should be HIGH risk
+        // $HOME 展开后同样识别敏感路径
         assert_eq!(
             summarizer.assess_bash_risk("cat $HOME/.ssh/id_rsa"),
             RiskLevel::High
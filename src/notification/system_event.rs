@@ -6,9 +6,14 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::agent::todos::TodoProgress;
+use crate::notification::answer_memory::RememberedAnswer;
 use crate::notification::event::{NotificationEvent, NotificationEventType};
-use crate::notification::summarizer::NotificationSummarizer;
+use crate::notification::formatter::{ChannelProfile, MessageFormatter};
+use crate::notification::shell_risk::{analyze_bash, RiskAnalysis};
+use crate::notification::summarizer::{ErrorCategory, NotificationSummarizer, RiskLevel};
 use crate::notification::urgency::Urgency;
+use crate::notification::waiting_kind::WaitingKind;
 
 /// System Event Payload - 发送给 OpenClaw 的结构化数据
 ///
@@ -62,6 +67,16 @@ pub enum EventData {
     },
     Error {
         message: String,
+        /// 错误类别（关键字或 AI 分类）
+        #[serde(
+            rename = "errorCategory",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        category: Option<ErrorCategory>,
+        /// 针对类别的处理建议
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suggestion: Option<String>,
     },
     Empty {},
 }
@@ -83,6 +98,68 @@ pub struct EventContext {
     pub question_fingerprint: Option<String>,
     /// 风险等级
     pub risk_level: String,
+    /// 因限流积压的其他通知（每行一条）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub digest: Vec<String>,
+    /// 可回复 `s` 在沙箱中执行
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sandbox_option: bool,
+    /// 同一项目中相同问题上次的回答（回答记忆开启时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_answer: Option<RememberedAnswer>,
+    /// 多少秒后自动发送上次的回答（`answer_memory.auto_answer`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_answer_secs: Option<u64>,
+    /// 等待输入的分类（仅 waiting_for_input）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_kind: Option<WaitingKind>,
+    /// Write/Edit 目标文件的影响评估（新建/覆盖、大小、git 跟踪、是否在项目外）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_impact: Option<String>,
+    /// 风险分析的具体发现（风险从高到低，如 "sudo 以管理员权限执行"）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risk_findings: Vec<String>,
+    /// transcript 中最近的助手消息（旧的在前，过长的已摘要）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_messages: Vec<String>,
+    /// Claude Code TODO 完成进度（完成类通知）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todos: Option<TodoProgress>,
+    /// 同类错误反复出现时提交的 issue 链接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_url: Option<String>,
+    /// 快照隐私模式下留在本机的快照对应的通知 ID（`cam show <id>`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
+}
+
+/// 回复选项提示（不含"回复"前缀），无需回复时为空
+pub fn reply_options(payload: &SystemEventPayload) -> &'static str {
+    match payload.event_type.as_str() {
+        "permission_request" if payload.context.sandbox_option => {
+            "y 允许 / n 拒绝 / s 沙箱执行"
+        }
+        "permission_request" => "y 允许 / n 拒绝",
+        "waiting_for_input" => "回复你的选择或输入内容",
+        _ => "",
+    }
+}
+
+/// 工具调用的风险分析：Bash 按命令结构分析（相对路径按 `cwd` 解析），其他工具同 [`assess_risk_level`]
+pub fn analyze_tool_risk(tool_name: &str, tool_input: &Value, cwd: Option<&str>) -> RiskAnalysis {
+    if tool_name == "Bash" {
+        let command = tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        return analyze_bash(command, cwd);
+    }
+    let risk = match assess_risk_level(tool_name, &tool_input.to_string()) {
+        "HIGH" => RiskLevel::High,
+        "MEDIUM" => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    };
+    RiskAnalysis::plain(risk)
 }
 
 /// 评估风险等级（返回字符串形式）
@@ -108,14 +185,15 @@ pub fn assess_risk_level(tool_name: &str, tool_input: &str) -> &'static str {
             let operation = if tool_name == "Read" { "read" } else { "write" };
             summarizer.assess_file_risk(path, operation)
         }
-        _ => crate::notification::summarizer::RiskLevel::Low,
+        _ => RiskLevel::Low,
     };
 
-    match risk {
-        crate::notification::summarizer::RiskLevel::Low => "LOW",
-        crate::notification::summarizer::RiskLevel::Medium => "MEDIUM",
-        crate::notification::summarizer::RiskLevel::High => "HIGH",
-    }
+    risk.as_str()
+}
+
+/// 已识别类别的处理建议（Other 不附带建议）
+fn error_suggestion(category: ErrorCategory) -> Option<String> {
+    (category != ErrorCategory::Other).then(|| category.suggestion().to_string())
 }
 
 impl SystemEventPayload {
@@ -154,20 +232,47 @@ impl SystemEventPayload {
                 notification_type: notification_type.clone(),
                 message: message.clone(),
             },
-            NotificationEventType::Error { message } => EventData::Error {
-                message: message.clone(),
-            },
+            NotificationEventType::Error { message } => {
+                let category = NotificationSummarizer::new().classify_error(message);
+                EventData::Error {
+                    message: message.clone(),
+                    category: Some(category),
+                    suggestion: error_suggestion(category),
+                }
+            }
             _ => EventData::Empty {},
         };
 
+        // Write/Edit 的文件影响评估
+        let file_impact = match &event.event_type {
+            NotificationEventType::PermissionRequest {
+                tool_name,
+                tool_input,
+            } => NotificationSummarizer::new()
+                .file_impact(tool_name, tool_input, event.project_path.as_deref())
+                .map(|impact| (tool_name, impact)),
+            _ => None,
+        };
+
         // 计算风险等级
+        let mut risk_findings = Vec::new();
         let risk_level = match &event.event_type {
+            // 写到项目目录之外一律为高风险
+            NotificationEventType::PermissionRequest { .. }
+                if file_impact
+                    .as_ref()
+                    .is_some_and(|(_, impact)| impact.outside_project == Some(true)) =>
+            {
+                "HIGH".to_string()
+            }
             NotificationEventType::PermissionRequest {
                 tool_name,
                 tool_input,
             } => {
-                let input_str = tool_input.to_string();
-                assess_risk_level(tool_name, &input_str).to_string()
+                let analysis =
+                    analyze_tool_risk(tool_name, tool_input, event.project_path.as_deref());
+                risk_findings = analysis.details();
+                analysis.risk.as_str().to_string()
             }
             // WaitingForInput 需要用户交互
             // 如果是需要关键决策，设为 HIGH
@@ -198,6 +303,17 @@ impl SystemEventPayload {
                 extracted_message: None,
                 question_fingerprint: None,
                 risk_level,
+                digest: Vec::new(),
+                sandbox_option: event.sandbox_option,
+                previous_answer: None,
+                auto_answer_secs: None,
+                waiting_kind: None,
+                file_impact: file_impact.map(|(tool_name, impact)| impact.describe(tool_name)),
+                risk_findings,
+                recent_messages: Vec::new(),
+                todos: None,
+                issue_url: None,
+                snapshot_id: None,
             },
         }
     }
@@ -223,6 +339,12 @@ impl SystemEventPayload {
         }
     }
 
+    /// 设置等待输入分类，并按分类更新 urgency
+    pub fn set_waiting_kind(&mut self, kind: WaitingKind) {
+        self.context.waiting_kind = Some(kind);
+        self.urgency = kind.urgency().as_str().to_string();
+    }
+
     /// 设置 AI 提取的消息和指纹
     ///
     /// 在发送通知前调用，避免在 from_event 中重复调用 AI
@@ -231,102 +353,59 @@ impl SystemEventPayload {
         self.context.question_fingerprint = Some(fingerprint);
     }
 
-    /// 转换为 Telegram 消息格式
-    pub fn to_telegram_message(&self) -> String {
-        let emoji = match self.urgency.as_str() {
-            "HIGH" => "⚠️",
-            "MEDIUM" => "💬",
-            _ => "ℹ️",
-        };
+    /// 设置错误类别（AI 兜底分类后覆盖关键字结果）
+    pub fn set_error_category(&mut self, value: ErrorCategory) {
+        if let EventData::Error {
+            ref mut category,
+            ref mut suggestion,
+            ..
+        } = self.event_data
+        {
+            *category = Some(value);
+            *suggestion = error_suggestion(value);
+        }
+    }
 
-        let event_desc = match self.event_type.as_str() {
-            "permission_request" => {
-                // 优先使用 AI 提取的消息
-                if let Some(extracted) = &self.context.extracted_message {
-                    extracted.clone()
-                } else if let EventData::PermissionRequest {
-                    tool_name,
-                    tool_input,
-                } = &self.event_data
-                {
-                    let cmd = tool_input
-                        .get("command")
-                        .or_else(|| tool_input.get("file_path"))
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-
-                    // Fallback: 截取终端最后 30 行
-                    let snapshot_tail = self.context.terminal_snapshot.as_ref().map(|snapshot| {
-                        let lines: Vec<&str> = snapshot.lines().collect();
-                        let start = lines.len().saturating_sub(30);
-                        lines[start..].join("\n")
-                    });
-
-                    if let Some(tail) = snapshot_tail {
-                        format!("执行: {} {}\n\n{}", tool_name, cmd, tail)
-                    } else {
-                        format!("执行: {} {}", tool_name, cmd)
-                    }
-                } else {
-                    "请求权限".to_string()
-                }
-            }
-            "waiting_for_input" => {
-                // 优先使用 AI 提取的消息
-                if let Some(extracted) = &self.context.extracted_message {
-                    extracted.clone()
-                } else if let Some(snapshot) = &self.context.terminal_snapshot {
-                    // Fallback: 截取终端最后 30 行
-                    let lines: Vec<&str> = snapshot.lines().collect();
-                    let start = lines.len().saturating_sub(30);
-                    let preview = lines[start..].join("\n");
-                    format!("等待输入\n\n{}", preview)
-                } else {
-                    "等待输入".to_string()
-                }
-            }
-            "notification" => {
-                // Show the actual notification message
-                if let EventData::Notification {
-                    message,
-                    notification_type,
-                } = &self.event_data
-                {
-                    format!("{}: {}", notification_type, message)
-                } else {
-                    "通知".to_string()
-                }
-            }
-            "error" => {
-                if let EventData::Error { message } = &self.event_data {
-                    format!("错误: {}", message)
-                } else {
-                    "发生错误".to_string()
-                }
-            }
-            "agent_exited" => "Agent 已退出".to_string(),
-            _ => self.event_type.clone(),
-        };
+    /// 附带相同问题上次的回答
+    pub fn set_previous_answer(&mut self, answer: Option<RememberedAnswer>) {
+        self.context.previous_answer = answer;
+    }
 
-        let risk = self.context.risk_level.as_str();
+    /// 附带自动回答的延迟
+    pub fn set_auto_answer(&mut self, delay_secs: Option<u64>) {
+        self.context.auto_answer_secs = delay_secs;
+    }
 
-        let risk_emoji = match risk {
-            "HIGH" => "🔴",
-            "MEDIUM" => "🟡",
-            "LOW" => "🟢",
-            _ => "⚪",
-        };
+    /// 附带 transcript 中最近的助手消息
+    pub fn set_recent_messages(&mut self, messages: Vec<String>) {
+        self.context.recent_messages = messages;
+    }
 
-        let action_hint = match self.event_type.as_str() {
-            "permission_request" => "回复 y 允许 / n 拒绝",
-            "waiting_for_input" => "回复你的选择或输入内容",
-            _ => "无需回复",
-        };
+    /// 附带 TODO 完成进度
+    pub fn set_todos(&mut self, progress: Option<TodoProgress>) {
+        self.context.todos = progress;
+    }
 
-        format!(
-            "{} *CAM* {}\n\n{}\n\n风险: {} {}\n\n{}",
-            emoji, self.agent_id, event_desc, risk_emoji, risk, action_hint
-        )
+    /// 附带重复错误提交的 issue 链接
+    pub fn set_issue_url(&mut self, url: Option<String>) {
+        self.context.issue_url = url;
+    }
+
+    /// 快照隐私模式：移除终端快照，只保留本机查看用的通知 ID
+    pub fn keep_snapshot_local(&mut self, notification_id: &str) {
+        if self.context.terminal_snapshot.take().is_some() {
+            self.context.snapshot_id = Some(notification_id.to_string());
+        }
+    }
+
+    /// 附带限流积压的摘要
+    pub fn set_digest(&mut self, lines: Vec<String>) {
+        self.context.digest = lines;
+    }
+
+    /// 转换为通知文本（默认 Markdown 格式，按渠道格式化见 `MessageFormatter`）
+    pub fn to_message(&self) -> String {
+        MessageFormatter::new(ChannelProfile::default()).format_system_event(self)
     }
 }
 
@@ -377,6 +456,23 @@ mod tests {
         assert_eq!(payload.context.risk_level, "LOW");
     }
 
+    #[test]
+    fn test_error_category_in_payload() {
+        let event = NotificationEvent::error("cam-1", "API Error: 429 rate_limit_error");
+        let mut payload = SystemEventPayload::from_event(&event, Urgency::Medium);
+        let json = payload.to_json();
+        assert_eq!(json["eventData"]["errorCategory"], "rate_limit");
+        assert!(json["eventData"]["suggestion"]
+            .as_str()
+            .unwrap()
+            .contains("稍等"));
+
+        payload.set_error_category(ErrorCategory::Other);
+        let json = payload.to_json();
+        assert_eq!(json["eventData"]["errorCategory"], "other");
+        assert!(json["eventData"].get("suggestion").is_none());
+    }
+
     #[test]
     fn test_system_event_payload_to_json() {
         let event = NotificationEvent::error("cam-456", "Test error");
@@ -521,7 +617,7 @@ mod tests {
         );
 
         let payload = SystemEventPayload::from_event(&event, Urgency::High);
-        let msg = payload.to_telegram_message();
+        let msg = payload.to_message();
 
         // HIGH urgency should use warning emoji
         assert!(
@@ -541,6 +637,26 @@ mod tests {
         assert!(msg.contains("回复你的选择或输入内容"));
     }
 
+    #[test]
+    fn test_digest_in_payload_and_message() {
+        let event = NotificationEvent::error("cam-1", "build failed");
+        let mut payload = SystemEventPayload::from_event(&event, Urgency::High);
+        assert!(payload.to_json()["context"].get("digest").is_none());
+
+        payload.set_digest(vec![
+            "[cam-2] Error: 测试失败".to_string(),
+            "[cam-3] AgentExited: Agent exited".to_string(),
+        ]);
+        assert_eq!(
+            payload.to_json()["context"]["digest"][0],
+            "[cam-2] Error: 测试失败"
+        );
+
+        let msg = payload.to_message();
+        assert!(msg.contains("限流期间另有 2 条通知"));
+        assert!(msg.contains("[cam-3] AgentExited"));
+    }
+
     #[test]
     fn test_permission_request_includes_terminal_tail_in_message() {
         let mut event = NotificationEvent::permission_request(
@@ -556,10 +672,58 @@ mod tests {
         );
 
         let payload = SystemEventPayload::from_event(&event, Urgency::High);
-        let msg = payload.to_telegram_message();
+        let msg = payload.to_message();
 
         // Tail should include the last line, and (by construction) omit the first.
         assert!(msg.contains("line 50"));
         assert!(!msg.contains("line 1\nline 2\nline 3"));
     }
+
+    #[test]
+    fn test_permission_request_risk_findings() {
+        let event = NotificationEvent::permission_request(
+            "cam-123",
+            "Bash",
+            serde_json::json!({"command": "cd .. && rm -rf other"}),
+        )
+        .with_project_path("/workspace/project");
+
+        let payload = SystemEventPayload::from_event(&event, Urgency::High);
+        assert_eq!(payload.context.risk_level, "HIGH");
+        assert_eq!(
+            payload.context.risk_findings,
+            ["递归删除项目外路径 other"]
+        );
+        assert!(payload
+            .to_message()
+            .contains("HIGH（递归删除项目外路径 other）"));
+    }
+
+    #[test]
+    fn test_permission_request_file_impact() {
+        let event = NotificationEvent::permission_request(
+            "cam-123",
+            "Write",
+            serde_json::json!({"file_path": "/workspace/shared/config.rs"}),
+        )
+        .with_project_path("/workspace/project");
+
+        let payload = SystemEventPayload::from_event(&event, Urgency::High);
+        assert_eq!(payload.context.risk_level, "HIGH");
+        assert_eq!(
+            payload.context.file_impact.as_deref(),
+            Some("新建文件 · 项目目录外")
+        );
+        assert!(payload.to_message().contains("影响: 新建文件 · 项目目录外"));
+
+        let inside = NotificationEvent::permission_request(
+            "cam-123",
+            "Write",
+            serde_json::json!({"file_path": "src/config.rs"}),
+        )
+        .with_project_path("/workspace/project");
+        let payload = SystemEventPayload::from_event(&inside, Urgency::High);
+        assert_eq!(payload.context.risk_level, "MEDIUM");
+        assert_eq!(payload.context.file_impact.as_deref(), Some("新建文件"));
+    }
 }
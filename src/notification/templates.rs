@@ -0,0 +1,249 @@
+//! 通知模板 - 用户自定义通知正文
+//!
+//! 在 `~/.config/code-agent-monitor/templates/` 下放置模板文件即可覆盖内置文案，
+//! 未提供的模板使用内置格式：
+//!
+//! | 文件 | 事件 |
+//! |------|------|
+//! | `permission_request.txt` | 权限请求 |
+//! | `waiting_for_input.txt` | 等待输入 |
+//! | `error.txt` | 错误 |
+//! | `completion.txt` | 停止、会话结束、Agent 退出 |
+//!
+//! 模板是纯文本，支持占位符 `{{project}}`、`{{question}}`、`{{options}}`、`{{risk}}`、
+//! `{{impact}}`（Write/Edit 的文件影响）、`{{agent_id}}`，未知占位符原样保留。渲染结果按渠道格式转义。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::notification::system_event::{reply_options, EventData, SystemEventPayload};
+
+/// 可覆盖的模板类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplateKind {
+    PermissionRequest,
+    WaitingForInput,
+    Error,
+    Completion,
+}
+
+impl TemplateKind {
+    pub const ALL: [TemplateKind; 4] = [
+        TemplateKind::PermissionRequest,
+        TemplateKind::WaitingForInput,
+        TemplateKind::Error,
+        TemplateKind::Completion,
+    ];
+
+    /// 模板文件名
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            TemplateKind::PermissionRequest => "permission_request.txt",
+            TemplateKind::WaitingForInput => "waiting_for_input.txt",
+            TemplateKind::Error => "error.txt",
+            TemplateKind::Completion => "completion.txt",
+        }
+    }
+
+    /// system event 类型对应的模板
+    pub fn for_event_type(event_type: &str) -> Option<Self> {
+        match event_type {
+            "permission_request" => Some(TemplateKind::PermissionRequest),
+            "waiting_for_input" => Some(TemplateKind::WaitingForInput),
+            "error" => Some(TemplateKind::Error),
+            "stop" | "session_end" | "agent_exited" => Some(TemplateKind::Completion),
+            _ => None,
+        }
+    }
+}
+
+/// 用户模板集合（为空时全部使用内置格式）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationTemplates {
+    templates: HashMap<TemplateKind, String>,
+}
+
+impl NotificationTemplates {
+    /// 默认模板目录
+    pub fn default_dir() -> PathBuf {
+        crate::infra::paths::config_dir().join("templates")
+    }
+
+    /// 从默认目录加载
+    pub fn load() -> Self {
+        Self::load_from(&Self::default_dir())
+    }
+
+    /// 从指定目录加载，缺失或为空的文件跳过
+    pub fn load_from(dir: &Path) -> Self {
+        let mut templates = Self::default();
+        for kind in TemplateKind::ALL {
+            if let Ok(content) = std::fs::read_to_string(dir.join(kind.file_name())) {
+                let content = content.trim_end();
+                if !content.is_empty() {
+                    templates = templates.with_template(kind, content);
+                }
+            }
+        }
+        templates
+    }
+
+    /// 设置模板
+    pub fn with_template(mut self, kind: TemplateKind, template: impl Into<String>) -> Self {
+        self.templates.insert(kind, template.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    pub fn get(&self, kind: TemplateKind) -> Option<&str> {
+        self.templates.get(&kind).map(String::as_str)
+    }
+
+    /// 用模板渲染 system event 正文，没有对应模板时返回 None
+    pub fn render(&self, payload: &SystemEventPayload) -> Option<String> {
+        let template = self.get(TemplateKind::for_event_type(&payload.event_type)?)?;
+        Some(render_template(template, &template_vars(payload)))
+    }
+}
+
+/// 替换 `{{name}}` 占位符（允许两侧空白），未知占位符原样保留
+pub fn render_template(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match vars.get(after[..end].trim()) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 模板占位符取值
+fn template_vars(payload: &SystemEventPayload) -> HashMap<&'static str, String> {
+    let project = payload
+        .project_path
+        .as_deref()
+        .and_then(|path| Path::new(path).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| payload.agent_id.clone());
+
+    let question = match (&payload.context.extracted_message, &payload.event_data) {
+        (Some(message), _) => message.clone(),
+        (
+            None,
+            EventData::PermissionRequest {
+                tool_name,
+                tool_input,
+            },
+        ) => {
+            let cmd = tool_input
+                .get("command")
+                .or_else(|| tool_input.get("file_path"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            format!("{} {}", tool_name, cmd)
+        }
+        (None, EventData::Error { message, .. }) => message.clone(),
+        (None, EventData::Notification { message, .. }) => message.clone(),
+        _ => match payload.event_type.as_str() {
+            "waiting_for_input" => "等待输入".to_string(),
+            "agent_exited" => "Agent 已退出".to_string(),
+            other => other.to_string(),
+        },
+    };
+
+    let options = reply_options(payload);
+
+    let (error_type, suggestion) = match &payload.event_data {
+        EventData::Error {
+            category,
+            suggestion,
+            ..
+        } => (
+            category.map(|c| c.label().to_string()).unwrap_or_default(),
+            suggestion.clone().unwrap_or_default(),
+        ),
+        _ => (String::new(), String::new()),
+    };
+
+    HashMap::from([
+        ("project", project),
+        ("agent_id", payload.agent_id.clone()),
+        ("question", question),
+        ("options", options.to_string()),
+        ("risk", payload.context.risk_level.clone()),
+        (
+            "impact",
+            payload.context.file_impact.clone().unwrap_or_default(),
+        ),
+        ("error_type", error_type),
+        ("suggestion", suggestion),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::event::NotificationEvent;
+    use crate::notification::urgency::Urgency;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_template_placeholders() {
+        let vars = HashMap::from([("project", "app".to_string()), ("risk", "HIGH".to_string())]);
+        assert_eq!(
+            render_template("[{{project}}] risk={{ risk }} {{unknown}} {{broken", &vars),
+            "[app] risk=HIGH {{unknown}} {{broken"
+        );
+    }
+
+    #[test]
+    fn test_render_permission_request() {
+        let event = NotificationEvent::permission_request(
+            "cam-1",
+            "Bash",
+            serde_json::json!({"command": "rm -rf build"}),
+        )
+        .with_project_path("/work/my-app");
+        let payload = SystemEventPayload::from_event(&event, Urgency::High);
+        let templates = NotificationTemplates::default().with_template(
+            TemplateKind::PermissionRequest,
+            "{{project}} wants to run {{question}} ({{risk}}). Reply {{options}}",
+        );
+        assert_eq!(
+            templates.render(&payload).unwrap(),
+            "my-app wants to run Bash rm -rf build (MEDIUM). Reply y 允许 / n 拒绝"
+        );
+
+        // 没有对应模板时使用内置格式
+        let exited = NotificationEvent::agent_exited("cam-1");
+        let payload = SystemEventPayload::from_event(&exited, Urgency::Medium);
+        assert!(templates.render(&payload).is_none());
+    }
+
+    #[test]
+    fn test_load_from_dir() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("completion.txt"), "✅ {{project}} 完成\n").unwrap();
+        std::fs::write(dir.path().join("error.txt"), "  \n").unwrap();
+        let templates = NotificationTemplates::load_from(dir.path());
+        assert_eq!(
+            templates.get(TemplateKind::Completion),
+            Some("✅ {{project}} 完成")
+        );
+        assert_eq!(templates.get(TemplateKind::Error), None);
+        assert!(NotificationTemplates::load_from(&dir.path().join("missing")).is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+//! 工具风险策略 - PreToolUse hook 在执行前按风险等级拦截或通知
+//!
+//! 权限确认只覆盖 agent 主动询问的调用，已被允许的工具（或 `--dangerously-skip-permissions`）
+//! 不会触发通知。PreToolUse 对每次调用都生效，可以在执行前拦截高风险操作。
+//!
+//! 判定结果以 hook 决定 JSON 输出到 stdout，由 Claude Code 直接允许或拒绝调用。
+//! Bash 命令的风险由 [`crate::notification::shell_risk`] 按命令结构分析，拦截原因附带具体发现。
+//!
+//! config.json 示例（默认全部关闭）：
+//! ```json
+//! { "tool_policy": { "block": "HIGH", "notify": "MEDIUM", "allow": "LOW", "ask_timeout_secs": 30 } }
+//! ```
+//!
+//! - `block`：达到该等级直接拒绝
+//! - `notify`：达到该等级发送通知；设置了 `ask_timeout_secs` 时等待 `cam reply` 远程决定
+//! - `allow`：不超过该等级直接允许，跳过 Claude Code 自身的权限确认
+//! - `sandbox`：远程确认高风险 Bash 调用时允许回复 `s` 在沙箱中代为执行（见 `infra::sandbox`）
+
+use serde_json::Value;
+
+use crate::infra::sandbox::SandboxProfile;
+use crate::notification::summarizer::RiskLevel;
+use crate::notification::system_event::analyze_tool_risk;
+
+/// 工具风险策略
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolPolicy {
+    /// 风险达到该等级时拦截
+    pub block: Option<RiskLevel>,
+    /// 风险达到该等级时通知（不拦截）
+    pub notify: Option<RiskLevel>,
+    /// 风险不超过该等级时直接允许
+    pub allow: Option<RiskLevel>,
+    /// 通知后等待远程回复的秒数（None 表示不等待）
+    pub ask_timeout_secs: Option<u64>,
+    /// 高风险 Bash 调用的沙箱执行配置
+    pub sandbox: Option<SandboxProfile>,
+}
+
+/// 策略判定结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// 放行
+    Allow,
+    /// 明确允许（跳过 Claude Code 的权限确认）
+    Approve { risk: RiskLevel },
+    /// 放行并通知
+    Notify { risk: RiskLevel },
+    /// 通知并等待远程回复
+    Ask { risk: RiskLevel, timeout_secs: u64 },
+    /// 拦截
+    Block { risk: RiskLevel, reason: String },
+}
+
+fn parse_risk(value: &str) -> Option<RiskLevel> {
+    match value.to_ascii_uppercase().as_str() {
+        "LOW" => Some(RiskLevel::Low),
+        "MEDIUM" => Some(RiskLevel::Medium),
+        "HIGH" => Some(RiskLevel::High),
+        _ => None,
+    }
+}
+
+fn risk_rank(risk: RiskLevel) -> u8 {
+    match risk {
+        RiskLevel::Low => 0,
+        RiskLevel::Medium => 1,
+        RiskLevel::High => 2,
+    }
+}
+
+impl ToolPolicy {
+    /// 读取 `~/.config/code-agent-monitor/config.json` 的 `tool_policy`
+    pub fn load() -> Self {
+        crate::infra::paths::config_section::<Value>("tool_policy")
+            .map(|value| Self::from_json(&value))
+            .unwrap_or_default()
+    }
+
+    /// 从 JSON 解析（无效等级视为关闭）
+    pub fn from_json(value: &Value) -> Self {
+        let level = |key: &str| value.get(key).and_then(|v| v.as_str()).and_then(parse_risk);
+        Self {
+            block: level("block"),
+            notify: level("notify"),
+            allow: level("allow"),
+            ask_timeout_secs: value
+                .get("ask_timeout_secs")
+                .and_then(|v| v.as_u64())
+                .filter(|secs| *secs > 0),
+            sandbox: value.get("sandbox").and_then(SandboxProfile::from_json),
+        }
+    }
+
+    /// 是否启用了任何规则
+    pub fn is_enabled(&self) -> bool {
+        self.block.is_some() || self.notify.is_some() || self.allow.is_some()
+    }
+
+    /// 判定一次工具调用
+    pub fn evaluate(&self, tool_name: &str, tool_input: &Value) -> PolicyDecision {
+        self.evaluate_in(tool_name, tool_input, None)
+    }
+
+    /// 判定一次工具调用，`cwd` 用于解析 Bash 命令中的相对路径
+    pub fn evaluate_in(
+        &self,
+        tool_name: &str,
+        tool_input: &Value,
+        cwd: Option<&str>,
+    ) -> PolicyDecision {
+        if !self.is_enabled() {
+            return PolicyDecision::Allow;
+        }
+        let analysis = analyze_tool_risk(tool_name, tool_input, cwd);
+        let risk = analysis.risk;
+        let reaches = |threshold: Option<RiskLevel>| {
+            threshold.is_some_and(|t| risk_rank(risk) >= risk_rank(t))
+        };
+
+        if reaches(self.block) {
+            let target = crate::infra::jsonl::extract_tool_target_from_input(tool_name, tool_input)
+                .map(|t| format!(" {}", t))
+                .unwrap_or_default();
+            PolicyDecision::Block {
+                risk,
+                reason: format!(
+                    "CAM tool_policy 拦截了 {}{}（{}），请确认后手动执行或调整策略",
+                    tool_name,
+                    target,
+                    match analysis.summary() {
+                        Some(summary) => format!("{}：{}", risk.description(), summary),
+                        None => risk.description().to_string(),
+                    }
+                ),
+            }
+        } else if reaches(self.notify) {
+            match self.ask_timeout_secs {
+                Some(timeout_secs) => PolicyDecision::Ask { risk, timeout_secs },
+                None => PolicyDecision::Notify { risk },
+            }
+        } else if self.allow.is_some_and(|t| risk_rank(risk) <= risk_rank(t)) {
+            PolicyDecision::Approve { risk }
+        } else {
+            PolicyDecision::Allow
+        }
+    }
+
+    /// 远程确认时可提供沙箱执行的配置（仅高风险 Bash 调用）
+    pub fn sandbox_for(&self, tool_name: &str, risk: RiskLevel) -> Option<&SandboxProfile> {
+        self.sandbox
+            .as_ref()
+            .filter(|_| tool_name == "Bash" && risk == RiskLevel::High)
+    }
+}
+
+impl PolicyDecision {
+    /// Claude Code PreToolUse hook 的 stdout 输出（允许或拒绝时返回决定）
+    pub fn hook_output(&self) -> Option<Value> {
+        match self {
+            PolicyDecision::Approve { risk } => Some(permission_output(
+                "allow",
+                &format!("CAM tool_policy 自动允许（{}）", risk.description()),
+            )),
+            PolicyDecision::Block { reason, .. } => Some(permission_output("deny", reason)),
+            _ => None,
+        }
+    }
+}
+
+/// 构造 PreToolUse 的权限决定（`allow` / `deny` / `ask`）
+pub fn permission_output(decision: &str, reason: &str) -> Value {
+    serde_json::json!({
+        "hookSpecificOutput": {
+            "hookEventName": "PreToolUse",
+            "permissionDecision": decision,
+            "permissionDecisionReason": reason,
+        }
+    })
+}
+
+/// 是否是选择沙箱执行的回复
+pub fn is_sandbox_reply(reply: &str) -> bool {
+    matches!(reply.trim(), "s" | "sandbox")
+}
+
+/// 把 `cam reply` 的回复转换为权限决定
+///
+/// `y` 与第一个选项视为允许；其他回复视为拒绝，原文作为原因反馈给 agent。
+pub fn reply_output(reply: &str) -> Value {
+    match reply.trim() {
+        "y" | "1" => permission_output("allow", "用户通过 CAM 远程允许"),
+        "n" => permission_output("deny", "用户通过 CAM 远程拒绝"),
+        other => permission_output("deny", &format!("用户通过 CAM 拒绝：{}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_disabled_by_default() {
+        let policy = ToolPolicy::from_json(&json!({}));
+        assert!(!policy.is_enabled());
+        assert_eq!(
+            policy.evaluate("Bash", &json!({"command": "rm -rf /"})),
+            PolicyDecision::Allow
+        );
+    }
+
+    #[test]
+    fn test_block_and_notify_thresholds() {
+        let policy = ToolPolicy::from_json(&json!({"block": "high", "notify": "MEDIUM"}));
+        let decision = policy.evaluate("Bash", &json!({"command": "rm -rf /"}));
+        assert!(matches!(
+            decision,
+            PolicyDecision::Block {
+                risk: RiskLevel::High,
+                ..
+            }
+        ));
+        let output = decision.hook_output().unwrap();
+        assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert!(output["hookSpecificOutput"]["permissionDecisionReason"]
+            .as_str()
+            .unwrap()
+            .contains("高风险：递归删除根目录 /"));
+        // 项目内的递归删除只通知
+        assert!(matches!(
+            policy.evaluate_in(
+                "Bash",
+                &json!({"command": "rm -rf build"}),
+                Some("/work/app")
+            ),
+            PolicyDecision::Notify {
+                risk: RiskLevel::Medium
+            }
+        ));
+
+        assert_eq!(
+            policy.evaluate("Bash", &json!({"command": "ls"})),
+            PolicyDecision::Allow
+        );
+        assert!(PolicyDecision::Allow.hook_output().is_none());
+    }
+
+    #[test]
+    fn test_allow_and_ask() {
+        let policy = ToolPolicy::from_json(
+            &json!({"notify": "MEDIUM", "allow": "LOW", "ask_timeout_secs": 30}),
+        );
+        let decision = policy.evaluate("Read", &json!({"file_path": "/tmp/out.log"}));
+        assert_eq!(
+            decision,
+            PolicyDecision::Approve {
+                risk: RiskLevel::Low
+            }
+        );
+        assert_eq!(
+            decision.hook_output().unwrap()["hookSpecificOutput"]["permissionDecision"],
+            "allow"
+        );
+        assert!(matches!(
+            policy.evaluate("Bash", &json!({"command": "rm -rf /"})),
+            PolicyDecision::Ask {
+                timeout_secs: 30,
+                ..
+            }
+        ));
+
+        assert!(policy.sandbox_for("Bash", RiskLevel::High).is_none());
+
+        let output = reply_output("y");
+        assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "allow");
+        let output = reply_output("先跑测试");
+        assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert!(output["hookSpecificOutput"]["permissionDecisionReason"]
+            .as_str()
+            .unwrap()
+            .contains("先跑测试"));
+    }
+
+    #[test]
+    fn test_sandbox_only_for_high_risk_bash() {
+        let policy = ToolPolicy::from_json(&json!({
+            "notify": "HIGH",
+            "ask_timeout_secs": 60,
+            "sandbox": {"backend": "docker", "image": "alpine:3"}
+        }));
+        assert!(policy.sandbox_for("Bash", RiskLevel::High).is_some());
+        assert!(policy.sandbox_for("Bash", RiskLevel::Medium).is_none());
+        assert!(policy.sandbox_for("Write", RiskLevel::High).is_none());
+
+        assert!(is_sandbox_reply("s"));
+        assert!(is_sandbox_reply(" sandbox "));
+        assert!(!is_sandbox_reply("n"));
+    }
+}
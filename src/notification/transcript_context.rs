@@ -0,0 +1,140 @@
+//! 对话上下文 - 确认通知附带 transcript 中最近的助手消息
+//!
+//! 终端快照只有当前屏幕，问题经常引用已经滚出屏幕的内容（"上面的方案"）。
+//! 需要回复的通知（等待输入、权限请求）和待处理确认附带 JSONL transcript 中
+//! 最近几条助手消息，过长的消息只保留开头和结尾。
+//!
+//! config.json 中 `"transcript_context": {"messages": 3, "max_width": 400}` 调整，
+//! `messages` 为 0 时关闭。
+
+use serde::Deserialize;
+
+use crate::agent::AgentManager;
+use crate::infra::jsonl::{JsonlEvent, JsonlParser};
+use crate::infra::text::{display_width, take_width, take_width_end};
+
+/// 摘要中开头部分占的比例（其余留给结尾）
+const HEAD_RATIO: f64 = 0.6;
+
+/// 对话上下文配置（config.json 的 `transcript_context`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TranscriptContextConfig {
+    /// 附带的助手消息数
+    pub messages: usize,
+    /// 单条消息的最大显示宽度，超过时摘要
+    pub max_width: usize,
+}
+
+impl Default for TranscriptContextConfig {
+    fn default() -> Self {
+        Self {
+            messages: 3,
+            max_width: 400,
+        }
+    }
+}
+
+impl TranscriptContextConfig {
+    /// 读取 config.json，缺失或无效时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("transcript_context").unwrap_or_default()
+    }
+
+    /// 读取 agent transcript 中最近的助手消息（旧的在前），agent 未知或没有 transcript 时为空
+    pub fn recent_for_agent(&self, agent_id: &str) -> Vec<String> {
+        if self.messages == 0 {
+            return Vec::new();
+        }
+        AgentManager::new()
+            .get_agent(agent_id)
+            .ok()
+            .flatten()
+            .and_then(|agent| agent.jsonl_path)
+            .map(|path| self.recent_messages(&path))
+            .unwrap_or_default()
+    }
+
+    /// 读取 transcript 中最近的助手消息（旧的在前）
+    pub fn recent_messages(&self, jsonl_path: &str) -> Vec<String> {
+        let Ok(events) = JsonlParser::new(jsonl_path).read_all_events() else {
+            return Vec::new();
+        };
+        let messages: Vec<String> = events
+            .into_iter()
+            .filter_map(|event| match event {
+                JsonlEvent::AssistantText { content, .. } if !content.trim().is_empty() => {
+                    Some(content)
+                }
+                _ => None,
+            })
+            .collect();
+        let start = messages.len().saturating_sub(self.messages);
+        messages[start..]
+            .iter()
+            .map(|message| summarize_message(message, self.max_width))
+            .collect()
+    }
+}
+
+/// 把消息压成一行，超过 `max_width` 时保留开头和结尾，中间用 `…` 省略
+pub fn summarize_message(message: &str, max_width: usize) -> String {
+    let text = message.split_whitespace().collect::<Vec<_>>().join(" ");
+    if display_width(&text) <= max_width {
+        return text;
+    }
+    let budget = max_width.saturating_sub(3);
+    let head_width = (budget as f64 * HEAD_RATIO) as usize;
+    let head = take_width(&text, head_width).trim_end();
+    let tail = take_width_end(&text, budget - head_width).trim_start();
+    format!("{} … {}", head, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_summarize_message() {
+        assert_eq!(
+            summarize_message("方案 A:\n\n  用 Redis", 100),
+            "方案 A: 用 Redis"
+        );
+
+        let long = format!("开头 {} 结尾", "x".repeat(200));
+        let summary = summarize_message(&long, 40);
+        assert!(display_width(&summary) <= 40);
+        assert!(summary.starts_with("开头 xxx"));
+        assert!(summary.ends_with("xxx 结尾"));
+        assert!(summary.contains(" … "));
+    }
+
+    #[test]
+    fn test_recent_messages_from_transcript() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for (kind, text) in [
+            ("assistant", "第一条"),
+            ("user", "继续"),
+            ("assistant", "方案一：重写登录"),
+            ("assistant", "方案二：只修复 token 校验"),
+        ] {
+            let line = serde_json::json!({
+                "type": kind,
+                "message": {"role": kind, "content": [{"type": "text", "text": text}]}
+            });
+            writeln!(file, "{}", line).unwrap();
+        }
+
+        let config = TranscriptContextConfig {
+            messages: 2,
+            max_width: 400,
+        };
+        let path = file.path().to_str().unwrap();
+        assert_eq!(
+            config.recent_messages(path),
+            vec!["方案一：重写登录", "方案二：只修复 token 校验"]
+        );
+        assert!(config.recent_messages("/nonexistent.jsonl").is_empty());
+    }
+}
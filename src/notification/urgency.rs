@@ -6,6 +6,9 @@
 //! - MEDIUM: User should know (agent exited, idle prompt)
 //! - LOW: Optional/silent (session start, tool use)
 
+use crate::notification::summarizer::ErrorCategory;
+use crate::notification::waiting_kind::WaitingKind;
+
 /// Urgency level for notifications
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Urgency {
@@ -72,14 +75,66 @@ pub fn get_urgency(event_type: &str, context: &str) -> Urgency {
                 .unwrap_or("");
             match notification_type {
                 "permission_prompt" => Urgency::High, // Permission confirmation
+                "permission_batch" => Urgency::High,  // Same tool approval from several agents
+                "tool_blocked" => Urgency::High,      // PreToolUse blocked by tool_policy
+                "reply_denied" => Urgency::High,      // Reply rejected by reply_auth
                 "idle_prompt" => Urgency::Medium,     // Idle waiting
+                // Watcher-generated summaries (tool batch, answered in terminal)
+                "tool_batch" | "resolved_in_terminal" => Urgency::Medium,
+                // PreToolUse reached tool_policy notify threshold
+                "risky_tool" => Urgency::Medium,
+                // Processing with no screen change for too long
+                "agent_stuck" => Urgency::Medium,
+                // Idle at a bare prompt for too long, suggesting shutdown
+                "agent_idle" => Urgency::Medium,
+                // Context window usage crossed context_alert_percent
+                "context_high" => Urgency::Medium,
+                // Delivery latency consistently above latency_slo
+                "latency_slo" => Urgency::High,
+                // Custom watch_rules matches (rule_info falls through to Low)
+                "rule_alert" => Urgency::High,
+                "rule_match" => Urgency::Medium,
+                // Automatic recovery executed / failed or retries exhausted
+                "recovery" => Urgency::Medium,
+                "recovery_failed" => Urgency::High,
+                // Team inbox messages forwarded by the team's camForward rule
+                "team_escalation" => Urgency::High,
+                "team_message" => Urgency::Medium,
+                // Member branch conflicted during `cam team-merge`
+                "team_merge_conflict" => Urgency::High,
                 _ => Urgency::Low,
             }
         }
-        // Error must be forwarded - needs intervention
-        "error" => Urgency::High,
-        // Waiting for input must be forwarded
-        "waitingforinput" => Urgency::High,
+        // Error must be forwarded - needs intervention, unless the category
+        // says the agent can recover on its own (rate limit, network, build/test)
+        "error" => {
+            let json: Option<serde_json::Value> = serde_json::from_str(raw_context).ok();
+            let category = json
+                .as_ref()
+                .and_then(|j| j.get("error_category"))
+                .and_then(|v| v.as_str())
+                .and_then(ErrorCategory::parse);
+            match category {
+                Some(
+                    ErrorCategory::RateLimit
+                    | ErrorCategory::Network
+                    | ErrorCategory::CompileFailure
+                    | ErrorCategory::TestFailure,
+                ) => Urgency::Medium,
+                _ => Urgency::High,
+            }
+        }
+        // Waiting for input: decisions and permission prompts block the agent,
+        // informational pauses and long-form input can wait. Unclassified stays HIGH.
+        "waitingforinput" => {
+            let json: Option<serde_json::Value> = serde_json::from_str(raw_context).ok();
+            json.as_ref()
+                .and_then(|j| j.get("waiting_kind"))
+                .and_then(|v| v.as_str())
+                .and_then(WaitingKind::parse)
+                .map(|kind| kind.urgency())
+                .unwrap_or(Urgency::High)
+        }
         // Agent abnormal exit - need to know (might be crash or killed)
         "agentexited" => Urgency::Medium,
         // stop/session_end - user triggered stop, no notification needed (user already knows)
@@ -124,6 +179,54 @@ mod tests {
         // notification with idle_prompt
         let context = r#"{"notification_type": "idle_prompt"}"#;
         assert_eq!(get_urgency("notification", context), Urgency::Medium);
+
+        // watcher-generated summaries
+        let context = r#"{"notification_type": "tool_batch", "message": "编辑 3 个文件"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::Medium);
+        let context = r#"{"notification_type": "resolved_in_terminal"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::Medium);
+
+        // tool_policy
+        let context = r#"{"notification_type": "risky_tool"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::Medium);
+        let context = r#"{"notification_type": "tool_blocked"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::High);
+
+        // stuck watchdog
+        let context = r#"{"notification_type": "agent_stuck"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::Medium);
+
+        // automatic recovery
+        let context = r#"{"notification_type": "recovery"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::Medium);
+        let context = r#"{"notification_type": "recovery_failed"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::High);
+
+        // forwarded team inbox messages
+        let context = r#"{"notification_type": "team_message"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::Medium);
+        let context = r#"{"notification_type": "team_escalation"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::High);
+        let context = r#"{"notification_type": "team_merge_conflict"}"#;
+        assert_eq!(get_urgency("notification", context), Urgency::High);
+
+        // recoverable error categories
+        let context = r#"{"error_category": "rate_limit"}"#;
+        assert_eq!(get_urgency("error", context), Urgency::Medium);
+        let context = r#"{"error_category": "test_failure"}"#;
+        assert_eq!(get_urgency("error", context), Urgency::Medium);
+        let context = r#"{"error_category": "auth_failure"}"#;
+        assert_eq!(get_urgency("error", context), Urgency::High);
+        let context = r#"{"error_category": "context_window"}"#;
+        assert_eq!(get_urgency("error", context), Urgency::High);
+
+        // waiting classification
+        let context = r#"{"waiting_kind": "informational_pause"}"#;
+        assert_eq!(get_urgency("WaitingForInput", context), Urgency::Medium);
+        let context = r#"{"waiting_kind": "long_form_input"}"#;
+        assert_eq!(get_urgency("waiting_for_input", context), Urgency::Medium);
+        let context = r#"{"waiting_kind": "blocking_permission"}"#;
+        assert_eq!(get_urgency("WaitingForInput", context), Urgency::High);
     }
 
     #[test]
@@ -0,0 +1,236 @@
+//! 等待输入分类 - 区分 agent 停下来等待的原因
+//!
+//! `is_decision_required` 只能区分"需要决策"和"其他"。这里结合 watcher 的模式类型、
+//! 终端文本和 AI 提取器的 `MessageType`，把 WaitingForInput 事件分为四类：
+//!
+//! | 分类 | 含义 | urgency |
+//! |------|------|---------|
+//! | `blocking_permission` | 工具执行前的权限确认 | HIGH |
+//! | `needs_decision` | 选择题、y/n 确认、方案决策 | HIGH |
+//! | `long_form_input` | 需要描述、补充信息等长文本回复 | MEDIUM |
+//! | `informational_pause` | 任务告一段落、按回车继续等 | MEDIUM |
+//!
+//! webhook 配置 `routes` 可以按分类投递到不同渠道（见 `WebhookConfig::routes`）。
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::extractor::MessageType;
+use crate::notification::urgency::Urgency;
+
+/// 权限确认的提示文字
+const PERMISSION_PATTERNS: [&str; 9] = [
+    "do you want to proceed",
+    "do you want to allow",
+    "do you want to make this edit",
+    "do you want to create",
+    "allow this",
+    "requires permission",
+    "requires approval",
+    "是否允许",
+    "需要权限",
+];
+
+/// 需要长文本回复的提示文字
+const LONG_FORM_PATTERNS: [&str; 10] = [
+    "describe",
+    "explain",
+    "provide more",
+    "more details",
+    "paste",
+    "what should",
+    "请描述",
+    "请提供",
+    "请说明",
+    "粘贴",
+];
+
+/// 等待输入的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitingKind {
+    NeedsDecision,
+    InformationalPause,
+    BlockingPermission,
+    LongFormInput,
+}
+
+impl WaitingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NeedsDecision => "needs_decision",
+            Self::InformationalPause => "informational_pause",
+            Self::BlockingPermission => "blocking_permission",
+            Self::LongFormInput => "long_form_input",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "needs_decision" => Some(Self::NeedsDecision),
+            "informational_pause" => Some(Self::InformationalPause),
+            "blocking_permission" => Some(Self::BlockingPermission),
+            "long_form_input" => Some(Self::LongFormInput),
+            _ => None,
+        }
+    }
+
+    /// 通知中显示的中文标签
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NeedsDecision => "需要决策",
+            Self::InformationalPause => "暂停",
+            Self::BlockingPermission => "权限确认",
+            Self::LongFormInput => "需要详细输入",
+        }
+    }
+
+    /// 该分类的 urgency：阻塞在决策或权限上的为 HIGH，其余为 MEDIUM
+    pub fn urgency(&self) -> Urgency {
+        match self {
+            Self::NeedsDecision | Self::BlockingPermission => Urgency::High,
+            Self::InformationalPause | Self::LongFormInput => Urgency::Medium,
+        }
+    }
+}
+
+impl std::fmt::Display for WaitingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// 文本中是否有编号选项（至少两个 `1.`/`2)` 形式的行）
+fn has_numbered_options(text: &str) -> bool {
+    text.lines()
+        .map(|line| line.trim().trim_start_matches(['❯', '>', ' ']))
+        .filter(|line| {
+            let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+            digits > 0 && line[digits..].starts_with(['.', ')'])
+        })
+        .count()
+        >= 2
+}
+
+fn contains_any(text: &str, patterns: &[&str]) -> bool {
+    patterns.iter().any(|pattern| text.contains(pattern))
+}
+
+/// 对 WaitingForInput 事件分类
+///
+/// - `pattern_type`: watcher 检测到的模式（`InputWaitPattern` 的名称）
+/// - `is_decision_required`: watcher 或 AI 提取器给出的决策标记
+/// - `message_type`: AI 提取器的消息类型（未调用 AI 时为 None）
+/// - `text`: AI 提取的消息，或终端快照末尾
+pub fn classify_waiting(
+    pattern_type: &str,
+    is_decision_required: bool,
+    message_type: Option<&MessageType>,
+    text: &str,
+) -> WaitingKind {
+    let lower = text.to_lowercase();
+    let pattern = pattern_type.to_lowercase().replace('_', "");
+
+    if pattern == "permissionrequest" || contains_any(&lower, &PERMISSION_PATTERNS) {
+        return WaitingKind::BlockingPermission;
+    }
+    if is_decision_required {
+        return WaitingKind::NeedsDecision;
+    }
+    match message_type {
+        Some(MessageType::Choice | MessageType::Confirmation) => WaitingKind::NeedsDecision,
+        Some(MessageType::OpenEnded) => WaitingKind::LongFormInput,
+        Some(MessageType::Idle { .. }) => WaitingKind::InformationalPause,
+        None => {
+            if pattern == "confirmation" || lower.contains("[y/n]") || has_numbered_options(&lower)
+            {
+                WaitingKind::NeedsDecision
+            } else if pattern == "colonprompt" || contains_any(&lower, &LONG_FORM_PATTERNS) {
+                WaitingKind::LongFormInput
+            } else {
+                WaitingKind::InformationalPause
+            }
+        }
+    }
+}
+
+/// 终端快照末尾（分类只看最近的输出）
+pub fn snapshot_tail(snapshot: &str, lines: usize) -> String {
+    let all: Vec<&str> = snapshot.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_by_patterns() {
+        let permission = "Bash command\n  rm -rf build\nDo you want to proceed?\n❯ 1. Yes\n  2. No";
+        assert_eq!(
+            classify_waiting("Other", false, None, permission),
+            WaitingKind::BlockingPermission
+        );
+        assert_eq!(
+            classify_waiting("Other", false, None, "Which framework?\n1. React\n2. Vue"),
+            WaitingKind::NeedsDecision
+        );
+        assert_eq!(
+            classify_waiting("Confirmation", false, None, "Continue? "),
+            WaitingKind::NeedsDecision
+        );
+        assert_eq!(
+            classify_waiting(
+                "Other",
+                false,
+                None,
+                "Please describe the expected behavior"
+            ),
+            WaitingKind::LongFormInput
+        );
+        assert_eq!(
+            classify_waiting("ClaudePrompt", false, None, "Done. Tests pass.\n>"),
+            WaitingKind::InformationalPause
+        );
+        assert_eq!(
+            classify_waiting("ClaudePrompt", true, None, ">"),
+            WaitingKind::NeedsDecision
+        );
+    }
+
+    #[test]
+    fn test_classify_with_message_type() {
+        let question = "Which database should I use?";
+        assert_eq!(
+            classify_waiting("Other", false, Some(&MessageType::Choice), question),
+            WaitingKind::NeedsDecision
+        );
+        assert_eq!(
+            classify_waiting("Other", false, Some(&MessageType::OpenEnded), question),
+            WaitingKind::LongFormInput
+        );
+        // 权限提示优先于提取器的类型
+        assert_eq!(
+            classify_waiting(
+                "Other",
+                false,
+                Some(&MessageType::Confirmation),
+                "Do you want to allow Write to src/main.rs?"
+            ),
+            WaitingKind::BlockingPermission
+        );
+    }
+
+    #[test]
+    fn test_kind_urgency_and_roundtrip() {
+        assert_eq!(WaitingKind::BlockingPermission.urgency(), Urgency::High);
+        assert_eq!(WaitingKind::InformationalPause.urgency(), Urgency::Medium);
+        for kind in [
+            WaitingKind::NeedsDecision,
+            WaitingKind::InformationalPause,
+            WaitingKind::BlockingPermission,
+            WaitingKind::LongFormInput,
+        ] {
+            assert_eq!(WaitingKind::parse(kind.as_str()), Some(kind));
+        }
+    }
+}
@@ -26,7 +26,7 @@ pub enum NotifyEvent {
     },
 }
 
-/// 通知器
+/// 控制台通知器（供旧版 `Watcher` 使用；接入自定义系统请实现 `notification::Notifier` trait）
 pub struct Notifier {
     /// 是否使用 OpenClaw 发送通知（已废弃，保留兼容性）
     #[allow(dead_code)]
@@ -82,6 +82,9 @@ impl Notifier {
 }
 
 /// 监控器 - 持续监控代理进程状态
+///
+/// 只扫描进程、打印启动/退出，不经过通知管道。`cam watch` 已改用 `agent::WatchEngine`。
+#[deprecated(note = "使用 agent::WatchEngine")]
 pub struct Watcher {
     /// 轮询间隔（秒）
     interval_secs: u64,
@@ -91,6 +94,7 @@ pub struct Watcher {
     last_agents: HashMap<u32, AgentInfo>,
 }
 
+#[allow(deprecated)]
 impl Watcher {
     pub fn new(interval_secs: u64, use_openclaw: bool) -> Self {
         Self {
@@ -1,11 +1,17 @@
 //! OpenClaw Webhook 客户端模块
 //!
 //! 通过 HTTP Webhook 调用 OpenClaw Gateway API
+//!
+//! 配置 `signing_secret` 后请求带 HMAC-SHA256 签名头（见 [`super::signing`]），
+//! 接收方可用 [`WebhookClient::verifier`] 验证。
 
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
+use super::signing::{self, WebhookVerifier};
+
 /// Webhook 客户端配置
 #[derive(Debug, Clone)]
 pub struct WebhookConfig {
@@ -18,6 +24,66 @@ pub struct WebhookConfig {
     /// Optional delivery defaults for `/hooks/agent`
     pub default_channel: Option<String>,
     pub default_to: Option<String>,
+    /// 消息格式覆盖（plain/markdown/markdownv2/mrkdwn/whatsapp/html），默认按渠道选择
+    pub format: Option<String>,
+    /// 渠道支持回复时，同一 agent 的后续通知作为首条消息的回复发送
+    pub threads: bool,
+    /// 按等待输入分类（如 `blocking_permission`）覆盖投递渠道
+    pub routes: HashMap<String, WebhookRoute>,
+    /// 请求签名密钥（HMAC-SHA256），未设置时不签名
+    pub signing_secret: Option<String>,
+    /// 投递失败时依次尝试的备用渠道
+    pub failover: Option<FailoverChain>,
+}
+
+/// 某类通知的投递目标
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookRoute {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+/// 备用渠道链（config.json 的 `webhook.failover`）
+///
+/// 首选渠道（`default_channel` 或 `routes` 匹配的渠道）在 `timeout_secs` 内没有确认送达时，
+/// 按 `channels` 的顺序改用下一个渠道，直到某一跳成功。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FailoverChain {
+    /// 备用渠道，按顺序尝试（渠道名或 `{"channel": .., "to": ..}`）
+    #[serde(deserialize_with = "deserialize_hops")]
+    pub channels: Vec<WebhookRoute>,
+    /// 每一跳等待送达确认的时间（秒），最后一跳使用 webhook 的 `timeout_secs`
+    #[serde(default = "default_failover_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_failover_timeout() -> u64 {
+    10
+}
+
+fn deserialize_hops<'de, D>(deserializer: D) -> Result<Vec<WebhookRoute>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Hop {
+        Channel(String),
+        Route(WebhookRoute),
+    }
+    Ok(Vec::<Hop>::deserialize(deserializer)?
+        .into_iter()
+        .map(|hop| match hop {
+            Hop::Channel(channel) => WebhookRoute {
+                channel: Some(channel),
+                to: None,
+            },
+            Hop::Route(route) => route,
+        })
+        .collect())
 }
 
 impl Default for WebhookConfig {
@@ -28,6 +94,11 @@ impl Default for WebhookConfig {
             timeout_secs: 30,
             default_channel: None,
             default_to: None,
+            format: None,
+            threads: true,
+            routes: HashMap::new(),
+            signing_secret: None,
+            failover: None,
         }
     }
 }
@@ -35,21 +106,7 @@ impl Default for WebhookConfig {
 /// 从配置文件加载 webhook 配置
 /// 配置文件路径: ~/.config/code-agent-monitor/config.json
 pub fn load_webhook_config_from_file() -> Option<WebhookConfig> {
-    use std::fs;
-
-    let config_path = dirs::home_dir()?
-        .join(".config")
-        .join("code-agent-monitor")
-        .join("config.json");
-
-    if !config_path.exists() {
-        return None;
-    }
-
-    let content = fs::read_to_string(&config_path).ok()?;
-    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
-
-    let webhook = json.get("webhook")?;
+    let webhook = crate::infra::paths::config_section::<serde_json::Value>("webhook")?;
 
     Some(WebhookConfig {
         gateway_url: webhook
@@ -74,6 +131,27 @@ pub fn load_webhook_config_from_file() -> Option<WebhookConfig> {
             .get("default_to")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string()),
+        format: webhook
+            .get("format")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        threads: webhook
+            .get("threads")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+        routes: webhook
+            .get("routes")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default(),
+        signing_secret: webhook
+            .get("signing_secret")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string()),
+        failover: webhook
+            .get("failover")
+            .and_then(|v| serde_json::from_value::<FailoverChain>(v.clone()).ok())
+            .filter(|chain| !chain.channels.is_empty()),
     })
 }
 
@@ -103,6 +181,9 @@ pub struct WebhookPayload {
     /// 接收者 ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub to: Option<String>,
+    /// 作为该消息的回复发送（Telegram reply-to、Slack thread）
+    #[serde(skip_serializing_if = "Option::is_none", rename = "replyTo")]
+    pub reply_to: Option<String>,
 }
 
 /// Webhook 响应
@@ -111,6 +192,28 @@ pub struct WebhookResponse {
     pub ok: bool,
     #[serde(default)]
     pub error: Option<String>,
+    /// 渠道返回的消息 ID（网关支持时）
+    #[serde(
+        default,
+        rename = "messageId",
+        alias = "message_id",
+        deserialize_with = "deserialize_message_id"
+    )]
+    pub message_id: Option<String>,
+}
+
+/// 消息 ID 可能是字符串（Slack ts）或数字（Telegram message_id）
+fn deserialize_message_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(id)) => Some(id),
+            Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        },
+    )
 }
 
 /// OpenClaw Webhook 客户端
@@ -135,6 +238,55 @@ impl WebhookClient {
         Ok(Self { client, config })
     }
 
+    /// 是否启用消息线程
+    pub fn threads_enabled(&self) -> bool {
+        self.config.threads
+    }
+
+    /// 某类通知（等待输入分类名）的投递目标
+    pub fn route(&self, kind: &str) -> Option<&WebhookRoute> {
+        self.config.routes.get(kind)
+    }
+
+    /// 备用渠道链
+    pub fn failover(&self) -> Option<&FailoverChain> {
+        self.config.failover.as_ref()
+    }
+
+    /// 请求超时（秒）
+    pub fn timeout_secs(&self) -> u64 {
+        self.config.timeout_secs
+    }
+
+    /// 按同一密钥验证签名的验证器，未配置 `signing_secret` 时为 None
+    pub fn verifier(&self) -> Option<WebhookVerifier> {
+        self.config
+            .signing_secret
+            .as_deref()
+            .map(WebhookVerifier::new)
+    }
+
+    /// 请求头：认证、内容类型，配置了密钥时附加签名
+    fn headers(&self, body: &[u8]) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            (
+                "Authorization",
+                format!("Bearer {}", self.config.hook_token),
+            ),
+            ("Content-Type", "application/json".to_string()),
+        ];
+        if let Some(secret) = &self.config.signing_secret {
+            let signed = signing::sign(secret, body);
+            headers.extend(
+                signed
+                    .pairs()
+                    .into_iter()
+                    .map(|(name, value)| (name, value.to_string())),
+            );
+        }
+        headers
+    }
+
     /// 发送通知到 OpenClaw Gateway (同步阻塞版本)
     pub fn send_notification_blocking(
         &self,
@@ -143,8 +295,32 @@ impl WebhookClient {
         channel: Option<String>,
         to: Option<String>,
     ) -> Result<WebhookResponse, String> {
-        use std::time::Duration;
+        self.send_reply_blocking(message, agent_id, channel, to, None)
+    }
 
+    /// 发送通知到 OpenClaw Gateway，`reply_to` 存在时作为该消息的回复发送 (同步阻塞版本)
+    pub fn send_reply_blocking(
+        &self,
+        message: String,
+        agent_id: Option<String>,
+        channel: Option<String>,
+        to: Option<String>,
+        reply_to: Option<String>,
+    ) -> Result<WebhookResponse, String> {
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        self.send_reply_blocking_within(message, agent_id, channel, to, reply_to, timeout)
+    }
+
+    /// 同 [`Self::send_reply_blocking`]，`timeout` 内网关没有确认送达时返回错误
+    pub fn send_reply_blocking_within(
+        &self,
+        message: String,
+        agent_id: Option<String>,
+        channel: Option<String>,
+        to: Option<String>,
+        reply_to: Option<String>,
+        timeout: Duration,
+    ) -> Result<WebhookResponse, String> {
         let url = format!("{}/hooks/agent", self.config.gateway_url);
 
         let payload = WebhookPayload {
@@ -155,22 +331,23 @@ impl WebhookClient {
             deliver: Some(true),
             channel,
             to,
+            reply_to,
         };
 
         // 使用 blocking client
         let blocking_client = reqwest::blocking::Client::builder()
-            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .timeout(timeout)
             .build()
             .map_err(|e| format!("Failed to create blocking client: {}", e))?;
 
-        let response = blocking_client
-            .post(&url)
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.hook_token),
-            )
-            .header("Content-Type", "application/json")
-            .json(&payload)
+        let body = serde_json::to_vec(&payload)
+            .map_err(|e| format!("Failed to serialize payload: {}", e))?;
+        let mut request = blocking_client.post(&url);
+        for (name, value) in self.headers(&body) {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(body)
             .send()
             .map_err(|e| format!("HTTP request failed: {}", e))?;
 
@@ -211,17 +388,17 @@ impl WebhookClient {
             deliver: Some(true),
             channel,
             to,
+            reply_to: None,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.hook_token),
-            )
-            .header("Content-Type", "application/json")
-            .json(&payload)
+        let body = serde_json::to_vec(&payload)
+            .map_err(|e| format!("Failed to serialize payload: {}", e))?;
+        let mut request = self.client.post(&url);
+        for (name, value) in self.headers(&body) {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(body)
             .send()
             .await
             .map_err(|e| format!("HTTP request failed: {}", e))?;
@@ -285,6 +462,35 @@ mod tests {
         assert!(result.unwrap_err().contains("hook_token"));
     }
 
+    #[test]
+    fn test_webhook_client_signs_requests() {
+        let unsigned = WebhookClient::new(WebhookConfig {
+            hook_token: "token".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(unsigned.headers(b"{}").len(), 2);
+        assert!(unsigned.verifier().is_none());
+
+        let client = WebhookClient::new(WebhookConfig {
+            hook_token: "token".to_string(),
+            signing_secret: Some("secret".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let body = br#"{"message":"hi"}"#;
+        let headers = client.headers(body);
+        let lookup = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        let verifier = client.verifier().unwrap();
+        assert!(verifier.verify_headers(lookup, body).is_ok());
+        assert!(verifier.verify_headers(lookup, body).is_err());
+    }
+
     #[test]
     fn test_webhook_payload_uses_camel_case_for_gateway() {
         let payload = WebhookPayload {
@@ -295,12 +501,43 @@ mod tests {
             deliver: Some(true),
             channel: Some("telegram".to_string()),
             to: Some("1440537501".to_string()),
+            reply_to: Some("812".to_string()),
         };
 
         let json = serde_json::to_value(&payload).unwrap();
         assert!(json.get("agentId").is_some());
         assert!(json.get("wakeMode").is_some());
+        assert_eq!(json["replyTo"], "812");
         assert!(json.get("agent_id").is_none());
         assert!(json.get("wake_mode").is_none());
     }
+
+    #[test]
+    fn test_webhook_response_message_id() {
+        let resp: WebhookResponse =
+            serde_json::from_str(r#"{"ok":true,"messageId":"812"}"#).unwrap();
+        assert_eq!(resp.message_id.as_deref(), Some("812"));
+        let resp: WebhookResponse =
+            serde_json::from_str(r#"{"ok":true,"message_id":813}"#).unwrap();
+        assert_eq!(resp.message_id.as_deref(), Some("813"));
+        let resp: WebhookResponse = serde_json::from_str(r#"{"ok":true}"#).unwrap();
+        assert!(resp.message_id.is_none());
+    }
+
+    #[test]
+    fn test_failover_chain_parses_names_and_routes() {
+        let chain: FailoverChain = serde_json::from_value(serde_json::json!({
+            "channels": ["ntfy", {"channel": "email", "to": "me@example.com"}]
+        }))
+        .unwrap();
+        assert_eq!(chain.timeout_secs, 10);
+        assert_eq!(chain.channels[0].channel.as_deref(), Some("ntfy"));
+        assert!(chain.channels[0].to.is_none());
+        assert_eq!(chain.channels[1].to.as_deref(), Some("me@example.com"));
+
+        let chain: FailoverChain =
+            serde_json::from_value(serde_json::json!({"channels": [], "timeout_secs": 5})).unwrap();
+        assert_eq!(chain.timeout_secs, 5);
+        assert!(chain.channels.is_empty());
+    }
 }
@@ -0,0 +1,479 @@
+//! 团队共享看板：定期把本机的 agent 状态快照发布到共享位置
+//!
+//! 每个成员写一份 `<member>.json`，并维护 `index.json`（成员文件列表）和静态页面
+//! `index.html`，团队在同一个页面里看到所有人的 agent。
+//!
+//! config.json 示例：
+//! ```json
+//! {
+//!   "dashboard_export": {
+//!     "target": {"type": "git", "repo": "/Users/alice/team-dashboard"},
+//!     "member": "alice",
+//!     "interval_secs": 300
+//!   }
+//! }
+//! ```
+//!
+//! `target` 支持：
+//! - `{"type": "dir", "path": "/Volumes/shared/cam"}`：共享目录（NFS、网盘同步目录等）
+//! - `{"type": "git", "repo": "...", "push": true}`：写入 git 仓库并提交、推送
+//! - `{"type": "http", "url": "https://dav.example.com/cam", "headers": {...}}`：
+//!   HTTP PUT（WebDAV、接受 PUT 的 S3 兼容存储）
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{AgentManager, AgentRecord, AgentStatus, AgentType, WatcherDaemon};
+use crate::notification::summarizer::RiskLevel;
+use crate::session::{ConfirmationType, ConversationStateManager, PendingConfirmation};
+
+/// 快照中确认上下文的最大长度
+const CONTEXT_MAX_CHARS: usize = 200;
+
+/// HTTP 发布的超时
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 发布目标
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DashboardTarget {
+    /// 共享目录
+    Dir { path: PathBuf },
+    /// git 仓库（写入后提交，`push` 为 true 时推送）
+    Git {
+        repo: PathBuf,
+        #[serde(default = "default_push")]
+        push: bool,
+    },
+    /// HTTP PUT 到 `<url>/<文件名>`
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+fn default_push() -> bool {
+    true
+}
+
+fn default_interval_secs() -> u64 {
+    300
+}
+
+/// 看板发布配置（config.json 的 `dashboard_export`）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DashboardExport {
+    pub target: DashboardTarget,
+    /// 成员名（默认 `$USER@主机名`）
+    #[serde(default)]
+    pub member: Option<String>,
+    /// 两次发布的间隔（秒）
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl DashboardExport {
+    /// 读取配置，未配置时返回 None（不发布）
+    pub fn load() -> Option<Self> {
+        crate::infra::paths::config_section("dashboard_export")
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(10))
+    }
+
+    pub fn member(&self) -> String {
+        self.member.clone().unwrap_or_else(default_member)
+    }
+}
+
+fn default_member() -> String {
+    let user = std::env::var("USER").unwrap_or_else(|_| "cam".to_string());
+    match sysinfo::System::host_name() {
+        Some(host) => format!("{}@{}", user, host),
+        None => user,
+    }
+}
+
+/// 快照中的 agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardAgent {
+    pub agent_id: String,
+    pub agent_type: AgentType,
+    pub project_path: String,
+    pub status: AgentStatus,
+    pub started_at: String,
+    /// 最近一次状态变化时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_since: Option<DateTime<Utc>>,
+}
+
+impl From<&AgentRecord> for DashboardAgent {
+    fn from(record: &AgentRecord) -> Self {
+        Self {
+            agent_id: record.agent_id.clone(),
+            agent_type: record.agent_type.clone(),
+            project_path: record.project_path.clone(),
+            status: record.status.clone(),
+            started_at: record.started_at.clone(),
+            status_since: record.history.last().map(|t| t.at),
+        }
+    }
+}
+
+/// 快照中的待处理确认
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardConfirmation {
+    pub id: String,
+    pub agent_id: String,
+    /// 确认类型（如 `permission_request:Bash`）
+    pub kind: String,
+    pub context: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk_level: Option<RiskLevel>,
+}
+
+impl From<&PendingConfirmation> for DashboardConfirmation {
+    fn from(pending: &PendingConfirmation) -> Self {
+        let kind = match &pending.confirmation_type {
+            ConfirmationType::PermissionRequest { tool, .. } => {
+                format!("permission_request:{}", tool)
+            }
+            ConfirmationType::HookDecision { tool, .. } => format!("hook_decision:{}", tool),
+            ConfirmationType::TaskApproval { .. } => "task_approval".to_string(),
+            ConfirmationType::ShutdownRequest { .. } => "shutdown_request".to_string(),
+            ConfirmationType::OptionSelection { .. } => "option_selection".to_string(),
+        };
+        Self {
+            id: pending.id.clone(),
+            agent_id: pending.agent_id.clone(),
+            kind,
+            context: crate::infra::truncate_str(&pending.context, CONTEXT_MAX_CHARS),
+            created_at: pending.created_at,
+            risk_level: pending.risk_level,
+        }
+    }
+}
+
+/// 一个成员的完整监控状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardSnapshot {
+    pub member: String,
+    pub generated_at: DateTime<Utc>,
+    pub cam_version: String,
+    pub watcher_running: bool,
+    pub agents: Vec<DashboardAgent>,
+    pub pending_confirmations: Vec<DashboardConfirmation>,
+}
+
+impl DashboardSnapshot {
+    pub fn new(
+        member: String,
+        watcher_running: bool,
+        agents: &[AgentRecord],
+        pending: &[PendingConfirmation],
+    ) -> Self {
+        Self {
+            member,
+            generated_at: Utc::now(),
+            cam_version: env!("CARGO_PKG_VERSION").to_string(),
+            watcher_running,
+            agents: agents.iter().map(DashboardAgent::from).collect(),
+            pending_confirmations: pending.iter().map(DashboardConfirmation::from).collect(),
+        }
+    }
+
+    /// 读取本机当前状态
+    pub fn collect(member: String) -> Result<Self> {
+        let agents = AgentManager::new().list_agents()?;
+        let pending = ConversationStateManager::new().get_pending_confirmations()?;
+        Ok(Self::new(
+            member,
+            WatcherDaemon::new().is_running(),
+            &agents,
+            &pending,
+        ))
+    }
+
+    /// 快照文件名（成员名中的路径分隔符等字符替换为 `_`）
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .member
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '@' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}.json", name.trim_start_matches('.'))
+    }
+}
+
+/// 成员文件列表
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DashboardIndex {
+    members: BTreeSet<String>,
+}
+
+fn update_index(existing: Option<&str>, file_name: &str) -> Result<String> {
+    let mut index: DashboardIndex = existing
+        .and_then(|content| serde_json::from_str(content).ok())
+        .unwrap_or_default();
+    index.members.insert(file_name.to_string());
+    Ok(serde_json::to_string_pretty(&index)?)
+}
+
+/// 发布一份快照：写入成员文件、更新 index.json 与 index.html
+pub fn publish(target: &DashboardTarget, snapshot: &DashboardSnapshot) -> Result<()> {
+    let file_name = snapshot.file_name();
+    let content = serde_json::to_string_pretty(snapshot)?;
+    match target {
+        DashboardTarget::Dir { path } => write_dir(path, &file_name, &content),
+        DashboardTarget::Git { repo, push } => {
+            write_dir(repo, &file_name, &content)?;
+            commit_git(repo, &snapshot.member, *push)
+        }
+        DashboardTarget::Http { url, headers } => put_http(url, headers, &file_name, &content),
+    }
+}
+
+fn write_dir(dir: &Path, file_name: &str, content: &str) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("无法创建 {}", dir.display()))?;
+    fs::write(dir.join(file_name), content)?;
+    let index_path = dir.join("index.json");
+    let existing = fs::read_to_string(&index_path).ok();
+    fs::write(&index_path, update_index(existing.as_deref(), file_name)?)?;
+    fs::write(dir.join("index.html"), DASHBOARD_HTML)?;
+    Ok(())
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<std::process::Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to execute git")
+}
+
+fn commit_git(repo: &Path, member: &str, push: bool) -> Result<()> {
+    let add = git(repo, &["add", "--all", "."])?;
+    if !add.status.success() {
+        return Err(anyhow!(
+            "git add failed: {}",
+            String::from_utf8_lossy(&add.stderr).trim()
+        ));
+    }
+    // 没有变化时 diff --cached --quiet 返回 0
+    if git(repo, &["diff", "--cached", "--quiet"])?
+        .status
+        .success()
+    {
+        return Ok(());
+    }
+    let message = format!("cam: update {} snapshot", member);
+    let commit = git(repo, &["commit", "-q", "-m", &message])?;
+    if !commit.status.success() {
+        return Err(anyhow!(
+            "git commit failed: {}",
+            String::from_utf8_lossy(&commit.stderr).trim()
+        ));
+    }
+    if push {
+        // 其他成员可能已推送，先 rebase（各自只写自己的文件，index.json 冲突时下次重新生成）
+        let _ = git(repo, &["pull", "-q", "--rebase", "--autostash"]);
+        let output = git(repo, &["push", "-q"])?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git push failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn put_http(
+    base_url: &str,
+    headers: &BTreeMap<String, String>,
+    file_name: &str,
+    content: &str,
+) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()?;
+    let base_url = base_url.trim_end_matches('/');
+    let put = |name: &str, body: String, content_type: &str| -> Result<()> {
+        let mut request = client
+            .put(format!("{}/{}", base_url, name))
+            .header("Content-Type", content_type)
+            .body(body);
+        for (key, value) in headers {
+            request = request.header(key, value);
+        }
+        let response = request.send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!("PUT {} failed: HTTP {}", name, response.status()));
+        }
+        Ok(())
+    };
+
+    put(file_name, content.to_string(), "application/json")?;
+    let mut get = client.get(format!("{}/index.json", base_url));
+    for (key, value) in headers {
+        get = get.header(key, value);
+    }
+    let existing = get
+        .send()
+        .ok()
+        .filter(|r| r.status().is_success())
+        .and_then(|r| r.text().ok());
+    put(
+        "index.json",
+        update_index(existing.as_deref(), file_name)?,
+        "application/json",
+    )?;
+    put(
+        "index.html",
+        DASHBOARD_HTML.to_string(),
+        "text/html; charset=utf-8",
+    )
+}
+
+/// 静态看板页面：读取 index.json 列出的成员快照
+const DASHBOARD_HTML: &str = r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>CAM Team Dashboard</title>
+<style>
+body { font-family: -apple-system, sans-serif; margin: 2em; color: #222; }
+h2 { margin-bottom: 0.2em; }
+.meta { color: #888; font-size: 0.9em; }
+table { border-collapse: collapse; margin: 0.5em 0 1.5em; }
+td, th { border-bottom: 1px solid #eee; padding: 4px 12px; text-align: left; }
+.waiting_for_input { color: #d97706; font-weight: bold; }
+.stale { color: #dc2626; }
+</style>
+</head>
+<body>
+<h1>CAM Team Dashboard</h1>
+<div id="members"></div>
+<script>
+const esc = s => String(s ?? "").replace(/[&<>"]/g, c => ({"&": "&amp;", "<": "&lt;", ">": "&gt;", '"': "&quot;"}[c]));
+const age = ts => Math.round((Date.now() - new Date(ts)) / 60000);
+async function load() {
+  const index = await (await fetch("index.json", {cache: "no-store"})).json();
+  const html = [];
+  for (const file of index.members) {
+    const s = await (await fetch(file, {cache: "no-store"})).json().catch(() => null);
+    if (!s) continue;
+    const mins = age(s.generated_at);
+    html.push(`<h2>${esc(s.member)}</h2><div class="meta ${mins > 30 ? "stale" : ""}">更新于 ${mins} 分钟前 · watcher ${s.watcher_running ? "运行中" : "未运行"} · cam ${esc(s.cam_version)}</div>`);
+    html.push("<table><tr><th>Agent</th><th>类型</th><th>状态</th><th>项目</th></tr>");
+    for (const a of s.agents) {
+      html.push(`<tr><td>${esc(a.agent_id)}</td><td>${esc(a.agent_type)}</td><td class="${esc(a.status)}">${esc(a.status)}</td><td>${esc(a.project_path)}</td></tr>`);
+    }
+    html.push("</table>");
+    if (s.pending_confirmations.length) {
+      html.push("<table><tr><th>待确认</th><th>Agent</th><th>内容</th></tr>");
+      for (const p of s.pending_confirmations) {
+        html.push(`<tr><td>${esc(p.kind)}</td><td>${esc(p.agent_id)}</td><td>${esc(p.context)}</td></tr>`);
+      }
+      html.push("</table>");
+    }
+  }
+  document.getElementById("members").innerHTML = html.join("");
+}
+load();
+setInterval(load, 60000);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn agent(agent_id: &str) -> AgentRecord {
+        serde_json::from_value(serde_json::json!({
+            "agent_id": agent_id,
+            "agent_type": "claude",
+            "project_path": "/work/app",
+            "tmux_session": agent_id,
+            "started_at": "2026-01-01T00:00:00Z",
+            "status": "waiting_for_input",
+            "jsonl_offset": 42,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_load_config() {
+        let config: DashboardExport = serde_json::from_value(serde_json::json!({
+            "target": {"type": "git", "repo": "/srv/dashboard"},
+            "member": "alice",
+        }))
+        .unwrap();
+        assert_eq!(
+            config.target,
+            DashboardTarget::Git {
+                repo: PathBuf::from("/srv/dashboard"),
+                push: true
+            }
+        );
+        assert_eq!(config.interval_secs, 300);
+        assert_eq!(config.member(), "alice");
+    }
+
+    #[test]
+    fn test_snapshot_serialization_drops_internal_fields() {
+        let snapshot = DashboardSnapshot::new("bob@mac/1".into(), true, &[agent("cam-1")], &[]);
+        assert_eq!(snapshot.file_name(), "bob@mac_1.json");
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["agents"][0]["agent_id"], "cam-1");
+        assert_eq!(json["agents"][0]["status"], "waiting_for_input");
+        assert!(json["agents"][0].get("jsonl_offset").is_none());
+        assert!(json["agents"][0].get("tmux_session").is_none());
+    }
+
+    #[test]
+    fn test_publish_to_dir_keeps_other_members() {
+        let tmp = TempDir::new().unwrap();
+        let target = DashboardTarget::Dir {
+            path: tmp.path().to_path_buf(),
+        };
+        for member in ["alice", "bob", "alice"] {
+            let snapshot = DashboardSnapshot::new(member.into(), false, &[agent("cam-1")], &[]);
+            publish(&target, &snapshot).unwrap();
+        }
+
+        let index: DashboardIndex =
+            serde_json::from_str(&fs::read_to_string(tmp.path().join("index.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            index.members.into_iter().collect::<Vec<_>>(),
+            ["alice.json", "bob.json"]
+        );
+        let alice: DashboardSnapshot =
+            serde_json::from_str(&fs::read_to_string(tmp.path().join("alice.json")).unwrap())
+                .unwrap();
+        assert_eq!(alice.agents.len(), 1);
+        assert!(tmp.path().join("index.html").exists());
+    }
+}
@@ -25,7 +25,7 @@ impl LaunchdService {
     pub fn new() -> Result<Self> {
         let home = dirs::home_dir().context("Failed to get home directory")?;
         let plist_path = home.join("Library/LaunchAgents").join(Self::PLIST_NAME);
-        let log_dir = home.join(".config/code-agent-monitor/logs");
+        let log_dir = crate::infra::paths::state_dir().join("logs");
 
         Ok(Self {
             plist_path,
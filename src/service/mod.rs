@@ -1,5 +1,13 @@
 //! Service management for CAM watcher daemon
+//!
+//! 以及 daemon 中的后台发布任务（团队看板）和 `cam serve` 的签名 webhook 接收器
 
+mod dashboard;
 mod launchd;
+mod webhook_receiver;
 
+pub use dashboard::{publish, DashboardExport, DashboardSnapshot, DashboardTarget};
 pub use launchd::{LaunchdService, ServiceStatus};
+pub use webhook_receiver::{
+    handle_request, reply_result_json, run_webhook_receiver, WebhookReply, WebhookRequest,
+};
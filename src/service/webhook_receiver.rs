@@ -0,0 +1,275 @@
+//! 签名 webhook 接收器：`cam serve --webhook-port` 接收远程回复
+//!
+//! 只接受 `POST /reply`，请求体为 JSON：
+//! ```json
+//! {"reply": "y", "target": "cam-123", "from": "telegram:42"}
+//! ```
+//! `target`、`from` 可省略。请求必须带 `X-CAM-Timestamp`/`X-CAM-Nonce`/`X-CAM-Signature`，
+//! 用 config.json 的 `webhook.signing_secret` 验证（见 [`crate::notification::signing`]），
+//! 验证通过后按 `cam reply` 的规则处理（包括 `reply_auth` 授权）。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+use crate::notification::WebhookVerifier;
+use crate::session::{ConversationStateManager, ReplyResult, ReplySender};
+
+/// 请求体上限
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// `POST /reply` 的请求体
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WebhookReply {
+    pub reply: String,
+    #[serde(default)]
+    pub target: Option<String>,
+    /// 回复者身份（用于 `reply_auth`）
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// 一个已读取的 HTTP 请求
+#[derive(Debug, Clone, Default)]
+pub struct WebhookRequest {
+    pub method: String,
+    pub path: String,
+    /// header 名统一为小写
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl WebhookRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// 验证并处理请求，返回 (状态码, JSON 响应)
+pub fn handle_request(
+    verifier: &WebhookVerifier,
+    request: &WebhookRequest,
+    on_reply: impl FnOnce(WebhookReply) -> Result<Value>,
+) -> (u16, Value) {
+    if request.path != "/reply" {
+        return (404, json!({"ok": false, "error": "not found"}));
+    }
+    if request.method != "POST" {
+        return (405, json!({"ok": false, "error": "method not allowed"}));
+    }
+    if let Err(e) = verifier.verify_headers(|name| request.header(name), &request.body) {
+        return (401, json!({"ok": false, "error": e.to_string()}));
+    }
+    let reply: WebhookReply = match serde_json::from_slice(&request.body) {
+        Ok(reply) => reply,
+        Err(e) => return (400, json!({"ok": false, "error": e.to_string()})),
+    };
+    match on_reply(reply) {
+        Ok(result) => (200, result),
+        Err(e) => (500, json!({"ok": false, "error": e.to_string()})),
+    }
+}
+
+/// 回复结果转换为 JSON 响应
+pub fn reply_result_json(result: &ReplyResult) -> Value {
+    match result {
+        ReplyResult::Sent {
+            agent_id,
+            reply,
+            verification,
+        } => json!({
+            "ok": true,
+            "status": "sent",
+            "agent_id": agent_id,
+            "reply": reply,
+            "verification": verification,
+        }),
+        ReplyResult::NeedSelection { options } => json!({
+            "ok": false,
+            "status": "need_selection",
+            "options": options
+                .iter()
+                .map(|o| json!({"agent_id": o.agent_id, "context": o.context}))
+                .collect::<Vec<_>>(),
+        }),
+        ReplyResult::NoPending => json!({"ok": false, "status": "no_pending"}),
+        ReplyResult::InvalidSelection(error) => {
+            json!({"ok": false, "status": "invalid_selection", "error": error})
+        }
+        ReplyResult::Denied { agent_id, reason } => json!({
+            "ok": false,
+            "status": "denied",
+            "agent_id": agent_id,
+            "error": reason,
+        }),
+    }
+}
+
+/// 读取一个 HTTP/1.1 请求（只支持 Content-Length）
+async fn read_request(stream: &mut TcpStream) -> Result<WebhookRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("invalid request line");
+    };
+    let mut request = WebhookRequest {
+        method: method.to_string(),
+        path: path.split('?').next().unwrap_or(path).to_string(),
+        ..Default::default()
+    };
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            bail!("unexpected end of headers");
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = request
+        .header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err(anyhow!("body too large"));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).await?;
+    Ok(request)
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+async fn serve_connection(mut stream: TcpStream, verifier: Arc<WebhookVerifier>) -> Result<()> {
+    let request = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(e) => {
+            return write_response(
+                &mut stream,
+                400,
+                &json!({"ok": false, "error": e.to_string()}),
+            )
+            .await;
+        }
+    };
+    let (status, body) = tokio::task::spawn_blocking(move || {
+        handle_request(&verifier, &request, |reply| {
+            let result = ConversationStateManager::new().handle_reply_from(
+                &reply.reply,
+                reply.target.as_deref(),
+                ReplySender::Remote(reply.from.as_deref()),
+            )?;
+            Ok(reply_result_json(&result))
+        })
+    })
+    .await?;
+    if status != 200 {
+        warn!(status, "Rejected webhook request");
+    }
+    write_response(&mut stream, status, &body).await
+}
+
+/// 在 `port` 上运行接收器，直到出错
+pub async fn run_webhook_receiver(port: u16, verifier: WebhookVerifier) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    info!(port, "Webhook receiver listening");
+    let verifier = Arc::new(verifier);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let verifier = Arc::clone(&verifier);
+        tokio::spawn(async move {
+            if let Err(e) = serve_connection(stream, verifier).await {
+                warn!(error = %e, "Webhook connection failed");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::signing;
+
+    fn signed_request(secret: &str, body: &str) -> WebhookRequest {
+        let signed = signing::sign(secret, body.as_bytes());
+        WebhookRequest {
+            method: "POST".to_string(),
+            path: "/reply".to_string(),
+            headers: signed
+                .pairs()
+                .iter()
+                .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let verifier = WebhookVerifier::new("secret");
+        let echo =
+            |reply: WebhookReply| Ok(json!({"ok": true, "reply": reply.reply, "from": reply.from}));
+
+        let request = signed_request("secret", r#"{"reply": "y", "from": "telegram:42"}"#);
+        let (status, body) = handle_request(&verifier, &request, echo);
+        assert_eq!(status, 200);
+        assert_eq!(body["reply"], "y");
+        assert_eq!(body["from"], "telegram:42");
+
+        // 重放、错误密钥、未签名都被拒绝，且不调用处理函数
+        let reject = |_: WebhookReply| -> Result<Value> { panic!("should not be called") };
+        assert_eq!(handle_request(&verifier, &request, reject).0, 401);
+        let forged = signed_request("other", r#"{"reply": "y"}"#);
+        assert_eq!(handle_request(&verifier, &forged, reject).0, 401);
+        let unsigned = WebhookRequest {
+            headers: HashMap::new(),
+            ..signed_request("secret", r#"{"reply": "y"}"#)
+        };
+        assert_eq!(handle_request(&verifier, &unsigned, reject).0, 401);
+
+        let wrong_path = WebhookRequest {
+            path: "/other".to_string(),
+            ..signed_request("secret", "{}")
+        };
+        assert_eq!(handle_request(&verifier, &wrong_path, reject).0, 404);
+        let invalid = signed_request("secret", r#"{"text": "y"}"#);
+        assert_eq!(handle_request(&verifier, &invalid, reject).0, 400);
+    }
+}
@@ -0,0 +1,391 @@
+//! 会话归档 - 压缩旧的 JSONL 会话记录并从 Claude projects 目录移除
+//!
+//! 归档目录结构：`<archive>/<项目目录名>/<session_id>.jsonl.gz`，
+//! `<archive>/index.jsonl` 每行记录一个已归档会话（项目、摘要、时间、大小），便于之后检索。
+//! 压缩使用 flate2（gzip 格式）。在 config.json 中设置 `session_prune` 后 watcher daemon 会定期执行。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// 归档索引文件名
+pub const ARCHIVE_INDEX_FILE: &str = "index.jsonl";
+
+/// 最小保留时间（秒），避免误归档仍在写入的会话
+const MIN_AGE_SECS: u64 = 86400;
+
+/// 默认归档目录
+pub fn default_archive_dir() -> PathBuf {
+    crate::infra::paths::state_dir().join("session-archive")
+}
+
+/// 归档选项
+#[derive(Debug, Clone)]
+pub struct PruneOptions {
+    /// 只归档超过此时间未修改的会话（秒）
+    pub older_than_secs: u64,
+    /// 归档目录
+    pub archive_dir: PathBuf,
+    /// 只统计不修改
+    pub dry_run: bool,
+}
+
+/// 归档索引条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedSession {
+    pub session_id: String,
+    /// Claude projects 下的目录名
+    pub project_dir: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_prompt: Option<String>,
+    /// JSONL 最后修改时间
+    pub modified: DateTime<Utc>,
+    pub archived_at: DateTime<Utc>,
+    pub original_bytes: u64,
+    pub archived_bytes: u64,
+    /// 相对于归档目录的路径
+    pub archive_path: String,
+}
+
+/// 归档结果
+#[derive(Debug, Clone, Default)]
+pub struct PruneReport {
+    pub archived: Vec<ArchivedSession>,
+    /// 归档失败的会话及原因
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl PruneReport {
+    /// 从 projects 目录释放的空间
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.archived.iter().map(|s| s.original_bytes).sum()
+    }
+
+    /// 归档文件占用的空间
+    pub fn archived_bytes(&self) -> u64 {
+        self.archived.iter().map(|s| s.archived_bytes).sum()
+    }
+}
+
+/// sessions-index.json 中用于索引的字段
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    session_id: String,
+    project_path: Option<String>,
+    summary: Option<String>,
+    first_prompt: Option<String>,
+}
+
+/// 归档 `projects_dir` 下超过时间的会话
+pub fn prune_sessions(projects_dir: &Path, options: &PruneOptions) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    if !projects_dir.exists() {
+        return Ok(report);
+    }
+
+    let min_age = Duration::from_secs(options.older_than_secs.max(MIN_AGE_SECS));
+    let cutoff = SystemTime::now()
+        .checked_sub(min_age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    for project in fs::read_dir(projects_dir)? {
+        let project = project?.path();
+        if !project.is_dir() {
+            continue;
+        }
+        let project_dir = project
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut candidates = Vec::new();
+        for entry in fs::read_dir(&project)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            if modified < cutoff {
+                candidates.push((path, modified, metadata.len()));
+            }
+        }
+        if candidates.is_empty() {
+            continue;
+        }
+
+        let index_path = project.join("sessions-index.json");
+        let index_entries = read_index_entries(&index_path);
+        let mut pruned_ids = Vec::new();
+
+        for (path, modified, original_bytes) in candidates {
+            let session_id = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let archive_path = format!("{}/{}.jsonl.gz", project_dir, session_id);
+            let info = index_entries.get(&session_id);
+            let mut archived = ArchivedSession {
+                session_id: session_id.clone(),
+                project_dir: project_dir.clone(),
+                project_path: info.and_then(|e| e.project_path.clone()),
+                summary: info.and_then(|e| e.summary.clone()),
+                first_prompt: info.and_then(|e| e.first_prompt.clone()),
+                modified: modified.into(),
+                archived_at: Utc::now(),
+                original_bytes,
+                archived_bytes: 0,
+                archive_path,
+            };
+
+            if options.dry_run {
+                report.archived.push(archived);
+                continue;
+            }
+
+            match archive_file(&path, &options.archive_dir.join(&archived.archive_path)) {
+                Ok(archived_bytes) => {
+                    archived.archived_bytes = archived_bytes;
+                    append_archive_index(&options.archive_dir, &archived)?;
+                    fs::remove_file(&path)?;
+                    pruned_ids.push(session_id);
+                    report.archived.push(archived);
+                }
+                Err(e) => report.failed.push((path, e.to_string())),
+            }
+        }
+
+        if !pruned_ids.is_empty() {
+            if let Err(e) = remove_index_entries(&index_path, &pruned_ids) {
+                tracing::warn!(path = %index_path.display(), error = %e, "Failed to update sessions index");
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// 读取已归档会话索引
+pub fn read_archive_index(archive_dir: &Path) -> Result<Vec<ArchivedSession>> {
+    let path = archive_dir.join(ARCHIVE_INDEX_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(&path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// gzip 压缩到目标路径，返回压缩后大小
+fn archive_file(source: &Path, target: &Path) -> Result<u64> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = target.with_extension("gz.tmp");
+    let compress = || -> Result<()> {
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(&tmp)?), Compression::best());
+        std::io::copy(&mut File::open(source)?, &mut encoder)?;
+        encoder.finish()?.into_inner()?.sync_all()?;
+        Ok(())
+    };
+    if let Err(e) = compress() {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, target)?;
+    Ok(fs::metadata(target)?.len())
+}
+
+fn append_archive_index(archive_dir: &Path, session: &ArchivedSession) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(archive_dir.join(ARCHIVE_INDEX_FILE))?;
+    writeln!(file, "{}", serde_json::to_string(session)?)?;
+    Ok(())
+}
+
+fn read_index_entries(index_path: &Path) -> HashMap<String, IndexEntry> {
+    let Ok(content) = fs::read_to_string(index_path) else {
+        return HashMap::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return HashMap::new();
+    };
+    json.get("entries")
+        .and_then(|e| e.as_array())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| serde_json::from_value::<IndexEntry>(e.clone()).ok())
+                .map(|e| (e.session_id.clone(), e))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 从 sessions-index.json 移除已归档的条目（保留其它字段原样），先写临时文件再改名
+fn remove_index_entries(index_path: &Path, session_ids: &[String]) -> Result<()> {
+    if !index_path.exists() {
+        return Ok(());
+    }
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(index_path)?)?;
+    if let Some(entries) = json.get_mut("entries").and_then(|e| e.as_array_mut()) {
+        entries.retain(|entry| {
+            entry
+                .get("sessionId")
+                .and_then(|id| id.as_str())
+                .is_none_or(|id| !session_ids.iter().any(|s| s == id))
+        });
+    }
+    let tmp = index_path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(&json)?)?;
+    fs::rename(&tmp, index_path)?;
+    Ok(())
+}
+
+/// watcher daemon 定期归档配置（config.json 的 `session_prune`）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PruneSchedule {
+    /// 归档超过 N 天未修改的会话
+    #[serde(default = "default_older_than_days")]
+    pub older_than_days: u64,
+    /// 归档目录（默认 `~/.config/code-agent-monitor/session-archive`）
+    #[serde(default)]
+    pub archive_dir: Option<PathBuf>,
+    /// 两次归档的间隔（小时）
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+}
+
+fn default_older_than_days() -> u64 {
+    60
+}
+
+fn default_interval_hours() -> u64 {
+    24
+}
+
+impl PruneSchedule {
+    /// 读取配置，未配置时返回 None（不自动归档）
+    pub fn load() -> Option<Self> {
+        crate::infra::paths::config_section("session_prune")
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_hours.max(1) * 3600)
+    }
+
+    pub fn options(&self) -> PruneOptions {
+        PruneOptions {
+            older_than_secs: self.older_than_days * 86400,
+            archive_dir: self.archive_dir.clone().unwrap_or_else(default_archive_dir),
+            dry_run: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn set_old_mtime(path: &Path) {
+        let old = SystemTime::now() - Duration::from_secs(90 * 86400);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_prune_archives_old_sessions() {
+        let dir = TempDir::new().unwrap();
+        let projects = dir.path().join("projects");
+        let project = projects.join("-work-app");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("old.jsonl"),
+            "{\"type\":\"user\"}\n".repeat(100),
+        )
+        .unwrap();
+        fs::write(project.join("new.jsonl"), "{}\n").unwrap();
+        set_old_mtime(&project.join("old.jsonl"));
+        fs::write(
+            project.join("sessions-index.json"),
+            serde_json::json!({
+                "version": 1,
+                "entries": [
+                    {"sessionId": "old", "projectPath": "/work/app", "summary": "Fix login"},
+                    {"sessionId": "new", "projectPath": "/work/app"}
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let archive = dir.path().join("archive");
+        let mut options = PruneOptions {
+            older_than_secs: 60 * 86400,
+            archive_dir: archive.clone(),
+            dry_run: true,
+        };
+        let report = prune_sessions(&projects, &options).unwrap();
+        assert_eq!(report.archived.len(), 1);
+        assert!(project.join("old.jsonl").exists());
+
+        options.dry_run = false;
+        let report = prune_sessions(&projects, &options).unwrap();
+        assert_eq!(report.archived.len(), 1);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.reclaimed_bytes(), 1600);
+        assert!(report.archived_bytes() < report.reclaimed_bytes());
+        assert!(!project.join("old.jsonl").exists());
+        assert!(project.join("new.jsonl").exists());
+        let mut restored = String::new();
+        flate2::read::GzDecoder::new(File::open(archive.join("-work-app/old.jsonl.gz")).unwrap())
+            .read_to_string(&mut restored)
+            .unwrap();
+        assert_eq!(restored, "{\"type\":\"user\"}\n".repeat(100));
+
+        let index = read_archive_index(&archive).unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index[0].summary.as_deref(), Some("Fix login"));
+        assert_eq!(index[0].archive_path, "-work-app/old.jsonl.gz");
+
+        let sessions: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(project.join("sessions-index.json")).unwrap())
+                .unwrap();
+        assert_eq!(sessions["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(sessions["entries"][0]["sessionId"], "new");
+        assert!(!project.join("sessions-index.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_schedule_defaults() {
+        let schedule: PruneSchedule = serde_json::from_str("{}").unwrap();
+        assert_eq!(schedule.older_than_days, 60);
+        assert_eq!(schedule.interval(), Duration::from_secs(86400));
+        assert_eq!(schedule.options().older_than_secs, 60 * 86400);
+    }
+}
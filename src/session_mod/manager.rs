@@ -5,9 +5,14 @@ use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use crate::infra::tmux::TmuxManager;
+use crate::infra::multiplexer::{self, Multiplexer};
+
+/// JSONL 在此时间内有写入的会话视为活跃（秒）
+const ACTIVE_WINDOW_SECS: u64 = 600;
 
 /// 会话信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +80,38 @@ struct JsonlUserMessage {
     content: Option<String>,
 }
 
+/// 会话状态过滤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    /// JSONL 最近仍在写入
+    Active,
+    /// 已结束（长时间无写入）
+    Completed,
+}
+
+impl SessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionStatus::Active => "active",
+            SessionStatus::Completed => "completed",
+        }
+    }
+}
+
+/// 会话排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionSort {
+    /// 最近修改在前
+    #[default]
+    Recent,
+    /// 最早修改在前
+    Oldest,
+    /// 消息数多的在前
+    Messages,
+}
+
 /// 会话过滤选项
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SessionFilter {
@@ -84,15 +121,35 @@ pub struct SessionFilter {
     /// 只返回最近 N 天的会话
     #[serde(skip_serializing_if = "Option::is_none")]
     pub days: Option<i64>,
+    /// 只返回最近 N 秒内修改的会话（与 `days` 同时设置时取较近的时间）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since_secs: Option<i64>,
+    /// 按状态过滤
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<SessionStatus>,
+    /// 排序方式
+    #[serde(default)]
+    pub sort: SessionSort,
     /// 限制返回数量
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<usize>,
 }
 
+impl SessionFilter {
+    /// 修改时间下限
+    fn cutoff(&self) -> Option<DateTime<Utc>> {
+        let days = self.days.map(|days| Utc::now() - Duration::days(days));
+        let since = self
+            .since_secs
+            .map(|secs| Utc::now() - Duration::seconds(secs));
+        days.into_iter().chain(since).max()
+    }
+}
+
 /// 会话管理器
 pub struct SessionManager {
     claude_projects_dir: PathBuf,
-    tmux_manager: TmuxManager,
+    tmux_manager: Arc<dyn Multiplexer>,
 }
 
 impl SessionManager {
@@ -100,18 +157,32 @@ impl SessionManager {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
         let claude_projects_dir = home.join(".claude").join("projects");
 
+        Self::with_projects_dir(claude_projects_dir)
+    }
+
+    /// 使用指定的 Claude projects 目录（测试用）
+    pub fn with_projects_dir(claude_projects_dir: impl Into<PathBuf>) -> Self {
         Self {
-            claude_projects_dir,
-            tmux_manager: TmuxManager::new(),
+            claude_projects_dir: claude_projects_dir.into(),
+            tmux_manager: multiplexer::from_config(),
         }
     }
 
+    /// Claude projects 目录
+    pub fn projects_dir(&self) -> &Path {
+        &self.claude_projects_dir
+    }
+
     /// 列出所有 Claude Code 会话
     pub fn list_sessions(&self) -> Result<Vec<SessionInfo>> {
         self.list_sessions_filtered(None)
     }
 
     /// 列出 Claude Code 会话（带过滤）
+    ///
+    /// 会话很多时先用目录名和索引文件 mtime 剪枝，避免读取无关项目的索引：
+    /// 项目目录名是路径编码（非字母数字替换为 `-`），索引 mtime 早于时间下限时
+    /// 其中所有条目的修改时间也都更早。
     pub fn list_sessions_filtered(
         &self,
         filter: Option<SessionFilter>,
@@ -122,32 +193,62 @@ impl SessionManager {
             return Ok(sessions);
         }
 
+        let cutoff = filter.as_ref().and_then(SessionFilter::cutoff);
+        let encoded_project = filter
+            .as_ref()
+            .and_then(|f| f.project_path.as_deref())
+            // 非 ASCII 字符的编码方式不确定，不剪枝
+            .filter(|p| p.is_ascii())
+            .map(encode_project_dir);
+
         // 遍历所有项目目录
         for entry in fs::read_dir(&self.claude_projects_dir)? {
             let entry = entry?;
             let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(ref encoded) = encoded_project {
+                if !entry
+                    .file_name()
+                    .to_string_lossy()
+                    .contains(encoded.as_str())
+                {
+                    continue;
+                }
+            }
 
-            if path.is_dir() {
-                let index_path = path.join("sessions-index.json");
-                if index_path.exists() {
-                    if let Ok(content) = fs::read_to_string(&index_path) {
-                        if let Ok(index) = serde_json::from_str::<ClaudeSessionIndex>(&content) {
-                            for entry in index.entries {
-                                sessions.push(SessionInfo {
-                                    id: entry.session_id,
-                                    project_path: entry.project_path.unwrap_or_default(),
-                                    summary: entry.summary,
-                                    git_branch: entry.git_branch,
-                                    message_count: entry.message_count.unwrap_or(0),
-                                    created: entry.created.unwrap_or_default(),
-                                    modified: entry.modified.unwrap_or_default(),
-                                    status: "inactive".to_string(),
-                                });
-                            }
-                        }
-                    }
+            let index_path = path.join("sessions-index.json");
+            if let Some(cutoff) = cutoff {
+                let index_modified = fs::metadata(&index_path).and_then(|m| m.modified());
+                match index_modified {
+                    Ok(modified) if DateTime::<Utc>::from(modified) >= cutoff => {}
+                    _ => continue,
                 }
             }
+
+            let Ok(content) = fs::read_to_string(&index_path) else {
+                continue;
+            };
+            let Ok(index) = serde_json::from_str::<ClaudeSessionIndex>(&content) else {
+                continue;
+            };
+            for entry in index.entries {
+                let jsonl_path = entry
+                    .full_path
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| path.join(format!("{}.jsonl", entry.session_id)));
+                sessions.push(SessionInfo {
+                    status: session_status(&jsonl_path).as_str().to_string(),
+                    id: entry.session_id,
+                    project_path: entry.project_path.unwrap_or_default(),
+                    summary: entry.summary,
+                    git_branch: entry.git_branch,
+                    message_count: entry.message_count.unwrap_or(0),
+                    created: entry.created.unwrap_or_default(),
+                    modified: entry.modified.unwrap_or_default(),
+                });
+            }
         }
 
         // 应用过滤
@@ -158,8 +259,7 @@ impl SessionManager {
             }
 
             // 按时间过滤
-            if let Some(days) = filter.days {
-                let cutoff = Utc::now() - Duration::days(days);
+            if let Some(cutoff) = cutoff {
                 sessions.retain(|s| {
                     if let Ok(modified) = DateTime::parse_from_rfc3339(&s.modified) {
                         modified.with_timezone(&Utc) > cutoff
@@ -169,8 +269,18 @@ impl SessionManager {
                 });
             }
 
-            // 按修改时间排序（最新的在前）
-            sessions.sort_by(|a, b| b.modified.cmp(&a.modified));
+            // 按状态过滤
+            if let Some(status) = filter.status {
+                sessions.retain(|s| s.status == status.as_str());
+            }
+
+            match filter.sort {
+                SessionSort::Recent => sessions.sort_by(|a, b| b.modified.cmp(&a.modified)),
+                SessionSort::Oldest => sessions.sort_by(|a, b| a.modified.cmp(&b.modified)),
+                SessionSort::Messages => {
+                    sessions.sort_by_key(|s| std::cmp::Reverse(s.message_count))
+                }
+            }
 
             // 限制数量
             if let Some(limit) = filter.limit {
@@ -282,7 +392,7 @@ impl SessionManager {
     }
 
     /// 查找会话 JSONL 文件
-    fn find_session_file(&self, session_id: &str) -> Result<Option<PathBuf>> {
+    pub fn find_session_file(&self, session_id: &str) -> Result<Option<PathBuf>> {
         if !self.claude_projects_dir.exists() {
             return Ok(None);
         }
@@ -377,6 +487,27 @@ impl SessionManager {
     }
 }
 
+/// Claude 项目目录名编码：非字母数字字符替换为 `-`
+fn encode_project_dir(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// 按 JSONL 最近写入时间判断会话状态
+fn session_status(jsonl_path: &Path) -> SessionStatus {
+    let recently_written = fs::metadata(jsonl_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age.as_secs() < ACTIVE_WINDOW_SECS);
+    if recently_written {
+        SessionStatus::Active
+    } else {
+        SessionStatus::Completed
+    }
+}
+
 impl Default for SessionManager {
     fn default() -> Self {
         Self::new()
@@ -1,10 +1,20 @@
 //! 会话管理 - Claude Code 会话和对话状态
 
+pub mod archive;
 pub mod manager;
+pub mod reconcile;
+pub mod reply_auth;
+pub mod reply_macro;
 pub mod state;
+pub mod verify;
 
-pub use manager::{SessionFilter, SessionManager};
+pub use archive::{ArchivedSession, PruneOptions, PruneReport, PruneSchedule};
+pub use manager::{SessionFilter, SessionManager, SessionSort, SessionStatus};
+pub use reconcile::PendingResolution;
+pub use reply_auth::{ReplyAuth, ReplyAuthConfig, ReplyDenial, ReplySender};
+pub use reply_macro::{MacroStep, ReplyMacro, ReplyMacros};
 pub use state::{
-    AgentContext, BatchFilter, BatchReplyResult, ConfirmationType, ConversationState,
+    AckResult, AgentContext, BatchFilter, BatchReplyResult, ConfirmationType, ConversationState,
     ConversationStateManager, PendingConfirmation, ReplyResult,
 };
+pub use verify::ReplyVerification;
@@ -0,0 +1,189 @@
+//! 待处理确认对账 - 清理已在终端中直接处理的确认
+//!
+//! Watcher 每轮轮询后对照 agent 当前屏幕：问题已不在屏幕底部（用户直接在终端作答，
+//! 或 agent 已继续执行）时标记为已解决；agent 已退出时标记为过期。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::session::{ConfirmationType, PendingConfirmation};
+
+/// 新注册的确认在此时间内不参与对账（秒），避免屏幕尚未刷新时误判
+pub const RECONCILE_GRACE_SECS: i64 = 10;
+
+/// 只在屏幕最后这些非空行中查找问题
+const SCREEN_TAIL_LINES: usize = 15;
+
+/// 问题行的最小长度（字符数），过短的行无法可靠匹配
+const MIN_SIGNIFICANT_CHARS: usize = 4;
+
+/// 确认的解决方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingResolution {
+    /// 问题已离开屏幕（在终端中作答或 agent 已继续）
+    ResolvedInTerminal,
+    /// agent 已退出
+    AgentExited,
+}
+
+/// agent 的当前屏幕状态
+#[derive(Debug, Clone)]
+pub enum AgentScreen {
+    /// session 存活，附带当前屏幕内容
+    Live(String),
+    /// session 已退出
+    Exited,
+}
+
+fn normalize(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 判断确认的问题是否仍在屏幕底部
+///
+/// 无法判断时（上下文中没有足够长的行）返回 None。
+pub fn prompt_present(context: &str, screen: &str) -> Option<bool> {
+    let significant: Vec<String> = context
+        .lines()
+        .map(normalize)
+        .filter(|l| l.chars().count() >= MIN_SIGNIFICANT_CHARS)
+        .collect();
+    if significant.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = screen
+        .lines()
+        .map(normalize)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let tail = &lines[lines.len().saturating_sub(SCREEN_TAIL_LINES)..];
+
+    Some(
+        significant
+            .iter()
+            .any(|wanted| tail.iter().any(|line| line.contains(wanted.as_str()))),
+    )
+}
+
+/// 找出需要清理的确认
+///
+/// 只处理 `screens` 中有记录的 agent（team 成员等非 tmux 确认不受影响）。
+pub fn find_resolved(
+    pending: &[PendingConfirmation],
+    screens: &HashMap<String, AgentScreen>,
+    now: DateTime<Utc>,
+) -> Vec<(PendingConfirmation, PendingResolution)> {
+    pending
+        .iter()
+        .filter_map(|confirmation| {
+            // 由 hook 进程自己等待和清理，与屏幕无关
+            if let ConfirmationType::HookDecision { .. } = confirmation.confirmation_type {
+                return None;
+            }
+            let resolution = match screens.get(&confirmation.agent_id)? {
+                AgentScreen::Exited => PendingResolution::AgentExited,
+                AgentScreen::Live(screen) => {
+                    let age = now.signed_duration_since(confirmation.created_at);
+                    if age.num_seconds() < RECONCILE_GRACE_SECS {
+                        return None;
+                    }
+                    match prompt_present(&confirmation.context, screen) {
+                        Some(false) => PendingResolution::ResolvedInTerminal,
+                        _ => return None,
+                    }
+                }
+            };
+            Some((confirmation.clone(), resolution))
+        })
+        .collect()
+}
+
+/// 是否在确认于终端中被处理时发送通知
+///
+/// 读取 `~/.config/code-agent-monitor/config.json` 的 `notify_resolved_in_terminal`（默认关闭）。
+pub fn notify_resolved_enabled() -> bool {
+    crate::infra::paths::config_section::<bool>("notify_resolved_in_terminal").unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn confirmation(agent_id: &str, context: &str, age_secs: i64) -> PendingConfirmation {
+        PendingConfirmation {
+            id: format!("conf-{}", agent_id),
+            agent_id: agent_id.to_string(),
+            team: None,
+            confirmation_type: ConfirmationType::TaskApproval {
+                task_id: "1".to_string(),
+            },
+            context: context.to_string(),
+            created_at: Utc::now() - chrono::Duration::seconds(age_secs),
+            tmux_session: None,
+            risk_level: None,
+            message_type: None,
+            recent_messages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_prompt_present() {
+        let context = "Do you want to create hello.txt?\n❯ 1. Yes";
+        let waiting =
+            "⏺ Write(hello.txt)\n\nDo you   want to create hello.txt?\n❯ 1. Yes\n  2. No\n";
+        assert_eq!(prompt_present(context, waiting), Some(true));
+
+        let resumed = "⏺ Write(hello.txt)\n  ⎿  Wrote 1 line\n\n❯ ";
+        assert_eq!(prompt_present(context, resumed), Some(false));
+
+        // 问题已滚出屏幕底部区域
+        let scrolled = format!("{}{}", waiting, "output line\n".repeat(20));
+        assert_eq!(prompt_present(context, &scrolled), Some(false));
+
+        assert_eq!(prompt_present("y?", waiting), None);
+    }
+
+    #[test]
+    fn test_find_resolved() {
+        let pending = vec![
+            confirmation("cam-waiting", "Allow this command? [Y/n]", 60),
+            confirmation("cam-answered", "Allow this command? [Y/n]", 60),
+            confirmation("cam-new", "Allow this command? [Y/n]", 1),
+            confirmation("cam-exited", "Allow this command? [Y/n]", 1),
+            confirmation("member@team", "Approve plan?", 60),
+        ];
+        let screens = HashMap::from([
+            (
+                "cam-waiting".to_string(),
+                AgentScreen::Live("Allow this command? [Y/n]".to_string()),
+            ),
+            (
+                "cam-answered".to_string(),
+                AgentScreen::Live(format!(
+                    "Allow this command? [Y/n] y\n{}",
+                    "Done.\n".repeat(20)
+                )),
+            ),
+            ("cam-new".to_string(), AgentScreen::Live(String::new())),
+            ("cam-exited".to_string(), AgentScreen::Exited),
+        ]);
+
+        let resolved: Vec<_> = find_resolved(&pending, &screens, Utc::now())
+            .into_iter()
+            .map(|(c, r)| (c.agent_id, r))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                (
+                    "cam-answered".to_string(),
+                    PendingResolution::ResolvedInTerminal
+                ),
+                ("cam-exited".to_string(), PendingResolution::AgentExited),
+            ]
+        );
+    }
+}
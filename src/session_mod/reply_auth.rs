@@ -0,0 +1,342 @@
+//! 回复授权 - 共享渠道中按风险等级限制谁可以回复确认
+//!
+//! config.json 示例：
+//! ```json
+//! {
+//!   "reply_auth": {
+//!     "high": ["tg:123456"],
+//!     "medium": ["tg:123456", "tg:654321"],
+//!     "require_sender": true
+//!   }
+//! }
+//! ```
+//!
+//! - 某个等级配置了列表时，只有列表中的回复者（`cam reply --from <id>`）可以回复该等级的确认；
+//!   未配置的等级不限制。没有风险等级的确认按 MEDIUM 处理。
+//! - `cam reply` 是聊天渠道（OpenClaw skill）回复的主要入口，无法区分本机终端，未指定 `--from`
+//!   时与 MCP、webhook 等远程入口一样视为未标识回复者：配置了列表的等级直接拒绝，与
+//!   `require_sender` 无关。
+//! - 只有进程内的本机界面（TUI）视为可信；`require_sender` 为 true 时也会被拒绝。
+//! - 聊天命令中停止 agent、关闭 team、静音提醒或查看终端输出的操作（kill、shutdown、snooze、
+//!   ack、logs 等）按 `high` 列表授权。
+//! - 被拒绝的回复写入 `~/.config/code-agent-monitor/reply_denials.jsonl` 并发送 HIGH 通知。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::notification::summarizer::RiskLevel;
+use crate::notification::{load_webhook_config_from_file, NotificationEvent, OpenclawNotifier};
+use crate::session::PendingConfirmation;
+
+/// 回复者
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplySender<'a> {
+    /// 进程内的本机界面（TUI）
+    Local,
+    /// 聊天渠道、MCP、webhook 等远程入口，None 表示未标识回复者
+    Remote(Option<&'a str>),
+}
+
+impl<'a> ReplySender<'a> {
+    /// `cam reply --from`：未指定时为未标识的回复者（聊天渠道经 skill 调用 `cam reply`）
+    pub fn from_cli(from: Option<&'a str>) -> Self {
+        Self::Remote(from)
+    }
+
+    /// 回复者标识
+    pub fn id(&self) -> Option<&'a str> {
+        match self {
+            Self::Local => None,
+            Self::Remote(id) => *id,
+        }
+    }
+}
+
+/// 回复授权配置（config.json 的 `reply_auth`）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ReplyAuthConfig {
+    pub high: Option<Vec<String>>,
+    pub medium: Option<Vec<String>>,
+    pub low: Option<Vec<String>>,
+    /// 拒绝未标识回复者的回复（仅对配置了列表的等级生效）
+    pub require_sender: bool,
+}
+
+impl ReplyAuthConfig {
+    /// 读取 config.json，未配置时返回 None（不限制）
+    pub fn load() -> Option<Self> {
+        crate::infra::paths::config_section("reply_auth")
+    }
+
+    fn allowlist(&self, risk: RiskLevel) -> Option<&[String]> {
+        match risk {
+            RiskLevel::High => self.high.as_deref(),
+            RiskLevel::Medium => self.medium.as_deref(),
+            RiskLevel::Low => self.low.as_deref(),
+        }
+    }
+
+    /// 检查回复者能否回复该确认，拒绝时返回原因
+    pub fn authorize(
+        &self,
+        confirmation: &PendingConfirmation,
+        sender: ReplySender<'_>,
+    ) -> Result<(), String> {
+        let risk = confirmation.risk_level.unwrap_or(RiskLevel::Medium);
+        let Some(allowed) = self.allowlist(risk) else {
+            return Ok(());
+        };
+        match sender {
+            ReplySender::Remote(Some(sender)) if allowed.iter().any(|id| id == sender) => Ok(()),
+            ReplySender::Remote(Some(sender)) => {
+                Err(format!("{} 无权回复{}的确认", sender, risk.description()))
+            }
+            ReplySender::Remote(None) => Err(format!(
+                "{}的确认需要标识回复者，远程回复未提供 sender",
+                risk.description()
+            )),
+            ReplySender::Local if self.require_sender => Err(format!(
+                "{}的确认需要标识回复者（--from）",
+                risk.description()
+            )),
+            ReplySender::Local => Ok(()),
+        }
+    }
+
+    /// 检查回复者能否执行停止 agent、静音提醒等聊天命令（按 HIGH 列表），拒绝时返回原因
+    pub fn authorize_command(&self, command: &str, sender: ReplySender<'_>) -> Result<(), String> {
+        let Some(allowed) = self.high.as_deref() else {
+            return Ok(());
+        };
+        match sender {
+            ReplySender::Remote(Some(sender)) if allowed.iter().any(|id| id == sender) => Ok(()),
+            ReplySender::Remote(Some(sender)) => Err(format!("{} 无权执行 {}", sender, command)),
+            ReplySender::Remote(None) => {
+                Err(format!("{} 需要标识回复者，远程命令未提供 sender", command))
+            }
+            ReplySender::Local if self.require_sender => {
+                Err(format!("{} 需要标识回复者（--from）", command))
+            }
+            ReplySender::Local => Ok(()),
+        }
+    }
+}
+
+/// 一次被拒绝的回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyDenial {
+    pub ts: DateTime<Utc>,
+    pub sender: Option<String>,
+    pub agent_id: String,
+    pub confirmation_id: String,
+    pub risk_level: Option<RiskLevel>,
+    pub reply: String,
+    pub reason: String,
+}
+
+/// 回复授权：配置 + 拒绝记录的去向
+#[derive(Debug, Clone)]
+pub struct ReplyAuth {
+    pub config: ReplyAuthConfig,
+    /// 拒绝记录（JSON Lines）
+    pub denial_log: PathBuf,
+    /// 拒绝时是否发送通知
+    pub notify: bool,
+}
+
+impl ReplyAuth {
+    /// 按 config.json 创建，未配置时返回 None
+    pub fn load() -> Option<Self> {
+        let config = ReplyAuthConfig::load()?;
+        Some(Self {
+            config,
+            denial_log: crate::infra::paths::state_dir().join("reply_denials.jsonl"),
+            notify: true,
+        })
+    }
+
+    /// 检查回复，拒绝时记录并通知，返回拒绝原因
+    pub fn check(
+        &self,
+        confirmation: &PendingConfirmation,
+        sender: ReplySender<'_>,
+        reply: &str,
+    ) -> Option<String> {
+        let reason = self.config.authorize(confirmation, sender).err()?;
+        let denial = ReplyDenial {
+            ts: Utc::now(),
+            sender: sender.id().map(|s| s.to_string()),
+            agent_id: confirmation.agent_id.clone(),
+            confirmation_id: confirmation.id.clone(),
+            risk_level: confirmation.risk_level,
+            reply: reply.to_string(),
+            reason: reason.clone(),
+        };
+        warn!(
+            agent_id = %denial.agent_id,
+            sender = ?denial.sender,
+            reason = %denial.reason,
+            "Reply denied"
+        );
+        if let Err(e) = self.record(&denial) {
+            warn!(error = %e, "Failed to record reply denial");
+        }
+        if self.notify {
+            notify_denial(&denial);
+        }
+        Some(reason)
+    }
+
+    fn record(&self, denial: &ReplyDenial) -> Result<()> {
+        if let Some(parent) = self.denial_log.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.denial_log)?;
+        writeln!(file, "{}", serde_json::to_string(denial)?)?;
+        Ok(())
+    }
+}
+
+fn notify_denial(denial: &ReplyDenial) {
+    let notifier = match load_webhook_config_from_file() {
+        Some(config) => {
+            OpenclawNotifier::with_webhook(config).unwrap_or_else(|_| OpenclawNotifier::new())
+        }
+        None => OpenclawNotifier::new(),
+    };
+    let message = format!(
+        "已拒绝回复 '{}'：{}（确认仍待处理）",
+        denial.reply, denial.reason
+    );
+    let event = NotificationEvent::notification(&denial.agent_id, "reply_denied", message)
+        .with_skip_dedup(true);
+    if let Err(e) = notifier.send_notification_event(&event) {
+        warn!(error = %e, "Failed to send reply denial notification");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::ConfirmationType;
+    use tempfile::TempDir;
+
+    fn confirmation(risk: Option<RiskLevel>) -> PendingConfirmation {
+        PendingConfirmation {
+            id: "c-1".to_string(),
+            agent_id: "cam-1".to_string(),
+            team: None,
+            confirmation_type: ConfirmationType::TaskApproval {
+                task_id: "1".to_string(),
+            },
+            context: "rm -rf build".to_string(),
+            created_at: Utc::now(),
+            tmux_session: None,
+            risk_level: risk,
+            message_type: None,
+            recent_messages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_authorize_by_risk_level() {
+        let config: ReplyAuthConfig = serde_json::from_value(serde_json::json!({
+            "high": ["tg:1"],
+            "medium": ["tg:1", "tg:2"],
+        }))
+        .unwrap();
+
+        let remote = |id| ReplySender::Remote(Some(id));
+        let high = confirmation(Some(RiskLevel::High));
+        assert!(config.authorize(&high, remote("tg:1")).is_ok());
+        assert!(config.authorize(&high, remote("tg:2")).is_err());
+        // 本机界面回复默认可信，未标识的远程回复不可信
+        assert!(config.authorize(&high, ReplySender::Local).is_ok());
+        assert!(config.authorize(&high, ReplySender::Remote(None)).is_err());
+        // `cam reply` 未带 --from 不可信，与 require_sender 无关
+        assert!(!config.require_sender);
+        assert!(config
+            .authorize(&high, ReplySender::from_cli(None))
+            .is_err());
+        assert!(config
+            .authorize(&high, ReplySender::from_cli(Some("tg:1")))
+            .is_ok());
+
+        // 未标注风险按 MEDIUM 处理
+        let unknown = confirmation(None);
+        assert!(config.authorize(&unknown, remote("tg:2")).is_ok());
+        assert!(config.authorize(&unknown, remote("tg:3")).is_err());
+
+        // LOW 未配置列表，不限制
+        let low = confirmation(Some(RiskLevel::Low));
+        assert!(config.authorize(&low, remote("tg:3")).is_ok());
+        assert!(config.authorize(&low, ReplySender::Remote(None)).is_ok());
+
+        let strict = ReplyAuthConfig {
+            require_sender: true,
+            ..config
+        };
+        assert!(strict.authorize(&high, ReplySender::Local).is_err());
+    }
+
+    #[test]
+    fn test_authorize_command() {
+        let config = ReplyAuthConfig {
+            high: Some(vec!["tg:1".to_string()]),
+            medium: Some(vec!["tg:1".to_string(), "tg:2".to_string()]),
+            ..Default::default()
+        };
+        assert!(config
+            .authorize_command("kill", ReplySender::Remote(Some("tg:1")))
+            .is_ok());
+        // MEDIUM 列表中的用户也不能执行
+        assert!(config
+            .authorize_command("kill", ReplySender::Remote(Some("tg:2")))
+            .is_err());
+        assert!(config
+            .authorize_command("snooze", ReplySender::Remote(None))
+            .is_err());
+        assert!(config.authorize_command("kill", ReplySender::Local).is_ok());
+        // 未配置 HIGH 列表时不限制
+        assert!(ReplyAuthConfig::default()
+            .authorize_command("kill", ReplySender::Remote(None))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_records_denial() {
+        let tmp = TempDir::new().unwrap();
+        let auth = ReplyAuth {
+            config: ReplyAuthConfig {
+                high: Some(vec!["tg:1".to_string()]),
+                ..Default::default()
+            },
+            denial_log: tmp.path().join("reply_denials.jsonl"),
+            notify: false,
+        };
+        let high = confirmation(Some(RiskLevel::High));
+        assert!(auth
+            .check(&high, ReplySender::Remote(Some("tg:1")), "y")
+            .is_none());
+        assert!(auth
+            .check(&high, ReplySender::Remote(Some("tg:9")), "y")
+            .is_some());
+
+        let log = fs::read_to_string(&auth.denial_log).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let denial: ReplyDenial = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(denial.sender.as_deref(), Some("tg:9"));
+        assert_eq!(denial.confirmation_id, "c-1");
+    }
+}
@@ -0,0 +1,203 @@
+//! 回复宏 - 用户自定义的多步回复（`cam reply @approve-tests`）
+//!
+//! config.json 中配置：
+//!
+//! ```json
+//! "reply_macros": {
+//!   "macros": {"approve-tests": ["y", {"wait": 2}, "run the test suite"]},
+//!   "projects": {
+//!     "~/work/api": {"approve-tests": ["y", {"wait": 2}, "cargo test --workspace"]}
+//!   }
+//! }
+//! ```
+//!
+//! 字符串步骤发送一条回复，`{"wait": 秒}` 等待。`projects` 按 agent 的项目目录覆盖同名宏
+//! （最长路径优先）。第一条回复按普通回复处理（按键转换、生效验证），之后的回复作为新消息输入 agent。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// 回复宏的一个步骤
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum MacroStep {
+    /// 发送一条回复
+    Send(String),
+    /// 等待（秒）
+    Wait { wait: f64 },
+}
+
+impl MacroStep {
+    fn describe(&self) -> String {
+        match self {
+            MacroStep::Send(text) => text.clone(),
+            MacroStep::Wait { wait } => format!("等待 {}s", wait),
+        }
+    }
+}
+
+/// 展开后的回复宏
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplyMacro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+impl ReplyMacro {
+    /// 步骤描述，如 "@approve-tests (y → 等待 2s → run the test suite)"
+    pub fn describe(&self) -> String {
+        let steps: Vec<String> = self.steps.iter().map(MacroStep::describe).collect();
+        format!("@{} ({})", self.name, steps.join(" → "))
+    }
+
+    /// 等待步骤的时长（负数按 0 处理）
+    pub fn wait_duration(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+}
+
+/// 回复宏配置（config.json 的 `reply_macros`）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ReplyMacros {
+    /// 全局宏
+    pub macros: BTreeMap<String, Vec<MacroStep>>,
+    /// 按项目目录覆盖的宏（支持 `~`）
+    pub projects: BTreeMap<String, BTreeMap<String, Vec<MacroStep>>>,
+}
+
+impl ReplyMacros {
+    /// 读取 config.json，缺失或无效时为空
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("reply_macros").unwrap_or_default()
+    }
+
+    /// 适用于项目的覆盖，按路径长度从长到短
+    fn project_overrides(&self, project: Option<&str>) -> Vec<&BTreeMap<String, Vec<MacroStep>>> {
+        let Some(project) = project else {
+            return Vec::new();
+        };
+        let project = Path::new(project);
+        let mut matched: Vec<_> = self
+            .projects
+            .iter()
+            .map(|(dir, macros)| (crate::notification::summarizer::expand_home(dir), macros))
+            .filter(|(dir, _)| project.starts_with(dir))
+            .collect();
+        matched.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+        matched.into_iter().map(|(_, macros)| macros).collect()
+    }
+
+    /// 查找宏：项目覆盖优先，其次全局；没有发送步骤的宏视为无效
+    pub fn resolve(&self, name: &str, project: Option<&str>) -> Option<ReplyMacro> {
+        let steps = self
+            .project_overrides(project)
+            .into_iter()
+            .chain(std::iter::once(&self.macros))
+            .find_map(|macros| macros.get(name))?;
+        if !has_send(steps) {
+            return None;
+        }
+        Some(ReplyMacro {
+            name: name.to_string(),
+            steps: steps.clone(),
+        })
+    }
+
+    /// 项目可用的宏名（含全局宏）
+    pub fn names(&self, project: Option<&str>) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .project_overrides(project)
+            .into_iter()
+            .chain(std::iter::once(&self.macros))
+            .flat_map(|macros| macros.iter())
+            .filter(|(_, steps)| has_send(steps))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+fn has_send(steps: &[MacroStep]) -> bool {
+    steps.iter().any(|step| matches!(step, MacroStep::Send(_)))
+}
+
+/// 解析 `@name` 形式的回复，返回宏名
+pub fn macro_name(reply: &str) -> Option<&str> {
+    let name = reply.trim().strip_prefix('@')?;
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReplyMacros {
+        serde_json::from_value(serde_json::json!({
+            "macros": {
+                "approve-tests": ["y", {"wait": 2}, "run the test suite"],
+                "deny": ["n"],
+                "noop": [{"wait": 1}]
+            },
+            "projects": {
+                "/work": {"deny": ["n", "try another approach"]},
+                "/work/api": {"approve-tests": ["y", {"wait": 0.5}, "cargo test"]}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_macro_name() {
+        assert_eq!(macro_name("@approve-tests"), Some("approve-tests"));
+        assert_eq!(macro_name(" @deny "), Some("deny"));
+        assert_eq!(macro_name("@"), None);
+        assert_eq!(macro_name("@src/main.rs please"), None);
+        assert_eq!(macro_name("y"), None);
+    }
+
+    #[test]
+    fn test_resolve_global_and_project_override() {
+        let macros = config();
+
+        let global = macros.resolve("approve-tests", Some("/other")).unwrap();
+        assert_eq!(
+            global.describe(),
+            "@approve-tests (y → 等待 2s → run the test suite)"
+        );
+
+        // 最长路径优先
+        let api = macros
+            .resolve("approve-tests", Some("/work/api/src"))
+            .unwrap();
+        assert_eq!(api.steps[2], MacroStep::Send("cargo test".to_string()));
+        let deny = macros.resolve("deny", Some("/work/api")).unwrap();
+        assert_eq!(deny.steps.len(), 2);
+
+        // 路径按组件匹配，/workspace 不属于 /work
+        let deny = macros.resolve("deny", Some("/workspace")).unwrap();
+        assert_eq!(deny.steps.len(), 1);
+
+        assert!(macros.resolve("missing", None).is_none());
+        assert!(macros.resolve("noop", None).is_none());
+    }
+
+    #[test]
+    fn test_names() {
+        let macros = config();
+        assert_eq!(macros.names(None), vec!["approve-tests", "deny"]);
+        assert_eq!(
+            macros.names(Some("/work/api")),
+            vec!["approve-tests", "deny"]
+        );
+    }
+}
@@ -7,12 +7,28 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-
-use crate::agent::AgentManager;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::agent::adapter::{get_adapter, KeySequence, PromptKind};
+use crate::agent::extractor::MessageType;
+use crate::agent::{AgentManager, AgentType};
+use crate::infra::event_bus::EventBus;
+use crate::infra::multiplexer::{self, Multiplexer};
+use crate::infra::schema::CONVERSATION_STATE_SCHEMA;
 use crate::infra::tmux::TmuxManager;
+use crate::notification::engagement::{ResponseKind, ResponseLog, ResponseRecord};
+use crate::notification::remember_reply;
 use crate::notification::summarizer::RiskLevel;
+use crate::session::reply_auth::{ReplyAuth, ReplySender};
+use crate::session::reply_macro::{self, MacroStep, ReplyMacro, ReplyMacros};
+use crate::session::verify::{
+    verify_attempts, AttemptOutcome, ReplyVerification, DEFAULT_VERIFY_DELAY,
+};
 use crate::team::{InboxMessage, TeamBridge};
 
 /// 确认类型
@@ -34,6 +50,12 @@ pub enum ConfirmationType {
     /// 选项选择
     #[serde(rename = "option_selection")]
     OptionSelection { options: Vec<String> },
+    /// PreToolUse hook 等待的远程决定（回复写入状态文件而不是终端）
+    #[serde(rename = "hook_decision")]
+    HookDecision {
+        tool: String,
+        input: serde_json::Value,
+    },
 }
 
 /// 待处理的确认
@@ -56,6 +78,12 @@ pub struct PendingConfirmation {
     /// 风险等级（用于批量过滤）
     #[serde(default)]
     pub risk_level: Option<RiskLevel>,
+    /// 提取的消息类型（用于把回复转换为正确的按键）
+    #[serde(default)]
+    pub message_type: Option<MessageType>,
+    /// transcript 中最近的助手消息（问题引用的上下文）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_messages: Vec<String>,
 }
 
 /// Agent 上下文
@@ -82,19 +110,45 @@ pub struct ConversationState {
     pub pending_confirmations: Vec<PendingConfirmation>,
     /// 最后更新时间
     pub last_updated: Option<DateTime<Utc>>,
+    /// HookDecision 确认的回复（确认 ID -> 标准化回复），由等待中的 hook 取走
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub hook_decisions: HashMap<String, String>,
+    /// 已确认收到通知的 agent（agent ID -> 确认时间），期间不再发送提醒
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub acknowledgements: HashMap<String, DateTime<Utc>>,
 }
 
 /// 回复结果
 #[derive(Debug, Clone)]
 pub enum ReplyResult {
     /// 回复已发送
-    Sent { agent_id: String, reply: String },
+    Sent {
+        agent_id: String,
+        reply: String,
+        /// 按键是否确认生效
+        verification: ReplyVerification,
+    },
     /// 需要选择目标
     NeedSelection { options: Vec<PendingConfirmation> },
     /// 没有待处理的确认
     NoPending,
     /// 无效的选择
     InvalidSelection(String),
+    /// 回复者无权回复该确认（见 `reply_auth`）
+    Denied { agent_id: String, reason: String },
+}
+
+/// `cam ack` 的结果
+#[derive(Debug, Clone)]
+pub enum AckResult {
+    /// 已记录确认
+    Acknowledged { agent_id: String },
+    /// 需要选择目标
+    NeedSelection { options: Vec<PendingConfirmation> },
+    /// 没有待处理的确认
+    NoPending,
+    /// 未找到目标
+    NotFound(String),
 }
 
 /// Batch filter for reply operations
@@ -106,6 +160,8 @@ pub enum BatchFilter {
     Agent(String),
     /// Reply to confirmations with specific risk level
     Risk(RiskLevel),
+    /// Reply to specific confirmation IDs (combined permission notifications)
+    Confirmations(Vec<String>),
 }
 
 /// Batch reply result
@@ -122,32 +178,74 @@ pub struct ConversationStateManager {
     state_file: PathBuf,
     agent_manager: AgentManager,
     team_bridge: TeamBridge,
-    tmux_manager: TmuxManager,
+    tmux_manager: Arc<dyn Multiplexer>,
+    /// 回复验证的稳定等待时间，None 表示不验证
+    verify_delay: Option<Duration>,
+    /// 回复授权，None 表示不限制
+    reply_auth: Option<ReplyAuth>,
+    /// 回复宏（`@name`）
+    reply_macros: ReplyMacros,
+    /// 回复和 ack 记录（用于通知响应统计）
+    response_log: ResponseLog,
 }
 
 impl ConversationStateManager {
     /// 创建新的状态管理器
     pub fn new() -> Self {
-        let state_file = dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".config/code-agent-monitor")
-            .join("conversation_state.json");
+        let state_file = crate::infra::paths::state_dir().join("conversation_state.json");
+
+        Self::with_agent_manager(state_file, AgentManager::new())
+    }
 
+    /// 使用指定的状态文件和 AgentManager 创建状态管理器
+    pub fn with_agent_manager(state_file: PathBuf, agent_manager: AgentManager) -> Self {
         Self {
+            response_log: ResponseLog::in_dir(state_file.parent().unwrap_or(Path::new("."))),
             state_file,
-            agent_manager: AgentManager::new(),
+            agent_manager,
             team_bridge: TeamBridge::new(),
-            tmux_manager: TmuxManager::new(),
+            tmux_manager: multiplexer::from_config(),
+            verify_delay: Some(DEFAULT_VERIFY_DELAY),
+            reply_auth: ReplyAuth::load(),
+            reply_macros: ReplyMacros::load(),
         }
     }
 
+    /// 设置回复验证的稳定等待时间（None 关闭验证）
+    pub fn with_reply_verification(mut self, delay: Option<Duration>) -> Self {
+        self.verify_delay = delay;
+        self
+    }
+
+    /// 设置回复授权（None 不限制）
+    pub fn with_reply_auth(mut self, reply_auth: Option<ReplyAuth>) -> Self {
+        self.reply_auth = reply_auth;
+        self
+    }
+
+    /// 设置回复宏
+    pub fn with_reply_macros(mut self, reply_macros: ReplyMacros) -> Self {
+        self.reply_macros = reply_macros;
+        self
+    }
+
+    /// 重新读取 config.json 中的 `reply_auth` 和 `reply_macros`（config.json 热重载）
+    pub fn reload_config(&mut self) {
+        self.reply_auth = ReplyAuth::load();
+        self.reply_macros = ReplyMacros::load();
+    }
+
     /// 创建用于测试的状态管理器
     pub fn new_for_test(state_file: PathBuf) -> Self {
         Self {
+            response_log: ResponseLog::in_dir(state_file.parent().unwrap_or(Path::new("."))),
             state_file,
             agent_manager: AgentManager::new_for_test(),
             team_bridge: TeamBridge::new(),
-            tmux_manager: TmuxManager::new(),
+            tmux_manager: Arc::new(TmuxManager::new()),
+            verify_delay: Some(DEFAULT_VERIFY_DELAY),
+            reply_auth: None,
+            reply_macros: ReplyMacros::default(),
         }
     }
 
@@ -158,8 +256,7 @@ impl ConversationStateManager {
         }
 
         let content = fs::read_to_string(&self.state_file)?;
-        let state: ConversationState = serde_json::from_str(&content)?;
-        Ok(state)
+        CONVERSATION_STATE_SCHEMA.load_versioned(&content)
     }
 
     /// 保存状态
@@ -169,7 +266,8 @@ impl ConversationStateManager {
             fs::create_dir_all(parent)?;
         }
 
-        let content = serde_json::to_string_pretty(state)?;
+        let content =
+            serde_json::to_string_pretty(&CONVERSATION_STATE_SCHEMA.to_versioned_value(state)?)?;
         fs::write(&self.state_file, content)?;
         Ok(())
     }
@@ -197,6 +295,8 @@ impl ConversationStateManager {
             created_at: Utc::now(),
             tmux_session: tmux_session.map(|s| s.to_string()),
             risk_level: None, // Will be set by caller if needed
+            message_type: None,
+            recent_messages: Vec::new(),
         };
 
         state.pending_confirmations.push(confirmation);
@@ -212,12 +312,54 @@ impl ConversationStateManager {
         Ok(id)
     }
 
+    /// 记录待处理确认的消息类型（由提取结果设置）
+    pub fn set_pending_message_type(
+        &self,
+        confirmation_id: &str,
+        message_type: MessageType,
+    ) -> Result<()> {
+        let mut state = self.load_state()?;
+        let confirmation = state
+            .pending_confirmations
+            .iter_mut()
+            .find(|c| c.id == confirmation_id)
+            .ok_or_else(|| anyhow!("未找到确认: {}", confirmation_id))?;
+        confirmation.message_type = Some(message_type);
+        state.last_updated = Some(Utc::now());
+        self.save_state(&state)
+    }
+
+    /// 附带 transcript 中最近的助手消息
+    pub fn set_pending_recent_messages(
+        &self,
+        confirmation_id: &str,
+        messages: Vec<String>,
+    ) -> Result<()> {
+        let mut state = self.load_state()?;
+        let confirmation = state
+            .pending_confirmations
+            .iter_mut()
+            .find(|c| c.id == confirmation_id)
+            .ok_or_else(|| anyhow!("未找到确认: {}", confirmation_id))?;
+        confirmation.recent_messages = messages;
+        state.last_updated = Some(Utc::now());
+        self.save_state(&state)
+    }
+
     /// 获取所有待处理的确认
     pub fn get_pending_confirmations(&self) -> Result<Vec<PendingConfirmation>> {
         let state = self.load_state()?;
         Ok(state.pending_confirmations)
     }
 
+    /// 目标（agent_id 或 confirmation_id）是否有待处理的确认
+    pub fn has_pending_for(&self, target: &str) -> Result<bool> {
+        Ok(self
+            .get_pending_confirmations()?
+            .iter()
+            .any(|c| c.agent_id == target || c.id == target))
+    }
+
     /// 获取最近的待处理确认
     pub fn get_latest_pending(&self) -> Result<Option<PendingConfirmation>> {
         let state = self.load_state()?;
@@ -234,12 +376,125 @@ impl ConversationStateManager {
             .position(|c| c.id == confirmation_id);
 
         let removed = pos.map(|i| state.pending_confirmations.remove(i));
+        // agent 没有其他待处理确认时，确认状态随之结束
+        if let Some(ref confirmation) = removed {
+            if !state
+                .pending_confirmations
+                .iter()
+                .any(|c| c.agent_id == confirmation.agent_id)
+            {
+                state.acknowledgements.remove(&confirmation.agent_id);
+            }
+        }
         state.last_updated = Some(Utc::now());
 
         self.save_state(&state)?;
         Ok(removed)
     }
 
+    /// 确认已收到通知，停止该 agent 的提醒（回复或 agent 恢复运行后自动结束）
+    ///
+    /// `target` 可以是确认 ID 或 agent ID；未指定时只有一个待处理确认才能确定目标。
+    pub fn acknowledge(&self, target: Option<&str>) -> Result<AckResult> {
+        let mut state = self.load_state()?;
+
+        let agent_id = match target {
+            Some(target) => match state
+                .pending_confirmations
+                .iter()
+                .find(|c| c.id == target || c.agent_id == target)
+            {
+                Some(confirmation) => confirmation.agent_id.clone(),
+                None if self.agent_manager.get_agent(target)?.is_some() => target.to_string(),
+                None => return Ok(AckResult::NotFound(target.to_string())),
+            },
+            None => match state.pending_confirmations.as_slice() {
+                [] => return Ok(AckResult::NoPending),
+                [confirmation] => confirmation.agent_id.clone(),
+                options => {
+                    return Ok(AckResult::NeedSelection {
+                        options: options.to_vec(),
+                    })
+                }
+            },
+        };
+
+        // 清理超过 1 天的确认记录
+        let one_day_ago = Utc::now() - chrono::Duration::days(1);
+        state.acknowledgements.retain(|_, at| *at > one_day_ago);
+        state.acknowledgements.insert(agent_id.clone(), Utc::now());
+        state.last_updated = Some(Utc::now());
+        self.save_state(&state)?;
+        self.record_response(&agent_id, ResponseKind::Ack, None, None);
+        Ok(AckResult::Acknowledged { agent_id })
+    }
+
+    /// 记录一次回复或 ack 并发布到事件总线（写入失败只记日志）
+    fn record_response(
+        &self,
+        agent_id: &str,
+        kind: ResponseKind,
+        sender: Option<&str>,
+        reply: Option<&str>,
+    ) {
+        let record = ResponseRecord::new(agent_id, kind, sender);
+        if let Err(e) = self.response_log.append(&record) {
+            warn!(error = %e, "Failed to record notification response");
+        }
+        let source = match kind {
+            ResponseKind::Reply => "reply",
+            ResponseKind::Ack => "ack",
+        };
+        self.event_bus().publish(
+            source,
+            Some(agent_id),
+            serde_json::json!({"sender": sender, "reply": reply}),
+        );
+    }
+
+    /// 共享事件总线（与状态文件同目录）
+    fn event_bus(&self) -> EventBus {
+        EventBus::in_dir(self.state_file.parent().unwrap_or(Path::new(".")))
+    }
+
+    /// agent 的通知是否已被确认（读取失败视为未确认）
+    pub fn is_acknowledged(&self, agent_id: &str) -> bool {
+        self.load_state()
+            .map(|state| state.acknowledgements.contains_key(agent_id))
+            .unwrap_or(false)
+    }
+
+    /// 清除 agent 的确认状态（agent 恢复运行时调用）
+    pub fn clear_acknowledgement(&self, agent_id: &str) -> Result<()> {
+        let mut state = self.load_state()?;
+        if state.acknowledgements.remove(agent_id).is_some() {
+            state.last_updated = Some(Utc::now());
+            self.save_state(&state)?;
+        }
+        Ok(())
+    }
+
+    /// 记录 HookDecision 确认的回复
+    pub fn record_hook_decision(&self, confirmation_id: &str, reply: &str) -> Result<()> {
+        let mut state = self.load_state()?;
+        state
+            .hook_decisions
+            .insert(confirmation_id.to_string(), reply.to_string());
+        state.last_updated = Some(Utc::now());
+        self.save_state(&state)
+    }
+
+    /// 取走 HookDecision 确认的回复（尚未回复时返回 None）
+    pub fn take_hook_decision(&self, confirmation_id: &str) -> Result<Option<String>> {
+        let mut state = self.load_state()?;
+        let Some(reply) = state.hook_decisions.remove(confirmation_id) else {
+            return Ok(None);
+        };
+        state.last_updated = Some(Utc::now());
+        self.save_state(&state)?;
+        Ok(Some(reply))
+    }
+
     /// 处理快捷回复
     ///
     /// 支持的回复格式：
@@ -248,6 +503,16 @@ impl ConversationStateManager {
     /// - "1" / "2" / "3" -> 发送对应选项
     /// - 其他 -> 原样发送
     pub fn handle_reply(&self, reply: &str, target: Option<&str>) -> Result<ReplyResult> {
+        self.handle_reply_from(reply, target, ReplySender::Local)
+    }
+
+    /// 处理来自指定回复者的快捷回复，按 `reply_auth` 授权
+    pub fn handle_reply_from(
+        &self,
+        reply: &str,
+        target: Option<&str>,
+        sender: ReplySender<'_>,
+    ) -> Result<ReplyResult> {
         let pending = self.get_pending_confirmations()?;
 
         if pending.is_empty() {
@@ -282,15 +547,51 @@ impl ConversationStateManager {
             }
         };
 
-        // 发送回复
-        self.send_reply_to_agent(&confirmation, &normalized_reply)?;
+        if let Some(reason) = self.check_reply_auth(&confirmation, sender, reply) {
+            return Ok(ReplyResult::Denied {
+                agent_id: confirmation.agent_id,
+                reason,
+            });
+        }
+
+        let (normalized_reply, verification) = match reply_macro::macro_name(reply) {
+            // 回复宏：`@name` 展开为多步回复
+            Some(name) => {
+                let reply_macro = match self.resolve_reply_macro(&confirmation, name) {
+                    Ok(reply_macro) => reply_macro,
+                    Err(message) => return Ok(ReplyResult::InvalidSelection(message)),
+                };
+                let verification = self.run_reply_macro(&confirmation, &reply_macro, |reply| {
+                    self.send_and_verify(&confirmation, reply)
+                })?;
+                (reply_macro.describe(), verification)
+            }
+            None => {
+                // 回答记忆：`r` 展开为上次的回答
+                let normalized_reply = self
+                    .normalize_reply(&remember_reply(&confirmation.agent_id, &normalized_reply));
+
+                // 发送回复并验证是否生效
+                let verification = self.send_and_verify(&confirmation, &normalized_reply)?;
+                (normalized_reply, verification)
+            }
+        };
 
-        // 移除已处理的确认
-        self.remove_pending(&confirmation.id)?;
+        // 移除已处理的确认（未生效时保留，便于重试）
+        if !matches!(verification, ReplyVerification::Unverified { .. }) {
+            self.remove_pending(&confirmation.id)?;
+            self.record_response(
+                &confirmation.agent_id,
+                ResponseKind::Reply,
+                sender.id(),
+                Some(&normalized_reply),
+            );
+        }
 
         Ok(ReplyResult::Sent {
             agent_id: confirmation.agent_id,
             reply: normalized_reply,
+            verification,
         })
     }
 
@@ -299,6 +600,16 @@ impl ConversationStateManager {
         &self,
         reply: &str,
         filter: BatchFilter,
+    ) -> Result<Vec<BatchReplyResult>> {
+        self.handle_reply_batch_from(reply, filter, ReplySender::Local)
+    }
+
+    /// 批量回复，逐个按 `reply_auth` 授权；被拒绝的确认保留为待处理
+    pub fn handle_reply_batch_from(
+        &self,
+        reply: &str,
+        filter: BatchFilter,
+        sender: ReplySender<'_>,
     ) -> Result<Vec<BatchReplyResult>> {
         let pending = self.get_pending_confirmations()?;
         let normalized_reply = self.normalize_reply(reply);
@@ -320,17 +631,56 @@ impl ConversationStateManager {
                     }
                 }
                 BatchFilter::Risk(risk) => c.risk_level.map(|r| r == *risk).unwrap_or(false),
+                BatchFilter::Confirmations(ids) => ids.contains(&c.id),
             })
             .cloned()
             .collect();
 
         for confirmation in filtered {
-            let result = match self.send_reply_to_agent(&confirmation, &normalized_reply) {
-                Ok(()) => {
+            if let Some(reason) = self.check_reply_auth(&confirmation, sender, reply) {
+                results.push(BatchReplyResult {
+                    agent_id: confirmation.agent_id,
+                    reply: normalized_reply.clone(),
+                    success: false,
+                    error: Some(reason),
+                });
+                continue;
+            }
+            let sent = match reply_macro::macro_name(reply) {
+                Some(name) => match self.resolve_reply_macro(&confirmation, name) {
+                    Ok(reply_macro) => self
+                        .run_reply_macro(&confirmation, &reply_macro, |reply| {
+                            self.send_reply_to_agent(&confirmation, reply)
+                                .map(|()| ReplyVerification::Skipped)
+                        })
+                        .map(|_| reply_macro.describe()),
+                    Err(message) => {
+                        // 宏不存在时保留确认
+                        results.push(BatchReplyResult {
+                            agent_id: confirmation.agent_id,
+                            reply: normalized_reply.clone(),
+                            success: false,
+                            error: Some(message),
+                        });
+                        continue;
+                    }
+                },
+                None => self
+                    .send_reply_to_agent(&confirmation, &normalized_reply)
+                    .map(|()| normalized_reply.clone()),
+            };
+            let result = match sent {
+                Ok(sent_reply) => {
                     let _ = self.remove_pending(&confirmation.id);
+                    self.record_response(
+                        &confirmation.agent_id,
+                        ResponseKind::Reply,
+                        sender.id(),
+                        Some(&sent_reply),
+                    );
                     BatchReplyResult {
                         agent_id: confirmation.agent_id,
-                        reply: normalized_reply.clone(),
+                        reply: sent_reply,
                         success: true,
                         error: None,
                     }
@@ -352,6 +702,79 @@ impl ConversationStateManager {
         Ok(results)
     }
 
+    /// 按 `reply_auth` 检查回复者，拒绝时返回原因
+    fn check_reply_auth(
+        &self,
+        confirmation: &PendingConfirmation,
+        sender: ReplySender<'_>,
+        reply: &str,
+    ) -> Option<String> {
+        self.reply_auth.as_ref()?.check(confirmation, sender, reply)
+    }
+
+    /// 按确认所属 agent 的项目查找回复宏，不存在时返回说明（含可用的宏）
+    fn resolve_reply_macro(
+        &self,
+        confirmation: &PendingConfirmation,
+        name: &str,
+    ) -> std::result::Result<ReplyMacro, String> {
+        let project = self
+            .agent_manager
+            .get_agent(&confirmation.agent_id)
+            .ok()
+            .flatten()
+            .map(|agent| agent.project_path);
+        if let Some(reply_macro) = self.reply_macros.resolve(name, project.as_deref()) {
+            return Ok(reply_macro);
+        }
+        let names = self.reply_macros.names(project.as_deref());
+        Err(if names.is_empty() {
+            format!(
+                "未知的回复宏 @{}（config.json 中未配置 reply_macros）",
+                name
+            )
+        } else {
+            format!("未知的回复宏 @{}，可用: @{}", name, names.join(", @"))
+        })
+    }
+
+    /// 执行回复宏：第一条回复由 `send_first` 发送（未生效时停止），之后的回复作为新消息输入
+    fn run_reply_macro(
+        &self,
+        confirmation: &PendingConfirmation,
+        reply_macro: &ReplyMacro,
+        send_first: impl FnOnce(&str) -> Result<ReplyVerification>,
+    ) -> Result<ReplyVerification> {
+        let mut send_first = Some(send_first);
+        let mut verification = ReplyVerification::Skipped;
+        for step in &reply_macro.steps {
+            match step {
+                MacroStep::Wait { wait } => std::thread::sleep(ReplyMacro::wait_duration(*wait)),
+                MacroStep::Send(text) => match send_first.take() {
+                    Some(send) => {
+                        verification = send(&self.normalize_reply(text))?;
+                        if matches!(verification, ReplyVerification::Unverified { .. }) {
+                            return Ok(verification);
+                        }
+                    }
+                    None => self.send_followup(confirmation, text)?,
+                },
+            }
+        }
+        Ok(verification)
+    }
+
+    /// 发送回复宏的后续消息（hook 决定只写入一次，后续消息直接输入终端）
+    fn send_followup(&self, confirmation: &PendingConfirmation, text: &str) -> Result<()> {
+        if let ConfirmationType::HookDecision { .. } = confirmation.confirmation_type {
+            let session = self
+                .resolve_tmux_session(confirmation)
+                .ok_or_else(|| anyhow!("无法找到 agent {} 的终端", confirmation.agent_id))?;
+            return self.send_to_tmux(&session, text);
+        }
+        self.send_reply_to_agent(confirmation, text)
+    }
+
     /// 标准化回复
     fn normalize_reply(&self, reply: &str) -> String {
         let reply_lower = reply.to_lowercase().trim().to_string();
@@ -361,26 +784,125 @@ impl ConversationStateManager {
                 "y".to_string()
             }
             "n" | "no" | "否" | "不" | "取消" | "拒绝" | "不允许" => "n".to_string(),
+            "s" | "sandbox" | "沙箱" | "沙箱执行" => "s".to_string(),
             "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => reply_lower,
             _ => reply.to_string(),
         }
     }
 
-    /// 发送回复到 agent
-    fn send_reply_to_agent(&self, confirmation: &PendingConfirmation, reply: &str) -> Result<()> {
+    /// 查找确认对应的 tmux session
+    fn resolve_tmux_session(&self, confirmation: &PendingConfirmation) -> Option<String> {
         // 优先使用 tmux_session
         if let Some(ref tmux_session) = confirmation.tmux_session {
-            return self.send_to_tmux(tmux_session, reply);
+            return Some(tmux_session.clone());
         }
 
         // 尝试通过 agent_id 查找 tmux session
-        if let Ok(agents) = self.agent_manager.list_agents() {
-            for agent in agents {
-                if agent.agent_id == confirmation.agent_id {
-                    return self.send_to_tmux(&agent.tmux_session, reply);
+        self.agent_manager
+            .get_agent(&confirmation.agent_id)
+            .ok()
+            .flatten()
+            .map(|agent| agent.tmux_session)
+    }
+
+    /// 按目标 agent 类型和提示方式转换回复按键
+    fn reply_key_sequences(
+        &self,
+        confirmation: &PendingConfirmation,
+        reply: &str,
+    ) -> Vec<KeySequence> {
+        // 未注册的 agent（如 team 成员）使用通用转换
+        let agent_type = self
+            .agent_manager
+            .get_agent(&confirmation.agent_id)
+            .ok()
+            .flatten()
+            .map(|agent| agent.agent_type)
+            .unwrap_or(AgentType::Unknown);
+        let prompt = PromptKind::detect(
+            confirmation.message_type.as_ref(),
+            &confirmation.context,
+            reply,
+        );
+        get_adapter(&agent_type).translate_reply(reply, prompt)
+    }
+
+    /// 发送回复并确认按键生效
+    ///
+    /// 发送后等待终端稳定再捕获屏幕，提示仍在底部时依次尝试备用按键序列。
+    fn send_and_verify(
+        &self,
+        confirmation: &PendingConfirmation,
+        reply: &str,
+    ) -> Result<ReplyVerification> {
+        if matches!(
+            confirmation.confirmation_type,
+            ConfirmationType::HookDecision { .. }
+        ) {
+            self.send_reply_to_agent(confirmation, reply)?;
+            return Ok(ReplyVerification::Skipped);
+        }
+        let (Some(session), Some(delay)) =
+            (self.resolve_tmux_session(confirmation), self.verify_delay)
+        else {
+            self.send_reply_to_agent(confirmation, reply)?;
+            return Ok(ReplyVerification::Skipped);
+        };
+
+        let Ok(before) = self.tmux_manager.capture_pane(&session, 50) else {
+            self.send_to_tmux(&session, reply)?;
+            return Ok(ReplyVerification::Skipped);
+        };
+
+        let sequences = self.reply_key_sequences(confirmation, reply);
+        let outcome = verify_attempts(
+            before,
+            sequences.len(),
+            || self.tmux_manager.capture_pane(&session, 50).ok(),
+            |index| {
+                if index > 0 {
+                    warn!(
+                        session = %session,
+                        method = %sequences[index - 1].describe(),
+                        "Reply did not take effect, trying next key sequence"
+                    );
                 }
+                self.send_sequence(&session, &sequences[index])
+            },
+            || std::thread::sleep(delay),
+        )?;
+
+        Ok(match outcome {
+            AttemptOutcome::Changed(attempts) => ReplyVerification::Verified {
+                attempts,
+                method: sequences[attempts as usize - 1].describe(),
+            },
+            AttemptOutcome::Unchanged(attempts) => ReplyVerification::Unverified { attempts },
+            // session 已退出，无法继续验证
+            AttemptOutcome::CaptureFailed => ReplyVerification::Skipped,
+        })
+    }
+
+    /// 按指定方式发送按键
+    fn send_sequence(&self, session: &str, sequence: &KeySequence) -> Result<()> {
+        match sequence {
+            KeySequence::Text(text) => self.send_to_tmux(session, text),
+            KeySequence::Raw(text) => self.tmux_manager.send_keys_raw(session, text),
+            KeySequence::Keys(keys) => {
+                let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+                self.tmux_manager.send_named_keys(session, &keys)
             }
         }
+    }
+
+    /// 发送回复到 agent
+    fn send_reply_to_agent(&self, confirmation: &PendingConfirmation, reply: &str) -> Result<()> {
+        if let ConfirmationType::HookDecision { .. } = confirmation.confirmation_type {
+            return self.record_hook_decision(&confirmation.id, reply);
+        }
+        if let Some(tmux_session) = self.resolve_tmux_session(confirmation) {
+            return self.send_to_tmux(&tmux_session, reply);
+        }
 
         // 如果是 team 成员，尝试通过 inbox 发送
         if let Some(ref team) = confirmation.team {
@@ -393,12 +915,8 @@ impl ConversationStateManager {
 
             if !member_name.is_empty() {
                 let msg = InboxMessage {
-                    from: "user".to_string(),
-                    text: reply.to_string(),
                     summary: Some("用户回复".to_string()),
-                    timestamp: Utc::now(),
-                    color: None,
-                    read: false,
+                    ..InboxMessage::new("user", reply)
                 };
                 self.team_bridge.send_to_inbox(team, member_name, msg)?;
                 return Ok(());
@@ -413,7 +931,7 @@ impl ConversationStateManager {
 
     /// 发送到 tmux session
     fn send_to_tmux(&self, session: &str, message: &str) -> Result<()> {
-        self.tmux_manager.send_keys(session, message)
+        self.tmux_manager.send_text(session, message)
     }
 
     /// 设置当前活跃的 Team
@@ -473,6 +991,30 @@ mod tests {
         assert!(state.pending_confirmations.is_empty());
     }
 
+    #[test]
+    fn test_legacy_state_round_trip() {
+        let (manager, temp) = create_test_manager();
+        let state_file = temp.path().join("conversation_state.json");
+        // v0：无 schema_version，也没有 pending_confirmations 字段
+        fs::write(
+            &state_file,
+            r#"{"current_team":"my-team","current_agent":null,"last_updated":null}"#,
+        )
+        .unwrap();
+
+        let state = manager.load_state().unwrap();
+        assert_eq!(state.current_team.as_deref(), Some("my-team"));
+        assert!(state.pending_confirmations.is_empty());
+
+        manager.save_state(&state).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&state_file).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], CONVERSATION_STATE_SCHEMA.version);
+
+        let reloaded = manager.load_state().unwrap();
+        assert_eq!(reloaded.current_team, state.current_team);
+    }
+
     #[test]
     fn test_register_pending() {
         let (manager, _temp) = create_test_manager();
@@ -497,6 +1039,35 @@ mod tests {
         assert_eq!(pending[0].agent_id, "cam-123");
     }
 
+    #[test]
+    fn test_set_pending_message_type() {
+        let (manager, _temp) = create_test_manager();
+
+        let id = manager
+            .register_pending(
+                "cam-123",
+                None,
+                ConfirmationType::OptionSelection {
+                    options: vec!["Yes".to_string(), "No".to_string()],
+                },
+                "❯ 1. Yes\n  2. No",
+                None,
+            )
+            .unwrap();
+        assert!(manager.get_pending_confirmations().unwrap()[0]
+            .message_type
+            .is_none());
+
+        manager
+            .set_pending_message_type(&id, MessageType::Choice)
+            .unwrap();
+        let pending = manager.get_pending_confirmations().unwrap();
+        assert_eq!(pending[0].message_type, Some(MessageType::Choice));
+        assert!(manager
+            .set_pending_message_type("conf-missing", MessageType::Choice)
+            .is_err());
+    }
+
     #[test]
     fn test_remove_pending() {
         let (manager, _temp) = create_test_manager();
@@ -522,6 +1093,123 @@ mod tests {
         assert!(pending.is_empty());
     }
 
+    #[test]
+    fn test_acknowledge() {
+        let (manager, temp) = create_test_manager();
+        assert!(matches!(
+            manager.acknowledge(None).unwrap(),
+            AckResult::NoPending
+        ));
+
+        let id = manager
+            .register_pending(
+                "cam-123",
+                None,
+                ConfirmationType::OptionSelection {
+                    options: vec!["1".to_string(), "2".to_string()],
+                },
+                "选择方案",
+                None,
+            )
+            .unwrap();
+        assert!(matches!(
+            manager.acknowledge(Some("cam-missing")).unwrap(),
+            AckResult::NotFound(_)
+        ));
+        assert!(matches!(
+            manager.acknowledge(Some(&id)).unwrap(),
+            AckResult::Acknowledged { ref agent_id } if agent_id == "cam-123"
+        ));
+        assert!(manager.is_acknowledged("cam-123"));
+        // ack 记录用于通知响应统计
+        let responses = ResponseLog::in_dir(temp.path()).load();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].kind, ResponseKind::Ack);
+
+        // 处理完待处理确认后确认状态结束
+        manager.remove_pending(&id).unwrap();
+        assert!(!manager.is_acknowledged("cam-123"));
+    }
+
+    #[test]
+    fn test_hook_decision_reply() {
+        let (manager, _temp) = create_test_manager();
+
+        let id = manager
+            .register_pending(
+                "cam-123",
+                None,
+                ConfirmationType::HookDecision {
+                    tool: "Bash".to_string(),
+                    input: serde_json::json!({"command": "rm -rf build"}),
+                },
+                "Bash rm -rf build",
+                Some("cam-123"),
+            )
+            .unwrap();
+        assert_eq!(manager.take_hook_decision(&id).unwrap(), None);
+
+        // 回复写入状态文件，不发送到终端
+        let result = manager.handle_reply("允许", None).unwrap();
+        assert!(matches!(
+            result,
+            ReplyResult::Sent {
+                verification: ReplyVerification::Skipped,
+                ..
+            }
+        ));
+        assert!(manager.get_pending_confirmations().unwrap().is_empty());
+        assert_eq!(
+            manager.take_hook_decision(&id).unwrap().as_deref(),
+            Some("y")
+        );
+        assert_eq!(manager.take_hook_decision(&id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_reply_macro() {
+        let (manager, _temp) = create_test_manager();
+        let manager = manager.with_reply_macros(
+            serde_json::from_value(serde_json::json!({
+                "macros": {"approve": [{"wait": 0}, "允许"]}
+            }))
+            .unwrap(),
+        );
+        let id = manager
+            .register_pending(
+                "cam-123",
+                None,
+                ConfirmationType::HookDecision {
+                    tool: "Bash".to_string(),
+                    input: serde_json::json!({"command": "cargo test"}),
+                },
+                "Bash cargo test",
+                Some("cam-123"),
+            )
+            .unwrap();
+
+        // 未知的宏不发送，确认保留
+        let result = manager.handle_reply("@missing", None).unwrap();
+        assert!(
+            matches!(result, ReplyResult::InvalidSelection(ref msg) if msg.contains("@approve"))
+        );
+        let results = manager
+            .handle_reply_batch("@missing", BatchFilter::All)
+            .unwrap();
+        assert!(!results[0].success);
+        assert_eq!(manager.get_pending_confirmations().unwrap().len(), 1);
+
+        let result = manager.handle_reply("@approve", Some("cam-123")).unwrap();
+        assert!(
+            matches!(result, ReplyResult::Sent { ref reply, .. } if reply == "@approve (等待 0s → 允许)")
+        );
+        assert!(manager.get_pending_confirmations().unwrap().is_empty());
+        assert_eq!(
+            manager.take_hook_decision(&id).unwrap().as_deref(),
+            Some("y")
+        );
+    }
+
     #[test]
     fn test_normalize_reply() {
         let (manager, _temp) = create_test_manager();
@@ -541,6 +1229,9 @@ mod tests {
         assert_eq!(manager.normalize_reply("不"), "n");
         assert_eq!(manager.normalize_reply("取消"), "n");
 
+        assert_eq!(manager.normalize_reply("沙箱"), "s");
+        assert_eq!(manager.normalize_reply("Sandbox"), "s");
+
         assert_eq!(manager.normalize_reply("1"), "1");
         assert_eq!(manager.normalize_reply("2"), "2");
 
@@ -719,6 +1410,46 @@ mod tests {
         assert!(pending.is_empty());
     }
 
+    #[test]
+    fn test_reply_auth_denies_and_keeps_pending() {
+        let (manager, temp) = create_test_manager();
+        let manager = manager.with_reply_auth(Some(ReplyAuth {
+            config: crate::session::ReplyAuthConfig {
+                medium: Some(vec!["tg:1".to_string()]),
+                ..Default::default()
+            },
+            denial_log: temp.path().join("reply_denials.jsonl"),
+            notify: false,
+        }));
+        manager
+            .register_pending(
+                "cam-123",
+                None,
+                ConfirmationType::PermissionRequest {
+                    tool: "Bash".to_string(),
+                    input: serde_json::json!({"command": "rm -rf build"}),
+                },
+                "rm -rf build",
+                Some("cam-123"),
+            )
+            .unwrap();
+
+        let result = manager
+            .handle_reply_from("y", None, ReplySender::Remote(Some("tg:2")))
+            .unwrap();
+        assert!(
+            matches!(result, ReplyResult::Denied { ref agent_id, .. } if agent_id == "cam-123")
+        );
+        let results = manager
+            .handle_reply_batch_from("y", BatchFilter::All, ReplySender::Remote(Some("tg:2")))
+            .unwrap();
+        assert!(!results[0].success);
+
+        assert_eq!(manager.get_pending_confirmations().unwrap().len(), 1);
+        let denials = fs::read_to_string(temp.path().join("reply_denials.jsonl")).unwrap();
+        assert_eq!(denials.lines().count(), 2);
+    }
+
     #[test]
     fn test_handle_reply_batch_agent_pattern() {
         let (manager, _temp) = create_test_manager();
@@ -0,0 +1,144 @@
+//! 回复验证 - 确认发送的按键确实生效
+//!
+//! 每次发送前重新捕获屏幕，等待终端稳定后再次捕获：与发送前逐字节相同说明按键没有生效，
+//! 才尝试适配器给出的下一种按键序列（如数字不带回车、方向键选择）；屏幕有任何变化
+//! （包括慢速重绘、出现下一个提示）都立即停止，避免重复回答或答到下一个问题。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 默认稳定等待时间
+pub const DEFAULT_VERIFY_DELAY: Duration = Duration::from_millis(800);
+
+/// 回复验证结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ReplyVerification {
+    /// 提示已消失或 agent 已继续
+    Verified { attempts: u32, method: String },
+    /// 所有按键序列尝试后提示仍在
+    Unverified { attempts: u32 },
+    /// 无法验证（非 tmux 通道或无法捕获屏幕）
+    Skipped,
+}
+
+impl ReplyVerification {
+    /// 是否确认生效
+    pub fn is_verified(&self) -> bool {
+        matches!(self, ReplyVerification::Verified { .. })
+    }
+}
+
+/// 逐个尝试按键序列的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// 第 n 次（从 1 开始）发送后屏幕有变化
+    Changed(u32),
+    /// 所有尝试后屏幕都没有变化
+    Unchanged(u32),
+    /// 无法再捕获屏幕（session 已退出）
+    CaptureFailed,
+}
+
+/// 从 `initial`（发送前的屏幕）开始依次尝试 `attempts` 种按键序列
+///
+/// `send(i)` 发送第 i 种序列，`wait` 等待终端稳定。每次尝试前重新捕获屏幕，
+/// 与上一次捕获不同（上一次按键在等待后才生效）也视为已生效。
+pub fn verify_attempts(
+    initial: String,
+    attempts: usize,
+    mut capture: impl FnMut() -> Option<String>,
+    mut send: impl FnMut(usize) -> Result<()>,
+    mut wait: impl FnMut(),
+) -> Result<AttemptOutcome> {
+    let mut before = initial;
+    for index in 0..attempts {
+        if index > 0 {
+            let Some(current) = capture() else {
+                return Ok(AttemptOutcome::CaptureFailed);
+            };
+            if current != before {
+                return Ok(AttemptOutcome::Changed(index as u32));
+            }
+        }
+        send(index)?;
+        wait();
+        let Some(after) = capture() else {
+            return Ok(AttemptOutcome::CaptureFailed);
+        };
+        if after != before {
+            return Ok(AttemptOutcome::Changed(index as u32 + 1));
+        }
+        before = after;
+    }
+    Ok(AttemptOutcome::Unchanged(attempts as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// 按顺序返回屏幕内容，记录发送了哪些序列
+    fn run(initial: &str, screens: &[&str], attempts: usize) -> (AttemptOutcome, Vec<usize>) {
+        let screens = RefCell::new(screens.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+        let sent = RefCell::new(Vec::new());
+        let outcome = verify_attempts(
+            initial.to_string(),
+            attempts,
+            || {
+                let mut screens = screens.borrow_mut();
+                (!screens.is_empty()).then(|| screens.remove(0))
+            },
+            |index| {
+                sent.borrow_mut().push(index);
+                Ok(())
+            },
+            || {},
+        )
+        .unwrap();
+        (outcome, sent.into_inner())
+    }
+
+    #[test]
+    fn test_verify_attempts() {
+        let prompt = "Do you want to proceed?\n❯ 1. Yes\n  2. No\n";
+
+        // 第一次就生效
+        let resumed = "Do you want to proceed?\n⏺ Writing hello.txt\n";
+        assert_eq!(
+            run(prompt, &[resumed], 3),
+            (AttemptOutcome::Changed(1), vec![0])
+        );
+
+        // 屏幕完全没变才尝试下一种
+        assert_eq!(
+            run(prompt, &[prompt, prompt, resumed], 3),
+            (AttemptOutcome::Changed(2), vec![0, 1])
+        );
+        assert_eq!(
+            run(prompt, &[prompt, prompt, prompt, prompt, prompt], 3),
+            (AttemptOutcome::Unchanged(3), vec![0, 1, 2])
+        );
+
+        // 只有尾部相同的新提示（下一个问题）也是变化，不再重发
+        let next_prompt = "Create file b.txt?\n❯ 1. Yes\n  2. No\n";
+        assert_eq!(
+            run(prompt, &[next_prompt], 3),
+            (AttemptOutcome::Changed(1), vec![0])
+        );
+
+        // 慢速重绘：等待后才变化，重发前的重新捕获发现变化即停止
+        assert_eq!(
+            run(prompt, &[prompt, resumed], 3),
+            (AttemptOutcome::Changed(1), vec![0])
+        );
+
+        // session 已退出
+        assert_eq!(
+            run(prompt, &[], 3),
+            (AttemptOutcome::CaptureFailed, vec![0])
+        );
+    }
+}
@@ -6,12 +6,22 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-use super::discovery::{TeamConfig, TeamMember};
+use super::discovery::{self, InboxForward, TeamConfig, TeamMember};
+use super::retention::{self, InboxRetention, PruneResult};
+use super::worktree::{self, MemberWorktree, WorktreeConfig};
+use crate::notification::webhook::WebhookRoute;
+
+/// 结构化 inbox 消息的 schema 版本（缺失为 0，即只有纯文本的旧消息）
+pub const INBOX_MESSAGE_VERSION: u32 = 1;
 
 /// Inbox 消息
+///
+/// 结构化字段（`type`、`task_ref`、`attachments`、`priority`）均可缺省，
+/// Claude Code 写入的纯文本消息和旧版本消息照常解析。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InboxMessage {
     pub from: String,
@@ -23,6 +33,157 @@ pub struct InboxMessage {
     pub color: Option<String>,
     #[serde(default)]
     pub read: bool,
+    /// 消息格式版本
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub schema_version: u32,
+    /// 消息类型
+    #[serde(rename = "type", default, skip_serializing_if = "MessageKind::is_text")]
+    pub kind: MessageKind,
+    /// 关联的任务 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_ref: Option<String>,
+    /// 附件（本机绝对路径）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<PathBuf>,
+    /// 优先级
+    #[serde(default, skip_serializing_if = "MessagePriority::is_normal")]
+    pub priority: MessagePriority,
+}
+
+fn is_zero(version: &u32) -> bool {
+    *version == 0
+}
+
+impl InboxMessage {
+    /// 当前版本的纯文本消息
+    pub fn new(from: &str, text: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            text: text.to_string(),
+            summary: None,
+            timestamp: Utc::now(),
+            color: None,
+            read: false,
+            schema_version: INBOX_MESSAGE_VERSION,
+            kind: MessageKind::Text,
+            task_ref: None,
+            attachments: Vec::new(),
+            priority: MessagePriority::Normal,
+        }
+    }
+
+    /// 附加结构化字段
+    pub fn with_fields(mut self, fields: MessageFields) -> Self {
+        self.kind = fields.kind;
+        self.task_ref = fields.task_ref;
+        self.attachments = fields.attachments;
+        self.priority = fields.priority;
+        self
+    }
+
+    /// 显示用的结构化标签，如 `[task #task-1] [high] [2 attachments]`
+    pub fn tags(&self) -> String {
+        let mut tags = Vec::new();
+        match (&self.kind, &self.task_ref) {
+            (MessageKind::Text, None) => {}
+            (kind, None) => tags.push(format!("[{}]", kind)),
+            (kind, Some(task_ref)) => tags.push(format!("[{} #{}]", kind, task_ref)),
+        }
+        if !self.priority.is_normal() {
+            tags.push(format!("[{}]", self.priority));
+        }
+        match self.attachments.len() {
+            0 => {}
+            1 => tags.push("[1 attachment]".to_string()),
+            n => tags.push(format!("[{} attachments]", n)),
+        }
+        tags.join(" ")
+    }
+}
+
+/// Inbox 消息类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    /// 普通文本
+    #[default]
+    Text,
+    /// 任务分配
+    Task,
+    /// 提问，需要对方回答
+    Question,
+    /// 对提问的回答
+    Answer,
+    /// 进度汇报
+    Status,
+    /// 任务结果
+    Result,
+    /// 请求评审
+    Review,
+    /// 新版本定义、当前版本不认识的类型
+    #[serde(other)]
+    #[value(skip)]
+    Other,
+}
+
+impl MessageKind {
+    pub fn is_text(&self) -> bool {
+        *self == Self::Text
+    }
+}
+
+impl std::fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Task => "task",
+            Self::Question => "question",
+            Self::Answer => "answer",
+            Self::Status => "status",
+            Self::Result => "result",
+            Self::Review => "review",
+            Self::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Inbox 消息优先级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MessagePriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl MessagePriority {
+    pub fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+}
+
+impl std::fmt::Display for MessagePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 发送时附加的结构化字段（MCP 参数直接反序列化为该结构）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MessageFields {
+    #[serde(rename = "type")]
+    pub kind: MessageKind,
+    pub task_ref: Option<String>,
+    pub attachments: Vec<PathBuf>,
+    pub priority: MessagePriority,
 }
 
 /// 特殊消息类型（通过 text 字段的 JSON 内容区分）
@@ -134,6 +295,62 @@ impl TeamBridge {
         self.get_team_dir(team).join("config.json")
     }
 
+    /// 读取 team 配置（不存在或无效时为 None）
+    pub fn get_team_config(&self, team: &str) -> Option<TeamConfig> {
+        discovery::load_team(&self.get_team_dir(team))
+    }
+
+    /// 获取 team 的 inbox 转发规则（未配置时为 None）
+    pub fn get_inbox_forward(&self, team: &str) -> Option<InboxForward> {
+        self.get_team_config(team)?.forward
+    }
+
+    /// 查找在该 tmux session 中运行的成员，返回 (team, 成员名)
+    pub fn find_member_by_session(&self, tmux_session: &str) -> Option<(String, String)> {
+        self.list_teams().into_iter().find_map(|team| {
+            let member = self
+                .get_team_config(&team)?
+                .members
+                .into_iter()
+                .find(|m| m.tmux_pane_id.as_deref() == Some(tmux_session))?;
+            Some((team, member.name))
+        })
+    }
+
+    /// 成员的通知投递目标（`camRoutes`）
+    ///
+    /// `agent_id` 为 `{member}@{team}`（inbox 转发）或成员运行所在的 tmux session。
+    pub fn member_route(&self, agent_id: &str) -> Option<WebhookRoute> {
+        let (team, member) = match AgentId::parse(agent_id) {
+            Some(id) => (id.team, id.name),
+            None => self.find_member_by_session(agent_id)?,
+        };
+        self.get_team_config(&team)?.routes.remove(&member)
+    }
+
+    /// 启用成员 worktree 隔离（写入 config.json 的 `camWorktree`）
+    pub fn set_worktree_config(&self, team: &str, worktree: &WorktreeConfig) -> Result<()> {
+        let config_path = self.get_config_path(team);
+        if !config_path.exists() {
+            return Err(anyhow!("Team '{}' does not exist", team));
+        }
+        let content = fs::read_to_string(&config_path)?;
+        let mut config: serde_json::Value = serde_json::from_str(&content)?;
+        config["camWorktree"] = serde_json::to_value(worktree)?;
+        fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
+        Ok(())
+    }
+
+    /// 读取成员与 worktree 的映射
+    pub fn load_worktrees(&self, team: &str) -> Vec<MemberWorktree> {
+        worktree::load_worktrees(&self.get_team_dir(team))
+    }
+
+    /// 保存成员与 worktree 的映射
+    pub fn save_worktrees(&self, team: &str, worktrees: &[MemberWorktree]) -> Result<()> {
+        worktree::save_worktrees(&self.get_team_dir(team), worktrees)
+    }
+
     /// 获取 team inboxes 目录路径
     fn get_inboxes_dir(&self, team: &str) -> PathBuf {
         self.get_team_dir(team).join("inboxes")
@@ -175,6 +392,9 @@ impl TeamBridge {
             lead_agent_id: None,
             created_at: Some(Utc::now().timestamp_millis() as u64),
             members: Vec::new(),
+            forward: None,
+            worktree: None,
+            routes: HashMap::new(),
         };
 
         // 写入配置文件（包含 project_path）
@@ -275,7 +495,9 @@ impl TeamBridge {
     }
 
     /// 发送消息到成员 inbox
-    pub fn send_to_inbox(&self, team: &str, member: &str, message: InboxMessage) -> Result<()> {
+    ///
+    /// 附件必须存在，写入前转换为绝对路径，接收方无需知道发送方的工作目录。
+    pub fn send_to_inbox(&self, team: &str, member: &str, mut message: InboxMessage) -> Result<()> {
         let inbox_path = self.get_inbox_path(team, member);
 
         // 确保 inboxes 目录存在
@@ -284,6 +506,11 @@ impl TeamBridge {
             return Err(anyhow!("Team '{}' does not exist", team));
         }
 
+        for attachment in &mut message.attachments {
+            *attachment = fs::canonicalize(&*attachment)
+                .map_err(|_| anyhow!("Attachment not found: {}", attachment.display()))?;
+        }
+
         // 读取现有消息
         let mut messages: Vec<InboxMessage> = if inbox_path.exists() {
             let content = fs::read_to_string(&inbox_path)?;
@@ -347,6 +574,68 @@ impl TeamBridge {
         Ok(marked_count)
     }
 
+    /// 获取成员归档日志路径
+    fn get_archive_path(&self, team: &str, member: &str) -> PathBuf {
+        self.get_team_dir(team)
+            .join("archive")
+            .join(format!("{}.jsonl", member))
+    }
+
+    /// 按保留策略清理成员 inbox，移出的消息追加到归档日志
+    pub fn prune_inbox(
+        &self,
+        team: &str,
+        member: &str,
+        policy: &InboxRetention,
+    ) -> Result<PruneResult> {
+        let inbox_path = self.get_inbox_path(team, member);
+
+        if !inbox_path.exists() {
+            return Ok(PruneResult {
+                member: member.to_string(),
+                archived: 0,
+                kept: 0,
+            });
+        }
+
+        let content = fs::read_to_string(&inbox_path)?;
+        let messages: Vec<InboxMessage> = serde_json::from_str(&content)?;
+        let (kept, archived) = policy.partition(messages, Utc::now());
+
+        if !archived.is_empty() {
+            // 先写归档再改写 inbox，中途失败时消息最多重复、不会丢失
+            retention::append_archive(&self.get_archive_path(team, member), &archived)?;
+            fs::write(&inbox_path, serde_json::to_string_pretty(&kept)?)?;
+        }
+
+        Ok(PruneResult {
+            member: member.to_string(),
+            archived: archived.len(),
+            kept: kept.len(),
+        })
+    }
+
+    /// 按保留策略清理 Team 所有 inbox
+    pub fn prune_team(&self, team: &str, policy: &InboxRetention) -> Result<Vec<PruneResult>> {
+        let inboxes_dir = self.get_inboxes_dir(team);
+        if !inboxes_dir.exists() {
+            return Err(anyhow!("Team '{}' does not exist", team));
+        }
+
+        let mut members: Vec<String> = fs::read_dir(&inboxes_dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| path.file_stem()?.to_str().map(String::from))
+            .collect();
+        members.sort();
+
+        members
+            .iter()
+            .map(|member| self.prune_inbox(team, member, policy))
+            .collect()
+    }
+
     /// 获取 Team 完整状态
     pub fn get_team_status(&self, team: &str) -> Result<TeamStatus> {
         let config_path = self.get_config_path(team);
@@ -593,6 +882,30 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("already exists"));
     }
 
+    #[test]
+    fn test_find_member_by_session() {
+        let (bridge, _temp) = create_test_bridge();
+
+        bridge.create_team("test-team", "Test", "/path").unwrap();
+        let member = TeamMember {
+            name: "developer".to_string(),
+            agent_id: "developer@test-team".to_string(),
+            agent_type: "general-purpose".to_string(),
+            model: None,
+            color: None,
+            is_active: Some(true),
+            tmux_pane_id: Some("cam-123".to_string()),
+            cwd: None,
+        };
+        bridge.spawn_member("test-team", member).unwrap();
+
+        assert_eq!(
+            bridge.find_member_by_session("cam-123"),
+            Some(("test-team".to_string(), "developer".to_string()))
+        );
+        assert_eq!(bridge.find_member_by_session("cam-456"), None);
+    }
+
     #[test]
     fn test_send_to_inbox() {
         let (bridge, _temp) = create_test_bridge();
@@ -600,12 +913,9 @@ mod tests {
         bridge.create_team("test-team", "Test", "/path").unwrap();
 
         let message = InboxMessage {
-            from: "team-lead".to_string(),
-            text: "Hello, developer!".to_string(),
             summary: Some("Greeting".to_string()),
-            timestamp: Utc::now(),
             color: Some("red".to_string()),
-            read: false,
+            ..InboxMessage::new("team-lead", "Hello, developer!")
         };
 
         let result = bridge.send_to_inbox("test-team", "developer", message);
@@ -618,18 +928,127 @@ mod tests {
         assert_eq!(messages[0].text, "Hello, developer!");
     }
 
+    #[test]
+    fn test_structured_inbox_message() {
+        let (bridge, temp) = create_test_bridge();
+        bridge.create_team("test-team", "Test", "/path").unwrap();
+        let spec = temp.path().join("spec.md");
+        fs::write(&spec, "# spec").unwrap();
+
+        let message = InboxMessage::new("team-lead", "请按 spec 实现").with_fields(MessageFields {
+            kind: MessageKind::Task,
+            task_ref: Some("task-1".to_string()),
+            attachments: vec![spec.clone()],
+            priority: MessagePriority::High,
+        });
+        assert_eq!(message.tags(), "[task #task-1] [high] [1 attachment]");
+        bridge
+            .send_to_inbox("test-team", "developer", message)
+            .unwrap();
+
+        let messages = bridge.read_inbox("test-team", "developer").unwrap();
+        assert_eq!(messages[0].schema_version, INBOX_MESSAGE_VERSION);
+        assert_eq!(messages[0].kind, MessageKind::Task);
+        assert_eq!(messages[0].task_ref.as_deref(), Some("task-1"));
+        assert_eq!(
+            messages[0].attachments,
+            vec![fs::canonicalize(&spec).unwrap()]
+        );
+        assert_eq!(messages[0].priority, MessagePriority::High);
+
+        let missing = InboxMessage::new("team-lead", "x").with_fields(MessageFields {
+            attachments: vec![temp.path().join("missing.md")],
+            ..MessageFields::default()
+        });
+        let err = bridge
+            .send_to_inbox("test-team", "developer", missing)
+            .unwrap_err();
+        assert!(err.to_string().contains("Attachment not found"));
+    }
+
+    #[test]
+    fn test_legacy_inbox_message_compat() {
+        // 旧版本 / Claude Code 写入的纯文本消息
+        let legacy: InboxMessage = serde_json::from_str(
+            r#"{"from":"developer","text":"done","timestamp":"2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.schema_version, 0);
+        assert_eq!(legacy.kind, MessageKind::Text);
+        assert!(legacy.attachments.is_empty());
+        assert_eq!(legacy.tags(), "");
+
+        // 纯文本消息不写出结构化字段
+        let json = serde_json::to_value(InboxMessage::new("cam", "hi")).unwrap();
+        assert!(json.get("type").is_none());
+        assert!(json.get("priority").is_none());
+        assert_eq!(json["schema_version"], INBOX_MESSAGE_VERSION);
+
+        // 未来版本的消息类型不影响读取
+        let future: InboxMessage = serde_json::from_str(
+            r#"{"from":"a","text":"t","timestamp":"2026-01-01T00:00:00Z","schema_version":2,"type":"handoff"}"#,
+        )
+        .unwrap();
+        assert_eq!(future.kind, MessageKind::Other);
+    }
+
+    #[test]
+    fn test_prune_inbox() {
+        let (bridge, temp) = create_test_bridge();
+        bridge.create_team("test-team", "Test", "/path").unwrap();
+        for i in 0..5 {
+            bridge
+                .send_to_inbox(
+                    "test-team",
+                    "developer",
+                    InboxMessage::new("sender", &format!("Message {}", i)),
+                )
+                .unwrap();
+        }
+        bridge.mark_as_read("test-team", "developer").unwrap();
+        bridge
+            .send_to_inbox("test-team", "developer", InboxMessage::new("sender", "new"))
+            .unwrap();
+
+        let policy = InboxRetention {
+            max_messages: 2,
+            max_age_days: 7,
+        };
+        let results = bridge.prune_team("test-team", &policy).unwrap();
+        assert_eq!(
+            results,
+            vec![PruneResult {
+                member: "developer".to_string(),
+                archived: 3,
+                kept: 3,
+            }]
+        );
+
+        let texts: Vec<String> = bridge
+            .read_inbox("test-team", "developer")
+            .unwrap()
+            .into_iter()
+            .map(|m| m.text)
+            .collect();
+        assert_eq!(texts, ["Message 3", "Message 4", "new"]);
+
+        let archive = temp
+            .path()
+            .join("teams/test-team/archive")
+            .join("developer.jsonl");
+        assert_eq!(fs::read_to_string(archive).unwrap().lines().count(), 3);
+
+        // 再次清理无变化
+        let results = bridge.prune_team("test-team", &policy).unwrap();
+        assert_eq!(results[0].archived, 0);
+        assert!(bridge.prune_team("nonexistent", &policy).is_err());
+    }
+
     #[test]
     fn test_send_to_inbox_nonexistent_team() {
         let (bridge, _temp) = create_test_bridge();
 
-        let message = InboxMessage {
-            from: "sender".to_string(),
-            text: "test".to_string(),
-            summary: None,
-            timestamp: Utc::now(),
-            color: None,
-            read: false,
-        };
+        let message = InboxMessage::new("sender", "test");
 
         let result = bridge.send_to_inbox("nonexistent", "member", message);
         assert!(result.is_err());
@@ -661,14 +1080,7 @@ mod tests {
 
         // 发送两条消息
         for i in 0..2 {
-            let message = InboxMessage {
-                from: "sender".to_string(),
-                text: format!("Message {}", i),
-                summary: None,
-                timestamp: Utc::now(),
-                color: None,
-                read: false,
-            };
+            let message = InboxMessage::new("sender", &format!("Message {}", i));
             bridge
                 .send_to_inbox("test-team", "developer", message)
                 .unwrap();
@@ -708,14 +1120,7 @@ mod tests {
         bridge.spawn_member("test-team", member).unwrap();
 
         // 发送一条未读消息
-        let message = InboxMessage {
-            from: "sender".to_string(),
-            text: "test".to_string(),
-            summary: None,
-            timestamp: Utc::now(),
-            color: None,
-            read: false,
-        };
+        let message = InboxMessage::new("sender", "test");
         bridge
             .send_to_inbox("test-team", "developer", message)
             .unwrap();
@@ -809,14 +1214,10 @@ mod tests {
                 let bridge = Arc::clone(&bridge);
                 thread::spawn(move || {
                     for msg_id in 0..messages_per_thread {
-                        let message = InboxMessage {
-                            from: format!("thread-{}", thread_id),
-                            text: format!("Message {} from thread {}", msg_id, thread_id),
-                            summary: None,
-                            timestamp: Utc::now(),
-                            color: None,
-                            read: false,
-                        };
+                        let message = InboxMessage::new(
+                            &format!("thread-{}", thread_id),
+                            &format!("Message {} from thread {}", msg_id, thread_id),
+                        );
                         // This should not fail or corrupt data
                         bridge
                             .send_to_inbox("concurrent-test", "developer", message)
@@ -3,7 +3,11 @@
 //! Claude Code Agent Teams 将配置存储在 `~/.claude/teams/{team-name}/config.json`
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::worktree::WorktreeConfig;
+use crate::notification::webhook::WebhookRoute;
 
 /// Team 配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +20,67 @@ pub struct TeamConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_at: Option<u64>,
     pub members: Vec<TeamMember>,
+    /// inbox 消息转发规则（config.json 的 `camForward`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward: Option<InboxForward>,
+    /// 成员 worktree 隔离（config.json 的 `camWorktree`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<WorktreeConfig>,
+    /// 按成员覆盖通知投递目标（config.json 的 `camRoutes`，键为成员名）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub routes: HashMap<String, WebhookRoute>,
+}
+
+/// Inbox 消息转发规则
+///
+/// 匹配的消息除了 InboxWatcher 自己的判断外，还会以 `{from}@{team}` 身份
+/// 发送到常规通知管道，例如成员写下 "BLOCKED" 或 "@human" 时提醒用户。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboxForward {
+    /// 消息包含任意关键词（不区分大小写）时转发
+    #[serde(default = "default_forward_keywords")]
+    pub keywords: Vec<String>,
+    /// 只转发这些成员发出的消息，为空时不限制
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub from: Vec<String>,
+    /// 紧急程度：`high`（默认）或 `medium`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub urgency: Option<String>,
+}
+
+fn default_forward_keywords() -> Vec<String> {
+    vec!["BLOCKED".to_string(), "@human".to_string()]
+}
+
+impl Default for InboxForward {
+    fn default() -> Self {
+        Self {
+            keywords: default_forward_keywords(),
+            from: Vec::new(),
+            urgency: None,
+        }
+    }
+}
+
+impl InboxForward {
+    /// 消息是否需要转发
+    pub fn matches(&self, from: &str, text: &str) -> bool {
+        if !self.from.is_empty() && !self.from.iter().any(|m| m == from) {
+            return false;
+        }
+        let text = text.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|keyword| !keyword.is_empty() && text.contains(&keyword.to_lowercase()))
+    }
+
+    /// 转发通知的类型，决定紧急程度（`team_escalation` 为 HIGH，`team_message` 为 MEDIUM）
+    pub fn notification_type(&self) -> &'static str {
+        match self.urgency.as_deref().map(str::to_lowercase).as_deref() {
+            Some("medium") => "team_message",
+            _ => "team_escalation",
+        }
+    }
 }
 
 /// Team 成员
@@ -71,13 +136,7 @@ pub fn discover_teams() -> Vec<TeamConfig> {
 
                 if let Some(name) = team_name {
                     if let Some(config) = load_team_config(&path) {
-                        teams.push(TeamConfig {
-                            team_name: name,
-                            description: config.description,
-                            lead_agent_id: config.lead_agent_id,
-                            created_at: config.created_at,
-                            members: config.members,
-                        });
+                        teams.push(config.into_team_config(name));
                     }
                 }
             }
@@ -99,6 +158,12 @@ pub fn get_team_members(team_name: &str) -> Option<Vec<TeamMember>> {
     load_team_config(&team_path).map(|c| c.members)
 }
 
+/// 从 team 目录读取配置（team 名称取目录名）
+pub fn load_team(team_dir: &Path) -> Option<TeamConfig> {
+    let name = team_dir.file_name()?.to_str()?.to_string();
+    load_team_config(team_dir).map(|config| config.into_team_config(name))
+}
+
 /// 获取指定 team 的活跃成员
 pub fn get_active_team_members(team_name: &str) -> Option<Vec<TeamMember>> {
     get_team_members(team_name).map(|members| {
@@ -110,7 +175,7 @@ pub fn get_active_team_members(team_name: &str) -> Option<Vec<TeamMember>> {
 }
 
 /// 从目录加载 team 配置
-fn load_team_config(team_dir: &PathBuf) -> Option<TeamConfigFile> {
+fn load_team_config(team_dir: &Path) -> Option<TeamConfigFile> {
     let config_path = team_dir.join("config.json");
 
     if !config_path.exists() {
@@ -132,6 +197,27 @@ struct TeamConfigFile {
     created_at: Option<u64>,
     #[serde(default)]
     members: Vec<TeamMember>,
+    #[serde(default, rename = "camForward")]
+    forward: Option<InboxForward>,
+    #[serde(default, rename = "camWorktree")]
+    worktree: Option<WorktreeConfig>,
+    #[serde(default, rename = "camRoutes")]
+    routes: HashMap<String, WebhookRoute>,
+}
+
+impl TeamConfigFile {
+    fn into_team_config(self, team_name: String) -> TeamConfig {
+        TeamConfig {
+            team_name,
+            description: self.description,
+            lead_agent_id: self.lead_agent_id,
+            created_at: self.created_at,
+            members: self.members,
+            forward: self.forward,
+            worktree: self.worktree,
+            routes: self.routes,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -213,5 +299,51 @@ mod tests {
         assert_eq!(config.created_at, Some(1770481532431));
         assert_eq!(config.members[0].model, Some("claude-opus-4-6".to_string()));
         assert_eq!(config.members[0].tmux_pane_id, Some("%1".to_string()));
+        assert!(config.forward.is_none());
+    }
+
+    #[test]
+    fn test_inbox_forward() {
+        let json = r#"{
+            "members": [],
+            "camForward": { "from": ["developer"], "urgency": "medium" }
+        }"#;
+        let config: TeamConfigFile = serde_json::from_str(json).unwrap();
+        let forward = config.forward.unwrap();
+        assert_eq!(forward.keywords, vec!["BLOCKED", "@human"]);
+        assert_eq!(forward.notification_type(), "team_message");
+
+        assert!(forward.matches("developer", "blocked on missing API key"));
+        assert!(forward.matches("developer", "需要 @Human 确认"));
+        assert!(!forward.matches("tester", "BLOCKED"));
+        assert!(!forward.matches("developer", "all tests pass"));
+
+        let all = InboxForward {
+            keywords: vec!["deploy".to_string()],
+            ..Default::default()
+        };
+        assert!(all.matches("anyone", "ready to DEPLOY"));
+        assert_eq!(all.notification_type(), "team_escalation");
+    }
+
+    #[test]
+    fn test_member_routes() {
+        let json = r#"{
+            "members": [],
+            "camRoutes": {
+                "frontend": { "channel": "telegram", "to": "alice" },
+                "backend": { "to": "bob" }
+            }
+        }"#;
+        let config =
+            TeamConfigFile::deserialize(serde_json::from_str::<serde_json::Value>(json).unwrap())
+                .unwrap()
+                .into_team_config("app".to_string());
+        assert_eq!(
+            config.routes["frontend"].channel.as_deref(),
+            Some("telegram")
+        );
+        assert_eq!(config.routes["backend"].to.as_deref(), Some("bob"));
+        assert!(config.routes["backend"].channel.is_none());
     }
 }
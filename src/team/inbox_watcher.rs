@@ -2,15 +2,28 @@
 //!
 //! 监控 `~/.claude/teams/{team-name}/inboxes/` 目录，
 //! 检测新消息并根据过滤规则触发通知。
+//!
+//! Team 配置了 `camForward` 时，匹配的消息（如包含 "BLOCKED"、"@human"）
+//! 以 `{from}@{team}` 身份转发到常规通知管道，不再走内部的 NotifyDecision。
 
 use anyhow::Result;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info};
 
-use super::bridge::{InboxMessage, SpecialMessage, TeamBridge};
+use super::bridge::{InboxMessage, MessagePriority, SpecialMessage, TeamBridge};
+use super::discovery::InboxForward;
+use super::retention::InboxRetention;
+use crate::infra::text::truncate_width;
 use crate::notification::openclaw::OpenclawNotifier;
+use crate::notification::NotificationEvent;
+
+/// 转发消息正文的最大显示宽度
+const FORWARD_TEXT_WIDTH: usize = 200;
+
+/// 按保留策略清理 inbox 的间隔
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
 
 /// 通知紧急程度
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -53,6 +66,10 @@ pub struct InboxWatcher {
     last_message_count: HashMap<PathBuf, usize>,
     /// 轮询间隔
     poll_interval: Duration,
+    /// inbox 保留策略
+    retention: InboxRetention,
+    /// 每个 Team 上次清理 inbox 的时间
+    last_pruned: HashMap<String, Instant>,
 }
 
 impl InboxWatcher {
@@ -64,6 +81,8 @@ impl InboxWatcher {
             last_modified: HashMap::new(),
             last_message_count: HashMap::new(),
             poll_interval: Duration::from_secs(2),
+            retention: InboxRetention::load(),
+            last_pruned: HashMap::new(),
         }
     }
 
@@ -75,6 +94,8 @@ impl InboxWatcher {
             last_modified: HashMap::new(),
             last_message_count: HashMap::new(),
             poll_interval: Duration::from_secs(2),
+            retention: InboxRetention::load(),
+            last_pruned: HashMap::new(),
         }
     }
 
@@ -124,6 +145,9 @@ impl InboxWatcher {
             return Ok(new_messages);
         }
 
+        let forward = self.team_bridge.get_inbox_forward(team);
+        self.prune_if_due(team, &inboxes_dir);
+
         // 遍历所有 inbox 文件
         for entry in std::fs::read_dir(&inboxes_dir)?.flatten() {
             let path = entry.path();
@@ -150,7 +174,12 @@ impl InboxWatcher {
                                     let new_msgs: Vec<_> = messages[last_count..].to_vec();
 
                                     // 处理新消息
-                                    self.process_new_messages(team, &member, &new_msgs)?;
+                                    self.process_new_messages(
+                                        team,
+                                        &member,
+                                        &new_msgs,
+                                        forward.as_ref(),
+                                    )?;
 
                                     new_messages.push((member.clone(), new_msgs));
                                 }
@@ -168,14 +197,56 @@ impl InboxWatcher {
         Ok(new_messages)
     }
 
+    /// 距上次清理超过 [`PRUNE_INTERVAL`] 时按保留策略清理 Team inbox
+    ///
+    /// 清理只移出已读消息，新到的未读消息总在文件末尾，因此把已记录的消息数减去
+    /// 移出的数量即可继续识别新消息。
+    fn prune_if_due(&mut self, team: &str, inboxes_dir: &Path) {
+        if self
+            .last_pruned
+            .get(team)
+            .is_some_and(|at| at.elapsed() < PRUNE_INTERVAL)
+        {
+            return;
+        }
+        self.last_pruned.insert(team.to_string(), Instant::now());
+
+        match self.team_bridge.prune_team(team, &self.retention) {
+            Ok(results) => {
+                for result in results.iter().filter(|r| r.archived > 0) {
+                    info!(team = %team, member = %result.member, archived = result.archived, "Archived inbox messages");
+                    let path = inboxes_dir.join(format!("{}.json", result.member));
+                    if let Some(count) = self.last_message_count.get_mut(&path) {
+                        *count = count.saturating_sub(result.archived);
+                    }
+                }
+            }
+            Err(e) => debug!(team = %team, error = %e, "Failed to prune team inboxes"),
+        }
+    }
+
     /// 处理新消息
     fn process_new_messages(
         &self,
         team: &str,
         member: &str,
         messages: &[InboxMessage],
+        forward: Option<&InboxForward>,
     ) -> Result<()> {
         for msg in messages {
+            if let Some(event) = forward.and_then(|rule| forward_event(team, member, msg, rule)) {
+                info!(team = %team, member = %member, from = %msg.from, "Forwarding inbox message");
+                if let Err(e) = self.notifier.send_notification_event(&event) {
+                    error!(
+                        team = %team,
+                        member = %member,
+                        error = %e,
+                        "Failed to forward inbox message"
+                    );
+                }
+                continue;
+            }
+
             let decision = self.should_notify(msg);
 
             match decision {
@@ -260,6 +331,19 @@ impl InboxWatcher {
             };
         }
 
+        // 发送方标记为高优先级的消息
+        if message.priority == MessagePriority::High {
+            return NotifyDecision::Notify {
+                urgency: Urgency::High,
+                summary: format!(
+                    "{} {}: {}",
+                    message.from,
+                    message.tags(),
+                    truncate_text(&message.text, 50)
+                ),
+            };
+        }
+
         // 检查是否包含错误关键词
         let text_lower = message.text.to_lowercase();
         if text_lower.contains("error")
@@ -356,6 +440,27 @@ pub struct PendingPermissionRequest {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// 按转发规则构建通知事件，不匹配时返回 None
+pub fn forward_event(
+    team: &str,
+    member: &str,
+    message: &InboxMessage,
+    forward: &InboxForward,
+) -> Option<NotificationEvent> {
+    if !forward.matches(&message.from, &message.text) {
+        return None;
+    }
+    let text = truncate_width(message.text.trim(), FORWARD_TEXT_WIDTH);
+    Some(
+        NotificationEvent::notification(
+            format!("{}@{}", message.from, team),
+            forward.notification_type(),
+            format!("[{}] {} → {}: {}", team, message.from, member, text),
+        )
+        .with_skip_dedup(true),
+    )
+}
+
 /// 截断文本
 fn truncate_text(text: &str, max_len: usize) -> String {
     if text.len() <= max_len {
@@ -368,17 +473,11 @@ fn truncate_text(text: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use crate::notification::NotificationEventType;
+    use crate::team::bridge::MessageKind;
 
     fn create_test_message(from: &str, text: &str) -> InboxMessage {
-        InboxMessage {
-            from: from.to_string(),
-            text: text.to_string(),
-            summary: None,
-            timestamp: Utc::now(),
-            color: None,
-            read: false,
-        }
+        InboxMessage::new(from, text)
     }
 
     #[test]
@@ -460,6 +559,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_should_notify_high_priority() {
+        let notifier = OpenclawNotifier::new();
+        let watcher = InboxWatcher::new(notifier);
+
+        let msg = InboxMessage {
+            kind: MessageKind::Review,
+            task_ref: Some("task-7".to_string()),
+            priority: MessagePriority::High,
+            ..create_test_message("developer", "登录模块改好了")
+        };
+
+        match watcher.should_notify(&msg) {
+            NotifyDecision::Notify { urgency, summary } => {
+                assert_eq!(urgency, Urgency::High);
+                assert_eq!(summary, "developer [review #task-7] [high]: 登录模块改好了");
+            }
+            _ => panic!("Expected Notify decision for high priority"),
+        }
+    }
+
     #[test]
     fn test_should_notify_chinese_error() {
         let notifier = OpenclawNotifier::new();
@@ -524,6 +644,40 @@ mod tests {
         assert_eq!(decision, NotifyDecision::Silent);
     }
 
+    #[test]
+    fn test_forward_event_from_team_config() {
+        let temp = tempfile::tempdir().unwrap();
+        let bridge = TeamBridge::new_with_base_dir(temp.path().to_path_buf());
+        bridge
+            .create_team("web", "Web team", "/workspace/web")
+            .unwrap();
+        assert!(bridge.get_inbox_forward("web").is_none());
+
+        let config_path = temp.path().join("teams/web/config.json");
+        let mut config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        config["camForward"] = serde_json::json!({ "keywords": ["BLOCKED"] });
+        std::fs::write(&config_path, config.to_string()).unwrap();
+        let forward = bridge.get_inbox_forward("web").unwrap();
+
+        let msg = create_test_message("developer", "BLOCKED: need database credentials");
+        let event = forward_event("web", "team-lead", &msg, &forward).unwrap();
+        assert_eq!(event.agent_id, "developer@web");
+        match event.event_type {
+            NotificationEventType::Notification {
+                notification_type,
+                message,
+            } => {
+                assert_eq!(notification_type, "team_escalation");
+                assert!(message.starts_with("[web] developer → team-lead: BLOCKED"));
+            }
+            other => panic!("unexpected event type: {:?}", other),
+        }
+
+        let msg = create_test_message("developer", "任务完成");
+        assert!(forward_event("web", "team-lead", &msg, &forward).is_none());
+    }
+
     #[test]
     fn test_truncate_text() {
         assert_eq!(truncate_text("short", 10), "short");
@@ -9,24 +9,36 @@
 //! - `orchestrator` - Agent 编排和任务分配
 //! - `inbox_watcher` - Inbox 目录监控和通知触发
 //! - `task_list` - 任务列表管理
+//! - `progress_history` - 进度快照和燃尽趋势
+//! - `retention` - Inbox 保留策略和消息归档
+//! - `worktree` - 成员 git worktree 隔离和分支合并
 //!
 //! ## 数据存储
 //!
 //! Team 数据存储在 `~/.claude/teams/{team-name}/` 目录：
 //! - `config.json` - Team 配置和成员列表
 //! - `inboxes/{member-name}.json` - 成员 inbox 消息
+//! - `archive/{member-name}.jsonl` - 按保留策略移出 inbox 的消息
 
 pub mod bridge;
 pub mod discovery;
 pub mod inbox_watcher;
 pub mod orchestrator;
+pub mod progress_history;
+pub mod retention;
 pub mod task_list;
+pub mod worktree;
 
 // Re-export commonly used types
-pub use bridge::{AgentId, InboxMessage, SpecialMessage, TeamBridge};
+pub use bridge::{
+    AgentId, InboxMessage, MessageFields, MessageKind, MessagePriority, SpecialMessage, TeamBridge,
+};
 pub use discovery::{
-    discover_teams, get_active_team_members, get_team_members, TeamConfig, TeamMember,
+    discover_teams, get_active_team_members, get_team_members, InboxForward, TeamConfig, TeamMember,
 };
 pub use inbox_watcher::{InboxWatcher, NotifyDecision, Urgency};
 pub use orchestrator::{SpawnResult, TeamOrchestrator, TeamProgress};
+pub use progress_history::{Burndown, ProgressHistory, ProgressSample};
+pub use retention::{InboxRetention, PruneResult};
 pub use task_list::{get_task, list_tasks, list_team_names, update_task_status, Task, TaskStatus};
+pub use worktree::{MemberMerge, MemberWorktree, MergeOutcome, WorktreeConfig};
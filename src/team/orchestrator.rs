@@ -9,13 +9,19 @@
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use super::bridge::{InboxMessage, TeamBridge};
+use super::bridge::{InboxMessage, MessageKind, TeamBridge};
 use super::discovery::TeamMember;
+use super::worktree::{create_member_worktree, merge_member_branches, MemberMerge, WorktreeConfig};
+use crate::agent::idle::IdleState;
 use crate::agent::{AgentManager, StartAgentRequest};
+use crate::cli::sessions::parse_since;
 use crate::infra::input::InputWaitDetector;
-use crate::session::state::{ConversationStateManager, ReplyResult};
+use crate::notification::SnoozeState;
+use crate::session::reply_macro;
+use crate::session::state::{AckResult, ConversationStateManager, ReplyResult};
+use crate::session::{ReplyAuthConfig, ReplySender};
 
 /// Team 中 Agent 的启动结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +77,11 @@ pub struct TaskAssignmentResult {
     pub assigned_to: String,
 }
 
+/// 聊天中 `snooze` 未指定时长时的默认暂停时间（秒）
+const DEFAULT_SNOOZE_SECS: i64 = 30 * 60;
+/// 聊天中 `logs` 返回的终端行数
+const CHAT_LOG_LINES: u32 = 30;
+
 /// 用户意图
 #[derive(Debug, Clone, PartialEq)]
 pub enum UserIntent {
@@ -93,10 +104,45 @@ pub enum UserIntent {
     Reject,
     /// 选择选项
     SelectOption(usize),
+    /// 确认已看到通知（停止提醒）
+    Acknowledge {
+        target: Option<String>,
+    },
     /// 关闭 Team
     ShutdownTeam {
         team: String,
     },
+    /// 暂停提醒（`snooze 30m [agent]`），`secs` 为 None 时恢复（`snooze off`）
+    Snooze {
+        secs: Option<i64>,
+        target: Option<String>,
+    },
+    /// agent 与待处理确认概览
+    Status,
+    /// 列出 agent
+    ListAgents,
+    /// 查看 agent 终端输出
+    Logs {
+        agent_id: String,
+    },
+    /// 停止 agent（未确认时只返回确认提示）
+    Kill {
+        agent_id: String,
+        confirmed: bool,
+    },
+    /// 回复空闲建议 `/kill [agent]`：停止建议关闭的 agent（默认最近一条建议）
+    KillIdle {
+        target: Option<String>,
+    },
+    /// 回复空闲建议 `/keep [agent]`：保留 agent，不再建议关闭
+    KeepIdle {
+        target: Option<String>,
+    },
+    /// 执行回复宏（`@approve-tests [agent]`）
+    ReplyMacro {
+        name: String,
+        target: Option<String>,
+    },
     /// 未知意图
     Unknown(String),
 }
@@ -105,6 +151,8 @@ pub enum UserIntent {
 pub struct TeamOrchestrator {
     team_bridge: TeamBridge,
     agent_manager: AgentManager,
+    /// 聊天命令授权（config.json 的 `reply_auth`），None 表示不限制
+    reply_auth: Option<ReplyAuthConfig>,
 }
 
 impl TeamOrchestrator {
@@ -113,6 +161,7 @@ impl TeamOrchestrator {
         Self {
             team_bridge: TeamBridge::new(),
             agent_manager: AgentManager::new(),
+            reply_auth: ReplyAuthConfig::load(),
         }
     }
 
@@ -121,11 +170,30 @@ impl TeamOrchestrator {
         Self {
             team_bridge: TeamBridge::new_with_base_dir(base_dir),
             agent_manager: AgentManager::new_for_test(),
+            reply_auth: None,
         }
     }
 
+    /// 使用指定的聊天命令授权配置
+    pub fn with_reply_auth(mut self, reply_auth: Option<ReplyAuthConfig>) -> Self {
+        self.reply_auth = reply_auth;
+        self
+    }
+
+    /// 按 `reply_auth` 的 HIGH 列表检查停止 agent、静音提醒等聊天命令，拒绝时返回原因
+    fn authorize_command(&self, command: &str, sender: ReplySender<'_>) -> Option<String> {
+        let reason = self
+            .reply_auth
+            .as_ref()?
+            .authorize_command(command, sender)
+            .err()?;
+        warn!(command, sender = ?sender.id(), reason = %reason, "Chat command denied");
+        Some(reason)
+    }
+
     /// 在 Team 中启动 Agent
     ///
+    /// - 配置了 `camWorktree` 时创建成员的 worktree 和分支，在其中启动
     /// - 创建 tmux session
     /// - 注册到 team config.json 的 members 数组
     /// - 返回 SpawnResult
@@ -151,7 +219,16 @@ impl TeamOrchestrator {
 
         // 获取 team 状态以获取 project_path
         let status = self.team_bridge.get_team_status(team)?;
-        let project_path = status.project_path.unwrap_or_else(|| ".".to_string());
+        let mut project_path = status.project_path.unwrap_or_else(|| ".".to_string());
+
+        // 配置了 camWorktree 时在成员自己的 worktree 中启动
+        if let Some(config) = self
+            .team_bridge
+            .get_team_config(team)
+            .and_then(|config| config.worktree)
+        {
+            project_path = self.member_worktree(team, name, &project_path, &config)?;
+        }
 
         // 使用 AgentManager 启动 agent
         let response = self.agent_manager.start_agent(StartAgentRequest {
@@ -161,6 +238,7 @@ impl TeamOrchestrator {
             initial_prompt: initial_prompt.map(|s| s.to_string()),
             agent_id: None,
             tmux_session: None,
+            container: None,
         })?;
 
         // 创建 TeamMember 并注册到 team
@@ -192,6 +270,56 @@ impl TeamOrchestrator {
         })
     }
 
+    /// 成员的 worktree 路径（已记录时复用，否则创建并记录）
+    fn member_worktree(
+        &self,
+        team: &str,
+        member: &str,
+        project_path: &str,
+        config: &WorktreeConfig,
+    ) -> Result<String> {
+        let mut worktrees = self.team_bridge.load_worktrees(team);
+        if let Some(existing) = worktrees.iter().find(|w| w.member == member) {
+            if std::path::Path::new(&existing.path).exists() {
+                return Ok(existing.path.clone());
+            }
+        }
+
+        let worktree =
+            create_member_worktree(std::path::Path::new(project_path), team, member, config)?;
+        info!(
+            team = %team,
+            member = %member,
+            branch = %worktree.branch,
+            path = %worktree.path,
+            "Created member worktree"
+        );
+        let path = worktree.path.clone();
+        worktrees.retain(|w| w.member != member);
+        worktrees.push(worktree);
+        self.team_bridge.save_worktrees(team, &worktrees)?;
+        Ok(path)
+    }
+
+    /// 依次合并成员分支到项目当前分支（`cam team-merge`）
+    ///
+    /// 返回项目路径和每个成员的合并结果；冲突的成员已中止合并，由调用方发送通知。
+    pub fn merge_team(&self, team: &str) -> Result<(String, Vec<MemberMerge>)> {
+        let status = self.team_bridge.get_team_status(team)?;
+        let project_path = status
+            .project_path
+            .ok_or_else(|| anyhow!("Team '{}' 没有项目路径", team))?;
+        let worktrees = self.team_bridge.load_worktrees(team);
+        if worktrees.is_empty() {
+            return Err(anyhow!(
+                "Team '{}' 没有成员 worktree（未配置 camWorktree？）",
+                team
+            ));
+        }
+        let report = merge_member_branches(std::path::Path::new(&project_path), &worktrees)?;
+        Ok((project_path, report))
+    }
+
     /// 获取 Team 聚合进度
     pub fn get_team_progress(&self, team: &str) -> Result<TeamProgress> {
         // 获取 team 状态
@@ -318,20 +446,19 @@ impl TeamOrchestrator {
         let task_id = format!("task-{}", chrono::Utc::now().timestamp_millis());
 
         // 发送任务到成员 inbox
+        let text = serde_json::json!({
+            "type": "task_assignment",
+            "task_id": task_id,
+            "subject": task,
+            "description": task,
+            "assigned_by": "user"
+        })
+        .to_string();
         let msg = InboxMessage {
-            from: "team-lead".to_string(),
-            text: serde_json::json!({
-                "type": "task_assignment",
-                "task_id": task_id,
-                "subject": task,
-                "description": task,
-                "assigned_by": "user"
-            })
-            .to_string(),
             summary: Some(format!("任务: {}", truncate_text(task, 30))),
-            timestamp: chrono::Utc::now(),
-            color: None,
-            read: false,
+            kind: MessageKind::Task,
+            task_ref: Some(task_id.clone()),
+            ..InboxMessage::new("team-lead", &text)
         };
 
         self.team_bridge.send_to_inbox(team, member, msg)?;
@@ -345,14 +472,21 @@ impl TeamOrchestrator {
 
     /// 处理用户回复
     ///
-    /// 解析用户输入，执行对应操作。
-    pub fn handle_user_reply(&self, reply: &str, context: Option<&str>) -> Result<String> {
+    /// 解析用户输入，执行对应操作。`sender` 为聊天渠道中的回复者标识，按 `reply_auth` 授权；
+    /// 聊天渠道的回复不是本机终端，未提供 `sender` 时视为不可信的回复者。
+    pub fn handle_user_reply(
+        &self,
+        reply: &str,
+        context: Option<&str>,
+        sender: Option<&str>,
+    ) -> Result<String> {
         let intent = self.parse_user_intent(reply);
+        let reply_sender = ReplySender::Remote(sender);
 
         match intent {
             UserIntent::Approve => {
                 let state_manager = ConversationStateManager::new();
-                match state_manager.handle_reply("y", None)? {
+                match state_manager.handle_reply_from("y", None, reply_sender)? {
                     ReplyResult::Sent { agent_id, .. } => Ok(format!("已批准 {} 的请求", agent_id)),
                     ReplyResult::NoPending => Ok("没有待处理的确认请求".to_string()),
                     ReplyResult::NeedSelection { options } => {
@@ -366,25 +500,78 @@ impl TeamOrchestrator {
                         ))
                     }
                     ReplyResult::InvalidSelection(msg) => Err(anyhow!("无效选择: {}", msg)),
+                    ReplyResult::Denied { reason, .. } => Ok(format!("已拒绝: {}", reason)),
                 }
             }
             UserIntent::Reject => {
                 let state_manager = ConversationStateManager::new();
-                match state_manager.handle_reply("n", None)? {
+                match state_manager.handle_reply_from("n", None, reply_sender)? {
                     ReplyResult::Sent { agent_id, .. } => Ok(format!("已拒绝 {} 的请求", agent_id)),
                     ReplyResult::NoPending => Ok("没有待处理的确认请求".to_string()),
+                    ReplyResult::Denied { reason, .. } => Ok(format!("已拒绝: {}", reason)),
                     _ => Ok("已处理".to_string()),
                 }
             }
             UserIntent::SelectOption(n) => {
                 let state_manager = ConversationStateManager::new();
-                match state_manager.handle_reply(&n.to_string(), None)? {
-                    ReplyResult::Sent { agent_id, reply } => {
-                        Ok(format!("已发送选项 {} 到 {}", reply, agent_id))
-                    }
+                match state_manager.handle_reply_from(&n.to_string(), None, reply_sender)? {
+                    ReplyResult::Sent {
+                        agent_id, reply, ..
+                    } => Ok(format!("已发送选项 {} 到 {}", reply, agent_id)),
+                    ReplyResult::Denied { reason, .. } => Ok(format!("已拒绝: {}", reason)),
                     _ => Ok("已处理".to_string()),
                 }
             }
+            UserIntent::ReplyMacro { name, target } => {
+                let state_manager = ConversationStateManager::new();
+                match state_manager.handle_reply_from(
+                    &format!("@{}", name),
+                    target.as_deref(),
+                    reply_sender,
+                )? {
+                    ReplyResult::Sent {
+                        agent_id, reply, ..
+                    } => Ok(format!("已发送 {} 到 {}", reply, agent_id)),
+                    ReplyResult::NoPending => Ok("没有待处理的确认请求".to_string()),
+                    ReplyResult::NeedSelection { options } => {
+                        let list: Vec<String> = options
+                            .iter()
+                            .map(|o| format!("- {} ({})", o.agent_id, o.context))
+                            .collect();
+                        Ok(format!(
+                            "有多个待处理请求，请指定目标：@{} <agent_id>\n{}",
+                            name,
+                            list.join("\n")
+                        ))
+                    }
+                    ReplyResult::InvalidSelection(msg) => Ok(msg),
+                    ReplyResult::Denied { reason, .. } => Ok(format!("已拒绝: {}", reason)),
+                }
+            }
+            UserIntent::Acknowledge { target } => {
+                // ack 会静音 HIGH 确认的提醒，与 snooze 一样按 HIGH 列表授权
+                if let Some(reason) = self.authorize_command("ack", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
+                let state_manager = ConversationStateManager::new();
+                match state_manager.acknowledge(target.as_deref())? {
+                    AckResult::Acknowledged { agent_id } => {
+                        Ok(format!("已确认 {} 的通知，稍后处理前不再提醒", agent_id))
+                    }
+                    AckResult::NoPending => Ok("没有待处理的确认请求".to_string()),
+                    AckResult::NeedSelection { options } => {
+                        let list: Vec<String> = options
+                            .iter()
+                            .map(|o| format!("- {} ({})", o.agent_id, o.context))
+                            .collect();
+                        Ok(format!(
+                            "有多个待处理请求，请指定目标：ack <agent_id>\n{}",
+                            list.join("\n")
+                        ))
+                    }
+                    AckResult::NotFound(target) => Ok(format!("未找到目标: {}", target)),
+                }
+            }
             UserIntent::CheckProgress { team } => {
                 if let Some(team_name) = team {
                     let progress = self.get_team_progress(&team_name)?;
@@ -435,17 +622,160 @@ impl TeamOrchestrator {
                 ))
             }
             UserIntent::ShutdownTeam { team } => {
+                if let Some(reason) = self.authorize_command("shutdown", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
                 self.shutdown_team(&team)?;
                 Ok(format!("已关闭 Team '{}'", team))
             }
+            UserIntent::Snooze { secs, target } => {
+                if let Some(reason) = self.authorize_command("snooze", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
+                let mut state = SnoozeState::load();
+                let scope = target.as_deref().unwrap_or("所有 agent");
+                let reply = match secs {
+                    Some(secs) => {
+                        let until = chrono::Utc::now() + chrono::Duration::seconds(secs);
+                        state.snooze(target.as_deref(), until);
+                        format!(
+                            "已暂停 {} 的提醒至 {}（HIGH 通知仍会发送）",
+                            scope,
+                            until.with_timezone(&chrono::Local).format("%H:%M")
+                        )
+                    }
+                    None => {
+                        state.resume(target.as_deref());
+                        format!("已恢复 {} 的提醒", scope)
+                    }
+                };
+                state.save()?;
+                Ok(reply)
+            }
+            UserIntent::Status => {
+                let agents = self.agent_manager.list_agents()?;
+                let pending = ConversationStateManager::new().get_pending_confirmations()?;
+                let waiting = agents.iter().filter(|a| a.status.is_waiting()).count();
+                let mut lines = vec![
+                    format!("Agents: {} 个（{} 个等待输入）", agents.len(), waiting),
+                    format!("待处理确认: {}", pending.len()),
+                ];
+                for confirmation in &pending {
+                    lines.push(format!(
+                        "  - {} ({})",
+                        confirmation.agent_id,
+                        confirmation.context.chars().take(60).collect::<String>()
+                    ));
+                }
+                if let Some(until) = SnoozeState::load().all.filter(|u| *u > chrono::Utc::now()) {
+                    lines.push(format!(
+                        "提醒已暂停至 {}",
+                        until.with_timezone(&chrono::Local).format("%H:%M")
+                    ));
+                }
+                let teams = self.team_bridge.list_teams();
+                if !teams.is_empty() {
+                    lines.push(format!("活跃的 Teams: {}", teams.join(", ")));
+                }
+                Ok(lines.join("\n"))
+            }
+            UserIntent::ListAgents => {
+                let agents = self.agent_manager.list_agents()?;
+                if agents.is_empty() {
+                    return Ok("没有运行中的 agent".to_string());
+                }
+                let list: Vec<String> = agents
+                    .iter()
+                    .map(|a| format!("{} {} {}", a.status.icon(), a.agent_id, a.project_path))
+                    .collect();
+                Ok(list.join("\n"))
+            }
+            UserIntent::Logs { agent_id } => {
+                // 终端输出可能包含敏感信息，发到共享聊天前按 HIGH 列表授权
+                if let Some(reason) = self.authorize_command("logs", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
+                let logs = self.agent_manager.get_logs(&agent_id, CHAT_LOG_LINES)?;
+                Ok(format!("{} 最近输出:\n{}", agent_id, logs.trim_end()))
+            }
+            UserIntent::Kill {
+                agent_id,
+                confirmed,
+            } => {
+                if let Some(reason) = self.authorize_command("kill", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
+                if self.agent_manager.get_agent(&agent_id)?.is_none() {
+                    return Ok(format!("未找到 agent: {}", agent_id));
+                }
+                if !confirmed {
+                    return Ok(format!(
+                        "确认停止 {}？回复 `kill {} confirm` 执行",
+                        agent_id, agent_id
+                    ));
+                }
+                self.agent_manager.stop_agent(&agent_id)?;
+                Ok(format!("已停止 {}", agent_id))
+            }
+            UserIntent::KillIdle { target } => {
+                if let Some(reason) = self.authorize_command("/kill", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
+                let mut state = IdleState::load();
+                let suggested = target
+                    .clone()
+                    .or_else(|| state.latest_suggestion().map(str::to_string));
+                let Some(agent_id) = suggested else {
+                    return Ok(
+                        "没有空闲的 agent，停止指定 agent 请用 `kill <agent_id>`".to_string()
+                    );
+                };
+                // 指定的 agent 没有空闲建议时按普通 kill 要求确认
+                if !state.suggested.contains_key(&agent_id) {
+                    return self.handle_user_reply(&format!("kill {}", agent_id), context, sender);
+                }
+                let Some(agent) = self.agent_manager.get_agent(&agent_id)? else {
+                    state.forget(&agent_id);
+                    state.save()?;
+                    return Ok(format!("未找到 agent: {}", agent_id));
+                };
+                if !agent.status.is_waiting() {
+                    return Ok(format!("{} 已恢复运行，未停止", agent_id));
+                }
+                self.agent_manager.stop_agent(&agent_id)?;
+                state.forget(&agent_id);
+                state.save()?;
+                Ok(format!("已停止空闲的 {}", agent_id))
+            }
+            UserIntent::KeepIdle { target } => {
+                if let Some(reason) = self.authorize_command("/keep", reply_sender) {
+                    return Ok(format!("已拒绝: {}", reason));
+                }
+                let mut state = IdleState::load();
+                let suggested = target
+                    .clone()
+                    .or_else(|| state.latest_suggestion().map(str::to_string));
+                let Some(agent_id) = suggested else {
+                    return Ok("没有空闲建议".to_string());
+                };
+                let agents = self.agent_manager.list_agents()?;
+                if !agents.iter().any(|a| a.agent_id == agent_id) {
+                    return Ok(format!("未找到 agent: {}", agent_id));
+                }
+                state.retain_agents(agents.iter().map(|a| a.agent_id.as_str()));
+                state.keep(&agent_id);
+                state.save()?;
+                Ok(format!("已保留 {}，不再提醒空闲", agent_id))
+            }
             UserIntent::Unknown(text) => {
                 // 尝试作为直接回复发送
                 let state_manager = ConversationStateManager::new();
-                match state_manager.handle_reply(&text, None)? {
-                    ReplyResult::Sent { agent_id, reply } => {
-                        Ok(format!("已发送 '{}' 到 {}", reply, agent_id))
-                    }
+                match state_manager.handle_reply_from(&text, None, reply_sender)? {
+                    ReplyResult::Sent {
+                        agent_id, reply, ..
+                    } => Ok(format!("已发送 '{}' 到 {}", reply, agent_id)),
                     ReplyResult::NoPending => Ok(format!("未识别的命令: {}", text)),
+                    ReplyResult::Denied { reason, .. } => Ok(format!("已拒绝: {}", reason)),
                     _ => Ok("已处理".to_string()),
                 }
             }
@@ -470,6 +800,24 @@ impl TeamOrchestrator {
             return UserIntent::Reject;
         }
 
+        // 确认已看到：ack [目标]
+        if matches!(input_lower.as_str(), "稍后" | "已读" | "知道了") {
+            return UserIntent::Acknowledge { target: None };
+        }
+        if let Some(rest) = input_lower.strip_prefix("ack") {
+            if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+                let target = input.trim().get(3..).unwrap_or("").trim();
+                return UserIntent::Acknowledge {
+                    target: (!target.is_empty()).then(|| target.to_string()),
+                };
+            }
+        }
+
+        // 聊天命令：snooze / status / list / logs / kill
+        if let Some(intent) = Self::parse_chat_command(input) {
+            return intent;
+        }
+
         // 数字选项
         if let Ok(n) = input_lower.parse::<usize>() {
             if (1..=9).contains(&n) {
@@ -521,6 +869,82 @@ impl TeamOrchestrator {
         UserIntent::Unknown(input.to_string())
     }
 
+    /// 解析聊天遥控命令
+    fn parse_chat_command(input: &str) -> Option<UserIntent> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let (command, args) = words.split_first()?;
+        if let Some(name) = reply_macro::macro_name(command) {
+            return matches!(args, [] | [_]).then(|| UserIntent::ReplyMacro {
+                name: name.to_string(),
+                target: args.first().map(|s| s.to_string()),
+            });
+        }
+        let command = command.to_lowercase();
+        match (command.as_str(), args) {
+            ("status" | "状态", []) => Some(UserIntent::Status),
+            ("list" | "ls" | "列表", []) => Some(UserIntent::ListAgents),
+            ("logs" | "日志", [agent_id]) => Some(UserIntent::Logs {
+                agent_id: agent_id.to_string(),
+            }),
+            ("/kill", [] | [_]) => Some(UserIntent::KillIdle {
+                target: args.first().map(|s| s.to_string()),
+            }),
+            ("/keep", [] | [_]) => Some(UserIntent::KeepIdle {
+                target: args.first().map(|s| s.to_string()),
+            }),
+            ("kill", [agent_id]) => Some(UserIntent::Kill {
+                agent_id: agent_id.to_string(),
+                confirmed: false,
+            }),
+            ("kill", [agent_id, confirm])
+                if matches!(confirm.to_lowercase().as_str(), "confirm" | "确认") =>
+            {
+                Some(UserIntent::Kill {
+                    agent_id: agent_id.to_string(),
+                    confirmed: true,
+                })
+            }
+            ("unsnooze", []) | ("snooze" | "暂停", ["off"] | ["off", _]) => {
+                Some(UserIntent::Snooze {
+                    secs: None,
+                    target: args.get(1).map(|s| s.to_string()),
+                })
+            }
+            ("unsnooze", [agent_id]) => Some(UserIntent::Snooze {
+                secs: None,
+                target: Some(agent_id.to_string()),
+            }),
+            ("snooze" | "暂停", []) => Some(UserIntent::Snooze {
+                secs: Some(DEFAULT_SNOOZE_SECS),
+                target: None,
+            }),
+            ("snooze" | "暂停", [first, rest @ ..]) if rest.len() <= 1 => {
+                // 纯数字按分钟；第一个参数不是时长时视为 agent
+                let duration = if first.chars().all(|c| c.is_ascii_digit()) {
+                    format!("{}m", first)
+                } else {
+                    first.to_string()
+                };
+                match (parse_since(&duration), rest) {
+                    (Ok(secs), []) if secs > 0 => Some(UserIntent::Snooze {
+                        secs: Some(secs),
+                        target: None,
+                    }),
+                    (Ok(secs), [agent_id]) if secs > 0 => Some(UserIntent::Snooze {
+                        secs: Some(secs),
+                        target: Some(agent_id.to_string()),
+                    }),
+                    (Err(_), []) => Some(UserIntent::Snooze {
+                        secs: Some(DEFAULT_SNOOZE_SECS),
+                        target: Some(first.to_string()),
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     /// 生成 Team 名称
     fn generate_team_name(&self, project: &str) -> String {
         let project_name = std::path::Path::new(project)
@@ -650,6 +1074,172 @@ mod tests {
         let _ = orchestrator.agent_manager().stop_agent(agent_id);
     }
 
+    #[test]
+    fn test_parse_acknowledge_intent() {
+        let (orchestrator, _temp) = create_test_orchestrator();
+        assert_eq!(
+            orchestrator.parse_user_intent("ack"),
+            UserIntent::Acknowledge { target: None }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("ACK cam-123"),
+            UserIntent::Acknowledge {
+                target: Some("cam-123".to_string())
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("稍后"),
+            UserIntent::Acknowledge { target: None }
+        );
+        assert!(matches!(
+            orchestrator.parse_user_intent("acknowledge everything"),
+            UserIntent::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_chat_commands() {
+        let (orchestrator, _temp) = create_test_orchestrator();
+        assert_eq!(
+            orchestrator.parse_user_intent("snooze 30m"),
+            UserIntent::Snooze {
+                secs: Some(1800),
+                target: None
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("snooze 2h cam-1"),
+            UserIntent::Snooze {
+                secs: Some(7200),
+                target: Some("cam-1".to_string())
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("snooze 15"),
+            UserIntent::Snooze {
+                secs: Some(900),
+                target: None
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("snooze cam-1"),
+            UserIntent::Snooze {
+                secs: Some(DEFAULT_SNOOZE_SECS),
+                target: Some("cam-1".to_string())
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("snooze off"),
+            UserIntent::Snooze {
+                secs: None,
+                target: None
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("@approve-tests cam-1"),
+            UserIntent::ReplyMacro {
+                name: "approve-tests".to_string(),
+                target: Some("cam-1".to_string())
+            }
+        );
+        assert!(matches!(
+            orchestrator.parse_user_intent("@src/main.rs looks wrong"),
+            UserIntent::Unknown(_)
+        ));
+        assert_eq!(orchestrator.parse_user_intent("Status"), UserIntent::Status);
+        assert_eq!(
+            orchestrator.parse_user_intent("list"),
+            UserIntent::ListAgents
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("logs cam-1"),
+            UserIntent::Logs {
+                agent_id: "cam-1".to_string()
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("kill cam-1"),
+            UserIntent::Kill {
+                agent_id: "cam-1".to_string(),
+                confirmed: false
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("kill cam-1 confirm"),
+            UserIntent::Kill {
+                agent_id: "cam-1".to_string(),
+                confirmed: true
+            }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("/kill"),
+            UserIntent::KillIdle { target: None }
+        );
+        assert_eq!(
+            orchestrator.parse_user_intent("/keep cam-1"),
+            UserIntent::KeepIdle {
+                target: Some("cam-1".to_string())
+            }
+        );
+        // 带多余参数的不是命令，按普通回复处理
+        assert!(matches!(
+            orchestrator.parse_user_intent("list all the files please"),
+            UserIntent::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn test_kill_unknown_agent() {
+        let (orchestrator, _temp) = create_test_orchestrator();
+        let reply = orchestrator
+            .handle_user_reply("kill cam-missing", None, None)
+            .unwrap();
+        assert!(reply.contains("未找到"));
+    }
+
+    #[test]
+    fn test_chat_commands_require_high_sender() {
+        let (orchestrator, _temp) = create_test_orchestrator();
+        let orchestrator = orchestrator.with_reply_auth(Some(ReplyAuthConfig {
+            high: Some(vec!["tg:1".to_string()]),
+            ..Default::default()
+        }));
+
+        orchestrator
+            .team_bridge()
+            .create_team("test-team-auth", "Test team", "/tmp")
+            .unwrap();
+
+        for command in [
+            "kill cam-1 confirm",
+            "snooze 30m",
+            "/kill cam-1",
+            "/keep",
+            "logs cam-1",
+            "ack cam-1",
+            "shutdown team test-team-auth",
+        ] {
+            let denied = orchestrator
+                .handle_user_reply(command, None, Some("tg:2"))
+                .unwrap();
+            assert!(denied.contains("tg:2 无权执行"), "{}: {}", command, denied);
+            let anonymous = orchestrator.handle_user_reply(command, None, None).unwrap();
+            assert!(
+                anonymous.starts_with("已拒绝"),
+                "{}: {}",
+                command,
+                anonymous
+            );
+        }
+
+        // 列表中的用户照常执行
+        let allowed = orchestrator
+            .handle_user_reply("kill cam-missing", None, Some("tg:1"))
+            .unwrap();
+        assert!(allowed.contains("未找到"));
+        assert!(orchestrator.team_bridge().team_exists("test-team-auth"));
+    }
+
     #[test]
     fn test_spawn_agent_team_not_exists() {
         let (orchestrator, _temp) = create_test_orchestrator();
@@ -0,0 +1,235 @@
+//! Team 进度历史 - 定期记录 TeamProgress 快照，用于查看燃尽趋势
+//!
+//! 快照按 team 追加到 `~/.config/code-agent-monitor/team-progress/{team}.jsonl`。
+//! Watcher daemon 每 [`SAMPLE_INTERVAL_SECS`] 秒为所有 team 记录一次，`cam team-progress`
+//! 查询时也会记录；与上一条相同且间隔不足时跳过，超过 [`MAX_SAMPLES`] 条时丢弃最旧的。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::bridge::TeamBridge;
+use super::orchestrator::{TeamOrchestrator, TeamProgress};
+
+/// 采样间隔（秒）
+pub const SAMPLE_INTERVAL_SECS: i64 = 5 * 60;
+/// 每个 team 保留的最多快照数
+pub const MAX_SAMPLES: usize = 2000;
+
+/// 进度快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressSample {
+    pub timestamp: DateTime<Utc>,
+    pub pending_tasks: usize,
+    pub completed_tasks: usize,
+    pub active_members: usize,
+    /// 等待输入的成员数
+    pub waiting: usize,
+}
+
+impl ProgressSample {
+    pub fn from_progress(progress: &TeamProgress, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            timestamp,
+            pending_tasks: progress.pending_tasks,
+            completed_tasks: progress.completed_tasks,
+            active_members: progress.active_members,
+            waiting: progress.waiting_for_input.len(),
+        }
+    }
+
+    /// 除时间外是否相同
+    fn same_counts(&self, other: &Self) -> bool {
+        self.pending_tasks == other.pending_tasks
+            && self.completed_tasks == other.completed_tasks
+            && self.active_members == other.active_members
+            && self.waiting == other.waiting
+    }
+}
+
+/// 单个 team 的进度历史文件
+#[derive(Debug, Clone)]
+pub struct ProgressHistory {
+    path: PathBuf,
+}
+
+impl ProgressHistory {
+    /// 默认目录下的历史文件
+    pub fn for_team(team: &str) -> Self {
+        let dir = crate::infra::paths::state_dir().join("team-progress");
+        Self::in_dir(&dir, team)
+    }
+
+    pub fn in_dir(dir: &Path, team: &str) -> Self {
+        Self {
+            path: dir.join(format!("{}.jsonl", team)),
+        }
+    }
+
+    /// 读取全部快照（按时间顺序），跳过无法解析的行
+    pub fn load(&self) -> Vec<ProgressSample> {
+        fs::read_to_string(&self.path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 记录一条快照，返回是否写入
+    pub fn record(&self, progress: &TeamProgress, now: DateTime<Utc>) -> Result<bool> {
+        let sample = ProgressSample::from_progress(progress, now);
+        let mut samples = self.load();
+        if let Some(last) = samples.last() {
+            let elapsed = (now - last.timestamp).num_seconds();
+            if last.same_counts(&sample) && elapsed < SAMPLE_INTERVAL_SECS {
+                return Ok(false);
+            }
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if samples.len() >= MAX_SAMPLES {
+            samples.push(sample);
+            let keep = &samples[samples.len() - MAX_SAMPLES..];
+            let content: Vec<String> = keep
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<serde_json::Result<_>>()?;
+            fs::write(&self.path, content.join("\n") + "\n")?;
+        } else {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            writeln!(file, "{}", serde_json::to_string(&sample)?)?;
+        }
+        Ok(true)
+    }
+}
+
+/// 为所有 team 记录一次快照（watcher daemon 定期调用），返回写入的数量
+pub fn record_all_teams(now: DateTime<Utc>) -> usize {
+    let orchestrator = TeamOrchestrator::new();
+    TeamBridge::new()
+        .list_teams()
+        .into_iter()
+        .filter(|team| {
+            orchestrator
+                .get_team_progress(team)
+                .and_then(|progress| ProgressHistory::for_team(team).record(&progress, now))
+                .unwrap_or(false)
+        })
+        .count()
+}
+
+/// 燃尽概况：首尾待处理任务数和每小时变化
+#[derive(Debug, Clone, PartialEq)]
+pub struct Burndown {
+    pub first_pending: usize,
+    pub last_pending: usize,
+    pub span_secs: i64,
+}
+
+impl Burndown {
+    /// 至少两条快照时计算
+    pub fn from_samples(samples: &[ProgressSample]) -> Option<Self> {
+        if samples.len() < 2 {
+            return None;
+        }
+        let (first, last) = (&samples[0], &samples[samples.len() - 1]);
+        Some(Self {
+            first_pending: first.pending_tasks,
+            last_pending: last.pending_tasks,
+            span_secs: (last.timestamp - first.timestamp).num_seconds(),
+        })
+    }
+
+    /// 待处理任务的变化量（负数表示在减少）
+    pub fn delta(&self) -> i64 {
+        self.last_pending as i64 - self.first_pending as i64
+    }
+
+    /// 每小时变化量
+    pub fn per_hour(&self) -> Option<f64> {
+        (self.span_secs > 0).then(|| self.delta() as f64 * 3600.0 / self.span_secs as f64)
+    }
+}
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 文本迷你图，超过 `width` 时只显示最近的值
+pub fn sparkline(values: &[usize], width: usize) -> String {
+    let values = &values[values.len().saturating_sub(width)..];
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            let level = (v * (SPARK_CHARS.len() - 1)).checked_div(max).unwrap_or(0);
+            SPARK_CHARS[level]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn progress(pending: usize, completed: usize) -> TeamProgress {
+        TeamProgress {
+            team_name: "web".to_string(),
+            total_members: 3,
+            active_members: 2,
+            pending_tasks: pending,
+            completed_tasks: completed,
+            waiting_for_input: vec!["developer".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_record_and_burndown() {
+        let temp = tempfile::tempdir().unwrap();
+        let history = ProgressHistory::in_dir(temp.path(), "web");
+        let start = Utc::now();
+
+        assert!(history.record(&progress(8, 0), start).unwrap());
+        // 未变化且间隔不足时跳过
+        assert!(!history
+            .record(&progress(8, 0), start + Duration::minutes(1))
+            .unwrap());
+        assert!(history
+            .record(&progress(6, 2), start + Duration::minutes(2))
+            .unwrap());
+        assert!(history
+            .record(&progress(6, 2), start + Duration::minutes(10))
+            .unwrap());
+        assert!(history
+            .record(&progress(4, 4), start + Duration::hours(1))
+            .unwrap());
+
+        let samples = history.load();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[0].waiting, 1);
+
+        let burndown = Burndown::from_samples(&samples).unwrap();
+        assert_eq!(burndown.delta(), -4);
+        assert_eq!(burndown.per_hour(), Some(-4.0));
+        assert!(Burndown::from_samples(&samples[..1]).is_none());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[8, 6, 4, 0], 10), "█▆▄▁");
+        assert_eq!(sparkline(&[0, 0], 10), "▁▁");
+        assert_eq!(sparkline(&[1, 2, 3, 4], 2), "▆█");
+        assert_eq!(sparkline(&[], 5), "");
+    }
+}
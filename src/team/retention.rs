@@ -0,0 +1,178 @@
+//! Inbox 保留策略 - 限制 inbox 文件大小，旧消息归档到压缩日志
+//!
+//! Team inbox 是整体读写的 JSON 数组，消息只增不减时每次轮询都要解析整个文件。
+//! 按 config.json 中的 `inbox_retention` 把超量或过期的**已读**消息移出 inbox，
+//! 逐行追加到 `~/.claude/teams/{team}/archive/{member}.jsonl`。未读消息始终保留。
+//!
+//! InboxWatcher 定期执行，也可以通过 `cam inbox prune <team>` 手动执行。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::bridge::InboxMessage;
+
+/// 保留策略（config.json 的 `inbox_retention`），0 表示不限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct InboxRetention {
+    /// 每个 inbox 最多保留的已读消息数
+    pub max_messages: usize,
+    /// 已读消息最长保留天数
+    pub max_age_days: u32,
+}
+
+impl Default for InboxRetention {
+    fn default() -> Self {
+        Self {
+            max_messages: 200,
+            max_age_days: 7,
+        }
+    }
+}
+
+impl InboxRetention {
+    /// 读取 config.json，缺失或无效时使用默认值
+    pub fn load() -> Self {
+        crate::infra::paths::config_section("inbox_retention").unwrap_or_default()
+    }
+
+    /// 拆分为（保留，归档），两部分都保持原顺序
+    ///
+    /// 先归档过期的已读消息，已读消息仍超过上限时再归档最旧的。
+    pub fn partition(
+        &self,
+        messages: Vec<InboxMessage>,
+        now: DateTime<Utc>,
+    ) -> (Vec<InboxMessage>, Vec<InboxMessage>) {
+        let cutoff =
+            (self.max_age_days > 0).then(|| now - Duration::days(i64::from(self.max_age_days)));
+        let expired = |msg: &InboxMessage| cutoff.is_some_and(|cutoff| msg.timestamp < cutoff);
+
+        let read_kept = messages
+            .iter()
+            .filter(|msg| msg.read && !expired(msg))
+            .count();
+        let mut over_limit = if self.max_messages > 0 {
+            read_kept.saturating_sub(self.max_messages)
+        } else {
+            0
+        };
+
+        let mut kept = Vec::new();
+        let mut archived = Vec::new();
+        for msg in messages {
+            if !msg.read {
+                kept.push(msg);
+            } else if expired(&msg) {
+                archived.push(msg);
+            } else if over_limit > 0 {
+                over_limit -= 1;
+                archived.push(msg);
+            } else {
+                kept.push(msg);
+            }
+        }
+        (kept, archived)
+    }
+}
+
+/// 单个 inbox 的清理结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PruneResult {
+    pub member: String,
+    /// 移入归档的消息数
+    pub archived: usize,
+    /// inbox 中剩余的消息数
+    pub kept: usize,
+}
+
+/// 把消息逐行追加到归档日志
+pub fn append_archive(path: &Path, messages: &[InboxMessage]) -> Result<()> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut lines = String::new();
+    for msg in messages {
+        lines.push_str(&serde_json::to_string(msg)?);
+        lines.push('\n');
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str, read: bool, age_days: i64, now: DateTime<Utc>) -> InboxMessage {
+        InboxMessage {
+            read,
+            timestamp: now - Duration::days(age_days),
+            ..InboxMessage::new("developer", text)
+        }
+    }
+
+    #[test]
+    fn test_partition() {
+        let now = Utc::now();
+        let messages = vec![
+            message("old-read", true, 10, now),
+            message("old-unread", false, 10, now),
+            message("read-1", true, 2, now),
+            message("read-2", true, 1, now),
+            message("read-3", true, 0, now),
+            message("new-unread", false, 0, now),
+        ];
+        let policy = InboxRetention {
+            max_messages: 2,
+            max_age_days: 7,
+        };
+
+        let (kept, archived) = policy.partition(messages.clone(), now);
+        let texts = |msgs: &[InboxMessage]| msgs.iter().map(|m| m.text.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            texts(&kept),
+            ["old-unread", "read-2", "read-3", "new-unread"]
+        );
+        assert_eq!(texts(&archived), ["old-read", "read-1"]);
+
+        let unlimited = InboxRetention {
+            max_messages: 0,
+            max_age_days: 0,
+        };
+        let (kept, archived) = unlimited.partition(messages, now);
+        assert_eq!(kept.len(), 6);
+        assert!(archived.is_empty());
+    }
+
+    #[test]
+    fn test_append_archive() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("archive").join("developer.jsonl");
+        let now = Utc::now();
+
+        append_archive(&path, &[message("a", true, 0, now)]).unwrap();
+        append_archive(
+            &path,
+            &[message("b", true, 0, now), message("c", true, 0, now)],
+        )
+        .unwrap();
+        append_archive(&path, &[]).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let texts: Vec<String> = content
+            .lines()
+            .map(|line| serde_json::from_str::<InboxMessage>(line).unwrap().text)
+            .collect();
+        assert_eq!(texts, ["a", "b", "c"]);
+    }
+}
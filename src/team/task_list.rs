@@ -131,6 +131,18 @@ pub fn update_task_status(team_name: &str, task_id: &str, status: TaskStatus) ->
     Ok(())
 }
 
+/// 把成员进行中的任务标记为完成，返回更新的任务 ID
+pub fn complete_member_tasks(team_name: &str, member: &str) -> Result<Vec<String>> {
+    let mut completed = Vec::new();
+    for task in list_tasks(team_name) {
+        if task.status == TaskStatus::InProgress && task.owner.as_deref() == Some(member) {
+            update_task_status(team_name, &task.id, TaskStatus::Completed)?;
+            completed.push(task.id);
+        }
+    }
+    Ok(completed)
+}
+
 /// 列出所有 team 名称
 pub fn list_team_names() -> Vec<String> {
     let tasks_dir = match get_tasks_dir() {
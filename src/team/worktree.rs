@@ -0,0 +1,340 @@
+//! 成员 worktree 隔离 - 每个 Team 成员在独立的 git worktree 和分支中工作
+//!
+//! Team 的 config.json 配置了 `camWorktree` 时，`spawn_agent` 为成员执行
+//! `git worktree add -b {prefix}{member} {dir}/{member} {base}`，agent 在 worktree 中启动，
+//! 避免多个成员同时修改同一个工作目录。成员与分支、路径的映射保存在
+//! `~/.claude/teams/{team}/cam-worktrees.json`。
+//!
+//! `cam team-merge <team>` 依次把成员分支合并回项目当前分支；冲突时中止该成员的合并、
+//! 发送通知并继续下一个成员。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::notification::NotificationEvent;
+
+/// 映射文件名（位于 team 目录）
+const WORKTREES_FILE: &str = "cam-worktrees.json";
+
+/// Team 的 worktree 配置（config.json 的 `camWorktree`）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorktreeConfig {
+    /// 成员分支的起点（默认项目当前 HEAD）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// 分支名前缀（默认 `cam/{team}/`）
+    #[serde(
+        default,
+        rename = "branchPrefix",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub branch_prefix: Option<String>,
+    /// worktree 根目录（默认 `~/.config/code-agent-monitor/worktrees/{team}`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+impl WorktreeConfig {
+    /// 成员的分支名
+    pub fn branch_name(&self, team: &str, member: &str) -> String {
+        match &self.branch_prefix {
+            Some(prefix) => format!("{}{}", prefix, member),
+            None => format!("cam/{}/{}", team, member),
+        }
+    }
+
+    /// 成员的 worktree 路径
+    pub fn worktree_path(&self, team: &str, member: &str) -> PathBuf {
+        let root = match &self.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => crate::infra::paths::state_dir()
+                .join("worktrees")
+                .join(team),
+        };
+        root.join(member)
+    }
+}
+
+/// 成员与 worktree 的映射
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberWorktree {
+    pub member: String,
+    pub branch: String,
+    pub path: String,
+    /// 创建时的起点提交
+    pub base: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 读取 team 目录下的映射，缺失或无效时为空
+pub fn load_worktrees(team_dir: &Path) -> Vec<MemberWorktree> {
+    fs::read_to_string(team_dir.join(WORKTREES_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_worktrees(team_dir: &Path, worktrees: &[MemberWorktree]) -> Result<()> {
+    fs::create_dir_all(team_dir)?;
+    fs::write(
+        team_dir.join(WORKTREES_FILE),
+        serde_json::to_string_pretty(worktrees)?,
+    )?;
+    Ok(())
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to execute git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 为成员创建 worktree 和分支（分支已存在时直接检出）
+pub fn create_member_worktree(
+    project: &Path,
+    team: &str,
+    member: &str,
+    config: &WorktreeConfig,
+) -> Result<MemberWorktree> {
+    let branch = config.branch_name(team, member);
+    let path = config.worktree_path(team, member);
+    if path.exists() {
+        return Err(anyhow!("worktree 路径已存在: {}", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let path_str = path.to_string_lossy().to_string();
+    let branch_ref = format!("refs/heads/{}", branch);
+    let base = if git(project, &["rev-parse", "--verify", "--quiet", &branch_ref]).is_ok() {
+        git(project, &["worktree", "add", &path_str, &branch])?;
+        git(project, &["rev-parse", &branch])?
+    } else {
+        let base = git(
+            project,
+            &["rev-parse", config.base.as_deref().unwrap_or("HEAD")],
+        )?;
+        git(
+            project,
+            &["worktree", "add", "-b", &branch, &path_str, &base],
+        )?;
+        base
+    };
+
+    Ok(MemberWorktree {
+        member: member.to_string(),
+        branch,
+        path: path_str,
+        base,
+        created_at: Utc::now(),
+    })
+}
+
+/// 单个成员分支的合并结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MergeOutcome {
+    /// 已合并
+    Merged { commits: usize },
+    /// 没有新提交
+    UpToDate,
+    /// 冲突，已中止合并
+    Conflict { files: Vec<String> },
+    /// 其他失败（分支不存在等）
+    Failed { error: String },
+}
+
+/// 成员合并报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberMerge {
+    pub member: String,
+    pub branch: String,
+    /// worktree 中有未提交的修改（不会被合并）
+    pub uncommitted: bool,
+    #[serde(flatten)]
+    pub outcome: MergeOutcome,
+}
+
+/// 依次将成员分支合并到项目当前分支
+///
+/// 项目有未提交的修改时直接返回错误；单个成员冲突时执行 `git merge --abort` 后继续。
+pub fn merge_member_branches(
+    project: &Path,
+    worktrees: &[MemberWorktree],
+) -> Result<Vec<MemberMerge>> {
+    let dirty = git(project, &["status", "--porcelain", "--untracked-files=no"])?;
+    if !dirty.is_empty() {
+        return Err(anyhow!("项目有未提交的修改，请先提交或暂存"));
+    }
+
+    Ok(worktrees
+        .iter()
+        .map(|worktree| {
+            let uncommitted = git(
+                Path::new(&worktree.path),
+                &["status", "--porcelain", "--untracked-files=no"],
+            )
+            .is_ok_and(|status| !status.is_empty());
+            MemberMerge {
+                member: worktree.member.clone(),
+                branch: worktree.branch.clone(),
+                uncommitted,
+                outcome: merge_branch(project, &worktree.branch),
+            }
+        })
+        .collect())
+}
+
+fn merge_branch(project: &Path, branch: &str) -> MergeOutcome {
+    let range = format!("HEAD..{}", branch);
+    let commits = match git(project, &["rev-list", "--count", &range]) {
+        Ok(count) => count.parse().unwrap_or(0),
+        Err(e) => {
+            return MergeOutcome::Failed {
+                error: e.to_string(),
+            }
+        }
+    };
+    if commits == 0 {
+        return MergeOutcome::UpToDate;
+    }
+
+    let message = format!("Merge {}", branch);
+    match git(project, &["merge", "--no-ff", "-m", &message, branch]) {
+        Ok(_) => MergeOutcome::Merged { commits },
+        Err(e) => {
+            let files: Vec<String> = git(project, &["diff", "--name-only", "--diff-filter=U"])
+                .map(|out| out.lines().map(str::to_string).collect())
+                .unwrap_or_default();
+            let _ = git(project, &["merge", "--abort"]);
+            if files.is_empty() {
+                MergeOutcome::Failed {
+                    error: e.to_string(),
+                }
+            } else {
+                MergeOutcome::Conflict { files }
+            }
+        }
+    }
+}
+
+/// 合并冲突的通知事件（以 `{member}@{team}` 身份发送，HIGH）
+pub fn conflict_event(team: &str, project: &str, merge: &MemberMerge) -> Option<NotificationEvent> {
+    let MergeOutcome::Conflict { files } = &merge.outcome else {
+        return None;
+    };
+    let message = format!(
+        "[{}] 合并 {} 的分支 {} 冲突（{}），已中止该合并",
+        team,
+        merge.member,
+        merge.branch,
+        files.join(", ")
+    );
+    Some(
+        NotificationEvent::notification(
+            format!("{}@{}", merge.member, team),
+            "team_merge_conflict",
+            message,
+        )
+        .with_project_path(project.to_string())
+        .with_skip_dedup(true),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {:?}: {:?}", args, status);
+    }
+
+    fn commit_file(repo: &Path, file: &str, content: &str) {
+        fs::write(repo.join(file), content).unwrap();
+        run_git(repo, &["add", file]);
+        run_git(repo, &["commit", "-q", "-m", file]);
+    }
+
+    #[test]
+    fn test_worktree_config() {
+        let config: WorktreeConfig =
+            serde_json::from_str(r#"{"branchPrefix": "feat/", "dir": "/tmp/wt"}"#).unwrap();
+        assert_eq!(config.branch_name("web", "dev"), "feat/dev");
+        assert_eq!(
+            config.worktree_path("web", "dev"),
+            PathBuf::from("/tmp/wt/dev")
+        );
+        assert_eq!(
+            WorktreeConfig::default().branch_name("web", "dev"),
+            "cam/web/dev"
+        );
+    }
+
+    #[test]
+    fn test_create_and_merge_member_worktrees() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        run_git(&project, &["init", "-q"]);
+        run_git(&project, &["config", "user.name", "cam"]);
+        run_git(&project, &["config", "user.email", "cam@test"]);
+        commit_file(&project, "README.md", "base\n");
+
+        let config = WorktreeConfig {
+            dir: Some(temp.path().join("wt").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let dev = create_member_worktree(&project, "web", "dev", &config).unwrap();
+        let qa = create_member_worktree(&project, "web", "qa", &config).unwrap();
+        let idle = create_member_worktree(&project, "web", "idle", &config).unwrap();
+        assert_eq!(dev.branch, "cam/web/dev");
+        assert!(create_member_worktree(&project, "web", "dev", &config).is_err());
+
+        commit_file(Path::new(&dev.path), "README.md", "dev\n");
+        commit_file(Path::new(&dev.path), "api.rs", "fn api() {}\n");
+        commit_file(Path::new(&qa.path), "README.md", "qa\n");
+
+        let team_dir = temp.path().join("team");
+        let worktrees = vec![dev, qa, idle];
+        save_worktrees(&team_dir, &worktrees).unwrap();
+        assert_eq!(load_worktrees(&team_dir), worktrees);
+
+        let report = merge_member_branches(&project, &worktrees).unwrap();
+        assert_eq!(report[0].outcome, MergeOutcome::Merged { commits: 2 });
+        assert_eq!(
+            report[1].outcome,
+            MergeOutcome::Conflict {
+                files: vec!["README.md".to_string()]
+            }
+        );
+        assert_eq!(report[2].outcome, MergeOutcome::UpToDate);
+        assert!(fs::read_to_string(project.join("api.rs")).is_ok());
+
+        let event = conflict_event("web", "/p", &report[1]).unwrap();
+        assert_eq!(event.agent_id, "qa@web");
+        assert!(conflict_event("web", "/p", &report[0]).is_none());
+    }
+}
@@ -13,12 +13,18 @@ use ratatui::prelude::*;
 
 use chrono::{DateTime, Local, TimeZone};
 
+use crate::agent::prompt_queue::{send_or_queue, Delivery, PromptQueue};
 use crate::notification::NotificationStore;
+use crate::session::reply_macro::macro_name;
+use crate::session::{ConversationStateManager, ReplyMacros, ReplyResult, ReplyVerification};
+use crate::tui::config::{Action, KeyMap, Theme, TuiConfig};
 use crate::tui::logs::LogsState;
 use crate::tui::search::SearchInput;
+use crate::tui::search_index::SearchIndex;
 use crate::tui::state::Focus;
-use crate::tui::state::{AgentItem, NotificationItem, View};
+use crate::tui::state::{AgentItem, NotificationItem, TeamItem, View};
 use crate::tui::terminal_stream::TerminalStream;
+use crate::team::{ProgressHistory, TeamBridge, TeamOrchestrator};
 use crate::{AgentManager, TmuxManager};
 
 pub type AppResult<T> = Result<T>;
@@ -50,6 +56,8 @@ pub struct App {
     pub filter_mode: bool,
     /// 过滤输入
     pub filter_input: SearchInput,
+    /// 模糊搜索索引（刷新 agent 和通知时重建）
+    pub search_index: SearchIndex,
     /// 上次鼠标滚动时间（用于节流）
     pub last_scroll_time: std::time::Instant,
     /// 当前焦点
@@ -60,6 +68,24 @@ pub struct App {
     pub detail_scroll_offset: usize,
     /// 终端预览滚动偏移
     pub preview_scroll_offset: usize,
+    /// 颜色主题
+    pub theme: Theme,
+    /// 按键绑定
+    pub keymap: KeyMap,
+    /// Team 进度（Teams 视图）
+    pub teams: Vec<TeamItem>,
+    /// Teams 视图选中索引
+    pub team_selected: usize,
+    /// 回复模式（回复选中的 agent）
+    pub reply_mode: bool,
+    /// 回复输入（文本或 `@宏`）
+    pub reply_input: SearchInput,
+    /// 选中 agent 可用的回复宏（进入回复模式时读取）
+    pub reply_macros: Vec<String>,
+    /// 最近一次回复的结果
+    pub reply_status: Option<String>,
+    /// 后台发送中的回复结果（回复宏可能包含等待）
+    reply_rx: Option<std::sync::mpsc::Receiver<String>>,
 }
 
 /// 鼠标滚动节流间隔（毫秒）- 限制滚动频率，确保每次滚动只移动一项
@@ -67,6 +93,11 @@ pub const SCROLL_THROTTLE_MS: u64 = 300;
 
 impl App {
     pub fn new() -> Self {
+        Self::with_config(TuiConfig::default())
+    }
+
+    /// 使用指定的主题和按键绑定创建
+    pub fn with_config(config: TuiConfig) -> Self {
         Self {
             should_quit: false,
             view: View::Dashboard,
@@ -80,11 +111,21 @@ impl App {
             logs_state: LogsState::new(),
             filter_mode: false,
             filter_input: SearchInput::new(),
+            search_index: SearchIndex::default(),
             last_scroll_time: std::time::Instant::now(),
             focus: Focus::AgentList,
             notification_selected: 0,
             detail_scroll_offset: 0,
             preview_scroll_offset: 0,
+            theme: config.theme,
+            keymap: config.keymap,
+            teams: Vec::new(),
+            team_selected: 0,
+            reply_mode: false,
+            reply_input: SearchInput::new(),
+            reply_macros: Vec::new(),
+            reply_status: None,
+            reply_rx: None,
         }
     }
 
@@ -92,28 +133,27 @@ impl App {
         self.should_quit = true;
     }
 
-    /// 选择下一个 agent
+    /// 选择下一个 agent（在过滤结果中）
     pub fn next_agent(&mut self) {
-        if !self.agents.is_empty() {
-            self.selected_index = (self.selected_index + 1) % self.agents.len();
+        let len = self.filtered_agents().len();
+        if len > 0 {
+            self.selected_index = (self.selected_index + 1) % len;
             self.preview_scroll_offset = 0;
         }
     }
 
-    /// 选择上一个 agent
+    /// 选择上一个 agent（在过滤结果中）
     pub fn prev_agent(&mut self) {
-        if !self.agents.is_empty() {
-            self.selected_index = self
-                .selected_index
-                .checked_sub(1)
-                .unwrap_or(self.agents.len() - 1);
+        let len = self.filtered_agents().len();
+        if len > 0 {
+            self.selected_index = self.selected_index.checked_sub(1).unwrap_or(len - 1);
             self.preview_scroll_offset = 0;
         }
     }
 
-    /// 获取当前选中的 agent
+    /// 获取当前选中的 agent（`selected_index` 是过滤结果中的位置）
     pub fn selected_agent(&self) -> Option<&AgentItem> {
-        self.agents.get(self.selected_index)
+        self.filtered_agents().get(self.selected_index).copied()
     }
 
     /// 切换视图
@@ -123,10 +163,52 @@ impl App {
                 let _ = self.logs_state.load();
                 View::Logs
             }
-            View::Logs => View::Dashboard,
+            View::Logs | View::Teams => View::Dashboard,
         };
     }
 
+    /// 打开 Team 进度视图
+    pub fn open_teams(&mut self) {
+        self.refresh_teams();
+        self.view = View::Teams;
+    }
+
+    /// 刷新 Team 进度和历史快照
+    pub fn refresh_teams(&mut self) {
+        let orchestrator = TeamOrchestrator::new();
+        self.teams = TeamBridge::new()
+            .list_teams()
+            .into_iter()
+            .filter_map(|team| {
+                let progress = orchestrator.get_team_progress(&team).ok()?;
+                let history = ProgressHistory::for_team(&team).load();
+                Some(TeamItem { progress, history })
+            })
+            .collect();
+        if self.team_selected >= self.teams.len() {
+            self.team_selected = self.teams.len().saturating_sub(1);
+        }
+    }
+
+    /// 选择下一个 team
+    pub fn next_team(&mut self) {
+        if !self.teams.is_empty() {
+            self.team_selected = (self.team_selected + 1) % self.teams.len();
+        }
+    }
+
+    /// 选择上一个 team
+    pub fn prev_team(&mut self) {
+        if !self.teams.is_empty() {
+            self.team_selected = self.team_selected.checked_sub(1).unwrap_or(self.teams.len() - 1);
+        }
+    }
+
+    /// 当前选中的 team
+    pub fn selected_team(&self) -> Option<&TeamItem> {
+        self.teams.get(self.team_selected)
+    }
+
     /// 进入过滤模式
     pub fn enter_filter_mode(&mut self) {
         self.filter_mode = true;
@@ -144,26 +226,112 @@ impl App {
         self.selected_index = 0;
     }
 
-    /// 获取过滤后的 agents（实时过滤）
+    /// 进入回复模式（需要选中 agent）
+    pub fn enter_reply_mode(&mut self) {
+        let Some(agent_id) = self.selected_agent().map(|agent| agent.id.clone()) else {
+            return;
+        };
+        self.reply_mode = true;
+        self.reply_input.clear();
+        // 列表中只有项目名，按完整路径匹配项目覆盖
+        let project = AgentManager::new()
+            .get_agent(&agent_id)
+            .ok()
+            .flatten()
+            .map(|agent| agent.project_path);
+        self.reply_macros = ReplyMacros::load().names(project.as_deref());
+    }
+
+    /// 取消回复
+    pub fn cancel_reply(&mut self) {
+        self.reply_mode = false;
+        self.reply_input.clear();
+    }
+
+    /// 在后台把回复发送给选中的 agent
+    pub fn submit_reply(&mut self) {
+        let reply = self.reply_input.text().trim().to_string();
+        self.cancel_reply();
+        let Some(agent_id) = self.selected_agent().map(|agent| agent.id.clone()) else {
+            return;
+        };
+        if reply.is_empty() {
+            return;
+        }
+        self.reply_status = Some(format!("正在发送 '{}' 到 {}…", reply, agent_id));
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let conversation = ConversationStateManager::new();
+            // 没有待处理确认时作为新指令发送，agent 处理中时排队
+            let status = if macro_name(&reply).is_none()
+                && !conversation.has_pending_for(&agent_id).unwrap_or(true)
+            {
+                describe_delivery(
+                    &agent_id,
+                    send_or_queue(&AgentManager::new(), &PromptQueue::new(), &agent_id, &reply),
+                )
+            } else {
+                describe_reply_result(&agent_id, conversation.handle_reply(&reply, Some(&agent_id)))
+            };
+            let _ = tx.send(status);
+        });
+        self.reply_rx = Some(rx);
+    }
+
+    /// 取回后台回复的结果
+    pub fn poll_reply_result(&mut self) {
+        if let Some(status) = self.reply_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.reply_status = Some(status);
+            self.reply_rx = None;
+        }
+    }
+
+    /// 重建模糊搜索索引
+    pub fn rebuild_search_index(&mut self) {
+        self.search_index = SearchIndex::build(&self.agents, &self.notifications);
+    }
+
+    /// 当前的搜索索引；列表在刷新之外被修改时临时重建
+    fn current_search_index(&self) -> std::borrow::Cow<'_, SearchIndex> {
+        if self
+            .search_index
+            .is_current(&self.agents, &self.notifications)
+        {
+            std::borrow::Cow::Borrowed(&self.search_index)
+        } else {
+            std::borrow::Cow::Owned(SearchIndex::build(&self.agents, &self.notifications))
+        }
+    }
+
+    /// 获取过滤后的 agents（实时模糊过滤，按匹配度排序）
     pub fn filtered_agents(&self) -> Vec<&AgentItem> {
         let query = self.filter_input.text();
-        if query.is_empty() {
-            self.agents.iter().collect()
-        } else {
-            let query_lower = query.to_lowercase();
-            self.agents
-                .iter()
-                .filter(|a| {
-                    a.id.to_lowercase().contains(&query_lower)
-                        || a.project.to_lowercase().contains(&query_lower)
-                })
-                .collect()
+        if query.trim().is_empty() {
+            return self.agents.iter().collect();
+        }
+        self.current_search_index()
+            .match_agents(query)
+            .into_iter()
+            .map(|i| &self.agents[i])
+            .collect()
+    }
+
+    /// 过滤后的通知下标（按时间顺序，与 `notifications` 一致）
+    pub fn filtered_notification_indices(&self) -> Vec<usize> {
+        let query = self.filter_input.text();
+        if query.trim().is_empty() {
+            return (0..self.notifications.len()).collect();
         }
+        self.current_search_index().match_notifications(query)
     }
 
-    /// 过滤输入变化时重置选择
+    /// 过滤输入变化时重置选择（通知选中最新的匹配项）
     pub fn on_filter_change(&mut self) {
         self.selected_index = 0;
+        if let Some(&last) = self.filtered_notification_indices().last() {
+            self.notification_selected = last;
+            self.detail_scroll_offset = 0;
+        }
     }
 
     /// 切换焦点（左侧面板之间切换）
@@ -192,28 +360,40 @@ impl App {
         };
     }
 
-    /// 选择下一条通知
+    /// 选择下一条通知（在过滤结果中）
     pub fn next_notification(&mut self) {
-        if !self.notifications.is_empty() {
-            self.notification_selected =
-                (self.notification_selected + 1) % self.notifications.len();
-            self.detail_scroll_offset = 0;
+        let visible = self.filtered_notification_indices();
+        if visible.is_empty() {
+            return;
         }
+        self.notification_selected = match visible.iter().position(|&i| i == self.notification_selected) {
+            Some(pos) => visible[(pos + 1) % visible.len()],
+            None => visible[visible.len() - 1],
+        };
+        self.detail_scroll_offset = 0;
     }
 
-    /// 选择上一条通知
+    /// 选择上一条通知（在过滤结果中）
     pub fn prev_notification(&mut self) {
-        if !self.notifications.is_empty() {
-            self.notification_selected = self
-                .notification_selected
-                .checked_sub(1)
-                .unwrap_or(self.notifications.len() - 1);
-            self.detail_scroll_offset = 0;
+        let visible = self.filtered_notification_indices();
+        if visible.is_empty() {
+            return;
         }
+        self.notification_selected = match visible.iter().position(|&i| i == self.notification_selected) {
+            Some(pos) => visible[pos.checked_sub(1).unwrap_or(visible.len() - 1)],
+            None => visible[visible.len() - 1],
+        };
+        self.detail_scroll_offset = 0;
     }
 
-    /// 获取当前选中的通知
+    /// 获取当前选中的通知（被过滤掉时返回 None）
     pub fn selected_notification(&self) -> Option<&NotificationItem> {
+        if !self
+            .filtered_notification_indices()
+            .contains(&self.notification_selected)
+        {
+            return None;
+        }
         self.notifications.get(self.notification_selected)
     }
 
@@ -279,6 +459,9 @@ impl App {
                     state,
                     started_at,
                     tmux_session: Some(agent.tmux_session.clone()),
+                    context_usage: agent.context_usage,
+                    ports: agent.ports,
+                    todos: agent.todos,
                 });
             }
         }
@@ -289,6 +472,12 @@ impl App {
         self.agents = items;
         self.last_refresh = std::time::Instant::now();
 
+        // 过滤结果变少时保持选中项在范围内
+        let visible = self.filtered_agents().len();
+        if self.selected_index >= visible {
+            self.selected_index = visible.saturating_sub(1);
+        }
+
         // 更新终端预览
         let session_to_refresh = self
             .selected_agent()
@@ -299,6 +488,7 @@ impl App {
 
         // 刷新通知
         self.refresh_notifications();
+        self.rebuild_search_index();
 
         Ok(())
     }
@@ -401,14 +591,46 @@ impl App {
         let _ = self.refresh_agents();
 
         // 调整选中索引
-        if self.selected_index > 0 && self.selected_index >= self.agents.len() {
-            self.selected_index = self.agents.len().saturating_sub(1);
+        let visible = self.filtered_agents().len();
+        if self.selected_index > 0 && self.selected_index >= visible {
+            self.selected_index = visible.saturating_sub(1);
         }
 
         Ok(Some(agent_id))
     }
 }
 
+/// TUI 回复结果的一行描述
+pub(crate) fn describe_reply_result(agent_id: &str, result: Result<ReplyResult>) -> String {
+    match result {
+        Ok(ReplyResult::Sent {
+            agent_id,
+            reply,
+            verification: ReplyVerification::Unverified { .. },
+        }) => format!("⚠️ 已发送 '{}' 到 {}，但提示仍在，请检查终端", reply, agent_id),
+        Ok(ReplyResult::Sent { agent_id, reply, .. }) => {
+            format!("已发送 '{}' 到 {}", reply, agent_id)
+        }
+        Ok(ReplyResult::NoPending | ReplyResult::NeedSelection { .. }) => {
+            format!("{} 没有待处理的确认", agent_id)
+        }
+        Ok(ReplyResult::InvalidSelection(message)) => message,
+        Ok(ReplyResult::Denied { reason, .. }) => format!("已拒绝: {}", reason),
+        Err(e) => format!("发送回复失败: {}", e),
+    }
+}
+
+/// TUI 发送新指令结果的一行描述
+pub(crate) fn describe_delivery(agent_id: &str, result: Result<Delivery>) -> String {
+    match result {
+        Ok(Delivery::Sent) => format!("已发送指令到 {}", agent_id),
+        Ok(Delivery::Queued(prompt)) => {
+            format!("{} 正在处理，指令已加入队列 {}", agent_id, prompt.id)
+        }
+        Err(e) => format!("发送指令失败: {}", e),
+    }
+}
+
 impl Default for App {
     fn default() -> Self {
         Self::new()
@@ -455,8 +677,12 @@ pub fn run(terminal: &mut Tui, app: &mut App, refresh_interval_ms: u64) -> AppRe
         if let Some(event) = poll_event(Duration::from_millis(100))? {
             match event {
                 TuiEvent::Key(key) => {
-                    // 检查是否是 Enter 键
-                    if key.code == crossterm::event::KeyCode::Enter {
+                    // 检查是否是 attach 键（默认 Enter）
+                    if !app.filter_mode
+                        && !app.reply_mode
+                        && app.view == View::Dashboard
+                        && app.keymap.is(Action::Attach, &key)
+                    {
                         // 只在 Agent 焦点时 attach tmux
                         if app.focus == Focus::AgentList {
                             if let Ok(Some(session)) = app.attach_selected_tmux() {
@@ -464,8 +690,8 @@ pub fn run(terminal: &mut Tui, app: &mut App, refresh_interval_ms: u64) -> AppRe
                                 restore_terminal(terminal)?;
 
                                 // 执行 tmux attach
-                                let status = std::process::Command::new("tmux")
-                                    .args(["attach-session", "-t", &session])
+                                let status = crate::infra::multiplexer::from_config()
+                                    .attach_command(&session)
                                     .status();
 
                                 // 重新初始化终端
@@ -482,11 +708,10 @@ pub fn run(terminal: &mut Tui, app: &mut App, refresh_interval_ms: u64) -> AppRe
                             }
                         }
                     }
-                    // 检查是否是 x 或 d 键（关闭 agent）
-                    if key.code == crossterm::event::KeyCode::Char('x')
-                        || key.code == crossterm::event::KeyCode::Char('d')
-                    {
-                        if !app.filter_mode
+                    // 检查是否是 close 键（默认 x 或 d，关闭 agent）
+                    if app.keymap.is(Action::Close, &key)
+                        && !app.filter_mode
+                            && !app.reply_mode
                             && app.view == View::Dashboard
                             && app.focus == Focus::AgentList
                         {
@@ -500,11 +725,13 @@ pub fn run(terminal: &mut Tui, app: &mut App, refresh_interval_ms: u64) -> AppRe
                             last_full_refresh = std::time::Instant::now();
                             continue;
                         }
-                    }
-                    let prev_selected = app.selected_index;
+                    // 按 agent id 比较：过滤结果变化时同一下标可能对应不同的 agent
+                    let prev_selected = app.selected_agent().map(|agent| agent.id.clone());
                     handle_key(app, key);
                     // Agent 焦点下选择变化时刷新终端预览
-                    if app.focus == Focus::AgentList && prev_selected != app.selected_index {
+                    if app.focus == Focus::AgentList
+                        && prev_selected.as_deref() != app.selected_agent().map(|agent| agent.id.as_str())
+                    {
                         app.switch_agent_stream();
                         let session_to_refresh = app
                             .selected_agent()
@@ -533,9 +760,14 @@ pub fn run(terminal: &mut Tui, app: &mut App, refresh_interval_ms: u64) -> AppRe
             }
         }
 
+        app.poll_reply_result();
+
         // 定时全量刷新
         if last_full_refresh.elapsed() >= refresh_interval {
             let _ = app.refresh_agents();
+            if app.view == View::Teams {
+                app.refresh_teams();
+            }
             last_full_refresh = std::time::Instant::now();
         }
     }
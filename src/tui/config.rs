@@ -0,0 +1,584 @@
+//! TUI 配置 - 主题颜色和按键绑定
+//!
+//! 从 `~/.config/code-agent-monitor/config.json` 的 `tui` 节读取，启动时校验：
+//! ```json
+//! {
+//!   "tui": {
+//!     "theme": "light",
+//!     "colors": { "accent": "#005fd7", "high": "red" },
+//!     "keys": { "down": ["j", "n"], "close": "ctrl-x" }
+//!   }
+//! }
+//! ```
+//!
+//! - `theme`: 内置主题 `dark`（默认）或 `light`（浅色背景终端）
+//! - `colors`: 覆盖单个颜色，支持颜色名（`red`、`dark-gray`）、`#rrggbb` 和 256 色序号
+//! - `keys`: 覆盖某个动作的全部按键，值为一个或多个按键（`q`、`G`、`ctrl-c`、`enter`、`esc`、
+//!   `tab`、`up`/`down`/`left`/`right`、`home`/`end`、`space`）
+//!
+//! 未知的颜色名、动作名、无法解析的按键，以及同一按键绑定到多个动作都会报错。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::notification::Urgency;
+use crate::AgentStatus;
+
+/// TUI 颜色主题
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// 焦点边框、过滤模式状态栏
+    pub accent: Color,
+    /// 非焦点边框
+    pub muted: Color,
+    /// 顶部状态栏
+    pub title_bg: Color,
+    pub title_fg: Color,
+    /// 日志视图状态栏背景
+    pub logs_title_bg: Color,
+    /// 底部快捷键栏
+    pub bar_bg: Color,
+    pub bar_fg: Color,
+    /// 过滤输入框
+    pub input_bg: Color,
+    pub input_fg: Color,
+    /// 选中的通知
+    pub selection_bg: Color,
+    pub selection_fg: Color,
+    /// 紧急程度 / 风险等级（也用于日志 ERROR / WARN）
+    pub high: Color,
+    pub medium: Color,
+    pub low: Color,
+    /// Agent 状态（processing 也用于日志 INFO）
+    pub processing: Color,
+    pub waiting: Color,
+    pub decision: Color,
+    pub unknown: Color,
+}
+
+impl Theme {
+    /// 深色背景（默认）
+    pub fn dark() -> Self {
+        Self {
+            accent: Color::Cyan,
+            muted: Color::DarkGray,
+            title_bg: Color::Blue,
+            title_fg: Color::White,
+            logs_title_bg: Color::Magenta,
+            bar_bg: Color::DarkGray,
+            bar_fg: Color::Reset,
+            input_bg: Color::Yellow,
+            input_fg: Color::Black,
+            selection_bg: Color::DarkGray,
+            selection_fg: Color::White,
+            high: Color::Red,
+            medium: Color::Yellow,
+            low: Color::DarkGray,
+            processing: Color::Green,
+            waiting: Color::Yellow,
+            decision: Color::Red,
+            unknown: Color::Reset,
+        }
+    }
+
+    /// 浅色背景：避免黄色、青色文字和深灰背景
+    pub fn light() -> Self {
+        Self {
+            accent: Color::Blue,
+            muted: Color::Rgb(110, 110, 110),
+            title_bg: Color::Blue,
+            title_fg: Color::White,
+            logs_title_bg: Color::Magenta,
+            bar_bg: Color::Rgb(220, 220, 220),
+            bar_fg: Color::Black,
+            input_bg: Color::Rgb(255, 235, 150),
+            input_fg: Color::Black,
+            selection_bg: Color::Rgb(205, 222, 250),
+            selection_fg: Color::Black,
+            high: Color::Rgb(190, 0, 0),
+            medium: Color::Rgb(170, 95, 0),
+            low: Color::Rgb(110, 110, 110),
+            processing: Color::Rgb(0, 125, 0),
+            waiting: Color::Rgb(170, 95, 0),
+            decision: Color::Rgb(190, 0, 0),
+            unknown: Color::Reset,
+        }
+    }
+
+    /// 按名称选择内置主题
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "accent" => &mut self.accent,
+            "muted" => &mut self.muted,
+            "title_bg" => &mut self.title_bg,
+            "title_fg" => &mut self.title_fg,
+            "logs_title_bg" => &mut self.logs_title_bg,
+            "bar_bg" => &mut self.bar_bg,
+            "bar_fg" => &mut self.bar_fg,
+            "input_bg" => &mut self.input_bg,
+            "input_fg" => &mut self.input_fg,
+            "selection_bg" => &mut self.selection_bg,
+            "selection_fg" => &mut self.selection_fg,
+            "high" => &mut self.high,
+            "medium" => &mut self.medium,
+            "low" => &mut self.low,
+            "processing" => &mut self.processing,
+            "waiting" => &mut self.waiting,
+            "decision" => &mut self.decision,
+            "unknown" => &mut self.unknown,
+            _ => return None,
+        })
+    }
+
+    /// 紧急程度颜色
+    pub fn urgency(&self, urgency: Urgency) -> Color {
+        match urgency {
+            Urgency::High => self.high,
+            Urgency::Medium => self.medium,
+            Urgency::Low => self.low,
+        }
+    }
+
+    /// 风险等级颜色（`HIGH` / `MEDIUM` / `LOW`）
+    pub fn risk(&self, risk: &str) -> Color {
+        match risk.to_ascii_uppercase().as_str() {
+            "HIGH" => self.high,
+            "MEDIUM" => self.medium,
+            "LOW" => self.low,
+            _ => self.unknown,
+        }
+    }
+
+    /// Agent 状态颜色
+    pub fn status(&self, status: &AgentStatus) -> Color {
+        match status {
+            AgentStatus::Processing | AgentStatus::Running => self.processing,
+            AgentStatus::WaitingForInput => self.waiting,
+            AgentStatus::DecisionRequired => self.decision,
+            AgentStatus::Unknown => self.unknown,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// 可绑定按键的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    /// 进入右侧面板
+    Open,
+    /// 返回 / 清除过滤 / 退出日志视图
+    Back,
+    /// 切换焦点
+    NextFocus,
+    /// 进入过滤模式
+    Filter,
+    /// 跳转到 agent 的 tmux 会话
+    Attach,
+    /// 关闭选中的 agent
+    Close,
+    /// 日志视图：切换过滤级别
+    LogLevel,
+    /// 日志视图：跳到最新
+    LogBottom,
+    /// 打开 Team 进度视图
+    Teams,
+    /// 回复选中的 agent（文本或 `@宏`）
+    Reply,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Quit,
+        Action::Up,
+        Action::Down,
+        Action::Open,
+        Action::Back,
+        Action::NextFocus,
+        Action::Filter,
+        Action::Attach,
+        Action::Close,
+        Action::LogLevel,
+        Action::LogBottom,
+        Action::Teams,
+        Action::Reply,
+    ];
+
+    /// 配置中的动作名
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Open => "open",
+            Action::Back => "back",
+            Action::NextFocus => "next_focus",
+            Action::Filter => "filter",
+            Action::Attach => "attach",
+            Action::Close => "close",
+            Action::LogLevel => "log_level",
+            Action::LogBottom => "log_bottom",
+            Action::Teams => "teams",
+            Action::Reply => "reply",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "ctrl-c"],
+            Action::Up => &["k", "up"],
+            Action::Down => &["j", "down"],
+            Action::Open => &["l", "right"],
+            Action::Back => &["esc", "h", "left"],
+            Action::NextFocus => &["tab"],
+            Action::Filter => &["/"],
+            Action::Attach => &["enter"],
+            Action::Close => &["x", "d"],
+            Action::LogLevel => &["f"],
+            Action::LogBottom => &["G"],
+            Action::Teams => &["t"],
+            Action::Reply => &["r"],
+        }
+    }
+}
+
+/// 一个按键（可带 Ctrl）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl KeyBinding {
+    /// 解析 `q`、`G`、`ctrl-c`、`enter`、`esc` 等
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (ctrl, key) = match spec.strip_prefix("ctrl-").or(spec.strip_prefix("ctrl+")) {
+            Some(rest) => (true, rest),
+            None => (false, spec),
+        };
+        let code = match key.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Self { code, ctrl })
+    }
+
+    /// 按键事件是否匹配（字符键允许 Shift，如 `G`）
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        self.code == key.code && self.ctrl == key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    /// 帮助栏中的显示
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            other => format!("{:?}", other),
+        };
+        if self.ctrl {
+            format!("Ctrl-{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+/// 动作到按键的映射
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|spec| KeyBinding::parse(spec))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// 按键对应的动作
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|binding| binding.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    /// 按键是否绑定到该动作
+    pub fn is(&self, action: Action, key: &KeyEvent) -> bool {
+        self.action(key) == Some(action)
+    }
+
+    /// 动作的主按键（帮助栏显示）
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .and_then(|keys| keys.first())
+            .map(|binding| binding.label())
+            .unwrap_or_default()
+    }
+}
+
+/// 一个或多个按键
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum KeySpecs {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpecs {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            KeySpecs::One(spec) => vec![spec],
+            KeySpecs::Many(specs) => specs,
+        }
+    }
+}
+
+/// config.json 的 `tui` 节
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct TuiConfigFile {
+    theme: Option<String>,
+    colors: HashMap<String, String>,
+    keys: HashMap<String, KeySpecs>,
+}
+
+/// 校验后的 TUI 配置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TuiConfig {
+    pub theme: Theme,
+    pub keymap: KeyMap,
+}
+
+impl TuiConfig {
+    /// 读取 config.json 的 `tui` 节；缺失时使用默认值，配置无效时返回全部错误
+    pub fn load() -> Result<Self> {
+        Self::load_from(&crate::infra::paths::config_path())
+    }
+
+    /// 从指定配置文件读取
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Ok(Self::default());
+        };
+        let json: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("{} 不是有效的 JSON: {}", path.display(), e))?;
+        match json.get("tui") {
+            Some(tui) => Self::from_value(tui.clone()),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// 解析并校验 `tui` 节
+    pub fn from_value(value: serde_json::Value) -> Result<Self> {
+        let file: TuiConfigFile =
+            serde_json::from_value(value).map_err(|e| anyhow!("tui 配置格式错误: {}", e))?;
+        let mut errors = Vec::new();
+
+        let mut theme = match file.theme.as_deref() {
+            None => Theme::default(),
+            Some(name) => Theme::preset(name).unwrap_or_else(|| {
+                errors.push(format!("未知主题 '{}'（可选: dark, light）", name));
+                Theme::default()
+            }),
+        };
+        let mut colors: Vec<_> = file.colors.into_iter().collect();
+        colors.sort();
+        for (name, value) in colors {
+            match (theme.color_mut(&name), Color::from_str(&value)) {
+                (Some(slot), Ok(color)) => *slot = color,
+                (None, _) => errors.push(format!("未知颜色项 '{}'", name)),
+                (Some(_), Err(_)) => errors.push(format!("颜色 {} 的值 '{}' 无效", name, value)),
+            }
+        }
+
+        let mut keymap = KeyMap::default();
+        let mut keys: Vec<_> = file.keys.into_iter().collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, specs) in keys {
+            let Some(action) = Action::parse(&name) else {
+                errors.push(format!("未知动作 '{}'", name));
+                continue;
+            };
+            let specs = specs.into_vec();
+            if specs.is_empty() {
+                errors.push(format!("动作 {} 至少需要一个按键", name));
+                continue;
+            }
+            let mut bindings = Vec::new();
+            for spec in specs {
+                match KeyBinding::parse(&spec) {
+                    Some(binding) => bindings.push(binding),
+                    None => errors.push(format!("动作 {} 的按键 '{}' 无效", name, spec)),
+                }
+            }
+            keymap.bindings.insert(action, bindings);
+        }
+
+        let mut owners: HashMap<KeyBinding, Action> = HashMap::new();
+        for (action, bindings) in &keymap.bindings {
+            for binding in bindings {
+                if let Some(other) = owners.insert(*binding, *action) {
+                    if other != *action {
+                        errors.push(format!(
+                            "按键 {} 同时绑定到 {} 和 {}",
+                            binding.label(),
+                            other.name(),
+                            action.name()
+                        ));
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Self { theme, keymap })
+        } else {
+            Err(anyhow!(errors.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let keymap = KeyMap::default();
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.action(&key(KeyCode::Char('j'), none)),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            keymap.action(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(keymap.action(&key(KeyCode::Char('c'), none)), None);
+        assert_eq!(
+            keymap.action(&key(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            Some(Action::LogBottom)
+        );
+        assert_eq!(keymap.label(Action::Attach), "Enter");
+    }
+
+    #[test]
+    fn test_config_overrides_theme_and_keys() {
+        let config = TuiConfig::from_value(json!({
+            "theme": "light",
+            "colors": {"accent": "#005fd7", "high": "red"},
+            "keys": {"down": ["n", "down"], "close": "ctrl-x"}
+        }))
+        .unwrap();
+        assert_eq!(config.theme.accent, Color::Rgb(0, 95, 215));
+        assert_eq!(config.theme.high, Color::Red);
+        assert_eq!(config.theme.bar_fg, Color::Black);
+
+        let none = KeyModifiers::NONE;
+        assert!(config
+            .keymap
+            .is(Action::Down, &key(KeyCode::Char('n'), none)));
+        assert_eq!(config.keymap.action(&key(KeyCode::Char('j'), none)), None);
+        assert!(config.keymap.is(
+            Action::Close,
+            &key(KeyCode::Char('x'), KeyModifiers::CONTROL)
+        ));
+        assert_eq!(config.keymap.action(&key(KeyCode::Char('x'), none)), None);
+    }
+
+    #[test]
+    fn test_config_validation_errors() {
+        let err = TuiConfig::from_value(json!({
+            "theme": "solarized",
+            "colors": {"accent": "not-a-color", "border": "red"},
+            "keys": {"jump": "g", "up": "ctrl-", "filter": "q"}
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("未知主题 'solarized'"), "{}", err);
+        assert!(
+            err.contains("颜色 accent 的值 'not-a-color' 无效"),
+            "{}",
+            err
+        );
+        assert!(err.contains("未知颜色项 'border'"), "{}", err);
+        assert!(err.contains("未知动作 'jump'"), "{}", err);
+        assert!(err.contains("动作 up 的按键 'ctrl-' 无效"), "{}", err);
+        assert!(err.contains("按键 q 同时绑定到 quit 和 filter"), "{}", err);
+    }
+}
@@ -1,9 +1,11 @@
 //! 事件处理模块
 
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, MouseEvent, MouseEventKind};
 use std::time::Duration;
 
+use crate::tui::Action;
+
 /// TUI 事件
 #[derive(Debug)]
 pub enum TuiEvent {
@@ -30,9 +32,14 @@ pub fn handle_key(app: &mut crate::tui::App, key: KeyEvent) {
         handle_filter_key(app, key);
         return;
     }
+    if app.reply_mode {
+        handle_reply_key(app, key);
+        return;
+    }
     match app.view {
         crate::tui::View::Dashboard => handle_dashboard_key(app, key),
         crate::tui::View::Logs => handle_logs_key(app, key),
+        crate::tui::View::Teams => handle_teams_key(app, key),
     }
 }
 
@@ -72,29 +79,47 @@ fn handle_filter_key(app: &mut crate::tui::App, key: KeyEvent) {
     }
 }
 
+fn handle_reply_key(app: &mut crate::tui::App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.submit_reply(),
+        KeyCode::Esc => app.cancel_reply(),
+
+        KeyCode::Left => app.reply_input.move_left(),
+        KeyCode::Right => app.reply_input.move_right(),
+        KeyCode::Home => app.reply_input.move_home(),
+        KeyCode::End => app.reply_input.move_end(),
+        KeyCode::Backspace => app.reply_input.backspace(),
+        KeyCode::Delete => app.reply_input.delete(),
+        KeyCode::Char(c) => app.reply_input.insert(c),
+        _ => {}
+    }
+}
+
 fn handle_dashboard_key(app: &mut crate::tui::App, key: KeyEvent) {
+    let Some(action) = app.keymap.action(&key) else {
+        return;
+    };
+
     // 右侧面板（Preview/Detail）有独立的按键处理
     match app.focus {
         crate::tui::Focus::Preview => {
-            match key.code {
-                KeyCode::Char('q') => app.quit(),
-                KeyCode::Char('j') | KeyCode::Down => app.preview_scroll_down(),
-                KeyCode::Char('k') | KeyCode::Up => app.preview_scroll_up(),
-                KeyCode::Esc | KeyCode::Left | KeyCode::Char('h') => app.exit_right_panel(),
-                KeyCode::Tab => app.toggle_focus(),
-                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
+            match action {
+                Action::Quit => app.quit(),
+                Action::Down => app.preview_scroll_down(),
+                Action::Up => app.preview_scroll_up(),
+                Action::Back => app.exit_right_panel(),
+                Action::NextFocus => app.toggle_focus(),
                 _ => {}
             }
             return;
         }
         crate::tui::Focus::Detail => {
-            match key.code {
-                KeyCode::Char('q') => app.quit(),
-                KeyCode::Char('j') | KeyCode::Down => app.detail_scroll_down(),
-                KeyCode::Char('k') | KeyCode::Up => app.detail_scroll_up(),
-                KeyCode::Esc | KeyCode::Left | KeyCode::Char('h') => app.exit_right_panel(),
-                KeyCode::Tab => app.toggle_focus(),
-                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
+            match action {
+                Action::Quit => app.quit(),
+                Action::Down => app.detail_scroll_down(),
+                Action::Up => app.detail_scroll_up(),
+                Action::Back => app.exit_right_panel(),
+                Action::NextFocus => app.toggle_focus(),
                 _ => {}
             }
             return;
@@ -103,46 +128,54 @@ fn handle_dashboard_key(app: &mut crate::tui::App, key: KeyEvent) {
     }
 
     // 左侧面板按键处理
-    match key.code {
-        KeyCode::Char('q') => app.quit(),
-        KeyCode::Tab => app.toggle_focus(),
-        KeyCode::Char('j') | KeyCode::Down => match app.focus {
+    match action {
+        Action::Quit => app.quit(),
+        Action::NextFocus => app.toggle_focus(),
+        Action::Down => match app.focus {
             crate::tui::Focus::AgentList => app.next_agent(),
             crate::tui::Focus::Notifications => app.prev_notification(),
             _ => {}
         },
-        KeyCode::Char('k') | KeyCode::Up => match app.focus {
+        Action::Up => match app.focus {
             crate::tui::Focus::AgentList => app.prev_agent(),
             crate::tui::Focus::Notifications => app.next_notification(),
             _ => {}
         },
         // → 或 l 进入右侧面板
-        KeyCode::Right | KeyCode::Char('l') => app.enter_right_panel(),
-        KeyCode::Char('/') => app.enter_filter_mode(),
-        KeyCode::Esc => {
+        Action::Open => app.enter_right_panel(),
+        Action::Filter => app.enter_filter_mode(),
+        Action::Teams => app.open_teams(),
+        Action::Reply if app.focus == crate::tui::Focus::AgentList => app.enter_reply_mode(),
+        Action::Back => {
             if !app.filter_input.is_empty() {
                 app.clear_filter();
             } else if app.focus != crate::tui::Focus::AgentList {
                 app.focus = crate::tui::Focus::AgentList;
             }
         }
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
-        KeyCode::Enter => {
-            // Enter 在 Agent 焦点时跳转 tmux（在 run 函数中处理）
-        }
+        // attach / close 在 Agent 焦点时由 run 函数处理
         _ => {}
     }
 }
 
 fn handle_logs_key(app: &mut crate::tui::App, key: KeyEvent) {
-    match key.code {
-        KeyCode::Char('q') => app.quit(),
-        KeyCode::Esc => app.toggle_view(),
-        KeyCode::Char('j') | KeyCode::Down => app.logs_state.scroll_down(),
-        KeyCode::Char('k') | KeyCode::Up => app.logs_state.scroll_up(),
-        KeyCode::Char('G') => app.logs_state.scroll_to_bottom(),
-        KeyCode::Char('f') => app.logs_state.toggle_filter(),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
+    match app.keymap.action(&key) {
+        Some(Action::Quit) => app.quit(),
+        Some(Action::Back) => app.toggle_view(),
+        Some(Action::Down) => app.logs_state.scroll_down(),
+        Some(Action::Up) => app.logs_state.scroll_up(),
+        Some(Action::LogBottom) => app.logs_state.scroll_to_bottom(),
+        Some(Action::LogLevel) => app.logs_state.toggle_filter(),
+        _ => {}
+    }
+}
+
+fn handle_teams_key(app: &mut crate::tui::App, key: KeyEvent) {
+    match app.keymap.action(&key) {
+        Some(Action::Quit) => app.quit(),
+        Some(Action::Back | Action::Teams) => app.toggle_view(),
+        Some(Action::Down) => app.next_team(),
+        Some(Action::Up) => app.prev_team(),
         _ => {}
     }
 }
@@ -186,6 +219,10 @@ pub fn handle_mouse(app: &mut crate::tui::App, mouse: MouseEvent) -> bool {
                     app.logs_state.scroll_down();
                     false
                 }
+                crate::tui::View::Teams => {
+                    app.next_team();
+                    false
+                }
             }
         }
         MouseEventKind::ScrollUp => {
@@ -215,6 +252,10 @@ pub fn handle_mouse(app: &mut crate::tui::App, mouse: MouseEvent) -> bool {
                     app.logs_state.scroll_up();
                     false
                 }
+                crate::tui::View::Teams => {
+                    app.prev_team();
+                    false
+                }
             }
         }
         _ => false, // 忽略其他鼠标事件（点击、拖拽等）
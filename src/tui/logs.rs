@@ -2,7 +2,6 @@
 
 use anyhow::Result;
 use std::collections::VecDeque;
-use std::path::PathBuf;
 
 /// 日志级别
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -69,9 +68,7 @@ impl LogsState {
 
     /// 加载日志文件
     pub fn load(&mut self) -> Result<()> {
-        let log_path = dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".config/code-agent-monitor/hook.log");
+        let log_path = crate::infra::paths::state_dir().join("hook.log");
 
         if log_path.exists() {
             let content = std::fs::read_to_string(&log_path)?;
@@ -1,9 +1,11 @@
 //! TUI 仪表盘模块
 
 mod app;
+mod config;
 mod event;
 mod logs;
 mod search;
+mod search_index;
 mod state;
 mod terminal_stream;
 mod ui;
@@ -12,9 +14,11 @@ mod ui;
 mod tests;
 
 pub use app::{init_terminal, restore_terminal, run, App, AppResult, Tui};
+pub use config::{Action, KeyBinding, KeyMap, Theme, TuiConfig};
 pub use event::{handle_key, handle_mouse, poll_event, TuiEvent};
 pub use logs::{LogLevel, LogsState};
 pub use search::SearchInput;
-pub use state::{AgentItem, Focus, NotificationItem, View};
+pub use search_index::{fuzzy_score, SearchIndex};
+pub use state::{AgentItem, Focus, NotificationItem, TeamItem, View};
 pub use terminal_stream::TerminalStream;
 pub use ui::render;
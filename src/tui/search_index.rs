@@ -0,0 +1,194 @@
+//! 全局模糊搜索 - 过滤模式下同时筛选 Agent 和通知
+//!
+//! 索引在刷新 agent 列表和通知时重建，保存每个条目的小写搜索字段：
+//! - Agent: agent_id、项目名、agent 类型、tmux session、状态
+//! - 通知: 内容、agent_id、事件类型、项目
+//!
+//! 查询按空白拆分为多个词，每个词都要模糊匹配（子序列）到同一条目的某个字段。
+//! 连续匹配、单词开头匹配和子串匹配得分更高；Agent 按得分排序，通知保持时间顺序。
+
+use chrono::{DateTime, Local};
+
+use crate::tui::{AgentItem, NotificationItem};
+
+/// 子串匹配的基础分（高于任何纯子序列匹配）
+const SUBSTRING_BONUS: i64 = 100;
+/// 连续匹配加分
+const CONSECUTIVE_BONUS: i64 = 5;
+/// 单词开头匹配加分
+const WORD_START_BONUS: i64 = 8;
+/// 单个间隔的最大扣分
+const MAX_GAP_PENALTY: i64 = 5;
+
+/// 模糊匹配得分（参数均为小写），不匹配时返回 None
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let query_len = query.chars().count() as i64;
+    if let Some(pos) = candidate.find(query) {
+        let at_word_start = candidate[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        let start_bonus = if at_word_start { WORD_START_BONUS } else { 0 };
+        return Some(SUBSTRING_BONUS + query_len * CONSECUTIVE_BONUS + start_bonus);
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut prev: Option<usize> = None;
+    for q in query.chars() {
+        let index = next + chars[next..].iter().position(|&c| c == q)?;
+        score += 1;
+        if index == 0 || !chars[index - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        match prev {
+            Some(p) if p + 1 == index => score += CONSECUTIVE_BONUS,
+            Some(p) => score -= ((index - p - 1) as i64).min(MAX_GAP_PENALTY),
+            None => {}
+        }
+        prev = Some(index);
+        next = index + 1;
+    }
+    Some(score)
+}
+
+/// 所有词都匹配时返回总分
+fn match_fields(terms: &[String], fields: &[String]) -> Option<i64> {
+    terms.iter().try_fold(0, |total, term| {
+        fields
+            .iter()
+            .filter_map(|field| fuzzy_score(term, field))
+            .max()
+            .map(|best| total + best)
+    })
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// 索引条目
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    /// 用于判断索引是否过期
+    key: String,
+    fields: Vec<String>,
+}
+
+/// Agent 和通知的搜索索引
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    agents: Vec<Entry>,
+    notifications: Vec<Entry>,
+}
+
+fn notification_key(agent_id: &str, timestamp: &DateTime<Local>) -> String {
+    format!("{}@{}", agent_id, timestamp.timestamp_millis())
+}
+
+impl SearchIndex {
+    /// 从当前的 agent 列表和通知构建
+    pub fn build(agents: &[AgentItem], notifications: &[NotificationItem]) -> Self {
+        let agents = agents
+            .iter()
+            .map(|agent| Entry {
+                key: agent.id.clone(),
+                fields: [
+                    Some(agent.id.as_str()),
+                    Some(agent.project.as_str()),
+                    Some(agent.agent_type.as_str()),
+                    agent.tmux_session.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .map(str::to_lowercase)
+                .chain(std::iter::once(format!("{:?}", agent.state).to_lowercase()))
+                .collect(),
+            })
+            .collect();
+        let notifications = notifications
+            .iter()
+            .map(|n| Entry {
+                key: notification_key(&n.agent_id, &n.timestamp),
+                fields: [
+                    Some(n.message.as_str()),
+                    Some(n.agent_id.as_str()),
+                    Some(n.event_type.as_str()),
+                    n.project.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .map(str::to_lowercase)
+                .collect(),
+            })
+            .collect();
+        Self {
+            agents,
+            notifications,
+        }
+    }
+
+    /// 索引是否对应给定的 agent 列表和通知
+    pub fn is_current(&self, agents: &[AgentItem], notifications: &[NotificationItem]) -> bool {
+        self.agents.len() == agents.len()
+            && self.notifications.len() == notifications.len()
+            && self
+                .agents
+                .iter()
+                .zip(agents)
+                .all(|(entry, agent)| entry.key == agent.id)
+            && self
+                .notifications
+                .iter()
+                .zip(notifications)
+                .all(|(entry, n)| entry.key == notification_key(&n.agent_id, &n.timestamp))
+    }
+
+    /// 匹配的 agent 下标，按得分降序（同分保持原顺序）
+    pub fn match_agents(&self, query: &str) -> Vec<usize> {
+        let terms = query_terms(query);
+        let mut scored: Vec<(usize, i64)> = self
+            .agents
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| match_fields(&terms, &entry.fields).map(|s| (i, s)))
+            .collect();
+        scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(i, _)| i).collect()
+    }
+
+    /// 匹配的通知下标（保持时间顺序）
+    pub fn match_notifications(&self, query: &str) -> Vec<usize> {
+        let terms = query_terms(query);
+        self.notifications
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| match_fields(&terms, &entry.fields).is_some())
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("cam", "code-agent-monitor").is_some());
+        assert!(fuzzy_score("wfi", "waitingforinput").is_some());
+        assert!(fuzzy_score("xyz", "code-agent-monitor").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        // 子串 > 单词开头的子序列 > 分散的子序列
+        let substring = fuzzy_score("agent", "code-agent-monitor").unwrap();
+        let initials = fuzzy_score("cam", "code-agent-monitor").unwrap();
+        let scattered = fuzzy_score("cdt", "code-agent-monitor").unwrap();
+        assert!(substring > initials, "{} {}", substring, initials);
+        assert!(initials > scattered, "{} {}", initials, scattered);
+    }
+}
@@ -1,6 +1,8 @@
 //! TUI 状态数据结构
 
+use crate::agent::todos::TodoItem;
 use crate::notification::Urgency;
+use crate::team::{ProgressSample, TeamProgress};
 use crate::AgentStatus;
 use chrono::{DateTime, Local};
 
@@ -13,6 +15,12 @@ pub struct AgentItem {
     pub state: AgentStatus,
     pub started_at: DateTime<Local>,
     pub tmux_session: Option<String>,
+    /// 上下文窗口使用率（百分比）
+    pub context_usage: Option<u8>,
+    /// 进程树正在监听的 TCP 端口
+    pub ports: Vec<u16>,
+    /// Claude Code 的 TODO 列表（watcher 从 TodoWrite 解析）
+    pub todos: Vec<TodoItem>,
 }
 
 /// 当前焦点区域
@@ -52,4 +60,14 @@ pub enum View {
     #[default]
     Dashboard,
     Logs,
+    /// Team 进度和待处理任务趋势
+    Teams,
+}
+
+/// Team 进度条目（TUI 显示用）
+#[derive(Debug, Clone)]
+pub struct TeamItem {
+    pub progress: TeamProgress,
+    /// 历史快照（按时间顺序）
+    pub history: Vec<ProgressSample>,
 }
@@ -1,8 +1,8 @@
 //! 终端实时流模块 - 使用 tmux pipe-pane
 
+use crate::infra::tmux_config::tmux_command;
 use anyhow::Result;
 use std::path::PathBuf;
-use std::process::Command;
 
 /// 终端流管理器
 pub struct TerminalStream {
@@ -29,7 +29,7 @@ impl TerminalStream {
         let _ = std::fs::remove_file(&pipe_path);
 
         // 启动 pipe-pane
-        let status = Command::new("tmux")
+        let status = tmux_command()
             .args([
                 "pipe-pane",
                 "-t",
@@ -51,9 +51,7 @@ impl TerminalStream {
     pub fn stop(&mut self) {
         if let Some(ref session) = self.current_pane {
             // 关闭 pipe-pane
-            let _ = Command::new("tmux")
-                .args(["pipe-pane", "-t", session])
-                .status();
+            let _ = tmux_command().args(["pipe-pane", "-t", session]).status();
         }
 
         // 清理文件
@@ -14,6 +14,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: chrono::Local::now(),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
             AgentItem {
                 id: "2".to_string(),
@@ -22,6 +25,9 @@ mod tests {
                 state: AgentStatus::Unknown,
                 started_at: chrono::Local::now(),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
         ];
 
@@ -64,6 +70,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: now - chrono::Duration::hours(2),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
             AgentItem {
                 id: "new".to_string(),
@@ -72,6 +81,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: now,
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
             AgentItem {
                 id: "mid".to_string(),
@@ -80,6 +92,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: now - chrono::Duration::hours(1),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
         ];
 
@@ -102,6 +117,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: chrono::Local::now(),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
             AgentItem {
                 id: "cam-456".to_string(),
@@ -110,6 +128,9 @@ mod tests {
                 state: AgentStatus::Unknown,
                 started_at: chrono::Local::now(),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
         ];
 
@@ -218,6 +239,9 @@ mod tests {
             state: AgentStatus::Processing,
             started_at: chrono::Local::now(),
             tmux_session: Some("cam-test".to_string()),
+            context_usage: None,
+            ports: Vec::new(),
+            todos: Vec::new(),
         }];
 
         let agent = app.selected_agent().unwrap();
@@ -235,6 +259,9 @@ mod tests {
             state: AgentStatus::Processing,
             started_at: chrono::Local::now(),
             tmux_session: Some("cam-test-close".to_string()),
+            context_usage: None,
+            ports: Vec::new(),
+            todos: Vec::new(),
         }];
 
         // close_selected_agent should return the agent ID
@@ -313,6 +340,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: chrono::Local::now(),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
             AgentItem {
                 id: "a2".to_string(),
@@ -321,6 +351,9 @@ mod tests {
                 state: AgentStatus::Processing,
                 started_at: chrono::Local::now(),
                 tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
             },
         ];
         app.notifications = vec![
@@ -458,4 +491,215 @@ mod tests {
         // Should still point to cam-target (now at index 2)
         assert_eq!(app.selected_notification().unwrap().agent_id, "cam-target");
     }
+
+    #[test]
+    fn test_remapped_keys_drive_dashboard() {
+        use crate::tui::{handle_key, TuiConfig};
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let config = TuiConfig::from_value(serde_json::json!({
+            "keys": {"down": "n", "up": "p", "quit": "ctrl-q"}
+        }))
+        .unwrap();
+        let mut app = App::with_config(config);
+        app.agents = (0..3)
+            .map(|i| AgentItem {
+                id: i.to_string(),
+                agent_type: "claude".to_string(),
+                project: "test".to_string(),
+                state: AgentStatus::Processing,
+                started_at: chrono::Local::now(),
+                tmux_session: None,
+                context_usage: None,
+                ports: Vec::new(),
+                todos: Vec::new(),
+            })
+            .collect();
+
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        handle_key(&mut app, press(KeyCode::Char('n')));
+        assert_eq!(app.selected_index, 1);
+        // 默认的 j 已被替换
+        handle_key(&mut app, press(KeyCode::Char('j')));
+        assert_eq!(app.selected_index, 1);
+        handle_key(&mut app, press(KeyCode::Char('p')));
+        assert_eq!(app.selected_index, 0);
+
+        handle_key(&mut app, press(KeyCode::Char('q')));
+        assert!(!app.should_quit);
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+        );
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_reply_mode() {
+        use crate::tui::handle_key;
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = App::new();
+        // 没有选中的 agent 时不进入回复模式
+        handle_key(&mut app, press(KeyCode::Char('r')));
+        assert!(!app.reply_mode);
+
+        app.agents = vec![AgentItem {
+            id: "cam-1".to_string(),
+            agent_type: "claude".to_string(),
+            project: "test".to_string(),
+            state: AgentStatus::WaitingForInput,
+            started_at: chrono::Local::now(),
+            tmux_session: None,
+            context_usage: None,
+            ports: Vec::new(),
+            todos: Vec::new(),
+        }];
+        handle_key(&mut app, press(KeyCode::Char('r')));
+        assert!(app.reply_mode);
+
+        // 回复模式下按键作为输入，不触发快捷键
+        for c in "@ok q".chars() {
+            handle_key(&mut app, press(KeyCode::Char(c)));
+        }
+        assert_eq!(app.reply_input.text(), "@ok q");
+        assert!(!app.should_quit);
+
+        handle_key(&mut app, press(KeyCode::Esc));
+        assert!(!app.reply_mode);
+        assert!(app.reply_input.is_empty());
+    }
+
+    #[test]
+    fn test_describe_reply_result() {
+        use crate::session::{ReplyResult, ReplyVerification};
+        use crate::tui::app::describe_reply_result;
+
+        let sent = ReplyResult::Sent {
+            agent_id: "cam-1".to_string(),
+            reply: "@approve (y → 等待 2s → run tests)".to_string(),
+            verification: ReplyVerification::Skipped,
+        };
+        assert_eq!(
+            describe_reply_result("cam-1", Ok(sent)),
+            "已发送 '@approve (y → 等待 2s → run tests)' 到 cam-1"
+        );
+        assert_eq!(
+            describe_reply_result("cam-1", Ok(ReplyResult::NoPending)),
+            "cam-1 没有待处理的确认"
+        );
+        assert_eq!(
+            describe_reply_result(
+                "cam-1",
+                Ok(ReplyResult::InvalidSelection("未知的回复宏 @x".to_string()))
+            ),
+            "未知的回复宏 @x"
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_search_agents_and_notifications() {
+        let mut app = App::new();
+        let agent = |id: &str, project: &str, state| AgentItem {
+            id: id.to_string(),
+            agent_type: "ClaudeCode".to_string(),
+            project: project.to_string(),
+            state,
+            started_at: chrono::Local::now(),
+            tmux_session: Some(id.to_string()),
+            context_usage: None,
+            ports: Vec::new(),
+            todos: Vec::new(),
+        };
+        app.agents = vec![
+            agent("cam-111", "web-frontend", AgentStatus::Processing),
+            agent("cam-222", "api-server", AgentStatus::WaitingForInput),
+            agent("cam-333", "code-agent-monitor", AgentStatus::DecisionRequired),
+        ];
+        let notification = |agent_id: &str, message: &str| crate::tui::NotificationItem {
+            timestamp: chrono::Local::now(),
+            agent_id: agent_id.to_string(),
+            message: message.to_string(),
+            urgency: crate::notification::Urgency::High,
+            event_type: "permission_request".to_string(),
+            project: None,
+            event_detail: None,
+            terminal_snapshot: None,
+            risk_level: None,
+        };
+        app.notifications = vec![
+            notification("cam-111", "Bash: npm run build"),
+            notification("cam-222", "Which database should I use?"),
+            notification("cam-111", "Tests passed"),
+        ];
+        app.rebuild_search_index();
+
+        // 子序列模糊匹配项目名
+        app.filter_input.set_text("agmon");
+        app.on_filter_change();
+        assert_eq!(app.filtered_agents().len(), 1);
+        assert_eq!(app.filtered_agents()[0].id, "cam-333");
+
+        // 按状态过滤
+        app.filter_input.set_text("waiting");
+        assert_eq!(app.filtered_agents().len(), 1);
+        assert_eq!(app.selected_agent().unwrap().id, "cam-222");
+
+        // 多个词都要匹配；通知按内容过滤并保持时间顺序
+        app.filter_input.set_text("cam-111 build");
+        app.on_filter_change();
+        assert!(app.filtered_agents().is_empty());
+        assert_eq!(app.filtered_notification_indices(), vec![0]);
+        assert_eq!(app.selected_notification().unwrap().message, "Bash: npm run build");
+
+        app.filter_input.set_text("cam-111");
+        app.on_filter_change();
+        assert_eq!(app.filtered_notification_indices(), vec![0, 2]);
+        assert_eq!(app.notification_selected, 2);
+        app.next_notification();
+        assert_eq!(app.notification_selected, 0);
+
+        app.clear_filter();
+        assert_eq!(app.filtered_agents().len(), 3);
+        assert_eq!(app.filtered_notification_indices().len(), 3);
+    }
+
+    #[test]
+    fn test_teams_view_navigation() {
+        use crate::team::{ProgressSample, TeamProgress};
+        use crate::tui::{handle_key, TeamItem};
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new();
+        app.view = View::Teams;
+        app.teams = ["web", "api"]
+            .iter()
+            .map(|name| TeamItem {
+                progress: TeamProgress {
+                    team_name: name.to_string(),
+                    total_members: 2,
+                    active_members: 2,
+                    pending_tasks: 3,
+                    completed_tasks: 1,
+                    waiting_for_input: Vec::new(),
+                },
+                history: vec![ProgressSample {
+                    timestamp: chrono::Utc::now(),
+                    pending_tasks: 3,
+                    completed_tasks: 1,
+                    active_members: 2,
+                    waiting: 0,
+                }],
+            })
+            .collect();
+
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        handle_key(&mut app, press(KeyCode::Char('j')));
+        assert_eq!(app.selected_team().unwrap().progress.team_name, "api");
+        handle_key(&mut app, press(KeyCode::Char('j')));
+        assert_eq!(app.team_selected, 0); // wrap around
+        handle_key(&mut app, press(KeyCode::Esc));
+        assert_eq!(app.view, View::Dashboard);
+    }
 }
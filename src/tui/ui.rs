@@ -1,9 +1,12 @@
 //! TUI 渲染模块
 
-use crate::tui::{App, View};
+use crate::agent::todos::{TodoItem, TodoProgress, TodoStatus};
+use crate::infra::ports::format_ports;
+use crate::infra::text::truncate_width;
+use crate::tui::{Action, App, View};
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Sparkline},
 };
 
 /// Notification panel height when unfocused
@@ -22,6 +25,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     match app.view {
         View::Dashboard => render_dashboard(app, frame),
         View::Logs => render_logs(app, frame),
+        View::Teams => render_teams(app, frame),
     }
 }
 
@@ -60,11 +64,16 @@ fn render_dashboard(app: &mut App, frame: &mut Frame) {
     } else {
         format!(" CAM TUI │ Agents: {}", app.agents.len())
     };
+    let status = match &app.reply_status {
+        Some(reply_status) => format!("{} │ {}", status, reply_status),
+        None => status,
+    };
     // 过滤模式时边框变色（类似 lazygit）
-    let status_style = if app.filter_mode {
-        Style::default().bg(Color::Cyan).fg(Color::Black)
+    let theme = &app.theme;
+    let status_style = if app.filter_mode || app.reply_mode {
+        Style::default().bg(theme.accent).fg(theme.input_fg)
     } else {
-        Style::default().bg(Color::Blue).fg(Color::White)
+        Style::default().bg(theme.title_bg).fg(theme.title_fg)
     };
     let status_bar = Paragraph::new(status).style(status_style);
     frame.render_widget(status_bar, vertical[0]);
@@ -84,7 +93,19 @@ fn render_dashboard(app: &mut App, frame: &mut Frame) {
     // 右侧区域：根据焦点动态切换
     match app.focus {
         crate::tui::Focus::AgentList | crate::tui::Focus::Preview => {
-            render_terminal_preview(app, frame, main_area[1])
+            // 选中的 agent 有 TODO 列表时在预览上方显示
+            let todos = app.selected_agent().map(|a| a.todos.clone()).unwrap_or_default();
+            if todos.is_empty() {
+                render_terminal_preview(app, frame, main_area[1])
+            } else {
+                let height = (todos.len() as u16 + 2).min(main_area[1].height / 3);
+                let right = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(height), Constraint::Min(0)])
+                    .split(main_area[1]);
+                render_todos(app, frame, right[0], &todos);
+                render_terminal_preview(app, frame, right[1])
+            }
         }
         crate::tui::Focus::Notifications | crate::tui::Focus::Detail => {
             render_notification_detail(app, frame, main_area[1])
@@ -98,32 +119,76 @@ fn render_dashboard(app: &mut App, frame: &mut Frame) {
     if app.filter_mode {
         let (before, after) = app.filter_input.split_at_cursor();
         let filter_bar = Paragraph::new(format!(" Filter: {}│{} ", before, after))
-            .style(Style::default().bg(Color::Yellow).fg(Color::Black));
+            .style(Style::default().bg(app.theme.input_bg).fg(app.theme.input_fg));
         frame.render_widget(filter_bar, vertical[3]);
+    } else if app.reply_mode {
+        let (before, after) = app.reply_input.split_at_cursor();
+        let target = app.selected_agent().map(|agent| agent.id.clone()).unwrap_or_default();
+        let hint = if app.reply_macros.is_empty() {
+            String::new()
+        } else {
+            format!("  (@{})", app.reply_macros.join(" @"))
+        };
+        let reply_bar = Paragraph::new(format!(" Reply {}: {}│{}{} ", target, before, after, hint))
+            .style(Style::default().bg(app.theme.input_bg).fg(app.theme.input_fg));
+        frame.render_widget(reply_bar, vertical[3]);
     } else if is_filtering {
         let filter_bar = Paragraph::new(format!(
-            " Filter: {} │ [Esc] clear │ [/] edit ",
-            filter_text
+            " Filter: {} │ [{}] clear │ [{}] edit ",
+            filter_text,
+            app.keymap.label(Action::Back),
+            app.keymap.label(Action::Filter)
         ))
-        .style(Style::default().bg(Color::DarkGray).fg(Color::Cyan));
+        .style(Style::default().bg(app.theme.bar_bg).fg(app.theme.accent));
         frame.render_widget(filter_bar, vertical[3]);
     } else {
+        let keys = &app.keymap;
         let help = match app.focus {
-            crate::tui::Focus::AgentList => {
-                " [Tab] 切换焦点  [j/k] 移动  [→/l] 预览  [Enter] tmux  [x] close  [/] filter  [q] quit "
-            }
-            crate::tui::Focus::Notifications => {
-                " [Tab] 切换焦点  [j/k] 移动  [→/l] 详情  [Esc] 返回  [q] quit "
-            }
-            crate::tui::Focus::Preview | crate::tui::Focus::Detail => {
-                " [j/k] 滚动  [Esc/←/h] 返回  [Tab] 切换焦点  [q] quit "
-            }
+            crate::tui::Focus::AgentList => format!(
+                " [{}] 切换焦点  [{}/{}] 移动  [{}] 预览  [{}] tmux  [{}] reply  [{}] close  [{}] filter  [{}] teams  [{}] quit ",
+                keys.label(Action::NextFocus),
+                keys.label(Action::Down),
+                keys.label(Action::Up),
+                keys.label(Action::Open),
+                keys.label(Action::Attach),
+                keys.label(Action::Reply),
+                keys.label(Action::Close),
+                keys.label(Action::Filter),
+                keys.label(Action::Teams),
+                keys.label(Action::Quit)
+            ),
+            crate::tui::Focus::Notifications => format!(
+                " [{}] 切换焦点  [{}/{}] 移动  [{}] 详情  [{}] 返回  [{}] quit ",
+                keys.label(Action::NextFocus),
+                keys.label(Action::Down),
+                keys.label(Action::Up),
+                keys.label(Action::Open),
+                keys.label(Action::Back),
+                keys.label(Action::Quit)
+            ),
+            crate::tui::Focus::Preview | crate::tui::Focus::Detail => format!(
+                " [{}/{}] 滚动  [{}] 返回  [{}] 切换焦点  [{}] quit ",
+                keys.label(Action::Down),
+                keys.label(Action::Up),
+                keys.label(Action::Back),
+                keys.label(Action::NextFocus),
+                keys.label(Action::Quit)
+            ),
         };
-        let help_bar = Paragraph::new(help).style(Style::default().bg(Color::DarkGray));
+        let help_bar = Paragraph::new(help)
+            .style(Style::default().bg(app.theme.bar_bg).fg(app.theme.bar_fg));
         frame.render_widget(help_bar, vertical[3]);
     }
 }
 
+/// 按显示宽度截断每一行（CJK/emoji 不会在字素簇中间截断）
+fn fit_lines(text: &str, width: usize) -> String {
+    text.lines()
+        .map(|line| truncate_width(line, width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// 渲染 Agent 列表（使用预先过滤的结果）
 fn render_agent_list_with_filtered(
     app: &App,
@@ -131,6 +196,7 @@ fn render_agent_list_with_filtered(
     area: Rect,
     filtered: &[&crate::tui::AgentItem],
 ) {
+    let inner_width = area.width.saturating_sub(2) as usize;
     let items: Vec<ListItem> = filtered
         .iter()
         .enumerate()
@@ -144,11 +210,33 @@ fn render_agent_list_with_filtered(
             let duration = chrono::Local::now()
                 .signed_duration_since(agent.started_at)
                 .num_minutes();
+            let context = agent
+                .context_usage
+                .map(|usage| format!(" | ctx {}%", usage))
+                .unwrap_or_default();
+            let todos = TodoProgress::of(&agent.todos)
+                .map(|p| format!(" | todo {}/{}", p.done, p.total))
+                .unwrap_or_default();
+            let ports = if agent.ports.is_empty() {
+                String::new()
+            } else {
+                format!(" | {}", format_ports(&agent.ports))
+            };
             let text = format!(
-                "{}{} {}\n   {} | {}\n   [{:?}] {}m",
-                selected, icon, agent.id, agent.agent_type, agent.project, agent.state, duration
+                "{}{} {}\n   {} | {}\n   [{:?}] {}m{}{}{}",
+                selected,
+                icon,
+                agent.id,
+                agent.agent_type,
+                agent.project,
+                agent.state,
+                duration,
+                context,
+                todos,
+                ports
             );
-            ListItem::new(text)
+            ListItem::new(fit_lines(&text, inner_width))
+                .style(Style::default().fg(app.theme.status(&agent.state)))
         })
         .collect();
 
@@ -160,12 +248,12 @@ fn render_agent_list_with_filtered(
     };
 
     // 焦点和过滤模式边框变色
-    let border_style = if app.filter_mode {
-        Style::default().fg(Color::Cyan)
-    } else if matches!(app.focus, crate::tui::Focus::AgentList | crate::tui::Focus::Preview) {
-        Style::default().fg(Color::Cyan)
+    let border_style = if app.filter_mode
+        || matches!(app.focus, crate::tui::Focus::AgentList | crate::tui::Focus::Preview)
+    {
+        Style::default().fg(app.theme.accent)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(app.theme.muted)
     };
 
     let list = List::new(items).block(
@@ -177,18 +265,48 @@ fn render_agent_list_with_filtered(
     frame.render_widget(list, area);
 }
 
+/// 渲染 TODO 列表（进行中的优先显示 activeForm）
+fn render_todos(app: &App, frame: &mut Frame, area: Rect, todos: &[TodoItem]) {
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = todos
+        .iter()
+        .map(|todo| {
+            let text = match (todo.status, &todo.active_form) {
+                (TodoStatus::InProgress, Some(active)) => active,
+                _ => &todo.content,
+            };
+            let style = match todo.status {
+                TodoStatus::Completed => Style::default().fg(app.theme.muted),
+                TodoStatus::InProgress => Style::default().fg(app.theme.accent),
+                TodoStatus::Pending => Style::default(),
+            };
+            ListItem::new(truncate_width(&format!("{} {}", todo.status.marker(), text), width)).style(style)
+        })
+        .collect();
+    let title = TodoProgress::of(todos)
+        .map(|p| format!(" TODO {}/{} ", p.done, p.total))
+        .unwrap_or_default();
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(list, area);
+}
+
 /// 渲染终端预览
 fn render_terminal_preview(app: &mut App, frame: &mut Frame, area: Rect) {
     let is_focused = app.focus == crate::tui::Focus::Preview;
     let border_style = if is_focused {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(app.theme.accent)
     } else {
         Style::default()
     };
     let title = if is_focused {
-        " Terminal Preview (j/k scroll, Esc back) "
+        format!(
+            " Terminal Preview ({}/{} scroll, {} back) ",
+            app.keymap.label(Action::Down),
+            app.keymap.label(Action::Up),
+            app.keymap.label(Action::Back)
+        )
     } else {
-        " Terminal Preview "
+        " Terminal Preview ".to_string()
     };
 
     let content = app.terminal_preview.as_str();
@@ -225,14 +343,19 @@ fn render_terminal_preview(app: &mut App, frame: &mut Frame, area: Rect) {
 
 /// 渲染通知区域
 fn render_notifications(app: &App, frame: &mut Frame, area: Rect) {
-    use crate::notification::Urgency;
-
     let is_focused = matches!(app.focus, crate::tui::Focus::Notifications | crate::tui::Focus::Detail);
     let visible_count: usize = if is_focused { NOTIF_VISIBLE_FOCUSED } else { NOTIF_VISIBLE_UNFOCUSED };
 
+    // 过滤模式下只显示匹配的通知（下标指向 app.notifications）
+    let visible = app.filtered_notification_indices();
+    let selected_pos = visible
+        .iter()
+        .position(|&i| i == app.notification_selected)
+        .unwrap_or(visible.len().saturating_sub(1));
+
     // 计算可见窗口：确保选中项始终在视窗内
-    let len = app.notifications.len();
-    let rev_selected = len.saturating_sub(1).saturating_sub(app.notification_selected);
+    let len = visible.len();
+    let rev_selected = len.saturating_sub(1).saturating_sub(selected_pos);
     let skip = if rev_selected < visible_count {
         0
     } else {
@@ -241,22 +364,15 @@ fn render_notifications(app: &App, frame: &mut Frame, area: Rect) {
 
     let today = chrono::Local::now().date_naive();
 
-    let items: Vec<ListItem> = app
-        .notifications
+    let items: Vec<ListItem> = visible
         .iter()
         .rev()
         .skip(skip)
         .take(visible_count)
-        .enumerate()
-        .map(|(i, n)| {
-            let color = match n.urgency {
-                Urgency::High => Color::Red,
-                Urgency::Medium => Color::Yellow,
-                Urgency::Low => Color::DarkGray,
-            };
+        .map(|&original_idx| {
+            let n = &app.notifications[original_idx];
+            let color = app.theme.urgency(n.urgency);
 
-            // skip + enumerate index 映射回原始索引
-            let original_idx = len.saturating_sub(1).saturating_sub(skip + i);
             let is_selected = is_focused && original_idx == app.notification_selected;
             let selected_marker = if is_selected { "→ " } else { "  " };
 
@@ -268,16 +384,19 @@ fn render_notifications(app: &App, frame: &mut Frame, area: Rect) {
                 }
             };
 
-            let text = format!(
-                "{}[{}] {}: {}",
-                selected_marker,
-                time_str,
-                n.agent_id,
-                n.message
+            let text = truncate_width(
+                &format!(
+                    "{}[{}] {}: {}",
+                    selected_marker,
+                    time_str,
+                    n.agent_id,
+                    n.message.replace('\n', " ")
+                ),
+                area.width.saturating_sub(2) as usize,
             );
             let style = if is_selected {
-                let fg = if color == Color::DarkGray { Color::White } else { color };
-                Style::default().fg(fg).bg(Color::DarkGray)
+                let fg = if color == app.theme.low { app.theme.selection_fg } else { color };
+                Style::default().fg(fg).bg(app.theme.selection_bg)
             } else {
                 Style::default().fg(color)
             };
@@ -286,15 +405,19 @@ fn render_notifications(app: &App, frame: &mut Frame, area: Rect) {
         .collect();
 
     let border_style = if is_focused {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(app.theme.accent)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(app.theme.muted)
     };
 
     let list = List::new(items).block(
         Block::default()
             .borders(Borders::ALL)
-            .title(" Notifications ")
+            .title(if app.filter_input.is_empty() {
+                " Notifications ".to_string()
+            } else {
+                format!(" Notifications ({}) ", len)
+            })
             .border_style(border_style),
     );
     frame.render_widget(list, area);
@@ -316,17 +439,25 @@ fn render_notifications(app: &App, frame: &mut Frame, area: Rect) {
 fn render_notification_detail(app: &mut App, frame: &mut Frame, area: Rect) {
     let is_focused = app.focus == crate::tui::Focus::Detail;
     let border_style = if is_focused {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(app.theme.accent)
     } else {
         Style::default()
     };
     let title = if is_focused {
-        " Notification Detail (j/k scroll, Esc back) "
+        format!(
+            " Notification Detail ({}/{} scroll, {} back) ",
+            app.keymap.label(Action::Down),
+            app.keymap.label(Action::Up),
+            app.keymap.label(Action::Back)
+        )
     } else {
-        " Notification Detail "
+        " Notification Detail ".to_string()
     };
 
+    // 需要着色的行（urgency / risk）
+    let mut highlights: Vec<(usize, Color)> = Vec::new();
     let lines: Vec<String> = if let Some(n) = app.selected_notification() {
+        highlights.push((3, app.theme.urgency(n.urgency)));
         let mut l = vec![
             format!("Time:     {}", n.timestamp.format("%Y-%m-%d %H:%M:%S")),
             format!("Agent:    {}", n.agent_id),
@@ -338,6 +469,7 @@ fn render_notification_detail(app: &mut App, frame: &mut Frame, area: Rect) {
             l.push(format!("Project:  {}", project));
         }
         if let Some(ref risk) = n.risk_level {
+            highlights.push((l.len(), app.theme.risk(risk)));
             l.push(format!("Risk:     {}", risk));
         }
 
@@ -387,7 +519,14 @@ fn render_notification_detail(app: &mut App, frame: &mut Frame, area: Rect) {
     if app.detail_scroll_offset > max_scroll {
         app.detail_scroll_offset = max_scroll;
     }
-    let content = lines.join("\n");
+    let content: Vec<Line> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| match highlights.iter().find(|(row, _)| *row == i) {
+            Some((_, color)) => Line::styled(line.as_str(), Style::default().fg(*color)),
+            None => Line::raw(line.as_str()),
+        })
+        .collect();
 
     let detail = Paragraph::new(content)
         .block(
@@ -431,7 +570,7 @@ fn render_logs(app: &App, frame: &mut Frame) {
         app.logs_state.lines.len()
     );
     let status_bar =
-        Paragraph::new(status).style(Style::default().bg(Color::Magenta).fg(Color::White));
+        Paragraph::new(status).style(Style::default().bg(app.theme.logs_title_bg).fg(app.theme.title_fg));
     frame.render_widget(status_bar, vertical[0]);
 
     // 日志内容
@@ -444,11 +583,11 @@ fn render_logs(app: &App, frame: &mut Frame) {
         .take(vertical[1].height as usize)
         .map(|line| {
             let style = if line.contains("ERROR") || line.contains("❌") {
-                Style::default().fg(Color::Red)
+                Style::default().fg(app.theme.high)
             } else if line.contains("WARN") || line.contains("⚠") {
-                Style::default().fg(Color::Yellow)
+                Style::default().fg(app.theme.medium)
             } else if line.contains("INFO") || line.contains("✅") {
-                Style::default().fg(Color::Green)
+                Style::default().fg(app.theme.processing)
             } else {
                 Style::default()
             };
@@ -471,7 +610,151 @@ fn render_logs(app: &App, frame: &mut Frame) {
     }
 
     // 快捷键
-    let help = " [j/k] 滚动  [f] 过滤级别  [G] 跳到最新  [Esc] 返回  [q] 退出 ";
-    let help_bar = Paragraph::new(help).style(Style::default().bg(Color::DarkGray));
+    let keys = &app.keymap;
+    let help = format!(
+        " [{}/{}] 滚动  [{}] 过滤级别  [{}] 跳到最新  [{}] 返回  [{}] 退出 ",
+        keys.label(Action::Down),
+        keys.label(Action::Up),
+        keys.label(Action::LogLevel),
+        keys.label(Action::LogBottom),
+        keys.label(Action::Back),
+        keys.label(Action::Quit)
+    );
+    let help_bar =
+        Paragraph::new(help).style(Style::default().bg(app.theme.bar_bg).fg(app.theme.bar_fg));
     frame.render_widget(help_bar, vertical[2]);
 }
+
+/// 渲染 Team 进度视图：左侧 team 列表，右侧当前进度和待处理任务趋势
+fn render_teams(app: &App, frame: &mut Frame) {
+    let area = frame.area();
+
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // 状态栏
+            Constraint::Min(8),    // 主区域
+            Constraint::Length(1), // 快捷键
+        ])
+        .split(area);
+
+    let status = format!(" CAM Teams │ Teams: {}", app.teams.len());
+    let status_bar =
+        Paragraph::new(status).style(Style::default().bg(app.theme.title_bg).fg(app.theme.title_fg));
+    frame.render_widget(status_bar, vertical[0]);
+
+    let main_area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(vertical[1]);
+
+    // Team 列表
+    let inner_width = main_area[0].width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = app
+        .teams
+        .iter()
+        .enumerate()
+        .map(|(i, team)| {
+            let selected = if i == app.team_selected { "→ " } else { "  " };
+            let progress = &team.progress;
+            let text = format!(
+                "{}{}\n   待处理 {} | 已完成 {}",
+                selected, progress.team_name, progress.pending_tasks, progress.completed_tasks
+            );
+            let color = if progress.waiting_for_input.is_empty() {
+                app.theme.processing
+            } else {
+                app.theme.waiting
+            };
+            ListItem::new(fit_lines(&text, inner_width)).style(Style::default().fg(color))
+        })
+        .collect();
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Teams ")
+            .border_style(Style::default().fg(app.theme.accent)),
+    );
+    frame.render_widget(list, main_area[0]);
+
+    // 右侧：进度详情 + 趋势
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(7), Constraint::Min(4)])
+        .split(main_area[1]);
+
+    let Some(team) = app.selected_team() else {
+        let empty = Paragraph::new(" 没有发现 Team（~/.claude/teams/）")
+            .style(Style::default().fg(app.theme.muted))
+            .block(Block::default().borders(Borders::ALL).title(" Progress "));
+        frame.render_widget(empty, main_area[1]);
+        render_teams_help(app, frame, vertical[2]);
+        return;
+    };
+
+    let progress = &team.progress;
+    let mut lines = vec![
+        format!("成员: {} 总计, {} 活跃", progress.total_members, progress.active_members),
+        format!("任务: {} 待处理, {} 已完成", progress.pending_tasks, progress.completed_tasks),
+    ];
+    if !progress.waiting_for_input.is_empty() {
+        lines.push(format!("等待输入: {}", progress.waiting_for_input.join(", ")));
+    }
+    match crate::team::Burndown::from_samples(&team.history) {
+        Some(burndown) => {
+            let rate = burndown
+                .per_hour()
+                .map(|rate| format!("，{:+.1}/小时", rate))
+                .unwrap_or_default();
+            lines.push(format!(
+                "燃尽: {} → {}（{:+}{}）",
+                burndown.first_pending,
+                burndown.last_pending,
+                burndown.delta(),
+                rate
+            ));
+        }
+        None => lines.push("燃尽: 快照不足（watcher 每 5 分钟记录一次）".to_string()),
+    }
+    let detail_width = right[0].width.saturating_sub(2) as usize;
+    let detail = Paragraph::new(fit_lines(&lines.join("\n"), detail_width)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", progress.team_name)),
+    );
+    frame.render_widget(detail, right[0]);
+
+    // 待处理任务趋势（只显示能放下的最近快照）
+    let chart_width = right[1].width.saturating_sub(2) as usize;
+    let pending: Vec<u64> = team
+        .history
+        .iter()
+        .map(|sample| sample.pending_tasks as u64)
+        .collect();
+    let visible = &pending[pending.len().saturating_sub(chart_width)..];
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" 待处理趋势 ({} 条快照) ", team.history.len())),
+        )
+        .data(visible)
+        .style(Style::default().fg(app.theme.accent));
+    frame.render_widget(sparkline, right[1]);
+
+    render_teams_help(app, frame, vertical[2]);
+}
+
+fn render_teams_help(app: &App, frame: &mut Frame, area: Rect) {
+    let keys = &app.keymap;
+    let help = format!(
+        " [{}/{}] 移动  [{}] 返回  [{}] 退出 ",
+        keys.label(Action::Down),
+        keys.label(Action::Up),
+        keys.label(Action::Back),
+        keys.label(Action::Quit)
+    );
+    let help_bar =
+        Paragraph::new(help).style(Style::default().bg(app.theme.bar_bg).fg(app.theme.bar_fg));
+    frame.render_widget(help_bar, area);
+}
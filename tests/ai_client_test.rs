@@ -39,6 +39,7 @@ mod config_tests {
             model: "claude-3-opus".to_string(),
             timeout_ms: 10000,
             max_tokens: 2000,
+            temperature: None,
             webhook: None,
             providers: Vec::new(),
         };
@@ -66,6 +67,7 @@ mod client_creation_tests {
             model: DEFAULT_MODEL.to_string(),
             timeout_ms: DEFAULT_TIMEOUT_MS,
             max_tokens: DEFAULT_MAX_TOKENS,
+            temperature: None,
             webhook: None,
             providers: Vec::new(),
         };
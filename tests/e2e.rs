@@ -1,13 +1,10 @@
 //! End-to-end integration tests for CAM MCP Server
 
-use code_agent_monitor::agent::AgentManager;
 use code_agent_monitor::mcp::{McpRequest, McpServer};
 
 /// Helper to create a test MCP server
 fn create_test_server() -> McpServer {
-    McpServer {
-        agent_manager: AgentManager::new_for_test(),
-    }
+    McpServer::new_for_test()
 }
 
 /// Helper to call MCP method
@@ -29,6 +26,13 @@ async fn call_method(
     response.result.unwrap()
 }
 
+/// Helper to create a project directory (agent/start preflight requires it to exist)
+fn project_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.to_string_lossy().into_owned()
+}
+
 fn cleanup_test_agents(server: &McpServer) {
     if let Ok(agents) = server.agent_manager.list_agents() {
         for agent in agents {
@@ -49,7 +53,7 @@ async fn test_e2e_start_send_stop_flow() {
         &server,
         "agent/start",
         serde_json::json!({
-            "project_path": "/tmp/e2e-test",
+            "project_path": project_dir("e2e-test"),
             "agent_type": "mock"
         }),
     )
@@ -115,7 +119,7 @@ async fn test_e2e_multiple_agents() {
         &server,
         "agent/start",
         serde_json::json!({
-            "project_path": "/tmp/e2e-test-1",
+            "project_path": project_dir("e2e-test-1"),
             "agent_type": "mock"
         }),
     )
@@ -129,7 +133,7 @@ async fn test_e2e_multiple_agents() {
         &server,
         "agent/start",
         serde_json::json!({
-            "project_path": "/tmp/e2e-test-2",
+            "project_path": project_dir("e2e-test-2"),
             "agent_type": "mock"
         }),
     )
@@ -190,7 +194,7 @@ async fn test_e2e_agent_with_initial_prompt() {
         &server,
         "agent/start",
         serde_json::json!({
-            "project_path": "/tmp/e2e-test-prompt",
+            "project_path": project_dir("e2e-test-prompt"),
             "agent_type": "mock",
             "initial_prompt": "initial message"
         }),
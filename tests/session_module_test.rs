@@ -11,3 +11,88 @@ fn test_session_module_exports_manager() {
 fn test_session_module_exports_state_manager() {
     fn _check_type(_manager: ConversationStateManager) {}
 }
+
+#[test]
+fn test_list_sessions_filtered_by_project_status_and_sort() {
+    use code_agent_monitor::session::{SessionFilter, SessionSort, SessionStatus};
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let now = chrono::Utc::now();
+    let write_project = |name: &str, project: &str, entries: &[(&str, i64, u32)]| {
+        let project_dir = dir.path().join(name);
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|(id, age_days, count)| {
+                serde_json::json!({
+                    "sessionId": id,
+                    "projectPath": project,
+                    "messageCount": count,
+                    "modified": (now - chrono::Duration::days(*age_days)).to_rfc3339(),
+                })
+            })
+            .collect();
+        std::fs::write(
+            project_dir.join("sessions-index.json"),
+            serde_json::json!({"version": 1, "entries": entries}).to_string(),
+        )
+        .unwrap();
+        project_dir
+    };
+    let app = write_project(
+        "-work-my-app",
+        "/work/my_app",
+        &[("s-old", 10, 50), ("s-new", 1, 5)],
+    );
+    write_project("-work-other", "/work/other", &[("s-other", 0, 1)]);
+    // 最近写入的 JSONL 视为活跃
+    std::fs::write(app.join("s-new.jsonl"), "{}\n").unwrap();
+
+    let manager = SessionManager::with_projects_dir(dir.path());
+    let ids = |filter: SessionFilter| -> Vec<String> {
+        manager
+            .list_sessions_filtered(Some(filter))
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect()
+    };
+
+    assert_eq!(
+        ids(SessionFilter {
+            project_path: Some("my_app".to_string()),
+            ..Default::default()
+        }),
+        ["s-new", "s-old"]
+    );
+    assert_eq!(
+        ids(SessionFilter {
+            project_path: Some("my_app".to_string()),
+            sort: SessionSort::Messages,
+            ..Default::default()
+        }),
+        ["s-old", "s-new"]
+    );
+    assert_eq!(
+        ids(SessionFilter {
+            since_secs: Some(2 * 86400),
+            ..Default::default()
+        }),
+        ["s-other", "s-new"]
+    );
+    assert_eq!(
+        ids(SessionFilter {
+            status: Some(SessionStatus::Active),
+            ..Default::default()
+        }),
+        ["s-new"]
+    );
+    assert_eq!(
+        ids(SessionFilter {
+            status: Some(SessionStatus::Completed),
+            limit: Some(1),
+            ..Default::default()
+        }),
+        ["s-other"]
+    );
+}
@@ -0,0 +1,209 @@
+//! 模拟 agent 的端到端测试（需要 tmux）
+
+use std::path::Path;
+use std::time::Duration;
+
+use code_agent_monitor::agent::simulator::SimulatedAgent;
+use code_agent_monitor::agent::{AgentManager, AgentWatcher, WatchEvent};
+use code_agent_monitor::session::{
+    ConfirmationType, ConversationStateManager, PendingResolution, ReplyResult, ReplyVerification,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+fn cam_exe() -> &'static Path {
+    Path::new(env!("CARGO_BIN_EXE_cam"))
+}
+
+#[test]
+fn test_simulated_agent_prompts_and_reads_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let agent = SimulatedAgent::spawn(
+        AgentManager::with_data_dir(dir.path().to_path_buf()),
+        cam_exe(),
+        "confirm",
+        "/tmp",
+    )
+    .unwrap();
+
+    // Given: 模拟 agent 已注册为 mock 类型
+    let agents = agent.manager().list_agents().unwrap();
+    assert_eq!(agents.len(), 1);
+    assert_eq!(agents[0].agent_id, agent.agent_id);
+
+    // When: 出现确认提示后输入回复
+    agent.wait_for_output("[Y/n]", TIMEOUT).unwrap();
+    agent.send("y").unwrap();
+
+    // Then: 模拟 agent 读取回复并继续剧本
+    agent.wait_for_output("> y", TIMEOUT).unwrap();
+    agent.wait_for_output("已清理", TIMEOUT).unwrap();
+}
+
+#[test]
+fn test_reply_path_reaches_simulated_agent() {
+    let dir = tempfile::tempdir().unwrap();
+    let agent = SimulatedAgent::spawn(
+        AgentManager::with_data_dir(dir.path().to_path_buf()),
+        cam_exe(),
+        "choices",
+        "/tmp",
+    )
+    .unwrap();
+    agent.wait_for_output("1. Yes", TIMEOUT).unwrap();
+
+    // Given: 模拟 agent 有一个待处理确认
+    let state = ConversationStateManager::with_agent_manager(
+        dir.path().join("conversation_state.json"),
+        AgentManager::with_data_dir(dir.path().to_path_buf()),
+    );
+    state
+        .register_pending(
+            &agent.agent_id,
+            None,
+            ConfirmationType::PermissionRequest {
+                tool: "Write".to_string(),
+                input: serde_json::json!({"file_path": "hello.txt"}),
+            },
+            "Do you want to create hello.txt?",
+            None,
+        )
+        .unwrap();
+
+    // When: 通过回复链路选择选项 1
+    let result = state.handle_reply("1", Some(&agent.agent_id)).unwrap();
+    match result {
+        ReplyResult::Sent {
+            agent_id,
+            verification,
+            ..
+        } => {
+            assert_eq!(agent_id, agent.agent_id);
+            assert!(verification.is_verified(), "{:?}", verification);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // Then: 回复经 tmux 送达模拟 agent
+    agent.wait_for_output("> 1", TIMEOUT).unwrap();
+    assert!(state.get_pending_confirmations().unwrap().is_empty());
+}
+
+#[test]
+fn test_unverified_reply_keeps_pending() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = AgentManager::with_data_dir(dir.path().to_path_buf());
+    // 不回显输入、也不读取输入的进程：任何按键都不会改变屏幕
+    let response = manager
+        .start_agent_with_command(
+            "/tmp".to_string(),
+            "sh -c 'stty -echo; echo \"Allow? [Y/n]\"; sleep 60'",
+        )
+        .unwrap();
+
+    let state = ConversationStateManager::with_agent_manager(
+        dir.path().join("conversation_state.json"),
+        AgentManager::with_data_dir(dir.path().to_path_buf()),
+    )
+    .with_reply_verification(Some(Duration::from_millis(300)));
+    state
+        .register_pending(
+            &response.agent_id,
+            None,
+            ConfirmationType::TaskApproval {
+                task_id: "1".to_string(),
+            },
+            "Allow? [Y/n]",
+            None,
+        )
+        .unwrap();
+
+    std::thread::sleep(Duration::from_millis(500));
+    let result = state.handle_reply("y", Some(&response.agent_id)).unwrap();
+    let _ = manager.stop_agent(&response.agent_id);
+
+    // 默认方式和备用方式（不带回车）都尝试过，确认保留
+    match result {
+        ReplyResult::Sent { verification, .. } => {
+            assert_eq!(verification, ReplyVerification::Unverified { attempts: 2 })
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(state.get_pending_confirmations().unwrap().len(), 1);
+}
+
+#[test]
+fn test_watcher_resolves_confirmation_answered_in_terminal() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.json");
+    let log: Vec<String> = (1..=20).map(|i| format!("⏺ step {}", i)).collect();
+    std::fs::write(
+        &script,
+        serde_json::json!({
+            "name": "answered",
+            "steps": [
+                {"kind": "prompt", "text": "Allow this command to run? [Y/n]"},
+                {"kind": "output", "text": log.join("\n")},
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let agent = SimulatedAgent::spawn(
+        AgentManager::with_data_dir(dir.path().to_path_buf()),
+        cam_exe(),
+        script.to_str().unwrap(),
+        "/tmp",
+    )
+    .unwrap();
+    agent.wait_for_output("[Y/n]", TIMEOUT).unwrap();
+
+    // Given: 一个已超过对账宽限期的待处理确认
+    let state = ConversationStateManager::with_agent_manager(
+        dir.path().join("conversation_state.json"),
+        AgentManager::with_data_dir(dir.path().to_path_buf()),
+    );
+    let id = state
+        .register_pending(
+            &agent.agent_id,
+            None,
+            ConfirmationType::TaskApproval {
+                task_id: "1".to_string(),
+            },
+            "Allow this command to run? [Y/n]",
+            None,
+        )
+        .unwrap();
+    let mut saved = state.load_state().unwrap();
+    saved.pending_confirmations[0].created_at = chrono::Utc::now() - chrono::Duration::minutes(1);
+    state.save_state(&saved).unwrap();
+
+    let mut watcher =
+        AgentWatcher::with_agent_manager(AgentManager::with_data_dir(dir.path().to_path_buf()));
+    let resolved = |events: Vec<WatchEvent>| {
+        events
+            .into_iter()
+            .any(|e| matches!(e, WatchEvent::ConfirmationResolved { .. }))
+    };
+
+    // 问题仍在屏幕上时保留
+    assert!(!resolved(watcher.poll_once().unwrap()));
+    assert_eq!(state.get_pending_confirmations().unwrap().len(), 1);
+
+    // When: 用户直接在终端中作答，agent 继续输出
+    agent.send("y").unwrap();
+    agent.wait_for_output("step 20", TIMEOUT).unwrap();
+
+    // Then: watcher 对账后移除确认
+    let events = watcher.poll_once().unwrap();
+    assert!(events.iter().any(|e| matches!(
+        e,
+        WatchEvent::ConfirmationResolved {
+            confirmation_id,
+            resolution: PendingResolution::ResolvedInTerminal,
+            ..
+        } if confirmation_id == &id
+    )));
+    assert!(state.get_pending_confirmations().unwrap().is_empty());
+}
@@ -0,0 +1,73 @@
+//! 终端快照语料的 golden 测试
+//!
+//! 语料在 `examples/snapshots/`，格式见 `code_agent_monitor::agent::extractor::corpus`。
+//! 修改识别规则后确认结果变化符合预期，用 `CAM_UPDATE_GOLDEN=1 cargo test --test
+//! snapshot_golden_test` 重写期望结果；手写的新快照（只有 `.txt`）也会在此时生成期望结果，
+//! agent 类型取文件名的第一段。
+
+use std::path::Path;
+
+use code_agent_monitor::agent::extractor::corpus::{
+    CorpusEntry, LocalAnalyzer, SnapshotCorpus, UPDATE_GOLDEN_ENV,
+};
+use tempfile::TempDir;
+
+#[test]
+fn test_snapshot_corpus_matches_golden() {
+    let corpus =
+        SnapshotCorpus::new(&Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/snapshots"));
+    let names = corpus.names().unwrap();
+    assert!(!names.is_empty(), "语料为空: {}", corpus.dir().display());
+
+    let tmp = TempDir::new().unwrap();
+    let analyzer = LocalAnalyzer::new(tmp.path()).unwrap();
+    let update = std::env::var_os(UPDATE_GOLDEN_ENV).is_some();
+
+    let mut failures = Vec::new();
+    for name in names {
+        let loaded = corpus.load(&name);
+        if let Err(e) = &loaded {
+            if !update {
+                failures.push(format!("{}: {:#}", name, e));
+                continue;
+            }
+        }
+        let snapshot = std::fs::read_to_string(corpus.snapshot_path(&name)).unwrap();
+        let actual = analyzer.analyze(&snapshot);
+        if loaded.as_ref().is_ok_and(|entry| entry.expected == actual) {
+            continue;
+        }
+
+        if update {
+            let entry = match loaded {
+                Ok(entry) => CorpusEntry {
+                    expected: actual,
+                    ..entry
+                },
+                Err(_) => CorpusEntry {
+                    agent_type: name.split('-').next().unwrap_or_default().to_string(),
+                    description: String::new(),
+                    snapshot,
+                    expected: actual,
+                    name,
+                },
+            };
+            corpus.save(&entry).unwrap();
+        } else if let Ok(entry) = loaded {
+            failures.push(format!(
+                "{}:\n  期望: {}\n  实际: {}",
+                name,
+                serde_json::to_string(&entry.expected).unwrap(),
+                serde_json::to_string(&actual).unwrap()
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} 个快照与期望结果不一致（确认变化符合预期后设置 {}=1 重写）:\n{}",
+        failures.len(),
+        UPDATE_GOLDEN_ENV,
+        failures.join("\n")
+    );
+}
@@ -22,6 +22,7 @@ mod args_parsing {
             initial_prompt: None,
             agent_id: None,
             tmux_session: None,
+            container: None,
         };
 
         // Then: agent_type 应该为 None（由 AgentManager 默认为 claude）
@@ -38,6 +39,7 @@ mod args_parsing {
             initial_prompt: None,
             agent_id: None,
             tmux_session: None,
+            container: None,
         };
 
         // Then: agent_type 应该正确设置
@@ -54,6 +56,7 @@ mod args_parsing {
             initial_prompt: Some("Hello, Claude!".to_string()),
             agent_id: None,
             tmux_session: None,
+            container: None,
         };
 
         // Then: initial_prompt 应该正确设置
@@ -70,6 +73,7 @@ mod args_parsing {
             initial_prompt: None,
             agent_id: Some("custom-agent-123".to_string()),
             tmux_session: None,
+            container: None,
         };
 
         // Then: agent_id 应该正确设置
@@ -86,6 +90,7 @@ mod args_parsing {
             initial_prompt: None,
             agent_id: None,
             tmux_session: Some("my-session".to_string()),
+            container: None,
         };
 
         // Then: tmux_session 应该正确设置
@@ -239,6 +244,7 @@ mod serialization {
             initial_prompt: None,
             agent_id: None,
             tmux_session: None,
+            container: None,
         };
 
         // When: 序列化
@@ -260,6 +266,7 @@ mod serialization {
             initial_prompt: Some("Hello".to_string()),
             agent_id: Some("agent-456".to_string()),
             tmux_session: Some("tmux-789".to_string()),
+            container: None,
         };
 
         // When: 序列化
@@ -299,6 +306,7 @@ mod serialization {
         let response = StartAgentResponse {
             agent_id: "cam-12345678".to_string(),
             tmux_session: "cam-12345678".to_string(),
+            prompt_delivered: None,
         };
 
         // When: 序列化
@@ -424,7 +432,13 @@ mod cli_start_args {
             cwd: None,
             name: None,
             resume: None,
+            container: None,
             json: false,
+            skip_preflight: false,
+            force: false,
+            wait_ready: false,
+            timeout: 60,
+            prompt_file: None,
             prompt: None,
         };
 
@@ -445,7 +459,13 @@ mod cli_start_args {
             cwd: Some("/tmp/project".to_string()),
             name: Some("my-session".to_string()),
             resume: None,
+            container: None,
             json: true,
+            skip_preflight: false,
+            force: false,
+            wait_ready: false,
+            timeout: 60,
+            prompt_file: None,
             prompt: Some("Hello".to_string()),
         };
 
@@ -465,7 +485,13 @@ mod cli_start_args {
             cwd: None,
             name: None,
             resume: Some("session-abc123".to_string()),
+            container: None,
             json: false,
+            skip_preflight: false,
+            force: false,
+            wait_ready: false,
+            timeout: 60,
+            prompt_file: None,
             prompt: None, // resume 和 prompt 互斥
         };
 
@@ -490,6 +516,10 @@ mod cli_start_output {
             tmux_session: "cam-12345678".to_string(),
             agent_type: "claude".to_string(),
             project_path: "/tmp/project".to_string(),
+            container: None,
+            warnings: Vec::new(),
+            ready: None,
+            prompt_delivered: None,
         };
 
         // When: 序列化为 JSON
@@ -512,6 +542,10 @@ mod cli_start_output {
             tmux_session: "cam-abc".to_string(),
             agent_type: "codex".to_string(),
             project_path: "/home/user/project".to_string(),
+            container: None,
+            warnings: Vec::new(),
+            ready: None,
+            prompt_delivered: None,
         };
 
         // When: 序列化为 pretty JSON
@@ -538,7 +572,13 @@ mod handle_start_errors {
             cwd: Some("/tmp".to_string()),
             name: None,
             resume: None,
+            container: None,
             json: false,
+            skip_preflight: false,
+            force: false,
+            wait_ready: false,
+            timeout: 60,
+            prompt_file: None,
             prompt: None,
         };
 
@@ -559,7 +599,13 @@ mod handle_start_errors {
             cwd: Some("/nonexistent/path/that/does/not/exist".to_string()),
             name: None,
             resume: None,
+            container: None,
             json: false,
+            skip_preflight: false,
+            force: false,
+            wait_ready: false,
+            timeout: 60,
+            prompt_file: None,
             prompt: None,
         };
 